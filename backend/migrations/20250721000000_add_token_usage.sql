@@ -0,0 +1,17 @@
+PRAGMA foreign_keys = ON;
+
+-- Token counts and reported cost for a coding agent execution process
+CREATE TABLE token_usage (
+    id                     BLOB PRIMARY KEY,
+    execution_process_id   BLOB NOT NULL UNIQUE,
+    input_tokens           INTEGER NOT NULL DEFAULT 0,
+    output_tokens          INTEGER NOT NULL DEFAULT 0,
+    cache_read_tokens      INTEGER NOT NULL DEFAULT 0,
+    cache_creation_tokens  INTEGER NOT NULL DEFAULT 0,
+    cost_usd               REAL,  -- Only set when the executor reports a cost
+    created_at             TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at             TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (execution_process_id) REFERENCES execution_processes(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_token_usage_created_at ON token_usage(created_at);
@@ -0,0 +1,22 @@
+PRAGMA foreign_keys = ON;
+
+-- Attempts racing on the same task with different executors
+CREATE TABLE task_attempt_groups (
+    id                 BLOB PRIMARY KEY,
+    task_id            BLOB NOT NULL,
+    winner_attempt_id  BLOB,  -- Set once a winner has been picked
+    created_at         TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at         TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+    FOREIGN KEY (winner_attempt_id) REFERENCES task_attempts(id) ON DELETE SET NULL
+);
+
+CREATE TABLE task_attempt_group_members (
+    group_id         BLOB NOT NULL,
+    task_attempt_id  BLOB NOT NULL UNIQUE,
+    PRIMARY KEY (group_id, task_attempt_id),
+    FOREIGN KEY (group_id) REFERENCES task_attempt_groups(id) ON DELETE CASCADE,
+    FOREIGN KEY (task_attempt_id) REFERENCES task_attempts(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_task_attempt_groups_task_id ON task_attempt_groups(task_id);
@@ -0,0 +1,49 @@
+PRAGMA foreign_keys = ON;
+
+-- Ordered agent stages a project can run on its task attempts
+CREATE TABLE pipelines (
+    id          BLOB PRIMARY KEY,
+    project_id  BLOB NOT NULL,
+    name        TEXT NOT NULL,
+    created_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+CREATE TABLE pipeline_stages (
+    id               BLOB PRIMARY KEY,
+    pipeline_id      BLOB NOT NULL,
+    position         INTEGER NOT NULL,
+    name             TEXT NOT NULL,
+    executor         TEXT NOT NULL,
+    prompt_template  TEXT NOT NULL,
+    condition        TEXT NOT NULL DEFAULT 'always'
+                        CHECK (condition IN ('always', 'previous_succeeded', 'check_fails')),
+    check_command    TEXT,  -- Shell command evaluated for 'check_fails'
+    UNIQUE (pipeline_id, position),
+    FOREIGN KEY (pipeline_id) REFERENCES pipelines(id) ON DELETE CASCADE
+);
+
+-- Stages copied onto an attempt when it starts, so later pipeline edits do not affect it
+CREATE TABLE task_attempt_stages (
+    id                    BLOB PRIMARY KEY,
+    task_attempt_id       BLOB NOT NULL,
+    position              INTEGER NOT NULL,
+    name                  TEXT NOT NULL,
+    executor              TEXT NOT NULL,
+    prompt_template       TEXT NOT NULL,
+    condition             TEXT NOT NULL DEFAULT 'always'
+                             CHECK (condition IN ('always', 'previous_succeeded', 'check_fails')),
+    check_command         TEXT,
+    status                TEXT NOT NULL DEFAULT 'pending'
+                             CHECK (status IN ('pending', 'running', 'completed', 'failed', 'skipped')),
+    execution_process_id  BLOB,
+    created_at            TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at            TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    UNIQUE (task_attempt_id, position),
+    FOREIGN KEY (task_attempt_id) REFERENCES task_attempts(id) ON DELETE CASCADE,
+    FOREIGN KEY (execution_process_id) REFERENCES execution_processes(id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_pipelines_project_id ON pipelines(project_id);
+CREATE INDEX idx_task_attempt_stages_execution_process_id ON task_attempt_stages(execution_process_id);
@@ -0,0 +1,45 @@
+-- 1. Add the replacement column with the wider CHECK
+ALTER TABLE execution_processes
+  ADD COLUMN process_type_new TEXT NOT NULL DEFAULT 'setupscript'
+    CHECK (process_type_new IN ('setupscript',
+                                'cleanupscript',
+                                'codingagent',
+                                'planningagent',
+                                'reviewagent',
+                                'devserver'));
+
+-- 2. Copy existing values across
+UPDATE execution_processes
+  SET process_type_new = process_type;
+
+-- 3. Drop any indexes that mention the old column
+DROP INDEX IF EXISTS idx_execution_processes_type;
+
+-- 4. Remove the old column (requires 3.35+)
+ALTER TABLE execution_processes DROP COLUMN process_type;
+
+-- 5. Rename the new column back to the canonical name
+ALTER TABLE execution_processes
+  RENAME COLUMN process_type_new TO process_type;
+
+-- 6. Re-create the index
+CREATE INDEX idx_execution_processes_type
+        ON execution_processes(process_type);
+
+-- 7. Findings reported by review runs
+CREATE TABLE review_findings (
+    id                    BLOB PRIMARY KEY,
+    task_attempt_id       BLOB NOT NULL,
+    execution_process_id  BLOB NOT NULL,
+    file_path             TEXT NOT NULL,
+    start_line            INTEGER,
+    end_line              INTEGER,
+    severity              TEXT NOT NULL DEFAULT 'warning'
+                             CHECK (severity IN ('info', 'warning', 'error')),
+    message               TEXT NOT NULL,
+    created_at            TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (task_attempt_id) REFERENCES task_attempts(id) ON DELETE CASCADE,
+    FOREIGN KEY (execution_process_id) REFERENCES execution_processes(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_review_findings_task_attempt_id ON review_findings(task_attempt_id);
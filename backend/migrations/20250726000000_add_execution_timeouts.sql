@@ -0,0 +1,33 @@
+-- 1. Add the replacement status column with the wider CHECK
+ALTER TABLE execution_processes
+  ADD COLUMN status_new TEXT NOT NULL DEFAULT 'running'
+    CHECK (status_new IN ('running',
+                          'completed',
+                          'failed',
+                          'killed',
+                          'timedout'));
+
+-- 2. Copy existing values across
+UPDATE execution_processes
+  SET status_new = status;
+
+-- 3. Drop any indexes that mention the old column
+DROP INDEX IF EXISTS idx_execution_processes_status;
+
+-- 4. Remove the old column (requires 3.35+)
+ALTER TABLE execution_processes DROP COLUMN status;
+
+-- 5. Rename the new column back to the canonical name
+ALTER TABLE execution_processes
+  RENAME COLUMN status_new TO status;
+
+-- 6. Re-create the index
+CREATE INDEX idx_execution_processes_status
+        ON execution_processes(status);
+
+-- 7. Why a timed-out process was stopped
+ALTER TABLE execution_processes ADD COLUMN timeout_reason TEXT;
+
+-- 8. Per-project limits, in seconds; NULL means no limit
+ALTER TABLE projects ADD COLUMN max_runtime_seconds INTEGER;
+ALTER TABLE projects ADD COLUMN idle_timeout_seconds INTEGER;
@@ -0,0 +1,7 @@
+-- Automatic retries are recorded as their own execution processes, linked to the
+-- failed process they replace
+ALTER TABLE execution_processes
+  ADD COLUMN retry_of_process_id BLOB REFERENCES execution_processes(id) ON DELETE SET NULL;
+
+CREATE INDEX idx_execution_processes_retry_of_process_id
+        ON execution_processes(retry_of_process_id);
@@ -0,0 +1,12 @@
+-- Messages sent to the stdin of running execution processes
+CREATE TABLE execution_process_inputs (
+    id                    BLOB PRIMARY KEY,
+    execution_process_id  BLOB NOT NULL,
+    message               TEXT NOT NULL,
+    entry_index           INTEGER,  -- Position in the conversation when the message was sent
+    created_at            TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (execution_process_id) REFERENCES execution_processes(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_execution_process_inputs_execution_process_id
+        ON execution_process_inputs(execution_process_id);
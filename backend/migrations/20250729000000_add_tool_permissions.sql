@@ -0,0 +1,49 @@
+PRAGMA foreign_keys = ON;
+
+-- How a project's agents are gated before risky tool calls
+CREATE TABLE permission_policies (
+    project_id        BLOB PRIMARY KEY,
+    timeout_seconds   INTEGER NOT NULL DEFAULT 300,
+    default_decision  TEXT NOT NULL DEFAULT 'deny'
+                         CHECK (default_decision IN ('allow', 'deny')),
+    created_at        TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at        TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+-- Allow/deny rules; every criterion that is set must match
+CREATE TABLE permission_rules (
+    id               BLOB PRIMARY KEY,
+    project_id       BLOB NOT NULL,
+    position         INTEGER NOT NULL,
+    decision         TEXT NOT NULL CHECK (decision IN ('allow', 'deny')),
+    tool_name        TEXT,  -- Glob over the tool name, e.g. 'Bash' or 'mcp__*'
+    path_glob        TEXT,  -- Glob over file paths, relative to the worktree
+    command_pattern  TEXT,  -- Regex over shell commands
+    UNIQUE (project_id, position),
+    FOREIGN KEY (project_id) REFERENCES permission_policies(project_id) ON DELETE CASCADE
+);
+
+-- Every permission prompt an agent raised and how it was decided
+CREATE TABLE permission_requests (
+    id                    BLOB PRIMARY KEY,
+    project_id            BLOB NOT NULL,
+    task_attempt_id       BLOB,
+    execution_process_id  BLOB,
+    tool_name             TEXT NOT NULL,
+    input                 TEXT NOT NULL,  -- Tool input as JSON
+    default_decision      TEXT NOT NULL CHECK (default_decision IN ('allow', 'deny')),
+    decision              TEXT CHECK (decision IN ('allow', 'deny')),  -- NULL while pending
+    decided_by            TEXT CHECK (decided_by IN ('rule', 'user', 'timeout', 'no_rules')),
+    rule_position         INTEGER,
+    message               TEXT,
+    expires_at            TEXT NOT NULL,
+    decided_at            TEXT,
+    created_at            TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE,
+    FOREIGN KEY (task_attempt_id) REFERENCES task_attempts(id) ON DELETE SET NULL,
+    FOREIGN KEY (execution_process_id) REFERENCES execution_processes(id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_permission_requests_project_id ON permission_requests(project_id);
+CREATE INDEX idx_permission_requests_task_attempt_id ON permission_requests(task_attempt_id);
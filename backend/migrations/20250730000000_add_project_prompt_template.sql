@@ -0,0 +1,2 @@
+-- Template for the prompt agents are spawned with; NULL uses the built-in prompt
+ALTER TABLE projects ADD COLUMN prompt_template TEXT;
@@ -0,0 +1,7 @@
+-- The persona an agent process acted as, so activity and scoring are attributed to the
+-- persona that did the work even if the task is reassigned later
+ALTER TABLE execution_processes
+  ADD COLUMN persona_id BLOB REFERENCES project_personas(id) ON DELETE SET NULL;
+
+CREATE INDEX idx_execution_processes_persona_id
+        ON execution_processes(persona_id);
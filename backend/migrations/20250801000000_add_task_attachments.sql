@@ -0,0 +1,16 @@
+-- Files, images, log excerpts and repository paths handed to agents alongside a task.
+-- Uploaded content lives under the asset dir; repo paths point into the project repository.
+CREATE TABLE task_attachments (
+    id            BLOB PRIMARY KEY,
+    task_id       BLOB NOT NULL,
+    kind          TEXT NOT NULL
+                     CHECK (kind IN ('file', 'image', 'log', 'repo_path')),
+    name          TEXT NOT NULL,
+    path          TEXT NOT NULL,
+    content_type  TEXT,
+    size_bytes    INTEGER,
+    created_at    TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_task_attachments_task_id ON task_attachments(task_id);
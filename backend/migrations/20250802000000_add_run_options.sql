@@ -0,0 +1,18 @@
+-- Model and extra CLI arguments an attempt's agent runs with; extra_args is a JSON array
+ALTER TABLE task_attempts ADD COLUMN model TEXT;
+ALTER TABLE task_attempts ADD COLUMN extra_args TEXT;
+
+-- What each agent run actually used
+ALTER TABLE execution_processes ADD COLUMN model TEXT;
+ALTER TABLE execution_processes ADD COLUMN cli_version TEXT;
+
+-- Agent CLI versions pinned per project, used instead of the executor's default release
+CREATE TABLE project_cli_versions (
+    project_id  BLOB NOT NULL,
+    executor    TEXT NOT NULL,
+    version     TEXT NOT NULL,
+    created_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    PRIMARY KEY (project_id, executor),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
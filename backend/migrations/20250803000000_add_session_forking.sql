@@ -0,0 +1,5 @@
+-- Worktree HEAD once an agent run's changes were committed, so later attempts can fork from it
+ALTER TABLE executor_sessions ADD COLUMN after_head_commit TEXT;
+
+-- Session a forked attempt resumes from until it has sessions of its own
+ALTER TABLE task_attempts ADD COLUMN forked_from_session_id BLOB REFERENCES executor_sessions(id) ON DELETE SET NULL;
@@ -0,0 +1,3 @@
+-- Whether a project's processes run in a Linux namespace sandbox, and if so with network access
+ALTER TABLE projects ADD COLUMN sandbox_mode TEXT NOT NULL DEFAULT 'disabled'
+    CHECK (sandbox_mode IN ('disabled', 'enabled', 'offline'));
@@ -0,0 +1,8 @@
+-- Per-project resource limits for each execution's process group; NULL means no limit
+ALTER TABLE projects ADD COLUMN memory_limit_mb INTEGER;
+ALTER TABLE projects ADD COLUMN cpu_limit_percent INTEGER;
+ALTER TABLE projects ADD COLUMN max_processes INTEGER;
+
+-- Most memory and CPU time an execution's processes used, sampled while it runs
+ALTER TABLE execution_processes ADD COLUMN peak_memory_bytes INTEGER;
+ALTER TABLE execution_processes ADD COLUMN cpu_time_ms INTEGER;
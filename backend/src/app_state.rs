@@ -14,6 +14,8 @@ pub enum ExecutionType {
     SetupScript,
     CleanupScript,
     CodingAgent,
+    PlanningAgent,
+    ReviewAgent,
     DevServer,
 }
 
@@ -24,6 +26,16 @@ pub struct RunningExecution {
     pub child: command_runner::CommandProcess,
 }
 
+/// An execution the monitor found finished
+#[derive(Debug)]
+pub struct CompletedExecution {
+    pub execution_id: Uuid,
+    pub task_attempt_id: Uuid,
+    pub success: bool,
+    pub exit_code: Option<i64>,
+    pub child: command_runner::CommandProcess,
+}
+
 #[derive(Debug, Clone)]
 pub struct AppState {
     running_executions: Arc<Mutex<HashMap<Uuid, RunningExecution>>>,
@@ -85,7 +97,11 @@ impl AppState {
             .any(|exec| exec.task_attempt_id == attempt_id)
     }
 
-    pub async fn get_running_executions_for_monitor(&self) -> Vec<(Uuid, Uuid, bool, Option<i64>)> {
+    /// Remove the executions that have finished, with their outcome and process handle
+    ///
+    /// Anything slow to do with a finished process, like fetching its remote workspace, is up
+    /// to the caller, so it does not hold up the other executions.
+    pub async fn get_running_executions_for_monitor(&self) -> Vec<CompletedExecution> {
         let mut executions = self.running_executions.lock().await;
         let mut completed_executions = Vec::new();
 
@@ -117,11 +133,20 @@ impl AppState {
         }
 
         // Remove completed executions from the map
-        for (execution_id, _, _, _) in &completed_executions {
-            executions.remove(execution_id);
-        }
-
         completed_executions
+            .into_iter()
+            .filter_map(|(execution_id, task_attempt_id, success, exit_code)| {
+                executions
+                    .remove(&execution_id)
+                    .map(|execution| CompletedExecution {
+                        execution_id,
+                        task_attempt_id,
+                        success,
+                        exit_code,
+                        child: execution.child,
+                    })
+            })
+            .collect()
     }
 
     // Running executions setters
@@ -130,23 +155,56 @@ impl AppState {
         executions.insert(execution_id, execution);
     }
 
+    /// Stop a running execution, giving it the configured grace period to shut down after
+    /// each signal; `Ok(None)` when it is not running
     pub async fn stop_running_execution_by_id(
         &self,
         execution_id: Uuid,
-    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<Option<command_runner::StopOutcome>, Box<dyn std::error::Error + Send + Sync>> {
+        // Don't hold the lock while the process winds down
+        let Some(mut exec) = self.running_executions.lock().await.remove(&execution_id) else {
+            return Ok(None);
+        };
+
+        let grace_period = self.get_stop_grace_period().await;
+        match exec.child.stop(grace_period).await {
+            Ok(outcome) => Ok(Some(outcome)),
+            Err(e) => {
+                // Keep tracking it so the stop can be retried
+                self.running_executions
+                    .lock()
+                    .await
+                    .insert(execution_id, exec);
+                Err(Box::new(e))
+            }
+        }
+    }
+
+    /// The most memory and CPU time each running execution has used so far
+    pub async fn sample_resource_usage(&self) -> Vec<(Uuid, command_runner::ResourceUsage)> {
+        let mut executions = self.running_executions.lock().await;
+        executions
+            .iter_mut()
+            .filter_map(|(execution_id, exec)| {
+                exec.child
+                    .resource_usage()
+                    .map(|usage| (*execution_id, usage))
+            })
+            .collect()
+    }
+
+    /// Write to the stdin of a running execution; `Ok(false)` when it is not running
+    pub async fn write_to_running_execution(
+        &self,
+        execution_id: Uuid,
+        data: &str,
+    ) -> Result<bool, command_runner::CommandError> {
         let mut executions = self.running_executions.lock().await;
         let Some(exec) = executions.get_mut(&execution_id) else {
             return Ok(false);
         };
 
-        // Kill the process using CommandRunner's kill method
-        exec.child
-            .kill()
-            .await
-            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
-
-        // only NOW remove it
-        executions.remove(&execution_id);
+        exec.child.write_stdin(data).await?;
         Ok(true)
     }
 
@@ -166,6 +224,53 @@ impl AppState {
         config.sound_file.clone()
     }
 
+    pub async fn get_review_executor(&self) -> Option<crate::executor::ExecutorConfig> {
+        let config = self.config.read().await;
+        config.review_executor.clone()
+    }
+
+    pub async fn get_executor_limits(
+        &self,
+        executor: &str,
+    ) -> crate::models::config::ExecutionLimits {
+        let config = self.config.read().await;
+        config
+            .executor_limits
+            .get(executor)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub async fn get_executor_resource_limits(
+        &self,
+        executor: &str,
+    ) -> crate::models::config::ResourceLimits {
+        let config = self.config.read().await;
+        config
+            .executor_resource_limits
+            .get(executor)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub async fn get_stop_grace_period(&self) -> std::time::Duration {
+        let config = self.config.read().await;
+        std::time::Duration::from_secs(config.stop_grace_period_seconds)
+    }
+
+    pub async fn get_pipeline_check_timeout(&self) -> std::time::Duration {
+        let config = self.config.read().await;
+        std::time::Duration::from_secs(config.pipeline_check_timeout_seconds)
+    }
+
+    pub async fn get_retry_policy(
+        &self,
+        executor: &str,
+    ) -> Option<crate::models::config::RetryPolicy> {
+        let config = self.config.read().await;
+        config.retry_policies.get(executor).cloned()
+    }
+
     pub fn get_config(&self) -> &Arc<tokio::sync::RwLock<crate::models::config::Config>> {
         &self.config
     }
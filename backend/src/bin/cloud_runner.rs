@@ -1,19 +1,40 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+    time::Duration,
+};
 
 use axum::{
     body::Body,
-    extract::{Path, State},
-    http::StatusCode,
-    response::{Json, Response},
-    routing::{delete, get, post},
+    extract::{DefaultBodyLimit, Path, Query, Request, State},
+    http::{Method, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Json, Response},
+    routing::{delete, get, post, put},
     Router,
 };
-use serde::Serialize;
+use axum_server::tls_rustls::RustlsConfig;
+use rustls::{
+    pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer},
+    server::WebPkiClientVerifier,
+    RootCertStore, ServerConfig,
+};
+use serde::{Deserialize, Serialize};
 use tokio::sync::Mutex;
 use tokio_util::io::ReaderStream;
 use tracing_subscriber::prelude::*;
 use uuid::Uuid;
-use vibe_kanban::command_runner::{CommandProcess, CommandRunner, CommandRunnerArgs};
+use vibe_kanban::command_runner::{
+    CommandError, CommandProcess, CommandRunner, CommandRunnerArgs, RunnerAllowlist, RunnerAuth,
+    RunnerWorkspaces, StopOutcome, DEFAULT_STOP_GRACE_PERIOD,
+};
+
+// Largest request body the runner will accept, except for workspace uploads
+const MAX_REQUEST_BYTES: usize = 16 * 1024 * 1024;
+
+// Largest repository bundle a workspace upload may carry
+const MAX_BUNDLE_BYTES: usize = 1024 * 1024 * 1024;
 
 // Structure to hold process and its streams
 struct ProcessEntry {
@@ -39,6 +60,11 @@ impl std::fmt::Debug for ProcessEntry {
 #[derive(Clone)]
 struct AppState {
     processes: Arc<Mutex<HashMap<String, ProcessEntry>>>,
+    auth: Option<Arc<RunnerAuth>>,
+    allowlist: Arc<RunnerAllowlist>,
+    workspaces: Arc<RunnerWorkspaces>,
+    // Serializes git operations on workspaces
+    workspace_lock: Arc<Mutex<()>>,
 }
 
 // Response type for API responses
@@ -58,7 +84,6 @@ impl<T> ApiResponse<T> {
         }
     }
 
-    #[allow(dead_code)]
     fn error(message: String) -> Self {
         Self {
             success: false,
@@ -74,6 +99,19 @@ struct CreateCommandResponse {
     process_id: String,
 }
 
+// Response type for a materialized workspace
+#[derive(Debug, Serialize)]
+struct WorkspaceResponse {
+    path: String,
+}
+
+// Query parameters for workspace requests
+#[derive(Debug, Deserialize)]
+struct WorkspaceQuery {
+    branch: String,
+    base: Option<String>,
+}
+
 // Response type for process status
 #[derive(Debug, Serialize)]
 struct ProcessStatusResponse {
@@ -94,34 +132,197 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .with(tracing_subscriber::fmt::layer())
         .init();
 
+    let auth = RunnerAuth::from_env();
+    let workspaces = RunnerWorkspaces::from_env()?;
+    let mut allowlist = RunnerAllowlist::from_env()?;
+    allowlist.allow_root(workspaces.root());
+    tracing::info!(
+        "Materializing workspaces in {}",
+        workspaces.root().display()
+    );
+    if allowlist.is_unrestricted() {
+        tracing::warn!(
+            "No CLOUD_RUNNER_ALLOWED_COMMANDS or CLOUD_RUNNER_ALLOWED_ROOTS set; any command may run"
+        );
+    }
+
+    // Only listen beyond localhost when callers must authenticate
+    let host = std::env::var("HOST").unwrap_or_else(|_| {
+        if auth.is_some() {
+            "0.0.0.0"
+        } else {
+            "127.0.0.1"
+        }
+        .to_string()
+    });
+    let host: IpAddr = host.parse()?;
+    if auth.is_none() && !host.is_loopback() {
+        return Err(format!(
+            "Refusing to listen on {} without CLOUD_RUNNER_HMAC_SECRET or CLOUD_RUNNER_TOKEN",
+            host
+        )
+        .into());
+    }
+
     // Create application state
     let app_state = AppState {
         processes: Arc::new(Mutex::new(HashMap::new())),
+        auth: auth.map(Arc::new),
+        allowlist: Arc::new(allowlist),
+        workspaces: Arc::new(workspaces),
+        workspace_lock: Arc::new(Mutex::new(())),
     };
 
     // Build router
     let app = Router::new()
-        .route("/health", get(health_check))
         .route("/commands", post(create_command))
         .route("/commands/:process_id", delete(kill_command))
         .route("/commands/:process_id/status", get(get_process_status))
         .route("/commands/:process_id/stdout", get(get_process_stdout))
         .route("/commands/:process_id/stderr", get(get_process_stderr))
+        .route(
+            "/commands/:process_id/stdin",
+            post(write_process_stdin).delete(close_process_stdin),
+        )
+        .route(
+            "/workspaces/:workspace_id",
+            put(upload_workspace)
+                .layer(DefaultBodyLimit::max(MAX_BUNDLE_BYTES))
+                .delete(delete_workspace),
+        )
+        .route(
+            "/workspaces/:workspace_id/bundle",
+            get(get_workspace_bundle),
+        )
+        .layer(DefaultBodyLimit::max(MAX_REQUEST_BYTES))
+        .route_layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            authenticate,
+        ))
+        .route("/health", get(health_check))
         .with_state(app_state);
 
     // Get port from environment or default to 8000
-    let port = std::env::var("PORT").unwrap_or_else(|_| "8000".to_string());
-    let addr = format!("0.0.0.0:{}", port);
-
-    tracing::info!("Cloud Runner server starting on {}", addr);
+    let port: u16 = std::env::var("PORT")
+        .unwrap_or_else(|_| "8000".to_string())
+        .parse()?;
+    let addr = SocketAddr::new(host, port);
 
     // Start the server
-    let listener = tokio::net::TcpListener::bind(&addr).await?;
-    axum::serve(listener, app).await?;
+    match tls_config()? {
+        Some(config) => {
+            tracing::info!("Cloud Runner server starting on https://{}", addr);
+            axum_server::bind_rustls(addr, RustlsConfig::from_config(Arc::new(config)))
+                .serve(app.into_make_service())
+                .await?;
+        }
+        None => {
+            tracing::info!("Cloud Runner server starting on http://{}", addr);
+            let listener = tokio::net::TcpListener::bind(addr).await?;
+            axum::serve(listener, app).await?;
+        }
+    }
 
     Ok(())
 }
 
+// TLS settings from CLOUD_RUNNER_TLS_CERT and CLOUD_RUNNER_TLS_KEY (PEM files), requiring client
+// certificates signed by CLOUD_RUNNER_TLS_CLIENT_CA when it is set
+fn tls_config() -> Result<Option<ServerConfig>, Box<dyn std::error::Error>> {
+    let (Ok(cert_path), Ok(key_path)) = (
+        std::env::var("CLOUD_RUNNER_TLS_CERT"),
+        std::env::var("CLOUD_RUNNER_TLS_KEY"),
+    ) else {
+        return Ok(None);
+    };
+
+    let certs = CertificateDer::pem_file_iter(&cert_path)?.collect::<Result<Vec<_>, _>>()?;
+    let key = PrivateKeyDer::from_pem_file(&key_path)?;
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let builder = ServerConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()?;
+
+    let builder = match std::env::var("CLOUD_RUNNER_TLS_CLIENT_CA") {
+        Ok(ca_path) => {
+            let mut roots = RootCertStore::empty();
+            for cert in CertificateDer::pem_file_iter(&ca_path)? {
+                roots.add(cert?)?;
+            }
+            tracing::info!("Requiring client certificates signed by {}", ca_path);
+            builder.with_client_cert_verifier(
+                WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider).build()?,
+            )
+        }
+        Err(_) => builder.with_no_client_auth(),
+    };
+
+    let mut config = builder.with_single_cert(certs, key)?;
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    Ok(Some(config))
+}
+
+// Reject requests that aren't signed with the runner's credentials
+async fn authenticate(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let Some(auth) = &state.auth else {
+        return next.run(request).await;
+    };
+
+    // Turn away requests without credentials before reading what may be a large body
+    if let Err(e) = auth.verify_headers(request.headers()) {
+        tracing::warn!("Rejected {} {}: {}", request.method(), request.uri(), e);
+        return reject(e);
+    }
+    if !auth.covers_body() {
+        return next.run(request).await;
+    }
+
+    let (parts, body) = request.into_parts();
+    let limit = body_limit(&parts.method, parts.uri.path());
+    let Ok(body) = axum::body::to_bytes(body, limit).await else {
+        return StatusCode::PAYLOAD_TOO_LARGE.into_response();
+    };
+
+    let path = parts
+        .uri
+        .path_and_query()
+        .map(|path| path.as_str())
+        .unwrap_or("/");
+    if let Err(e) = auth.verify(parts.method.as_str(), path, &parts.headers, &body) {
+        tracing::warn!("Rejected {} {}: {}", parts.method, path, e);
+        return reject(e);
+    }
+
+    next.run(Request::from_parts(parts, Body::from(body))).await
+}
+
+// Largest body a request may carry: only workspace uploads carry repository bundles
+fn body_limit(method: &Method, path: &str) -> usize {
+    if method == Method::PUT && path.starts_with("/workspaces/") {
+        MAX_BUNDLE_BYTES
+    } else {
+        MAX_REQUEST_BYTES
+    }
+}
+
+// Respond to a refused request with its reason
+fn reject(error: CommandError) -> Response {
+    let status = match &error {
+        CommandError::Unauthenticated { .. } => StatusCode::UNAUTHORIZED,
+        CommandError::NotPermitted { .. } => StatusCode::FORBIDDEN,
+        CommandError::IoError { error } if error.kind() == std::io::ErrorKind::NotFound => {
+            StatusCode::NOT_FOUND
+        }
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    let message = match error {
+        CommandError::Unauthenticated { message } | CommandError::NotPermitted { message } => {
+            message
+        }
+        error => error.to_string(),
+    };
+    (status, Json(ApiResponse::<()>::error(message))).into_response()
+}
+
 // Health check endpoint
 async fn health_check() -> Json<ApiResponse<String>> {
     Json(ApiResponse::success("Cloud Runner is healthy".to_string()))
@@ -131,9 +332,14 @@ async fn health_check() -> Json<ApiResponse<String>> {
 async fn create_command(
     State(state): State<AppState>,
     Json(request): Json<CommandRunnerArgs>,
-) -> Result<Json<ApiResponse<CreateCommandResponse>>, StatusCode> {
+) -> Result<Json<ApiResponse<CreateCommandResponse>>, Response> {
     tracing::info!("Creating command: {} {:?}", request.command, request.args);
 
+    if let Err(e) = state.allowlist.check(&request) {
+        tracing::warn!("Refused command {}: {}", request.command, e);
+        return Err(reject(e));
+    }
+
     // Create a local command runner from the request
     let runner = CommandRunner::from_args(request);
 
@@ -142,7 +348,7 @@ async fn create_command(
         Ok(process) => process,
         Err(e) => {
             tracing::error!("Failed to start command: {}", e);
-            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR.into_response());
         }
     };
 
@@ -157,7 +363,7 @@ async fn create_command(
         Ok(streams) => streams,
         Err(e) => {
             tracing::error!("Failed to get process streams: {}", e);
-            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR.into_response());
         }
     };
 
@@ -203,72 +409,45 @@ async fn create_command(
     })))
 }
 
-// Kill a running command
+// Query parameters for stopping a command
+#[derive(Debug, Deserialize)]
+struct StopCommandQuery {
+    grace_period_ms: Option<u64>,
+}
+
+// Stop a running command, escalating from SIGINT to SIGKILL
 async fn kill_command(
     State(state): State<AppState>,
     Path(process_id): Path<String>,
-) -> Result<Json<ApiResponse<String>>, StatusCode> {
-    tracing::info!("Killing command with process_id: {}", process_id);
+    Query(query): Query<StopCommandQuery>,
+) -> Result<Json<ApiResponse<StopOutcome>>, StatusCode> {
+    tracing::info!("Stopping command with process_id: {}", process_id);
 
-    let mut processes = state.processes.lock().await;
+    // Release the lock while the process is given time to shut down
+    let Some(mut entry) = state.processes.lock().await.remove(&process_id) else {
+        tracing::warn!("Process not found: {}", process_id);
+        return Err(StatusCode::NOT_FOUND);
+    };
 
-    if let Some(mut entry) = processes.remove(&process_id) {
-        // First check if the process has already finished
-        match entry.process.status().await {
-            Ok(Some(_)) => {
-                // Process already finished, consider kill successful
-                tracing::info!(
-                    "Process {} already completed, kill considered successful",
-                    process_id
-                );
-                Ok(Json(ApiResponse::success(
-                    "Process was already completed".to_string(),
-                )))
-            }
-            Ok(None) => {
-                // Process still running, attempt to kill
-                match entry.process.kill().await {
-                    Ok(()) => {
-                        tracing::info!("Successfully killed process: {}", process_id);
-                        Ok(Json(ApiResponse::success(
-                            "Process killed successfully".to_string(),
-                        )))
-                    }
-                    Err(e) => {
-                        tracing::error!("Failed to kill process {}: {}", process_id, e);
-
-                        // Check if it's a "No such process" error (process finished during kill)
-                        if e.to_string().contains("No such process") {
-                            tracing::info!("Process {} finished during kill attempt", process_id);
-                            Ok(Json(ApiResponse::success(
-                                "Process finished during kill attempt".to_string(),
-                            )))
-                        } else {
-                            Err(StatusCode::INTERNAL_SERVER_ERROR)
-                        }
-                    }
-                }
-            }
-            Err(e) => {
-                tracing::error!("Failed to check status for process {}: {}", process_id, e);
-                // Still attempt to kill
-                match entry.process.kill().await {
-                    Ok(()) => {
-                        tracing::info!("Successfully killed process: {}", process_id);
-                        Ok(Json(ApiResponse::success(
-                            "Process killed successfully".to_string(),
-                        )))
-                    }
-                    Err(e) => {
-                        tracing::error!("Failed to kill process {}: {}", process_id, e);
-                        Err(StatusCode::INTERNAL_SERVER_ERROR)
-                    }
+    let grace_period = query
+        .grace_period_ms
+        .map(Duration::from_millis)
+        .unwrap_or(DEFAULT_STOP_GRACE_PERIOD);
+
+    match entry.process.stop(grace_period).await {
+        Ok(outcome) => {
+            match outcome.signal {
+                Some(signal) => {
+                    tracing::info!("Process {} stopped by {:?}", process_id, signal)
                 }
+                None => tracing::info!("Process {} had already completed", process_id),
             }
+            Ok(Json(ApiResponse::success(outcome)))
+        }
+        Err(e) => {
+            tracing::error!("Failed to stop process {}: {}", process_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
         }
-    } else {
-        tracing::warn!("Process not found: {}", process_id);
-        Err(StatusCode::NOT_FOUND)
     }
 }
 
@@ -314,6 +493,50 @@ async fn get_process_status(
     }
 }
 
+// Write to the stdin of a running command
+async fn write_process_stdin(
+    State(state): State<AppState>,
+    Path(process_id): Path<String>,
+    body: String,
+) -> Result<Json<ApiResponse<()>>, StatusCode> {
+    let mut processes = state.processes.lock().await;
+
+    if let Some(entry) = processes.get_mut(&process_id) {
+        match entry.process.write_stdin(&body).await {
+            Ok(()) => Ok(Json(ApiResponse::success(()))),
+            Err(CommandError::StdinUnavailable) => Err(StatusCode::CONFLICT),
+            Err(e) => {
+                tracing::error!("Failed to write stdin of process {}: {}", process_id, e);
+                Err(StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        }
+    } else {
+        tracing::warn!("Process not found: {}", process_id);
+        Err(StatusCode::NOT_FOUND)
+    }
+}
+
+// Close the stdin of a running command
+async fn close_process_stdin(
+    State(state): State<AppState>,
+    Path(process_id): Path<String>,
+) -> Result<Json<ApiResponse<()>>, StatusCode> {
+    let mut processes = state.processes.lock().await;
+
+    if let Some(entry) = processes.get_mut(&process_id) {
+        match entry.process.close_stdin().await {
+            Ok(()) => Ok(Json(ApiResponse::success(()))),
+            Err(e) => {
+                tracing::error!("Failed to close stdin of process {}: {}", process_id, e);
+                Err(StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        }
+    } else {
+        tracing::warn!("Process not found: {}", process_id);
+        Err(StatusCode::NOT_FOUND)
+    }
+}
+
 // Get stdout stream for a running command (direct streaming, no buffering)
 async fn get_process_stdout(
     State(state): State<AppState>,
@@ -399,3 +622,80 @@ async fn get_process_stderr(
         Err(StatusCode::NOT_FOUND)
     }
 }
+
+// Materialize an uploaded bundle of a branch as a workspace commands can run in
+async fn upload_workspace(
+    State(state): State<AppState>,
+    Path(workspace_id): Path<String>,
+    Query(query): Query<WorkspaceQuery>,
+    bundle: axum::body::Bytes,
+) -> Result<Json<ApiResponse<WorkspaceResponse>>, Response> {
+    tracing::info!(
+        "Materializing workspace {} on branch {}",
+        workspace_id,
+        query.branch
+    );
+
+    let _guard = state.workspace_lock.lock().await;
+    match state
+        .workspaces
+        .materialize(&workspace_id, &query.branch, &bundle)
+        .await
+    {
+        Ok(path) => Ok(Json(ApiResponse::success(WorkspaceResponse {
+            path: path.to_string_lossy().to_string(),
+        }))),
+        Err(e) => {
+            tracing::error!("Failed to materialize workspace {}: {}", workspace_id, e);
+            Err(reject(e))
+        }
+    }
+}
+
+// Bundle the commits a workspace's branch gained since `base`
+async fn get_workspace_bundle(
+    State(state): State<AppState>,
+    Path(workspace_id): Path<String>,
+    Query(query): Query<WorkspaceQuery>,
+) -> Result<Response, Response> {
+    let base = query.base.unwrap_or_default();
+
+    let _guard = state.workspace_lock.lock().await;
+    match state
+        .workspaces
+        .export(&workspace_id, &query.branch, &base)
+        .await
+    {
+        Ok(Some(bundle)) => Response::builder()
+            .header("content-type", "application/octet-stream")
+            .body(Body::from(bundle))
+            .map_err(|e| {
+                tracing::error!("Failed to build bundle response: {}", e);
+                StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            }),
+        Ok(None) => Ok(StatusCode::NO_CONTENT.into_response()),
+        Err(e) => {
+            tracing::error!("Failed to bundle workspace {}: {}", workspace_id, e);
+            Err(reject(e))
+        }
+    }
+}
+
+// Delete a workspace once its worktree is cleaned up
+async fn delete_workspace(
+    State(state): State<AppState>,
+    Path(workspace_id): Path<String>,
+) -> Result<Json<ApiResponse<()>>, Response> {
+    let _guard = state.workspace_lock.lock().await;
+    match state.workspaces.remove(&workspace_id) {
+        Ok(true) => {
+            tracing::info!("Deleted workspace {}", workspace_id);
+            Ok(Json(ApiResponse::success(())))
+        }
+        Ok(false) => Err(StatusCode::NOT_FOUND.into_response()),
+        Err(e) => {
+            tracing::error!("Failed to delete workspace {}: {}", workspace_id, e);
+            Err(reject(e))
+        }
+    }
+}
@@ -49,15 +49,6 @@ export const EDITOR_LABELS: Record<string, string> = {
     "custom": "Custom"
 };
 
-export const MCP_SUPPORTED_EXECUTORS: string[] = [
-    "claude",
-    "amp",
-    "gemini",
-    "sst-opencode",
-    "charm-opencode",
-    "claude-code-router"
-];
-
 export const SOUND_FILES: SoundFile[] = [
     "abstract-sound1",
     "abstract-sound2",
@@ -96,6 +87,9 @@ fn generate_types_content() -> String {
         vibe_kanban::models::config::ThemeMode::decl(),
         vibe_kanban::models::config::EditorConfig::decl(),
         vibe_kanban::models::config::GitHubConfig::decl(),
+        vibe_kanban::models::config::ExecutionLimits::decl(),
+        vibe_kanban::models::config::ResourceLimits::decl(),
+        vibe_kanban::models::config::RetryPolicy::decl(),
         vibe_kanban::models::config::EditorType::decl(),
         vibe_kanban::models::config::EditorConstants::decl(),
         vibe_kanban::models::config::SoundFile::decl(),
@@ -103,6 +97,11 @@ fn generate_types_content() -> String {
         vibe_kanban::routes::config::ConfigConstants::decl(),
         vibe_kanban::executor::ExecutorConfig::decl(),
         vibe_kanban::executor::ExecutorConstants::decl(),
+        vibe_kanban::executor::McpConfigFormat::decl(),
+        vibe_kanban::executor::McpCapability::decl(),
+        vibe_kanban::executor::PlanMode::decl(),
+        vibe_kanban::executor::ExecutorCapabilities::decl(),
+        vibe_kanban::models::project::SandboxMode::decl(),
         vibe_kanban::models::project::CreateProject::decl(),
         vibe_kanban::models::project::CreateProjectFromGitHub::decl(),
         vibe_kanban::models::project::Project::decl(),
@@ -126,11 +125,13 @@ fn generate_types_content() -> String {
         vibe_kanban::models::task_attempt::CreateTaskAttempt::decl(),
         vibe_kanban::models::task_attempt::UpdateTaskAttempt::decl(),
         vibe_kanban::models::task_attempt::CreateFollowUpAttempt::decl(),
+        vibe_kanban::models::task_attempt::ForkTaskAttempt::decl(),
         vibe_kanban::routes::filesystem::DirectoryEntry::decl(),
         vibe_kanban::routes::filesystem::DirectoryListResponse::decl(),
         vibe_kanban::routes::auth::DeviceStartResponse::decl(),
         vibe_kanban::services::github_service::RepositoryInfo::decl(),
         vibe_kanban::routes::task_attempts::ProcessLogsResponse::decl(),
+        vibe_kanban::routes::task_attempts::ApprovePlanRequest::decl(),
         vibe_kanban::models::task_attempt::DiffChunkType::decl(),
         vibe_kanban::models::task_attempt::DiffChunk::decl(),
         vibe_kanban::models::task_attempt::FileDiff::decl(),
@@ -144,9 +145,47 @@ fn generate_types_content() -> String {
         vibe_kanban::models::execution_process::ExecutionProcessType::decl(),
         vibe_kanban::models::execution_process::CreateExecutionProcess::decl(),
         vibe_kanban::models::execution_process::UpdateExecutionProcess::decl(),
+        vibe_kanban::command_runner::StopSignal::decl(),
+        vibe_kanban::command_runner::StopOutcome::decl(),
+        vibe_kanban::models::execution_process_input::ExecutionProcessInput::decl(),
+        vibe_kanban::models::execution_process_input::SendInputRequest::decl(),
         vibe_kanban::models::executor_session::ExecutorSession::decl(),
         vibe_kanban::models::executor_session::CreateExecutorSession::decl(),
         vibe_kanban::models::executor_session::UpdateExecutorSession::decl(),
+        vibe_kanban::models::token_usage::TokenUsage::decl(),
+        vibe_kanban::models::token_usage::ExecutionProcessUsage::decl(),
+        vibe_kanban::models::token_usage::UsageSummary::decl(),
+        vibe_kanban::models::pipeline::StageCondition::decl(),
+        vibe_kanban::models::pipeline::StageStatus::decl(),
+        vibe_kanban::models::pipeline::PipelineStage::decl(),
+        vibe_kanban::models::pipeline::Pipeline::decl(),
+        vibe_kanban::models::pipeline::CreatePipeline::decl(),
+        vibe_kanban::models::pipeline::UpdatePipeline::decl(),
+        vibe_kanban::models::pipeline::TaskAttemptStage::decl(),
+        vibe_kanban::models::review_finding::ReviewSeverity::decl(),
+        vibe_kanban::models::review_finding::ReviewFinding::decl(),
+        vibe_kanban::models::review_finding::StartReviewRequest::decl(),
+        vibe_kanban::models::review_finding::ReviewFollowUpRequest::decl(),
+        vibe_kanban::models::permission::PermissionDecision::decl(),
+        vibe_kanban::models::permission::PermissionDecisionSource::decl(),
+        vibe_kanban::models::permission::PermissionRule::decl(),
+        vibe_kanban::models::permission::PermissionPolicy::decl(),
+        vibe_kanban::models::permission::UpdatePermissionPolicy::decl(),
+        vibe_kanban::models::permission::PermissionRequest::decl(),
+        vibe_kanban::models::permission::DecidePermissionRequest::decl(),
+        vibe_kanban::models::prompt_template::PromptPreview::decl(),
+        vibe_kanban::models::task_attachment::AttachmentKind::decl(),
+        vibe_kanban::models::task_attachment::TaskAttachment::decl(),
+        vibe_kanban::models::task_attachment::CreateTaskAttachment::decl(),
+        vibe_kanban::models::project_cli_version::ProjectCliVersion::decl(),
+        vibe_kanban::models::project_cli_version::SetProjectCliVersion::decl(),
+        vibe_kanban::models::task_attempt_group::TaskAttemptGroup::decl(),
+        vibe_kanban::models::task_attempt_group::CreateTaskAttemptGroup::decl(),
+        vibe_kanban::models::task_attempt_group::DiffStats::decl(),
+        vibe_kanban::models::task_attempt_group::AttemptComparison::decl(),
+        vibe_kanban::models::task_attempt_group::AttemptGroupComparison::decl(),
+        vibe_kanban::models::task_attempt_group::WinnerAction::decl(),
+        vibe_kanban::models::task_attempt_group::PickWinnerRequest::decl(),
         vibe_kanban::executor::NormalizedConversation::decl(),
         vibe_kanban::executor::NormalizedEntry::decl(),
         vibe_kanban::executor::NormalizedEntryType::decl(),
@@ -3,7 +3,20 @@ use std::str::FromStr;
 use rmcp::{transport::stdio, ServiceExt};
 use sqlx::{sqlite::SqliteConnectOptions, SqlitePool};
 use tracing_subscriber::{prelude::*, EnvFilter};
-use vibe_kanban::{mcp::task_server::TaskServer, sentry_layer, utils::asset_dir};
+use uuid::Uuid;
+use vibe_kanban::{
+    mcp::{permission_server::PermissionServer, task_server::TaskServer},
+    sentry_layer,
+    utils::asset_dir,
+};
+
+/// Value following `flag` on the command line
+fn arg_value(args: &[String], flag: &str) -> Option<String> {
+    args.iter()
+        .position(|arg| arg == flag)
+        .and_then(|index| args.get(index + 1))
+        .cloned()
+}
 
 fn main() -> anyhow::Result<()> {
     let environment = if cfg!(debug_assertions) {
@@ -33,8 +46,6 @@ fn main() -> anyhow::Result<()> {
                 .with(sentry_layer())
                 .init();
 
-            tracing::debug!("[MCP] Starting MCP task server...");
-
             // Database connection
             let database_url = format!(
                 "sqlite://{}",
@@ -44,6 +55,29 @@ fn main() -> anyhow::Result<()> {
             let options = SqliteConnectOptions::from_str(&database_url)?.create_if_missing(false);
             let pool = SqlitePool::connect_with(options).await?;
 
+            // `--permissions` serves the tool permission gateway for one agent worktree
+            let args: Vec<String> = std::env::args().collect();
+            if args.iter().any(|arg| arg == "--permissions") {
+                let project_id = arg_value(&args, "--project-id")
+                    .ok_or_else(|| anyhow::anyhow!("--project-id is required"))?;
+                let project_id = Uuid::parse_str(&project_id)?;
+                let worktree_path = arg_value(&args, "--worktree")
+                    .ok_or_else(|| anyhow::anyhow!("--worktree is required"))?;
+
+                tracing::debug!("[MCP] Starting MCP permission server...");
+                let service = PermissionServer::new(pool, project_id, worktree_path)
+                    .serve(stdio())
+                    .await
+                    .inspect_err(|e| {
+                        tracing::error!("serving error: {:?}", e);
+                        sentry::capture_error(e);
+                    })?;
+
+                service.waiting().await?;
+                return Ok(());
+            }
+
+            tracing::debug!("[MCP] Starting MCP task server...");
             let service = TaskServer::new(pool)
                 .serve(stdio())
                 .await
@@ -1,14 +1,64 @@
+use std::{future::Future, sync::Arc, time::Duration};
+
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use tokio::io::AsyncRead;
+use ts_rs::TS;
 
-use crate::models::Environment;
+use crate::models::{config::ResourceLimits, Environment};
 
 mod local;
 mod remote;
+mod resources;
+mod runner_auth;
+mod sandbox;
+mod workspace;
+
+pub use local::{LocalCommandExecutor, LocalProcessHandle};
+pub use remote::{RemoteCommandExecutor, RunnerClient};
+pub use resources::{ResourceGroup, ResourceUsage};
+// The allowlist is only used by the cloud runner binary
+#[allow(unused_imports)]
+pub use runner_auth::{RunnerAllowlist, RunnerAuth};
+pub use sandbox::{SandboxPolicy, SandboxedCommandExecutor};
+// The runner side of workspaces is only used by the cloud runner binary
+#[allow(unused_imports)]
+pub use workspace::{RemoteWorkspace, RunnerWorkspaces};
+
+tokio::task_local! {
+    static SANDBOX: Option<SandboxPolicy>;
+    static RESOURCE_LIMITS: ResourceLimits;
+}
+
+/// Run `future` with every local command it starts confined to `policy`
+pub async fn with_sandbox<F: Future>(policy: Option<SandboxPolicy>, future: F) -> F::Output {
+    SANDBOX.scope(policy, future).await
+}
+
+/// The sandbox commands started from the current task are confined to, if any
+pub fn current_sandbox() -> Option<SandboxPolicy> {
+    SANDBOX.try_with(|policy| policy.clone()).ok().flatten()
+}
+
+/// Run `future` with every local command it starts bounded by `limits`
+pub async fn with_resource_limits<F: Future>(limits: ResourceLimits, future: F) -> F::Output {
+    RESOURCE_LIMITS.scope(limits, future).await
+}
 
-pub use local::LocalCommandExecutor;
-pub use remote::RemoteCommandExecutor;
+/// The resource limits commands started from the current task are bounded by
+pub fn current_resource_limits() -> ResourceLimits {
+    RESOURCE_LIMITS
+        .try_with(|limits| *limits)
+        .unwrap_or_default()
+}
+
+/// Whether commands run locally or on a remote runner, from `ENVIRONMENT`
+pub fn current_environment() -> Environment {
+    std::env::var("ENVIRONMENT")
+        .ok()
+        .and_then(|env| env.parse().ok())
+        .unwrap_or(Environment::Local)
+}
 
 // Core trait that defines the interface for command execution
 #[async_trait]
@@ -29,8 +79,14 @@ pub trait ProcessHandle: Send + Sync {
     /// Wait for the process to complete and return exit status
     async fn wait(&mut self) -> Result<CommandExitStatus, CommandError>;
 
-    /// Kill the process
-    async fn kill(&mut self) -> Result<(), CommandError>;
+    /// Stop the process, sending SIGINT, then SIGTERM and finally SIGKILL, waiting
+    /// `grace_period` for it to exit after each of the first two
+    async fn stop(&mut self, grace_period: Duration) -> Result<StopOutcome, CommandError>;
+
+    /// Kill the process without giving it a chance to shut down
+    async fn kill(&mut self) -> Result<(), CommandError> {
+        self.stop(Duration::ZERO).await.map(|_| ())
+    }
 
     /// Get streams for stdout and stderr
     async fn stream(&mut self) -> Result<CommandStream, CommandError>;
@@ -38,12 +94,71 @@ pub trait ProcessHandle: Send + Sync {
     /// Get process identifier (for debugging/logging)
     fn process_id(&self) -> String;
 
+    /// Write to the process's stdin while it runs
+    async fn write_stdin(&mut self, _data: &str) -> Result<(), CommandError> {
+        Err(CommandError::StdinUnavailable)
+    }
+
+    /// Close stdin kept open for `write_stdin`, so a process reading it to the end can exit
+    async fn close_stdin(&mut self) -> Result<(), CommandError> {
+        Ok(())
+    }
+
+    /// The most memory and CPU time the process and its descendants have used so far
+    fn resource_usage(&mut self) -> Option<ResourceUsage> {
+        None
+    }
+
+    /// Bring what the finished process changed outside the local worktree back into it
+    ///
+    /// A no-op for processes that run in the worktree itself. Kept separate from `try_wait` so
+    /// the transfer happens outside the running-executions lock and can be retried.
+    async fn sync_workspace(&mut self) -> Result<(), CommandError> {
+        Ok(())
+    }
+
     /// Check current status (alias for try_wait for backward compatibility)
     async fn status(&mut self) -> Result<Option<CommandExitStatus>, CommandError> {
         self.try_wait().await
     }
 }
 
+/// How long a stopping process gets to exit after each signal, unless configured otherwise
+pub const DEFAULT_STOP_GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+/// A signal sent to stop a process, in the order they are escalated through
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "lowercase")]
+pub enum StopSignal {
+    Interrupt,
+    Terminate,
+    Kill,
+}
+
+impl StopSignal {
+    /// The signals to send in turn, skipping straight to SIGKILL without a grace period
+    pub fn escalation(grace_period: Duration) -> &'static [StopSignal] {
+        if grace_period.is_zero() {
+            &[StopSignal::Kill]
+        } else {
+            &[
+                StopSignal::Interrupt,
+                StopSignal::Terminate,
+                StopSignal::Kill,
+            ]
+        }
+    }
+}
+
+/// How a stopped process ended: the signal it exited after, or `None` if it had already
+/// exited before any was sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct StopOutcome {
+    pub signal: Option<StopSignal>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CommandRunnerArgs {
     pub command: String,
@@ -51,6 +166,8 @@ pub struct CommandRunnerArgs {
     pub working_dir: Option<String>,
     pub env_vars: Vec<(String, String)>,
     pub stdin: Option<String>,
+    #[serde(default)]
+    pub keep_stdin_open: bool,
 }
 
 pub struct CommandRunner {
@@ -60,6 +177,8 @@ pub struct CommandRunner {
     working_dir: Option<String>,
     env_vars: Vec<(String, String)>,
     stdin: Option<String>,
+    keep_stdin_open: bool,
+    temp_files: Vec<Arc<tempfile::TempPath>>,
 }
 impl Default for CommandRunner {
     fn default() -> Self {
@@ -69,6 +188,8 @@ impl Default for CommandRunner {
 
 pub struct CommandProcess {
     handle: Box<dyn ProcessHandle>,
+    // Files the process reads, deleted once it is dropped
+    _temp_files: Vec<Arc<tempfile::TempPath>>,
 }
 
 impl std::fmt::Debug for CommandProcess {
@@ -93,6 +214,15 @@ pub enum CommandError {
     },
     ProcessNotStarted,
     NoCommandSet,
+    StdinUnavailable,
+    /// The runner rejected the request's credentials, or none were sent
+    Unauthenticated {
+        message: String,
+    },
+    /// The runner does not allow the command or its working directory
+    NotPermitted {
+        message: String,
+    },
     IoError {
         error: std::io::Error,
     },
@@ -120,6 +250,15 @@ impl std::fmt::Display for CommandError {
             CommandError::NoCommandSet => {
                 write!(f, "No command has been set")
             }
+            CommandError::StdinUnavailable => {
+                write!(f, "Process does not accept input")
+            }
+            CommandError::Unauthenticated { message } => {
+                write!(f, "Runner authentication failed: {}", message)
+            }
+            CommandError::NotPermitted { message } => {
+                write!(f, "Runner refused the command: {}", message)
+            }
             CommandError::IoError { error } => {
                 write!(f, "Failed to spawn command: {}", error)
             }
@@ -154,6 +293,19 @@ impl CommandExitStatus {
     pub fn code(&self) -> Option<i32> {
         self.code
     }
+
+    /// Create a CommandExitStatus for a process whose outcome was decided by its executor
+    /// rather than by the process itself (e.g. an agent stopped after its turn completed)
+    pub fn from_outcome(code: Option<i32>, success: bool) -> Self {
+        Self {
+            code,
+            success,
+            #[cfg(unix)]
+            signal: None,
+            remote_process_id: None,
+            remote_session_id: None,
+        }
+    }
 }
 
 pub struct CommandStream {
@@ -163,9 +315,7 @@ pub struct CommandStream {
 
 impl CommandRunner {
     pub fn new() -> Self {
-        let env = std::env::var("ENVIRONMENT").unwrap_or_else(|_| "local".to_string());
-        let mode = env.parse().unwrap_or(Environment::Local);
-        match mode {
+        match current_environment() {
             Environment::Cloud => CommandRunner {
                 executor: Box::new(RemoteCommandExecutor::new()),
                 command: None,
@@ -173,14 +323,21 @@ impl CommandRunner {
                 working_dir: None,
                 env_vars: Vec::new(),
                 stdin: None,
+                keep_stdin_open: false,
+                temp_files: Vec::new(),
             },
             Environment::Local => CommandRunner {
-                executor: Box::new(LocalCommandExecutor::new()),
+                executor: match current_sandbox() {
+                    Some(policy) => Box::new(SandboxedCommandExecutor::new(policy)),
+                    None => Box::new(LocalCommandExecutor::new()),
+                },
                 command: None,
                 args: Vec::new(),
                 working_dir: None,
                 env_vars: Vec::new(),
                 stdin: None,
+                keep_stdin_open: false,
+                temp_files: Vec::new(),
             },
         }
     }
@@ -212,6 +369,13 @@ impl CommandRunner {
         self
     }
 
+    /// Keep stdin open after writing the initial input, so more can be written while the
+    /// process runs
+    pub fn keep_stdin_open(&mut self) -> &mut Self {
+        self.keep_stdin_open = true;
+        self
+    }
+
     pub fn working_dir(&mut self, dir: &str) -> &mut Self {
         self.working_dir = Some(dir.to_string());
         self
@@ -222,6 +386,12 @@ impl CommandRunner {
         self
     }
 
+    /// Keep `path` until the started process is dropped, then delete it
+    pub fn temp_file(&mut self, path: tempfile::TempPath) -> &mut Self {
+        self.temp_files.push(Arc::new(path));
+        self
+    }
+
     /// Convert the current CommandRunner state to a CreateCommandRequest
     pub fn to_args(&self) -> Option<CommandRunnerArgs> {
         Some(CommandRunnerArgs {
@@ -230,6 +400,7 @@ impl CommandRunner {
             working_dir: self.working_dir.clone(),
             env_vars: self.env_vars.clone(),
             stdin: self.stdin.clone(),
+            keep_stdin_open: self.keep_stdin_open,
         })
     }
 
@@ -255,6 +426,10 @@ impl CommandRunner {
             runner.stdin(stdin);
         }
 
+        if request.keep_stdin_open {
+            runner.keep_stdin_open();
+        }
+
         runner
     }
 
@@ -262,11 +437,22 @@ impl CommandRunner {
         let request = self.to_args().ok_or(CommandError::NoCommandSet)?;
         let handle = self.executor.start(&request).await?;
 
-        Ok(CommandProcess { handle })
+        Ok(CommandProcess {
+            handle,
+            _temp_files: self.temp_files.clone(),
+        })
     }
 }
 
 impl CommandProcess {
+    /// Wrap a handle created outside of a CommandExecutor
+    pub fn from_handle(handle: Box<dyn ProcessHandle>) -> Self {
+        Self {
+            handle,
+            _temp_files: Vec::new(),
+        }
+    }
+
     #[allow(dead_code)]
     pub async fn status(&mut self) -> Result<Option<CommandExitStatus>, CommandError> {
         self.handle.status().await
@@ -280,10 +466,39 @@ impl CommandProcess {
         self.handle.kill().await
     }
 
+    pub async fn stop(&mut self, grace_period: Duration) -> Result<StopOutcome, CommandError> {
+        self.handle.stop(grace_period).await
+    }
+
     pub async fn stream(&mut self) -> Result<CommandStream, CommandError> {
         self.handle.stream().await
     }
 
+    pub async fn write_stdin(&mut self, data: &str) -> Result<(), CommandError> {
+        self.handle.write_stdin(data).await
+    }
+
+    pub async fn close_stdin(&mut self) -> Result<(), CommandError> {
+        self.handle.close_stdin().await
+    }
+
+    /// Replace the handle with one wrapping it, keeping the files the process reads
+    pub fn map_handle(
+        mut self,
+        wrap: impl FnOnce(Box<dyn ProcessHandle>) -> Box<dyn ProcessHandle>,
+    ) -> Self {
+        self.handle = wrap(self.handle);
+        self
+    }
+
+    pub fn resource_usage(&mut self) -> Option<ResourceUsage> {
+        self.handle.resource_usage()
+    }
+
+    pub async fn sync_workspace(&mut self) -> Result<(), CommandError> {
+        self.handle.sync_workspace().await
+    }
+
     #[allow(dead_code)]
     pub async fn wait(&mut self) -> Result<CommandExitStatus, CommandError> {
         self.handle.wait().await
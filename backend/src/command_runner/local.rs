@@ -7,11 +7,14 @@ use nix::{
     sys::signal::{killpg, Signal},
     unistd::{getpgid, Pid},
 };
-use tokio::process::Command;
+use tokio::{
+    io::AsyncWriteExt,
+    process::{ChildStdin, Command},
+};
 
 use crate::command_runner::{
-    CommandError, CommandExecutor, CommandExitStatus, CommandRunnerArgs, CommandStream,
-    ProcessHandle,
+    current_resource_limits, CommandError, CommandExecutor, CommandExitStatus, CommandRunnerArgs,
+    CommandStream, ProcessHandle, ResourceGroup, ResourceUsage, StopOutcome, StopSignal,
 };
 
 pub struct LocalCommandExecutor;
@@ -34,58 +37,102 @@ impl CommandExecutor for LocalCommandExecutor {
         &self,
         request: &CommandRunnerArgs,
     ) -> Result<Box<dyn ProcessHandle>, CommandError> {
-        let mut cmd = Command::new(&request.command);
+        let mut cmd = build_command(request);
+        let resources = bound_command(&mut cmd, request)?;
+        Ok(Box::new(spawn_command(&mut cmd, request, resources).await?))
+    }
+}
 
-        cmd.args(&request.args)
-            .kill_on_drop(true)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped());
+/// The command `request` describes, with piped stdio
+pub(super) fn build_command(request: &CommandRunnerArgs) -> Command {
+    let mut cmd = Command::new(&request.command);
 
-        if let Some(dir) = &request.working_dir {
-            cmd.current_dir(dir);
-        }
+    cmd.args(&request.args)
+        .kill_on_drop(true)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
 
-        for (key, val) in &request.env_vars {
-            cmd.env(key, val);
-        }
+    if let Some(dir) = &request.working_dir {
+        cmd.current_dir(dir);
+    }
 
-        let mut child = cmd.group_spawn().map_err(|e| CommandError::SpawnFailed {
-            command: format!("{} {}", request.command, request.args.join(" ")),
-            error: e,
-        })?;
+    for (key, val) in &request.env_vars {
+        cmd.env(key, val);
+    }
 
-        if let Some(prompt) = &request.stdin {
-            // Write prompt to stdin safely
-            if let Some(mut stdin) = child.inner().stdin.take() {
-                use tokio::io::AsyncWriteExt;
-                stdin.write_all(prompt.as_bytes()).await?;
+    cmd
+}
+
+/// Bound `cmd` by the current task's resource limits
+pub(super) fn bound_command(
+    cmd: &mut Command,
+    request: &CommandRunnerArgs,
+) -> Result<ResourceGroup, CommandError> {
+    ResourceGroup::confine(&current_resource_limits(), cmd).map_err(|e| CommandError::SpawnFailed {
+        command: format!("{} {}", request.command, request.args.join(" ")),
+        error: e,
+    })
+}
+
+/// Spawn `cmd` in its own process group and hand it the request's stdin
+pub(super) async fn spawn_command(
+    cmd: &mut Command,
+    request: &CommandRunnerArgs,
+    resources: ResourceGroup,
+) -> Result<LocalProcessHandle, CommandError> {
+    let mut child = cmd.group_spawn().map_err(|e| CommandError::SpawnFailed {
+        command: format!("{} {}", request.command, request.args.join(" ")),
+        error: e,
+    })?;
+
+    if let Some(prompt) = &request.stdin {
+        // Write prompt to stdin safely
+        if let Some(mut stdin) = child.inner().stdin.take() {
+            stdin.write_all(prompt.as_bytes()).await?;
+            if request.keep_stdin_open {
+                stdin.flush().await?;
+                child.inner().stdin = Some(stdin);
+            } else {
                 stdin.shutdown().await?;
             }
         }
-
-        Ok(Box::new(LocalProcessHandle::new(child)))
     }
+
+    Ok(LocalProcessHandle::new(child).with_resources(resources))
 }
 
 pub struct LocalProcessHandle {
     child: Option<AsyncGroupChild>,
+    stdin: Option<ChildStdin>,
     process_id: String,
+    pid: Option<u32>,
+    resources: ResourceGroup,
 }
 
 impl LocalProcessHandle {
     pub fn new(mut child: AsyncGroupChild) -> Self {
-        let process_id = child
-            .inner()
-            .id()
+        let pid = child.inner().id();
+        let process_id = pid
             .map(|id| id.to_string())
             .unwrap_or_else(|| "unknown".to_string());
 
+        let stdin = child.inner().stdin.take();
+
         Self {
             child: Some(child),
+            stdin,
             process_id,
+            pid,
+            resources: ResourceGroup::default(),
         }
     }
+
+    /// Enforce limits and measure usage through `resources`, which the child was spawned in
+    pub fn with_resources(mut self, resources: ResourceGroup) -> Self {
+        self.resources = resources;
+        self
+    }
 }
 
 #[async_trait]
@@ -117,57 +164,49 @@ impl ProcessHandle for LocalProcessHandle {
         }
     }
 
-    async fn kill(&mut self) -> Result<(), CommandError> {
-        match &mut self.child {
-            Some(child) => {
-                // hit the whole process group, not just the leader
-                #[cfg(unix)]
-                {
-                    if let Some(pid) = child.inner().id() {
-                        let pgid = getpgid(Some(Pid::from_raw(pid as i32))).map_err(|e| {
-                            CommandError::KillFailed {
-                                error: std::io::Error::other(e),
-                            }
-                        })?;
-
-                        for sig in [Signal::SIGINT, Signal::SIGTERM, Signal::SIGKILL] {
-                            if let Err(e) = killpg(pgid, sig) {
-                                tracing::warn!(
-                                    "Failed to send signal {:?} to process group {}: {}",
-                                    sig,
-                                    pgid,
-                                    e
-                                );
-                            }
-                            tokio::time::sleep(Duration::from_secs(2)).await;
-                            if child
-                                .inner()
-                                .try_wait()
-                                .map_err(|e| CommandError::StatusCheckFailed { error: e })?
-                                .is_some()
-                            {
-                                break; // gone!
-                            }
-                        }
-                    }
-                }
+    async fn stop(&mut self, grace_period: Duration) -> Result<StopOutcome, CommandError> {
+        let Some(child) = &mut self.child else {
+            return Err(CommandError::ProcessNotStarted);
+        };
 
-                // final fallback – command_group already targets the group
-                child
-                    .kill()
-                    .await
-                    .map_err(|e| CommandError::KillFailed { error: e })?;
-                child
-                    .wait()
-                    .await
-                    .map_err(|e| CommandError::KillFailed { error: e })?; // reap
+        let mut outcome = StopOutcome { signal: None };
+        if child
+            .inner()
+            .try_wait()
+            .map_err(|e| CommandError::StatusCheckFailed { error: e })?
+            .is_none()
+        {
+            for &signal in StopSignal::escalation(grace_period) {
+                outcome.signal = Some(signal);
+                if signal == StopSignal::Kill {
+                    // command_group already targets the whole group
+                    child
+                        .kill()
+                        .await
+                        .map_err(|e| CommandError::KillFailed { error: e })?;
+                    break;
+                }
 
-                // Clear the handle after successful kill
-                self.child = None;
-                Ok(())
+                signal_group(child, signal);
+                if wait_for_exit(child, grace_period).await? {
+                    break; // gone!
+                }
             }
-            None => Err(CommandError::ProcessNotStarted),
         }
+
+        child
+            .wait()
+            .await
+            .map_err(|e| CommandError::KillFailed { error: e })?; // reap
+
+        // The leader exiting doesn't take backgrounded children with it, so
+        // sweep whatever is left of the group. The child is kept so status()
+        // still reports how it exited.
+        if let Some(pid) = self.pid {
+            kill_group(pid);
+        }
+        self.stdin = None;
+        Ok(outcome)
     }
 
     async fn stream(&mut self) -> Result<CommandStream, CommandError> {
@@ -184,6 +223,90 @@ impl ProcessHandle for LocalProcessHandle {
     fn process_id(&self) -> String {
         self.process_id.clone()
     }
+
+    async fn write_stdin(&mut self, data: &str) -> Result<(), CommandError> {
+        let stdin = self.stdin.as_mut().ok_or(CommandError::StdinUnavailable)?;
+        stdin.write_all(data.as_bytes()).await?;
+        stdin.flush().await?;
+        Ok(())
+    }
+
+    async fn close_stdin(&mut self) -> Result<(), CommandError> {
+        if let Some(mut stdin) = self.stdin.take() {
+            stdin.shutdown().await?;
+        }
+        Ok(())
+    }
+
+    fn resource_usage(&mut self) -> Option<ResourceUsage> {
+        self.resources.sample(self.pid)
+    }
+}
+
+/// Send `signal` to the whole process group `child` leads, not just the leader
+fn signal_group(child: &mut AsyncGroupChild, signal: StopSignal) {
+    #[cfg(unix)]
+    {
+        let Some(pid) = child.inner().id() else {
+            return;
+        };
+        let sig = match signal {
+            StopSignal::Interrupt => Signal::SIGINT,
+            StopSignal::Terminate => Signal::SIGTERM,
+            StopSignal::Kill => Signal::SIGKILL,
+        };
+        match getpgid(Some(Pid::from_raw(pid as i32))) {
+            Ok(pgid) => {
+                if let Err(e) = killpg(pgid, sig) {
+                    tracing::warn!(
+                        "Failed to send signal {:?} to process group {}: {}",
+                        sig,
+                        pgid,
+                        e
+                    );
+                }
+            }
+            Err(e) => tracing::warn!("Failed to find process group of {}: {}", pid, e),
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = (child, signal);
+}
+
+/// SIGKILL every process left in the group led by `pid`, which may already be reaped
+fn kill_group(pid: u32) {
+    #[cfg(unix)]
+    {
+        // command_group spawns the leader with its own pid as the group id
+        match killpg(Pid::from_raw(pid as i32), Signal::SIGKILL) {
+            Ok(()) | Err(nix::errno::Errno::ESRCH) => {}
+            Err(e) => tracing::warn!("Failed to kill process group {}: {}", pid, e),
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = pid;
+}
+
+/// Whether `child` exits within `timeout`
+async fn wait_for_exit(
+    child: &mut AsyncGroupChild,
+    timeout: Duration,
+) -> Result<bool, CommandError> {
+    let deadline = tokio::time::Instant::now() + timeout;
+    loop {
+        if child
+            .inner()
+            .try_wait()
+            .map_err(|e| CommandError::StatusCheckFailed { error: e })?
+            .is_some()
+        {
+            return Ok(true);
+        }
+        if tokio::time::Instant::now() >= deadline {
+            return Ok(false);
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
 }
 
 // Local-specific implementations for shared types
@@ -219,11 +342,11 @@ impl CommandStream {
 
 #[cfg(test)]
 mod tests {
-    use std::process::Stdio;
+    use std::{process::Stdio, time::Duration};
 
     use command_group::{AsyncCommandGroup, AsyncGroupChild};
     use tokio::{
-        io::{AsyncReadExt, AsyncWriteExt},
+        io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
         process::Command,
     };
 
@@ -356,6 +479,60 @@ mod tests {
         assert_eq!(runner_output, test_input);
     }
 
+    #[tokio::test]
+    async fn test_write_stdin_while_running() {
+        std::env::set_var("ENVIRONMENT", "local");
+
+        let mut runner = CommandRunner::new();
+        let mut process = runner
+            .command("cat")
+            .stdin("first\n")
+            .keep_stdin_open()
+            .start()
+            .await
+            .expect("CommandRunner should start cat command");
+
+        let mut stream = process.stream().await.expect("Should get stream");
+        let mut stdout = BufReader::new(stream.stdout.take().expect("Should have stdout"));
+        let mut line = String::new();
+        stdout
+            .read_line(&mut line)
+            .await
+            .expect("Should read stdout");
+        assert_eq!(line, "first\n");
+
+        // cat is still waiting on stdin, so it echoes what we write next
+        process
+            .write_stdin("second\n")
+            .await
+            .expect("Should write to stdin");
+        line.clear();
+        stdout
+            .read_line(&mut line)
+            .await
+            .expect("Should read stdout");
+        assert_eq!(line, "second\n");
+    }
+
+    #[tokio::test]
+    async fn test_write_stdin_after_prompt_is_rejected() {
+        std::env::set_var("ENVIRONMENT", "local");
+
+        let mut runner = CommandRunner::new();
+        let mut process = runner
+            .command("cat")
+            .stdin("prompt")
+            .start()
+            .await
+            .expect("CommandRunner should start cat command");
+
+        assert!(matches!(
+            process.write_stdin("more").await,
+            Err(CommandError::StdinUnavailable)
+        ));
+        process.wait().await.expect("cat should exit on EOF");
+    }
+
     #[tokio::test]
     async fn test_working_directory() {
         // Use pwd command to check working directory
@@ -568,6 +745,117 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_stop_escalates_until_process_exits() {
+        std::env::set_var("ENVIRONMENT", "local");
+
+        // sleep exits on SIGINT
+        let mut runner = CommandRunner::new();
+        let mut process = runner
+            .command("sleep")
+            .arg("60")
+            .start()
+            .await
+            .expect("Should start sleep");
+        let outcome = process
+            .stop(Duration::from_secs(2))
+            .await
+            .expect("Should stop sleep");
+        assert_eq!(outcome.signal, Some(StopSignal::Interrupt));
+
+        // Ignoring SIGINT falls through to SIGTERM
+        let mut runner = CommandRunner::new();
+        let mut process = runner
+            .command("sh")
+            .arg("-c")
+            .arg("trap '' INT; sleep 60")
+            .start()
+            .await
+            .expect("Should start sh");
+        tokio::time::sleep(Duration::from_millis(200)).await; // let the trap install
+        let outcome = process
+            .stop(Duration::from_millis(500))
+            .await
+            .expect("Should stop sh");
+        assert_eq!(outcome.signal, Some(StopSignal::Terminate));
+
+        // Without a grace period it is killed outright
+        let mut runner = CommandRunner::new();
+        let mut process = runner
+            .command("sleep")
+            .arg("60")
+            .start()
+            .await
+            .expect("Should start sleep");
+        let outcome = process
+            .stop(Duration::ZERO)
+            .await
+            .expect("Should kill sleep");
+        assert_eq!(outcome.signal, Some(StopSignal::Kill));
+    }
+
+    #[tokio::test]
+    async fn test_stop_kills_backgrounded_children() {
+        std::env::set_var("ENVIRONMENT", "local");
+
+        let pid_file = tempfile::NamedTempFile::new().unwrap();
+        let script = format!(
+            "sh -c \"trap '' INT; exec sleep 60\" & echo $! > {}; wait",
+            pid_file.path().display()
+        );
+        let mut runner = CommandRunner::new();
+        let mut process = runner
+            .command("sh")
+            .arg("-c")
+            .arg(&script)
+            .start()
+            .await
+            .expect("Should start sh");
+        tokio::time::sleep(Duration::from_millis(300)).await; // let the child start
+
+        // The leader exits on SIGINT, the backgrounded child ignores it
+        let outcome = process
+            .stop(Duration::from_secs(2))
+            .await
+            .expect("Should stop sh");
+        assert_eq!(outcome.signal, Some(StopSignal::Interrupt));
+        assert!(process.status().await.unwrap().is_some());
+
+        let child: i32 = std::fs::read_to_string(pid_file.path())
+            .unwrap()
+            .trim()
+            .parse()
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        // Gone, or a zombie waiting on whoever it was reparented to
+        let alive = std::fs::read_to_string(format!("/proc/{child}/stat"))
+            .map(|stat| !stat.contains(") Z "))
+            .unwrap_or(false);
+        assert!(
+            !alive,
+            "Backgrounded child should have been killed with the group"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stop_after_exit_sends_no_signal() {
+        std::env::set_var("ENVIRONMENT", "local");
+
+        let mut runner = CommandRunner::new();
+        let mut process = runner
+            .command("true")
+            .start()
+            .await
+            .expect("Should start true");
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        let outcome = process
+            .stop(Duration::from_secs(1))
+            .await
+            .expect("Should stop true");
+        assert_eq!(outcome.signal, None);
+    }
+
     #[tokio::test]
     async fn test_status_monitoring() {
         // Test status monitoring with a quick command
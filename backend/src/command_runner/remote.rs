@@ -1,18 +1,20 @@
 use std::{
     pin::Pin,
     task::{Context, Poll},
+    time::Duration,
 };
 
 use async_trait::async_trait;
+use reqwest::Method;
 use tokio::io::AsyncRead;
 
 use crate::command_runner::{
     CommandError, CommandExecutor, CommandExitStatus, CommandRunnerArgs, CommandStream,
-    ProcessHandle,
+    ProcessHandle, RemoteWorkspace, RunnerAuth, StopOutcome,
 };
 
 pub struct RemoteCommandExecutor {
-    cloud_server_url: String,
+    client: Result<RunnerClient, String>,
 }
 
 impl Default for RemoteCommandExecutor {
@@ -23,9 +25,11 @@ impl Default for RemoteCommandExecutor {
 
 impl RemoteCommandExecutor {
     pub fn new() -> Self {
-        let cloud_server_url = std::env::var("CLOUD_SERVER_URL")
-            .unwrap_or_else(|_| "http://localhost:8000".to_string());
-        Self { cloud_server_url }
+        let client = RunnerClient::from_env().map_err(|e| {
+            tracing::error!("Invalid cloud runner client configuration: {}", e);
+            e.to_string()
+        });
+        Self { client }
     }
 }
 
@@ -35,15 +39,31 @@ impl CommandExecutor for RemoteCommandExecutor {
         &self,
         request: &CommandRunnerArgs,
     ) -> Result<Box<dyn ProcessHandle>, CommandError> {
-        let client = reqwest::Client::new();
+        let client = self.client.as_ref().map_err(|e| CommandError::IoError {
+            error: std::io::Error::other(e.clone()),
+        })?;
+
+        // Commands in a worktree run in the runner's copy of it
+        let workspace = match &request.working_dir {
+            Some(dir) => RemoteWorkspace::upload(client, dir).await?,
+            None => None,
+        };
+        let mut request = request.clone();
+        if let Some(workspace) = &workspace {
+            request.working_dir = Some(workspace.remote_path().to_string());
+        }
+
+        let body = serde_json::to_vec(&request).map_err(|e| CommandError::IoError {
+            error: std::io::Error::other(e),
+        })?;
         let response = client
-            .post(format!("{}/commands", self.cloud_server_url))
-            .json(request)
-            .send()
-            .await
-            .map_err(|e| CommandError::IoError {
-                error: std::io::Error::other(e),
-            })?;
+            .send(
+                Method::POST,
+                "/commands",
+                Some(("application/json", body)),
+                |error| CommandError::IoError { error },
+            )
+            .await?;
 
         let result: serde_json::Value =
             response.json().await.map_err(|e| CommandError::IoError {
@@ -60,42 +80,166 @@ impl CommandExecutor for RemoteCommandExecutor {
                     )),
                 })?;
 
-        Ok(Box::new(RemoteProcessHandle::new(
-            process_id.to_string(),
-            self.cloud_server_url.clone(),
-        )))
+        Ok(Box::new(
+            RemoteProcessHandle::new(process_id.to_string(), client.clone())
+                .with_workspace(workspace),
+        ))
+    }
+}
+
+/// HTTP client for a `cloud_runner`, authenticating every request it sends
+#[derive(Debug, Clone)]
+pub struct RunnerClient {
+    base_url: String,
+    http: reqwest::Client,
+    auth: Option<RunnerAuth>,
+}
+
+impl RunnerClient {
+    /// A client for `CLOUD_SERVER_URL` using the runner credentials in the environment
+    ///
+    /// `CLOUD_RUNNER_CA_CERT` names a PEM certificate to trust for the runner, and
+    /// `CLOUD_RUNNER_CLIENT_CERT` with `CLOUD_RUNNER_CLIENT_KEY` (PKCS#8) name the client
+    /// certificate to present when the runner requires one.
+    pub fn from_env() -> Result<Self, CommandError> {
+        let base_url = std::env::var("CLOUD_SERVER_URL")
+            .unwrap_or_else(|_| "http://localhost:8000".to_string());
+        let read = |var: &str| -> Result<Option<Vec<u8>>, CommandError> {
+            std::env::var(var)
+                .ok()
+                .map(|path| {
+                    std::fs::read(&path).map_err(|e| CommandError::IoError {
+                        error: std::io::Error::new(
+                            e.kind(),
+                            format!("Failed to read {} '{}': {}", var, path, e),
+                        ),
+                    })
+                })
+                .transpose()
+        };
+        let tls_error = |e: reqwest::Error| CommandError::IoError {
+            error: std::io::Error::other(e),
+        };
+
+        let mut builder = reqwest::Client::builder();
+        if let Some(ca) = read("CLOUD_RUNNER_CA_CERT")? {
+            builder = builder
+                .add_root_certificate(reqwest::Certificate::from_pem(&ca).map_err(tls_error)?);
+        }
+        match (
+            read("CLOUD_RUNNER_CLIENT_CERT")?,
+            read("CLOUD_RUNNER_CLIENT_KEY")?,
+        ) {
+            (Some(cert), Some(key)) => {
+                builder = builder
+                    .identity(reqwest::Identity::from_pkcs8_pem(&cert, &key).map_err(tls_error)?);
+            }
+            (None, None) => {}
+            _ => {
+                return Err(CommandError::IoError {
+                    error: std::io::Error::other(
+                        "CLOUD_RUNNER_CLIENT_CERT and CLOUD_RUNNER_CLIENT_KEY must be set together",
+                    ),
+                })
+            }
+        }
+
+        let auth = RunnerAuth::from_env();
+        if auth.is_none() {
+            tracing::warn!(
+                "No CLOUD_RUNNER_HMAC_SECRET or CLOUD_RUNNER_TOKEN set; runner requests are unauthenticated"
+            );
+        }
+
+        Ok(Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            http: builder.build().map_err(tls_error)?,
+            auth,
+        })
+    }
+
+    /// Send a signed request for `path_and_query` with an optional `(content type, body)`,
+    /// turning transport failures into `error` and authentication or allowlist rejections into
+    /// their own variants
+    pub async fn send(
+        &self,
+        method: Method,
+        path_and_query: &str,
+        body: Option<(&'static str, Vec<u8>)>,
+        error: impl FnOnce(std::io::Error) -> CommandError,
+    ) -> Result<reqwest::Response, CommandError> {
+        let (content_type, body) = body.unwrap_or_default();
+        let mut request = self.http.request(
+            method.clone(),
+            format!("{}{}", self.base_url, path_and_query),
+        );
+        if let Some(auth) = &self.auth {
+            for (name, value) in auth.sign(method.as_str(), path_and_query, &body) {
+                request = request.header(name, value);
+            }
+        }
+        if !body.is_empty() {
+            request = request.header("content-type", content_type).body(body);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| error(std::io::Error::other(e)))?;
+
+        let rejected: fn(String) -> CommandError = match response.status() {
+            reqwest::StatusCode::UNAUTHORIZED => {
+                |message| CommandError::Unauthenticated { message }
+            }
+            reqwest::StatusCode::FORBIDDEN => |message| CommandError::NotPermitted { message },
+            _ => return Ok(response),
+        };
+        let status = response.status();
+        let message = response
+            .json::<serde_json::Value>()
+            .await
+            .ok()
+            .and_then(|body| body["error"].as_str().map(str::to_string))
+            .unwrap_or_else(|| status.to_string());
+        Err(rejected(message))
     }
 }
 
 pub struct RemoteProcessHandle {
     process_id: String,
-    cloud_server_url: String,
+    client: RunnerClient,
+    workspace: Option<RemoteWorkspace>,
 }
 
 impl RemoteProcessHandle {
-    pub fn new(process_id: String, cloud_server_url: String) -> Self {
+    pub fn new(process_id: String, client: RunnerClient) -> Self {
         Self {
             process_id,
-            cloud_server_url,
+            client,
+            workspace: None,
         }
     }
+
+    /// Fetch the commits made in `workspace` back once the process ends
+    pub fn with_workspace(mut self, workspace: Option<RemoteWorkspace>) -> Self {
+        self.workspace = workspace;
+        self
+    }
 }
 
 #[async_trait]
 impl ProcessHandle for RemoteProcessHandle {
     async fn try_wait(&mut self) -> Result<Option<CommandExitStatus>, CommandError> {
         // Make HTTP request to get status from cloud server
-        let client = reqwest::Client::new();
-        let response = client
-            .get(format!(
-                "{}/commands/{}/status",
-                self.cloud_server_url, self.process_id
-            ))
-            .send()
-            .await
-            .map_err(|e| CommandError::StatusCheckFailed {
-                error: std::io::Error::other(e),
-            })?;
+        let response = self
+            .client
+            .send(
+                Method::GET,
+                &format!("/commands/{}/status", self.process_id),
+                None,
+                |error| CommandError::StatusCheckFailed { error },
+            )
+            .await?;
 
         if !response.status().is_success() {
             if response.status() == reqwest::StatusCode::NOT_FOUND {
@@ -144,61 +288,8 @@ impl ProcessHandle for RemoteProcessHandle {
     async fn wait(&mut self) -> Result<CommandExitStatus, CommandError> {
         // Poll the status endpoint until process completes
         loop {
-            let client = reqwest::Client::new();
-            let response = client
-                .get(format!(
-                    "{}/commands/{}/status",
-                    self.cloud_server_url, self.process_id
-                ))
-                .send()
-                .await
-                .map_err(|e| CommandError::StatusCheckFailed {
-                    error: std::io::Error::other(e),
-                })?;
-
-            if !response.status().is_success() {
-                if response.status() == reqwest::StatusCode::NOT_FOUND {
-                    return Err(CommandError::StatusCheckFailed {
-                        error: std::io::Error::new(
-                            std::io::ErrorKind::NotFound,
-                            "Process not found",
-                        ),
-                    });
-                } else {
-                    return Err(CommandError::StatusCheckFailed {
-                        error: std::io::Error::other("Status check failed"),
-                    });
-                }
-            }
-
-            let result: serde_json::Value =
-                response
-                    .json()
-                    .await
-                    .map_err(|e| CommandError::StatusCheckFailed {
-                        error: std::io::Error::other(e),
-                    })?;
-
-            let data =
-                result["data"]
-                    .as_object()
-                    .ok_or_else(|| CommandError::StatusCheckFailed {
-                        error: std::io::Error::other("Invalid response format"),
-                    })?;
-
-            let running = data["running"].as_bool().unwrap_or(false);
-
-            if !running {
-                // Process completed, extract exit status and return
-                let exit_code = data["exit_code"].as_i64().map(|c| c as i32);
-                let success = data["success"].as_bool().unwrap_or(false);
-
-                return Ok(CommandExitStatus::from_remote(
-                    exit_code,
-                    success,
-                    Some(self.process_id.clone()),
-                    None,
-                ));
+            if let Some(status) = self.try_wait().await? {
+                return Ok(status);
             }
 
             // Wait a bit before polling again
@@ -206,23 +297,25 @@ impl ProcessHandle for RemoteProcessHandle {
         }
     }
 
-    async fn kill(&mut self) -> Result<(), CommandError> {
-        let client = reqwest::Client::new();
-        let response = client
-            .delete(format!(
-                "{}/commands/{}",
-                self.cloud_server_url, self.process_id
-            ))
-            .send()
-            .await
-            .map_err(|e| CommandError::KillFailed {
-                error: std::io::Error::other(e),
-            })?;
+    async fn stop(&mut self, grace_period: Duration) -> Result<StopOutcome, CommandError> {
+        let response = self
+            .client
+            .send(
+                Method::DELETE,
+                &format!(
+                    "/commands/{}?grace_period_ms={}",
+                    self.process_id,
+                    grace_period.as_millis()
+                ),
+                None,
+                |error| CommandError::KillFailed { error },
+            )
+            .await?;
 
         if !response.status().is_success() {
             if response.status() == reqwest::StatusCode::NOT_FOUND {
                 // Process not found, might have already finished - treat as success
-                return Ok(());
+                return Ok(StopOutcome { signal: None });
             }
 
             return Err(CommandError::KillFailed {
@@ -233,32 +326,46 @@ impl ProcessHandle for RemoteProcessHandle {
             });
         }
 
-        // Check if server indicates process was already completed
-        if let Ok(result) = response.json::<serde_json::Value>().await {
-            if let Some(data) = result.get("data") {
-                if let Some(message) = data.as_str() {
-                    tracing::info!("Kill result: {}", message);
-                }
+        let result: serde_json::Value =
+            response
+                .json()
+                .await
+                .map_err(|e| CommandError::KillFailed {
+                    error: std::io::Error::other(e),
+                })?;
+        let outcome: StopOutcome = serde_json::from_value(result["data"].clone()).map_err(|e| {
+            CommandError::KillFailed {
+                error: std::io::Error::other(format!("Invalid stop response {}: {}", result, e)),
             }
+        })?;
+        tracing::info!(
+            "Remote process {} stopped by {:?}",
+            self.process_id,
+            outcome.signal
+        );
+
+        // Keep whatever the process got done before it was stopped
+        if let Err(e) = self.sync_workspace().await {
+            tracing::error!(
+                "Failed to fetch commits of stopped remote process {}: {}",
+                self.process_id,
+                e
+            );
         }
 
-        Ok(())
+        Ok(outcome)
     }
 
     async fn stream(&mut self) -> Result<CommandStream, CommandError> {
         // Create HTTP streams for stdout and stderr concurrently
-        let stdout_url = format!(
-            "{}/commands/{}/stdout",
-            self.cloud_server_url, self.process_id
-        );
-        let stderr_url = format!(
-            "{}/commands/{}/stderr",
-            self.cloud_server_url, self.process_id
-        );
+        let stdout_path = format!("/commands/{}/stdout", self.process_id);
+        let stderr_path = format!("/commands/{}/stderr", self.process_id);
 
         // Create both streams concurrently using tokio::try_join!
-        let (stdout_result, stderr_result) =
-            tokio::try_join!(HTTPStream::new(stdout_url), HTTPStream::new(stderr_url))?;
+        let (stdout_result, stderr_result) = tokio::try_join!(
+            HTTPStream::new(&self.client, &stdout_path),
+            HTTPStream::new(&self.client, &stderr_path)
+        )?;
 
         let stdout_stream: Option<Box<dyn AsyncRead + Unpin + Send>> =
             Some(Box::new(stdout_result) as Box<dyn AsyncRead + Unpin + Send>);
@@ -274,6 +381,62 @@ impl ProcessHandle for RemoteProcessHandle {
     fn process_id(&self) -> String {
         self.process_id.clone()
     }
+
+    /// Bring the process's commits back into the local worktree, once
+    ///
+    /// The workspace is kept until a download succeeds, so a failed one can be retried.
+    async fn sync_workspace(&mut self) -> Result<(), CommandError> {
+        if let Some(workspace) = &self.workspace {
+            workspace.download(&self.client).await?;
+            self.workspace = None;
+        }
+        Ok(())
+    }
+
+    async fn write_stdin(&mut self, data: &str) -> Result<(), CommandError> {
+        let response = self
+            .client
+            .send(
+                Method::POST,
+                &format!("/commands/{}/stdin", self.process_id),
+                Some(("text/plain", data.as_bytes().to_vec())),
+                |error| CommandError::IoError { error },
+            )
+            .await?;
+
+        match response.status() {
+            status if status.is_success() => Ok(()),
+            reqwest::StatusCode::CONFLICT => Err(CommandError::StdinUnavailable),
+            status => Err(CommandError::IoError {
+                error: std::io::Error::other(format!(
+                    "Remote stdin write failed with status: {}",
+                    status
+                )),
+            }),
+        }
+    }
+
+    async fn close_stdin(&mut self) -> Result<(), CommandError> {
+        let response = self
+            .client
+            .send(
+                Method::DELETE,
+                &format!("/commands/{}/stdin", self.process_id),
+                None,
+                |error| CommandError::IoError { error },
+            )
+            .await?;
+
+        match response.status() {
+            status if status.is_success() || status == reqwest::StatusCode::NOT_FOUND => Ok(()),
+            status => Err(CommandError::IoError {
+                error: std::io::Error::other(format!(
+                    "Remote stdin close failed with status: {}",
+                    status
+                )),
+            }),
+        }
+    }
 }
 
 /// HTTP-based AsyncRead wrapper for true streaming
@@ -288,15 +451,12 @@ pub struct HTTPStream {
 impl Unpin for HTTPStream {}
 
 impl HTTPStream {
-    pub async fn new(url: String) -> Result<Self, CommandError> {
-        let client = reqwest::Client::new();
+    pub async fn new(client: &RunnerClient, path: &str) -> Result<Self, CommandError> {
         let response = client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| CommandError::IoError {
-                error: std::io::Error::other(e),
-            })?;
+            .send(Method::GET, path, None, |error| CommandError::IoError {
+                error,
+            })
+            .await?;
 
         if !response.status().is_success() {
             return Err(CommandError::IoError {
@@ -0,0 +1,475 @@
+//! Bounds and measures the process group an execution runs in
+//!
+//! Limits go into a cgroup v2 subtree when the server can manage one: the cgroup named by
+//! `VIBE_KANBAN_CGROUP`, or the server's own cgroup when it is delegated (e.g. a systemd unit
+//! with `Delegate=yes`). Otherwise they fall back to rlimits, which apply to each process
+//! rather than the whole group, and CPU limits are not enforced.
+
+use std::io;
+
+use tokio::process::Command;
+
+use crate::models::config::ResourceLimits;
+
+/// Resources an execution's processes have used
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResourceUsage {
+    pub peak_memory_bytes: i64,
+    pub cpu_time_ms: i64,
+}
+
+impl ResourceUsage {
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    fn max(self, other: ResourceUsage) -> ResourceUsage {
+        ResourceUsage {
+            peak_memory_bytes: self.peak_memory_bytes.max(other.peak_memory_bytes),
+            cpu_time_ms: self.cpu_time_ms.max(other.cpu_time_ms),
+        }
+    }
+}
+
+/// Where an execution's limits are enforced and its usage measured
+#[derive(Debug, Default)]
+pub struct ResourceGroup {
+    #[cfg(target_os = "linux")]
+    cgroup: Option<linux::Cgroup>,
+    peak: Option<ResourceUsage>,
+}
+
+impl ResourceGroup {
+    /// Apply `limits` to `command` when it is spawned
+    pub fn confine(limits: &ResourceLimits, command: &mut Command) -> io::Result<Self> {
+        if limits.is_unlimited() {
+            return Ok(Self::default());
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            let (cgroup, remaining) = linux::Cgroup::create(limits)?;
+            let rlimits = linux::Rlimits::new(&remaining)?;
+            let procs = cgroup.as_ref().map(linux::Cgroup::procs).transpose()?;
+            // SAFETY: the hook only makes syscalls on data allocated before the fork
+            unsafe {
+                command.pre_exec(move || {
+                    if let Some(procs) = &procs {
+                        linux::join_cgroup(procs)?;
+                    }
+                    rlimits.apply()
+                });
+            }
+            Ok(Self { cgroup, peak: None })
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            let _ = command;
+            tracing::warn!("Resource limits are only enforced on Linux; running unlimited");
+            Ok(Self::default())
+        }
+    }
+
+    /// The most the process group led by `pid` has used so far, if it can be measured
+    pub fn sample(&mut self, pid: Option<u32>) -> Option<ResourceUsage> {
+        #[cfg(target_os = "linux")]
+        {
+            let current = self
+                .cgroup
+                .as_ref()
+                .and_then(linux::Cgroup::usage)
+                .or_else(|| pid.and_then(linux::process_group_usage));
+            if let Some(current) = current {
+                self.peak = Some(self.peak.unwrap_or_default().max(current));
+            }
+        }
+        #[cfg(not(target_os = "linux"))]
+        let _ = pid;
+
+        self.peak
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::{
+        fs::{self, File, OpenOptions},
+        io,
+        os::unix::{fs::MetadataExt, io::AsRawFd},
+        path::{Path, PathBuf},
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            OnceLock,
+        },
+    };
+
+    use nix::sys::resource::{getrlimit, rlim_t, setrlimit, Resource};
+
+    use super::ResourceUsage;
+    use crate::models::config::ResourceLimits;
+
+    const CGROUP_MOUNT: &str = "/sys/fs/cgroup";
+    const CONTROLLERS: &[&str] = &["memory", "cpu", "pids"];
+    /// cgroup v2 grants CPU quota per period of this many microseconds
+    const CPU_PERIOD_USEC: i64 = 100_000;
+    /// Prefix of the cgroups executions run in
+    const EXECUTION_PREFIX: &str = "execution-";
+
+    static CGROUP_PARENT: OnceLock<Option<PathBuf>> = OnceLock::new();
+    static NEXT_CGROUP: AtomicU64 = AtomicU64::new(0);
+
+    /// A cgroup holding one execution's processes, removed when dropped
+    #[derive(Debug)]
+    pub struct Cgroup {
+        path: PathBuf,
+    }
+
+    impl Cgroup {
+        /// Create a cgroup enforcing what it can of `limits`, returning the limits it can't
+        pub fn create(limits: &ResourceLimits) -> io::Result<(Option<Cgroup>, ResourceLimits)> {
+            let Some(parent) = cgroup_parent() else {
+                return Ok((None, *limits));
+            };
+
+            let path = parent.join(format!(
+                "{}{}-{}",
+                EXECUTION_PREFIX,
+                std::process::id(),
+                NEXT_CGROUP.fetch_add(1, Ordering::Relaxed)
+            ));
+            fs::create_dir(&path)?;
+            let cgroup = Cgroup { path };
+
+            let mut remaining = *limits;
+            if let Some(mb) = positive(limits.memory_limit_mb) {
+                if cgroup.set("memory.max", mb * 1024 * 1024)? {
+                    // Swapped out pages don't count towards memory.max
+                    let _ = cgroup.set("memory.swap.max", 0);
+                    remaining.memory_limit_mb = None;
+                }
+            }
+            if let Some(percent) = positive(limits.cpu_limit_percent) {
+                let quota = format!("{} {}", percent * CPU_PERIOD_USEC / 100, CPU_PERIOD_USEC);
+                if cgroup.set("cpu.max", quota)? {
+                    remaining.cpu_limit_percent = None;
+                }
+            }
+            if let Some(count) = positive(limits.max_processes) {
+                if cgroup.set("pids.max", count)? {
+                    remaining.max_processes = None;
+                }
+            }
+
+            Ok((Some(cgroup), remaining))
+        }
+
+        /// Write a control file; `Ok(false)` when its controller isn't enabled here
+        fn set(&self, file: &str, value: impl ToString) -> io::Result<bool> {
+            match fs::write(self.path.join(file), value.to_string()) {
+                Ok(()) => Ok(true),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+                Err(e) => Err(e),
+            }
+        }
+
+        /// The file a process writes to in order to move itself into this cgroup
+        pub fn procs(&self) -> io::Result<File> {
+            OpenOptions::new()
+                .write(true)
+                .open(self.path.join("cgroup.procs"))
+        }
+
+        /// Peak memory and total CPU time of every process that ran in this cgroup
+        pub fn usage(&self) -> Option<ResourceUsage> {
+            let read = |file: &str| fs::read_to_string(self.path.join(file)).ok();
+            // memory.peak needs Linux 5.19; before that the current usage is the best we have
+            let memory = read("memory.peak")
+                .or_else(|| read("memory.current"))?
+                .trim()
+                .parse::<i64>()
+                .ok()?;
+            let cpu_usec = read("cpu.stat")?
+                .lines()
+                .find_map(|line| line.strip_prefix("usage_usec "))?
+                .trim()
+                .parse::<i64>()
+                .ok()?;
+
+            Some(ResourceUsage {
+                peak_memory_bytes: memory,
+                cpu_time_ms: cpu_usec / 1000,
+            })
+        }
+    }
+
+    impl Drop for Cgroup {
+        fn drop(&mut self) {
+            // A cgroup can only be removed once it's empty, so take any stragglers with it
+            let _ = fs::write(self.path.join("cgroup.kill"), "1");
+            if let Err(e) = fs::remove_dir(&self.path) {
+                tracing::debug!("Failed to remove cgroup {}: {}", self.path.display(), e);
+            }
+        }
+    }
+
+    /// Move the calling process into the cgroup `procs` belongs to; safe between fork and exec
+    pub fn join_cgroup(procs: &File) -> io::Result<()> {
+        // SAFETY: writes a static buffer to a file descriptor the caller keeps open
+        let written = unsafe { libc::write(procs.as_raw_fd(), b"0".as_ptr().cast(), 1) };
+        if written < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    /// The limits a cgroup couldn't take, as per-process rlimits
+    pub struct Rlimits {
+        data: Option<rlim_t>,
+        nproc: Option<rlim_t>,
+    }
+
+    impl Rlimits {
+        pub fn new(limits: &ResourceLimits) -> io::Result<Self> {
+            if positive(limits.cpu_limit_percent).is_some() {
+                tracing::warn!("CPU limits need a delegated cgroup v2 subtree; not enforcing");
+            }
+
+            // Address space limits break runtimes like V8 that reserve large unused regions,
+            // so bound the data segment and private writable mappings instead
+            let data = positive(limits.memory_limit_mb).map(|mb| (mb * 1024 * 1024) as rlim_t);
+            // RLIMIT_NPROC counts every process the user runs, so allow for those already
+            // running
+            let nproc = match positive(limits.max_processes) {
+                Some(count) => Some((count as usize + user_process_count()?) as rlim_t),
+                None => None,
+            };
+
+            Ok(Self { data, nproc })
+        }
+
+        /// Lower the calling process's limits; safe between fork and exec
+        pub fn apply(&self) -> io::Result<()> {
+            if let Some(limit) = self.data {
+                lower_rlimit(Resource::RLIMIT_DATA, limit)?;
+            }
+            if let Some(limit) = self.nproc {
+                lower_rlimit(Resource::RLIMIT_NPROC, limit)?;
+            }
+            Ok(())
+        }
+    }
+
+    /// Lower a limit of the calling process; safe between fork and exec
+    fn lower_rlimit(resource: Resource, limit: rlim_t) -> io::Result<()> {
+        // Raising the hard limit needs privileges we don't have
+        let (_, hard) = getrlimit(resource)?;
+        let limit = limit.min(hard);
+        setrlimit(resource, limit, limit)?;
+        Ok(())
+    }
+
+    /// Memory and CPU time of the live members of process group `pgid`
+    pub fn process_group_usage(pgid: u32) -> Option<ResourceUsage> {
+        // SAFETY: sysconf only reads system configuration
+        let (ticks_per_second, page_size) = unsafe {
+            (
+                libc::sysconf(libc::_SC_CLK_TCK),
+                libc::sysconf(libc::_SC_PAGESIZE),
+            )
+        };
+        if ticks_per_second <= 0 || page_size <= 0 {
+            return None;
+        }
+
+        let mut members = 0;
+        let mut rss_pages = 0;
+        let mut cpu_ticks = 0;
+        for entry in fs::read_dir("/proc").ok()?.flatten() {
+            if !is_process(&entry) {
+                continue;
+            }
+            let Ok(stat) = fs::read_to_string(entry.path().join("stat")) else {
+                continue; // Exited since the directory was listed
+            };
+            if let Some(process) = parse_stat(&stat).filter(|p| p.pgrp == pgid as i64) {
+                members += 1;
+                rss_pages += process.rss_pages;
+                cpu_ticks += process.cpu_ticks;
+            }
+        }
+
+        (members > 0).then(|| ResourceUsage {
+            peak_memory_bytes: rss_pages * page_size,
+            cpu_time_ms: cpu_ticks * 1000 / ticks_per_second,
+        })
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub(super) struct ProcStat {
+        pub pgrp: i64,
+        pub cpu_ticks: i64, // Including children the process has reaped
+        pub rss_pages: i64,
+    }
+
+    /// Parse the fields we need from `/proc/<pid>/stat`
+    pub(super) fn parse_stat(stat: &str) -> Option<ProcStat> {
+        // The command name is in parentheses and may itself contain spaces or parentheses
+        let fields: Vec<&str> = stat
+            .get(stat.rfind(')')? + 1..)?
+            .split_whitespace()
+            .collect();
+        let field = |index: usize| fields.get(index)?.parse::<i64>().ok();
+
+        Some(ProcStat {
+            pgrp: field(2)?,
+            cpu_ticks: field(11)? + field(12)? + field(13)? + field(14)?,
+            rss_pages: field(21)?,
+        })
+    }
+
+    /// The cgroup executions are created under, set up on first use
+    fn cgroup_parent() -> Option<&'static Path> {
+        CGROUP_PARENT
+            .get_or_init(|| match prepare_cgroup_parent() {
+                Ok(parent) => {
+                    tracing::info!(
+                        "Limiting executions with cgroups under {}",
+                        parent.display()
+                    );
+                    Some(parent)
+                }
+                Err(e) => {
+                    tracing::info!(
+                        "cgroup v2 unavailable, limiting executions with rlimits: {}",
+                        e
+                    );
+                    None
+                }
+            })
+            .as_deref()
+    }
+
+    fn prepare_cgroup_parent() -> io::Result<PathBuf> {
+        let (parent, is_own) = match std::env::var_os("VIBE_KANBAN_CGROUP") {
+            Some(path) => (PathBuf::from(path), false),
+            None => (own_cgroup()?, true),
+        };
+
+        let available = fs::read_to_string(parent.join("cgroup.controllers"))?;
+        let enable = CONTROLLERS
+            .iter()
+            .filter(|controller| available.split_whitespace().any(|c| c == **controller))
+            .map(|controller| format!("+{}", controller))
+            .collect::<Vec<_>>()
+            .join(" ");
+        if enable.is_empty() {
+            return Err(io::Error::other(
+                "no memory, cpu or pids controller is delegated",
+            ));
+        }
+
+        let subtree_control = parent.join("cgroup.subtree_control");
+        if let Err(e) = fs::write(&subtree_control, &enable) {
+            // Only a cgroup without processes of its own can enable controllers for its
+            // children, so move the server into a leaf of its own first
+            if !is_own || e.raw_os_error() != Some(libc::EBUSY) {
+                return Err(e);
+            }
+            let server = parent.join("server");
+            if let Err(e) = fs::create_dir(&server) {
+                if e.kind() != io::ErrorKind::AlreadyExists {
+                    return Err(e);
+                }
+            }
+            fs::write(server.join("cgroup.procs"), std::process::id().to_string())?;
+            fs::write(&subtree_control, &enable)?;
+        }
+
+        // Remove the empty cgroups of executions a previous server didn't clean up
+        for entry in fs::read_dir(&parent)?.flatten() {
+            if entry
+                .file_name()
+                .to_string_lossy()
+                .starts_with(EXECUTION_PREFIX)
+            {
+                let _ = fs::remove_dir(entry.path());
+            }
+        }
+
+        Ok(parent)
+    }
+
+    /// The unified-hierarchy cgroup the server runs in
+    fn own_cgroup() -> io::Result<PathBuf> {
+        let cgroups = fs::read_to_string("/proc/self/cgroup")?;
+        let path = cgroups
+            .lines()
+            .find_map(|line| line.strip_prefix("0::"))
+            .ok_or_else(|| io::Error::other("not running under cgroup v2"))?;
+        Ok(Path::new(CGROUP_MOUNT).join(path.trim_start_matches('/')))
+    }
+
+    /// Processes owned by the current user
+    fn user_process_count() -> io::Result<usize> {
+        // SAFETY: getuid never fails
+        let uid = unsafe { libc::getuid() };
+        Ok(fs::read_dir("/proc")?
+            .flatten()
+            .filter(|entry| is_process(entry) && entry.metadata().is_ok_and(|m| m.uid() == uid))
+            .count())
+    }
+
+    /// Whether a `/proc` entry is a process directory
+    fn is_process(entry: &fs::DirEntry) -> bool {
+        entry
+            .file_name()
+            .to_str()
+            .is_some_and(|name| name.bytes().all(|b| b.is_ascii_digit()))
+    }
+
+    fn positive(limit: Option<i64>) -> Option<i64> {
+        limit.filter(|value| *value > 0)
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::linux::{parse_stat, ProcStat};
+
+    #[test]
+    fn parse_stat_handles_spaces_and_parentheses_in_the_command_name() {
+        let stat = "4242 (cargo (build) x) S 4200 4242 4242 0 -1 4194560 1200 0 0 0 \
+                    150 30 7 3 20 0 4 0 123456 104857600 2560 18446744073709551615";
+
+        assert_eq!(
+            parse_stat(stat),
+            Some(ProcStat {
+                pgrp: 4242,
+                cpu_ticks: 190,
+                rss_pages: 2560,
+            })
+        );
+        assert_eq!(parse_stat("4242 (truncated"), None);
+    }
+
+    #[tokio::test]
+    async fn sample_measures_a_running_process_group() {
+        let mut cmd = crate::command_runner::local::build_command(
+            &crate::command_runner::CommandRunnerArgs {
+                command: "sleep".to_string(),
+                args: vec!["5".to_string()],
+                working_dir: None,
+                env_vars: Vec::new(),
+                stdin: None,
+                keep_stdin_open: false,
+            },
+        );
+        let mut group = super::ResourceGroup::default();
+        let mut child =
+            command_group::AsyncCommandGroup::group_spawn(&mut cmd).expect("Should spawn sleep");
+
+        let usage = group
+            .sample(child.inner().id())
+            .expect("Should measure sleep");
+        assert!(usage.peak_memory_bytes > 0);
+
+        child.kill().await.expect("Should kill sleep");
+    }
+}
@@ -0,0 +1,685 @@
+//! Authenticates calls between `RemoteCommandExecutor` and `cloud_runner`, and limits what the
+//! runner will execute
+//!
+//! Both sides read the same environment: `CLOUD_RUNNER_HMAC_SECRET` signs every request with
+//! HMAC-SHA256 over its method, path, timestamp and body hash, while `CLOUD_RUNNER_TOKEN` sends a
+//! bearer token instead. The signature also covers the timestamp, so a captured request can only
+//! be replayed within `MAX_CLOCK_SKEW`.
+
+use std::path::{Path, PathBuf};
+
+use axum::http::HeaderMap;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+use crate::command_runner::{CommandError, CommandRunnerArgs};
+
+pub const TIMESTAMP_HEADER: &str = "x-runner-timestamp";
+pub const SIGNATURE_HEADER: &str = "x-runner-signature";
+
+/// How far a signed request's timestamp may be from the runner's clock, in seconds
+const MAX_CLOCK_SKEW: i64 = 300;
+
+/// Shells whose `-c` script is checked against the allowlist by the program it starts with
+const SHELLS: &[&str] = &["sh", "bash", "zsh"];
+
+/// Environment variables the executors set, accepted when commands are restricted
+///
+/// Others (`BASH_ENV`, `LD_PRELOAD`, `NODE_OPTIONS`, `PATH`, ...) could make an allowed program
+/// run arbitrary code, so they have to be allowed explicitly.
+const ALLOWED_ENV_VARS: &[&str] = &["NODE_NO_WARNINGS", "COLUMNS", "RUST_LOG"];
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Credentials shared by the server and its runner
+#[derive(Clone)]
+pub enum RunnerAuth {
+    Token(String),
+    Hmac(String),
+}
+
+impl std::fmt::Debug for RunnerAuth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RunnerAuth::Token(_) => write!(f, "RunnerAuth::Token(..)"),
+            RunnerAuth::Hmac(_) => write!(f, "RunnerAuth::Hmac(..)"),
+        }
+    }
+}
+
+impl RunnerAuth {
+    /// The configured credentials, preferring HMAC signing over a bearer token
+    pub fn from_env() -> Option<Self> {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        var("CLOUD_RUNNER_HMAC_SECRET")
+            .map(RunnerAuth::Hmac)
+            .or_else(|| var("CLOUD_RUNNER_TOKEN").map(RunnerAuth::Token))
+    }
+
+    /// Headers that authenticate a request for `path_and_query` carrying `body`
+    pub fn sign(
+        &self,
+        method: &str,
+        path_and_query: &str,
+        body: &[u8],
+    ) -> Vec<(&'static str, String)> {
+        self.sign_at(method, path_and_query, body, chrono::Utc::now().timestamp())
+    }
+
+    fn sign_at(
+        &self,
+        method: &str,
+        path_and_query: &str,
+        body: &[u8],
+        timestamp: i64,
+    ) -> Vec<(&'static str, String)> {
+        match self {
+            RunnerAuth::Token(token) => vec![("authorization", format!("Bearer {}", token))],
+            RunnerAuth::Hmac(secret) => {
+                let mac = signing_mac(secret, method, path_and_query, body, timestamp);
+                vec![
+                    (TIMESTAMP_HEADER, timestamp.to_string()),
+                    (SIGNATURE_HEADER, hex::encode(mac.finalize().into_bytes())),
+                ]
+            }
+        }
+    }
+
+    /// Check that a request was made by a holder of these credentials
+    pub fn verify(
+        &self,
+        method: &str,
+        path_and_query: &str,
+        headers: &HeaderMap,
+        body: &[u8],
+    ) -> Result<(), CommandError> {
+        self.verify_at(
+            method,
+            path_and_query,
+            headers,
+            body,
+            chrono::Utc::now().timestamp(),
+        )
+    }
+
+    fn verify_at(
+        &self,
+        method: &str,
+        path_and_query: &str,
+        headers: &HeaderMap,
+        body: &[u8],
+        now: i64,
+    ) -> Result<(), CommandError> {
+        self.verify_headers_at(headers, now)?;
+        match self {
+            RunnerAuth::Token(_) => Ok(()),
+            RunnerAuth::Hmac(secret) => {
+                let (timestamp, signature) = signature_headers(headers)?;
+                signing_mac(secret, method, path_and_query, body, timestamp)
+                    .verify_slice(&signature)
+                    .map_err(|_| unauthenticated("Invalid request signature"))
+            }
+        }
+    }
+
+    /// Check what can be checked before the body is read: the whole of a bearer token, or that
+    /// a signature is present, well formed and recent
+    pub fn verify_headers(&self, headers: &HeaderMap) -> Result<(), CommandError> {
+        self.verify_headers_at(headers, chrono::Utc::now().timestamp())
+    }
+
+    fn verify_headers_at(&self, headers: &HeaderMap, now: i64) -> Result<(), CommandError> {
+        match self {
+            RunnerAuth::Token(token) => {
+                let presented = header(headers, "authorization")?
+                    .strip_prefix("Bearer ")
+                    .ok_or_else(|| unauthenticated("Expected a bearer token"))?;
+                if constant_time_eq(presented.as_bytes(), token.as_bytes()) {
+                    Ok(())
+                } else {
+                    Err(unauthenticated("Invalid bearer token"))
+                }
+            }
+            RunnerAuth::Hmac(_) => {
+                let (timestamp, signature) = signature_headers(headers)?;
+                if (now - timestamp).abs() > MAX_CLOCK_SKEW {
+                    return Err(unauthenticated("Request timestamp is too far from now"));
+                }
+                if signature.len() != Sha256::output_size() {
+                    return Err(unauthenticated("Malformed request signature"));
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Whether `verify` needs the request body, which only signatures cover
+    #[allow(dead_code)]
+    pub fn covers_body(&self) -> bool {
+        matches!(self, RunnerAuth::Hmac(_))
+    }
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &str) -> Result<&'a str, CommandError> {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .ok_or_else(|| unauthenticated(format!("Missing {} header", name)))
+}
+
+/// The timestamp and decoded signature of a signed request
+fn signature_headers(headers: &HeaderMap) -> Result<(i64, Vec<u8>), CommandError> {
+    let timestamp = header(headers, TIMESTAMP_HEADER)?
+        .parse()
+        .map_err(|_| unauthenticated("Invalid request timestamp"))?;
+    let signature = hex::decode(header(headers, SIGNATURE_HEADER)?)
+        .map_err(|_| unauthenticated("Malformed request signature"))?;
+    Ok((timestamp, signature))
+}
+
+fn signing_mac(
+    secret: &str,
+    method: &str,
+    path_and_query: &str,
+    body: &[u8],
+    timestamp: i64,
+) -> HmacSha256 {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(
+        format!(
+            "{}\n{}\n{}\n{}",
+            method.to_uppercase(),
+            path_and_query,
+            timestamp,
+            hex::encode(Sha256::digest(body))
+        )
+        .as_bytes(),
+    );
+    mac
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn unauthenticated(message: impl Into<String>) -> CommandError {
+    CommandError::Unauthenticated {
+        message: message.into(),
+    }
+}
+
+fn not_permitted(message: String) -> CommandError {
+    CommandError::NotPermitted { message }
+}
+
+/// The commands and working directories a runner accepts; `None` accepts any
+///
+/// Only the program a command starts is checked (for shell `-c` scripts, the program the script
+/// starts with), so commands chained inside a script are not. An entry that is a path allows
+/// that file only; a bare name allows whatever it resolves to on the runner's `PATH`. Pair the
+/// allowlist with working directory roots and a sandbox rather than relying on it alone.
+#[derive(Debug, Clone, Default)]
+pub struct RunnerAllowlist {
+    commands: Option<Vec<String>>,
+    roots: Option<Vec<PathBuf>>,
+    env_vars: Vec<String>, // Accepted on top of `ALLOWED_ENV_VARS` when commands are restricted
+}
+
+impl RunnerAllowlist {
+    /// Read the comma-separated `CLOUD_RUNNER_ALLOWED_COMMANDS`, `CLOUD_RUNNER_ALLOWED_ROOTS`
+    /// and `CLOUD_RUNNER_ALLOWED_ENV`
+    #[allow(dead_code)]
+    pub fn from_env() -> std::io::Result<Self> {
+        let list = |name: &str| {
+            std::env::var(name).ok().map(|value| {
+                value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|item| !item.is_empty())
+                    .map(str::to_string)
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        let mut allowlist = Self::new(
+            list("CLOUD_RUNNER_ALLOWED_COMMANDS"),
+            list("CLOUD_RUNNER_ALLOWED_ROOTS")
+                .map(|roots| roots.into_iter().map(PathBuf::from).collect()),
+        )?;
+        allowlist.env_vars = list("CLOUD_RUNNER_ALLOWED_ENV").unwrap_or_default();
+        Ok(allowlist)
+    }
+
+    /// Accept only `commands` run under `roots`, each of which must exist
+    pub fn new(
+        commands: Option<Vec<String>>,
+        roots: Option<Vec<PathBuf>>,
+    ) -> std::io::Result<Self> {
+        let roots = roots
+            .map(|roots| {
+                roots
+                    .iter()
+                    .map(std::fs::canonicalize)
+                    .collect::<std::io::Result<Vec<_>>>()
+            })
+            .transpose()?;
+        Ok(Self {
+            commands,
+            roots,
+            env_vars: Vec::new(),
+        })
+    }
+
+    /// Also accept working directories under `root`, when roots are restricted at all
+    #[allow(dead_code)]
+    pub fn allow_root(&mut self, root: &Path) {
+        if let Some(roots) = &mut self.roots {
+            roots.push(root.to_path_buf());
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn is_unrestricted(&self) -> bool {
+        self.commands.is_none() && self.roots.is_none()
+    }
+
+    /// Reject `request` unless its program, environment and working directory are allowed
+    pub fn check(&self, request: &CommandRunnerArgs) -> Result<(), CommandError> {
+        if let Some(commands) = &self.commands {
+            if let Some(operator) = shell_script(request).and_then(control_operator) {
+                return Err(not_permitted(format!(
+                    "Shell scripts may only run a single allowed command, found '{}'",
+                    operator
+                )));
+            }
+            for name in env_var_names(request) {
+                if !ALLOWED_ENV_VARS.contains(&name) && !self.env_vars.iter().any(|v| v == name) {
+                    return Err(not_permitted(format!(
+                        "Environment variable '{}' is not in the runner's allowlist",
+                        name
+                    )));
+                }
+            }
+            let working_dir = request.working_dir.as_deref().map(Path::new);
+            for program in invoked_programs(request) {
+                if !commands
+                    .iter()
+                    .any(|allowed| is_allowed_program(program, allowed, working_dir))
+                {
+                    return Err(not_permitted(format!(
+                        "Command '{}' is not in the runner's allowlist",
+                        program
+                    )));
+                }
+            }
+        }
+
+        if let Some(roots) = &self.roots {
+            let Some(dir) = &request.working_dir else {
+                return Err(not_permitted(
+                    "Commands must set a working directory under an allowed root".to_string(),
+                ));
+            };
+            let dir = std::fs::canonicalize(dir).map_err(|_| {
+                not_permitted(format!("Working directory '{}' does not exist", dir))
+            })?;
+            if !roots.iter().any(|root| dir.starts_with(root)) {
+                return Err(not_permitted(format!(
+                    "Working directory '{}' is outside the runner's allowed roots",
+                    dir.display()
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The script `request` hands to a shell with `-c`, if any
+fn shell_script(request: &CommandRunnerArgs) -> Option<&str> {
+    if !SHELLS.contains(&program_name(&request.command)) {
+        return None;
+    }
+    match request.args.as_slice() {
+        [flag, script, ..] if flag == "-c" => Some(script),
+        _ => None,
+    }
+}
+
+/// The program `request` runs, plus the one its script starts with when that is a shell `-c`
+///
+/// Only meaningful for scripts without control operators, which could start other programs.
+fn invoked_programs(request: &CommandRunnerArgs) -> Vec<&str> {
+    let mut programs = vec![request.command.as_str()];
+    // Skip `VAR=value` prefixes
+    if let Some(program) = shell_script(request)
+        .and_then(|script| script.split_whitespace().find(|w| !w.contains('=')))
+    {
+        programs.push(program);
+    }
+    programs
+}
+
+/// The environment variables `request` sets, including `VAR=value` prefixes of its script
+fn env_var_names(request: &CommandRunnerArgs) -> Vec<&str> {
+    let mut names: Vec<&str> = request.env_vars.iter().map(|(k, _)| k.as_str()).collect();
+    if let Some(script) = shell_script(request) {
+        names.extend(
+            script
+                .split_whitespace()
+                .map_while(|word| word.split_once('=').map(|(name, _)| name)),
+        );
+    }
+    names
+}
+
+/// Whether running `program` from `working_dir` runs the file the allowlist entry names
+fn is_allowed_program(program: &str, allowed: &str, working_dir: Option<&Path>) -> bool {
+    if program == allowed {
+        return true;
+    }
+    match (
+        resolve_program(program, working_dir),
+        resolve_program(allowed, None),
+    ) {
+        (Some(program), Some(allowed)) => program == allowed,
+        _ => false,
+    }
+}
+
+/// The file `program` runs: a path as it stands (relative to `working_dir`), a bare name
+/// through `PATH`
+fn resolve_program(program: &str, working_dir: Option<&Path>) -> Option<PathBuf> {
+    let path = Path::new(program);
+    let candidate = if path.components().count() > 1 {
+        match working_dir {
+            Some(dir) => dir.join(path),
+            None => path.to_path_buf(),
+        }
+    } else {
+        let paths = std::env::var_os("PATH")?;
+        std::env::split_paths(&paths)
+            .map(|dir| dir.join(program))
+            .find(|candidate| candidate.is_file())?
+    };
+    std::fs::canonicalize(candidate).ok()
+}
+
+/// The first operator in `script` that could run a command other than its first word
+///
+/// Command substitution counts inside double quotes; nothing counts inside single quotes.
+fn control_operator(script: &str) -> Option<&'static str> {
+    let mut chars = script.chars().peekable();
+    let (mut single_quoted, mut double_quoted) = (false, false);
+    while let Some(c) = chars.next() {
+        if single_quoted {
+            single_quoted = c != '\'';
+            continue;
+        }
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '\'' if !double_quoted => single_quoted = true,
+            '"' => double_quoted = !double_quoted,
+            '`' => return Some("`"),
+            '$' if chars.peek() == Some(&'(') => return Some("$("),
+            _ if double_quoted => {}
+            ';' => return Some(";"),
+            '&' => return Some("&"),
+            '|' => return Some("|"),
+            '<' => return Some("<"),
+            '>' => return Some(">"),
+            '(' | ')' => return Some("("),
+            '\n' => return Some("newline"),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn program_name(program: &str) -> &str {
+    Path::new(program)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(program)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{HeaderName, HeaderValue};
+
+    use super::*;
+
+    fn headers(pairs: Vec<(&'static str, String)>) -> HeaderMap {
+        pairs
+            .into_iter()
+            .map(|(name, value)| {
+                (
+                    HeaderName::from_static(name),
+                    HeaderValue::from_str(&value).unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    fn args(command: &str, args: &[&str], working_dir: Option<&str>) -> CommandRunnerArgs {
+        CommandRunnerArgs {
+            command: command.to_string(),
+            args: args.iter().map(|a| a.to_string()).collect(),
+            working_dir: working_dir.map(str::to_string),
+            env_vars: Vec::new(),
+            stdin: None,
+            keep_stdin_open: false,
+        }
+    }
+
+    #[test]
+    fn hmac_signatures_cover_method_path_body_and_time() {
+        let auth = RunnerAuth::Hmac("secret".to_string());
+        let signed = headers(auth.sign_at("POST", "/commands", b"{}", 1_000));
+
+        assert!(auth
+            .verify_at("POST", "/commands", &signed, b"{}", 1_010)
+            .is_ok());
+        for (method, path, body, now) in [
+            ("DELETE", "/commands", &b"{}"[..], 1_010),
+            ("POST", "/commands/1", &b"{}"[..], 1_010),
+            ("POST", "/commands", &b"{ }"[..], 1_010),
+            ("POST", "/commands", &b"{}"[..], 1_000 + MAX_CLOCK_SKEW + 1),
+        ] {
+            assert!(matches!(
+                auth.verify_at(method, path, &signed, body, now),
+                Err(CommandError::Unauthenticated { .. })
+            ));
+        }
+
+        let other = RunnerAuth::Hmac("other".to_string());
+        assert!(other
+            .verify_at("POST", "/commands", &signed, b"{}", 1_010)
+            .is_err());
+        assert!(auth
+            .verify_at("POST", "/commands", &HeaderMap::new(), b"{}", 1_010)
+            .is_err());
+    }
+
+    #[test]
+    fn headers_are_checked_before_the_body() {
+        let auth = RunnerAuth::Hmac("secret".to_string());
+        let signed = headers(auth.sign_at("PUT", "/workspaces/1", b"bundle", 1_000));
+        assert!(auth.verify_headers_at(&signed, 1_010).is_ok());
+        assert!(auth
+            .verify_headers_at(&signed, 1_000 + MAX_CLOCK_SKEW + 1)
+            .is_err());
+        assert!(auth.verify_headers_at(&HeaderMap::new(), 1_010).is_err());
+        let truncated = headers(vec![
+            (TIMESTAMP_HEADER, "1000".to_string()),
+            (SIGNATURE_HEADER, "abcd".to_string()),
+        ]);
+        assert!(auth.verify_headers_at(&truncated, 1_010).is_err());
+
+        let token = RunnerAuth::Token("token".to_string());
+        assert!(token
+            .verify_headers(&headers(token.sign("PUT", "/workspaces/1", b"")))
+            .is_ok());
+        assert!(token.verify_headers(&signed).is_err());
+    }
+
+    #[test]
+    fn bearer_tokens_must_match() {
+        let auth = RunnerAuth::Token("token".to_string());
+        let signed = headers(auth.sign("GET", "/commands/1/status", b""));
+
+        assert!(auth
+            .verify("GET", "/commands/1/status", &signed, b"")
+            .is_ok());
+        assert!(RunnerAuth::Token("tokens".to_string())
+            .verify("GET", "/commands/1/status", &signed, b"")
+            .is_err());
+    }
+
+    #[test]
+    fn allowlist_checks_programs_and_shell_scripts() {
+        let allowlist =
+            RunnerAllowlist::new(Some(vec!["bash".to_string(), "npx".to_string()]), None).unwrap();
+
+        let bash = resolve_program("bash", None).unwrap();
+        assert!(allowlist
+            .check(&args(&bash.to_string_lossy(), &["-c", "npx claude"], None))
+            .is_ok());
+        assert!(allowlist
+            .check(&args(
+                "bash",
+                &["-c", "NODE_NO_WARNINGS=1 npx claude"],
+                None
+            ))
+            .is_ok());
+        assert!(matches!(
+            allowlist.check(&args("bash", &["-c", "rm -rf /"], None)),
+            Err(CommandError::NotPermitted { .. })
+        ));
+        assert!(allowlist.check(&args("python3", &[], None)).is_err());
+        assert!(allowlist
+            .check(&args("bash", &["-c", "npx claude -p 'a; b | c > d'"], None))
+            .is_ok());
+        assert!(RunnerAllowlist::default()
+            .check(&args("python3", &[], None))
+            .is_ok());
+    }
+
+    #[test]
+    fn allowlist_rejects_shell_scripts_chaining_commands() {
+        let allowlist = RunnerAllowlist::new(Some(vec!["npx".to_string()]), None).unwrap();
+
+        for script in [
+            "npx claude; rm -rf ~",
+            "npx claude && curl evil",
+            "npx claude | sh",
+            "npx claude > ~/.bashrc",
+            "npx claude `rm -rf ~`",
+            "npx claude \"$(rm -rf ~)\"",
+            "npx claude\nrm -rf ~",
+            "npx claude & rm -rf ~",
+        ] {
+            assert!(
+                matches!(
+                    allowlist.check(&args("sh", &["-c", script], None)),
+                    Err(CommandError::NotPermitted { .. })
+                ),
+                "{} should be rejected",
+                script
+            );
+        }
+    }
+
+    #[test]
+    fn allowlist_rejects_environment_that_runs_other_code() {
+        let allowlist = RunnerAllowlist::new(Some(vec!["npx".to_string()]), None).unwrap();
+
+        let mut request = args("npx", &["claude"], None);
+        request.env_vars = vec![("NODE_NO_WARNINGS".to_string(), "1".to_string())];
+        assert!(allowlist.check(&request).is_ok());
+        for name in ["BASH_ENV", "LD_PRELOAD", "NODE_OPTIONS", "PATH"] {
+            request.env_vars = vec![(name.to_string(), "/tmp/evil".to_string())];
+            assert!(
+                matches!(
+                    allowlist.check(&request),
+                    Err(CommandError::NotPermitted { .. })
+                ),
+                "{} should be rejected",
+                name
+            );
+        }
+        assert!(allowlist
+            .check(&args(
+                "sh",
+                &["-c", "NODE_OPTIONS=--require=/tmp/evil.js npx claude"],
+                None
+            ))
+            .is_err());
+
+        let mut configured = allowlist.clone();
+        configured.env_vars = vec!["ANTHROPIC_API_KEY".to_string()];
+        request.env_vars = vec![("ANTHROPIC_API_KEY".to_string(), "key".to_string())];
+        assert!(configured.check(&request).is_ok());
+    }
+
+    #[test]
+    fn allowlist_matches_programs_by_the_file_they_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let tool = dir.path().join("tool");
+        let evil = dir.path().join("evil");
+        std::fs::create_dir(&evil).unwrap();
+        for file in [tool.clone(), evil.join("tool"), evil.join("sh")] {
+            std::fs::write(&file, "").unwrap();
+        }
+        let allowlist = RunnerAllowlist::new(
+            Some(vec!["sh".to_string(), tool.to_string_lossy().into_owned()]),
+            None,
+        )
+        .unwrap();
+
+        // A path entry allows that file, however it is reached
+        assert!(allowlist
+            .check(&args(&tool.to_string_lossy(), &[], None))
+            .is_ok());
+        let dotted = dir.path().join("evil/../tool");
+        assert!(allowlist
+            .check(&args(&dotted.to_string_lossy(), &[], None))
+            .is_ok());
+        assert!(allowlist
+            .check(&args("./tool", &[], dir.path().to_str()))
+            .is_ok());
+        for program in [
+            evil.join("tool").to_string_lossy().into_owned(),
+            "tool".to_string(),
+        ] {
+            assert!(allowlist.check(&args(&program, &[], None)).is_err());
+        }
+
+        // A bare entry allows what it resolves to on PATH, not any file of that name
+        let sh = resolve_program("sh", None).unwrap();
+        assert!(allowlist
+            .check(&args(&sh.to_string_lossy(), &[], None))
+            .is_ok());
+        assert!(matches!(
+            allowlist.check(&args(&evil.join("sh").to_string_lossy(), &[], None)),
+            Err(CommandError::NotPermitted { .. })
+        ));
+    }
+
+    #[test]
+    fn allowlist_confines_working_directories_to_roots() {
+        let root = tempfile::tempdir().unwrap();
+        let inside = root.path().join("repo");
+        std::fs::create_dir(&inside).unwrap();
+        let allowlist = RunnerAllowlist::new(None, Some(vec![root.path().to_path_buf()])).unwrap();
+
+        assert!(allowlist.check(&args("git", &[], inside.to_str())).is_ok());
+        assert!(allowlist
+            .check(&args("git", &[], inside.join("..").join("..").to_str()))
+            .is_err());
+        assert!(allowlist.check(&args("git", &[], None)).is_err());
+    }
+}
@@ -0,0 +1,900 @@
+//! Confines local processes to a Linux namespace sandbox, in the style of bubblewrap
+//!
+//! Before exec, the child unshares user, mount, PID and (optionally) network namespaces, builds
+//! a new root on a tmpfs holding only the policy's paths, a fresh `/proc` and a minimal `/dev`,
+//! and pivots into it. Everything is prepared in the parent, so the child only makes raw
+//! syscalls between fork and exec.
+
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use async_trait::async_trait;
+use tokio::process::Command;
+
+use crate::{
+    command_runner::{
+        local::{bound_command, build_command, spawn_command},
+        CommandError, CommandExecutor, CommandRunnerArgs, ProcessHandle,
+    },
+    models::project::SandboxMode,
+};
+
+/// Host directories every sandbox can read, when they exist
+const SYSTEM_PATHS: &[&str] = &[
+    "/usr",
+    "/bin",
+    "/sbin",
+    "/lib",
+    "/lib32",
+    "/lib64",
+    "/libx32",
+    "/etc",
+    "/opt",
+    "/nix",
+    "/sys",
+    "/run/systemd/resolve", // resolv.conf often points here
+];
+
+/// Toolchains and agent CLI configuration under the user's home, mounted read-only
+const HOME_PATHS: &[&str] = &[
+    ".cargo",
+    ".rustup",
+    ".nvm",
+    ".volta",
+    ".bun",
+    ".deno",
+    ".pyenv",
+    ".local/bin",
+    ".gitconfig",
+    ".config/git",
+    ".claude",
+    ".claude.json",
+    ".codex",
+    ".gemini",
+    ".config/amp",
+    ".config/opencode",
+    ".local/share/opencode",
+    ".aider.conf.yml",
+];
+
+/// Where agent CLIs keep their sessions and state under the user's home, mounted writable so
+/// a later run can resume the session. Created when the CLI's configuration directory exists.
+const HOME_STATE_PATHS: &[&str] = &[
+    ".claude/projects",
+    ".claude/todos",
+    ".claude/shell-snapshots",
+    ".claude/statsig",
+    ".codex/sessions",
+    ".codex/log",
+];
+
+/// Parts of a worktree's common .git directory that committing writes to
+const COMMON_GIT_PATHS: &[&str] = &["objects", "refs", "logs"];
+
+/// What a sandboxed process can see of the host
+///
+/// Paths that don't exist are skipped. The rest of the filesystem is hidden; the home directory
+/// and `/tmp` are empty and writable, and are discarded when the process exits.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SandboxPolicy {
+    pub writable: Vec<PathBuf>,
+    pub read_only: Vec<PathBuf>,
+    pub network: bool,
+}
+
+impl SandboxPolicy {
+    /// The sandbox a project's processes in `worktree` run in, if its mode asks for one
+    pub fn for_project(mode: SandboxMode, worktree: &Path) -> Option<Self> {
+        let network = match mode {
+            SandboxMode::Disabled => return None,
+            SandboxMode::Enabled => true,
+            SandboxMode::Offline => false,
+        };
+
+        let mut writable = vec![worktree.to_path_buf()];
+        let mut read_only: Vec<PathBuf> = SYSTEM_PATHS.iter().map(PathBuf::from).collect();
+        if let Some(home) = dirs::home_dir() {
+            add_home_paths(&home, &mut writable, &mut read_only);
+        }
+
+        // A worktree's index, objects and refs live in the main repository's .git directory.
+        // Only those are writable: its config and hooks would run outside the sandbox.
+        if let Ok(repo) = git2::Repository::open(worktree) {
+            if repo.is_worktree() {
+                let common_dir = std::fs::read_to_string(repo.path().join("commondir"))
+                    .and_then(|relative| std::fs::canonicalize(repo.path().join(relative.trim())));
+                if let Ok(common_dir) = common_dir {
+                    if let Ok(worktree_dir) = std::fs::canonicalize(repo.path()) {
+                        writable.push(worktree_dir);
+                    }
+                    // Branch reflogs are written next to the refs on every commit
+                    writable.extend(COMMON_GIT_PATHS.iter().map(|path| common_dir.join(path)));
+                    read_only.push(common_dir);
+                }
+            }
+        }
+
+        Some(Self {
+            writable,
+            read_only,
+            network,
+        })
+    }
+
+    /// Confine `command` to this sandbox when it is spawned
+    pub fn confine(&self, command: &mut Command) -> io::Result<()> {
+        #[cfg(target_os = "linux")]
+        {
+            let working_dir = command.as_std().get_current_dir().map(Path::to_path_buf);
+            let setup = linux::Setup::new(self, working_dir.as_deref())?;
+            // SAFETY: the hook only makes syscalls on data allocated before the fork
+            unsafe {
+                command.pre_exec(move || setup.run());
+            }
+            Ok(())
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            let _ = command;
+            Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Sandboxed execution is only available on Linux",
+            ))
+        }
+    }
+}
+
+/// Mount the agent CLIs' configuration under `home` read-only and their state writable
+fn add_home_paths(home: &Path, writable: &mut Vec<PathBuf>, read_only: &mut Vec<PathBuf>) {
+    read_only.extend(HOME_PATHS.iter().map(|path| home.join(path)));
+    for path in HOME_STATE_PATHS {
+        let path = home.join(path);
+        if path.parent().is_some_and(Path::is_dir) {
+            // Paths that don't exist aren't mounted, and the CLI can't create them read-only
+            if let Err(e) = std::fs::create_dir_all(&path) {
+                tracing::warn!("Failed to create {}: {}", path.display(), e);
+            }
+        }
+        writable.push(path);
+    }
+}
+
+/// Runs commands like `LocalCommandExecutor`, confined to a `SandboxPolicy`
+pub struct SandboxedCommandExecutor {
+    policy: SandboxPolicy,
+}
+
+impl SandboxedCommandExecutor {
+    pub fn new(policy: SandboxPolicy) -> Self {
+        Self { policy }
+    }
+}
+
+#[async_trait]
+impl CommandExecutor for SandboxedCommandExecutor {
+    async fn start(
+        &self,
+        request: &CommandRunnerArgs,
+    ) -> Result<Box<dyn ProcessHandle>, CommandError> {
+        let mut cmd = build_command(request);
+        // Join the execution's cgroup before the sandbox hides it
+        let resources = bound_command(&mut cmd, request)?;
+        self.policy
+            .confine(&mut cmd)
+            .map_err(|e| CommandError::SpawnFailed {
+                command: format!("{} {}", request.command, request.args.join(" ")),
+                error: e,
+            })?;
+        Ok(Box::new(spawn_command(&mut cmd, request, resources).await?))
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::{
+        collections::{BTreeMap, BTreeSet},
+        ffi::{CStr, CString},
+        fs, io,
+        os::unix::ffi::OsStrExt,
+        path::{Path, PathBuf},
+        ptr,
+    };
+
+    use libc::{c_int, c_uint, c_ulong};
+
+    use super::SandboxPolicy;
+
+    /// Where the host's root is reachable while the new root is assembled
+    const OLD_ROOT: &str = "/oldroot";
+    /// Where the new root is assembled
+    const NEW_ROOT: &str = "/newroot";
+    /// Host devices passed through to the sandbox's `/dev`, when they exist
+    const DEVICES: &[&str] = &["null", "zero", "random", "urandom", "tty"];
+    /// Links every `/dev` is expected to have, pointing into the sandbox's `/proc`
+    const DEV_LINKS: &[(&str, &str)] = &[
+        ("fd", "/proc/self/fd"),
+        ("stdin", "/proc/self/fd/0"),
+        ("stdout", "/proc/self/fd/1"),
+        ("stderr", "/proc/self/fd/2"),
+    ];
+
+    enum Entry {
+        Bind { is_dir: bool, read_only: bool },
+        Symlink(PathBuf),
+        Dev,
+        Proc,
+        Tmpfs,
+        Dir,
+    }
+
+    enum Step {
+        Dir(CString),
+        File(CString),
+        Symlink {
+            target: CString,
+            link: CString,
+        },
+        Bind {
+            source: CString,
+            target: CString,
+            remount_flags: Option<c_ulong>, // Read-only remount, keeping the host's locked flags
+        },
+        Tmpfs(CString),
+        Proc(CString),
+    }
+
+    /// Everything the child does between fork and exec, prepared in the parent
+    pub(super) struct Setup {
+        unshare_flags: c_int,
+        uid_map: Vec<u8>,
+        gid_map: Vec<u8>,
+        network: bool,
+        steps: Vec<Step>,
+        working_dir: CString,
+    }
+
+    impl Setup {
+        pub(super) fn new(policy: &SandboxPolicy, working_dir: Option<&Path>) -> io::Result<Self> {
+            let mut entries = BTreeMap::new();
+            for path in &policy.read_only {
+                add_path(&mut entries, path, true)?;
+            }
+            for path in &policy.writable {
+                add_path(&mut entries, path, false)?;
+            }
+            entries.insert(PathBuf::from("/dev"), Entry::Dev);
+            entries.insert(PathBuf::from("/proc"), Entry::Proc);
+            entries.insert(PathBuf::from("/tmp"), Entry::Tmpfs);
+            if let Some(home) = dirs::home_dir() {
+                entries.entry(home).or_insert(Entry::Dir);
+            }
+
+            // Parents sort before their children, so mount points exist before they're used
+            let mut created = BTreeSet::new();
+            let mut steps = Vec::new();
+            for (path, entry) in &entries {
+                for ancestor in path
+                    .ancestors()
+                    .skip(1)
+                    .collect::<Vec<_>>()
+                    .into_iter()
+                    .rev()
+                {
+                    if ancestor != Path::new("/") && created.insert(ancestor.to_path_buf()) {
+                        steps.push(Step::Dir(new_root_path(ancestor)?));
+                    }
+                }
+                let target = new_root_path(path)?;
+                match entry {
+                    Entry::Bind { is_dir, read_only } => {
+                        steps.push(if *is_dir {
+                            Step::Dir(target.clone())
+                        } else {
+                            Step::File(target.clone())
+                        });
+                        let remount_flags = match read_only {
+                            true => Some(read_only_remount_flags(path)?),
+                            false => None,
+                        };
+                        steps.push(Step::Bind {
+                            source: old_root_path(path)?,
+                            target,
+                            remount_flags,
+                        });
+                    }
+                    Entry::Dev => {
+                        steps.push(Step::Dir(target.clone()));
+                        steps.push(Step::Tmpfs(target));
+                        for device in DEVICES {
+                            let device = path.join(device);
+                            if device.exists() {
+                                let target = new_root_path(&device)?;
+                                steps.push(Step::File(target.clone()));
+                                steps.push(Step::Bind {
+                                    source: old_root_path(&device)?,
+                                    target,
+                                    remount_flags: None,
+                                });
+                            }
+                        }
+                        for (link, link_target) in DEV_LINKS {
+                            steps.push(Step::Symlink {
+                                target: c_path(Path::new(link_target))?,
+                                link: new_root_path(&path.join(link))?,
+                            });
+                        }
+                    }
+                    Entry::Proc => {
+                        steps.push(Step::Dir(target.clone()));
+                        steps.push(Step::Proc(target));
+                    }
+                    Entry::Symlink(link_target) => steps.push(Step::Symlink {
+                        target: c_path(link_target)?,
+                        link: target,
+                    }),
+                    Entry::Tmpfs => {
+                        steps.push(Step::Dir(target.clone()));
+                        steps.push(Step::Tmpfs(target));
+                    }
+                    Entry::Dir => steps.push(Step::Dir(target)),
+                }
+                created.insert(path.clone());
+            }
+
+            let mut unshare_flags = libc::CLONE_NEWUSER | libc::CLONE_NEWNS | libc::CLONE_NEWPID;
+            if !policy.network {
+                unshare_flags |= libc::CLONE_NEWNET;
+            }
+            // SAFETY: getuid and getgid cannot fail
+            let (uid, gid) = unsafe { (libc::getuid(), libc::getgid()) };
+
+            Ok(Self {
+                unshare_flags,
+                uid_map: format!("{uid} {uid} 1\n").into_bytes(),
+                gid_map: format!("{gid} {gid} 1\n").into_bytes(),
+                network: policy.network,
+                steps,
+                working_dir: c_path(working_dir.unwrap_or(Path::new("/")))?,
+            })
+        }
+
+        /// Enter the sandbox; runs in the child between fork and exec
+        pub(super) fn run(&self) -> io::Result<()> {
+            // SAFETY: every pointer handed to the kernel comes from a CString owned by self
+            unsafe {
+                check(libc::unshare(self.unshare_flags))?;
+                write_file(c"/proc/self/setgroups", b"deny")?;
+                write_file(c"/proc/self/uid_map", &self.uid_map)?;
+                write_file(c"/proc/self/gid_map", &self.gid_map)?;
+                if !self.network {
+                    bring_up_loopback()?;
+                }
+                enter_pid_namespace()?;
+
+                // Keep our mounts from propagating back to the host
+                check(libc::mount(
+                    ptr::null(),
+                    c"/".as_ptr(),
+                    ptr::null(),
+                    libc::MS_REC | libc::MS_PRIVATE,
+                    ptr::null(),
+                ))?;
+
+                // Swap in a scratch root, with the host's root reachable under it
+                mount_tmpfs(c"/tmp")?;
+                make_dir(c"/tmp/oldroot")?;
+                make_dir(c"/tmp/newroot")?;
+                pivot_root(c"/tmp", c"/tmp/oldroot")?;
+                check(libc::chdir(c"/".as_ptr()))?;
+
+                mount_tmpfs(c"/newroot")?;
+                for step in &self.steps {
+                    step.run()?;
+                }
+                check(libc::umount2(c"/oldroot".as_ptr(), libc::MNT_DETACH))?;
+
+                // Make the assembled tree the root and drop the scratch one
+                check(libc::chdir(c"/newroot".as_ptr()))?;
+                pivot_root(c".", c".")?;
+                check(libc::umount2(c".".as_ptr(), libc::MNT_DETACH))?;
+                check(libc::chdir(self.working_dir.as_ptr()))?;
+            }
+            Ok(())
+        }
+    }
+
+    impl Step {
+        unsafe fn run(&self) -> io::Result<()> {
+            match self {
+                Step::Dir(path) => make_dir(path),
+                Step::File(path) => {
+                    let fd = libc::open(
+                        path.as_ptr(),
+                        libc::O_CREAT | libc::O_WRONLY | libc::O_CLOEXEC,
+                        0o644,
+                    );
+                    check(fd)?;
+                    libc::close(fd);
+                    Ok(())
+                }
+                Step::Symlink { target, link } => {
+                    ignore_exists(check(libc::symlink(target.as_ptr(), link.as_ptr())))
+                }
+                Step::Bind {
+                    source,
+                    target,
+                    remount_flags,
+                } => {
+                    check(libc::mount(
+                        source.as_ptr(),
+                        target.as_ptr(),
+                        ptr::null(),
+                        libc::MS_BIND | libc::MS_REC,
+                        ptr::null(),
+                    ))?;
+                    if let Some(flags) = remount_flags {
+                        check(libc::mount(
+                            ptr::null(),
+                            target.as_ptr(),
+                            ptr::null(),
+                            *flags,
+                            ptr::null(),
+                        ))?;
+                    }
+                    Ok(())
+                }
+                Step::Tmpfs(path) => mount_tmpfs(path),
+                Step::Proc(path) => check(libc::mount(
+                    c"proc".as_ptr(),
+                    path.as_ptr(),
+                    c"proc".as_ptr(),
+                    libc::MS_NOSUID | libc::MS_NODEV | libc::MS_NOEXEC,
+                    ptr::null(),
+                )),
+            }
+        }
+    }
+
+    /// Record `path` for mounting, following it if it is a symlink
+    fn add_path(
+        entries: &mut BTreeMap<PathBuf, Entry>,
+        path: &Path,
+        read_only: bool,
+    ) -> io::Result<()> {
+        if !path.is_absolute() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Sandbox path '{}' is not absolute", path.display()),
+            ));
+        }
+        let metadata = match fs::symlink_metadata(path) {
+            Ok(metadata) => metadata,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e),
+        };
+
+        if metadata.file_type().is_symlink() {
+            entries.insert(path.to_path_buf(), Entry::Symlink(fs::read_link(path)?));
+            return match fs::canonicalize(path) {
+                Ok(resolved) => add_path(entries, &resolved, read_only),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+                Err(e) => Err(e),
+            };
+        }
+
+        let entry = Entry::Bind {
+            is_dir: metadata.is_dir(),
+            read_only,
+        };
+        match entries.get(path) {
+            // Writable wins when a path is listed both ways
+            Some(Entry::Bind {
+                read_only: false, ..
+            }) => {}
+            _ => {
+                entries.insert(path.to_path_buf(), entry);
+            }
+        }
+        Ok(())
+    }
+
+    /// Remount flags that make a bind mount of `path` read-only
+    ///
+    /// Flags of mounts from outside the user namespace are locked, so the remount has to keep
+    /// them.
+    fn read_only_remount_flags(path: &Path) -> io::Result<c_ulong> {
+        let mut flags = libc::MS_BIND | libc::MS_REMOUNT | libc::MS_RDONLY;
+        let path = c_path(path)?;
+        // SAFETY: stat is a plain out-parameter for statvfs
+        let stat = unsafe {
+            let mut stat: libc::statvfs = std::mem::zeroed();
+            check(libc::statvfs(path.as_ptr(), &mut stat))?;
+            stat
+        };
+        for (st_flag, ms_flag) in [
+            (libc::ST_NOSUID, libc::MS_NOSUID),
+            (libc::ST_NODEV, libc::MS_NODEV),
+            (libc::ST_NOEXEC, libc::MS_NOEXEC),
+            (libc::ST_NOATIME, libc::MS_NOATIME),
+            (libc::ST_NODIRATIME, libc::MS_NODIRATIME),
+            (libc::ST_RELATIME, libc::MS_RELATIME),
+        ] {
+            if stat.f_flag & st_flag != 0 {
+                flags |= ms_flag;
+            }
+        }
+        Ok(flags)
+    }
+
+    fn old_root_path(path: &Path) -> io::Result<CString> {
+        c_path(&Path::new(OLD_ROOT).join(path.strip_prefix("/").unwrap_or(path)))
+    }
+
+    fn new_root_path(path: &Path) -> io::Result<CString> {
+        c_path(&Path::new(NEW_ROOT).join(path.strip_prefix("/").unwrap_or(path)))
+    }
+
+    fn c_path(path: &Path) -> io::Result<CString> {
+        CString::new(path.as_os_str().as_bytes()).map_err(io::Error::other)
+    }
+
+    fn check(result: c_int) -> io::Result<()> {
+        if result == -1 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(())
+        }
+    }
+
+    fn ignore_exists(result: io::Result<()>) -> io::Result<()> {
+        match result {
+            Err(e) if e.raw_os_error() == Some(libc::EEXIST) => Ok(()),
+            result => result,
+        }
+    }
+
+    unsafe fn make_dir(path: &CStr) -> io::Result<()> {
+        ignore_exists(check(libc::mkdir(path.as_ptr(), 0o755)))
+    }
+
+    unsafe fn mount_tmpfs(path: &CStr) -> io::Result<()> {
+        check(libc::mount(
+            c"tmpfs".as_ptr(),
+            path.as_ptr(),
+            c"tmpfs".as_ptr(),
+            libc::MS_NOSUID | libc::MS_NODEV,
+            c"mode=0755".as_ptr().cast(),
+        ))
+    }
+
+    unsafe fn pivot_root(new_root: &CStr, put_old: &CStr) -> io::Result<()> {
+        let result = libc::syscall(libc::SYS_pivot_root, new_root.as_ptr(), put_old.as_ptr());
+        check(result as c_int)
+    }
+
+    unsafe fn write_file(path: &CStr, contents: &[u8]) -> io::Result<()> {
+        let fd = libc::open(path.as_ptr(), libc::O_WRONLY | libc::O_CLOEXEC);
+        check(fd)?;
+        let written = libc::write(fd, contents.as_ptr().cast(), contents.len());
+        libc::close(fd);
+        if written == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    /// Fork into the new PID namespace, where the command runs as the child of its init
+    ///
+    /// Only children join a PID namespace, so this process stays behind and the first child
+    /// becomes the namespace's init, reaping orphans. Both only wait and exit with the
+    /// command's status, and let go of the descriptors `spawn` waits on to see the command exec.
+    ///
+    /// command_group's `setpgid` hook runs after this one, in the command, so this process
+    /// leads the group signals are sent to and the command ends up leading a group of its own.
+    /// Init is in the first group and forwards stop signals to the command's.
+    unsafe fn enter_pid_namespace() -> io::Result<()> {
+        check(libc::setpgid(0, 0))?;
+        let init = libc::fork();
+        check(init)?;
+        if init > 0 {
+            handle_stop_signals(libc::SIG_IGN);
+            outlive(init);
+        }
+        let command = libc::fork();
+        check(command)?;
+        if command > 0 {
+            handle_stop_signals(forward_to_command as libc::sighandler_t);
+            outlive(command);
+        }
+        Ok(())
+    }
+
+    unsafe fn handle_stop_signals(handler: libc::sighandler_t) {
+        for signal in [libc::SIGINT, libc::SIGTERM] {
+            libc::signal(signal, handler);
+        }
+    }
+
+    /// Send `signal` on from init to the command, which is the namespace's second process
+    extern "C" fn forward_to_command(signal: c_int) {
+        const COMMAND: libc::pid_t = 2;
+        // SAFETY: kill is async-signal-safe
+        unsafe {
+            // Before its setpgid hook has run, the command is still in init's group
+            if libc::kill(-COMMAND, signal) == -1 {
+                libc::kill(COMMAND, signal);
+            }
+        }
+    }
+
+    /// Wait for `child`, reaping any other children meanwhile, and exit the way it did
+    unsafe fn outlive(child: libc::pid_t) -> ! {
+        if libc::syscall(libc::SYS_close_range, 3, c_uint::MAX, 0) == -1 {
+            for fd in 3..1024 {
+                libc::close(fd);
+            }
+        }
+
+        let mut status = 0;
+        loop {
+            let pid = libc::waitpid(-1, &mut status, 0);
+            if pid == child {
+                break;
+            }
+            if pid == -1 && io::Error::last_os_error().raw_os_error() != Some(libc::EINTR) {
+                libc::_exit(1);
+            }
+        }
+        if libc::WIFSIGNALED(status) {
+            // A namespace's init can't signal itself, so it falls through to the shell's code
+            let signal = libc::WTERMSIG(status);
+            libc::signal(signal, libc::SIG_DFL);
+            libc::kill(libc::getpid(), signal);
+            libc::_exit(128 + signal);
+        }
+        libc::_exit(libc::WEXITSTATUS(status))
+    }
+
+    /// A new network namespace starts with its loopback interface down
+    unsafe fn bring_up_loopback() -> io::Result<()> {
+        let fd = libc::socket(libc::AF_INET, libc::SOCK_DGRAM | libc::SOCK_CLOEXEC, 0);
+        check(fd)?;
+        let mut request: libc::ifreq = std::mem::zeroed();
+        for (dst, src) in request.ifr_name.iter_mut().zip(b"lo") {
+            *dst = *src as libc::c_char;
+        }
+        request.ifr_ifru.ifru_flags = (libc::IFF_UP | libc::IFF_RUNNING) as libc::c_short;
+        let result = check(libc::ioctl(fd, libc::SIOCSIFFLAGS as _, &request));
+        libc::close(fd);
+        result
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use tokio::io::AsyncReadExt;
+
+    use super::*;
+    use crate::command_runner::CommandProcess;
+
+    async fn run_sandboxed(policy: &SandboxPolicy, script: &str, dir: &Path) -> (bool, String) {
+        let request = CommandRunnerArgs {
+            command: "sh".to_string(),
+            args: vec!["-c".to_string(), script.to_string()],
+            working_dir: Some(dir.to_string_lossy().to_string()),
+            env_vars: Vec::new(),
+            stdin: None,
+            keep_stdin_open: false,
+        };
+        let handle = SandboxedCommandExecutor::new(policy.clone())
+            .start(&request)
+            .await
+            .expect("sandboxed command should start");
+        let mut process = CommandProcess::from_handle(handle);
+
+        let mut stream = process.stream().await.unwrap();
+        let mut output = String::new();
+        stream
+            .stdout
+            .take()
+            .unwrap()
+            .read_to_string(&mut output)
+            .await
+            .unwrap();
+        let status = process.wait().await.unwrap();
+        (status.success(), output)
+    }
+
+    #[tokio::test]
+    async fn test_sandbox_hides_everything_but_its_paths() {
+        let worktree = tempfile::tempdir().unwrap();
+        let secret = tempfile::tempdir().unwrap();
+        std::fs::write(secret.path().join("token"), "hunter2").unwrap();
+        let policy = SandboxPolicy {
+            writable: vec![worktree.path().to_path_buf()],
+            read_only: SYSTEM_PATHS.iter().map(PathBuf::from).collect(),
+            network: false,
+        };
+
+        let script = format!(
+            "pwd; echo changed > result.txt; cat {} || echo hidden; touch /usr/x || echo read-only",
+            secret.path().join("token").display()
+        );
+        let (success, output) = run_sandboxed(&policy, &script, worktree.path()).await;
+
+        assert!(success, "sandboxed script failed: {output}");
+        assert_eq!(
+            output.lines().collect::<Vec<_>>(),
+            vec![worktree.path().to_str().unwrap(), "hidden", "read-only"]
+        );
+        assert_eq!(
+            std::fs::read_to_string(worktree.path().join("result.txt")).unwrap(),
+            "changed\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sandbox_has_its_own_processes_and_devices() {
+        let worktree = tempfile::tempdir().unwrap();
+        let policy = SandboxPolicy {
+            writable: vec![worktree.path().to_path_buf()],
+            read_only: SYSTEM_PATHS.iter().map(PathBuf::from).collect(),
+            network: false,
+        };
+
+        let script = "echo $$; set -- /proc/[0-9]*; echo $#; ls /dev; echo ok > /dev/null";
+        let (success, output) = run_sandboxed(&policy, script, worktree.path()).await;
+
+        assert!(success, "sandboxed script failed: {output}");
+        let lines: Vec<_> = output.lines().collect();
+        // Only the namespace's init and the shell are visible
+        assert_eq!(lines[..2], ["2", "2"]);
+        assert!(lines.contains(&"null"));
+        for name in &lines[2..] {
+            assert!(
+                ["fd", "stdin", "stdout", "stderr", "null", "zero", "random", "urandom", "tty"]
+                    .contains(name),
+                "unexpected /dev entry {name}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sandboxed_command_is_stopped_gracefully() {
+        let worktree = tempfile::tempdir().unwrap();
+        let policy = SandboxPolicy {
+            writable: vec![worktree.path().to_path_buf()],
+            read_only: SYSTEM_PATHS.iter().map(PathBuf::from).collect(),
+            network: false,
+        };
+        let request = CommandRunnerArgs {
+            command: "sleep".to_string(),
+            args: vec!["60".to_string()],
+            working_dir: Some(worktree.path().to_string_lossy().to_string()),
+            env_vars: Vec::new(),
+            stdin: None,
+            keep_stdin_open: false,
+        };
+        let handle = SandboxedCommandExecutor::new(policy)
+            .start(&request)
+            .await
+            .expect("sandboxed command should start");
+        let mut process = CommandProcess::from_handle(handle);
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+
+        // SIGINT is passed on to sleep, and the processes waiting on it exit the way it did
+        let outcome = process
+            .stop(std::time::Duration::from_secs(2))
+            .await
+            .unwrap();
+        assert_eq!(
+            outcome.signal,
+            Some(crate::command_runner::StopSignal::Interrupt)
+        );
+        assert_eq!(process.status().await.unwrap().unwrap().code, Some(130));
+    }
+
+    #[tokio::test]
+    async fn test_sandboxed_follow_up_resumes_the_session_an_earlier_run_saved() {
+        let worktree = tempfile::tempdir().unwrap();
+        let home = tempfile::tempdir().unwrap();
+        std::fs::create_dir(home.path().join(".claude")).unwrap();
+        std::fs::write(home.path().join(".claude/settings.json"), "{}").unwrap();
+        std::fs::create_dir(home.path().join(".codex")).unwrap();
+        let mut policy = SandboxPolicy {
+            writable: vec![worktree.path().to_path_buf()],
+            read_only: SYSTEM_PATHS.iter().map(PathBuf::from).collect(),
+            network: false,
+        };
+        add_home_paths(home.path(), &mut policy.writable, &mut policy.read_only);
+
+        let claude = home.path().join(".claude");
+        let session = claude.join("projects/worktree/s-1.jsonl");
+        let codex_session = home.path().join(".codex/sessions/s-1.jsonl");
+        let script = format!(
+            "mkdir {projects}/worktree && echo first > {session} && echo first > {codex} \
+             && (echo changed > {settings} || echo read-only)",
+            projects = claude.join("projects").display(),
+            session = session.display(),
+            codex = codex_session.display(),
+            settings = claude.join("settings.json").display(),
+        );
+        let (success, output) = run_sandboxed(&policy, &script, worktree.path()).await;
+        assert!(success, "first run failed: {output}");
+        assert_eq!(output.trim(), "read-only");
+
+        // The follow-up runs in a sandbox of its own and finds the session where it was left
+        let script = format!("cat {} {}", session.display(), codex_session.display());
+        let (success, output) = run_sandboxed(&policy, &script, worktree.path()).await;
+        assert!(success, "follow-up failed: {output}");
+        assert_eq!(output, "first\nfirst\n");
+        assert_eq!(
+            std::fs::read_to_string(claude.join("settings.json")).unwrap(),
+            "{}"
+        );
+    }
+
+    #[test]
+    fn test_policy_for_project() {
+        let worktree = tempfile::tempdir().unwrap();
+        assert!(SandboxPolicy::for_project(SandboxMode::Disabled, worktree.path()).is_none());
+
+        // Agent state under the home directory is writable as well
+        let outside_home = |policy: &SandboxPolicy| -> Vec<PathBuf> {
+            let home = dirs::home_dir().unwrap_or_default();
+            policy
+                .writable
+                .iter()
+                .filter(|path| !path.starts_with(&home))
+                .cloned()
+                .collect()
+        };
+
+        let policy = SandboxPolicy::for_project(SandboxMode::Offline, worktree.path()).unwrap();
+        assert!(!policy.network);
+        assert_eq!(outside_home(&policy), vec![worktree.path().to_path_buf()]);
+        assert!(policy.read_only.contains(&PathBuf::from("/usr")));
+
+        // Only the parts of the common .git directory committing needs are writable
+        let repo = tempfile::tempdir().unwrap();
+        let git = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .args(args)
+                .current_dir(repo.path())
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success(), "git {args:?} failed");
+        };
+        git(&["init", "--quiet"]);
+        git(&[
+            "-c",
+            "user.name=test",
+            "-c",
+            "user.email=test@example.com",
+            "commit",
+            "--quiet",
+            "--allow-empty",
+            "-m",
+            "initial",
+        ]);
+        let worktree = repo.path().join("worktree");
+        git(&["worktree", "add", "--quiet", worktree.to_str().unwrap()]);
+
+        let common_dir = std::fs::canonicalize(repo.path().join(".git")).unwrap();
+        let policy = SandboxPolicy::for_project(SandboxMode::Enabled, &worktree).unwrap();
+        assert_eq!(
+            outside_home(&policy),
+            vec![
+                worktree.clone(),
+                common_dir.join("worktrees/worktree"),
+                common_dir.join("objects"),
+                common_dir.join("refs"),
+                common_dir.join("logs"),
+            ]
+        );
+        assert!(policy.read_only.contains(&common_dir));
+    }
+}
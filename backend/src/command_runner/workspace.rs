@@ -0,0 +1,549 @@
+//! Moves an attempt's worktree to a `cloud_runner` and its commits back
+//!
+//! Before a remote command starts in a worktree, the worktree's branch is sent to the runner as
+//! a git bundle and materialized in a workspace there; the command runs in that workspace. When
+//! it finishes, the runner commits anything left uncommitted and returns the new commits as a
+//! bundle, which is fast-forwarded into the local branch. A workspace is keyed by its local
+//! worktree, so setup scripts, agents and dev servers of one attempt share it, along with any
+//! untracked files (e.g. installed dependencies) they leave behind.
+//!
+//! Only committed history is uploaded. Uncommitted changes and ignored files in the local
+//! worktree, including task attachments staged under [`STAGING_DIR`], never reach the runner, so
+//! agents running there cannot see them; `upload` warns when it leaves such state behind.
+
+use std::path::{Path, PathBuf};
+
+use reqwest::Method;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::{
+    command_runner::{CommandError, RunnerClient},
+    models::task_attachment::STAGING_DIR,
+};
+
+/// Author of the commits a runner makes for changes a command left uncommitted
+const RUNNER_GIT_IDENTITY: [&str; 4] = [
+    "-c",
+    "user.name=Vibe Kanban Runner",
+    "-c",
+    "user.email=runner@vibekanban.com",
+];
+
+/// A local worktree mirrored on a runner
+#[derive(Debug, Clone)]
+pub struct RemoteWorkspace {
+    id: String,
+    local_path: String,
+    remote_path: String,
+    branch: String,
+    base_commit: String,
+}
+
+impl RemoteWorkspace {
+    /// Send the branch checked out at `local_path` to the runner, or `None` when `local_path`
+    /// is not the root of a worktree on a branch
+    pub async fn upload(
+        client: &RunnerClient,
+        local_path: &str,
+    ) -> Result<Option<Self>, CommandError> {
+        let dir = Path::new(local_path);
+        let Ok(toplevel) = git_output(dir, &["rev-parse", "--show-toplevel"]).await else {
+            return Ok(None);
+        };
+        if std::fs::canonicalize(toplevel.trim()).ok() != std::fs::canonicalize(dir).ok() {
+            return Ok(None);
+        }
+        let Ok(branch) = git_output(dir, &["symbolic-ref", "--short", "HEAD"]).await else {
+            return Ok(None); // detached HEAD
+        };
+        let branch = branch.trim().to_string();
+        let base_commit = git_output(dir, &["rev-parse", "HEAD"])
+            .await?
+            .trim()
+            .to_string();
+        warn_about_local_state(dir).await;
+
+        let bundle = temp_bundle_path();
+        let bundle_arg = bundle.to_string_lossy().to_string();
+        let created = git(dir, &["bundle", "create", &bundle_arg, &branch]).await;
+        let bytes = created.and(std::fs::read(&bundle).map_err(CommandError::from));
+        let _ = std::fs::remove_file(&bundle);
+        let bytes = bytes?;
+
+        let id = workspace_id(local_path);
+        let response = client
+            .send(
+                Method::PUT,
+                &format!("/workspaces/{}?branch={}", id, urlencoding::encode(&branch)),
+                Some(("application/octet-stream", bytes)),
+                |error| CommandError::IoError { error },
+            )
+            .await?;
+        let result: serde_json::Value =
+            runner_response(response)
+                .await?
+                .json()
+                .await
+                .map_err(|e| CommandError::IoError {
+                    error: std::io::Error::other(e),
+                })?;
+        let remote_path = result["data"]["path"]
+            .as_str()
+            .ok_or_else(|| CommandError::IoError {
+                error: std::io::Error::other(format!(
+                    "Missing workspace path in response: {}",
+                    result
+                )),
+            })?
+            .to_string();
+
+        tracing::info!(
+            "Synchronized {} ({} at {}) to runner workspace {}",
+            local_path,
+            branch,
+            base_commit,
+            remote_path
+        );
+        Ok(Some(Self {
+            id,
+            local_path: local_path.to_string(),
+            remote_path,
+            branch,
+            base_commit,
+        }))
+    }
+
+    /// Where the worktree lives on the runner
+    pub fn remote_path(&self) -> &str {
+        &self.remote_path
+    }
+
+    /// Fast-forward the local branch to the commits made on the runner since the upload
+    pub async fn download(&self, client: &RunnerClient) -> Result<(), CommandError> {
+        let response = client
+            .send(
+                Method::GET,
+                &format!(
+                    "/workspaces/{}/bundle?branch={}&base={}",
+                    self.id,
+                    urlencoding::encode(&self.branch),
+                    self.base_commit
+                ),
+                None,
+                |error| CommandError::IoError { error },
+            )
+            .await?;
+        let response = runner_response(response).await?;
+        if response.status() == reqwest::StatusCode::NO_CONTENT {
+            return Ok(()); // nothing new
+        }
+        let bytes = response.bytes().await.map_err(|e| CommandError::IoError {
+            error: std::io::Error::other(e),
+        })?;
+
+        let dir = Path::new(&self.local_path);
+        let bundle = temp_bundle_path();
+        let bundle_arg = bundle.to_string_lossy().to_string();
+        let fetched = match std::fs::write(&bundle, &bytes) {
+            Ok(()) => git(dir, &["fetch", "--quiet", &bundle_arg, &self.branch]).await,
+            Err(e) => Err(e.into()),
+        };
+        let _ = std::fs::remove_file(&bundle);
+        fetched?;
+        git(dir, &["merge", "--ff-only", "--quiet", "FETCH_HEAD"]).await?;
+
+        tracing::info!(
+            "Fetched commits on {} from runner workspace {}",
+            self.branch,
+            self.remote_path
+        );
+        Ok(())
+    }
+
+    /// Delete the runner's copy of the worktree at `local_path`
+    pub async fn release(client: &RunnerClient, local_path: &str) -> Result<(), CommandError> {
+        let response = client
+            .send(
+                Method::DELETE,
+                &format!("/workspaces/{}", workspace_id(local_path)),
+                None,
+                |error| CommandError::IoError { error },
+            )
+            .await?;
+        match response.status() {
+            reqwest::StatusCode::NOT_FOUND => Ok(()),
+            _ => runner_response(response).await.map(|_| ()),
+        }
+    }
+}
+
+/// Workspaces a runner materializes from uploaded bundles, under one root directory
+#[derive(Debug, Clone)]
+pub struct RunnerWorkspaces {
+    root: PathBuf,
+}
+
+impl RunnerWorkspaces {
+    /// Workspaces under `CLOUD_RUNNER_WORKSPACE_ROOT`, or a directory in the system temp dir
+    #[allow(dead_code)]
+    pub fn from_env() -> std::io::Result<Self> {
+        let root = std::env::var("CLOUD_RUNNER_WORKSPACE_ROOT")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| std::env::temp_dir().join("vibe-kanban-runner"));
+        Self::new(root)
+    }
+
+    pub fn new(root: PathBuf) -> std::io::Result<Self> {
+        std::fs::create_dir_all(&root)?;
+        Ok(Self {
+            root: std::fs::canonicalize(root)?,
+        })
+    }
+
+    #[allow(dead_code)]
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Check out `branch` from `bundle` in workspace `id`, creating it or bringing it up to date
+    ///
+    /// An existing workspace is fast-forwarded, left as is when it is already ahead (e.g.
+    /// holding commits an earlier command made that were not fetched back yet), and reset to the
+    /// uploaded branch when that was rewritten, e.g. rebased, locally.
+    pub async fn materialize(
+        &self,
+        id: &str,
+        branch: &str,
+        bundle: &[u8],
+    ) -> Result<PathBuf, CommandError> {
+        let dir = self.workspace_dir(id)?;
+        check_branch(branch).await?;
+
+        let bundle_path = self.root.join(format!("{}.bundle", id));
+        std::fs::write(&bundle_path, bundle)?;
+        let bundle_arg = bundle_path.to_string_lossy().to_string();
+        let dir_arg = dir.to_string_lossy().to_string();
+
+        let result = if dir.exists() {
+            match git(&dir, &["fetch", "--quiet", &bundle_arg, branch]).await {
+                Ok(()) => update_to_fetched(&dir).await,
+                Err(e) => Err(e),
+            }
+        } else {
+            git(
+                &self.root,
+                &[
+                    "clone",
+                    "--quiet",
+                    "--branch",
+                    branch,
+                    &bundle_arg,
+                    &dir_arg,
+                ],
+            )
+            .await
+        };
+        let _ = std::fs::remove_file(&bundle_path);
+        result.map(|_| dir)
+    }
+
+    /// Commit what workspace `id` left uncommitted on `branch`, then bundle its commits since
+    /// `base`; `None` when there are none
+    pub async fn export(
+        &self,
+        id: &str,
+        branch: &str,
+        base: &str,
+    ) -> Result<Option<Vec<u8>>, CommandError> {
+        let dir = self.existing_workspace_dir(id)?;
+        check_branch(branch).await?;
+        if base.is_empty() || !base.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(CommandError::NotPermitted {
+                message: format!("Invalid base commit '{}'", base),
+            });
+        }
+
+        git(&dir, &["add", "--all"]).await?;
+        if git(&dir, &["diff", "--cached", "--quiet"]).await.is_err() {
+            let mut args = RUNNER_GIT_IDENTITY.to_vec();
+            args.extend([
+                "commit",
+                "--quiet",
+                "--no-verify",
+                "-m",
+                "Uncommitted changes from remote execution",
+            ]);
+            git(&dir, &args).await?;
+        }
+
+        let tip = git_output(&dir, &["rev-parse", branch]).await?;
+        if tip.trim() == base {
+            return Ok(None);
+        }
+
+        let bundle_path = self.root.join(format!("{}.export.bundle", id));
+        let bundle_arg = bundle_path.to_string_lossy().to_string();
+        let exclude = format!("^{}", base);
+        let created = git(&dir, &["bundle", "create", &bundle_arg, branch, &exclude]).await;
+        let bytes = created.and(std::fs::read(&bundle_path).map_err(CommandError::from));
+        let _ = std::fs::remove_file(&bundle_path);
+        bytes.map(Some)
+    }
+
+    /// Delete workspace `id`; `false` when it does not exist
+    pub fn remove(&self, id: &str) -> Result<bool, CommandError> {
+        let dir = self.workspace_dir(id)?;
+        if !dir.exists() {
+            return Ok(false);
+        }
+        std::fs::remove_dir_all(dir)?;
+        Ok(true)
+    }
+
+    fn workspace_dir(&self, id: &str) -> Result<PathBuf, CommandError> {
+        if id.is_empty() || id.len() > 64 || !id.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(CommandError::NotPermitted {
+                message: format!("Invalid workspace id '{}'", id),
+            });
+        }
+        Ok(self.root.join(id))
+    }
+
+    fn existing_workspace_dir(&self, id: &str) -> Result<PathBuf, CommandError> {
+        let dir = self.workspace_dir(id)?;
+        if !dir.exists() {
+            return Err(CommandError::IoError {
+                error: std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("Workspace {} does not exist", id),
+                ),
+            });
+        }
+        Ok(dir)
+    }
+}
+
+/// Bring the workspace at `dir` up to the branch just fetched into `FETCH_HEAD`
+async fn update_to_fetched(dir: &Path) -> Result<(), CommandError> {
+    let ahead = git(dir, &["merge-base", "--is-ancestor", "FETCH_HEAD", "HEAD"]).await;
+    if ahead.is_ok() {
+        return Ok(());
+    }
+    let behind = git(dir, &["merge-base", "--is-ancestor", "HEAD", "FETCH_HEAD"]).await;
+    if behind.is_ok() {
+        git(dir, &["merge", "--ff-only", "--quiet", "FETCH_HEAD"]).await
+    } else {
+        git(dir, &["reset", "--hard", "--quiet", "FETCH_HEAD"]).await
+    }
+}
+
+/// Warn about what the worktree at `dir` holds that the upload leaves behind
+async fn warn_about_local_state(dir: &Path) {
+    let uncommitted = git_output(dir, &["status", "--porcelain"])
+        .await
+        .unwrap_or_default();
+    if !uncommitted.trim().is_empty() {
+        tracing::warn!(
+            "{} has uncommitted changes, which are not sent to the runner",
+            dir.display()
+        );
+    }
+    if dir.join(STAGING_DIR).exists() {
+        tracing::warn!(
+            "Attachments staged in {} are git-ignored and not sent to the runner",
+            dir.join(STAGING_DIR).display()
+        );
+    }
+}
+
+/// Runners key workspaces by the local worktree they mirror
+fn workspace_id(local_path: &str) -> String {
+    hex::encode(&Sha256::digest(local_path.as_bytes())[..16])
+}
+
+fn temp_bundle_path() -> PathBuf {
+    std::env::temp_dir().join(format!("vibe-kanban-{}.bundle", Uuid::new_v4()))
+}
+
+/// Reject branch names git would not accept, or that could be read as options
+async fn check_branch(branch: &str) -> Result<(), CommandError> {
+    let valid = !branch.starts_with('-')
+        && tokio::process::Command::new("git")
+            .args(["check-ref-format", "--branch", branch])
+            .output()
+            .await
+            .is_ok_and(|output| output.status.success());
+    if valid {
+        Ok(())
+    } else {
+        Err(CommandError::NotPermitted {
+            message: format!("Invalid branch name '{}'", branch),
+        })
+    }
+}
+
+/// Turn the runner refusing a workspace request into an error
+async fn runner_response(response: reqwest::Response) -> Result<reqwest::Response, CommandError> {
+    if response.status().is_success() {
+        return Ok(response);
+    }
+    let status = response.status();
+    let message = response
+        .json::<serde_json::Value>()
+        .await
+        .ok()
+        .and_then(|body| body["error"].as_str().map(str::to_string))
+        .unwrap_or_else(|| status.to_string());
+    Err(CommandError::IoError {
+        error: std::io::Error::other(format!("Runner workspace request failed: {}", message)),
+    })
+}
+
+async fn git(dir: &Path, args: &[&str]) -> Result<(), CommandError> {
+    git_output(dir, args).await.map(|_| ())
+}
+
+async fn git_output(dir: &Path, args: &[&str]) -> Result<String, CommandError> {
+    let output = tokio::process::Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .await?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        Err(CommandError::IoError {
+            error: std::io::Error::other(format!(
+                "git {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn run_git(dir: &Path, args: &[&str]) -> String {
+        let mut full: Vec<&str> = RUNNER_GIT_IDENTITY.to_vec();
+        full.extend(args);
+        git_output(dir, &full).await.expect("git should succeed")
+    }
+
+    #[tokio::test]
+    async fn workspaces_round_trip_commits_through_bundles() {
+        let local = tempfile::tempdir().unwrap();
+        let runner = tempfile::tempdir().unwrap();
+        run_git(
+            local.path(),
+            &["init", "--quiet", "--initial-branch", "attempt"],
+        )
+        .await;
+        std::fs::write(local.path().join("README.md"), "hello\n").unwrap();
+        run_git(local.path(), &["add", "README.md"]).await;
+        run_git(local.path(), &["commit", "--quiet", "-m", "Initial"]).await;
+        let base = run_git(local.path(), &["rev-parse", "HEAD"]).await;
+        let base = base.trim();
+
+        let bundle_path = local.path().join("upload.bundle");
+        let bundle_arg = bundle_path.to_string_lossy().to_string();
+        run_git(local.path(), &["bundle", "create", &bundle_arg, "attempt"]).await;
+        let workspaces = RunnerWorkspaces::new(runner.path().join("workspaces")).unwrap();
+        let dir = workspaces
+            .materialize("abc123", "attempt", &std::fs::read(&bundle_path).unwrap())
+            .await
+            .expect("Should materialize workspace");
+        assert_eq!(
+            std::fs::read_to_string(dir.join("README.md")).unwrap(),
+            "hello\n"
+        );
+
+        // Nothing changed yet
+        assert!(workspaces
+            .export("abc123", "attempt", base)
+            .await
+            .unwrap()
+            .is_none());
+
+        // An agent edits without committing
+        std::fs::write(dir.join("README.md"), "hello from the runner\n").unwrap();
+        let bundle = workspaces
+            .export("abc123", "attempt", base)
+            .await
+            .unwrap()
+            .expect("Should bundle the new commit");
+
+        std::fs::write(&bundle_path, bundle).unwrap();
+        run_git(local.path(), &["fetch", "--quiet", &bundle_arg, "attempt"]).await;
+        run_git(
+            local.path(),
+            &["merge", "--ff-only", "--quiet", "FETCH_HEAD"],
+        )
+        .await;
+        assert_eq!(
+            std::fs::read_to_string(local.path().join("README.md")).unwrap(),
+            "hello from the runner\n"
+        );
+
+        assert!(workspaces.remove("abc123").unwrap());
+        assert!(!workspaces.remove("abc123").unwrap());
+    }
+
+    #[tokio::test]
+    async fn workspaces_follow_rewritten_branches() {
+        let local = tempfile::tempdir().unwrap();
+        let runner = tempfile::tempdir().unwrap();
+        let workspaces = RunnerWorkspaces::new(runner.path().to_path_buf()).unwrap();
+        let bundle_path = local.path().join("upload.bundle");
+        let bundle_arg = bundle_path.to_string_lossy().to_string();
+        let upload = || async {
+            run_git(local.path(), &["bundle", "create", &bundle_arg, "attempt"]).await;
+            workspaces
+                .materialize("abc123", "attempt", &std::fs::read(&bundle_path).unwrap())
+                .await
+                .expect("Should materialize workspace")
+        };
+
+        run_git(
+            local.path(),
+            &["init", "--quiet", "--initial-branch", "attempt"],
+        )
+        .await;
+        std::fs::write(local.path().join("README.md"), "first\n").unwrap();
+        run_git(local.path(), &["add", "README.md"]).await;
+        run_git(local.path(), &["commit", "--quiet", "-m", "First"]).await;
+        upload().await;
+
+        // The branch is rewritten locally, as a rebase would
+        std::fs::write(local.path().join("README.md"), "rewritten\n").unwrap();
+        run_git(
+            local.path(),
+            &["commit", "--quiet", "--all", "--amend", "-m", "Rewritten"],
+        )
+        .await;
+        let dir = upload().await;
+        assert_eq!(
+            std::fs::read_to_string(dir.join("README.md")).unwrap(),
+            "rewritten\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn workspaces_reject_unsafe_ids_and_branches() {
+        let runner = tempfile::tempdir().unwrap();
+        let workspaces = RunnerWorkspaces::new(runner.path().to_path_buf()).unwrap();
+
+        assert!(matches!(
+            workspaces.materialize("../escape", "main", b"").await,
+            Err(CommandError::NotPermitted { .. })
+        ));
+        assert!(matches!(
+            workspaces.materialize("abc", "--upload-pack=sh", b"").await,
+            Err(CommandError::NotPermitted { .. })
+        ));
+    }
+}
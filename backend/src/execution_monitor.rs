@@ -3,12 +3,17 @@ use uuid::Uuid;
 
 use crate::{
     app_state::AppState,
+    command_runner::{self, RemoteWorkspace, RunnerClient},
     models::{
         execution_process::{ExecutionProcess, ExecutionProcessStatus, ExecutionProcessType},
+        pipeline::{StageStatus, TaskAttemptStage},
+        project::Project,
+        review_finding::{parse_findings, ReportedFinding, ReviewFinding, ReviewSeverity},
         task::{Task, TaskStatus},
         task_attempt::TaskAttempt,
+        token_usage::ExecutionProcessUsage,
     },
-    services::{NotificationConfig, NotificationService, ProcessService},
+    services::{LogStreamService, NotificationConfig, NotificationService, ProcessService},
     utils::worktree_manager::WorktreeManager,
 };
 
@@ -86,6 +91,16 @@ async fn handle_setup_delegation(app_state: &AppState, delegation_context: Deleg
             )
             .await
         }
+        "planning_agent" => {
+            ProcessService::start_planning_agent(
+                &app_state.db_pool,
+                app_state,
+                attempt_id,
+                task_id,
+                project_id,
+            )
+            .await
+        }
         "followup" => {
             let prompt = params
                 .additional
@@ -129,11 +144,13 @@ async fn handle_setup_delegation(app_state: &AppState, delegation_context: Deleg
 }
 
 /// Commit any unstaged changes in the worktree after execution completion
+///
+/// Returns the worktree's HEAD commit afterwards.
 async fn commit_execution_changes(
     worktree_path: &str,
     attempt_id: Uuid,
     summary: Option<&str>,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
     // Run git operations in a blocking task since git2 is synchronous
     let worktree_path = worktree_path.to_string();
     let summary = summary.map(|s| s.to_string());
@@ -153,7 +170,8 @@ async fn commit_execution_changes(
         });
 
         if !has_changes {
-            return Ok::<(), Box<dyn std::error::Error + Send + Sync>>(());
+            let head = worktree_repo.head()?.peel_to_commit()?;
+            return Ok::<String, Box<dyn std::error::Error + Send + Sync>>(head.id().to_string());
         }
 
         // Get the current signature for commits
@@ -177,7 +195,7 @@ async fn commit_execution_changes(
         } else {
             format!("Task attempt {} - Final changes", attempt_id)
         };
-        worktree_repo.commit(
+        let commit_id = worktree_repo.commit(
             Some("HEAD"),
             &signature,
             &signature,
@@ -186,11 +204,9 @@ async fn commit_execution_changes(
             &[&parent_commit],
         )?;
 
-        Ok(())
+        Ok(commit_id.to_string())
     })
-    .await??;
-
-    Ok(())
+    .await?
 }
 
 /// Check if worktree has uncommitted changes and warn if so
@@ -216,6 +232,23 @@ async fn delete_worktree(
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let worktree_path_buf = std::path::PathBuf::from(worktree_path);
 
+    // Remote runners keep their own copy of the worktree; drop it on a best-effort basis
+    if command_runner::current_environment().is_cloud() {
+        let released = match RunnerClient::from_env() {
+            Ok(client) => RemoteWorkspace::release(&client, worktree_path)
+                .await
+                .map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        if let Err(e) = released {
+            tracing::warn!(
+                "Failed to release runner workspace for attempt {}: {}",
+                attempt_id,
+                e
+            );
+        }
+    }
+
     // Check if worktree directory exists first - no-op if already gone
     if !worktree_path_buf.exists() {
         tracing::debug!(
@@ -252,8 +285,32 @@ pub async fn cleanup_task_worktrees(
     pool: &sqlx::SqlitePool,
     task_id: Uuid,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let task_attempts_with_project =
-        TaskAttempt::find_by_task_id_with_project(pool, task_id).await?;
+    cleanup_worktrees(pool, task_id, |_| true).await
+}
+
+/// Clean up the worktrees of selected attempts of a task (immediate cleanup)
+pub async fn cleanup_attempt_worktrees(
+    pool: &sqlx::SqlitePool,
+    task_id: Uuid,
+    attempt_ids: &[Uuid],
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    cleanup_worktrees(pool, task_id, |attempt_id| {
+        attempt_ids.contains(&attempt_id)
+    })
+    .await
+}
+
+async fn cleanup_worktrees(
+    pool: &sqlx::SqlitePool,
+    task_id: Uuid,
+    include: impl Fn(Uuid) -> bool,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let task_attempts_with_project: Vec<_> =
+        TaskAttempt::find_by_task_id_with_project(pool, task_id)
+            .await?
+            .into_iter()
+            .filter(|(attempt_id, _, _)| include(*attempt_id))
+            .collect();
 
     if task_attempts_with_project.is_empty() {
         tracing::debug!("No worktrees found for task {} to clean up", task_id);
@@ -518,106 +575,13 @@ pub async fn execution_monitor(app_state: AppState) {
     loop {
         tokio::select! {
             _ = interval.tick() => {
-                // Check for completed processes FIRST to avoid race conditions
-                let completed_executions = app_state.get_running_executions_for_monitor().await;
-
-                // Handle completed executions
-                for (execution_process_id, task_attempt_id, success, exit_code) in completed_executions {
-                    let status_text = if success {
-                        "completed successfully"
-                    } else {
-                        "failed"
-                    };
-                    let exit_text = if let Some(code) = exit_code {
-                        format!(" with exit code {}", code)
-                    } else {
-                        String::new()
-                    };
-
-                    tracing::info!(
-                        "Execution {} {}{}",
-                        execution_process_id,
-                        status_text,
-                        exit_text
-                    );
-
-                    // Update the execution process record
-                    let execution_status = if success {
-                        ExecutionProcessStatus::Completed
-                    } else {
-                        ExecutionProcessStatus::Failed
-                    };
+                // Sample usage before finished processes are dropped from the running set
+                record_resource_usage(&app_state).await;
 
-                    if let Err(e) = ExecutionProcess::update_completion(
-                        &app_state.db_pool,
-                        execution_process_id,
-                        execution_status,
-                        exit_code,
-                    )
-                    .await
-                    {
-                        tracing::error!(
-                            "Failed to update execution process {} completion: {}",
-                            execution_process_id,
-                            e
-                        );
-                    }
+                // Check for completed processes FIRST to avoid race conditions
+                handle_completed_executions(&app_state).await;
 
-                    // Get the execution process to determine next steps
-                    if let Ok(Some(execution_process)) =
-                        ExecutionProcess::find_by_id(&app_state.db_pool, execution_process_id).await
-                    {
-                        match execution_process.process_type {
-                            ExecutionProcessType::SetupScript => {
-                                handle_setup_completion(
-                                    &app_state,
-                                    task_attempt_id,
-                                    execution_process,
-                                    success,
-                                )
-                                .await;
-                            }
-                            ExecutionProcessType::CleanupScript => {
-                                handle_cleanup_completion(
-                                    &app_state,
-                                    task_attempt_id,
-                                    execution_process_id,
-                                    execution_process,
-                                    success,
-                                    exit_code,
-                                )
-                                .await;
-                            }
-                            ExecutionProcessType::CodingAgent => {
-                                handle_coding_agent_completion(
-                                    &app_state,
-                                    task_attempt_id,
-                                    execution_process_id,
-                                    execution_process,
-                                    success,
-                                    exit_code,
-                                )
-                                .await;
-                            }
-                            ExecutionProcessType::DevServer => {
-                                handle_dev_server_completion(
-                                    &app_state,
-                                    task_attempt_id,
-                                    execution_process_id,
-                                    execution_process,
-                                    success,
-                                    exit_code,
-                                )
-                                .await;
-                            }
-                        }
-                    } else {
-                        tracing::error!(
-                            "Failed to find execution process {} for completion handling",
-                            execution_process_id
-                        );
-                    }
-                }
+                enforce_execution_limits(&app_state).await;
 
                 // Check for orphaned execution processes AFTER handling completions
                 // Add a small delay to ensure completed processes are properly handled first
@@ -681,10 +645,13 @@ pub async fn execution_monitor(app_state: AppState) {
 
                         tracing::info!("Marked orphaned execution process {} as failed", process.id);
 
-                        // Update task status to InReview for coding agent and setup script failures
+                        // Update task status to InReview for agent and setup script failures
                         if matches!(
                             process.process_type,
-                            ExecutionProcessType::CodingAgent | ExecutionProcessType::SetupScript
+                            ExecutionProcessType::CodingAgent
+                                | ExecutionProcessType::PlanningAgent
+                                | ExecutionProcessType::ReviewAgent
+                                | ExecutionProcessType::SetupScript
                         ) {
                             if let Ok(Some(task_attempt)) =
                                 TaskAttempt::find_by_id(&app_state.db_pool, process.task_attempt_id).await
@@ -747,6 +714,412 @@ pub async fn execution_monitor(app_state: AppState) {
     }
 }
 
+/// Record the executions that have exited and hand each to its completion handler
+async fn handle_completed_executions(app_state: &AppState) {
+    let completed_executions = app_state.get_running_executions_for_monitor().await;
+
+    // Handle completed executions
+    for completed in completed_executions {
+        let execution_process_id = completed.execution_id;
+        let task_attempt_id = completed.task_attempt_id;
+        let success = completed.success;
+        let exit_code = completed.exit_code;
+
+        // Commits made on a remote runner come back before the completion handlers
+        // look at the worktree
+        sync_workspace(app_state, execution_process_id, completed.child).await;
+
+        let status_text = if success {
+            "completed successfully"
+        } else {
+            "failed"
+        };
+        let exit_text = if let Some(code) = exit_code {
+            format!(" with exit code {}", code)
+        } else {
+            String::new()
+        };
+
+        tracing::info!(
+            "Execution {} {}{}",
+            execution_process_id,
+            status_text,
+            exit_text
+        );
+
+        // Update the execution process record
+        let execution_status = if success {
+            ExecutionProcessStatus::Completed
+        } else {
+            ExecutionProcessStatus::Failed
+        };
+
+        if let Err(e) = ExecutionProcess::update_completion(
+            &app_state.db_pool,
+            execution_process_id,
+            execution_status,
+            exit_code,
+        )
+        .await
+        {
+            tracing::error!(
+                "Failed to update execution process {} completion: {}",
+                execution_process_id,
+                e
+            );
+        }
+
+        // Get the execution process to determine next steps
+        if let Ok(Some(execution_process)) =
+            ExecutionProcess::find_by_id(&app_state.db_pool, execution_process_id).await
+        {
+            // Transient agent failures are retried instead of failing the attempt
+            if !success && schedule_retry(app_state, &execution_process, exit_code).await {
+                record_token_usage(app_state, &execution_process).await;
+                continue;
+            }
+
+            match execution_process.process_type {
+                ExecutionProcessType::SetupScript => {
+                    handle_setup_completion(app_state, task_attempt_id, execution_process, success)
+                        .await;
+                }
+                ExecutionProcessType::CleanupScript => {
+                    handle_cleanup_completion(
+                        app_state,
+                        task_attempt_id,
+                        execution_process_id,
+                        execution_process,
+                        success,
+                        exit_code,
+                    )
+                    .await;
+                }
+                ExecutionProcessType::CodingAgent => {
+                    handle_coding_agent_completion(
+                        app_state,
+                        task_attempt_id,
+                        execution_process_id,
+                        execution_process,
+                        success,
+                        exit_code,
+                    )
+                    .await;
+                }
+                ExecutionProcessType::PlanningAgent => {
+                    handle_planning_agent_completion(
+                        app_state,
+                        task_attempt_id,
+                        execution_process_id,
+                        execution_process,
+                        success,
+                        exit_code,
+                    )
+                    .await;
+                }
+                ExecutionProcessType::ReviewAgent => {
+                    handle_review_agent_completion(
+                        app_state,
+                        task_attempt_id,
+                        execution_process_id,
+                        execution_process,
+                        success,
+                        exit_code,
+                    )
+                    .await;
+                }
+                ExecutionProcessType::DevServer => {
+                    handle_dev_server_completion(
+                        app_state,
+                        task_attempt_id,
+                        execution_process_id,
+                        execution_process,
+                        success,
+                        exit_code,
+                    )
+                    .await;
+                }
+            }
+        } else {
+            tracing::error!(
+                "Failed to find execution process {} for completion handling",
+                execution_process_id
+            );
+        }
+    }
+}
+
+/// Attempts at fetching a finished process's remote workspace before giving up
+const WORKSPACE_SYNC_ATTEMPTS: u32 = 3;
+
+/// Bring a finished process's remote changes back into its worktree, retrying failed transfers
+///
+/// A transfer that keeps failing is reported on the process instead of failing it, since the
+/// command itself finished; its commits stay in the runner's workspace.
+async fn sync_workspace(
+    app_state: &AppState,
+    execution_process_id: Uuid,
+    mut child: command_runner::CommandProcess,
+) {
+    let mut delay = std::time::Duration::from_millis(500);
+    for attempt in 1..=WORKSPACE_SYNC_ATTEMPTS {
+        let error = match child.sync_workspace().await {
+            Ok(()) => return,
+            Err(e) => e,
+        };
+        if attempt < WORKSPACE_SYNC_ATTEMPTS {
+            tracing::warn!(
+                "Failed to fetch the workspace of execution process {} (attempt {}): {}",
+                execution_process_id,
+                attempt,
+                error
+            );
+            tokio::time::sleep(delay).await;
+            delay *= 2;
+            continue;
+        }
+
+        tracing::error!(
+            "Failed to fetch the workspace of execution process {}: {}",
+            execution_process_id,
+            error
+        );
+        let message = format!(
+            "\nFailed to fetch the changes made on the runner, they are still in its workspace: {}\n",
+            error
+        );
+        if let Err(e) =
+            ExecutionProcess::append_stderr(&app_state.db_pool, execution_process_id, &message)
+                .await
+        {
+            tracing::error!(
+                "Failed to report workspace fetch failure for execution process {}: {}",
+                execution_process_id,
+                e
+            );
+        }
+    }
+}
+
+/// Store the peak memory and CPU time sampled from each running execution's process group
+async fn record_resource_usage(app_state: &AppState) {
+    for (execution_id, usage) in app_state.sample_resource_usage().await {
+        if let Err(e) = ExecutionProcess::update_resource_usage(
+            &app_state.db_pool,
+            execution_id,
+            usage.peak_memory_bytes,
+            usage.cpu_time_ms,
+        )
+        .await
+        {
+            tracing::error!(
+                "Failed to record resource usage for execution process {}: {}",
+                execution_id,
+                e
+            );
+        }
+    }
+}
+
+/// Stop running processes that exceeded their project's or executor's runtime or idle limit
+///
+/// When both set a limit the stricter one applies. Dev servers are meant to run until stopped
+/// and are never timed out.
+async fn enforce_execution_limits(app_state: &AppState) {
+    let pool = &app_state.db_pool;
+    let running_processes = match ExecutionProcess::find_running(pool).await {
+        Ok(processes) => processes,
+        Err(e) => {
+            tracing::error!("Failed to query running execution processes: {}", e);
+            return;
+        }
+    };
+
+    let now = chrono::Utc::now();
+    for process in running_processes {
+        if process.process_type == ExecutionProcessType::DevServer {
+            continue;
+        }
+
+        let Ok(Some(task_attempt)) = TaskAttempt::find_by_id(pool, process.task_attempt_id).await
+        else {
+            continue;
+        };
+        let Ok(Some(task)) = Task::find_by_id(pool, task_attempt.task_id).await else {
+            continue;
+        };
+        let Ok(Some(project)) = Project::find_by_id(pool, task.project_id).await else {
+            continue;
+        };
+
+        let mut limits = project.execution_limits();
+        if let Some(executor) = process.executor_type.as_deref() {
+            limits = limits.strictest(app_state.get_executor_limits(executor).await);
+        }
+
+        // Output is flushed to the database in batches, so prefer the live log's timestamp
+        let last_output = LogStreamService::last_output_at(process.id)
+            .unwrap_or(process.updated_at)
+            .max(process.started_at);
+        let runtime = (now - process.started_at).to_std().unwrap_or_default();
+        let idle = (now - last_output).to_std().unwrap_or_default();
+        let Some(reason) = limits.exceeded(runtime, idle) else {
+            continue;
+        };
+
+        let outcome = match app_state.stop_running_execution_by_id(process.id).await {
+            Ok(Some(outcome)) => outcome,
+            // Already finished; the completion handler takes it from here
+            Ok(None) => continue,
+            Err(e) => {
+                tracing::error!(
+                    "Failed to stop timed out execution process {}: {}",
+                    process.id,
+                    e
+                );
+                continue;
+            }
+        };
+        tracing::warn!(
+            "Stopped execution process {} for task attempt {} with {:?}: {}",
+            process.id,
+            process.task_attempt_id,
+            outcome.signal,
+            reason
+        );
+
+        if let Err(e) = ExecutionProcess::mark_timed_out(pool, process.id, &reason).await {
+            tracing::error!(
+                "Failed to mark execution process {} as timed out: {}",
+                process.id,
+                e
+            );
+        }
+
+        if matches!(
+            process.process_type,
+            ExecutionProcessType::CodingAgent
+                | ExecutionProcessType::PlanningAgent
+                | ExecutionProcessType::ReviewAgent
+                | ExecutionProcessType::SetupScript
+        ) {
+            finalize_task_completion(app_state, process.task_attempt_id, &task, false, None).await;
+        }
+    }
+}
+
+/// Schedule an automatic retry of a failed agent run when its executor's retry policy marks
+/// the failure as transient
+///
+/// Returns `true` when a retry was scheduled, in which case the failure is not handled any
+/// further. The retry starts after the policy's backoff delay; if it cannot be started the
+/// attempt is finalized as failed then.
+async fn schedule_retry(
+    app_state: &AppState,
+    execution_process: &ExecutionProcess,
+    exit_code: Option<i64>,
+) -> bool {
+    if !matches!(
+        execution_process.process_type,
+        ExecutionProcessType::CodingAgent
+            | ExecutionProcessType::PlanningAgent
+            | ExecutionProcessType::ReviewAgent
+    ) {
+        return false;
+    }
+    let Some(executor) = execution_process.executor_type.as_deref() else {
+        return false;
+    };
+    let Some(policy) = app_state.get_retry_policy(executor).await else {
+        return false;
+    };
+
+    let output = format!(
+        "{}\n{}",
+        execution_process.stdout.as_deref().unwrap_or_default(),
+        execution_process.stderr.as_deref().unwrap_or_default()
+    );
+    if !policy.is_transient(&output) {
+        return false;
+    }
+
+    let original_id = execution_process
+        .retry_of_process_id
+        .unwrap_or(execution_process.id);
+    let retries = match ExecutionProcess::count_retries(&app_state.db_pool, original_id).await {
+        Ok(retries) => retries,
+        Err(e) => {
+            tracing::error!(
+                "Failed to count retries of execution process {}: {}",
+                original_id,
+                e
+            );
+            return false;
+        }
+    };
+    let Some(delay) = policy.delay_before(retries as usize) else {
+        tracing::warn!(
+            "Execution process {} failed transiently but its {} retries are used up",
+            original_id,
+            retries
+        );
+        return false;
+    };
+
+    tracing::info!(
+        "Retrying transient failure of execution process {} in {:?} (retry {} of {})",
+        execution_process.id,
+        delay,
+        retries + 1,
+        policy.max_attempts
+    );
+
+    let app_state = app_state.clone();
+    let failed = execution_process.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(delay).await;
+
+        // Someone started another run on the attempt while we were waiting
+        if app_state
+            .has_running_execution(failed.task_attempt_id)
+            .await
+        {
+            tracing::info!(
+                "Skipping retry of execution process {}: task attempt {} is already running",
+                failed.id,
+                failed.task_attempt_id
+            );
+            return;
+        }
+
+        if let Err(e) =
+            ProcessService::retry_execution(&app_state.db_pool, &app_state, &failed, original_id)
+                .await
+        {
+            tracing::error!("Failed to retry execution process {}: {}", failed.id, e);
+            let task =
+                match TaskAttempt::find_by_id(&app_state.db_pool, failed.task_attempt_id).await {
+                    Ok(Some(task_attempt)) => {
+                        Task::find_by_id(&app_state.db_pool, task_attempt.task_id).await
+                    }
+                    _ => Ok(None),
+                };
+            if let Ok(Some(task)) = task {
+                finalize_task_completion(
+                    &app_state,
+                    failed.task_attempt_id,
+                    &task,
+                    false,
+                    exit_code,
+                )
+                .await;
+            }
+        }
+    });
+
+    true
+}
+
 /// Handle setup script completion
 async fn handle_setup_completion(
     app_state: &AppState,
@@ -830,6 +1203,37 @@ async fn handle_setup_completion(
     }
 }
 
+/// Extract token usage from a finished coding agent's output and store it
+async fn record_token_usage(app_state: &AppState, execution_process: &ExecutionProcess) {
+    let (Some(stdout), Some(executor_type)) = (
+        &execution_process.stdout,
+        execution_process.executor_type.as_deref(),
+    ) else {
+        return;
+    };
+    let Ok(executor_config) = executor_type.parse::<crate::executor::ExecutorConfig>() else {
+        return;
+    };
+
+    let Some(usage) = executor_config
+        .create_executor()
+        .extract_token_usage(stdout)
+        .filter(|usage| !usage.is_empty())
+    else {
+        return;
+    };
+
+    if let Err(e) =
+        ExecutionProcessUsage::upsert(&app_state.db_pool, execution_process.id, &usage).await
+    {
+        tracing::error!(
+            "Failed to store token usage for execution process {}: {}",
+            execution_process.id,
+            e
+        );
+    }
+}
+
 /// Handle coding agent completion
 async fn handle_coding_agent_completion(
     app_state: &AppState,
@@ -870,6 +1274,8 @@ async fn handle_coding_agent_completion(
         None
     };
 
+    record_token_usage(app_state, &execution_process).await;
+
     // Note: Notifications and status updates moved to cleanup completion handler
     // to ensure they only fire after all processing (including cleanup) is complete
 
@@ -878,23 +1284,39 @@ async fn handle_coding_agent_completion(
         TaskAttempt::find_by_id(&app_state.db_pool, task_attempt_id).await
     {
         // Commit any unstaged changes after execution completion
-        if let Err(e) = commit_execution_changes(
+        match commit_execution_changes(
             &task_attempt.worktree_path,
             task_attempt_id,
             summary.as_deref(),
         )
         .await
         {
-            tracing::error!(
+            Ok(head_commit) => {
+                tracing::info!(
+                    "Successfully committed execution changes for attempt {}",
+                    task_attempt_id
+                );
+                // Later attempts can fork from this point
+                if let Err(e) =
+                    crate::models::executor_session::ExecutorSession::update_after_head_commit(
+                        &app_state.db_pool,
+                        execution_process_id,
+                        &head_commit,
+                    )
+                    .await
+                {
+                    tracing::error!(
+                        "Failed to record head commit for execution process {}: {}",
+                        execution_process_id,
+                        e
+                    );
+                }
+            }
+            Err(e) => tracing::error!(
                 "Failed to commit execution changes for attempt {}: {}",
                 task_attempt_id,
                 e
-            );
-        } else {
-            tracing::info!(
-                "Successfully committed execution changes for attempt {}",
-                task_attempt_id
-            );
+            ),
         }
 
         // Coding agent execution completed
@@ -905,45 +1327,25 @@ async fn handle_coding_agent_completion(
 
         // Run cleanup script if configured, otherwise immediately finalize task
         if let Ok(Some(task)) = Task::find_by_id(&app_state.db_pool, task_attempt.task_id).await {
-            // Check if cleanup script should run
-            let should_run_cleanup = if let Ok(Some(project)) =
-                crate::models::project::Project::find_by_id(&app_state.db_pool, task.project_id)
-                    .await
+            // Pipeline stages run one after another before cleanup
+            if advance_pipeline(
+                app_state,
+                &task,
+                task_attempt_id,
+                execution_process_id,
+                success,
+            )
+            .await
             {
-                project
-                    .cleanup_script
-                    .as_ref()
-                    .map(|script| !script.trim().is_empty())
-                    .unwrap_or(false)
-            } else {
-                false
-            };
+                return;
+            }
 
-            if should_run_cleanup {
-                // Run cleanup script - completion will be handled in cleanup completion handler
-                if let Err(e) =
-                    crate::services::process_service::ProcessService::run_cleanup_script_if_configured(
-                        &app_state.db_pool,
-                        app_state,
-                        task_attempt_id,
-                        task_attempt.task_id,
-                        task.project_id,
-                    )
-                    .await
-                {
-                    tracing::error!(
-                        "Failed to run cleanup script for attempt {}: {}",
-                        task_attempt_id,
-                        e
-                    );
-                    // Even if cleanup fails to start, finalize the task
-                    finalize_task_completion(app_state, task_attempt_id, &task, success, exit_code).await;
-                }
-            } else {
-                // No cleanup script configured, immediately finalize task
-                finalize_task_completion(app_state, task_attempt_id, &task, success, exit_code)
-                    .await;
+            // A successful run is reviewed before cleanup when a reviewer is configured
+            if success && start_configured_review(app_state, &task, task_attempt_id).await {
+                return;
             }
+
+            run_cleanup_or_finalize(app_state, task_attempt_id, &task, success, exit_code).await;
         }
     } else {
         tracing::error!(
@@ -953,6 +1355,388 @@ async fn handle_coding_agent_completion(
     }
 }
 
+/// Run the project's cleanup script, or finalize the task straight away when there is none
+async fn run_cleanup_or_finalize(
+    app_state: &AppState,
+    task_attempt_id: Uuid,
+    task: &Task,
+    success: bool,
+    exit_code: Option<i64>,
+) {
+    // Check if cleanup script should run
+    let should_run_cleanup = if let Ok(Some(project)) =
+        crate::models::project::Project::find_by_id(&app_state.db_pool, task.project_id).await
+    {
+        project
+            .cleanup_script
+            .as_ref()
+            .map(|script| !script.trim().is_empty())
+            .unwrap_or(false)
+    } else {
+        false
+    };
+
+    if should_run_cleanup {
+        // Run cleanup script - completion will be handled in cleanup completion handler
+        if let Err(e) =
+            crate::services::process_service::ProcessService::run_cleanup_script_if_configured(
+                &app_state.db_pool,
+                app_state,
+                task_attempt_id,
+                task.id,
+                task.project_id,
+            )
+            .await
+        {
+            tracing::error!(
+                "Failed to run cleanup script for attempt {}: {}",
+                task_attempt_id,
+                e
+            );
+            // Even if cleanup fails to start, finalize the task
+            finalize_task_completion(app_state, task_attempt_id, task, success, exit_code).await;
+        }
+    } else {
+        // No cleanup script configured, immediately finalize task
+        finalize_task_completion(app_state, task_attempt_id, task, success, exit_code).await;
+    }
+}
+
+/// Record the outcome of a pipeline stage and start the attempt's next stage
+///
+/// Returns `true` when another stage was started, in which case cleanup and finalization
+/// wait for the pipeline to finish.
+async fn advance_pipeline(
+    app_state: &AppState,
+    task: &Task,
+    task_attempt_id: Uuid,
+    execution_process_id: Uuid,
+    success: bool,
+) -> bool {
+    let stage = match TaskAttemptStage::find_by_execution_process_id(
+        &app_state.db_pool,
+        execution_process_id,
+    )
+    .await
+    {
+        Ok(Some(stage)) => stage,
+        Ok(None) => return false,
+        Err(e) => {
+            tracing::error!(
+                "Failed to find pipeline stage for execution process {}: {}",
+                execution_process_id,
+                e
+            );
+            return false;
+        }
+    };
+
+    let status = if success {
+        StageStatus::Completed
+    } else {
+        StageStatus::Failed
+    };
+    if let Err(e) =
+        TaskAttemptStage::update_status(&app_state.db_pool, stage.id, status, None).await
+    {
+        tracing::error!("Failed to update pipeline stage {} status: {}", stage.id, e);
+    }
+
+    match ProcessService::start_next_pipeline_stage(
+        &app_state.db_pool,
+        app_state,
+        task_attempt_id,
+        task.id,
+        task.project_id,
+        success,
+    )
+    .await
+    {
+        Ok(started) => started,
+        Err(e) => {
+            tracing::error!(
+                "Failed to start next pipeline stage for attempt {}: {}",
+                task_attempt_id,
+                e
+            );
+            false
+        }
+    }
+}
+
+/// Pick an attempt's pipeline up again once the check gating `stage` has finished
+///
+/// The stage runs when its check failed and is skipped otherwise. Once no stage is left the
+/// attempt is reviewed, cleaned up and finalized as after its last stage.
+pub async fn handle_pipeline_check_result(
+    app_state: &AppState,
+    task_id: Uuid,
+    stage: TaskAttemptStage,
+    check_passed: bool,
+    previous_succeeded: bool,
+) {
+    let task_attempt_id = stage.task_attempt_id;
+    let task = match Task::find_by_id(&app_state.db_pool, task_id).await {
+        Ok(Some(task)) => task,
+        Ok(None) => {
+            tracing::error!("Failed to find task {} for pipeline check", task_id);
+            return;
+        }
+        Err(e) => {
+            tracing::error!("Failed to find task {} for pipeline check: {}", task_id, e);
+            return;
+        }
+    };
+
+    let started = match ProcessService::continue_pipeline_after_check(
+        &app_state.db_pool,
+        app_state,
+        task.id,
+        task.project_id,
+        &stage,
+        check_passed,
+        previous_succeeded,
+    )
+    .await
+    {
+        Ok(started) => started,
+        Err(e) => {
+            tracing::error!(
+                "Failed to continue pipeline for attempt {} after stage '{}' check: {}",
+                task_attempt_id,
+                stage.name,
+                e
+            );
+            false
+        }
+    };
+    if started {
+        return;
+    }
+
+    if previous_succeeded && start_configured_review(app_state, &task, task_attempt_id).await {
+        return;
+    }
+    run_cleanup_or_finalize(app_state, task_attempt_id, &task, previous_succeeded, None).await;
+}
+
+/// Start a review of the attempt's changes with the configured review executor
+///
+/// Returns `true` when a review was started, in which case cleanup and finalization wait for
+/// it to finish.
+async fn start_configured_review(app_state: &AppState, task: &Task, task_attempt_id: Uuid) -> bool {
+    let Some(config) = app_state.get_review_executor().await else {
+        return false;
+    };
+
+    match ProcessService::start_review_agent(
+        &app_state.db_pool,
+        app_state,
+        task_attempt_id,
+        task.id,
+        task.project_id,
+        config,
+    )
+    .await
+    {
+        Ok(started) => started,
+        Err(e) => {
+            tracing::error!(
+                "Failed to start code review for attempt {}: {}",
+                task_attempt_id,
+                e
+            );
+            false
+        }
+    }
+}
+
+/// Handle review agent completion
+///
+/// The reviewer's final message is parsed into findings, and a failed review is recorded as an
+/// error finding so it is not mistaken for a clean one. Reviews do not change the worktree, so
+/// the attempt then continues with cleanup and finalization as a coding run would.
+async fn handle_review_agent_completion(
+    app_state: &AppState,
+    task_attempt_id: Uuid,
+    execution_process_id: Uuid,
+    execution_process: ExecutionProcess,
+    success: bool,
+    exit_code: Option<i64>,
+) {
+    record_token_usage(app_state, &execution_process).await;
+    record_review_findings(app_state, task_attempt_id, &execution_process).await;
+    if !success {
+        tracing::warn!(
+            "Code review {} for attempt {} failed with exit code {:?}",
+            execution_process_id,
+            task_attempt_id,
+            exit_code
+        );
+        record_review_failure(app_state, task_attempt_id, execution_process_id, exit_code).await;
+    }
+
+    let task = match TaskAttempt::find_by_id(&app_state.db_pool, task_attempt_id).await {
+        Ok(Some(task_attempt)) => Task::find_by_id(&app_state.db_pool, task_attempt.task_id).await,
+        _ => Ok(None),
+    };
+    match task {
+        // A review only runs after a successful coding run, so its own outcome does not fail
+        // the attempt
+        Ok(Some(task)) => {
+            run_cleanup_or_finalize(app_state, task_attempt_id, &task, true, None).await
+        }
+        _ => tracing::error!(
+            "Failed to find task for review agent completion of attempt {}",
+            task_attempt_id
+        ),
+    }
+}
+
+/// Parse the findings out of a finished review's output and store them
+async fn record_review_findings(
+    app_state: &AppState,
+    task_attempt_id: Uuid,
+    execution_process: &ExecutionProcess,
+) {
+    let (Some(stdout), Some(executor_type)) = (
+        &execution_process.stdout,
+        execution_process.executor_type.as_deref(),
+    ) else {
+        return;
+    };
+    let Ok(executor_config) = executor_type.parse::<crate::executor::ExecutorConfig>() else {
+        return;
+    };
+
+    let conversation = match executor_config
+        .create_executor()
+        .normalize_logs(stdout, &execution_process.working_directory)
+    {
+        Ok(conversation) => conversation,
+        Err(e) => {
+            tracing::error!(
+                "Failed to normalize review logs for execution process {}: {}",
+                execution_process.id,
+                e
+            );
+            return;
+        }
+    };
+    // Reviews in Claude's plan mode answer through ExitPlanMode rather than a final message
+    let Some(review) = crate::executor::extract_plan(&conversation.entries) else {
+        return;
+    };
+
+    let findings = parse_findings(&review);
+    if let Err(e) = ReviewFinding::create_many(
+        &app_state.db_pool,
+        task_attempt_id,
+        execution_process.id,
+        &findings,
+    )
+    .await
+    {
+        tracing::error!(
+            "Failed to store review findings for execution process {}: {}",
+            execution_process.id,
+            e
+        );
+    } else {
+        tracing::info!(
+            "Stored {} review findings for attempt {}",
+            findings.len(),
+            task_attempt_id
+        );
+    }
+}
+
+/// Record that a review did not finish, so its findings are known to be incomplete
+async fn record_review_failure(
+    app_state: &AppState,
+    task_attempt_id: Uuid,
+    execution_process_id: Uuid,
+    exit_code: Option<i64>,
+) {
+    let message = match exit_code {
+        Some(code) => format!(
+            "The review failed with exit code {}, so its findings may be incomplete",
+            code
+        ),
+        None => "The review did not finish, so its findings may be incomplete".to_string(),
+    };
+    let failure = ReportedFinding {
+        file: String::new(),
+        start_line: None,
+        end_line: None,
+        severity: ReviewSeverity::Error,
+        message,
+    };
+    if let Err(e) = ReviewFinding::create_many(
+        &app_state.db_pool,
+        task_attempt_id,
+        execution_process_id,
+        &[failure],
+    )
+    .await
+    {
+        tracing::error!(
+            "Failed to record review failure for execution process {}: {}",
+            execution_process_id,
+            e
+        );
+    }
+}
+
+/// Handle planning agent completion
+///
+/// Planning runs are read-only, so there is nothing to commit or clean up: the task goes
+/// straight to review, where the plan is approved for implementation.
+async fn handle_planning_agent_completion(
+    app_state: &AppState,
+    task_attempt_id: Uuid,
+    execution_process_id: Uuid,
+    execution_process: ExecutionProcess,
+    success: bool,
+    exit_code: Option<i64>,
+) {
+    if let Some(assistant_message) = execution_process
+        .stdout
+        .as_deref()
+        .and_then(crate::executor::parse_assistant_message_from_logs)
+    {
+        if let Err(e) = crate::models::executor_session::ExecutorSession::update_summary(
+            &app_state.db_pool,
+            execution_process_id,
+            &assistant_message,
+        )
+        .await
+        {
+            tracing::error!(
+                "Failed to update summary for execution process {}: {}",
+                execution_process_id,
+                e
+            );
+        }
+    }
+
+    record_token_usage(app_state, &execution_process).await;
+
+    let task = match TaskAttempt::find_by_id(&app_state.db_pool, task_attempt_id).await {
+        Ok(Some(task_attempt)) => Task::find_by_id(&app_state.db_pool, task_attempt.task_id).await,
+        _ => Ok(None),
+    };
+    match task {
+        Ok(Some(task)) => {
+            finalize_task_completion(app_state, task_attempt_id, &task, success, exit_code).await
+        }
+        _ => tracing::error!(
+            "Failed to find task for planning agent completion of attempt {}",
+            task_attempt_id
+        ),
+    }
+}
+
 /// Finalize task completion with notifications and status updates
 async fn finalize_task_completion(
     app_state: &AppState,
@@ -1191,3 +1975,113 @@ async fn handle_dev_server_completion(
         );
     }
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::{
+        path::Path,
+        time::{Duration, Instant},
+    };
+
+    use super::*;
+    use crate::{
+        models::{
+            executor_session::ExecutorSession,
+            task_attempt::{CreateTaskAttempt, DiffChunkType},
+        },
+        test_utils::{insert_project, insert_task, test_app_state},
+    };
+
+    #[tokio::test]
+    async fn test_mock_run_is_committed_and_shows_in_the_diff() {
+        let repo_dir = tempfile::TempDir::new().unwrap();
+        let repo = Repository::init(repo_dir.path()).unwrap();
+        let mut config = repo.config().unwrap();
+        config.set_str("user.name", "Test User").unwrap();
+        config.set_str("user.email", "test@example.com").unwrap();
+        let repo_path = repo_dir.path().to_str().unwrap();
+
+        let scenario_dir = tempfile::TempDir::new().unwrap();
+        let scenario = scenario_dir.path().join("scenario.json");
+        std::fs::write(
+            &scenario,
+            r#"{"steps": [
+                {"type": "write_file", "path": "src/hello.txt", "content": "hello\n"},
+                {"type": "stdout", "line": "Wrote the greeting"}
+            ]}"#,
+        )
+        .unwrap();
+
+        let app_state = test_app_state().await;
+        let pool = &app_state.db_pool;
+        let project_id = insert_project(pool, repo_path).await;
+        let task_id = insert_task(pool, project_id).await;
+        let attempt = TaskAttempt::create(
+            pool,
+            &CreateTaskAttempt {
+                executor: Some(format!("mock:{}", scenario.display())),
+                base_branch: None,
+                plan_first: None,
+                pipeline_id: None,
+                model: None,
+                extra_args: None,
+            },
+            task_id,
+        )
+        .await
+        .unwrap();
+
+        ProcessService::start_execution(pool, &app_state, attempt.id, task_id, project_id, false)
+            .await
+            .unwrap();
+
+        // Drive the monitor until the agent has exited and its completion was handled
+        let deadline = Instant::now() + Duration::from_secs(30);
+        let process = loop {
+            handle_completed_executions(&app_state).await;
+            let processes = ExecutionProcess::find_by_task_attempt_id(pool, attempt.id)
+                .await
+                .unwrap();
+            if let Some(process) = processes
+                .into_iter()
+                .find(|process| process.status != ExecutionProcessStatus::Running)
+            {
+                break process;
+            }
+            assert!(Instant::now() < deadline, "mock agent did not finish");
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        };
+        assert_eq!(process.status, ExecutionProcessStatus::Completed);
+
+        // The run's change was committed on the attempt branch
+        let worktree = Repository::open(&attempt.worktree_path).unwrap();
+        assert!(worktree.statuses(None).unwrap().is_empty());
+        let head = worktree.head().unwrap().peel_to_commit().unwrap();
+        assert!(head
+            .tree()
+            .unwrap()
+            .get_path(Path::new("src/hello.txt"))
+            .is_ok());
+        let session = ExecutorSession::find_by_execution_process_id(pool, process.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(session.after_head_commit, Some(head.id().to_string()));
+
+        let diff = TaskAttempt::get_diff(pool, attempt.id, task_id, project_id)
+            .await
+            .unwrap();
+        assert_eq!(diff.files.len(), 1);
+        assert_eq!(diff.files[0].path, "src/hello.txt");
+        assert!(diff.files[0].chunks.iter().any(|chunk| {
+            matches!(chunk.chunk_type, DiffChunkType::Insert) && chunk.content.contains("hello")
+        }));
+
+        let task = Task::find_by_id(pool, task_id).await.unwrap().unwrap();
+        assert_eq!(task.status, TaskStatus::InReview);
+
+        WorktreeManager::cleanup_worktree(Path::new(&attempt.worktree_path), Some(repo_path))
+            .await
+            .unwrap();
+    }
+}
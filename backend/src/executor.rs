@@ -9,9 +9,18 @@ use uuid::Uuid;
 use crate::{
     command_runner::{CommandError, CommandProcess, CommandRunner},
     executors::{
-        AiderExecutor, AmpExecutor, CCRExecutor, CharmOpencodeExecutor, ClaudeExecutor,
-        CodexExecutor, EchoExecutor, GeminiExecutor, SetupScriptExecutor, SstOpencodeExecutor,
+        AcpExecutor, AiderExecutor, AmpExecutor, CCRExecutor, CharmOpencodeExecutor,
+        ClaudeExecutor, CodexExecutor, CustomExecutor, EchoExecutor, GeminiExecutor, MockExecutor,
+        SetupScriptExecutor, SstOpencodeExecutor,
     },
+    models::{
+        project::Project,
+        prompt_template::{task_prompt, PromptContext},
+        task::Task,
+        task_attachment::AttachmentDelivery,
+        token_usage::TokenUsage,
+    },
+    services::LogStreamService,
 };
 
 // Constants for database streaming - fast for near-real-time updates
@@ -71,6 +80,154 @@ pub enum ActionType {
     Other { description: String },
 }
 
+/// Turns executor stdout into JSON-patch operations on a `NormalizedConversation`, one line at
+/// a time, so live streams never have to re-normalize the whole log
+pub trait IncrementalNormalizer: Send {
+    /// Consume one complete stdout line and return the patch operations it produces
+    fn push_line(&mut self, line: &str) -> Vec<serde_json::Value>;
+
+    /// Flush anything held back once stdout has closed
+    fn finish(&mut self) -> Vec<serde_json::Value> {
+        Vec::new()
+    }
+}
+
+/// Prompt asking an agent to plan a task without changing anything
+pub fn planning_prompt(task_prompt: &str) -> String {
+    format!(
+        r#"{}
+
+You are in planning mode. Do not modify any files, run commands that change state, or commit anything.
+Investigate the codebase as needed, then reply with a step-by-step implementation plan in Markdown as your final message."#,
+        task_prompt
+    )
+}
+
+async fn load_prompt_context(
+    pool: &sqlx::SqlitePool,
+    task_id: Uuid,
+    worktree_path: &str,
+) -> Result<(Project, PromptContext), ExecutorError> {
+    let task = Task::find_by_id(pool, task_id)
+        .await?
+        .ok_or(ExecutorError::TaskNotFound)?;
+    let project = Project::find_by_id(pool, task.project_id)
+        .await?
+        .ok_or_else(|| {
+            ExecutorError::ContextCollectionFailed(format!("Project {} not found", task.project_id))
+        })?;
+    let context = PromptContext::load(pool, &task, worktree_path).await?;
+    Ok((project, context))
+}
+
+/// The prompt an agent starts a task with, rendered from the project's prompt template
+pub async fn render_task_prompt(
+    pool: &sqlx::SqlitePool,
+    task_id: Uuid,
+    worktree_path: &str,
+    attachment_delivery: AttachmentDelivery,
+) -> Result<String, ExecutorError> {
+    let (project, mut context) = load_prompt_context(pool, task_id, worktree_path).await?;
+    context.attachment_delivery = attachment_delivery;
+    Ok(task_prompt(&project, &context))
+}
+
+/// The plan presented in a conversation: the last `PlanPresentation` entry, falling back to
+/// the last assistant message for agents that answer plan prompts in plain text
+pub fn extract_plan(entries: &[NormalizedEntry]) -> Option<String> {
+    entries
+        .iter()
+        .rev()
+        .find_map(|entry| match &entry.entry_type {
+            NormalizedEntryType::ToolUse {
+                action_type: ActionType::PlanPresentation { plan },
+                ..
+            } => Some(plan.clone()),
+            _ => None,
+        })
+        .or_else(|| {
+            entries
+                .iter()
+                .rev()
+                .find_map(|entry| match entry.entry_type {
+                    NormalizedEntryType::AssistantMessage if !entry.content.trim().is_empty() => {
+                        Some(entry.content.trim().to_string())
+                    }
+                    _ => None,
+                })
+        })
+}
+
+/// Entry presenting `plan`, as shown for planning runs
+pub fn plan_entry(plan: String) -> NormalizedEntry {
+    NormalizedEntry {
+        timestamp: Some(chrono::Utc::now().to_rfc3339()),
+        entry_type: NormalizedEntryType::ToolUse {
+            tool_name: "plan".to_string(),
+            action_type: ActionType::PlanPresentation { plan: plan.clone() },
+        },
+        content: plan,
+        metadata: None,
+    }
+}
+
+/// Patch operation appending an entry to the conversation
+pub fn add_entry_patch(entry: &NormalizedEntry) -> serde_json::Value {
+    serde_json::json!({
+        "op": "add",
+        "path": "/entries/-",
+        "value": entry
+    })
+}
+
+/// Patch operation replacing the entry at `index`
+pub fn replace_entry_patch(index: usize, entry: &NormalizedEntry) -> serde_json::Value {
+    serde_json::json!({
+        "op": "replace",
+        "path": format!("/entries/{}", index),
+        "value": entry
+    })
+}
+
+/// Fallback normalizer for executors whose stdout lines normalize independently: each line is
+/// run through `Executor::normalize_logs` on its own and the resulting entries are appended
+pub struct LineNormalizer {
+    executor: Box<dyn Executor>,
+    worktree_path: String,
+}
+
+impl LineNormalizer {
+    pub fn new(executor: Box<dyn Executor>, worktree_path: impl Into<String>) -> Self {
+        Self {
+            executor,
+            worktree_path: worktree_path.into(),
+        }
+    }
+}
+
+impl IncrementalNormalizer for LineNormalizer {
+    fn push_line(&mut self, line: &str) -> Vec<serde_json::Value> {
+        match self.executor.normalize_logs(line, &self.worktree_path) {
+            Ok(normalized) => normalized.entries.iter().map(add_entry_patch).collect(),
+            Err(e) => {
+                tracing::warn!("Failed to normalize streamed line: {}", e);
+                Vec::new()
+            }
+        }
+    }
+}
+
+/// Build the incremental normalizer for `executor`, falling back to [`LineNormalizer`]
+pub fn incremental_normalizer_for(
+    executor: Box<dyn Executor>,
+    worktree_path: &str,
+) -> Box<dyn IncrementalNormalizer> {
+    match executor.incremental_normalizer(worktree_path) {
+        Some(normalizer) => normalizer,
+        None => Box::new(LineNormalizer::new(executor, worktree_path)),
+    }
+}
+
 /// Context information for spawn failures to provide comprehensive error details
 #[derive(Debug, Clone)]
 pub struct SpawnContext {
@@ -156,6 +313,8 @@ pub enum ExecutorError {
     GitError(String),
     InvalidSessionId(String),
     FollowUpNotSupported,
+    PlanNotSupported,
+    ConfigurationError(String),
 }
 
 impl std::fmt::Display for ExecutorError {
@@ -198,6 +357,12 @@ impl std::fmt::Display for ExecutorError {
             ExecutorError::FollowUpNotSupported => {
                 write!(f, "This executor does not support follow-up sessions")
             }
+            ExecutorError::PlanNotSupported => {
+                write!(f, "This executor does not support plan mode")
+            }
+            ExecutorError::ConfigurationError(msg) => {
+                write!(f, "Executor configuration error: {}", msg)
+            }
         }
     }
 }
@@ -252,12 +417,20 @@ impl ExecutorError {
 #[async_trait]
 pub trait Executor: Send + Sync {
     /// Spawn the command for a given task attempt
+    ///
+    /// The default renders the project's prompt template and sends it through
+    /// `spawn_with_prompt`.
     async fn spawn(
         &self,
         pool: &sqlx::SqlitePool,
         task_id: Uuid,
         worktree_path: &str,
-    ) -> Result<CommandProcess, ExecutorError>;
+    ) -> Result<CommandProcess, ExecutorError> {
+        let prompt =
+            render_task_prompt(pool, task_id, worktree_path, self.attachment_delivery()).await?;
+        self.spawn_with_prompt(pool, task_id, &prompt, worktree_path)
+            .await
+    }
 
     /// Spawn a follow-up session for executors that support it
     ///
@@ -274,6 +447,59 @@ pub trait Executor: Send + Sync {
     ) -> Result<CommandProcess, ExecutorError> {
         Err(ExecutorError::FollowUpNotSupported)
     }
+
+    /// Spawn a copy of another worktree's session that continues with the fork's prompt,
+    /// leaving the original session as it was
+    ///
+    /// Only executors that report `session_forking` implement this.
+    async fn spawn_fork(
+        &self,
+        _pool: &sqlx::SqlitePool,
+        _task_id: Uuid,
+        _fork: &ForkInfo,
+        _worktree_path: &str,
+    ) -> Result<CommandProcess, ExecutorError> {
+        Err(ExecutorError::FollowUpNotSupported)
+    }
+
+    /// Spawn a new session driven by `prompt` instead of the task's own prompt
+    ///
+    /// Agents implement this so plan mode can instruct them; the default (scripts, dev
+    /// servers) refuses.
+    async fn spawn_with_prompt(
+        &self,
+        _pool: &sqlx::SqlitePool,
+        _task_id: Uuid,
+        _prompt: &str,
+        _worktree_path: &str,
+    ) -> Result<CommandProcess, ExecutorError> {
+        Err(ExecutorError::PlanNotSupported)
+    }
+
+    /// Spawn a read-only run that answers with an implementation plan for the task
+    ///
+    /// The default sends [`planning_prompt`] through `spawn_with_prompt`; executors with a
+    /// native plan or read-only mode override this to use it.
+    async fn spawn_plan(
+        &self,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        worktree_path: &str,
+    ) -> Result<CommandProcess, ExecutorError> {
+        let prompt = planning_prompt(
+            &render_task_prompt(pool, task_id, worktree_path, self.attachment_delivery()).await?,
+        );
+        self.spawn_with_prompt(pool, task_id, &prompt, worktree_path)
+            .await
+    }
+
+    /// How task attachments are referenced in this executor's prompts
+    ///
+    /// CLIs that expand `@path` mentions override this; the rest get plain paths.
+    fn attachment_delivery(&self) -> AttachmentDelivery {
+        AttachmentDelivery::Paths
+    }
+
     /// Normalize executor logs into a standard format
     fn normalize_logs(
         &self,
@@ -290,6 +516,37 @@ pub trait Executor: Send + Sync {
         })
     }
 
+    /// Extract token usage (and cost, if reported) from the raw stdout of a finished run
+    ///
+    /// Executors whose output carries no usage information keep the default, which
+    /// reports nothing.
+    fn extract_token_usage(&self, _logs: &str) -> Option<TokenUsage> {
+        None
+    }
+
+    /// Text written to a running session's stdin to deliver a user `message`
+    ///
+    /// `None` (the default) means the executor cannot take input while it runs; executors
+    /// that return `Some` must keep stdin open when they spawn.
+    ///
+    /// Claude reads stream-json messages from stdin, ACP agents answer queued messages in a
+    /// new turn and custom executors declared `interactive` take raw lines. The other CLIs
+    /// read their whole prompt before starting.
+    fn format_input(&self, _message: &str) -> Option<String> {
+        None
+    }
+
+    /// Create a normalizer that turns stdout into conversation patches as it arrives
+    ///
+    /// Executors that carry state across lines (merging chunks, updating earlier entries)
+    /// must override this; `None` means every line can go through `normalize_logs` alone.
+    fn incremental_normalizer(
+        &self,
+        _worktree_path: &str,
+    ) -> Option<Box<dyn IncrementalNormalizer>> {
+        None
+    }
+
     #[allow(clippy::result_large_err)]
     async fn setup_streaming(
         &self,
@@ -344,6 +601,52 @@ pub trait Executor: Send + Sync {
         Ok(child)
     }
 
+    /// Execute a planning run and stream output to database in real-time
+    async fn execute_plan_streaming(
+        &self,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        attempt_id: Uuid,
+        execution_process_id: Uuid,
+        worktree_path: &str,
+    ) -> Result<CommandProcess, ExecutorError> {
+        let mut child = self.spawn_plan(pool, task_id, worktree_path).await?;
+        Self::setup_streaming(self, &mut child, pool, attempt_id, execution_process_id).await?;
+        Ok(child)
+    }
+
+    /// Execute a run driven by `prompt` and stream output to database in real-time
+    async fn execute_prompt_streaming(
+        &self,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        attempt_id: Uuid,
+        execution_process_id: Uuid,
+        prompt: &str,
+        worktree_path: &str,
+    ) -> Result<CommandProcess, ExecutorError> {
+        let mut child = self
+            .spawn_with_prompt(pool, task_id, prompt, worktree_path)
+            .await?;
+        Self::setup_streaming(self, &mut child, pool, attempt_id, execution_process_id).await?;
+        Ok(child)
+    }
+
+    /// Execute a fork's first run and stream output to database in real-time
+    async fn execute_fork_streaming(
+        &self,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        attempt_id: Uuid,
+        execution_process_id: Uuid,
+        fork: &ForkInfo,
+        worktree_path: &str,
+    ) -> Result<CommandProcess, ExecutorError> {
+        let mut child = self.spawn_fork(pool, task_id, fork, worktree_path).await?;
+        Self::setup_streaming(self, &mut child, pool, attempt_id, execution_process_id).await?;
+        Ok(child)
+    }
+
     /// Execute a follow-up command and stream output to database in real-time
     #[allow(clippy::too_many_arguments)]
     async fn execute_followup_streaming(
@@ -374,6 +677,43 @@ pub enum ExecutorType {
         config: ExecutorConfig,
         follow_up: Option<FollowUpInfo>,
     },
+    /// Read-only planning run whose result is approved before implementation
+    PlanningAgent {
+        config: ExecutorConfig,
+        follow_up: Option<FollowUpInfo>,
+    },
+    /// Pipeline stage run with a prompt built from the stage template
+    PipelineStage {
+        config: ExecutorConfig,
+        prompt: String,
+    },
+    /// Read-only review of an attempt's diff
+    ReviewAgent {
+        config: ExecutorConfig,
+        prompt: String,
+    },
+    /// First run of a forked attempt: a copy of the session it was forked from
+    ForkedAgent {
+        config: ExecutorConfig,
+        fork: ForkInfo,
+        planning: bool,
+    },
+}
+
+impl ExecutorType {
+    /// The agent configuration, for runs driven by a coding agent
+    pub fn agent_config(&self) -> Option<&ExecutorConfig> {
+        match self {
+            ExecutorType::CodingAgent { config, .. }
+            | ExecutorType::PlanningAgent { config, .. }
+            | ExecutorType::PipelineStage { config, .. }
+            | ExecutorType::ReviewAgent { config, .. }
+            | ExecutorType::ForkedAgent { config, .. } => Some(config),
+            ExecutorType::SetupScript(_)
+            | ExecutorType::CleanupScript(_)
+            | ExecutorType::DevServer(_) => None,
+        }
+    }
 }
 
 /// Information needed to continue a previous session
@@ -383,6 +723,79 @@ pub struct FollowUpInfo {
     pub prompt: String,
 }
 
+/// Information needed to continue a copy of another attempt's session
+#[derive(Debug, Clone)]
+pub struct ForkInfo {
+    pub session_id: String,
+    pub source_worktree_path: String, // Where the forked session ran
+    pub prompt: String,
+}
+
+/// Model, extra CLI arguments and pinned CLI version for an agent run
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RunOptions {
+    pub model: Option<String>,
+    pub extra_args: Vec<String>,
+    pub cli_version: Option<String>, // Replaces the version of the npx package the CLI runs from
+}
+
+impl RunOptions {
+    /// Only the options an executor with `capabilities` can honour
+    pub fn supported_by(self, capabilities: &ExecutorCapabilities) -> Self {
+        Self {
+            model: self.model.filter(|_| capabilities.model_selection),
+            extra_args: if capabilities.extra_args {
+                self.extra_args
+            } else {
+                Vec::new()
+            },
+            cli_version: self.cli_version.filter(|_| capabilities.version_pinning),
+        }
+    }
+
+    /// `command` with its npx package pinned to `cli_version`, followed by `--model` and the
+    /// extra arguments
+    ///
+    /// Arguments are appended to the CLI's own flags, so commands that read the prompt from
+    /// stdin or append flags later keep working.
+    pub fn apply(&self, command: &str) -> String {
+        let (shell_cmd, _) = crate::utils::shell::get_shell_command();
+        let mut command = match &self.cli_version {
+            Some(version) => pin_npx_package(command, version),
+            None => command.to_string(),
+        };
+        if let Some(model) = &self.model {
+            command.push_str(" --model ");
+            command.push_str(&crate::utils::shell::shell_quote(model, shell_cmd));
+        }
+        for arg in &self.extra_args {
+            command.push(' ');
+            command.push_str(&crate::utils::shell::shell_quote(arg, shell_cmd));
+        }
+        command
+    }
+}
+
+/// Replace the version of the package `npx` runs in `command`, e.g. `@scope/cli@latest`
+fn pin_npx_package(command: &str, version: &str) -> String {
+    let mut tokens: Vec<String> = command.split(' ').map(str::to_string).collect();
+    let Some(npx) = tokens.iter().position(|token| token == "npx") else {
+        return command.to_string();
+    };
+    if let Some(package) = tokens[npx + 1..]
+        .iter_mut()
+        .find(|token| !token.is_empty() && !token.starts_with('-'))
+    {
+        // A leading @ belongs to the scope, not the version
+        let name = match package[1..].find('@') {
+            Some(at) => package[..at + 1].to_string(),
+            None => package.clone(),
+        };
+        *package = format!("{}@{}", name, version);
+    }
+    tokens.join(" ")
+}
+
 /// Configuration for different executor types
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[serde(tag = "type", rename_all = "kebab-case")]
@@ -404,6 +817,96 @@ pub enum ExecutorConfig {
     SstOpencode,
     Aider,
     Codex,
+    /// User-declared executor from `custom_executors.json`
+    Custom {
+        name: String,
+    },
+    /// Any agent speaking the Agent Client Protocol over stdio
+    Acp {
+        command: String,
+    },
+    /// Scripted fake agent replaying a scenario file, for offline testing
+    Mock {
+        scenario: String,
+    },
+}
+
+/// File format an executor keeps its MCP servers in
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum McpConfigFormat {
+    Json,
+    Toml,
+}
+
+/// Where an executor's MCP servers live in its config file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct McpCapability {
+    pub config_format: McpConfigFormat,
+    pub servers_path: Vec<String>, // Keys leading to the servers object
+}
+
+/// How an executor produces implementation plans
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum PlanMode {
+    /// Cannot run planning phases
+    Unsupported,
+    /// Asked for a plan through its prompt
+    Prompted,
+    /// Plans in a read-only mode of its own
+    Native,
+    /// Always plans before implementing, like the Claude plan executor
+    Always,
+}
+
+/// Stands in for an executor that has no read-only mode, refusing every run
+struct NoReadOnlyMode {
+    executor: String,
+}
+
+#[async_trait]
+impl Executor for NoReadOnlyMode {
+    async fn spawn_with_prompt(
+        &self,
+        _pool: &sqlx::SqlitePool,
+        _task_id: Uuid,
+        _prompt: &str,
+        _worktree_path: &str,
+    ) -> Result<CommandProcess, ExecutorError> {
+        Err(ExecutorError::ConfigurationError(format!(
+            "{} has no read-only mode",
+            self.executor
+        )))
+    }
+}
+
+impl PlanMode {
+    /// Whether the executor has a mode that cannot modify the worktree, as reviews need
+    pub fn is_read_only(self) -> bool {
+        matches!(self, PlanMode::Native | PlanMode::Always)
+    }
+}
+
+/// What an executor can do, so callers branch on capabilities instead of executor names
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ExecutorCapabilities {
+    pub executor: String, // Executor id, as stored on attempts and processes
+    pub follow_up: bool,
+    pub live_input: bool, // Takes messages while it runs, through `Executor::format_input`
+    pub mcp: Option<McpCapability>,
+    pub plan_mode: PlanMode,
+    pub streaming: bool, // Output is normalized into a conversation as it arrives
+    pub image_input: bool,
+    pub model_selection: bool,
+    pub extra_args: bool,      // Passes extra arguments through to its CLI
+    pub version_pinning: bool, // Runs its CLI through npx, so the version can be pinned
+    pub session_forking: bool,
+    pub permission_prompts: bool, // Asks the project's permission policy before using tools
 }
 
 // Constants for frontend
@@ -412,6 +915,75 @@ pub enum ExecutorConfig {
 pub struct ExecutorConstants {
     pub executor_types: Vec<ExecutorConfig>,
     pub executor_labels: Vec<String>,
+    pub executor_capabilities: Vec<ExecutorCapabilities>,
+}
+
+impl ExecutorConstants {
+    /// Built-in coding agents followed by any user-declared custom executors
+    pub fn new() -> Self {
+        let mut constants = Self {
+            executor_types: vec![
+                ExecutorConfig::Echo,
+                ExecutorConfig::Claude,
+                ExecutorConfig::ClaudePlan,
+                ExecutorConfig::Amp,
+                ExecutorConfig::Gemini,
+                ExecutorConfig::CharmOpencode,
+                ExecutorConfig::ClaudeCodeRouter,
+                ExecutorConfig::SstOpencode,
+                ExecutorConfig::Aider,
+                ExecutorConfig::Codex,
+                ExecutorConfig::Acp {
+                    command: "npx -y @zed-industries/claude-code-acp".to_string(),
+                },
+                ExecutorConfig::Acp {
+                    command: "npx -y @google/gemini-cli@latest --experimental-acp".to_string(),
+                },
+            ],
+            executor_labels: vec![
+                "Echo (Test Mode)".to_string(),
+                "Claude Code".to_string(),
+                "Claude Code Plan".to_string(),
+                "Amp".to_string(),
+                "Gemini".to_string(),
+                "Charm Opencode".to_string(),
+                "Claude Code Router".to_string(),
+                "SST Opencode".to_string(),
+                "Aider".to_string(),
+                "Codex".to_string(),
+                "Claude Code (ACP)".to_string(),
+                "Gemini (ACP)".to_string(),
+            ],
+            executor_capabilities: Vec::new(),
+        };
+
+        match crate::executors::custom::CustomExecutorsFile::load() {
+            Ok(file) => {
+                for definition in &file.executors {
+                    constants.executor_types.push(ExecutorConfig::Custom {
+                        name: definition.name.clone(),
+                    });
+                    constants
+                        .executor_labels
+                        .push(definition.label().to_string());
+                }
+            }
+            Err(e) => tracing::warn!("Failed to load custom executors: {}", e),
+        }
+
+        constants.executor_capabilities = constants
+            .executor_types
+            .iter()
+            .map(ExecutorConfig::capabilities)
+            .collect();
+        constants
+    }
+}
+
+impl Default for ExecutorConstants {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl FromStr for ExecutorConfig {
@@ -432,30 +1004,95 @@ impl FromStr for ExecutorConfig {
             "setup-script" => Ok(ExecutorConfig::SetupScript {
                 script: "setup script".to_string(),
             }),
-            _ => Err(format!("Unknown executor type: {}", s)),
+            _ => {
+                if let Some(name) = s.strip_prefix("custom:").filter(|n| !n.is_empty()) {
+                    Ok(ExecutorConfig::Custom {
+                        name: name.to_string(),
+                    })
+                } else if let Some(command) = s.strip_prefix("acp:").filter(|c| !c.is_empty()) {
+                    Ok(ExecutorConfig::Acp {
+                        command: command.to_string(),
+                    })
+                } else if let Some(scenario) = s.strip_prefix("mock:").filter(|p| !p.is_empty()) {
+                    Ok(ExecutorConfig::Mock {
+                        scenario: scenario.to_string(),
+                    })
+                } else {
+                    Err(format!("Unknown executor type: {}", s))
+                }
+            }
         }
     }
 }
 
 impl ExecutorConfig {
     pub fn create_executor(&self) -> Box<dyn Executor> {
+        self.create_executor_with(&RunOptions::default())
+    }
+
+    /// Create the executor for a run with `options`
+    ///
+    /// Executors ignore the options their capabilities don't list.
+    pub fn create_executor_with(&self, options: &RunOptions) -> Box<dyn Executor> {
         match self {
             ExecutorConfig::Echo => Box::new(EchoExecutor),
-            ExecutorConfig::Claude => Box::new(ClaudeExecutor::new()),
-            ExecutorConfig::ClaudePlan => Box::new(ClaudeExecutor::new_plan_mode()),
-            ExecutorConfig::Amp => Box::new(AmpExecutor),
-            ExecutorConfig::Gemini => Box::new(GeminiExecutor),
-            ExecutorConfig::ClaudeCodeRouter => Box::new(CCRExecutor::new()),
+            ExecutorConfig::Claude => Box::new(ClaudeExecutor::new().with_run_options(options)),
+            ExecutorConfig::ClaudePlan => {
+                Box::new(ClaudeExecutor::new_plan_mode().with_run_options(options))
+            }
+            ExecutorConfig::Amp => Box::new(AmpExecutor::default().with_run_options(options)),
+            ExecutorConfig::Gemini => Box::new(GeminiExecutor::default().with_run_options(options)),
+            ExecutorConfig::ClaudeCodeRouter => {
+                Box::new(CCRExecutor::new().with_run_options(options))
+            }
             ExecutorConfig::CharmOpencode => Box::new(CharmOpencodeExecutor),
-            ExecutorConfig::SstOpencode => Box::new(SstOpencodeExecutor::new()),
-            ExecutorConfig::Aider => Box::new(AiderExecutor::new()),
-            ExecutorConfig::Codex => Box::new(CodexExecutor::new()),
+            ExecutorConfig::SstOpencode => {
+                Box::new(SstOpencodeExecutor::new().with_run_options(options))
+            }
+            ExecutorConfig::Aider => Box::new(AiderExecutor::new().with_run_options(options)),
+            ExecutorConfig::Codex => Box::new(CodexExecutor::new().with_run_options(options)),
+            ExecutorConfig::Custom { name } => Box::new(CustomExecutor::load(name)),
+            ExecutorConfig::Acp { command } => Box::new(AcpExecutor::new(command.clone())),
+            ExecutorConfig::Mock { scenario } => Box::new(MockExecutor::new(scenario.clone())),
             ExecutorConfig::SetupScript { script } => {
                 Box::new(SetupScriptExecutor::new(script.clone()))
             }
         }
     }
 
+    /// An executor whose prompt-driven runs cannot modify the worktree, for reviews
+    ///
+    /// Executors with a native plan mode run in it; the rest have no mode that keeps them from
+    /// editing, so they refuse to start.
+    pub fn create_read_only_executor_with(&self, options: &RunOptions) -> Box<dyn Executor> {
+        match self {
+            ExecutorConfig::Claude | ExecutorConfig::ClaudePlan => {
+                Box::new(ClaudeExecutor::new_plan_mode().with_run_options(options))
+            }
+            ExecutorConfig::Aider => {
+                Box::new(AiderExecutor::new().with_run_options(options).read_only())
+            }
+            ExecutorConfig::Codex => {
+                Box::new(CodexExecutor::new().with_run_options(options).read_only())
+            }
+            ExecutorConfig::Custom { name } => Box::new(CustomExecutor::load(name).read_only()),
+            _ => Box::new(NoReadOnlyMode {
+                executor: self.to_string(),
+            }),
+        }
+    }
+
+    /// The executor that implements this one's plans
+    ///
+    /// Executors with `PlanMode::Always` can't implement, so they hand over to their regular
+    /// counterpart; every other executor implements its own plans.
+    pub fn implementer(&self) -> ExecutorConfig {
+        match self {
+            ExecutorConfig::ClaudePlan => ExecutorConfig::Claude,
+            other => other.clone(),
+        }
+    }
+
     pub fn config_path(&self) -> Option<std::path::PathBuf> {
         match self {
             ExecutorConfig::Echo => None,
@@ -488,35 +1125,140 @@ impl ExecutorConfig {
                 dirs::home_dir().map(|home| home.join(".codex").join("config.toml"))
             }
             ExecutorConfig::SetupScript { .. } => None,
+            ExecutorConfig::Custom { .. } => None,
+            ExecutorConfig::Acp { .. } => None,
+            ExecutorConfig::Mock { .. } => None,
         }
     }
 
-    /// Get the JSON attribute path for MCP servers in the config file
-    pub fn mcp_attribute_path(&self) -> Option<Vec<&'static str>> {
+    /// The capability descriptor for this executor
+    pub fn capabilities(&self) -> ExecutorCapabilities {
+        let json_mcp = |path: &[&str]| {
+            Some(McpCapability {
+                config_format: McpConfigFormat::Json,
+                servers_path: path.iter().map(|key| key.to_string()).collect(),
+            })
+        };
+        let mut capabilities = ExecutorCapabilities {
+            executor: self.to_string(),
+            follow_up: true,
+            live_input: false,
+            mcp: None,
+            plan_mode: PlanMode::Prompted,
+            streaming: true,
+            image_input: false,
+            model_selection: false,
+            extra_args: true,
+            version_pinning: true,
+            session_forking: false,
+            permission_prompts: false,
+        };
+
         match self {
-            ExecutorConfig::Echo => None, // Echo doesn't support MCP
-            ExecutorConfig::CharmOpencode => Some(vec!["mcpServers"]),
-            ExecutorConfig::SstOpencode => Some(vec!["mcp"]),
-            ExecutorConfig::Claude => Some(vec!["mcpServers"]),
-            ExecutorConfig::ClaudePlan => None, // Claude Plan shares Claude config
-            ExecutorConfig::Amp => Some(vec!["amp", "mcpServers"]), // Nested path for Amp
-            ExecutorConfig::Gemini => Some(vec!["mcpServers"]),
-            ExecutorConfig::ClaudeCodeRouter => Some(vec!["mcpServers"]),
-            ExecutorConfig::Aider => None, // Aider doesn't support MCP. https://github.com/Aider-AI/aider/issues/3314
-            ExecutorConfig::Codex => None, // Codex uses TOML config, frontend doesn't handle TOML yet
-            ExecutorConfig::SetupScript { .. } => None, // Setup scripts don't support MCP
+            ExecutorConfig::Echo | ExecutorConfig::SetupScript { .. } => {
+                capabilities.follow_up = false;
+                capabilities.plan_mode = PlanMode::Unsupported;
+                capabilities.streaming = false;
+                capabilities.extra_args = false;
+                capabilities.version_pinning = false;
+            }
+            ExecutorConfig::Claude => {
+                capabilities.live_input = true;
+                capabilities.mcp = json_mcp(&["mcpServers"]);
+                capabilities.plan_mode = PlanMode::Native;
+                capabilities.image_input = true;
+                capabilities.model_selection = true;
+                capabilities.session_forking = true;
+                capabilities.permission_prompts = true;
+            }
+            // Shares Claude's config, so MCP servers are managed there. Its read-only tools
+            // still go through the permission gateway, so the project's policy applies.
+            ExecutorConfig::ClaudePlan => {
+                capabilities.plan_mode = PlanMode::Always;
+                capabilities.image_input = true;
+                capabilities.model_selection = true;
+                capabilities.permission_prompts = true;
+            }
+            // Amp reads a flat "amp.mcpServers" key rather than a nested object
+            ExecutorConfig::Amp => capabilities.mcp = json_mcp(&["amp.mcpServers"]),
+            ExecutorConfig::Gemini => {
+                capabilities.mcp = json_mcp(&["mcpServers"]);
+                capabilities.image_input = true;
+                capabilities.model_selection = true;
+            }
+            ExecutorConfig::ClaudeCodeRouter => {
+                capabilities.live_input = true;
+                capabilities.mcp = json_mcp(&["mcpServers"]);
+                capabilities.image_input = true;
+                capabilities.permission_prompts = true;
+            }
+            // The prompt is the last argument of its command line
+            ExecutorConfig::CharmOpencode => {
+                capabilities.mcp = json_mcp(&["mcpServers"]);
+                capabilities.streaming = false;
+                capabilities.extra_args = false;
+                capabilities.version_pinning = false;
+            }
+            ExecutorConfig::SstOpencode => {
+                capabilities.mcp = json_mcp(&["mcp"]);
+                capabilities.model_selection = true;
+            }
+            // Aider doesn't support MCP. https://github.com/Aider-AI/aider/issues/3314
+            ExecutorConfig::Aider => {
+                capabilities.plan_mode = PlanMode::Native;
+                capabilities.image_input = true;
+                capabilities.model_selection = true;
+                capabilities.version_pinning = false;
+            }
+            ExecutorConfig::Codex => {
+                capabilities.mcp = Some(McpCapability {
+                    config_format: McpConfigFormat::Toml,
+                    servers_path: vec!["mcp_servers".to_string()],
+                });
+                capabilities.plan_mode = PlanMode::Native;
+                capabilities.image_input = true;
+                capabilities.model_selection = true;
+            }
+            // Custom executors can do what their definition declares
+            ExecutorConfig::Custom { name } => {
+                capabilities.extra_args = false;
+                capabilities.version_pinning = false;
+                let definition = crate::executors::custom::CustomExecutorsFile::load()
+                    .ok()
+                    .and_then(|file| file.find(name).cloned());
+                match definition {
+                    Some(definition) => {
+                        capabilities.follow_up = definition.follow_up_command.is_some();
+                        capabilities.live_input = definition.interactive;
+                        if definition.plan_command.is_some() {
+                            capabilities.plan_mode = PlanMode::Native;
+                        }
+                        capabilities.streaming =
+                            !definition.rules.is_empty() || definition.fallback.is_some();
+                    }
+                    None => {
+                        capabilities.follow_up = false;
+                        capabilities.plan_mode = PlanMode::Unsupported;
+                        capabilities.streaming = false;
+                    }
+                }
+            }
+            // ACP sessions are started without MCP servers; their permission requests are
+            // answered from the project's policy
+            ExecutorConfig::Acp { .. } => {
+                capabilities.live_input = true;
+                capabilities.extra_args = false;
+                capabilities.version_pinning = false;
+                capabilities.permission_prompts = true;
+            }
+            // Follow-ups replay the scenario's follow-up run under the reported session
+            ExecutorConfig::Mock { .. } => {
+                capabilities.extra_args = false;
+                capabilities.version_pinning = false;
+                capabilities.session_forking = true;
+            }
         }
-    }
-
-    /// Check if this executor supports MCP configuration
-    pub fn supports_mcp(&self) -> bool {
-        !matches!(
-            self,
-            ExecutorConfig::Echo
-                | ExecutorConfig::Aider
-                | ExecutorConfig::SetupScript { .. }
-                | ExecutorConfig::Codex
-        )
+        capabilities
     }
 
     /// Get the display name for this executor
@@ -533,6 +1275,9 @@ impl ExecutorConfig {
             ExecutorConfig::Aider => "Aider",
             ExecutorConfig::Codex => "Codex",
             ExecutorConfig::SetupScript { .. } => "Setup Script",
+            ExecutorConfig::Custom { .. } => "Custom",
+            ExecutorConfig::Acp { .. } => "Agent Client Protocol",
+            ExecutorConfig::Mock { .. } => "Mock Agent",
         }
     }
 }
@@ -551,6 +1296,9 @@ impl std::fmt::Display for ExecutorConfig {
             ExecutorConfig::Aider => "aider",
             ExecutorConfig::Codex => "codex",
             ExecutorConfig::SetupScript { .. } => "setup-script",
+            ExecutorConfig::Custom { name } => return write!(f, "custom:{}", name),
+            ExecutorConfig::Acp { command } => return write!(f, "acp:{}", command),
+            ExecutorConfig::Mock { scenario } => return write!(f, "mock:{}", scenario),
         };
         write!(f, "{}", s)
     }
@@ -616,6 +1364,7 @@ async fn stream_stdout_to_db(
                         session_id_parsed = true;
                     }
                 }
+                LogStreamService::push_stdout(execution_process_id, &line);
                 accumulated_output.push_str(&line);
                 update_counter += 1;
 
@@ -661,6 +1410,8 @@ async fn stream_stdout_to_db(
             tracing::error!("Failed to flush stdout for attempt {}: {}", attempt_id, e);
         }
     }
+
+    LogStreamService::finish(execution_process_id);
 }
 
 /// Stream stderr from a child process to the database (buffered with timeout)
@@ -943,7 +1694,7 @@ mod tests {
 
     #[test]
     fn test_amp_log_normalization() {
-        let amp_executor = AmpExecutor;
+        let amp_executor = AmpExecutor::default();
         let amp_logs = r#"{"type":"initial","threadID":"T-f8f7fec0-b330-47ab-b63a-b72c42f1ef6a"}
 {"type":"messages","messages":[[0,{"role":"user","content":[{"type":"text","text":"Task title: Create and start should open task\nTask description: When I press 'create & start' on task creation dialog it should then open the task in the sidebar"}],"meta":{"sentAt":1751544747623}}]],"toolResults":[]}
 {"type":"messages","messages":[[1,{"role":"assistant","content":[{"type":"thinking","thinking":"The user wants to implement a feature where pressing \"create & start\" on the task creation dialog should open the task in the sidebar."},{"type":"text","text":"I'll help you implement the \"create & start\" functionality. Let me explore the codebase to understand the current task creation and sidebar structure."},{"type":"tool_use","id":"toolu_01FQqskzGAhZaZu8H6qSs5pV","name":"todo_write","input":{"todos":[{"id":"1","content":"Explore task creation dialog component","status":"todo","priority":"high"}]}}],"state":{"type":"complete","stopReason":"tool_use"}}]],"toolResults":[]}"#;
@@ -1064,8 +1815,7 @@ mod tests {
         assert_eq!(aider_config.to_string(), "aider");
 
         // Test that it doesn't support MCP
-        assert!(!aider_config.supports_mcp());
-        assert_eq!(aider_config.mcp_attribute_path(), None);
+        assert_eq!(aider_config.capabilities().mcp, None);
 
         // Test that it has the correct config path
         let config_path = aider_config.config_path();
@@ -1078,4 +1828,98 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(result.unwrap().executor_type, "aider");
     }
+
+    #[test]
+    fn test_custom_executor_config_round_trip() {
+        let config: ExecutorConfig = "custom:my-agent".parse().unwrap();
+        assert!(matches!(&config, ExecutorConfig::Custom { name } if name == "my-agent"));
+        assert_eq!(config.to_string(), "custom:my-agent");
+        assert_eq!(config.capabilities().mcp, None);
+        assert!("custom:".parse::<ExecutorConfig>().is_err());
+
+        let acp: ExecutorConfig = "acp:my-agent --acp".parse().unwrap();
+        assert!(matches!(&acp, ExecutorConfig::Acp { command } if command == "my-agent --acp"));
+        assert_eq!(acp.to_string(), "acp:my-agent --acp");
+
+        let mock: ExecutorConfig = "mock:/tmp/scenario.json".parse().unwrap();
+        assert!(
+            matches!(&mock, ExecutorConfig::Mock { scenario } if scenario == "/tmp/scenario.json")
+        );
+        assert_eq!(mock.to_string(), "mock:/tmp/scenario.json");
+        assert!(mock.capabilities().follow_up);
+
+        let json = serde_json::to_value(&config).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"type": "custom", "name": "my-agent"})
+        );
+    }
+
+    #[test]
+    fn test_executor_capabilities() {
+        let claude = ExecutorConfig::Claude.capabilities();
+        assert_eq!(claude.executor, "claude");
+        assert_eq!(claude.plan_mode, PlanMode::Native);
+        assert!(claude.follow_up && claude.session_forking);
+
+        let plan = ExecutorConfig::ClaudePlan.capabilities();
+        assert_eq!(plan.plan_mode, PlanMode::Always);
+        assert_eq!(plan.mcp, None);
+        assert!(matches!(
+            ExecutorConfig::ClaudePlan.implementer(),
+            ExecutorConfig::Claude
+        ));
+        assert!(matches!(
+            ExecutorConfig::Codex.implementer(),
+            ExecutorConfig::Codex
+        ));
+
+        let amp = ExecutorConfig::Amp.capabilities().mcp.unwrap();
+        assert_eq!(amp.config_format, McpConfigFormat::Json);
+        assert_eq!(amp.servers_path, vec!["amp.mcpServers".to_string()]);
+
+        let codex = ExecutorConfig::Codex.capabilities().mcp.unwrap();
+        assert_eq!(codex.config_format, McpConfigFormat::Toml);
+
+        let echo = ExecutorConfig::Echo.capabilities();
+        assert!(!echo.follow_up && !echo.streaming);
+        assert_eq!(echo.plan_mode, PlanMode::Unsupported);
+    }
+
+    #[tokio::test]
+    async fn test_reviews_refuse_executors_without_a_read_only_mode() {
+        assert!(ExecutorConfig::Codex
+            .capabilities()
+            .plan_mode
+            .is_read_only());
+        assert!(!ExecutorConfig::Amp.capabilities().plan_mode.is_read_only());
+
+        let pool = crate::test_utils::test_pool().await;
+        let result = ExecutorConfig::Amp
+            .create_read_only_executor_with(&RunOptions::default())
+            .spawn_with_prompt(&pool, Uuid::new_v4(), "Review the diff", "/tmp")
+            .await;
+        assert!(matches!(result, Err(ExecutorError::ConfigurationError(_))));
+    }
+
+    #[test]
+    fn test_run_options_apply() {
+        let options = RunOptions {
+            model: Some("sonnet".to_string()),
+            extra_args: vec!["--max-turns".to_string(), "5".to_string()],
+            cli_version: Some("1.0.0".to_string()),
+        };
+        assert_eq!(
+            options.apply("npx -y @anthropic-ai/claude-code@latest -p"),
+            "npx -y @anthropic-ai/claude-code@1.0.0 -p --model 'sonnet' '--max-turns' '5'"
+        );
+        assert_eq!(
+            pin_npx_package("npx @sourcegraph/amp --format=jsonl", "0.0.1"),
+            "npx @sourcegraph/amp@0.0.1 --format=jsonl"
+        );
+        assert_eq!(RunOptions::default().apply("aider --yes"), "aider --yes");
+
+        let echo = ExecutorConfig::Echo.capabilities();
+        assert_eq!(options.supported_by(&echo), RunOptions::default());
+    }
 }
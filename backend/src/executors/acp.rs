@@ -0,0 +1,305 @@
+//! Agent Client Protocol executor
+//!
+//! Drives any agent that implements the Agent Client Protocol (JSON-RPC over stdio), so new
+//! agents work without a bespoke output parser. The agent always runs locally.
+
+mod client;
+mod protocol;
+
+use async_trait::async_trait;
+use client::AcpConnection;
+use uuid::Uuid;
+
+use crate::{
+    command_runner::{CommandError, CommandProcess, ProcessHandle},
+    executor::{
+        Executor, ExecutorError, IncrementalNormalizer, NormalizedConversation, SpawnContext,
+    },
+    mcp::permission_server::PermissionServer,
+    models::{task::Task, token_usage::TokenUsage},
+    utils::shell::get_shell_command,
+};
+
+/// An executor that talks to an ACP-compatible agent
+pub struct AcpExecutor {
+    command: String,
+}
+
+impl AcpExecutor {
+    pub fn new(command: impl Into<String>) -> Self {
+        Self {
+            command: command.into(),
+        }
+    }
+
+    fn executor_type(&self) -> String {
+        format!("acp:{}", self.command)
+    }
+
+    /// Answers the agent's permission requests from the task's project policy
+    async fn permissions(
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        worktree_path: &str,
+    ) -> Result<PermissionServer, ExecutorError> {
+        let task = Task::find_by_id(pool, task_id)
+            .await?
+            .ok_or(ExecutorError::TaskNotFound)?;
+        Ok(PermissionServer::new(
+            pool.clone(),
+            task.project_id,
+            worktree_path.to_string(),
+        ))
+    }
+
+    /// Start the agent and complete the handshake, then run `prompt` in the background
+    async fn start_session(
+        &self,
+        worktree_path: &str,
+        resume_session_id: Option<&str>,
+        prompt: String,
+        permissions: Option<PermissionServer>,
+    ) -> Result<CommandProcess, (CommandError, Vec<String>)> {
+        let (shell_cmd, shell_arg) = get_shell_command();
+        let args = vec![shell_arg.to_string(), self.command.clone()];
+        let (mut connection, mut handle) =
+            AcpConnection::spawn(shell_cmd, &args, worktree_path, permissions)
+                .map_err(|e| (e, args.clone()))?;
+
+        let handshake = async {
+            let can_load = connection.initialize().await?;
+            match resume_session_id {
+                Some(session_id) if can_load => {
+                    connection.load_session(session_id, worktree_path).await
+                }
+                Some(_) => Err("Agent does not support session/load".to_string()),
+                None => connection.new_session(worktree_path).await.map(|_| ()),
+            }
+        };
+
+        if let Err(e) = handshake.await {
+            let _ = handle.kill().await;
+            return Err((
+                CommandError::IoError {
+                    error: std::io::Error::other(e),
+                },
+                args,
+            ));
+        }
+
+        let cancel_rx = handle
+            .take_cancel_receiver()
+            .expect("cancel receiver is only taken once");
+        let input_rx = handle
+            .take_input_receiver()
+            .expect("input receiver is only taken once");
+        connection.run_prompt(prompt, cancel_rx, input_rx);
+
+        Ok(CommandProcess::from_handle(Box::new(handle)))
+    }
+
+    fn spawn_context(&self, args: Vec<String>, worktree_path: &str) -> SpawnContext {
+        let (shell_cmd, _) = get_shell_command();
+        SpawnContext {
+            executor_type: self.executor_type(),
+            command: shell_cmd.to_string(),
+            args,
+            working_dir: worktree_path.to_string(),
+            task_id: None,
+            task_title: None,
+            additional_context: None,
+        }
+    }
+}
+
+#[async_trait]
+impl Executor for AcpExecutor {
+    async fn spawn_with_prompt(
+        &self,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        prompt: &str,
+        worktree_path: &str,
+    ) -> Result<CommandProcess, ExecutorError> {
+        let permissions = Self::permissions(pool, task_id, worktree_path).await?;
+        self.start_session(worktree_path, None, prompt.to_string(), Some(permissions))
+            .await
+            .map_err(|(e, args)| {
+                self.spawn_context(args, worktree_path)
+                    .with_task(task_id, None)
+                    .with_context("ACP agent session for new task")
+                    .spawn_error(e)
+            })
+    }
+
+    async fn spawn_followup(
+        &self,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        session_id: &str,
+        prompt: &str,
+        worktree_path: &str,
+    ) -> Result<CommandProcess, ExecutorError> {
+        let permissions = Self::permissions(pool, task_id, worktree_path).await?;
+        self.start_session(
+            worktree_path,
+            Some(session_id),
+            prompt.to_string(),
+            Some(permissions),
+        )
+        .await
+        .map_err(|(e, args)| {
+            self.spawn_context(args, worktree_path)
+                .with_context(format!("ACP agent followup for session {}", session_id))
+                .spawn_error(e)
+        })
+    }
+
+    fn normalize_logs(
+        &self,
+        logs: &str,
+        worktree_path: &str,
+    ) -> Result<NormalizedConversation, String> {
+        let mut normalizer = protocol::TranscriptNormalizer::default();
+        for line in logs.lines() {
+            let trimmed = line.trim();
+            if !trimmed.is_empty() {
+                normalizer.push_line(trimmed, worktree_path);
+            }
+        }
+        let (entries, session_id) = normalizer.finish();
+
+        Ok(NormalizedConversation {
+            entries,
+            session_id,
+            executor_type: self.executor_type(),
+            prompt: None,
+            summary: None,
+        })
+    }
+
+    /// Messages are queued and sent as the next prompt once the current turn ends
+    fn format_input(&self, message: &str) -> Option<String> {
+        Some(message.to_string())
+    }
+
+    fn extract_token_usage(&self, logs: &str) -> Option<TokenUsage> {
+        // Each prompt turn's response reports that turn's usage
+        let mut total: Option<TokenUsage> = None;
+        for line in logs.lines() {
+            let Ok(message) = serde_json::from_str::<serde_json::Value>(line.trim()) else {
+                continue;
+            };
+            if let Some(usage) = protocol::prompt_usage(&message) {
+                total.get_or_insert_with(TokenUsage::default).add(&usage);
+            }
+        }
+        total
+    }
+
+    fn incremental_normalizer(
+        &self,
+        worktree_path: &str,
+    ) -> Option<Box<dyn IncrementalNormalizer>> {
+        Some(Box::new(AcpIncrementalNormalizer {
+            transcript: protocol::TranscriptNormalizer::default(),
+            worktree_path: worktree_path.to_string(),
+        }))
+    }
+}
+
+/// Streams transcript changes: merged chunks and tool call updates become `replace` patches
+struct AcpIncrementalNormalizer {
+    transcript: protocol::TranscriptNormalizer,
+    worktree_path: String,
+}
+
+impl IncrementalNormalizer for AcpIncrementalNormalizer {
+    fn push_line(&mut self, line: &str) -> Vec<serde_json::Value> {
+        self.transcript.push_line(line.trim(), &self.worktree_path);
+        self.transcript.take_patches()
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use tokio::io::AsyncReadExt;
+
+    use super::*;
+    use crate::executor::NormalizedEntryType;
+
+    /// A minimal agent that answers the handshake and a single prompt turn
+    const FAKE_AGENT: &str = r#"read l; echo '{"jsonrpc":"2.0","id":0,"result":{"protocolVersion":1,"agentCapabilities":{"loadSession":true}}}'
+read l; echo '{"jsonrpc":"2.0","id":1,"result":{"sessionId":"s-1"}}'
+read l; echo '{"jsonrpc":"2.0","method":"session/update","params":{"sessionId":"s-1","update":{"sessionUpdate":"agent_message_chunk","content":{"type":"text","text":"All done"}}}}'
+echo '{"jsonrpc":"2.0","id":2,"result":{"stopReason":"end_turn"}}'"#;
+
+    #[tokio::test]
+    async fn test_session_round_trip_with_fake_agent() {
+        let executor = AcpExecutor::new(FAKE_AGENT);
+        let dir = std::env::temp_dir();
+        let worktree = dir.to_string_lossy();
+
+        let mut process = executor
+            .start_session(&worktree, None, "hello".to_string(), None)
+            .await
+            .map_err(|(e, _)| e)
+            .expect("handshake should succeed");
+
+        let mut stdout = process.stream().await.unwrap().stdout.unwrap();
+        let mut transcript = String::new();
+        stdout.read_to_string(&mut transcript).await.unwrap();
+
+        let status = process.wait().await.unwrap();
+        assert!(status.success());
+
+        let conversation = executor.normalize_logs(&transcript, &worktree).unwrap();
+        assert_eq!(conversation.session_id.as_deref(), Some("s-1"));
+        assert_eq!(conversation.entries.len(), 1);
+        assert!(matches!(
+            conversation.entries[0].entry_type,
+            NormalizedEntryType::AssistantMessage
+        ));
+        assert_eq!(conversation.entries[0].content, "All done");
+    }
+
+    #[tokio::test]
+    async fn test_message_sent_during_a_turn_is_answered_after_it() {
+        // Takes a second over the first turn, then answers the queued message
+        let agent = r#"read l; echo '{"jsonrpc":"2.0","id":0,"result":{"protocolVersion":1,"agentCapabilities":{"loadSession":true}}}'
+read l; echo '{"jsonrpc":"2.0","id":1,"result":{"sessionId":"s-1"}}'
+read l; sleep 1; echo '{"jsonrpc":"2.0","id":2,"result":{"stopReason":"end_turn"}}'
+read l; case "$l" in *'"text":"and then?"'*) echo '{"jsonrpc":"2.0","method":"session/update","params":{"sessionId":"s-1","update":{"sessionUpdate":"agent_message_chunk","content":{"type":"text","text":"Queued answer"}}}}';; esac
+echo '{"jsonrpc":"2.0","id":3,"result":{"stopReason":"end_turn"}}'"#;
+        let executor = AcpExecutor::new(agent);
+        let dir = std::env::temp_dir();
+        let worktree = dir.to_string_lossy();
+
+        let mut process = executor
+            .start_session(&worktree, None, "hello".to_string(), None)
+            .await
+            .map_err(|(e, _)| e)
+            .expect("handshake should succeed");
+        let input = executor.format_input("and then?").unwrap();
+        process
+            .write_stdin(&input)
+            .await
+            .expect("Should queue a message while the turn runs");
+
+        let mut stdout = process.stream().await.unwrap().stdout.unwrap();
+        let mut transcript = String::new();
+        stdout.read_to_string(&mut transcript).await.unwrap();
+
+        let status = process.wait().await.unwrap();
+        assert!(status.success());
+
+        let conversation = executor.normalize_logs(&transcript, &worktree).unwrap();
+        assert_eq!(conversation.entries.len(), 1);
+        assert_eq!(conversation.entries[0].content, "Queued answer");
+
+        assert!(matches!(
+            process.write_stdin(&input).await,
+            Err(CommandError::StdinUnavailable)
+        ));
+    }
+}
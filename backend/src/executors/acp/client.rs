@@ -0,0 +1,546 @@
+//! JSON-RPC client side of the Agent Client Protocol
+//!
+//! The agent runs as a local child process speaking newline-delimited JSON-RPC over stdio.
+//! The handshake (`initialize` plus `session/new` or `session/load`) happens before the
+//! process is handed back to the caller; the prompt turn then runs on a background task that
+//! answers permission requests from the project's policy and writes a transcript which is
+//! exposed as the process stdout. Messages sent while a turn runs are queued and answered in
+//! another turn once it ends.
+
+use std::{
+    process::Stdio,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use command_group::AsyncCommandGroup;
+use serde_json::{json, Value};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader, DuplexStream, Lines},
+    process::{ChildStdin, ChildStdout, Command},
+    sync::{mpsc, oneshot},
+};
+
+use super::protocol::{permission_outcome, permission_tool_call, PROTOCOL_VERSION};
+use crate::{
+    command_runner::{
+        self, CommandError, CommandExitStatus, CommandStream, LocalProcessHandle, ProcessHandle,
+        ResourceGroup, ResourceUsage, StopOutcome,
+    },
+    mcp::permission_server::PermissionServer,
+    models::permission::PermissionDecision,
+};
+
+/// How long the handshake may take (includes e.g. `npx` downloading the agent)
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(120);
+/// How long an agent may keep running after its turn finished before it is stopped
+const EXIT_GRACE: Duration = Duration::from_secs(5);
+/// How long to wait for the agent to honour `session/cancel` before killing it
+const CANCEL_GRACE: Duration = Duration::from_secs(3);
+const TRANSCRIPT_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Outcome of the prompt turn, set by the background task once the agent has responded
+#[derive(Debug, Clone, Copy)]
+struct TurnOutcome {
+    success: bool,
+    finished_at: Instant,
+}
+
+type SharedTurn = Arc<Mutex<Option<TurnOutcome>>>;
+
+pub struct AcpConnection {
+    stdin: Option<ChildStdin>,
+    lines: Lines<BufReader<ChildStdout>>,
+    transcript: DuplexStream,
+    next_id: u64,
+    session_id: Option<String>,
+    turn: SharedTurn,
+    permissions: Option<PermissionServer>, // Without one, every permission request is refused
+}
+
+impl AcpConnection {
+    /// Spawn the agent and wire up its stdio
+    pub fn spawn(
+        program: &str,
+        args: &[String],
+        working_dir: &str,
+        permissions: Option<PermissionServer>,
+    ) -> Result<(Self, AcpProcessHandle), CommandError> {
+        let mut cmd = Command::new(program);
+        cmd.args(args)
+            .kill_on_drop(true)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .current_dir(working_dir)
+            .env("NODE_NO_WARNINGS", "1");
+        let resources =
+            ResourceGroup::confine(&command_runner::current_resource_limits(), &mut cmd).map_err(
+                |e| CommandError::SpawnFailed {
+                    command: program.to_string(),
+                    error: e,
+                },
+            )?;
+        if let Some(policy) = command_runner::current_sandbox() {
+            policy
+                .confine(&mut cmd)
+                .map_err(|e| CommandError::SpawnFailed {
+                    command: program.to_string(),
+                    error: e,
+                })?;
+        }
+
+        let mut child = cmd.group_spawn().map_err(|e| CommandError::SpawnFailed {
+            command: format!("{} {}", program, args.join(" ")),
+            error: e,
+        })?;
+
+        let stdin = child.inner().stdin.take();
+        let stdout = child
+            .inner()
+            .stdout
+            .take()
+            .ok_or(CommandError::ProcessNotStarted)?;
+        let (transcript, transcript_reader) = tokio::io::duplex(TRANSCRIPT_BUFFER_SIZE);
+        let (cancel_tx, cancel_rx) = oneshot::channel();
+        let (input_tx, input_rx) = mpsc::unbounded_channel();
+        let turn = SharedTurn::default();
+
+        let connection = Self {
+            stdin,
+            lines: BufReader::new(stdout).lines(),
+            transcript,
+            next_id: 0,
+            session_id: None,
+            turn: turn.clone(),
+            permissions,
+        };
+        let handle = AcpProcessHandle {
+            inner: LocalProcessHandle::new(child).with_resources(resources),
+            transcript: Some(transcript_reader),
+            cancel_tx: Some(cancel_tx),
+            cancel_rx: Some(cancel_rx),
+            input_tx,
+            input_rx: Some(input_rx),
+            turn,
+            forced_status: None,
+        };
+
+        Ok((connection, handle))
+    }
+
+    /// Negotiate the protocol version; returns whether the agent can resume sessions
+    pub async fn initialize(&mut self) -> Result<bool, String> {
+        let result = self
+            .timed_request(
+                "initialize",
+                json!({
+                    "protocolVersion": PROTOCOL_VERSION,
+                    "clientCapabilities": {
+                        "fs": { "readTextFile": false, "writeTextFile": false },
+                        "terminal": false
+                    }
+                }),
+            )
+            .await?;
+
+        Ok(result
+            .pointer("/agentCapabilities/loadSession")
+            .and_then(Value::as_bool)
+            .unwrap_or(false))
+    }
+
+    pub async fn new_session(&mut self, cwd: &str) -> Result<String, String> {
+        let result = self
+            .timed_request("session/new", json!({ "cwd": cwd, "mcpServers": [] }))
+            .await?;
+        let session_id = result
+            .get("sessionId")
+            .and_then(Value::as_str)
+            .ok_or_else(|| "session/new response is missing sessionId".to_string())?
+            .to_string();
+        self.set_session(session_id.clone()).await;
+        Ok(session_id)
+    }
+
+    pub async fn load_session(&mut self, session_id: &str, cwd: &str) -> Result<(), String> {
+        let id = self
+            .send_request(
+                "session/load",
+                json!({ "sessionId": session_id, "cwd": cwd, "mcpServers": [] }),
+            )
+            .await?;
+        // The agent replays the previous conversation before responding; that history is
+        // already stored with the earlier execution, so it is not recorded again.
+        tokio::time::timeout(
+            HANDSHAKE_TIMEOUT,
+            self.read_response(id, "session/load", false),
+        )
+        .await
+        .map_err(|_| "Timed out waiting for session/load".to_string())??;
+        self.set_session(session_id.to_string()).await;
+        Ok(())
+    }
+
+    /// Run the prompt turn, and one for each batch of messages queued meanwhile, in the
+    /// background
+    pub fn run_prompt(
+        mut self,
+        prompt: String,
+        cancel_rx: oneshot::Receiver<()>,
+        mut input_rx: mpsc::UnboundedReceiver<String>,
+    ) {
+        tokio::spawn(async move {
+            let session_id = self.session_id.clone().unwrap_or_default();
+            let mut cancel_rx = Some(cancel_rx);
+            let mut prompt = prompt;
+            let success = loop {
+                let success = match self.prompt(&session_id, &prompt, &mut cancel_rx).await {
+                    Ok(result) => {
+                        result.get("stopReason").and_then(Value::as_str) == Some("end_turn")
+                    }
+                    Err(e) => {
+                        tracing::error!("ACP prompt turn for session {} failed: {}", session_id, e);
+                        false
+                    }
+                };
+                match next_prompt(&mut input_rx) {
+                    Some(next) if success => prompt = next,
+                    _ => break success,
+                }
+            };
+            input_rx.close();
+
+            *self.turn.lock().unwrap() = Some(TurnOutcome {
+                success,
+                finished_at: Instant::now(),
+            });
+
+            // Closing stdin and the transcript lets well-behaved agents exit and ends stdout
+            self.stdin = None;
+            let _ = self.transcript.shutdown().await;
+        });
+    }
+
+    async fn prompt(
+        &mut self,
+        session_id: &str,
+        prompt: &str,
+        cancel_rx: &mut Option<oneshot::Receiver<()>>,
+    ) -> Result<Value, String> {
+        let id = self
+            .send_request(
+                "session/prompt",
+                json!({
+                    "sessionId": session_id,
+                    "prompt": [{ "type": "text", "text": prompt }]
+                }),
+            )
+            .await?;
+
+        loop {
+            let line = if let Some(pending) = cancel_rx.as_mut() {
+                tokio::select! {
+                    line = self.lines.next_line() => line,
+                    cancelled = pending => {
+                        *cancel_rx = None;
+                        if cancelled.is_ok() {
+                            self.send(&json!({
+                                "jsonrpc": "2.0",
+                                "method": "session/cancel",
+                                "params": { "sessionId": session_id }
+                            }))
+                            .await?;
+                        }
+                        continue;
+                    }
+                }
+            } else {
+                self.lines.next_line().await
+            };
+
+            if let Some(result) = self.handle_line(line, id, "session/prompt", true).await? {
+                return Ok(result);
+            }
+        }
+    }
+
+    async fn timed_request(&mut self, method: &str, params: Value) -> Result<Value, String> {
+        let id = self.send_request(method, params).await?;
+        tokio::time::timeout(HANDSHAKE_TIMEOUT, self.read_response(id, method, true))
+            .await
+            .map_err(|_| format!("Timed out waiting for {}", method))?
+    }
+
+    async fn send_request(&mut self, method: &str, params: Value) -> Result<u64, String> {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.send(&json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }))
+            .await?;
+        Ok(id)
+    }
+
+    async fn read_response(
+        &mut self,
+        id: u64,
+        method: &str,
+        record_updates: bool,
+    ) -> Result<Value, String> {
+        loop {
+            let line = self.lines.next_line().await;
+            if let Some(result) = self.handle_line(line, id, method, record_updates).await? {
+                return Ok(result);
+            }
+        }
+    }
+
+    /// Process one line from the agent, returning the result if it answers request `id`
+    async fn handle_line(
+        &mut self,
+        line: std::io::Result<Option<String>>,
+        id: u64,
+        method: &str,
+        record_updates: bool,
+    ) -> Result<Option<Value>, String> {
+        let line = line
+            .map_err(|e| format!("Failed to read from agent: {}", e))?
+            .ok_or_else(|| format!("Agent exited before responding to {}", method))?;
+
+        let Ok(message) = serde_json::from_str::<Value>(line.trim()) else {
+            tracing::debug!("Ignoring non JSON-RPC output from ACP agent: {}", line);
+            return Ok(None);
+        };
+
+        if message.get("method").is_some() {
+            self.handle_agent_message(message, record_updates).await;
+            return Ok(None);
+        }
+
+        if message.get("id").and_then(Value::as_u64) != Some(id) {
+            return Ok(None);
+        }
+
+        self.record(&message).await;
+        if let Some(error) = message.get("error") {
+            let text = error
+                .get("message")
+                .and_then(Value::as_str)
+                .map(|s| s.to_string())
+                .unwrap_or_else(|| error.to_string());
+            return Err(format!("{} failed: {}", method, text));
+        }
+        Ok(Some(message.get("result").cloned().unwrap_or(Value::Null)))
+    }
+
+    /// Handle a notification or request initiated by the agent
+    async fn handle_agent_message(&mut self, message: Value, record_updates: bool) {
+        let method = message.get("method").and_then(Value::as_str).unwrap_or("");
+        let request_id = message.get("id").cloned();
+
+        match (method, request_id) {
+            ("session/update", None) => {
+                if record_updates {
+                    self.record(&message).await;
+                }
+            }
+            ("session/request_permission", Some(request_id)) => {
+                self.record(&message).await;
+                let params = message.get("params").cloned().unwrap_or(Value::Null);
+                let allowed = self.is_permitted(&params).await;
+                let response = json!({
+                    "jsonrpc": "2.0",
+                    "id": request_id,
+                    "result": { "outcome": permission_outcome(&params, allowed) }
+                });
+                if let Err(e) = self.send(&response).await {
+                    tracing::warn!("Failed to answer ACP permission request: {}", e);
+                }
+                self.record(&response).await;
+            }
+            (_, Some(request_id)) => {
+                let response = json!({
+                    "jsonrpc": "2.0",
+                    "id": request_id,
+                    "error": { "code": -32601, "message": format!("Method not found: {}", method) }
+                });
+                if let Err(e) = self.send(&response).await {
+                    tracing::warn!("Failed to answer ACP request {}: {}", method, e);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Whether the project's policy, or whoever it waits on, allows a permission request
+    async fn is_permitted(&self, params: &Value) -> bool {
+        let Some(permissions) = &self.permissions else {
+            return false;
+        };
+        let (tool_name, input) = permission_tool_call(params);
+        match permissions.decide(&tool_name, &input).await {
+            Ok(request) => request.decision == Some(PermissionDecision::Allow),
+            Err(e) => {
+                tracing::error!("{}", e);
+                false
+            }
+        }
+    }
+
+    async fn set_session(&mut self, session_id: String) {
+        // Top-level `session_id` is what the generic stdout streamer stores on the session
+        self.record(&json!({ "session_id": session_id })).await;
+        self.session_id = Some(session_id);
+    }
+
+    async fn send(&mut self, message: &Value) -> Result<(), String> {
+        let stdin = self
+            .stdin
+            .as_mut()
+            .ok_or_else(|| "Agent stdin is closed".to_string())?;
+        let mut line = message.to_string();
+        line.push('\n');
+        stdin
+            .write_all(line.as_bytes())
+            .await
+            .map_err(|e| format!("Failed to write to agent: {}", e))?;
+        stdin
+            .flush()
+            .await
+            .map_err(|e| format!("Failed to write to agent: {}", e))
+    }
+
+    async fn record(&mut self, message: &Value) {
+        let mut line = message.to_string();
+        line.push('\n');
+        if let Err(e) = self.transcript.write_all(line.as_bytes()).await {
+            tracing::debug!("Dropping ACP transcript line: {}", e);
+        }
+    }
+}
+
+/// The messages queued while a turn ran, as the next prompt
+///
+/// Once nothing is queued the queue is closed, so later messages are refused rather than
+/// left unanswered.
+fn next_prompt(input_rx: &mut mpsc::UnboundedReceiver<String>) -> Option<String> {
+    let mut queued = Vec::new();
+    while let Ok(message) = input_rx.try_recv() {
+        queued.push(message);
+    }
+    if queued.is_empty() {
+        input_rx.close();
+        // Anything sent before the queue closed is still answered
+        while let Ok(message) = input_rx.try_recv() {
+            queued.push(message);
+        }
+    }
+    (!queued.is_empty()).then(|| queued.join("\n\n"))
+}
+
+/// Process handle for an ACP agent whose stdout is replaced by the protocol transcript
+pub struct AcpProcessHandle {
+    inner: LocalProcessHandle,
+    transcript: Option<DuplexStream>,
+    cancel_tx: Option<oneshot::Sender<()>>,
+    cancel_rx: Option<oneshot::Receiver<()>>,
+    input_tx: mpsc::UnboundedSender<String>,
+    input_rx: Option<mpsc::UnboundedReceiver<String>>,
+    turn: SharedTurn,
+    forced_status: Option<CommandExitStatus>,
+}
+
+impl AcpProcessHandle {
+    /// Receiver the prompt task listens on for cancellation
+    pub fn take_cancel_receiver(&mut self) -> Option<oneshot::Receiver<()>> {
+        self.cancel_rx.take()
+    }
+
+    /// Receiver the prompt task takes queued messages from
+    pub fn take_input_receiver(&mut self) -> Option<mpsc::UnboundedReceiver<String>> {
+        self.input_rx.take()
+    }
+
+    fn turn_outcome(&self) -> Option<TurnOutcome> {
+        *self.turn.lock().unwrap()
+    }
+}
+
+#[async_trait]
+impl ProcessHandle for AcpProcessHandle {
+    async fn try_wait(&mut self) -> Result<Option<CommandExitStatus>, CommandError> {
+        if let Some(status) = &self.forced_status {
+            return Ok(Some(status.clone()));
+        }
+
+        if let Some(status) = self.inner.try_wait().await? {
+            return Ok(Some(status));
+        }
+
+        // Agents are servers and may not exit on EOF; stop them once the turn is over
+        match self.turn_outcome() {
+            Some(outcome) if outcome.finished_at.elapsed() >= EXIT_GRACE => {
+                self.inner.kill().await?;
+                let status = CommandExitStatus::from_outcome(
+                    Some(if outcome.success { 0 } else { 1 }),
+                    outcome.success,
+                );
+                self.forced_status = Some(status.clone());
+                Ok(Some(status))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    async fn wait(&mut self) -> Result<CommandExitStatus, CommandError> {
+        loop {
+            if let Some(status) = self.try_wait().await? {
+                return Ok(status);
+            }
+            tokio::time::sleep(Duration::from_millis(200)).await;
+        }
+    }
+
+    async fn stop(&mut self, grace_period: Duration) -> Result<StopOutcome, CommandError> {
+        if self.forced_status.is_some() {
+            return Ok(StopOutcome { signal: None });
+        }
+
+        // Ask the agent to stop the turn first so it can wind down cleanly
+        if let Some(cancel_tx) = self.cancel_tx.take() {
+            let _ = cancel_tx.send(());
+            let deadline = Instant::now() + CANCEL_GRACE;
+            while Instant::now() < deadline && self.turn_outcome().is_none() {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        }
+
+        self.inner.stop(grace_period).await
+    }
+
+    async fn stream(&mut self) -> Result<CommandStream, CommandError> {
+        let stderr = self.inner.stream().await?.stderr;
+        Ok(CommandStream {
+            stdout: self
+                .transcript
+                .take()
+                .map(|s| Box::new(s) as Box<dyn tokio::io::AsyncRead + Unpin + Send>),
+            stderr,
+        })
+    }
+
+    fn process_id(&self) -> String {
+        self.inner.process_id()
+    }
+
+    /// Queue a message for the agent to answer once the current turn ends
+    async fn write_stdin(&mut self, data: &str) -> Result<(), CommandError> {
+        self.input_tx
+            .send(data.to_string())
+            .map_err(|_| CommandError::StdinUnavailable)
+    }
+
+    fn resource_usage(&mut self) -> Option<ResourceUsage> {
+        self.inner.resource_usage()
+    }
+}
@@ -0,0 +1,654 @@
+//! Agent Client Protocol message types and transcript normalization
+//!
+//! The ACP client records every message it receives from the agent (plus its own replies to
+//! permission requests) as one JSON-RPC message per line. This module turns that transcript
+//! back into normalized conversation entries.
+
+use std::collections::{BTreeSet, HashMap};
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    executor::{
+        add_entry_patch, replace_entry_patch, ActionType, NormalizedEntry, NormalizedEntryType,
+    },
+    models::token_usage::TokenUsage,
+    utils::path::make_path_relative,
+};
+
+/// Protocol version advertised during `initialize`
+pub const PROTOCOL_VERSION: u64 = 1;
+
+/// Payload of a `session/update` notification
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "sessionUpdate", rename_all = "snake_case")]
+pub enum SessionUpdate {
+    UserMessageChunk {
+        content: ContentBlock,
+    },
+    AgentMessageChunk {
+        content: ContentBlock,
+    },
+    AgentThoughtChunk {
+        content: ContentBlock,
+    },
+    ToolCall(ToolCall),
+    ToolCallUpdate(ToolCallUpdate),
+    Plan {
+        entries: Vec<PlanEntry>,
+    },
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentBlock {
+    Text {
+        text: String,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolKind {
+    Read,
+    Edit,
+    Delete,
+    Move,
+    Search,
+    Execute,
+    Think,
+    Fetch,
+    #[default]
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ToolCallLocation {
+    pub path: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolCall {
+    pub tool_call_id: String,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub kind: ToolKind,
+    #[serde(default)]
+    pub status: Option<String>,
+    #[serde(default)]
+    pub locations: Vec<ToolCallLocation>,
+    #[serde(default)]
+    pub raw_input: Option<Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolCallUpdate {
+    pub tool_call_id: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub kind: Option<ToolKind>,
+    #[serde(default)]
+    pub status: Option<String>,
+    #[serde(default)]
+    pub locations: Option<Vec<ToolCallLocation>>,
+    #[serde(default)]
+    pub raw_input: Option<Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PlanEntry {
+    pub content: String,
+    #[serde(default)]
+    pub status: Option<String>,
+}
+
+/// Usage an agent reported for one prompt turn in its `session/prompt` response
+pub fn prompt_usage(message: &Value) -> Option<TokenUsage> {
+    message.pointer("/result/stopReason")?;
+    let usage = message.pointer("/result/usage")?;
+    let count = |key: &str| usage.get(key).and_then(Value::as_i64).unwrap_or(0);
+    Some(TokenUsage {
+        input_tokens: count("inputTokens"),
+        output_tokens: count("outputTokens"),
+        cache_read_tokens: count("cachedReadTokens"),
+        cache_creation_tokens: count("cachedWriteTokens"),
+        cost_usd: None,
+    })
+}
+
+/// The tool name and input a `session/request_permission` request is checked against
+///
+/// ACP has no tool names, so rules match the tool call's kind (`edit`, `execute`, ...) or its
+/// title. The first location stands in for a `path` input the agent didn't report.
+pub fn permission_tool_call(params: &Value) -> (String, Value) {
+    let tool_call = params.get("toolCall").cloned().unwrap_or(Value::Null);
+    let tool_name = ["kind", "title"]
+        .iter()
+        .find_map(|key| tool_call.get(*key).and_then(Value::as_str))
+        .unwrap_or("tool")
+        .to_string();
+
+    let mut input = match tool_call.get("rawInput") {
+        Some(Value::Object(input)) => input.clone(),
+        _ => serde_json::Map::new(),
+    };
+    if !input.contains_key("path") {
+        if let Some(path) = tool_call.pointer("/locations/0/path") {
+            input.insert("path".to_string(), path.clone());
+        }
+    }
+    (tool_name, Value::Object(input))
+}
+
+/// Pick the reply to a `session/request_permission` request the policy has decided
+///
+/// Only one-off options are chosen, so the agent asks again next time rather than remembering
+/// the answer; if it offers none the request is cancelled.
+pub fn permission_outcome(params: &Value, allowed: bool) -> Value {
+    let options = params
+        .get("options")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+
+    let find = |kind: &str| {
+        options
+            .iter()
+            .find(|option| option.get("kind").and_then(Value::as_str) == Some(kind))
+            .and_then(|option| option.get("optionId").cloned())
+    };
+
+    match find(if allowed { "allow_once" } else { "reject_once" }) {
+        Some(option_id) => serde_json::json!({ "outcome": "selected", "optionId": option_id }),
+        None => serde_json::json!({ "outcome": "cancelled" }),
+    }
+}
+
+impl ToolCall {
+    fn apply(&mut self, update: ToolCallUpdate) {
+        if let Some(title) = update.title {
+            self.title = title;
+        }
+        if let Some(kind) = update.kind {
+            self.kind = kind;
+        }
+        if update.status.is_some() {
+            self.status = update.status;
+        }
+        if let Some(locations) = update.locations {
+            self.locations = locations;
+        }
+        if update.raw_input.is_some() {
+            self.raw_input = update.raw_input;
+        }
+    }
+
+    fn raw_input_str(&self, keys: &[&str]) -> Option<String> {
+        let input = self.raw_input.as_ref()?;
+        keys.iter()
+            .find_map(|key| input.get(*key).and_then(Value::as_str))
+            .map(|s| s.to_string())
+    }
+
+    fn path(&self) -> Option<String> {
+        self.locations
+            .first()
+            .map(|location| location.path.clone())
+            .or_else(|| self.raw_input_str(&["path", "file_path", "abs_path"]))
+    }
+
+    fn action_type(&self, worktree_path: &str) -> ActionType {
+        let path = self.path().map(|p| make_path_relative(&p, worktree_path));
+        match (&self.kind, path) {
+            (ToolKind::Read, Some(path)) => ActionType::FileRead { path },
+            (ToolKind::Edit | ToolKind::Delete | ToolKind::Move, Some(path)) => {
+                ActionType::FileWrite { path }
+            }
+            (ToolKind::Execute, _) => ActionType::CommandRun {
+                command: self
+                    .raw_input_str(&["command", "cmd"])
+                    .unwrap_or_else(|| self.title.clone()),
+            },
+            (ToolKind::Search, _) => ActionType::Search {
+                query: self
+                    .raw_input_str(&["query", "pattern"])
+                    .unwrap_or_else(|| self.title.clone()),
+            },
+            (ToolKind::Fetch, _) => ActionType::WebFetch {
+                url: self
+                    .raw_input_str(&["url"])
+                    .unwrap_or_else(|| self.title.clone()),
+            },
+            _ => ActionType::Other {
+                description: self.title.clone(),
+            },
+        }
+    }
+
+    fn tool_name(&self) -> String {
+        match self.kind {
+            ToolKind::Read => "read",
+            ToolKind::Edit => "edit",
+            ToolKind::Delete => "delete",
+            ToolKind::Move => "move",
+            ToolKind::Search => "search",
+            ToolKind::Execute => "execute",
+            ToolKind::Think => "think",
+            ToolKind::Fetch => "fetch",
+            ToolKind::Other => "tool",
+        }
+        .to_string()
+    }
+
+    fn to_entry(&self, worktree_path: &str) -> NormalizedEntry {
+        let action_type = self.action_type(worktree_path);
+        let content = if self.title.is_empty() {
+            match &action_type {
+                ActionType::FileRead { path } | ActionType::FileWrite { path } => {
+                    format!("`{}`", path)
+                }
+                ActionType::CommandRun { command } => format!("`{}`", command),
+                ActionType::Search { query } => format!("`{}`", query),
+                ActionType::WebFetch { url } => format!("`{}`", url),
+                _ => self.tool_name(),
+            }
+        } else {
+            self.title.clone()
+        };
+
+        let content = match self.status.as_deref() {
+            Some("failed") => format!("{} (failed)", content),
+            _ => content,
+        };
+
+        NormalizedEntry {
+            timestamp: None,
+            entry_type: NormalizedEntryType::ToolUse {
+                tool_name: self.tool_name(),
+                action_type,
+            },
+            content,
+            metadata: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChunkKind {
+    User,
+    Agent,
+    Thought,
+}
+
+/// Accumulates a transcript into normalized entries
+#[derive(Default)]
+pub struct TranscriptNormalizer {
+    entries: Vec<NormalizedEntry>,
+    session_id: Option<String>,
+    last_chunk: Option<ChunkKind>,
+    tool_calls: HashMap<String, (usize, ToolCall)>,
+    permission_requests: HashMap<String, String>,
+    /// Number of entries already handed out by `take_patches`
+    emitted: usize,
+    /// Already-emitted entries modified since the last `take_patches`
+    touched: BTreeSet<usize>,
+}
+
+impl TranscriptNormalizer {
+    pub fn push_line(&mut self, line: &str, worktree_path: &str) {
+        let Ok(message) = serde_json::from_str::<Value>(line) else {
+            return;
+        };
+
+        if let Some(session_id) = message.get("session_id").and_then(Value::as_str) {
+            self.session_id
+                .get_or_insert_with(|| session_id.to_string());
+            return;
+        }
+
+        match message.get("method").and_then(Value::as_str) {
+            Some("session/update") => {
+                let Some(update) = message.pointer("/params/update") else {
+                    return;
+                };
+                if let Ok(update) = serde_json::from_value::<SessionUpdate>(update.clone()) {
+                    self.push_update(update, worktree_path);
+                }
+            }
+            Some("session/request_permission") => {
+                let title = message
+                    .pointer("/params/toolCall/title")
+                    .and_then(Value::as_str)
+                    .unwrap_or("tool call")
+                    .to_string();
+                if let Some(id) = message.get("id") {
+                    self.permission_requests.insert(id.to_string(), title);
+                }
+            }
+            Some(_) => {}
+            None => self.push_response(&message),
+        }
+    }
+
+    fn push_response(&mut self, message: &Value) {
+        if let Some(error) = message.get("error") {
+            let text = error
+                .get("message")
+                .and_then(Value::as_str)
+                .map(|s| s.to_string())
+                .unwrap_or_else(|| error.to_string());
+            self.push_entry(NormalizedEntryType::ErrorMessage, text);
+            return;
+        }
+
+        if let Some(outcome) = message.pointer("/result/outcome") {
+            let title = message
+                .get("id")
+                .and_then(|id| self.permission_requests.remove(&id.to_string()))
+                .unwrap_or_else(|| "tool call".to_string());
+            let content = match outcome.get("optionId").and_then(Value::as_str) {
+                Some(option_id) => format!("Permission granted for {} ({})", title, option_id),
+                None => format!("Permission denied for {}", title),
+            };
+            self.push_entry(NormalizedEntryType::SystemMessage, content);
+            return;
+        }
+
+        match message
+            .pointer("/result/stopReason")
+            .and_then(Value::as_str)
+        {
+            Some("end_turn") | None => {}
+            Some(reason) => {
+                self.push_entry(
+                    NormalizedEntryType::SystemMessage,
+                    format!("Agent stopped: {}", reason),
+                );
+            }
+        }
+    }
+
+    fn push_update(&mut self, update: SessionUpdate, worktree_path: &str) {
+        match update {
+            SessionUpdate::UserMessageChunk { content } => {
+                self.push_chunk(ChunkKind::User, content)
+            }
+            SessionUpdate::AgentMessageChunk { content } => {
+                self.push_chunk(ChunkKind::Agent, content)
+            }
+            SessionUpdate::AgentThoughtChunk { content } => {
+                self.push_chunk(ChunkKind::Thought, content)
+            }
+            SessionUpdate::ToolCall(tool_call) => {
+                let entry = tool_call.to_entry(worktree_path);
+                self.entries.push(entry);
+                self.last_chunk = None;
+                self.tool_calls.insert(
+                    tool_call.tool_call_id.clone(),
+                    (self.entries.len() - 1, tool_call),
+                );
+            }
+            SessionUpdate::ToolCallUpdate(update) => {
+                match self.tool_calls.get_mut(&update.tool_call_id) {
+                    Some((index, tool_call)) => {
+                        tool_call.apply(update);
+                        self.entries[*index] = tool_call.to_entry(worktree_path);
+                        self.touched.insert(*index);
+                    }
+                    None => {
+                        // Update for a call we never saw announced; treat it as the announcement
+                        let tool_call = ToolCall {
+                            tool_call_id: update.tool_call_id.clone(),
+                            title: String::new(),
+                            kind: ToolKind::Other,
+                            status: None,
+                            locations: vec![],
+                            raw_input: None,
+                        };
+                        self.push_update(SessionUpdate::ToolCall(tool_call), worktree_path);
+                        self.push_update(SessionUpdate::ToolCallUpdate(update), worktree_path);
+                    }
+                }
+            }
+            SessionUpdate::Plan { entries } => {
+                let plan = entries
+                    .iter()
+                    .map(|entry| {
+                        let done = entry.status.as_deref() == Some("completed");
+                        format!("- [{}] {}", if done { "x" } else { " " }, entry.content)
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                self.entries.push(NormalizedEntry {
+                    timestamp: None,
+                    entry_type: NormalizedEntryType::ToolUse {
+                        tool_name: "plan".to_string(),
+                        action_type: ActionType::PlanPresentation { plan: plan.clone() },
+                    },
+                    content: plan,
+                    metadata: None,
+                });
+                self.last_chunk = None;
+            }
+            SessionUpdate::Unknown => {}
+        }
+    }
+
+    /// Streamed chunks of the same kind are merged into a single entry
+    fn push_chunk(&mut self, kind: ChunkKind, content: ContentBlock) {
+        let ContentBlock::Text { text } = content else {
+            return;
+        };
+
+        if self.last_chunk == Some(kind) {
+            if let Some(entry) = self.entries.last_mut() {
+                entry.content.push_str(&text);
+                self.touched.insert(self.entries.len() - 1);
+                return;
+            }
+        }
+
+        let entry_type = match kind {
+            ChunkKind::User => NormalizedEntryType::UserMessage,
+            ChunkKind::Agent => NormalizedEntryType::AssistantMessage,
+            ChunkKind::Thought => NormalizedEntryType::Thinking,
+        };
+        self.push_entry(entry_type, text);
+        self.last_chunk = Some(kind);
+    }
+
+    fn push_entry(&mut self, entry_type: NormalizedEntryType, content: String) {
+        self.entries.push(NormalizedEntry {
+            timestamp: None,
+            entry_type,
+            content,
+            metadata: None,
+        });
+        self.last_chunk = None;
+    }
+
+    /// Patch operations describing every change since the previous call
+    pub fn take_patches(&mut self) -> Vec<Value> {
+        let emitted = self.emitted;
+        let mut patches: Vec<Value> = std::mem::take(&mut self.touched)
+            .into_iter()
+            .filter(|index| *index < emitted)
+            .map(|index| replace_entry_patch(index, &self.entries[index]))
+            .collect();
+        patches.extend(self.entries[emitted..].iter().map(add_entry_patch));
+        self.emitted = self.entries.len();
+        patches
+    }
+
+    pub fn finish(self) -> (Vec<NormalizedEntry>, Option<String>) {
+        (self.entries, self.session_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn normalize(logs: &str) -> (Vec<NormalizedEntry>, Option<String>) {
+        let mut normalizer = TranscriptNormalizer::default();
+        for line in logs.lines() {
+            normalizer.push_line(line, "/repo");
+        }
+        normalizer.finish()
+    }
+
+    #[test]
+    fn test_chunks_are_coalesced() {
+        let logs = r#"{"session_id":"sess-1"}
+{"jsonrpc":"2.0","method":"session/update","params":{"sessionId":"sess-1","update":{"sessionUpdate":"agent_thought_chunk","content":{"type":"text","text":"Let me "}}}}
+{"jsonrpc":"2.0","method":"session/update","params":{"sessionId":"sess-1","update":{"sessionUpdate":"agent_thought_chunk","content":{"type":"text","text":"think"}}}}
+{"jsonrpc":"2.0","method":"session/update","params":{"sessionId":"sess-1","update":{"sessionUpdate":"agent_message_chunk","content":{"type":"text","text":"Hello "}}}}
+{"jsonrpc":"2.0","method":"session/update","params":{"sessionId":"sess-1","update":{"sessionUpdate":"agent_message_chunk","content":{"type":"text","text":"world"}}}}
+{"jsonrpc":"2.0","id":3,"result":{"stopReason":"end_turn"}}"#;
+
+        let (entries, session_id) = normalize(logs);
+        assert_eq!(session_id.as_deref(), Some("sess-1"));
+        assert_eq!(entries.len(), 2);
+        assert!(matches!(
+            entries[0].entry_type,
+            NormalizedEntryType::Thinking
+        ));
+        assert_eq!(entries[0].content, "Let me think");
+        assert!(matches!(
+            entries[1].entry_type,
+            NormalizedEntryType::AssistantMessage
+        ));
+        assert_eq!(entries[1].content, "Hello world");
+    }
+
+    #[test]
+    fn test_tool_call_updates_replace_entry() {
+        let logs = r#"{"jsonrpc":"2.0","method":"session/update","params":{"sessionId":"s","update":{"sessionUpdate":"tool_call","toolCallId":"t1","title":"Reading file","kind":"read","status":"pending"}}}
+{"jsonrpc":"2.0","method":"session/update","params":{"sessionId":"s","update":{"sessionUpdate":"tool_call_update","toolCallId":"t1","locations":[{"path":"/repo/src/main.rs"}],"status":"completed"}}}
+{"jsonrpc":"2.0","method":"session/update","params":{"sessionId":"s","update":{"sessionUpdate":"tool_call","toolCallId":"t2","title":"","kind":"execute","rawInput":{"command":"cargo test"}}}}"#;
+
+        let (entries, _) = normalize(logs);
+        assert_eq!(entries.len(), 2);
+        match &entries[0].entry_type {
+            NormalizedEntryType::ToolUse {
+                action_type: ActionType::FileRead { path },
+                ..
+            } => assert_eq!(path, "src/main.rs"),
+            other => panic!("unexpected entry type {:?}", other),
+        }
+        assert_eq!(entries[0].content, "Reading file");
+        assert_eq!(entries[1].content, "`cargo test`");
+    }
+
+    #[test]
+    fn test_take_patches_replaces_grown_entries() {
+        let mut normalizer = TranscriptNormalizer::default();
+        normalizer.push_line(
+            r#"{"jsonrpc":"2.0","method":"session/update","params":{"sessionId":"s","update":{"sessionUpdate":"agent_message_chunk","content":{"type":"text","text":"Hello "}}}}"#,
+            "/repo",
+        );
+        let first = normalizer.take_patches();
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0]["op"], "add");
+
+        normalizer.push_line(
+            r#"{"jsonrpc":"2.0","method":"session/update","params":{"sessionId":"s","update":{"sessionUpdate":"agent_message_chunk","content":{"type":"text","text":"world"}}}}"#,
+            "/repo",
+        );
+        normalizer.push_line(
+            r#"{"jsonrpc":"2.0","method":"session/update","params":{"sessionId":"s","update":{"sessionUpdate":"tool_call","toolCallId":"t1","title":"Reading file","kind":"read"}}}"#,
+            "/repo",
+        );
+        let second = normalizer.take_patches();
+        assert_eq!(second.len(), 2);
+        assert_eq!(second[0]["op"], "replace");
+        assert_eq!(second[0]["path"], "/entries/0");
+        assert_eq!(second[0]["value"]["content"], "Hello world");
+        assert_eq!(second[1]["op"], "add");
+        assert!(normalizer.take_patches().is_empty());
+    }
+
+    #[test]
+    fn test_permission_and_stop_reason() {
+        let logs = r#"{"jsonrpc":"2.0","id":0,"method":"session/request_permission","params":{"sessionId":"s","toolCall":{"toolCallId":"t1","title":"Write config"},"options":[{"optionId":"yes","name":"Allow","kind":"allow_once"}]}}
+{"jsonrpc":"2.0","id":0,"result":{"outcome":{"outcome":"selected","optionId":"yes"}}}
+{"jsonrpc":"2.0","id":3,"result":{"stopReason":"cancelled"}}
+{"jsonrpc":"2.0","id":4,"error":{"code":-32603,"message":"boom"}}"#;
+
+        let (entries, _) = normalize(logs);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(
+            entries[0].content,
+            "Permission granted for Write config (yes)"
+        );
+        assert_eq!(entries[1].content, "Agent stopped: cancelled");
+        assert!(matches!(
+            entries[2].entry_type,
+            NormalizedEntryType::ErrorMessage
+        ));
+    }
+
+    #[test]
+    fn test_prompt_usage_from_turn_response() {
+        let message = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "result": {
+                "stopReason": "end_turn",
+                "usage": {"inputTokens": 120, "outputTokens": 30, "cachedReadTokens": 80}
+            }
+        });
+        let usage = prompt_usage(&message).unwrap();
+        assert_eq!(usage.input_tokens, 120);
+        assert_eq!(usage.output_tokens, 30);
+        assert_eq!(usage.cache_read_tokens, 80);
+
+        let message =
+            serde_json::json!({"jsonrpc": "2.0", "id": 2, "result": {"stopReason": "end_turn"}});
+        assert!(prompt_usage(&message).is_none());
+    }
+
+    #[test]
+    fn test_permission_outcome_only_answers_once() {
+        let params = serde_json::json!({
+            "options": [
+                {"optionId": "once", "kind": "allow_once"},
+                {"optionId": "always", "kind": "allow_always"},
+                {"optionId": "no", "kind": "reject_once"}
+            ]
+        });
+        assert_eq!(permission_outcome(&params, true)["optionId"], "once");
+        assert_eq!(permission_outcome(&params, false)["optionId"], "no");
+
+        let params =
+            serde_json::json!({ "options": [{"optionId": "always", "kind": "allow_always"}] });
+        assert_eq!(permission_outcome(&params, true)["outcome"], "cancelled");
+    }
+
+    #[test]
+    fn test_permission_tool_call_uses_kind_and_location() {
+        let params = serde_json::json!({
+            "toolCall": {
+                "toolCallId": "t1",
+                "title": "Write config",
+                "kind": "edit",
+                "rawInput": {"content": "x"},
+                "locations": [{"path": "/tmp/worktree/config.toml"}]
+            }
+        });
+        let (tool_name, input) = permission_tool_call(&params);
+        assert_eq!(tool_name, "edit");
+        assert_eq!(input["path"], "/tmp/worktree/config.toml");
+        assert_eq!(input["content"], "x");
+    }
+}
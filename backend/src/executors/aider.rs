@@ -6,13 +6,14 @@ use uuid::Uuid;
 use crate::{
     command_runner::{CommandProcess, CommandRunner},
     executor::{
-        ActionType, Executor, ExecutorError, NormalizedConversation, NormalizedEntry,
-        NormalizedEntryType,
+        planning_prompt, render_task_prompt, ActionType, Executor, ExecutorError,
+        NormalizedConversation, NormalizedEntry, NormalizedEntryType, RunOptions,
     },
     models::{
-        execution_process::ExecutionProcess, executor_session::ExecutorSession, task::Task,
-        task_attempt::TaskAttempt,
+        execution_process::ExecutionProcess, executor_session::ExecutorSession,
+        task_attachment::staged_files, task_attempt::TaskAttempt,
     },
+    services::LogStreamService,
     utils::{path::make_path_relative, shell::get_shell_command},
 };
 
@@ -251,6 +252,9 @@ pub async fn stream_aider_stdout_stderr_to_db(
                         .await;
 
                         if let Some(Content { stdout, stderr }) = content {
+                            if let Some(stdout) = &stdout {
+                                LogStreamService::push_stdout(execution_process_id, stdout);
+                            }
                             if let Err(e) = ExecutionProcess::append_output(
                                 &pool,
                                 execution_process_id,
@@ -278,6 +282,9 @@ pub async fn stream_aider_stdout_stderr_to_db(
             if let Some(Content { stdout, stderr }) =
                 flush_buffered_content(&mut diff_state, &worktree_path)
             {
+                if let Some(stdout) = &stdout {
+                    LogStreamService::push_stdout(execution_process_id, stdout);
+                }
                 if let Err(e) = ExecutionProcess::append_output(
                     &pool,
                     execution_process_id,
@@ -343,6 +350,7 @@ pub async fn stream_aider_stdout_stderr_to_db(
 
     // Wait for both tasks to complete
     let _ = tokio::join!(stdout_task, stderr_task);
+    LogStreamService::finish(execution_process_id);
 }
 
 /// Format diff content as a normalized JSON entry for atomic edit actions
@@ -466,27 +474,62 @@ impl AiderExecutor {
             command: "aider . --yes-always --no-show-model-warnings --skip-sanity-check-repo --no-stream --no-fancy-input".to_string(),
         }
     }
+
+    /// Apply a run's model, extra arguments and pinned CLI version to the Aider CLI command
+    pub fn with_run_options(mut self, options: &RunOptions) -> Self {
+        self.command = options.apply(&self.command);
+        self
+    }
+
+    /// Run in Aider's ask mode, which answers questions without editing files
+    pub fn read_only(mut self) -> Self {
+        self.command = format!("{} --chat-mode ask", self.command);
+        self
+    }
+
+    /// Stream stdout and stderr through Aider's filter into the database
+    async fn stream_filtered(
+        child: &mut CommandProcess,
+        pool: &sqlx::SqlitePool,
+        attempt_id: Uuid,
+        execution_process_id: Uuid,
+        worktree_path: &str,
+    ) {
+        // Take stdout and stderr pipes for Aider filtering
+        let streams = child
+            .stream()
+            .await
+            .expect("Failed to get stdio from child process");
+        let stdout = streams
+            .stdout
+            .expect("Failed to take stdout from child process");
+        let stderr = streams
+            .stderr
+            .expect("Failed to take stderr from child process");
+
+        // Start Aider filtering task
+        let pool_clone = pool.clone();
+        let worktree_path_clone = worktree_path.to_string();
+        tokio::spawn(stream_aider_stdout_stderr_to_db(
+            stdout,
+            stderr,
+            pool_clone,
+            attempt_id,
+            execution_process_id,
+            worktree_path_clone,
+        ));
+    }
 }
 
 #[async_trait]
 impl Executor for AiderExecutor {
-    async fn spawn(
+    async fn spawn_with_prompt(
         &self,
-        pool: &sqlx::SqlitePool,
+        _pool: &sqlx::SqlitePool,
         task_id: Uuid,
+        prompt: &str,
         worktree_path: &str,
     ) -> Result<CommandProcess, ExecutorError> {
-        // Get the task to fetch its description
-        let task = Task::find_by_id(pool, task_id)
-            .await?
-            .ok_or(ExecutorError::TaskNotFound)?;
-
-        let prompt = if let Some(task_description) = task.description {
-            format!("{}\n{}", task.title, task_description)
-        } else {
-            task.title.to_string()
-        };
-
         // Create temporary message file
         let base_dir = TaskAttempt::get_worktree_base_dir();
         let sessions_dir = base_dir.join("aider").join("aider-messages");
@@ -516,12 +559,16 @@ impl Executor for AiderExecutor {
 
         // Use shell command for cross-platform compatibility
         let (shell_cmd, shell_arg) = get_shell_command();
-        let aider_command = format!(
+        let mut aider_command = format!(
             "{} --chat-history-file {} --message-file {}",
             &self.command,
             chat_file.to_string_lossy(),
             message_file.to_string_lossy()
         );
+        // Staged attachments go into the chat as read-only files
+        for file in staged_files(worktree_path, false) {
+            aider_command.push_str(&format!(" --read {}", file));
+        }
 
         // Write message file after command is prepared for better error context
         tokio::fs::write(&message_file, prompt.as_bytes())
@@ -546,7 +593,7 @@ impl Executor for AiderExecutor {
 
         let child = command.start().await.map_err(|e| {
             crate::executor::SpawnContext::from_command(&command, &self.executor_type)
-                .with_task(task_id, Some(task.title.clone()))
+                .with_task(task_id, None)
                 .with_context(format!("{} CLI execution for new task", self.executor_type))
                 .spawn_error(e)
         })?;
@@ -590,30 +637,109 @@ impl Executor for AiderExecutor {
 
         let mut child = self.spawn(pool, task_id, worktree_path).await?;
 
-        // Take stdout and stderr pipes for Aider filtering
-        let streams = child
-            .stream()
+        Self::stream_filtered(
+            &mut child,
+            pool,
+            attempt_id,
+            execution_process_id,
+            worktree_path,
+        )
+        .await;
+        Ok(child)
+    }
+
+    async fn spawn_plan(
+        &self,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        worktree_path: &str,
+    ) -> Result<CommandProcess, ExecutorError> {
+        let prompt = planning_prompt(
+            &render_task_prompt(pool, task_id, worktree_path, self.attachment_delivery()).await?,
+        );
+
+        let ask_mode = AiderExecutor {
+            executor_type: self.executor_type.clone(),
+            command: self.command.clone(),
+        }
+        .read_only();
+        ask_mode
+            .spawn_with_prompt(pool, task_id, &prompt, worktree_path)
             .await
-            .expect("Failed to get stdio from child process");
-        let stdout = streams
-            .stdout
-            .expect("Failed to take stdout from child process");
-        let stderr = streams
-            .stderr
-            .expect("Failed to take stderr from child process");
+    }
 
-        // Start Aider filtering task
-        let pool_clone = pool.clone();
-        let worktree_path_clone = worktree_path.to_string();
-        tokio::spawn(stream_aider_stdout_stderr_to_db(
-            stdout,
-            stderr,
-            pool_clone,
+    /// Execute a planning run with Aider filtering for stdout and stderr
+    async fn execute_plan_streaming(
+        &self,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        attempt_id: Uuid,
+        execution_process_id: Uuid,
+        worktree_path: &str,
+    ) -> Result<CommandProcess, ExecutorError> {
+        // Generate our own session ID and store it in the database immediately
+        let session_id = format!("aider_task_{}", task_id);
+        if let Err(e) =
+            ExecutorSession::update_session_id(pool, execution_process_id, &session_id).await
+        {
+            tracing::error!(
+                "Failed to update session ID for execution process {}: {}",
+                execution_process_id,
+                e
+            );
+        } else {
+            tracing::info!(
+                "Set session ID {} for execution process {}",
+                session_id,
+                execution_process_id
+            );
+        }
+
+        let mut child = self.spawn_plan(pool, task_id, worktree_path).await?;
+        Self::stream_filtered(
+            &mut child,
+            pool,
             attempt_id,
             execution_process_id,
-            worktree_path_clone,
-        ));
+            worktree_path,
+        )
+        .await;
+        Ok(child)
+    }
 
+    /// Execute a prompt-driven run with Aider filtering for stdout and stderr
+    async fn execute_prompt_streaming(
+        &self,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        attempt_id: Uuid,
+        execution_process_id: Uuid,
+        prompt: &str,
+        worktree_path: &str,
+    ) -> Result<CommandProcess, ExecutorError> {
+        // Generate our own session ID and store it in the database immediately
+        let session_id = format!("aider_task_{}", task_id);
+        if let Err(e) =
+            ExecutorSession::update_session_id(pool, execution_process_id, &session_id).await
+        {
+            tracing::error!(
+                "Failed to update session ID for execution process {}: {}",
+                execution_process_id,
+                e
+            );
+        }
+
+        let mut child = self
+            .spawn_with_prompt(pool, task_id, prompt, worktree_path)
+            .await?;
+        Self::stream_filtered(
+            &mut child,
+            pool,
+            attempt_id,
+            execution_process_id,
+            worktree_path,
+        )
+        .await;
         Ok(child)
     }
 
@@ -677,30 +803,14 @@ impl Executor for AiderExecutor {
             .spawn_followup(pool, task_id, session_id, prompt, worktree_path)
             .await?;
 
-        // Take stdout and stderr pipes for Aider filtering
-        let streams = child
-            .stream()
-            .await
-            .expect("Failed to get stdio from child process");
-        let stdout = streams
-            .stdout
-            .expect("Failed to take stdout from child process");
-        let stderr = streams
-            .stderr
-            .expect("Failed to take stderr from child process");
-
-        // Start Aider filtering task
-        let pool_clone = pool.clone();
-        let worktree_path_clone = worktree_path.to_string();
-        tokio::spawn(stream_aider_stdout_stderr_to_db(
-            stdout,
-            stderr,
-            pool_clone,
+        Self::stream_filtered(
+            &mut child,
+            pool,
             attempt_id,
             execution_process_id,
-            worktree_path_clone,
-        ));
-
+            worktree_path,
+        )
+        .await;
         Ok(child)
     }
 
@@ -740,12 +850,15 @@ impl Executor for AiderExecutor {
 
         // Use shell command for cross-platform compatibility
         let (shell_cmd, shell_arg) = get_shell_command();
-        let aider_command = format!(
+        let mut aider_command = format!(
             "{} --restore-chat-history --chat-history-file {} --message-file {}",
             self.command,
             chat_file.to_string_lossy(),
             message_file.to_string_lossy()
         );
+        for file in staged_files(worktree_path, false) {
+            aider_command.push_str(&format!(" --read {}", file));
+        }
 
         // Write message file after command is prepared for better error context
         tokio::fs::write(&message_file, prompt.as_bytes())
@@ -9,14 +9,17 @@ use crate::{
     executor,
     executor::{
         ActionType, Executor, ExecutorError, NormalizedConversation, NormalizedEntry,
-        NormalizedEntryType,
+        NormalizedEntryType, RunOptions,
     },
-    models::task::Task,
+    models::token_usage::TokenUsage,
     utils::shell::get_shell_command,
 };
 
 /// An executor that uses Amp to process tasks
-pub struct AmpExecutor;
+#[derive(Default)]
+pub struct AmpExecutor {
+    options: RunOptions,
+}
 
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
 #[serde(tag = "type")]
@@ -89,6 +92,21 @@ impl AmpJson {
         }
     }
 
+    /// Usage carried by a `token-usage` event
+    pub fn token_usage(&self) -> Option<TokenUsage> {
+        let AmpJson::TokenUsage(report) = self else {
+            return None;
+        };
+        let count = |key: &str| report.get(key).and_then(|v| v.as_i64()).unwrap_or(0);
+        Some(TokenUsage {
+            input_tokens: count("inputTokens"),
+            output_tokens: count("outputTokens"),
+            cache_read_tokens: count("cacheReadInputTokens"),
+            cache_creation_tokens: count("cacheCreationInputTokens"),
+            cost_usd: report.get("costUSD").and_then(|c| c.as_f64()),
+        })
+    }
+
     pub fn has_streaming_content(&self) -> bool {
         match self {
             AmpJson::Messages { messages, .. } => messages.iter().any(|(_index, message)| {
@@ -189,50 +207,31 @@ impl AmpContentItem {
 
 #[async_trait]
 impl Executor for AmpExecutor {
-    async fn spawn(
+    async fn spawn_with_prompt(
         &self,
-        pool: &sqlx::SqlitePool,
+        _pool: &sqlx::SqlitePool,
         task_id: Uuid,
+        prompt: &str,
         worktree_path: &str,
     ) -> Result<CommandProcess, ExecutorError> {
-        // Get the task to fetch its description
-        let task = Task::find_by_id(pool, task_id)
-            .await?
-            .ok_or(ExecutorError::TaskNotFound)?;
-
-        let prompt = if let Some(task_description) = task.description {
-            format!(
-                r#"project_id: {}
-            
-Task title: {}
-Task description: {}"#,
-                task.project_id, task.title, task_description
-            )
-        } else {
-            format!(
-                r#"project_id: {}
-            
-Task title: {}"#,
-                task.project_id, task.title
-            )
-        };
-
         // Use shell command for cross-platform compatibility
         let (shell_cmd, shell_arg) = get_shell_command();
         // --format=jsonl is deprecated in latest versions of Amp CLI
-        let amp_command = "npx @sourcegraph/amp@0.0.1752148945-gd8844f --format=jsonl";
+        let amp_command = self
+            .options
+            .apply("npx @sourcegraph/amp@0.0.1752148945-gd8844f --format=jsonl");
 
         let mut command = CommandRunner::new();
         command
             .command(shell_cmd)
             .arg(shell_arg)
-            .arg(amp_command)
-            .stdin(&prompt)
+            .arg(&amp_command)
+            .stdin(prompt)
             .working_dir(worktree_path);
 
         let proc = command.start().await.map_err(|e| {
             executor::SpawnContext::from_command(&command, "Amp")
-                .with_task(task_id, Some(task.title.clone()))
+                .with_task(task_id, None)
                 .with_context("Amp CLI execution for new task")
                 .spawn_error(e)
         })?;
@@ -250,10 +249,10 @@ Task title: {}"#,
     ) -> Result<CommandProcess, ExecutorError> {
         // Use shell command for cross-platform compatibility
         let (shell_cmd, shell_arg) = get_shell_command();
-        let amp_command = format!(
+        let amp_command = self.options.apply(&format!(
             "npx @sourcegraph/amp@0.0.1752148945-gd8844f threads continue {} --format=jsonl",
             session_id
-        );
+        ));
 
         let mut command = CommandRunner::new();
         command
@@ -326,9 +325,23 @@ Task title: {}"#,
             summary: None,
         })
     }
+
+    fn extract_token_usage(&self, logs: &str) -> Option<TokenUsage> {
+        // Amp reports running totals for the thread, so the last report covers the run
+        logs.lines()
+            .rev()
+            .filter_map(|line| serde_json::from_str::<AmpJson>(line.trim()).ok())
+            .find_map(|event| event.token_usage())
+    }
 }
 
 impl AmpExecutor {
+    /// Apply a run's extra arguments and pinned CLI version to the Amp CLI commands
+    pub fn with_run_options(mut self, options: &RunOptions) -> Self {
+        self.options = options.clone();
+        self
+    }
+
     /// Convert absolute paths to relative paths based on worktree path
     fn make_path_relative(&self, path: &str, worktree_path: &str) -> String {
         let path_obj = Path::new(path);
@@ -610,10 +623,27 @@ impl AmpExecutor {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_extract_token_usage_keeps_last_report() {
+        let logs = r#"{"type":"initial","threadID":"T-1"}
+{"type":"token-usage","inputTokens":100,"outputTokens":20,"cacheReadInputTokens":5}
+{"type":"token-usage","inputTokens":250,"outputTokens":60,"cacheReadInputTokens":40,"cacheCreationInputTokens":7}
+{"type":"shutdown"}"#;
+
+        let usage = AmpExecutor::default().extract_token_usage(logs).unwrap();
+        assert_eq!(usage.input_tokens, 250);
+        assert_eq!(usage.output_tokens, 60);
+        assert_eq!(usage.cache_read_tokens, 40);
+        assert_eq!(usage.cache_creation_tokens, 7);
+        assert!(AmpExecutor::default()
+            .extract_token_usage(r#"{"type":"shutdown"}"#)
+            .is_none());
+    }
+
     #[test]
     fn test_filter_streaming_messages() {
         // Test logs that simulate the actual normalize_logs behavior
-        let amp_executor = AmpExecutor;
+        let amp_executor = AmpExecutor::default();
         let logs = r#"{"type":"messages","messages":[[7,{"role":"assistant","content":[{"type":"text","text":"Created all three files: test1.txt, test2.txt, and test3.txt"}],"state":{"type":"streaming"}}]],"toolResults":[]}
 {"type":"messages","messages":[[7,{"role":"assistant","content":[{"type":"text","text":"Created all three files: test1.txt, test2.txt, and test3.txt, each with a line of text."}],"state":{"type":"streaming"}}]],"toolResults":[]}
 {"type":"messages","messages":[[7,{"role":"assistant","content":[{"type":"text","text":"Created all three files: test1.txt, test2.txt, and test3.txt, each with a line of text."}],"state":{"type":"complete","stopReason":"end_turn"}}]],"toolResults":[]}"#;
@@ -637,7 +667,7 @@ mod tests {
     #[test]
     fn test_filter_preserves_messages_without_state() {
         // Test that messages without state metadata are preserved (for compatibility)
-        let amp_executor = AmpExecutor;
+        let amp_executor = AmpExecutor::default();
         let logs = r#"{"type":"messages","messages":[[1,{"role":"assistant","content":[{"type":"text","text":"Regular message"}]}]],"toolResults":[]}"#;
 
         let result = amp_executor.normalize_logs(logs, "/tmp/test");
@@ -5,6 +5,7 @@ use crate::{
     command_runner::CommandProcess,
     executor::{Executor, ExecutorError, NormalizedConversation},
     executors::ClaudeExecutor,
+    models::{task_attachment::AttachmentDelivery, token_usage::TokenUsage},
 };
 
 /// An executor that uses Claude Code Router (CCR) to process tasks
@@ -19,10 +20,20 @@ impl Default for CCRExecutor {
 
 impl CCRExecutor {
     pub fn new() -> Self {
-        Self(ClaudeExecutor::with_command(
-            "claude-code-router".to_string(),
-            "npx -y @musistudio/claude-code-router code -p --dangerously-skip-permissions --verbose --output-format=stream-json".to_string(),
-        ))
+        // The router passes Claude's permission flags through, so it shares Claude's gateway
+        Self(
+            ClaudeExecutor::with_command(
+                "claude-code-router".to_string(),
+                "npx -y @musistudio/claude-code-router code -p --verbose --output-format=stream-json"
+                    .to_string(),
+            )
+            .with_permission_gateway(),
+        )
+    }
+
+    /// Apply a run's extra arguments and pinned CLI version to the router's command
+    pub fn with_run_options(self, options: &crate::executor::RunOptions) -> Self {
+        Self(self.0.with_run_options(options))
     }
 }
 
@@ -37,6 +48,19 @@ impl Executor for CCRExecutor {
         self.0.spawn(pool, task_id, worktree_path).await
     }
 
+    async fn spawn_with_prompt(
+        &self,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        prompt: &str,
+        worktree_path: &str,
+    ) -> Result<CommandProcess, ExecutorError> {
+        // Not `spawn_plan`: Claude's native plan mode would bypass the router
+        self.0
+            .spawn_with_prompt(pool, task_id, prompt, worktree_path)
+            .await
+    }
+
     async fn spawn_followup(
         &self,
         pool: &sqlx::SqlitePool,
@@ -50,6 +74,14 @@ impl Executor for CCRExecutor {
             .await
     }
 
+    fn attachment_delivery(&self) -> AttachmentDelivery {
+        self.0.attachment_delivery()
+    }
+
+    fn format_input(&self, message: &str) -> Option<String> {
+        self.0.format_input(message)
+    }
+
     fn normalize_logs(
         &self,
         logs: &str,
@@ -60,6 +92,11 @@ impl Executor for CCRExecutor {
         result.executor_type = "claude-code-router".to_string();
         Ok(result)
     }
+
+    fn extract_token_usage(&self, logs: &str) -> Option<TokenUsage> {
+        self.0
+            .extract_token_usage(&filter_ccr_service_messages(logs))
+    }
 }
 
 /// Filter out CCR service messages that appear in stdout but shouldn't be shown to users
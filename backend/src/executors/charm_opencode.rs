@@ -4,7 +4,6 @@ use uuid::Uuid;
 use crate::{
     command_runner::{CommandProcess, CommandRunner},
     executor::{Executor, ExecutorError},
-    models::task::Task,
     utils::shell::get_shell_command,
 };
 
@@ -13,34 +12,13 @@ pub struct CharmOpencodeExecutor;
 
 #[async_trait]
 impl Executor for CharmOpencodeExecutor {
-    async fn spawn(
+    async fn spawn_with_prompt(
         &self,
-        pool: &sqlx::SqlitePool,
+        _pool: &sqlx::SqlitePool,
         task_id: Uuid,
+        prompt: &str,
         worktree_path: &str,
     ) -> Result<CommandProcess, ExecutorError> {
-        // Get the task to fetch its description
-        let task = Task::find_by_id(pool, task_id)
-            .await?
-            .ok_or(ExecutorError::TaskNotFound)?;
-
-        let prompt = if let Some(task_description) = task.description {
-            format!(
-                r#"project_id: {}
-            
-Task title: {}
-Task description: {}"#,
-                task.project_id, task.title, task_description
-            )
-        } else {
-            format!(
-                r#"project_id: {}
-            
-Task title: {}"#,
-                task.project_id, task.title
-            )
-        };
-
         // Use shell command for cross-platform compatibility
         let (shell_cmd, shell_arg) = get_shell_command();
         let opencode_command = format!(
@@ -57,7 +35,7 @@ Task title: {}"#,
 
         let proc = command.start().await.map_err(|e| {
             crate::executor::SpawnContext::from_command(&command, "CharmOpenCode")
-                .with_task(task_id, Some(task.title.clone()))
+                .with_task(task_id, None)
                 .with_context("CharmOpenCode CLI execution for new task")
                 .spawn_error(e)
         })?;
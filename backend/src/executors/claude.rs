@@ -1,18 +1,27 @@
-use std::path::Path;
+mod session;
+
+use std::path::{Path, PathBuf};
 
 use async_trait::async_trait;
+use session::{user_message, LiveInputHandle};
 use uuid::Uuid;
 
 use crate::{
     command_runner::{CommandProcess, CommandRunner},
     executor::{
-        ActionType, Executor, ExecutorError, NormalizedConversation, NormalizedEntry,
-        NormalizedEntryType,
+        ActionType, Executor, ExecutorError, ForkInfo, NormalizedConversation, NormalizedEntry,
+        NormalizedEntryType, RunOptions,
+    },
+    mcp::permission_server,
+    models::{
+        permission::PermissionPolicy, task::Task, task_attachment::AttachmentDelivery,
+        token_usage::TokenUsage,
     },
-    models::task::Task,
-    utils::shell::get_shell_command,
+    utils::shell::{get_shell_command, shell_quote},
 };
 
+const CLAUDE_PLAN_COMMAND: &str = "npx -y @anthropic-ai/claude-code@latest -p --permission-mode=plan --verbose --output-format=stream-json";
+
 fn create_watchkill_script(command: &str) -> String {
     let claude_plan_stop_indicator = "Exit plan mode?";
     format!(
@@ -20,7 +29,8 @@ fn create_watchkill_script(command: &str) -> String {
 set -euo pipefail
 
 word="{}"
-command="{}"
+# The command line is already shell-quoted, so bash splits it into the right words
+command=({})
 
 exit_code=0
 while IFS= read -r line; do
@@ -28,7 +38,7 @@ while IFS= read -r line; do
     if [[ $line == *"$word"* ]]; then
         exit 0
     fi
-done < <($command <&0 2>&1)
+done < <("${{command[@]}}" <&0 2>&1)
 
 exit_code=${{PIPESTATUS[0]}}
 exit "$exit_code"
@@ -37,10 +47,51 @@ exit "$exit_code"
     )
 }
 
+/// Where Claude keeps its settings and sessions
+fn claude_config_dir() -> Option<PathBuf> {
+    std::env::var_os("CLAUDE_CONFIG_DIR")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".claude")))
+}
+
+/// The directory Claude keeps the sessions run in `working_dir` in
+fn sessions_dir(config_dir: &Path, working_dir: &str) -> PathBuf {
+    let working_dir = std::fs::canonicalize(working_dir)
+        .map(|path| path.to_string_lossy().into_owned())
+        .unwrap_or_else(|_| working_dir.to_string());
+    let name: String = working_dir
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    config_dir.join("projects").join(name)
+}
+
+/// Make session `session_id`, run in `from`, resumable from `to`
+fn copy_session(config_dir: &Path, session_id: &str, from: &str, to: &str) -> Result<(), String> {
+    let file_name = format!("{}.jsonl", session_id);
+    let source = sessions_dir(config_dir, from).join(&file_name);
+    if !source.is_file() {
+        return Err(format!(
+            "Claude session {} not found at {}",
+            session_id,
+            source.display()
+        ));
+    }
+
+    let target_dir = sessions_dir(config_dir, to);
+    std::fs::create_dir_all(&target_dir)
+        .and_then(|_| std::fs::copy(&source, target_dir.join(&file_name)))
+        .map_err(|e| format!("Failed to copy Claude session {}: {}", session_id, e))?;
+    Ok(())
+}
+
 /// An executor that uses Claude CLI to process tasks
 pub struct ClaudeExecutor {
     executor_type: String,
     command: String,
+    plan_mode: bool, // Runs `command` in the watchkill script, stopping once the plan is written
+    options: RunOptions,
+    permission_gateway: bool, // Ask the backend's permission server before risky tool calls
 }
 
 impl Default for ClaudeExecutor {
@@ -54,16 +105,22 @@ impl ClaudeExecutor {
     pub fn new() -> Self {
         Self {
             executor_type: "Claude Code".to_string(),
-            command: "npx -y @anthropic-ai/claude-code@latest -p --dangerously-skip-permissions --verbose --output-format=stream-json".to_string(),
+            command:
+                "npx -y @anthropic-ai/claude-code@latest -p --verbose --output-format=stream-json"
+                    .to_string(),
+            plan_mode: false,
+            options: RunOptions::default(),
+            permission_gateway: true,
         }
     }
 
     pub fn new_plan_mode() -> Self {
-        let command = "npx -y @anthropic-ai/claude-code@latest -p --permission-mode=plan --verbose --output-format=stream-json";
-        let script = create_watchkill_script(command);
         Self {
             executor_type: "ClaudePlan".to_string(),
-            command: script,
+            command: CLAUDE_PLAN_COMMAND.to_string(),
+            plan_mode: true,
+            options: RunOptions::default(),
+            permission_gateway: true,
         }
     }
 
@@ -72,104 +129,274 @@ impl ClaudeExecutor {
         Self {
             executor_type,
             command,
+            plan_mode: false,
+            options: RunOptions::default(),
+            permission_gateway: false,
         }
     }
-}
 
-#[async_trait]
-impl Executor for ClaudeExecutor {
-    async fn spawn(
+    /// Send tool calls through the permission server, as the default Claude executor does
+    pub fn with_permission_gateway(mut self) -> Self {
+        self.permission_gateway = true;
+        self
+    }
+
+    /// Apply a run's model, extra arguments and pinned CLI version to the CLI commands
+    pub fn with_run_options(mut self, options: &RunOptions) -> Self {
+        self.command = options.apply(&self.command);
+        self.options = options.clone();
+        self
+    }
+
+    /// The CLI command for a task's worktree, with `resume_args` picking the session to
+    /// continue if given, and the MCP config it reads, which must outlive the process
+    async fn command_for_task(
         &self,
         pool: &sqlx::SqlitePool,
         task_id: Uuid,
         worktree_path: &str,
-    ) -> Result<CommandProcess, ExecutorError> {
-        // Get the task to fetch its description
+        resume_args: Option<&str>,
+    ) -> Result<(String, Option<tempfile::TempPath>), ExecutorError> {
+        let mut command = self.command.clone();
+        if let Some(resume_args) = resume_args {
+            command = format!("{} {}", command, resume_args);
+        }
+
+        let mcp_config = if self.permission_gateway {
+            self.permission_gateway_config(pool, task_id, worktree_path)
+                .await?
+        } else {
+            None
+        };
+        if let Some(config_path) = &mcp_config {
+            // The watchkill script runs the command under bash whatever the platform
+            let shell = if self.plan_mode {
+                "bash"
+            } else {
+                get_shell_command().0
+            };
+            command = format!(
+                "{} --mcp-config {} --permission-prompt-tool {}",
+                command,
+                shell_quote(&config_path.to_string_lossy(), shell),
+                permission_server::PERMISSION_PROMPT_TOOL
+            );
+        }
+
+        if self.plan_mode {
+            command = create_watchkill_script(&command);
+        }
+        Ok((command, mcp_config))
+    }
+
+    /// Write the MCP config that sends tool calls through the permission server
+    ///
+    /// Without the server's binary (e.g. under `cargo run`) the gateway is skipped, unless the
+    /// project has a policy: that is refused rather than left unenforced.
+    async fn permission_gateway_config(
+        &self,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        worktree_path: &str,
+    ) -> Result<Option<tempfile::TempPath>, ExecutorError> {
         let task = Task::find_by_id(pool, task_id)
             .await?
             .ok_or(ExecutorError::TaskNotFound)?;
+        let unavailable = |reason: String| {
+            ExecutorError::ConfigurationError(format!(
+                "Permission gateway unavailable for {}: {}",
+                self.executor_type, reason
+            ))
+        };
 
-        let prompt = if let Some(task_description) = task.description {
-            format!(
-                r#"project_id: {}
-            
-Task title: {}
-Task description: {}"#,
-                task.project_id, task.title, task_description
-            )
-        } else {
-            format!(
-                r#"project_id: {}
-            
-Task title: {}"#,
-                task.project_id, task.title
-            )
+        let Some(binary) = permission_server::server_binary() else {
+            if PermissionPolicy::is_configured(pool, task.project_id).await? {
+                return Err(unavailable("MCP server binary not found".to_string()));
+            }
+            tracing::warn!(
+                "MCP server binary not found; running {} without the permission gateway as project {} has no permission policy",
+                self.executor_type,
+                task.project_id
+            );
+            return Ok(None);
         };
 
+        permission_server::write_mcp_config(&binary, task.project_id, worktree_path)
+            .map(Some)
+            .map_err(|e| unavailable(e.to_string()))
+    }
+
+    /// Run `claude_command` on `prompt`, taking further messages on stdin unless in plan mode
+    ///
+    /// Plan runs are cut short by the watchkill script once the plan is presented, so they
+    /// keep reading plain text.
+    fn with_prompt(&self, command: &mut CommandRunner, claude_command: &str, prompt: &str) {
+        if self.plan_mode {
+            command.arg(claude_command).stdin(prompt);
+        } else {
+            command
+                .arg(&format!("{} --input-format=stream-json", claude_command))
+                .stdin(&user_message(prompt))
+                .keep_stdin_open();
+        }
+    }
+
+    /// Continue the session `resume_args` picks with `prompt`
+    async fn spawn_resumed(
+        &self,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        resume_args: &str,
+        prompt: &str,
+        worktree_path: &str,
+        context: String,
+    ) -> Result<CommandProcess, ExecutorError> {
         // Use shell command for cross-platform compatibility
         let (shell_cmd, shell_arg) = get_shell_command();
-        // Pass prompt via stdin instead of command line to avoid shell escaping issues
-        let claude_command = &self.command;
+
+        let (claude_command, mcp_config) = self
+            .command_for_task(pool, task_id, worktree_path, Some(resume_args))
+            .await?;
 
         let mut command = CommandRunner::new();
         command
             .command(shell_cmd)
             .arg(shell_arg)
-            .arg(claude_command)
-            .stdin(&prompt)
             .working_dir(worktree_path)
             .env("NODE_NO_WARNINGS", "1");
+        self.with_prompt(&mut command, &claude_command, prompt);
+        if let Some(mcp_config) = mcp_config {
+            command.temp_file(mcp_config);
+        }
 
         let proc = command.start().await.map_err(|e| {
             crate::executor::SpawnContext::from_command(&command, &self.executor_type)
-                .with_task(task_id, Some(task.title.clone()))
-                .with_context(format!("{} CLI execution for new task", self.executor_type))
+                .with_context(context)
                 .spawn_error(e)
         })?;
-        Ok(proc)
+
+        Ok(self.with_live_input(proc))
     }
 
-    async fn spawn_followup(
+    fn with_live_input(&self, process: CommandProcess) -> CommandProcess {
+        if self.plan_mode {
+            return process;
+        }
+        process.map_handle(|handle| Box::new(LiveInputHandle::new(handle)))
+    }
+}
+
+#[async_trait]
+impl Executor for ClaudeExecutor {
+    async fn spawn_with_prompt(
         &self,
-        _pool: &sqlx::SqlitePool,
-        _task_id: Uuid,
-        session_id: &str,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
         prompt: &str,
         worktree_path: &str,
     ) -> Result<CommandProcess, ExecutorError> {
         // Use shell command for cross-platform compatibility
         let (shell_cmd, shell_arg) = get_shell_command();
-
-        // Determine the command based on whether this is plan mode or not
-        let claude_command = if self.executor_type == "ClaudePlan" {
-            let command = format!(
-                "npx -y @anthropic-ai/claude-code@latest -p --permission-mode=plan --verbose --output-format=stream-json --resume={}",
-                session_id
-            );
-            create_watchkill_script(&command)
-        } else {
-            format!("{} --resume={}", self.command, session_id)
-        };
+        // Pass prompt via stdin instead of command line to avoid shell escaping issues
+        let (claude_command, mcp_config) = self
+            .command_for_task(pool, task_id, worktree_path, None)
+            .await?;
 
         let mut command = CommandRunner::new();
         command
             .command(shell_cmd)
             .arg(shell_arg)
-            .arg(&claude_command)
-            .stdin(prompt)
             .working_dir(worktree_path)
             .env("NODE_NO_WARNINGS", "1");
+        self.with_prompt(&mut command, &claude_command, prompt);
+        if let Some(mcp_config) = mcp_config {
+            command.temp_file(mcp_config);
+        }
 
         let proc = command.start().await.map_err(|e| {
             crate::executor::SpawnContext::from_command(&command, &self.executor_type)
-                .with_context(format!(
-                    "{} CLI followup execution for session {}",
-                    self.executor_type, session_id
-                ))
+                .with_task(task_id, None)
+                .with_context(format!("{} CLI execution for new task", self.executor_type))
                 .spawn_error(e)
         })?;
+        Ok(self.with_live_input(proc))
+    }
+
+    async fn spawn_plan(
+        &self,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        worktree_path: &str,
+    ) -> Result<CommandProcess, ExecutorError> {
+        // Claude has a native plan mode that presents its plan through ExitPlanMode
+        ClaudeExecutor::new_plan_mode()
+            .with_run_options(&self.options)
+            .spawn(pool, task_id, worktree_path)
+            .await
+    }
+
+    async fn spawn_followup(
+        &self,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        session_id: &str,
+        prompt: &str,
+        worktree_path: &str,
+    ) -> Result<CommandProcess, ExecutorError> {
+        self.spawn_resumed(
+            pool,
+            task_id,
+            &format!("--resume={}", session_id),
+            prompt,
+            worktree_path,
+            format!(
+                "{} CLI followup execution for session {}",
+                self.executor_type, session_id
+            ),
+        )
+        .await
+    }
+
+    /// Claude looks sessions up by working directory, so the forked one is copied into the
+    /// fork's before `--fork-session` continues it under a new id
+    async fn spawn_fork(
+        &self,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        fork: &ForkInfo,
+        worktree_path: &str,
+    ) -> Result<CommandProcess, ExecutorError> {
+        let config_dir = claude_config_dir().ok_or_else(|| {
+            ExecutorError::ConfigurationError("Claude config directory not found".to_string())
+        })?;
+        copy_session(
+            &config_dir,
+            &fork.session_id,
+            &fork.source_worktree_path,
+            worktree_path,
+        )
+        .map_err(ExecutorError::InvalidSessionId)?;
+        self.spawn_resumed(
+            pool,
+            task_id,
+            &format!("--resume={} --fork-session", fork.session_id),
+            &fork.prompt,
+            worktree_path,
+            format!(
+                "{} CLI fork of session {}",
+                self.executor_type, fork.session_id
+            ),
+        )
+        .await
+    }
 
-        Ok(proc)
+    /// Claude reads `@path` mentions into the conversation
+    fn attachment_delivery(&self) -> AttachmentDelivery {
+        AttachmentDelivery::Mentions
+    }
+
+    fn format_input(&self, message: &str) -> Option<String> {
+        (!self.plan_mode).then(|| user_message(message))
     }
 
     fn normalize_logs(
@@ -351,6 +578,39 @@ Task title: {}"#,
             summary: None,
         })
     }
+
+    fn extract_token_usage(&self, logs: &str) -> Option<TokenUsage> {
+        use serde_json::Value;
+
+        let mut total: Option<TokenUsage> = None;
+
+        // Each run ends with a "result" message carrying the usage and cost of the run
+        for line in logs.lines() {
+            let Ok(json) = serde_json::from_str::<Value>(line.trim()) else {
+                continue;
+            };
+            if json.get("type").and_then(|t| t.as_str()) != Some("result") {
+                continue;
+            }
+
+            let count = |key: &str| {
+                json.get("usage")
+                    .and_then(|u| u.get(key))
+                    .and_then(|v| v.as_i64())
+                    .unwrap_or(0)
+            };
+            let usage = TokenUsage {
+                input_tokens: count("input_tokens"),
+                output_tokens: count("output_tokens"),
+                cache_read_tokens: count("cache_read_input_tokens"),
+                cache_creation_tokens: count("cache_creation_input_tokens"),
+                cost_usd: json.get("total_cost_usd").and_then(|c| c.as_f64()),
+            };
+            total.get_or_insert_with(TokenUsage::default).add(&usage);
+        }
+
+        total
+    }
 }
 
 impl ClaudeExecutor {
@@ -655,6 +915,40 @@ mod tests {
             .any(|e| e.content.contains("Unrecognized JSON")));
     }
 
+    #[test]
+    fn test_extract_token_usage_from_result() {
+        let executor = ClaudeExecutor::new();
+        let logs = r#"{"type":"assistant","message":{"role":"assistant","content":[{"type":"text","text":"Hi"}],"usage":{"input_tokens":3,"output_tokens":1}},"session_id":"abc"}
+{"type":"result","subtype":"success","is_error":false,"total_cost_usd":0.0421,"usage":{"input_tokens":12,"cache_creation_input_tokens":2048,"cache_read_input_tokens":13000,"output_tokens":350},"result":"Done"}"#;
+
+        let usage = executor.extract_token_usage(logs).unwrap();
+        assert_eq!(usage.input_tokens, 12);
+        assert_eq!(usage.output_tokens, 350);
+        assert_eq!(usage.cache_read_tokens, 13000);
+        assert_eq!(usage.cache_creation_tokens, 2048);
+        assert_eq!(usage.cost_usd, Some(0.0421));
+
+        assert!(executor
+            .extract_token_usage(r#"{"type":"system","subtype":"init"}"#)
+            .is_none());
+    }
+
+    #[test]
+    fn test_watchkill_script_keeps_quoted_arguments() {
+        let script = create_watchkill_script("printf '%s\\n' 'two  words' 'Exit plan mode?' never");
+        let output = std::process::Command::new("bash")
+            .arg("-c")
+            .arg(&script)
+            .output()
+            .unwrap();
+
+        assert!(output.status.success());
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            "two  words\nExit plan mode?\n"
+        );
+    }
+
     #[test]
     fn test_make_path_relative() {
         let executor = ClaudeExecutor::new();
@@ -820,4 +1114,62 @@ mod tests {
 
         assert_eq!(result, "List directory: `components`");
     }
+
+    #[tokio::test]
+    async fn test_gateway_without_binary_is_only_refused_under_a_policy() {
+        if permission_server::server_binary().is_some() {
+            return; // The binary was built next to the tests, so nothing is missing
+        }
+        let pool = crate::test_utils::test_pool().await;
+        let project_id = crate::test_utils::insert_project(&pool, "/tmp/test-repo").await;
+        let task_id = crate::test_utils::insert_task(&pool, project_id).await;
+        let executor = ClaudeExecutor::new();
+
+        let (command, mcp_config) = executor
+            .command_for_task(&pool, task_id, "/tmp/test-worktree", None)
+            .await
+            .expect("Runs without the gateway when the project has no policy");
+        assert!(mcp_config.is_none());
+        assert!(!command.contains("--mcp-config"));
+
+        PermissionPolicy::update(
+            &pool,
+            project_id,
+            &crate::models::permission::UpdatePermissionPolicy {
+                timeout_seconds: 60,
+                default_decision: crate::models::permission::PermissionDecision::Deny,
+                rules: Vec::new(),
+            },
+        )
+        .await
+        .unwrap();
+        let refused = executor
+            .command_for_task(&pool, task_id, "/tmp/test-worktree", None)
+            .await;
+        assert!(matches!(refused, Err(ExecutorError::ConfigurationError(_))));
+    }
+
+    #[test]
+    fn test_forked_session_is_copied_to_the_fork_worktree() {
+        let config_dir = tempfile::tempdir().unwrap();
+        let source = tempfile::tempdir().unwrap();
+        let fork = tempfile::tempdir().unwrap();
+        let (source, fork) = (
+            source.path().to_str().unwrap(),
+            fork.path().to_str().unwrap(),
+        );
+
+        assert!(copy_session(config_dir.path(), "s-1", source, fork).is_err());
+
+        let source_sessions = sessions_dir(config_dir.path(), source);
+        std::fs::create_dir_all(&source_sessions).unwrap();
+        std::fs::write(source_sessions.join("s-1.jsonl"), "{}\n").unwrap();
+        copy_session(config_dir.path(), "s-1", source, fork).unwrap();
+
+        let copied = sessions_dir(config_dir.path(), fork).join("s-1.jsonl");
+        assert_eq!(std::fs::read_to_string(copied).unwrap(), "{}\n");
+        assert!(source_sessions.join("s-1.jsonl").is_file());
+        let name = source_sessions.file_name().unwrap().to_string_lossy();
+        assert!(name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'));
+    }
 }
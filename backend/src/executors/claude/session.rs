@@ -0,0 +1,216 @@
+//! Live input for a running Claude session
+//!
+//! With `--input-format stream-json` Claude reads user messages from stdin as JSON lines,
+//! answers each with its own turn ending in a `result` line, and exits once stdin closes.
+//! Stdin is kept open while there are messages to answer and closed after the last result.
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+use crate::command_runner::{
+    CommandError, CommandExitStatus, CommandStream, ProcessHandle, ResourceUsage, StopOutcome,
+};
+
+const STDOUT_BUFFER_SIZE: usize = 64 * 1024;
+
+/// A user message in Claude's stream-json input format, as one line
+pub fn user_message(text: &str) -> String {
+    let mut line = json!({
+        "type": "user",
+        "message": {
+            "role": "user",
+            "content": [{ "type": "text", "text": text }]
+        }
+    })
+    .to_string();
+    line.push('\n');
+    line
+}
+
+#[derive(Debug, Default)]
+struct Turns {
+    pending: usize, // Messages written after the first that have not been answered yet
+    finished: bool, // The last message was answered, so stdin is to be closed
+}
+
+/// Process handle that closes Claude's stdin once every message it was sent is answered
+pub struct LiveInputHandle {
+    inner: Box<dyn ProcessHandle>,
+    turns: Arc<Mutex<Turns>>,
+    stdin_closed: bool,
+}
+
+impl LiveInputHandle {
+    pub fn new(inner: Box<dyn ProcessHandle>) -> Self {
+        Self {
+            inner,
+            turns: Arc::default(),
+            stdin_closed: false,
+        }
+    }
+
+    async fn close_stdin_when_finished(&mut self) -> Result<(), CommandError> {
+        if !self.stdin_closed && self.turns.lock().unwrap().finished {
+            self.inner.close_stdin().await?;
+            self.stdin_closed = true;
+        }
+        Ok(())
+    }
+}
+
+/// Count a line of Claude's output against the messages still waiting for an answer
+fn record_output(turns: &Mutex<Turns>, line: &[u8]) {
+    let is_result = serde_json::from_slice::<Value>(line)
+        .ok()
+        .is_some_and(|json| json.get("type").and_then(Value::as_str) == Some("result"));
+    if !is_result {
+        return;
+    }
+
+    let mut turns = turns.lock().unwrap();
+    match turns.pending {
+        0 => turns.finished = true,
+        _ => turns.pending -= 1,
+    }
+}
+
+#[async_trait]
+impl ProcessHandle for LiveInputHandle {
+    async fn try_wait(&mut self) -> Result<Option<CommandExitStatus>, CommandError> {
+        self.close_stdin_when_finished().await?;
+        self.inner.try_wait().await
+    }
+
+    async fn wait(&mut self) -> Result<CommandExitStatus, CommandError> {
+        loop {
+            if let Some(status) = self.try_wait().await? {
+                return Ok(status);
+            }
+            tokio::time::sleep(Duration::from_millis(200)).await;
+        }
+    }
+
+    async fn stop(&mut self, grace_period: Duration) -> Result<StopOutcome, CommandError> {
+        self.inner.stop(grace_period).await
+    }
+
+    /// Stdout is passed through unchanged, watching for the `result` that ends each turn
+    async fn stream(&mut self) -> Result<CommandStream, CommandError> {
+        let mut stream = self.inner.stream().await?;
+        let Some(stdout) = stream.stdout.take() else {
+            return Ok(stream);
+        };
+
+        let (mut writer, reader) = tokio::io::duplex(STDOUT_BUFFER_SIZE);
+        let turns = self.turns.clone();
+        tokio::spawn(async move {
+            let mut stdout = BufReader::new(stdout);
+            let mut line = Vec::new();
+            loop {
+                line.clear();
+                match stdout.read_until(b'\n', &mut line).await {
+                    Ok(0) => break,
+                    Ok(_) => {
+                        record_output(&turns, &line);
+                        if writer.write_all(&line).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(e) => {
+                        tracing::warn!("Failed to read Claude output: {}", e);
+                        break;
+                    }
+                }
+            }
+            let _ = writer.shutdown().await;
+        });
+
+        stream.stdout = Some(Box::new(reader));
+        Ok(stream)
+    }
+
+    fn process_id(&self) -> String {
+        self.inner.process_id()
+    }
+
+    async fn write_stdin(&mut self, data: &str) -> Result<(), CommandError> {
+        {
+            let mut turns = self.turns.lock().unwrap();
+            if turns.finished {
+                return Err(CommandError::StdinUnavailable);
+            }
+            turns.pending += 1;
+        }
+
+        let written = self.inner.write_stdin(data).await;
+        if written.is_err() {
+            let mut turns = self.turns.lock().unwrap();
+            turns.pending = turns.pending.saturating_sub(1);
+        }
+        written
+    }
+
+    async fn close_stdin(&mut self) -> Result<(), CommandError> {
+        self.turns.lock().unwrap().finished = true;
+        self.close_stdin_when_finished().await
+    }
+
+    fn resource_usage(&mut self) -> Option<ResourceUsage> {
+        self.inner.resource_usage()
+    }
+
+    async fn sync_workspace(&mut self) -> Result<(), CommandError> {
+        self.inner.sync_workspace().await
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::command_runner::CommandRunner;
+
+    #[tokio::test]
+    async fn test_stdin_closes_once_every_message_is_answered() {
+        // Answers two messages, then waits for stdin to close before exiting
+        let script = r#"read -r first; read -r second; echo '{"type":"result"}'; echo '{"type":"result"}'; cat >/dev/null; echo done"#;
+        let mut process = CommandRunner::new()
+            .command("sh")
+            .arg("-c")
+            .arg(script)
+            .stdin(&user_message("first"))
+            .keep_stdin_open()
+            .start()
+            .await
+            .expect("Failed to start process")
+            .map_handle(|handle| Box::new(LiveInputHandle::new(handle)));
+
+        process
+            .write_stdin(&user_message("second"))
+            .await
+            .expect("Should accept a message while the first turn runs");
+
+        let mut stdout = process.stream().await.unwrap().stdout.unwrap();
+        let status = tokio::time::timeout(Duration::from_secs(10), process.wait())
+            .await
+            .expect("Stdin was never closed")
+            .unwrap();
+        assert!(status.success());
+
+        let mut output = String::new();
+        tokio::io::AsyncReadExt::read_to_string(&mut stdout, &mut output)
+            .await
+            .unwrap();
+        assert_eq!(output.lines().last(), Some("done"));
+
+        assert!(matches!(
+            process.write_stdin(&user_message("too late")).await,
+            Err(CommandError::StdinUnavailable)
+        ));
+    }
+}
@@ -3,7 +3,8 @@ use uuid::Uuid;
 
 use crate::{
     command_runner::{CommandProcess, CommandRunner},
-    executor::{Executor, ExecutorError},
+    executor::{Executor, ExecutorError, IncrementalNormalizer},
+    executors::setup_script::ScriptLogNormalizer,
     models::{project::Project, task::Task},
     utils::shell::get_shell_command,
 };
@@ -118,4 +119,14 @@ impl Executor for CleanupScriptExecutor {
             summary: None,
         })
     }
+
+    fn incremental_normalizer(
+        &self,
+        _worktree_path: &str,
+    ) -> Option<Box<dyn IncrementalNormalizer>> {
+        Some(Box::new(ScriptLogNormalizer::new(format!(
+            "Executing cleanup script:\n{}",
+            self.script
+        ))))
+    }
 }
@@ -9,10 +9,12 @@ use uuid::Uuid;
 use crate::{
     command_runner::{CommandProcess, CommandRunner},
     executor::{
-        ActionType, Executor, ExecutorError, NormalizedConversation, NormalizedEntry,
-        NormalizedEntryType,
+        planning_prompt, render_task_prompt, ActionType, Executor, ExecutorError,
+        NormalizedConversation, NormalizedEntry, NormalizedEntryType, RunOptions,
+    },
+    models::{
+        executor_session::ExecutorSession, task_attachment::staged_files, token_usage::TokenUsage,
     },
-    models::{executor_session::ExecutorSession, task::Task},
     utils::{path::make_path_relative, shell::get_shell_command},
 };
 
@@ -164,10 +166,60 @@ pub async fn stream_codex_stderr_to_extract_session(
     }
 }
 
+/// Stream stdout to the database and scan stderr for the session id (stderr is not saved)
+async fn stream_codex_child(
+    child: &mut CommandProcess,
+    pool: &sqlx::SqlitePool,
+    attempt_id: Uuid,
+    execution_process_id: Uuid,
+) {
+    // Get streams from the child process
+    let streams = child
+        .stream()
+        .await
+        .expect("Failed to get streams from child process");
+
+    // Take stdout for logs (standard streaming)
+    let stdout = streams
+        .stdout
+        .expect("Failed to take stdout from child process");
+
+    // Take stderr for session extraction only (don't save to DB)
+    let stderr = streams
+        .stderr
+        .expect("Failed to take stderr from child process");
+
+    let pool_clone1 = pool.clone();
+    let pool_clone2 = pool.clone();
+
+    // Stream stdout to database (true = is_stdout)
+    tokio::spawn(crate::executor::stream_output_to_db(
+        stdout,
+        pool_clone1,
+        attempt_id,
+        execution_process_id,
+        true,
+    ));
+
+    // Stream stderr for session extraction only (don't save to DB)
+    tokio::spawn(stream_codex_stderr_to_extract_session(
+        stderr,
+        pool_clone2,
+        execution_process_id,
+    ));
+}
+
+/// Codex CLI invocation; the sandbox flags depend on the kind of run
+const CODEX_COMMAND: &str = "npx @openai/codex exec --json --skip-git-repo-check";
+
+/// Lets implementation runs edit the worktree and run commands without prompting
+const CODEX_BYPASS_SANDBOX: &str = "--dangerously-bypass-approvals-and-sandbox";
+
 /// An executor that uses Codex CLI to process tasks
 pub struct CodexExecutor {
     executor_type: String,
-    command: String,
+    options: RunOptions,
+    read_only: bool, // Prompt-driven runs stay in the read-only sandbox, as for reviews
 }
 
 impl Default for CodexExecutor {
@@ -181,52 +233,85 @@ impl CodexExecutor {
     pub fn new() -> Self {
         Self {
             executor_type: "Codex".to_string(),
-            command: "npx @openai/codex exec --json --dangerously-bypass-approvals-and-sandbox --skip-git-repo-check".to_string(),
+            options: RunOptions::default(),
+            read_only: false,
         }
     }
-}
 
-#[async_trait]
-impl Executor for CodexExecutor {
-    async fn spawn(
-        &self,
-        pool: &sqlx::SqlitePool,
-        task_id: Uuid,
-        worktree_path: &str,
-    ) -> Result<CommandProcess, ExecutorError> {
-        // Get the task to fetch its description
-        let task = Task::find_by_id(pool, task_id)
-            .await?
-            .ok_or(ExecutorError::TaskNotFound)?;
-
-        let prompt = if let Some(task_description) = task.description {
-            format!(
-                r#"project_id: {}
-            
-Task title: {}
-Task description: {}"#,
-                task.project_id, task.title, task_description
-            )
-        } else {
-            format!("project_id: {}\n{}", task.project_id, task.title)
-        };
+    /// Apply a run's model, extra arguments and pinned CLI version to the Codex CLI command
+    pub fn with_run_options(mut self, options: &RunOptions) -> Self {
+        self.options = options.clone();
+        self
+    }
+
+    /// Run prompt-driven sessions with the planning command, so they cannot edit the worktree
+    pub fn read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+
+    /// Command line for implementation and follow-up runs
+    fn command(&self) -> String {
+        format!(
+            "{} {}",
+            self.options.apply(CODEX_COMMAND),
+            CODEX_BYPASS_SANDBOX
+        )
+    }
 
+    /// Command line for planning runs, which stay in Codex's read-only sandbox whatever
+    /// extra arguments the run carries
+    fn plan_command(&self) -> String {
+        let mut options = self.options.clone();
+        options
+            .extra_args
+            .retain(|arg| arg != CODEX_BYPASS_SANDBOX && arg != "--full-auto");
+        format!("{} --sandbox read-only", options.apply(CODEX_COMMAND))
+    }
+
+    /// Run `codex_command` in the worktree with `prompt` on stdin
+    fn runner(&self, codex_command: &str, prompt: &str, worktree_path: &str) -> CommandRunner {
         // Use shell command for cross-platform compatibility
         let (shell_cmd, shell_arg) = get_shell_command();
 
+        // Hand staged image attachments to Codex as images rather than paths
+        let mut codex_command = codex_command.to_string();
+        for image in staged_files(worktree_path, true) {
+            codex_command.push_str(&format!(" --image {}", image));
+        }
+
         let mut command = CommandRunner::new();
         command
             .command(shell_cmd)
             .arg(shell_arg)
-            .arg(&self.command)
-            .stdin(&prompt)
+            .arg(&codex_command)
+            .stdin(prompt)
             .working_dir(worktree_path)
             .env("NODE_NO_WARNINGS", "1")
             .env("RUST_LOG", "info"); // Enable rust logging to capture session info
+        command
+    }
+}
+
+#[async_trait]
+impl Executor for CodexExecutor {
+    async fn spawn_with_prompt(
+        &self,
+        _pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        prompt: &str,
+        worktree_path: &str,
+    ) -> Result<CommandProcess, ExecutorError> {
+        let codex_command = if self.read_only {
+            self.plan_command()
+        } else {
+            self.command()
+        };
+        let command = self.runner(&codex_command, prompt, worktree_path);
 
         let child = command.start().await.map_err(|e| {
             crate::executor::SpawnContext::from_command(&command, &self.executor_type)
-                .with_task(task_id, Some(task.title.clone()))
+                .with_task(task_id, None)
                 .with_context(format!("{} CLI execution for new task", self.executor_type))
                 .spawn_error(e)
         })?;
@@ -251,7 +336,7 @@ Task description: {}"#,
 
         let codex_command = format!(
             "{} -c experimental_resume={}",
-            self.command,
+            self.command(),
             rollout_file_path.display()
         );
 
@@ -277,6 +362,25 @@ Task description: {}"#,
         Ok(child)
     }
 
+    async fn spawn_plan(
+        &self,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        worktree_path: &str,
+    ) -> Result<CommandProcess, ExecutorError> {
+        let prompt = planning_prompt(
+            &render_task_prompt(pool, task_id, worktree_path, self.attachment_delivery()).await?,
+        );
+
+        let command = self.runner(&self.plan_command(), &prompt, worktree_path);
+        command.start().await.map_err(|e| {
+            crate::executor::SpawnContext::from_command(&command, &self.executor_type)
+                .with_task(task_id, None)
+                .with_context(format!("{} CLI planning run", self.executor_type))
+                .spawn_error(e)
+        })
+    }
+
     /// Custom streaming setup to handle stderr for session extraction
     async fn execute_streaming(
         &self,
@@ -288,41 +392,37 @@ Task description: {}"#,
     ) -> Result<CommandProcess, ExecutorError> {
         let mut child = self.spawn(pool, task_id, worktree_path).await?;
 
-        // Get streams from the child process
-        let streams = child
-            .stream()
-            .await
-            .expect("Failed to get streams from child process");
-
-        // Take stdout for logs (standard streaming)
-        let stdout = streams
-            .stdout
-            .expect("Failed to take stdout from child process");
-
-        // Take stderr for session extraction only (don't save to DB)
-        let stderr = streams
-            .stderr
-            .expect("Failed to take stderr from child process");
-
-        let pool_clone1 = pool.clone();
-        let pool_clone2 = pool.clone();
-
-        // Stream stdout to database (true = is_stdout)
-        tokio::spawn(crate::executor::stream_output_to_db(
-            stdout,
-            pool_clone1,
-            attempt_id,
-            execution_process_id,
-            true,
-        ));
+        stream_codex_child(&mut child, pool, attempt_id, execution_process_id).await;
+        Ok(child)
+    }
 
-        // Stream stderr for session extraction only (don't save to DB)
-        tokio::spawn(stream_codex_stderr_to_extract_session(
-            stderr,
-            pool_clone2,
-            execution_process_id,
-        ));
+    /// Custom plan streaming with same stderr handling
+    async fn execute_plan_streaming(
+        &self,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        attempt_id: Uuid,
+        execution_process_id: Uuid,
+        worktree_path: &str,
+    ) -> Result<CommandProcess, ExecutorError> {
+        let mut child = self.spawn_plan(pool, task_id, worktree_path).await?;
+        stream_codex_child(&mut child, pool, attempt_id, execution_process_id).await;
+        Ok(child)
+    }
 
+    async fn execute_prompt_streaming(
+        &self,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        attempt_id: Uuid,
+        execution_process_id: Uuid,
+        prompt: &str,
+        worktree_path: &str,
+    ) -> Result<CommandProcess, ExecutorError> {
+        let mut child = self
+            .spawn_with_prompt(pool, task_id, prompt, worktree_path)
+            .await?;
+        stream_codex_child(&mut child, pool, attempt_id, execution_process_id).await;
         Ok(child)
     }
 
@@ -341,41 +441,7 @@ Task description: {}"#,
             .spawn_followup(pool, task_id, session_id, prompt, worktree_path)
             .await?;
 
-        // Get streams from the child process
-        let streams = child
-            .stream()
-            .await
-            .expect("Failed to get streams from child process");
-
-        // Take stdout for logs (standard streaming)
-        let stdout = streams
-            .stdout
-            .expect("Failed to take stdout from child process");
-
-        // Take stderr for session extraction only (don't save to DB)
-        let stderr = streams
-            .stderr
-            .expect("Failed to take stderr from child process");
-
-        let pool_clone1 = pool.clone();
-        let pool_clone2 = pool.clone();
-
-        // Stream stdout to database (true = is_stdout)
-        tokio::spawn(crate::executor::stream_output_to_db(
-            stdout,
-            pool_clone1,
-            attempt_id,
-            execution_process_id,
-            true,
-        ));
-
-        // Stream stderr for session extraction only (don't save to DB)
-        tokio::spawn(stream_codex_stderr_to_extract_session(
-            stderr,
-            pool_clone2,
-            execution_process_id,
-        ));
-
+        stream_codex_child(&mut child, pool, attempt_id, execution_process_id).await;
         Ok(child)
     }
 
@@ -526,7 +592,7 @@ Task description: {}"#,
                             continue;
                         }
                         "token_count" => {
-                            // Skip token count entries
+                            // Usage is recorded separately via extract_token_usage
                             continue;
                         }
                         "agent_message" => {
@@ -614,6 +680,53 @@ Task description: {}"#,
             summary: None,
         })
     }
+
+    fn extract_token_usage(&self, logs: &str) -> Option<TokenUsage> {
+        let mut per_response: Option<TokenUsage> = None;
+        let mut cumulative: Option<TokenUsage> = None;
+
+        // Older Codex versions emit a token_count event per model response, with that response's
+        // counts at the top level. Newer ones report running totals for the session under
+        // `info.total_token_usage`, so only the last of those counts. Cached input is included
+        // in input_tokens either way.
+        for line in logs.lines() {
+            let Ok(json) = serde_json::from_str::<Value>(line.trim()) else {
+                continue;
+            };
+            let Some(msg) = json.get("msg") else {
+                continue;
+            };
+            if msg.get("type").and_then(|t| t.as_str()) != Some("token_count") {
+                continue;
+            }
+
+            match msg.get("info") {
+                Some(info) => {
+                    if let Some(total) = info.get("total_token_usage") {
+                        cumulative = Some(codex_usage(total));
+                    }
+                }
+                None => per_response
+                    .get_or_insert_with(TokenUsage::default)
+                    .add(&codex_usage(msg)),
+            }
+        }
+
+        cumulative.or(per_response)
+    }
+}
+
+/// Usage from a Codex token count object
+fn codex_usage(counts: &Value) -> TokenUsage {
+    let count = |key: &str| counts.get(key).and_then(|v| v.as_i64()).unwrap_or(0);
+    let cached = count("cached_input_tokens");
+    TokenUsage {
+        input_tokens: (count("input_tokens") - cached).max(0),
+        output_tokens: count("output_tokens"),
+        cache_read_tokens: cached,
+        cache_creation_tokens: 0,
+        cost_usd: None,
+    }
 }
 
 #[cfg(test)]
@@ -712,6 +825,53 @@ mod tests {
         assert_eq!(result.entries.len(), 0);
     }
 
+    #[test]
+    fn test_plan_command_is_read_only_whatever_the_extra_args() {
+        let executor = CodexExecutor::new().with_run_options(&RunOptions {
+            model: Some("o3".to_string()),
+            extra_args: vec![CODEX_BYPASS_SANDBOX.to_string(), "--full-auto".to_string()],
+            cli_version: None,
+        });
+
+        let plan = executor.plan_command();
+        assert!(plan.ends_with("--sandbox read-only"));
+        assert!(!plan.contains(CODEX_BYPASS_SANDBOX));
+        assert!(!plan.contains("--full-auto"));
+        assert!(plan.contains("--model 'o3'"));
+        assert!(executor.command().contains(CODEX_BYPASS_SANDBOX));
+    }
+
+    #[test]
+    fn test_extract_token_usage_sums_token_counts() {
+        let executor = CodexExecutor::new();
+        let logs = r#"{"id":"1","msg":{"type":"token_count","input_tokens":1674,"cached_input_tokens":1627,"output_tokens":384,"reasoning_output_tokens":384,"total_tokens":2058}}
+{"id":"1","msg":{"type":"agent_message","message":"Working on it"}}
+{"id":"2","msg":{"type":"token_count","input_tokens":2000,"cached_input_tokens":1000,"output_tokens":16,"total_tokens":2016}}"#;
+
+        let usage = executor.extract_token_usage(logs).unwrap();
+        assert_eq!(usage.input_tokens, 47 + 1000);
+        assert_eq!(usage.cache_read_tokens, 2627);
+        assert_eq!(usage.output_tokens, 400);
+        assert_eq!(usage.cost_usd, None);
+
+        assert!(executor
+            .extract_token_usage(r#"{"id":"1","msg":{"type":"task_started"}}"#)
+            .is_none());
+    }
+
+    #[test]
+    fn test_extract_token_usage_keeps_last_running_total() {
+        let executor = CodexExecutor::new();
+        let logs = r#"{"id":"1","msg":{"type":"token_count","info":null}}
+{"id":"1","msg":{"type":"token_count","info":{"total_token_usage":{"input_tokens":1674,"cached_input_tokens":1627,"output_tokens":384,"total_tokens":2058},"last_token_usage":{"input_tokens":1674,"cached_input_tokens":1627,"output_tokens":384,"total_tokens":2058}}}}
+{"id":"2","msg":{"type":"token_count","info":{"total_token_usage":{"input_tokens":3674,"cached_input_tokens":2627,"output_tokens":400,"total_tokens":4074},"last_token_usage":{"input_tokens":2000,"cached_input_tokens":1000,"output_tokens":16,"total_tokens":2016}}}}"#;
+
+        let usage = executor.extract_token_usage(logs).unwrap();
+        assert_eq!(usage.input_tokens, 1047);
+        assert_eq!(usage.cache_read_tokens, 2627);
+        assert_eq!(usage.output_tokens, 400);
+    }
+
     #[test]
     fn test_normalize_logs_malformed_json() {
         let executor = CodexExecutor::new();
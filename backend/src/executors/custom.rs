@@ -0,0 +1,1079 @@
+use std::{collections::HashMap, io::Write};
+
+use async_trait::async_trait;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use uuid::Uuid;
+
+use crate::{
+    command_runner::{CommandProcess, CommandRunner},
+    executor::{
+        planning_prompt, render_task_prompt, ActionType, Executor, ExecutorError,
+        NormalizedConversation, NormalizedEntry, NormalizedEntryType, SpawnContext,
+    },
+    models::{task::Task, token_usage::TokenUsage},
+    services::LogStreamService,
+    utils::{
+        self,
+        path::make_path_relative,
+        shell::{get_shell_command, shell_quote},
+    },
+};
+
+/// Contents of `custom_executors.json` in the asset directory
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CustomExecutorsFile {
+    #[serde(default)]
+    pub executors: Vec<CustomExecutorDefinition>,
+}
+
+/// A user-declared coding agent CLI
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomExecutorDefinition {
+    /// Unique identifier, referenced as `custom:<name>`
+    pub name: String,
+    /// Human readable label shown in the UI (defaults to `name`)
+    #[serde(default)]
+    pub label: Option<String>,
+    /// Shell command line used to start a new session
+    pub command: String,
+    /// How the task prompt is handed to the command
+    #[serde(default)]
+    pub prompt_delivery: PromptDelivery,
+    /// Keep stdin open while the command runs, so messages can be sent to it one per line
+    #[serde(default)]
+    pub interactive: bool,
+    /// Shell command line used to resume a session; `{session_id}` is substituted
+    #[serde(default)]
+    pub follow_up_command: Option<String>,
+    /// Shell command line for read-only runs; planning falls back to `command`, reviews need it
+    #[serde(default)]
+    pub plan_command: Option<String>,
+    /// Where the session id shows up in stdout
+    #[serde(default)]
+    pub session_id: Option<LinePattern>,
+    /// Rules mapping stdout lines onto normalized entries, first match wins
+    #[serde(default)]
+    pub rules: Vec<OutputRule>,
+    /// Entry type for lines that match no rule; such lines are dropped when unset
+    #[serde(default)]
+    pub fallback: Option<EntryKind>,
+    /// Lines reporting token usage; values from every matching line are summed
+    #[serde(default)]
+    pub usage: Option<UsageRule>,
+    /// Extra environment variables for the command
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+}
+
+/// How the prompt reaches the CLI
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PromptDelivery {
+    /// Written to stdin
+    #[default]
+    Stdin,
+    /// Quoted into the `{prompt}` placeholder, or appended as the last argument
+    Argument,
+    /// Written to a temp file whose path replaces `{prompt_file}`, or is appended
+    File,
+}
+
+/// Selects values from an output line
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LinePattern {
+    /// Line is parsed as JSON and the pointer must resolve (to `equals`, if given)
+    JsonPointer {
+        pointer: String,
+        #[serde(default)]
+        equals: Option<String>,
+    },
+    /// Line must match the regex; the first capture group is the extracted value
+    Regex { pattern: String },
+}
+
+/// Maps matching lines onto a normalized entry
+///
+/// `content`, `tool_name` and `action_value` are templates: `{/json/pointer}` reads from a
+/// JSON line, `{0}`..`{9}` read regex captures and `{line}` is the raw line.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutputRule {
+    #[serde(rename = "match")]
+    pub matcher: LinePattern,
+    pub entry: EntryKind,
+    #[serde(default)]
+    pub content: Option<String>,
+    #[serde(default)]
+    pub tool_name: Option<String>,
+    #[serde(default)]
+    pub action: Option<ActionKind>,
+    #[serde(default)]
+    pub action_value: Option<String>,
+}
+
+/// Reads token counts from matching lines
+///
+/// Each field is a template like those in [`OutputRule`] that must render to a number.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageRule {
+    #[serde(rename = "match")]
+    pub matcher: LinePattern,
+    #[serde(default)]
+    pub input_tokens: Option<String>,
+    #[serde(default)]
+    pub output_tokens: Option<String>,
+    #[serde(default)]
+    pub cache_read_tokens: Option<String>,
+    #[serde(default)]
+    pub cache_creation_tokens: Option<String>,
+    #[serde(default)]
+    pub cost_usd: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryKind {
+    UserMessage,
+    AssistantMessage,
+    ToolUse,
+    SystemMessage,
+    ErrorMessage,
+    Thinking,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActionKind {
+    FileRead,
+    FileWrite,
+    CommandRun,
+    Search,
+    WebFetch,
+    TaskCreate,
+    PlanPresentation,
+    Other,
+}
+
+impl CustomExecutorsFile {
+    /// Load custom executor definitions, treating a missing file as empty
+    pub fn load() -> Result<Self, String> {
+        let path = utils::custom_executors_path();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+    }
+
+    pub fn find(&self, name: &str) -> Option<&CustomExecutorDefinition> {
+        self.executors.iter().find(|def| def.name == name)
+    }
+}
+
+impl CustomExecutorDefinition {
+    pub fn label(&self) -> &str {
+        self.label.as_deref().unwrap_or(&self.name)
+    }
+}
+
+/// Pattern with its regex compiled once up front
+#[derive(Debug, Clone)]
+enum CompiledPattern {
+    JsonPointer {
+        pointer: String,
+        equals: Option<String>,
+    },
+    Regex(Regex),
+}
+
+/// A successful pattern match, used to render templates
+enum LineMatch<'a> {
+    Json(Value),
+    Regex(regex::Captures<'a>),
+}
+
+impl CompiledPattern {
+    fn compile(pattern: &LinePattern) -> Result<Self, String> {
+        match pattern {
+            LinePattern::JsonPointer { pointer, equals } => Ok(CompiledPattern::JsonPointer {
+                pointer: pointer.clone(),
+                equals: equals.clone(),
+            }),
+            LinePattern::Regex { pattern } => Regex::new(pattern)
+                .map(CompiledPattern::Regex)
+                .map_err(|e| format!("Invalid regex '{}': {}", pattern, e)),
+        }
+    }
+
+    fn matches<'a>(&self, line: &'a str) -> Option<LineMatch<'a>> {
+        match self {
+            CompiledPattern::JsonPointer { pointer, equals } => {
+                let json: Value = serde_json::from_str(line).ok()?;
+                let value = json.pointer(pointer)?;
+                if let Some(expected) = equals {
+                    if value_to_string(value) != *expected {
+                        return None;
+                    }
+                }
+                Some(LineMatch::Json(json))
+            }
+            CompiledPattern::Regex(regex) => regex.captures(line).map(LineMatch::Regex),
+        }
+    }
+
+    /// Extract the value this pattern points at
+    fn extract(&self, line: &str) -> Option<String> {
+        match (self, self.matches(line)?) {
+            (CompiledPattern::JsonPointer { pointer, .. }, LineMatch::Json(json)) => {
+                json.pointer(pointer).map(value_to_string)
+            }
+            (_, LineMatch::Regex(captures)) => captures
+                .get(1)
+                .or_else(|| captures.get(0))
+                .map(|m| m.as_str().to_string()),
+            _ => None,
+        }
+    }
+}
+
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Substitute `{...}` placeholders in a rule template
+fn render_template(template: &str, line: &str, line_match: &LineMatch) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let Some(end) = after.find('}') else {
+            result.push_str(&rest[start..]);
+            return result;
+        };
+        let key = &after[..end];
+        let value = match (key, line_match) {
+            ("line", _) => Some(line.to_string()),
+            (key, LineMatch::Json(json)) if key.starts_with('/') => {
+                json.pointer(key).map(value_to_string)
+            }
+            (key, LineMatch::Regex(captures)) => key
+                .parse::<usize>()
+                .ok()
+                .and_then(|i| captures.get(i))
+                .map(|m| m.as_str().to_string()),
+            _ => None,
+        };
+        match value {
+            Some(value) => result.push_str(&value),
+            None => result.push_str(&rest[start..start + end + 2]),
+        }
+        rest = &after[end + 1..];
+    }
+
+    result.push_str(rest);
+    result
+}
+
+#[derive(Debug, Clone)]
+struct CompiledRule {
+    matcher: CompiledPattern,
+    rule: OutputRule,
+}
+
+/// An executor driven entirely by a user-supplied [`CustomExecutorDefinition`]
+pub struct CustomExecutor {
+    name: String,
+    definition: Result<CompiledDefinition, String>,
+    read_only: bool, // Prompt-driven runs use the plan command, as for reviews
+}
+
+#[derive(Debug, Clone)]
+struct CompiledDefinition {
+    definition: CustomExecutorDefinition,
+    session_id: Option<CompiledPattern>,
+    rules: Vec<CompiledRule>,
+    usage: Option<CompiledPattern>,
+}
+
+impl CompiledDefinition {
+    fn compile(definition: CustomExecutorDefinition) -> Result<Self, String> {
+        let session_id = definition
+            .session_id
+            .as_ref()
+            .map(CompiledPattern::compile)
+            .transpose()?;
+        let rules = definition
+            .rules
+            .iter()
+            .map(|rule| {
+                Ok(CompiledRule {
+                    matcher: CompiledPattern::compile(&rule.matcher)?,
+                    rule: rule.clone(),
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        let usage = definition
+            .usage
+            .as_ref()
+            .map(|rule| CompiledPattern::compile(&rule.matcher))
+            .transpose()?;
+
+        Ok(Self {
+            definition,
+            session_id,
+            rules,
+            usage,
+        })
+    }
+}
+
+impl CustomExecutor {
+    /// Look up `name` in the custom executors file
+    ///
+    /// Lookup and compile errors are deferred until the executor is used so that
+    /// `ExecutorConfig::create_executor` can stay infallible.
+    pub fn load(name: &str) -> Self {
+        let definition = CustomExecutorsFile::load().and_then(|file| {
+            file.find(name)
+                .cloned()
+                .ok_or_else(|| format!("Custom executor '{}' is not defined", name))
+        });
+        Self::from_result(name, definition)
+    }
+
+    fn from_result(name: &str, definition: Result<CustomExecutorDefinition, String>) -> Self {
+        Self {
+            name: name.to_string(),
+            definition: definition.and_then(CompiledDefinition::compile),
+            read_only: false,
+        }
+    }
+
+    /// Run prompt-driven sessions with the plan command, which should not edit the worktree
+    ///
+    /// Definitions without a plan command refuse such runs rather than fall back to `command`.
+    pub fn read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn compiled(&self) -> Result<&CompiledDefinition, ExecutorError> {
+        self.definition
+            .as_ref()
+            .map_err(|e| ExecutorError::ConfigurationError(e.clone()))
+    }
+
+    fn executor_type(&self) -> String {
+        format!("custom:{}", self.name)
+    }
+
+    /// Build the shell command line, applying the configured prompt delivery
+    ///
+    /// Returns the command line, the stdin to write, and the prompt file (deleted when dropped)
+    #[allow(clippy::result_large_err, clippy::type_complexity)]
+    fn build_command_line(
+        &self,
+        template: &str,
+        prompt: &str,
+        shell_cmd: &str,
+    ) -> Result<(String, Option<String>, Option<tempfile::TempPath>), ExecutorError> {
+        let compiled = self.compiled()?;
+        match compiled.definition.prompt_delivery {
+            PromptDelivery::Stdin => Ok((template.to_string(), Some(prompt.to_string()), None)),
+            PromptDelivery::Argument => {
+                let quoted = shell_quote(prompt, shell_cmd);
+                Ok((
+                    substitute_or_append(template, "{prompt}", &quoted),
+                    None,
+                    None,
+                ))
+            }
+            PromptDelivery::File => {
+                let write_error = |e: std::io::Error| {
+                    ExecutorError::ContextCollectionFailed(format!(
+                        "Failed to write prompt file: {}",
+                        e
+                    ))
+                };
+                let mut file = tempfile::Builder::new()
+                    .prefix("vibe-kanban-prompt-")
+                    .suffix(".md")
+                    .tempfile()
+                    .map_err(write_error)?;
+                file.write_all(prompt.as_bytes()).map_err(write_error)?;
+                let path = file.into_temp_path();
+                let quoted = shell_quote(&path.to_string_lossy(), shell_cmd);
+                Ok((
+                    substitute_or_append(template, "{prompt_file}", &quoted),
+                    None,
+                    Some(path),
+                ))
+            }
+        }
+    }
+
+    /// Prepare the command runner for a session, applying prompt delivery and env
+    #[allow(clippy::result_large_err)]
+    fn build_runner(
+        &self,
+        template: &str,
+        prompt: &str,
+        worktree_path: &str,
+    ) -> Result<CommandRunner, ExecutorError> {
+        let compiled = self.compiled()?;
+        let (shell_cmd, shell_arg) = get_shell_command();
+        let (command_line, stdin, prompt_file) =
+            self.build_command_line(template, prompt, shell_cmd)?;
+
+        let mut command = CommandRunner::new();
+        command
+            .command(shell_cmd)
+            .arg(shell_arg)
+            .arg(&command_line)
+            .working_dir(worktree_path);
+        if let Some(stdin) = &stdin {
+            command.stdin(stdin);
+        }
+        if compiled.definition.interactive {
+            command.keep_stdin_open();
+        }
+        if let Some(prompt_file) = prompt_file {
+            command.temp_file(prompt_file);
+        }
+        for (key, value) in &compiled.definition.env {
+            command.env(key, value);
+        }
+
+        Ok(command)
+    }
+
+    /// Convert a single stdout line into a normalized entry using the rule set
+    fn normalize_line(
+        &self,
+        compiled: &CompiledDefinition,
+        line: &str,
+        worktree_path: &str,
+    ) -> Option<NormalizedEntry> {
+        for compiled_rule in &compiled.rules {
+            let Some(line_match) = compiled_rule.matcher.matches(line) else {
+                continue;
+            };
+            let rule = &compiled_rule.rule;
+            let render = |template: &Option<String>| {
+                template
+                    .as_deref()
+                    .map(|t| render_template(t, line, &line_match))
+            };
+
+            let content = render(&rule.content).unwrap_or_else(|| line.to_string());
+            let entry_type = match rule.entry {
+                EntryKind::ToolUse => {
+                    let tool_name = render(&rule.tool_name).unwrap_or_else(|| "tool".to_string());
+                    let value = render(&rule.action_value).unwrap_or_else(|| content.clone());
+                    NormalizedEntryType::ToolUse {
+                        action_type: to_action_type(
+                            rule.action.unwrap_or(ActionKind::Other),
+                            value,
+                            worktree_path,
+                        ),
+                        tool_name,
+                    }
+                }
+                kind => simple_entry_type(kind),
+            };
+
+            return Some(NormalizedEntry {
+                timestamp: None,
+                entry_type,
+                content,
+                metadata: serde_json::from_str(line).ok(),
+            });
+        }
+
+        // Usage reports are accounted separately rather than shown in the conversation
+        if compiled
+            .usage
+            .as_ref()
+            .is_some_and(|pattern| pattern.matches(line).is_some())
+        {
+            return None;
+        }
+
+        compiled.definition.fallback.map(|kind| NormalizedEntry {
+            timestamp: None,
+            entry_type: match kind {
+                EntryKind::ToolUse => NormalizedEntryType::ToolUse {
+                    tool_name: "tool".to_string(),
+                    action_type: ActionType::Other {
+                        description: line.to_string(),
+                    },
+                },
+                kind => simple_entry_type(kind),
+            },
+            content: line.to_string(),
+            metadata: None,
+        })
+    }
+}
+
+fn substitute_or_append(template: &str, placeholder: &str, value: &str) -> String {
+    if template.contains(placeholder) {
+        template.replace(placeholder, value)
+    } else {
+        format!("{} {}", template, value)
+    }
+}
+
+fn simple_entry_type(kind: EntryKind) -> NormalizedEntryType {
+    match kind {
+        EntryKind::UserMessage => NormalizedEntryType::UserMessage,
+        EntryKind::AssistantMessage => NormalizedEntryType::AssistantMessage,
+        EntryKind::SystemMessage => NormalizedEntryType::SystemMessage,
+        EntryKind::ErrorMessage => NormalizedEntryType::ErrorMessage,
+        EntryKind::Thinking => NormalizedEntryType::Thinking,
+        EntryKind::ToolUse => unreachable!("tool use entries carry an action type"),
+    }
+}
+
+fn to_action_type(kind: ActionKind, value: String, worktree_path: &str) -> ActionType {
+    match kind {
+        ActionKind::FileRead => ActionType::FileRead {
+            path: make_path_relative(&value, worktree_path),
+        },
+        ActionKind::FileWrite => ActionType::FileWrite {
+            path: make_path_relative(&value, worktree_path),
+        },
+        ActionKind::CommandRun => ActionType::CommandRun { command: value },
+        ActionKind::Search => ActionType::Search { query: value },
+        ActionKind::WebFetch => ActionType::WebFetch { url: value },
+        ActionKind::TaskCreate => ActionType::TaskCreate { description: value },
+        ActionKind::PlanPresentation => ActionType::PlanPresentation { plan: value },
+        ActionKind::Other => ActionType::Other { description: value },
+    }
+}
+
+/// Stream stdout to the database, extracting the session id with the configured pattern
+async fn stream_custom_stdout(
+    output: impl tokio::io::AsyncRead + Unpin,
+    pool: sqlx::SqlitePool,
+    attempt_id: Uuid,
+    execution_process_id: Uuid,
+    session_pattern: CompiledPattern,
+) {
+    use crate::models::{execution_process::ExecutionProcess, executor_session::ExecutorSession};
+
+    let mut reader = BufReader::new(output);
+    let mut line = String::new();
+    let mut session_id_parsed = false;
+
+    loop {
+        line.clear();
+        match reader.read_line(&mut line).await {
+            Ok(0) => break,
+            Ok(_) => {
+                if !session_id_parsed {
+                    if let Some(session_id) = session_pattern.extract(line.trim()) {
+                        if let Err(e) = ExecutorSession::update_session_id(
+                            &pool,
+                            execution_process_id,
+                            &session_id,
+                        )
+                        .await
+                        {
+                            tracing::error!(
+                                "Failed to update session ID for execution process {}: {}",
+                                execution_process_id,
+                                e
+                            );
+                        }
+                        session_id_parsed = true;
+                    }
+                }
+
+                LogStreamService::push_stdout(execution_process_id, &line);
+                if let Err(e) =
+                    ExecutionProcess::append_output(&pool, execution_process_id, Some(&line), None)
+                        .await
+                {
+                    tracing::error!("Failed to update stdout for attempt {}: {}", attempt_id, e);
+                }
+            }
+            Err(e) => {
+                tracing::error!("Error reading stdout for attempt {}: {}", attempt_id, e);
+                break;
+            }
+        }
+    }
+
+    LogStreamService::finish(execution_process_id);
+}
+
+#[async_trait]
+impl Executor for CustomExecutor {
+    async fn spawn_with_prompt(
+        &self,
+        _pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        prompt: &str,
+        worktree_path: &str,
+    ) -> Result<CommandProcess, ExecutorError> {
+        let compiled = self.compiled()?;
+        let template = match &compiled.definition.plan_command {
+            Some(plan_command) if self.read_only => plan_command,
+            None if self.read_only => return Err(ExecutorError::ConfigurationError(format!(
+                "Custom executor '{}' has no read-only mode: its definition sets no plan_command",
+                self.name
+            ))),
+            _ => &compiled.definition.command,
+        };
+        let command = self.build_runner(template, prompt, worktree_path)?;
+        command.start().await.map_err(|e| {
+            SpawnContext::from_command(&command, self.executor_type())
+                .with_task(task_id, None)
+                .with_context(format!("Custom executor '{}' for new task", self.name))
+                .spawn_error(e)
+        })
+    }
+
+    async fn spawn_plan(
+        &self,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        worktree_path: &str,
+    ) -> Result<CommandProcess, ExecutorError> {
+        let compiled = self.compiled()?;
+        let task = Task::find_by_id(pool, task_id)
+            .await?
+            .ok_or(ExecutorError::TaskNotFound)?;
+        let template = compiled
+            .definition
+            .plan_command
+            .as_deref()
+            .unwrap_or(&compiled.definition.command);
+
+        let prompt = planning_prompt(
+            &render_task_prompt(pool, task_id, worktree_path, self.attachment_delivery()).await?,
+        );
+
+        let command = self.build_runner(template, &prompt, worktree_path)?;
+        command.start().await.map_err(|e| {
+            SpawnContext::from_command(&command, self.executor_type())
+                .with_task(task_id, Some(task.title.clone()))
+                .with_context(format!("Custom executor '{}' planning run", self.name))
+                .spawn_error(e)
+        })
+    }
+
+    async fn spawn_followup(
+        &self,
+        _pool: &sqlx::SqlitePool,
+        _task_id: Uuid,
+        session_id: &str,
+        prompt: &str,
+        worktree_path: &str,
+    ) -> Result<CommandProcess, ExecutorError> {
+        let compiled = self.compiled()?;
+        let Some(template) = &compiled.definition.follow_up_command else {
+            return Err(ExecutorError::FollowUpNotSupported);
+        };
+
+        let (shell_cmd, _) = get_shell_command();
+        let command_template =
+            template.replace("{session_id}", &shell_quote(session_id, shell_cmd));
+        let command = self.build_runner(&command_template, prompt, worktree_path)?;
+        command.start().await.map_err(|e| {
+            SpawnContext::from_command(&command, self.executor_type())
+                .with_context(format!(
+                    "Custom executor '{}' followup for session {}",
+                    self.name, session_id
+                ))
+                .spawn_error(e)
+        })
+    }
+
+    fn normalize_logs(
+        &self,
+        logs: &str,
+        worktree_path: &str,
+    ) -> Result<NormalizedConversation, String> {
+        let compiled = self.definition.as_ref().map_err(|e| e.clone())?;
+        let mut entries = Vec::new();
+        let mut session_id = None;
+
+        for line in logs.lines() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+
+            if session_id.is_none() {
+                session_id = compiled
+                    .session_id
+                    .as_ref()
+                    .and_then(|pattern| pattern.extract(trimmed));
+            }
+
+            if let Some(entry) = self.normalize_line(compiled, trimmed, worktree_path) {
+                entries.push(entry);
+            }
+        }
+
+        Ok(NormalizedConversation {
+            entries,
+            session_id,
+            executor_type: self.executor_type(),
+            prompt: None,
+            summary: None,
+        })
+    }
+
+    fn format_input(&self, message: &str) -> Option<String> {
+        let compiled = self.definition.as_ref().ok()?;
+        compiled
+            .definition
+            .interactive
+            .then(|| format!("{}\n", message))
+    }
+
+    fn extract_token_usage(&self, logs: &str) -> Option<TokenUsage> {
+        let compiled = self.definition.as_ref().ok()?;
+        let (rule, pattern) = compiled
+            .definition
+            .usage
+            .as_ref()
+            .zip(compiled.usage.as_ref())?;
+        let mut total: Option<TokenUsage> = None;
+
+        for line in logs.lines() {
+            let trimmed = line.trim();
+            let Some(line_match) = pattern.matches(trimmed) else {
+                continue;
+            };
+            let render = |template: &Option<String>| {
+                template
+                    .as_deref()
+                    .map(|t| render_template(t, trimmed, &line_match))
+            };
+            let count = |template: &Option<String>| {
+                render(template)
+                    .and_then(|v| v.trim().parse::<i64>().ok())
+                    .unwrap_or(0)
+            };
+            let usage = TokenUsage {
+                input_tokens: count(&rule.input_tokens),
+                output_tokens: count(&rule.output_tokens),
+                cache_read_tokens: count(&rule.cache_read_tokens),
+                cache_creation_tokens: count(&rule.cache_creation_tokens),
+                cost_usd: render(&rule.cost_usd).and_then(|v| v.trim().parse::<f64>().ok()),
+            };
+            total.get_or_insert_with(TokenUsage::default).add(&usage);
+        }
+
+        total
+    }
+
+    async fn setup_streaming(
+        &self,
+        child: &mut CommandProcess,
+        pool: &sqlx::SqlitePool,
+        attempt_id: Uuid,
+        execution_process_id: Uuid,
+    ) -> Result<(), ExecutorError> {
+        let session_pattern = self.compiled()?.session_id.clone();
+        let streams = child
+            .stream()
+            .await
+            .expect("Failed to get stdio from child process");
+        let stdout = streams
+            .stdout
+            .expect("Failed to take stdout from child process");
+        let stderr = streams
+            .stderr
+            .expect("Failed to take stderr from child process");
+
+        match session_pattern {
+            Some(pattern) => {
+                tokio::spawn(stream_custom_stdout(
+                    stdout,
+                    pool.clone(),
+                    attempt_id,
+                    execution_process_id,
+                    pattern,
+                ));
+            }
+            None => {
+                tokio::spawn(crate::executor::stream_output_to_db(
+                    stdout,
+                    pool.clone(),
+                    attempt_id,
+                    execution_process_id,
+                    true,
+                ));
+            }
+        }
+        tokio::spawn(crate::executor::stream_output_to_db(
+            stderr,
+            pool.clone(),
+            attempt_id,
+            execution_process_id,
+            false,
+        ));
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn definition() -> CustomExecutorDefinition {
+        serde_json::from_value(serde_json::json!({
+            "name": "my-agent",
+            "command": "my-agent --json",
+            "prompt_delivery": "argument",
+            "follow_up_command": "my-agent --json --resume {session_id}",
+            "session_id": { "type": "json_pointer", "pointer": "/session" },
+            "rules": [
+                {
+                    "match": { "type": "json_pointer", "pointer": "/kind", "equals": "say" },
+                    "entry": "assistant_message",
+                    "content": "{/text}"
+                },
+                {
+                    "match": { "type": "regex", "pattern": "^\\$ (.+)$" },
+                    "entry": "tool_use",
+                    "tool_name": "shell",
+                    "action": "command_run",
+                    "action_value": "{1}"
+                },
+                {
+                    "match": { "type": "regex", "pattern": "^edited (\\S+)$" },
+                    "entry": "tool_use",
+                    "tool_name": "edit",
+                    "action": "file_write",
+                    "content": "Edited `{1}`",
+                    "action_value": "{1}"
+                }
+            ],
+            "fallback": "system_message",
+            "usage": {
+                "match": { "type": "json_pointer", "pointer": "/kind", "equals": "usage" },
+                "input_tokens": "{/in}",
+                "output_tokens": "{/out}",
+                "cost_usd": "{/cost}"
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_format_input_requires_interactive() {
+        let executor = CustomExecutor::from_result("my-agent", Ok(definition()));
+        assert_eq!(executor.format_input("yes"), None);
+
+        let mut interactive = definition();
+        interactive.interactive = true;
+        let executor = CustomExecutor::from_result("my-agent", Ok(interactive));
+        assert_eq!(executor.format_input("yes").as_deref(), Some("yes\n"));
+    }
+
+    #[test]
+    fn test_normalize_logs_applies_rules_in_order() {
+        let executor = CustomExecutor::from_result("my-agent", Ok(definition()));
+        let logs = r#"{"session":"abc-123","kind":"init"}
+{"kind":"say","text":"Looking at the code"}
+$ cargo test
+edited /tmp/work/src/lib.rs
+something else"#;
+
+        let result = executor.normalize_logs(logs, "/tmp/work").unwrap();
+
+        assert_eq!(result.session_id.as_deref(), Some("abc-123"));
+        assert_eq!(result.executor_type, "custom:my-agent");
+        assert_eq!(result.entries.len(), 5);
+
+        assert!(matches!(
+            result.entries[0].entry_type,
+            NormalizedEntryType::SystemMessage
+        ));
+        assert!(matches!(
+            result.entries[1].entry_type,
+            NormalizedEntryType::AssistantMessage
+        ));
+        assert_eq!(result.entries[1].content, "Looking at the code");
+
+        match &result.entries[2].entry_type {
+            NormalizedEntryType::ToolUse {
+                tool_name,
+                action_type: ActionType::CommandRun { command },
+            } => {
+                assert_eq!(tool_name, "shell");
+                assert_eq!(command, "cargo test");
+            }
+            other => panic!("unexpected entry type {:?}", other),
+        }
+
+        match &result.entries[3].entry_type {
+            NormalizedEntryType::ToolUse {
+                action_type: ActionType::FileWrite { path },
+                ..
+            } => assert_eq!(path, "src/lib.rs"),
+            other => panic!("unexpected entry type {:?}", other),
+        }
+        assert_eq!(result.entries[3].content, "Edited `/tmp/work/src/lib.rs`");
+    }
+
+    #[test]
+    fn test_unmatched_lines_dropped_without_fallback() {
+        let mut def = definition();
+        def.fallback = None;
+        let executor = CustomExecutor::from_result("my-agent", Ok(def));
+
+        let result = executor
+            .normalize_logs("noise\n{\"kind\":\"say\",\"text\":\"hi\"}", "/tmp")
+            .unwrap();
+        assert_eq!(result.entries.len(), 1);
+        assert_eq!(result.entries[0].content, "hi");
+    }
+
+    #[test]
+    fn test_extract_token_usage_sums_matching_lines() {
+        let executor = CustomExecutor::from_result("my-agent", Ok(definition()));
+        let logs = r#"{"kind":"usage","in":100,"out":20,"cost":0.5}
+{"kind":"say","text":"hi"}
+{"kind":"usage","in":"50","out":5}"#;
+
+        let usage = executor.extract_token_usage(logs).unwrap();
+        assert_eq!(usage.input_tokens, 150);
+        assert_eq!(usage.output_tokens, 25);
+        assert_eq!(usage.cache_read_tokens, 0);
+        assert_eq!(usage.cost_usd, Some(0.5));
+    }
+
+    #[test]
+    fn test_regex_session_id_uses_first_capture() {
+        let pattern = CompiledPattern::compile(&LinePattern::Regex {
+            pattern: r"session: (\S+)".to_string(),
+        })
+        .unwrap();
+        assert_eq!(
+            pattern.extract("started session: s-42 ok").as_deref(),
+            Some("s-42")
+        );
+        assert_eq!(pattern.extract("no session here"), None);
+    }
+
+    #[test]
+    fn test_invalid_regex_reported_on_use() {
+        let mut def = definition();
+        def.rules[1].matcher = LinePattern::Regex {
+            pattern: "(".to_string(),
+        };
+        let executor = CustomExecutor::from_result("my-agent", Ok(def));
+        assert!(executor.normalize_logs("x", "/tmp").is_err());
+    }
+
+    #[test]
+    fn test_render_template_leaves_unknown_placeholders() {
+        let line_match = LineMatch::Json(serde_json::json!({"a": 1}));
+        assert_eq!(
+            render_template("{/a} {/missing} {line} {", "raw", &line_match),
+            "1 {/missing} raw {"
+        );
+    }
+
+    #[test]
+    fn test_prompt_delivery_argument_substitutes_placeholder() {
+        let executor = CustomExecutor::from_result("my-agent", Ok(definition()));
+
+        let (line, stdin, _) = executor
+            .build_command_line("agent -p {prompt} --json", "it's done", "sh")
+            .unwrap();
+        assert_eq!(line, "agent -p 'it'\\''s done' --json");
+        assert!(stdin.is_none());
+
+        let (line, _, _) = executor.build_command_line("agent", "hello", "sh").unwrap();
+        assert_eq!(line, "agent 'hello'");
+    }
+
+    #[test]
+    fn test_prompt_delivery_file_is_removed_when_dropped() {
+        let mut def = definition();
+        def.prompt_delivery = PromptDelivery::File;
+        let executor = CustomExecutor::from_result("my-agent", Ok(def));
+
+        let (line, stdin, prompt_file) = executor
+            .build_command_line("agent --prompt-file {prompt_file}", "hello", "sh")
+            .unwrap();
+        let prompt_file = prompt_file.expect("file delivery should create a prompt file");
+        let path = prompt_file.to_path_buf();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "hello");
+        assert_eq!(
+            line,
+            format!("agent --prompt-file '{}'", path.to_string_lossy())
+        );
+        assert!(stdin.is_none());
+
+        drop(prompt_file);
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_read_only_runs_use_the_plan_command() {
+        let pool = crate::test_utils::test_pool().await;
+        let dir = tempfile::tempdir().unwrap();
+        let mut def = definition();
+        def.command = "echo editing".to_string();
+        def.plan_command = Some("echo read-only".to_string());
+        let executor = CustomExecutor::from_result("my-agent", Ok(def)).read_only();
+
+        let mut process = executor
+            .spawn_with_prompt(
+                &pool,
+                Uuid::new_v4(),
+                "review",
+                dir.path().to_str().unwrap(),
+            )
+            .await
+            .unwrap();
+        let mut stdout = process.stream().await.unwrap().stdout.unwrap();
+        let mut output = String::new();
+        tokio::io::AsyncReadExt::read_to_string(&mut stdout, &mut output)
+            .await
+            .unwrap();
+        process.wait().await.unwrap();
+        assert_eq!(output.trim(), "read-only review");
+    }
+
+    #[tokio::test]
+    async fn test_read_only_runs_without_a_plan_command_are_refused() {
+        let pool = crate::test_utils::test_pool().await;
+        let dir = tempfile::tempdir().unwrap();
+        let mut def = definition();
+        def.command = "echo editing".to_string();
+        def.plan_command = None;
+        let executor = CustomExecutor::from_result("my-agent", Ok(def)).read_only();
+
+        let result = executor
+            .spawn_with_prompt(
+                &pool,
+                Uuid::new_v4(),
+                "review",
+                dir.path().to_str().unwrap(),
+            )
+            .await;
+        assert!(matches!(result, Err(ExecutorError::ConfigurationError(_))));
+    }
+}
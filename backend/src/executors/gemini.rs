@@ -11,9 +11,7 @@ use async_trait::async_trait;
 use config::{
     max_chunk_size, max_display_size, max_latency_ms, max_message_size, GeminiStreamConfig,
 };
-// Re-export for external use
-use serde_json::Value;
-pub use streaming::GeminiPatchBatch;
+use serde::Deserialize;
 use streaming::GeminiStreaming;
 use uuid::Uuid;
 
@@ -21,50 +19,68 @@ use crate::{
     command_runner::{CommandProcess, CommandRunner},
     executor::{
         Executor, ExecutorError, NormalizedConversation, NormalizedEntry, NormalizedEntryType,
+        RunOptions,
     },
-    models::task::Task,
+    models::{
+        execution_process::ExecutionProcess, task::Task, task_attachment::AttachmentDelivery,
+        token_usage::TokenUsage,
+    },
+    services::LogStreamService,
     utils::shell::get_shell_command,
 };
 
+/// One line of `gemini --output-format stream-json` output
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum GeminiEvent {
+    Message {
+        role: String,
+        content: String,
+    },
+    Result {
+        #[serde(default)]
+        stats: GeminiStats,
+    },
+    #[serde(other)]
+    Other,
+}
+
+/// Run totals from the final `result` event
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct GeminiStats {
+    input_tokens: i64,
+    output_tokens: i64,
+    cached: i64,
+}
+
+impl GeminiEvent {
+    fn parse(line: &str) -> Option<Self> {
+        serde_json::from_str(line.trim()).ok()
+    }
+}
+
 /// An executor that uses Gemini CLI to process tasks
-pub struct GeminiExecutor;
+#[derive(Default)]
+pub struct GeminiExecutor {
+    options: RunOptions,
+}
 
 #[async_trait]
 impl Executor for GeminiExecutor {
-    async fn spawn(
+    async fn spawn_with_prompt(
         &self,
-        pool: &sqlx::SqlitePool,
+        _pool: &sqlx::SqlitePool,
         task_id: Uuid,
+        prompt: &str,
         worktree_path: &str,
     ) -> Result<CommandProcess, ExecutorError> {
-        // Get the task to fetch its description
-        let task = Task::find_by_id(pool, task_id)
-            .await?
-            .ok_or(ExecutorError::TaskNotFound)?;
-
-        let prompt = if let Some(task_description) = task.description {
-            format!(
-                r#"project_id: {}
-            
-Task title: {}
-Task description: {}"#,
-                task.project_id, task.title, task_description
-            )
-        } else {
-            format!(
-                r#"project_id: {}
-            
-Task title: {}"#,
-                task.project_id, task.title
-            )
-        };
-
-        let mut command = Self::create_gemini_command(worktree_path);
-        command.stdin(&prompt);
+        let mut command = self.create_gemini_command(worktree_path);
+        command.stdin(prompt);
 
         let proc = command.start().await.map_err(|e| {
             crate::executor::SpawnContext::from_command(&command, "Gemini")
-                .with_task(task_id, Some(task.title.clone()))
+                .with_task(task_id, None)
                 .with_context("Gemini CLI execution for new task")
                 .spawn_error(e)
         })?;
@@ -102,6 +118,47 @@ Task title: {}"#,
         Ok(proc)
     }
 
+    async fn execute_plan_streaming(
+        &self,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        attempt_id: Uuid,
+        execution_process_id: Uuid,
+        worktree_path: &str,
+    ) -> Result<CommandProcess, ExecutorError> {
+        tracing::info!(
+            "Starting Gemini planning for task {} attempt {}",
+            task_id,
+            attempt_id
+        );
+
+        Self::update_session_id(pool, execution_process_id, &attempt_id.to_string()).await;
+
+        let mut proc = self.spawn_plan(pool, task_id, worktree_path).await?;
+        Self::setup_streaming(pool, &mut proc, attempt_id, execution_process_id).await;
+
+        Ok(proc)
+    }
+
+    async fn execute_prompt_streaming(
+        &self,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        attempt_id: Uuid,
+        execution_process_id: Uuid,
+        prompt: &str,
+        worktree_path: &str,
+    ) -> Result<CommandProcess, ExecutorError> {
+        Self::update_session_id(pool, execution_process_id, &attempt_id.to_string()).await;
+
+        let mut proc = self
+            .spawn_with_prompt(pool, task_id, prompt, worktree_path)
+            .await?;
+        Self::setup_streaming(pool, &mut proc, attempt_id, execution_process_id).await;
+
+        Ok(proc)
+    }
+
     async fn spawn_followup(
         &self,
         pool: &sqlx::SqlitePool,
@@ -154,6 +211,11 @@ Task title: {}"#,
         Ok(proc)
     }
 
+    /// Gemini reads `@path` mentions into the conversation
+    fn attachment_delivery(&self) -> AttachmentDelivery {
+        AttachmentDelivery::Mentions
+    }
+
     fn normalize_logs(
         &self,
         logs: &str,
@@ -168,6 +230,14 @@ Task title: {}"#,
                 continue;
             }
 
+            // The stored run totals are read by extract_token_usage, not shown
+            if matches!(
+                GeminiEvent::parse(trimmed),
+                Some(GeminiEvent::Result { .. })
+            ) {
+                continue;
+            }
+
             // Try to parse as JSON first (for NormalizedEntry format)
             if trimmed.starts_with('{') {
                 match serde_json::from_str::<NormalizedEntry>(trimmed) {
@@ -228,21 +298,46 @@ Task title: {}"#,
         })
     }
 
-    // Note: Gemini streaming is handled by the Gemini-specific WAL system.
-    // See emit_content_batch() method which calls GeminiExecutor::push_patch().
+    fn extract_token_usage(&self, logs: &str) -> Option<TokenUsage> {
+        logs.lines()
+            .rev()
+            .filter_map(GeminiEvent::parse)
+            .find_map(|event| match event {
+                GeminiEvent::Result { stats } => Some(TokenUsage {
+                    // Cached input is included in input_tokens
+                    input_tokens: (stats.input_tokens - stats.cached).max(0),
+                    output_tokens: stats.output_tokens,
+                    cache_read_tokens: stats.cached,
+                    cache_creation_tokens: 0,
+                    cost_usd: None,
+                }),
+                _ => None,
+            })
+    }
+
+    // Note: Gemini pushes its own patches to the shared log stream as content arrives.
+    // See emit_message_patch().
 }
 
 impl GeminiExecutor {
+    /// Apply a run's model, extra arguments and pinned CLI version to the Gemini CLI command
+    pub fn with_run_options(mut self, options: &RunOptions) -> Self {
+        self.options = options.clone();
+        self
+    }
+
     /// Create a standardized Gemini CLI command
-    fn create_gemini_command(worktree_path: &str) -> CommandRunner {
+    fn create_gemini_command(&self, worktree_path: &str) -> CommandRunner {
         let (shell_cmd, shell_arg) = get_shell_command();
-        let gemini_command = "npx @google/gemini-cli@latest --yolo";
+        let gemini_command = self
+            .options
+            .apply("npx @google/gemini-cli@latest --yolo --output-format stream-json");
 
         let mut command = CommandRunner::new();
         command
             .command(shell_cmd)
             .arg(shell_arg)
-            .arg(gemini_command)
+            .arg(&gemini_command)
             .working_dir(worktree_path)
             .env("NODE_NO_WARNINGS", "1");
         command
@@ -316,20 +411,7 @@ impl GeminiExecutor {
         ));
     }
 
-    /// Push patches to the Gemini WAL system
-    pub fn push_patch(execution_process_id: Uuid, patches: Vec<Value>, content_length: usize) {
-        GeminiStreaming::push_patch(execution_process_id, patches, content_length);
-    }
-
-    /// Get WAL batches for an execution process, optionally filtering by cursor
-    pub fn get_wal_batches(
-        execution_process_id: Uuid,
-        after_batch_id: Option<u64>,
-    ) -> Option<Vec<GeminiPatchBatch>> {
-        GeminiStreaming::get_wal_batches(execution_process_id, after_batch_id)
-    }
-
-    /// Clean up WAL when execution process finishes
+    /// Flush remaining content and close the live log when the execution process finishes
     pub async fn finalize_execution(
         pool: &sqlx::SqlitePool,
         execution_process_id: Uuid,
@@ -378,7 +460,7 @@ impl GeminiExecutor {
                 }
             })];
 
-            Self::push_patch(execution_process_id, patch_vec, current_message.len());
+            LogStreamService::push_patches(execution_process_id, patch_vec);
         } else {
             // Growing message: replace current entry
             if *entry_count == 0 {
@@ -396,7 +478,7 @@ impl GeminiExecutor {
                 }
             })];
 
-            Self::push_patch(execution_process_id, patch_vec, current_message.len());
+            LogStreamService::push_patches(execution_process_id, patch_vec);
         }
     }
 
@@ -500,7 +582,7 @@ You are continuing work on the above task. The execution history shows what has
             comprehensive_prompt.len()
         );
 
-        let mut command = GeminiExecutor::create_gemini_command(worktree_path);
+        let mut command = self.create_gemini_command(worktree_path);
         command.stdin(comprehensive_prompt);
 
         let proc = command.start().await.map_err(|e| {
@@ -520,6 +602,57 @@ You are continuing work on the above task. The execution history shows what has
         Ok(proc)
     }
 
+    /// Take the complete lines out of `pending` and return the assistant text they carry
+    ///
+    /// Lines that are not stream-json events (older CLIs print plain text) pass through with
+    /// the usual formatting. The `result` event is returned as is so it can be stored for
+    /// `extract_token_usage`.
+    fn take_stream_text(
+        pending: &mut String,
+        accumulated_message: &str,
+        at_eof: bool,
+    ) -> (String, Option<String>) {
+        let mut text = String::new();
+        let mut result_line = None;
+
+        let complete = match pending.rfind('\n') {
+            Some(pos) => pos + 1,
+            None if at_eof => pending.len(),
+            None => return (text, None),
+        };
+        let lines: String = pending.drain(..complete).collect();
+
+        for line in lines.lines() {
+            match GeminiEvent::parse(line) {
+                Some(GeminiEvent::Message { role, content }) if role == "assistant" => {
+                    text.push_str(&content);
+                }
+                Some(GeminiEvent::Result { .. }) => result_line = Some(line.trim().to_string()),
+                Some(_) => {}
+                None => {
+                    let seen = format!("{}{}", accumulated_message, text);
+                    text.push_str(&Self::format_gemini_output(&format!("{}\n", line), &seen));
+                }
+            }
+        }
+
+        (text, result_line)
+    }
+
+    /// Store the final `result` event alongside the conversation entries
+    async fn store_result_line(pool: &sqlx::SqlitePool, execution_process_id: Uuid, line: &str) {
+        if let Err(e) =
+            ExecutionProcess::append_stdout(pool, execution_process_id, &format!("{}\n", line))
+                .await
+        {
+            tracing::error!(
+                "Failed to store Gemini run totals for execution process {}: {}",
+                execution_process_id,
+                e
+            );
+        }
+    }
+
     /// Format Gemini CLI output by inserting line breaks where periods are directly
     /// followed by capital letters (common Gemini CLI formatting issue).
     /// Handles both intra-chunk and cross-chunk period-to-capital transitions.
@@ -580,6 +713,7 @@ You are continuing work on the above task. The execution history shows what has
         let mut current_message = String::new(); // Current assistant message content
         let mut db_buffer = String::new(); // Buffer for database storage (using ChunkStore)
         let mut entry_count = 0usize; // Track assistant message entries
+        let mut pending_line = String::new(); // Output after the last complete line
 
         let mut read_buf = vec![0u8; chunk_limit.min(max_chunk_size())]; // Use configurable chunk limit, capped for memory efficiency
         let mut last_chunk_emit = Instant::now();
@@ -597,7 +731,16 @@ You are continuing work on the above task. The execution history shows what has
         loop {
             match reader.read(&mut read_buf).await {
                 Ok(0) => {
-                    // EOF: emit final content and flush to database
+                    // EOF: take whatever is left of the last line
+                    let (text, result_line) =
+                        Self::take_stream_text(&mut pending_line, &current_message, true);
+                    current_message.push_str(&text);
+                    db_buffer.push_str(&text);
+                    if let Some(line) = result_line {
+                        Self::store_result_line(&pool, execution_process_id, &line).await;
+                    }
+
+                    // Emit final content and flush to database
                     Self::emit_final_content(
                         execution_process_id,
                         &current_message,
@@ -610,9 +753,13 @@ You are continuing work on the above task. The execution history shows what has
                     break;
                 }
                 Ok(n) => {
-                    // Convert bytes to string and apply Gemini-specific formatting
-                    let raw_chunk = String::from_utf8_lossy(&read_buf[..n]);
-                    let formatted_chunk = Self::format_gemini_output(&raw_chunk, &current_message);
+                    // Convert bytes to string and pull the assistant text out of complete lines
+                    pending_line.push_str(&String::from_utf8_lossy(&read_buf[..n]));
+                    let (formatted_chunk, result_line) =
+                        Self::take_stream_text(&mut pending_line, &current_message, false);
+                    if let Some(line) = result_line {
+                        Self::store_result_line(&pool, execution_process_id, &line).await;
+                    }
 
                     // Add to both buffers
                     current_message.push_str(&formatted_chunk);
@@ -695,3 +842,54 @@ You are continuing work on the above task. The execution history shows what has
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_take_stream_text_keeps_partial_lines() {
+        let mut pending = concat!(
+            r#"{"type":"init","session_id":"s-1","model":"gemini-2.5-pro"}"#,
+            "\n",
+            r#"{"type":"message","role":"user","content":"Fix it"}"#,
+            "\n",
+            r#"{"type":"message","role":"assistant","content":"On it","delta":true}"#,
+            "\n",
+            r#"{"type":"message","role":"assis"#
+        )
+        .to_string();
+
+        let (text, result_line) = GeminiExecutor::take_stream_text(&mut pending, "", false);
+        assert_eq!(text, "On it");
+        assert!(result_line.is_none());
+        assert_eq!(pending, r#"{"type":"message","role":"assis"#);
+
+        pending.push_str(r#"tant","content":" now."}"#);
+        let (text, _) = GeminiExecutor::take_stream_text(&mut pending, "On it", true);
+        assert_eq!(text, " now.");
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn test_result_line_is_usage_not_conversation() {
+        let mut pending = concat!(
+            "Plain text output\n",
+            r#"{"type":"result","status":"success","stats":{"total_tokens":160,"input_tokens":130,"output_tokens":30,"cached":50}}"#,
+            "\n"
+        )
+        .to_string();
+        let (text, result_line) = GeminiExecutor::take_stream_text(&mut pending, "", false);
+        assert_eq!(text, "Plain text output\n");
+        let result_line = result_line.unwrap();
+
+        let executor = GeminiExecutor::default();
+        let usage = executor.extract_token_usage(&result_line).unwrap();
+        assert_eq!(usage.input_tokens, 80);
+        assert_eq!(usage.cache_read_tokens, 50);
+        assert_eq!(usage.output_tokens, 30);
+
+        let conversation = executor.normalize_logs(&result_line, "/tmp").unwrap();
+        assert!(conversation.entries.is_empty());
+    }
+}
@@ -3,26 +3,16 @@
 //! This module contains configuration structures and functions for the Gemini executor,
 //! including environment variable resolution for runtime parameters.
 
-/// Configuration for Gemini WAL compaction and DB chunking
+/// Configuration for Gemini DB chunking
 #[derive(Debug, Clone)]
 pub struct GeminiStreamConfig {
     pub max_db_chunk_size: usize,
-    pub wal_compaction_threshold: usize,
-    pub wal_compaction_size: usize,
-    pub wal_compaction_interval_ms: u64,
-    pub max_wal_batches: usize,
-    pub max_wal_total_size: usize,
 }
 
 impl Default for GeminiStreamConfig {
     fn default() -> Self {
         Self {
             max_db_chunk_size: max_message_size(),
-            wal_compaction_threshold: 40,
-            wal_compaction_size: max_message_size() * 2,
-            wal_compaction_interval_ms: 30000,
-            max_wal_batches: 100,
-            max_wal_total_size: 1024 * 1024, // 1MB per process
         }
     }
 }
@@ -0,0 +1,463 @@
+use std::path::{Component, Path};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    command_runner::{CommandProcess, CommandRunner},
+    executor::{
+        Executor, ExecutorConfig, ExecutorError, ForkInfo, IncrementalNormalizer,
+        NormalizedConversation, NormalizedEntry, NormalizedEntryType, SpawnContext,
+    },
+    models::{executor_session::ExecutorSession, token_usage::TokenUsage},
+    utils::shell::{get_shell_command, shell_quote},
+};
+
+/// One scripted run of the mock agent
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MockScenario {
+    /// Executor whose log format the emitted lines are in, e.g. "claude"; plain text if unset
+    #[serde(default)]
+    pub format: Option<String>,
+    /// Session id reported for the run, so follow-ups and forks can resume it
+    #[serde(default)]
+    pub session_id: Option<String>,
+    #[serde(default)]
+    pub steps: Vec<MockStep>,
+    #[serde(default)]
+    pub exit_code: i32,
+    /// Run replayed by follow-ups and forks; they replay this scenario when unset
+    #[serde(default)]
+    pub follow_up: Option<Box<MockScenario>>,
+}
+
+/// A single action of a mock run, performed in order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MockStep {
+    Stdout {
+        line: String,
+    },
+    Stderr {
+        line: String,
+    },
+    Sleep {
+        ms: u64,
+    },
+    /// Path is relative to the worktree
+    WriteFile {
+        path: String,
+        content: String,
+    },
+    DeleteFile {
+        path: String,
+    },
+}
+
+impl MockScenario {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read mock scenario {}: {}", path, e))?;
+        let scenario: Self = serde_json::from_str(&contents)
+            .map_err(|e| format!("Invalid mock scenario {}: {}", path, e))?;
+        scenario.validate()?;
+        Ok(scenario)
+    }
+
+    /// Files may only be touched inside the worktree
+    fn validate(&self) -> Result<(), String> {
+        for step in &self.steps {
+            if let MockStep::WriteFile { path, .. } | MockStep::DeleteFile { path } = step {
+                let escapes = Path::new(path)
+                    .components()
+                    .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir));
+                if path.is_empty() || escapes {
+                    return Err(format!(
+                        "Mock scenario path '{}' must stay inside the worktree",
+                        path
+                    ));
+                }
+            }
+        }
+        match &self.follow_up {
+            Some(follow_up) => follow_up.validate(),
+            None => Ok(()),
+        }
+    }
+
+    /// The scenario a follow-up replays
+    pub fn follow_up(&self) -> &MockScenario {
+        self.follow_up.as_deref().unwrap_or(self)
+    }
+
+    /// POSIX shell script performing the steps and exiting with the scenario's code
+    pub fn script(&self) -> String {
+        let quote = |value: &str| shell_quote(value, "sh");
+        let mut lines = vec!["set -e".to_string()];
+        for step in &self.steps {
+            lines.push(match step {
+                MockStep::Stdout { line } => format!("printf '%s\\n' {}", quote(line)),
+                MockStep::Stderr { line } => format!("printf '%s\\n' {} >&2", quote(line)),
+                MockStep::Sleep { ms } => format!("sleep {}.{:03}", ms / 1000, ms % 1000),
+                MockStep::WriteFile { path, content } => format!(
+                    "mkdir -p \"$(dirname {path})\" && printf '%s' {} > {path}",
+                    quote(content),
+                    path = quote(path)
+                ),
+                MockStep::DeleteFile { path } => format!("rm -f {}", quote(path)),
+            });
+        }
+        lines.push(format!("exit {}", self.exit_code));
+        lines.join("\n")
+    }
+}
+
+/// A fake agent that replays a scenario file, for exercising the attempt lifecycle offline
+pub struct MockExecutor {
+    scenario_path: String,
+}
+
+impl MockExecutor {
+    pub fn new(scenario_path: impl Into<String>) -> Self {
+        Self {
+            scenario_path: scenario_path.into(),
+        }
+    }
+
+    /// Executor that normalizes the scenario's log format
+    fn format_executor(&self) -> Option<Box<dyn Executor>> {
+        let format = MockScenario::load(&self.scenario_path).ok()?.format?;
+        match format.parse::<ExecutorConfig>() {
+            Ok(ExecutorConfig::Mock { .. }) | Err(_) => None,
+            Ok(config) => Some(config.create_executor()),
+        }
+    }
+
+    async fn spawn_scenario(
+        &self,
+        scenario: &MockScenario,
+        task_id: Uuid,
+        worktree_path: &str,
+    ) -> Result<CommandProcess, ExecutorError> {
+        let (shell_cmd, shell_arg) = get_shell_command();
+        if shell_cmd == "cmd" {
+            return Err(ExecutorError::ConfigurationError(
+                "The mock agent needs a POSIX shell".to_string(),
+            ));
+        }
+
+        let mut command = CommandRunner::new();
+        command
+            .command(shell_cmd)
+            .arg(shell_arg)
+            .arg(&scenario.script())
+            .working_dir(worktree_path);
+
+        command.start().await.map_err(|e| {
+            SpawnContext::from_command(&command, "Mock")
+                .with_task(task_id, None)
+                .with_context(format!("Mock scenario {}", self.scenario_path))
+                .spawn_error(e)
+        })
+    }
+
+    /// Spawn `scenario`, report its session id and stream its output
+    async fn execute_scenario(
+        &self,
+        scenario: &MockScenario,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        attempt_id: Uuid,
+        execution_process_id: Uuid,
+        worktree_path: &str,
+    ) -> Result<CommandProcess, ExecutorError> {
+        if let Some(session_id) = &scenario.session_id {
+            ExecutorSession::update_session_id(pool, execution_process_id, session_id).await?;
+        }
+        let mut child = self
+            .spawn_scenario(scenario, task_id, worktree_path)
+            .await?;
+        self.setup_streaming(&mut child, pool, attempt_id, execution_process_id)
+            .await?;
+        Ok(child)
+    }
+}
+
+#[async_trait]
+impl Executor for MockExecutor {
+    async fn spawn(
+        &self,
+        _pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        worktree_path: &str,
+    ) -> Result<CommandProcess, ExecutorError> {
+        let scenario =
+            MockScenario::load(&self.scenario_path).map_err(ExecutorError::ConfigurationError)?;
+        self.spawn_scenario(&scenario, task_id, worktree_path).await
+    }
+
+    async fn spawn_with_prompt(
+        &self,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        _prompt: &str,
+        worktree_path: &str,
+    ) -> Result<CommandProcess, ExecutorError> {
+        self.spawn(pool, task_id, worktree_path).await
+    }
+
+    async fn spawn_followup(
+        &self,
+        _pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        _session_id: &str,
+        _prompt: &str,
+        worktree_path: &str,
+    ) -> Result<CommandProcess, ExecutorError> {
+        let scenario =
+            MockScenario::load(&self.scenario_path).map_err(ExecutorError::ConfigurationError)?;
+        self.spawn_scenario(scenario.follow_up(), task_id, worktree_path)
+            .await
+    }
+
+    async fn spawn_fork(
+        &self,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        fork: &ForkInfo,
+        worktree_path: &str,
+    ) -> Result<CommandProcess, ExecutorError> {
+        self.spawn_followup(pool, task_id, &fork.session_id, &fork.prompt, worktree_path)
+            .await
+    }
+
+    fn normalize_logs(
+        &self,
+        logs: &str,
+        worktree_path: &str,
+    ) -> Result<NormalizedConversation, String> {
+        if let Some(executor) = self.format_executor() {
+            return executor.normalize_logs(logs, worktree_path);
+        }
+
+        // Plain scenarios read as one assistant message per line
+        let entries = logs
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| NormalizedEntry {
+                timestamp: None,
+                entry_type: NormalizedEntryType::AssistantMessage,
+                content: line.to_string(),
+                metadata: None,
+            })
+            .collect();
+        Ok(NormalizedConversation {
+            entries,
+            session_id: None,
+            executor_type: "mock".to_string(),
+            prompt: None,
+            summary: None,
+        })
+    }
+
+    fn extract_token_usage(&self, logs: &str) -> Option<TokenUsage> {
+        self.format_executor()?.extract_token_usage(logs)
+    }
+
+    fn incremental_normalizer(
+        &self,
+        worktree_path: &str,
+    ) -> Option<Box<dyn IncrementalNormalizer>> {
+        self.format_executor()?
+            .incremental_normalizer(worktree_path)
+    }
+
+    async fn execute_streaming(
+        &self,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        attempt_id: Uuid,
+        execution_process_id: Uuid,
+        worktree_path: &str,
+    ) -> Result<CommandProcess, ExecutorError> {
+        let scenario =
+            MockScenario::load(&self.scenario_path).map_err(ExecutorError::ConfigurationError)?;
+        self.execute_scenario(
+            &scenario,
+            pool,
+            task_id,
+            attempt_id,
+            execution_process_id,
+            worktree_path,
+        )
+        .await
+    }
+
+    async fn execute_plan_streaming(
+        &self,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        attempt_id: Uuid,
+        execution_process_id: Uuid,
+        worktree_path: &str,
+    ) -> Result<CommandProcess, ExecutorError> {
+        self.execute_streaming(
+            pool,
+            task_id,
+            attempt_id,
+            execution_process_id,
+            worktree_path,
+        )
+        .await
+    }
+
+    async fn execute_prompt_streaming(
+        &self,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        attempt_id: Uuid,
+        execution_process_id: Uuid,
+        _prompt: &str,
+        worktree_path: &str,
+    ) -> Result<CommandProcess, ExecutorError> {
+        self.execute_streaming(
+            pool,
+            task_id,
+            attempt_id,
+            execution_process_id,
+            worktree_path,
+        )
+        .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn execute_followup_streaming(
+        &self,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        attempt_id: Uuid,
+        execution_process_id: Uuid,
+        _session_id: &str,
+        _prompt: &str,
+        worktree_path: &str,
+    ) -> Result<CommandProcess, ExecutorError> {
+        let scenario =
+            MockScenario::load(&self.scenario_path).map_err(ExecutorError::ConfigurationError)?;
+        self.execute_scenario(
+            scenario.follow_up(),
+            pool,
+            task_id,
+            attempt_id,
+            execution_process_id,
+            worktree_path,
+        )
+        .await
+    }
+
+    async fn execute_fork_streaming(
+        &self,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        attempt_id: Uuid,
+        execution_process_id: Uuid,
+        fork: &ForkInfo,
+        worktree_path: &str,
+    ) -> Result<CommandProcess, ExecutorError> {
+        self.execute_followup_streaming(
+            pool,
+            task_id,
+            attempt_id,
+            execution_process_id,
+            &fork.session_id,
+            &fork.prompt,
+            worktree_path,
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::AsyncReadExt;
+
+    use super::*;
+
+    fn scenario_json() -> &'static str {
+        r#"{
+            "format": "claude",
+            "session_id": "mock-session",
+            "steps": [
+                {"type": "stdout", "line": "{\"type\":\"assistant\",\"message\":{\"content\":\"it's done\"}}"},
+                {"type": "sleep", "ms": 10},
+                {"type": "write_file", "path": "src/new file.txt", "content": "hello 'world'\n"},
+                {"type": "delete_file", "path": "old.txt"},
+                {"type": "stderr", "line": "warning"}
+            ],
+            "exit_code": 3,
+            "follow_up": {"steps": [{"type": "stdout", "line": "again"}]}
+        }"#
+    }
+
+    #[test]
+    fn test_scenario_parsing_and_validation() {
+        let scenario: MockScenario = serde_json::from_str(scenario_json()).unwrap();
+        assert_eq!(scenario.session_id.as_deref(), Some("mock-session"));
+        assert_eq!(scenario.steps.len(), 5);
+        assert!(scenario.validate().is_ok());
+        assert_eq!(
+            scenario.follow_up().steps,
+            vec![MockStep::Stdout {
+                line: "again".to_string()
+            }]
+        );
+
+        for path in ["../escape.txt", "/etc/passwd", ""] {
+            let scenario = MockScenario {
+                steps: vec![MockStep::DeleteFile {
+                    path: path.to_string(),
+                }],
+                ..Default::default()
+            };
+            assert!(scenario.validate().is_err(), "{} should be rejected", path);
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_scenario_script_replays_steps() {
+        std::env::set_var("ENVIRONMENT", "local");
+        let worktree = tempfile::TempDir::new().unwrap();
+        std::fs::write(worktree.path().join("old.txt"), "stale").unwrap();
+        let scenario: MockScenario = serde_json::from_str(scenario_json()).unwrap();
+
+        let mut command = CommandRunner::new();
+        command
+            .command("sh")
+            .arg("-c")
+            .arg(&scenario.script())
+            .working_dir(worktree.path().to_str().unwrap());
+        let mut process = command.start().await.unwrap();
+        let mut streams = process.stream().await.unwrap();
+        let mut stdout = String::new();
+        streams
+            .stdout
+            .as_mut()
+            .unwrap()
+            .read_to_string(&mut stdout)
+            .await
+            .unwrap();
+        let status = process.wait().await.unwrap();
+
+        assert_eq!(
+            stdout,
+            "{\"type\":\"assistant\",\"message\":{\"content\":\"it's done\"}}\n"
+        );
+        assert_eq!(status.code(), Some(3));
+        assert_eq!(
+            std::fs::read_to_string(worktree.path().join("src/new file.txt")).unwrap(),
+            "hello 'world'\n"
+        );
+        assert!(!worktree.path().join("old.txt").exists());
+    }
+}
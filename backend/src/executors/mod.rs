@@ -1,3 +1,4 @@
+pub mod acp;
 pub mod aider;
 pub mod amp;
 pub mod ccr;
@@ -5,12 +6,16 @@ pub mod charm_opencode;
 pub mod claude;
 pub mod cleanup_script;
 pub mod codex;
+pub mod custom;
 pub mod dev_server;
 pub mod echo;
 pub mod gemini;
+pub mod mock;
+pub mod planning;
 pub mod setup_script;
 pub mod sst_opencode;
 
+pub use acp::AcpExecutor;
 pub use aider::AiderExecutor;
 pub use amp::AmpExecutor;
 pub use ccr::CCRExecutor;
@@ -18,8 +23,11 @@ pub use charm_opencode::CharmOpencodeExecutor;
 pub use claude::ClaudeExecutor;
 pub use cleanup_script::CleanupScriptExecutor;
 pub use codex::CodexExecutor;
+pub use custom::CustomExecutor;
 pub use dev_server::DevServerExecutor;
 pub use echo::EchoExecutor;
 pub use gemini::GeminiExecutor;
+pub use mock::MockExecutor;
+pub use planning::PlanningExecutor;
 pub use setup_script::SetupScriptExecutor;
 pub use sst_opencode::SstOpencodeExecutor;
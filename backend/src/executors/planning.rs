@@ -0,0 +1,420 @@
+//! Plan mode for any executor
+//!
+//! Wraps an agent so its runs go through `spawn_plan`/`execute_plan_streaming`, and turns the
+//! answer into a `PlanPresentation` entry that can be approved for implementation.
+
+use async_trait::async_trait;
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::{
+    command_runner::CommandProcess,
+    executor::{
+        extract_plan, incremental_normalizer_for, plan_entry, replace_entry_patch, ActionType,
+        Executor, ExecutorConfig, ExecutorError, ForkInfo, IncrementalNormalizer,
+        NormalizedConversation, NormalizedEntry, NormalizedEntryType, PlanMode, RunOptions,
+    },
+    models::{
+        execution_process::{ExecutionProcess, ExecutionProcessType},
+        token_usage::TokenUsage,
+    },
+};
+
+/// Runs the configured executor in plan mode
+pub struct PlanningExecutor {
+    config: ExecutorConfig,
+    inner: Box<dyn Executor>,
+}
+
+impl PlanningExecutor {
+    pub fn new(config: ExecutorConfig) -> Self {
+        Self::with_run_options(config, &RunOptions::default())
+    }
+
+    pub fn with_run_options(config: ExecutorConfig, options: &RunOptions) -> Self {
+        // Claude's own plan mode keeps follow-ups read-only too
+        let inner = match config {
+            ExecutorConfig::Claude => ExecutorConfig::ClaudePlan.create_executor_with(options),
+            _ => config.create_executor_with(options),
+        };
+        Self { config, inner }
+    }
+}
+
+/// Follow-ups on a plan, and forks of one, refine it rather than start implementing
+fn plan_followup_prompt(prompt: &str) -> String {
+    format!(
+        "{}\n\nYou are still in planning mode: do not modify any files. Reply with the updated implementation plan in Markdown as your final message.",
+        prompt
+    )
+}
+
+fn plan_fork(fork: &ForkInfo) -> ForkInfo {
+    ForkInfo {
+        prompt: plan_followup_prompt(&fork.prompt),
+        ..fork.clone()
+    }
+}
+
+#[async_trait]
+impl Executor for PlanningExecutor {
+    async fn spawn(
+        &self,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        worktree_path: &str,
+    ) -> Result<CommandProcess, ExecutorError> {
+        self.inner.spawn_plan(pool, task_id, worktree_path).await
+    }
+
+    async fn spawn_fork(
+        &self,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        fork: &ForkInfo,
+        worktree_path: &str,
+    ) -> Result<CommandProcess, ExecutorError> {
+        self.inner
+            .spawn_fork(pool, task_id, &plan_fork(fork), worktree_path)
+            .await
+    }
+
+    async fn spawn_followup(
+        &self,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        session_id: &str,
+        prompt: &str,
+        worktree_path: &str,
+    ) -> Result<CommandProcess, ExecutorError> {
+        self.inner
+            .spawn_followup(
+                pool,
+                task_id,
+                session_id,
+                &plan_followup_prompt(prompt),
+                worktree_path,
+            )
+            .await
+    }
+
+    fn normalize_logs(
+        &self,
+        logs: &str,
+        worktree_path: &str,
+    ) -> Result<NormalizedConversation, String> {
+        let mut conversation = self.inner.normalize_logs(logs, worktree_path)?;
+        capture_plan(&mut conversation.entries);
+        Ok(conversation)
+    }
+
+    fn extract_token_usage(&self, logs: &str) -> Option<TokenUsage> {
+        self.inner.extract_token_usage(logs)
+    }
+
+    fn incremental_normalizer(
+        &self,
+        worktree_path: &str,
+    ) -> Option<Box<dyn IncrementalNormalizer>> {
+        Some(Box::new(PlanCaptureNormalizer {
+            inner: incremental_normalizer_for(self.config.create_executor(), worktree_path),
+            entry_count: 0,
+            last_assistant: None,
+            has_plan: false,
+        }))
+    }
+
+    async fn execute_streaming(
+        &self,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        attempt_id: Uuid,
+        execution_process_id: Uuid,
+        worktree_path: &str,
+    ) -> Result<CommandProcess, ExecutorError> {
+        self.inner
+            .execute_plan_streaming(
+                pool,
+                task_id,
+                attempt_id,
+                execution_process_id,
+                worktree_path,
+            )
+            .await
+    }
+
+    async fn execute_followup_streaming(
+        &self,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        attempt_id: Uuid,
+        execution_process_id: Uuid,
+        session_id: &str,
+        prompt: &str,
+        worktree_path: &str,
+    ) -> Result<CommandProcess, ExecutorError> {
+        self.inner
+            .execute_followup_streaming(
+                pool,
+                task_id,
+                attempt_id,
+                execution_process_id,
+                session_id,
+                &plan_followup_prompt(prompt),
+                worktree_path,
+            )
+            .await
+    }
+
+    async fn execute_fork_streaming(
+        &self,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        attempt_id: Uuid,
+        execution_process_id: Uuid,
+        fork: &ForkInfo,
+        worktree_path: &str,
+    ) -> Result<CommandProcess, ExecutorError> {
+        self.inner
+            .execute_fork_streaming(
+                pool,
+                task_id,
+                attempt_id,
+                execution_process_id,
+                &plan_fork(fork),
+                worktree_path,
+            )
+            .await
+    }
+}
+
+/// The latest plan produced in an attempt, by a planning phase or the legacy Claude plan
+/// executor
+pub async fn find_latest_plan(
+    pool: &sqlx::SqlitePool,
+    attempt_id: Uuid,
+) -> Result<Option<String>, sqlx::Error> {
+    let execution_processes = ExecutionProcess::find_by_task_attempt_id(pool, attempt_id).await?;
+
+    // Look for planning processes (most recent first)
+    for plan_process in execution_processes.iter().rev().filter(|p| {
+        p.process_type == ExecutionProcessType::PlanningAgent
+            || p.executor_type
+                .as_deref()
+                .and_then(|executor_type| executor_type.parse::<ExecutorConfig>().ok())
+                .is_some_and(|config| config.capabilities().plan_mode == PlanMode::Always)
+    }) {
+        let Some(stdout) = plan_process.stdout.as_deref() else {
+            continue;
+        };
+        let Some(executor_config) = plan_process
+            .executor_type
+            .as_deref()
+            .and_then(|executor_type| executor_type.parse::<ExecutorConfig>().ok())
+        else {
+            continue;
+        };
+        if stdout.trim().is_empty() {
+            continue;
+        }
+
+        // Use working directory for normalization
+        let working_dir_path = match std::fs::canonicalize(&plan_process.working_directory) {
+            Ok(canonical_path) => canonical_path.to_string_lossy().to_string(),
+            Err(_) => plan_process.working_directory.clone(),
+        };
+
+        if let Some(plan_content) = PlanningExecutor::new(executor_config)
+            .normalize_logs(stdout, &working_dir_path)
+            .ok()
+            .and_then(|conversation| extract_plan(&conversation.entries))
+        {
+            return Ok(Some(plan_content));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Present the plan of a finished planning run as a `PlanPresentation` entry
+///
+/// Agents without a native plan tool answer in plain text, so their last assistant message
+/// becomes the plan entry.
+pub fn capture_plan(entries: &mut [NormalizedEntry]) {
+    if entries.iter().any(is_plan_entry) {
+        return;
+    }
+    let Some(plan) = extract_plan(entries) else {
+        return;
+    };
+    if let Some(entry) = entries
+        .iter_mut()
+        .rev()
+        .find(|entry| matches!(entry.entry_type, NormalizedEntryType::AssistantMessage))
+    {
+        *entry = plan_entry(plan);
+    }
+}
+
+fn is_plan_entry(entry: &NormalizedEntry) -> bool {
+    matches!(
+        entry.entry_type,
+        NormalizedEntryType::ToolUse {
+            action_type: ActionType::PlanPresentation { .. },
+            ..
+        }
+    )
+}
+
+/// Streams the wrapped normalizer's patches and, once stdout closes, replaces the last
+/// assistant message with the plan entry just like [`capture_plan`]
+struct PlanCaptureNormalizer {
+    inner: Box<dyn IncrementalNormalizer>,
+    entry_count: usize,
+    last_assistant: Option<(usize, String)>,
+    has_plan: bool,
+}
+
+impl PlanCaptureNormalizer {
+    fn observe(&mut self, patches: &[Value]) {
+        for patch in patches {
+            let path = patch
+                .get("path")
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            let value = patch.get("value").cloned().unwrap_or(Value::Null);
+
+            if path == "/entries" {
+                let entries: Vec<NormalizedEntry> =
+                    serde_json::from_value(value).unwrap_or_default();
+                self.entry_count = 0;
+                self.last_assistant = None;
+                for entry in &entries {
+                    self.track(self.entry_count, entry);
+                }
+                continue;
+            }
+
+            let Some(index) = path.strip_prefix("/entries/") else {
+                continue;
+            };
+            let index = match index {
+                "-" => self.entry_count,
+                index => match index.parse() {
+                    Ok(index) => index,
+                    Err(_) => continue,
+                },
+            };
+            if let Ok(entry) = serde_json::from_value::<NormalizedEntry>(value) {
+                self.track(index, &entry);
+            }
+        }
+    }
+
+    fn track(&mut self, index: usize, entry: &NormalizedEntry) {
+        self.entry_count = self.entry_count.max(index + 1);
+        if is_plan_entry(entry) {
+            self.has_plan = true;
+        } else if matches!(entry.entry_type, NormalizedEntryType::AssistantMessage) {
+            if !entry.content.trim().is_empty() {
+                self.last_assistant = Some((index, entry.content.trim().to_string()));
+            }
+        } else if self.last_assistant.as_ref().map(|(i, _)| *i) == Some(index) {
+            self.last_assistant = None;
+        }
+    }
+}
+
+impl IncrementalNormalizer for PlanCaptureNormalizer {
+    fn push_line(&mut self, line: &str) -> Vec<Value> {
+        let patches = self.inner.push_line(line);
+        self.observe(&patches);
+        patches
+    }
+
+    fn finish(&mut self) -> Vec<Value> {
+        let mut patches = self.inner.finish();
+        self.observe(&patches);
+        if !self.has_plan {
+            if let Some((index, plan)) = self.last_assistant.take() {
+                patches.push(replace_entry_patch(index, &plan_entry(plan)));
+            }
+        }
+        patches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::add_entry_patch;
+
+    fn message(entry_type: NormalizedEntryType, content: &str) -> NormalizedEntry {
+        NormalizedEntry {
+            timestamp: None,
+            entry_type,
+            content: content.to_string(),
+            metadata: None,
+        }
+    }
+
+    /// Emits one entry per line: `say:` lines are assistant messages, the rest system messages
+    struct Lines;
+
+    impl IncrementalNormalizer for Lines {
+        fn push_line(&mut self, line: &str) -> Vec<Value> {
+            let entry = match line.strip_prefix("say:") {
+                Some(text) => message(NormalizedEntryType::AssistantMessage, text),
+                None => message(NormalizedEntryType::SystemMessage, line),
+            };
+            vec![add_entry_patch(&entry)]
+        }
+    }
+
+    #[test]
+    fn test_capture_plan_converts_last_assistant_message() {
+        let mut entries = vec![
+            message(NormalizedEntryType::AssistantMessage, "Looking around"),
+            message(NormalizedEntryType::SystemMessage, "ran ls"),
+            message(
+                NormalizedEntryType::AssistantMessage,
+                "1. Do it\n2. Test it",
+            ),
+        ];
+        capture_plan(&mut entries);
+
+        assert!(matches!(
+            entries[0].entry_type,
+            NormalizedEntryType::AssistantMessage
+        ));
+        match &entries[2].entry_type {
+            NormalizedEntryType::ToolUse {
+                action_type: ActionType::PlanPresentation { plan },
+                ..
+            } => assert_eq!(plan, "1. Do it\n2. Test it"),
+            other => panic!("unexpected entry type {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_streamed_plan_is_replaced_on_finish() {
+        let mut normalizer = PlanCaptureNormalizer {
+            inner: Box::new(Lines),
+            entry_count: 0,
+            last_assistant: None,
+            has_plan: false,
+        };
+        normalizer.push_line("say:thinking");
+        normalizer.push_line("tool call");
+        normalizer.push_line("say:the plan");
+
+        let patches = normalizer.finish();
+        assert_eq!(patches.len(), 1);
+        assert_eq!(patches[0]["op"], "replace");
+        assert_eq!(patches[0]["path"], "/entries/2");
+        assert_eq!(
+            patches[0]["value"]["entry_type"]["action_type"]["plan"],
+            "the plan"
+        );
+    }
+}
@@ -3,7 +3,10 @@ use uuid::Uuid;
 
 use crate::{
     command_runner::{CommandProcess, CommandRunner},
-    executor::{Executor, ExecutorError},
+    executor::{
+        add_entry_patch, Executor, ExecutorError, IncrementalNormalizer, NormalizedEntry,
+        NormalizedEntryType,
+    },
     models::{project::Project, task::Task},
     utils::shell::get_shell_command,
 };
@@ -124,4 +127,118 @@ impl Executor for SetupScriptExecutor {
             summary: None,
         })
     }
+
+    fn incremental_normalizer(
+        &self,
+        _worktree_path: &str,
+    ) -> Option<Box<dyn IncrementalNormalizer>> {
+        Some(Box::new(ScriptLogNormalizer::new(format!(
+            "Executing setup script:\n{}",
+            self.script
+        ))))
+    }
+}
+
+/// Streams script output the same way script `normalize_logs` groups it: a header entry, then
+/// chunks of up to 10 lines, cut early at error-like lines
+pub struct ScriptLogNormalizer {
+    header: Option<String>,
+    current_chunk: String,
+}
+
+impl ScriptLogNormalizer {
+    pub fn new(header: String) -> Self {
+        Self {
+            header: Some(header),
+            current_chunk: String::new(),
+        }
+    }
+
+    fn take_header(&mut self) -> Vec<serde_json::Value> {
+        self.header
+            .take()
+            .map(|content| {
+                add_entry_patch(&NormalizedEntry {
+                    timestamp: None,
+                    entry_type: NormalizedEntryType::SystemMessage,
+                    content,
+                    metadata: None,
+                })
+            })
+            .into_iter()
+            .collect()
+    }
+
+    fn flush_chunk(&mut self, entry_type: NormalizedEntryType) -> Option<serde_json::Value> {
+        let content = std::mem::take(&mut self.current_chunk);
+        if content.trim().is_empty() {
+            return None;
+        }
+        Some(add_entry_patch(&NormalizedEntry {
+            timestamp: Some(chrono::Utc::now().to_rfc3339()),
+            entry_type,
+            content: content.trim().to_string(),
+            metadata: None,
+        }))
+    }
+}
+
+impl IncrementalNormalizer for ScriptLogNormalizer {
+    fn push_line(&mut self, line: &str) -> Vec<serde_json::Value> {
+        let mut patches = self.take_header();
+        self.current_chunk.push_str(line);
+        self.current_chunk.push('\n');
+
+        let lower = line.to_lowercase();
+        let is_error =
+            lower.contains("error") || lower.contains("failed") || lower.contains("exception");
+        if is_error || self.current_chunk.lines().count() >= 10 {
+            let entry_type = if is_error {
+                NormalizedEntryType::ErrorMessage
+            } else {
+                NormalizedEntryType::SystemMessage
+            };
+            patches.extend(self.flush_chunk(entry_type));
+        }
+        patches
+    }
+
+    fn finish(&mut self) -> Vec<serde_json::Value> {
+        let mut patches = self.take_header();
+        patches.extend(self.flush_chunk(NormalizedEntryType::SystemMessage));
+        patches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_script_normalizer_matches_normalize_logs() {
+        let executor = SetupScriptExecutor::new("npm install".to_string());
+        let logs = (0..12)
+            .map(|i| format!("line {}", i))
+            .chain(["npm ERR! failed".to_string(), "done".to_string()])
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let mut normalizer = executor.incremental_normalizer("/repo").unwrap();
+        let mut patches: Vec<serde_json::Value> = logs
+            .lines()
+            .flat_map(|line| normalizer.push_line(line))
+            .collect();
+        patches.extend(normalizer.finish());
+
+        let expected = executor.normalize_logs(&logs, "/repo").unwrap().entries;
+        assert_eq!(patches.len(), expected.len());
+        for (patch, entry) in patches.iter().zip(&expected) {
+            assert_eq!(patch["op"], "add");
+            assert_eq!(patch["value"]["content"], entry.content.as_str());
+            assert_eq!(
+                patch["value"]["entry_type"],
+                serde_json::to_value(&entry.entry_type).unwrap()
+            );
+        }
+    }
 }
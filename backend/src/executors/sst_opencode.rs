@@ -5,8 +5,9 @@ use uuid::Uuid;
 
 use crate::{
     command_runner::{CommandProcess, CommandRunner},
-    executor::{Executor, ExecutorError, NormalizedConversation, NormalizedEntry},
-    models::{execution_process::ExecutionProcess, executor_session::ExecutorSession, task::Task},
+    executor::{Executor, ExecutorError, NormalizedConversation, NormalizedEntry, RunOptions},
+    models::{execution_process::ExecutionProcess, executor_session::ExecutorSession},
+    services::LogStreamService,
     utils::shell::get_shell_command,
 };
 
@@ -107,6 +108,9 @@ pub async fn stream_opencode_stderr_to_db(
                 .await;
 
                 if let Some(Content { stdout, stderr }) = content {
+                    if let Some(stdout) = &stdout {
+                        LogStreamService::push_stdout(execution_process_id, stdout);
+                    }
                     tracing::debug!(
                         "Processed OpenCode content for attempt {}: stdout={:?} stderr={:?}",
                         attempt_id,
@@ -135,6 +139,8 @@ pub async fn stream_opencode_stderr_to_db(
             }
         }
     }
+
+    LogStreamService::finish(execution_process_id);
 }
 
 /// Format OpenCode clean content as normalized JSON entries for direct database storage
@@ -230,40 +236,55 @@ impl SstOpencodeExecutor {
             command: "npx -y opencode-ai@latest run --print-logs".to_string(),
         }
     }
+
+    /// Apply a run's model, extra arguments and pinned CLI version to the OpenCode CLI command
+    pub fn with_run_options(mut self, options: &RunOptions) -> Self {
+        self.command = options.apply(&self.command);
+        self
+    }
+
+    /// Stream stderr through OpenCode's filter into the database
+    async fn stream_filtered(
+        proc: &mut CommandProcess,
+        pool: &sqlx::SqlitePool,
+        attempt_id: Uuid,
+        execution_process_id: Uuid,
+        worktree_path: &str,
+    ) {
+        // Get stderr stream from CommandProcess for OpenCode filtering
+        let mut stream = proc
+            .stream()
+            .await
+            .expect("Failed to get streams from command process");
+        let stderr = stream
+            .stderr
+            .take()
+            .expect("Failed to get stderr from command stream");
+
+        // Start OpenCode stderr filtering task
+        let pool_clone = pool.clone();
+        let worktree_path_clone = worktree_path.to_string();
+        tokio::spawn(stream_opencode_stderr_to_db(
+            stderr,
+            pool_clone,
+            attempt_id,
+            execution_process_id,
+            worktree_path_clone,
+        ));
+    }
 }
 
 /// An executor that resumes an SST Opencode session
 
 #[async_trait]
 impl Executor for SstOpencodeExecutor {
-    async fn spawn(
+    async fn spawn_with_prompt(
         &self,
-        pool: &sqlx::SqlitePool,
+        _pool: &sqlx::SqlitePool,
         task_id: Uuid,
+        prompt: &str,
         worktree_path: &str,
     ) -> Result<CommandProcess, ExecutorError> {
-        // Get the task to fetch its description
-        let task = Task::find_by_id(pool, task_id)
-            .await?
-            .ok_or(ExecutorError::TaskNotFound)?;
-
-        let prompt = if let Some(task_description) = task.description {
-            format!(
-                r#"project_id: {}
-            
-Task title: {}
-Task description: {}"#,
-                task.project_id, task.title, task_description
-            )
-        } else {
-            format!(
-                r#"project_id: {}
-            
-Task title: {}"#,
-                task.project_id, task.title
-            )
-        };
-
         // Use shell command for cross-platform compatibility
         let (shell_cmd, shell_arg) = get_shell_command();
         let opencode_command = &self.command;
@@ -273,13 +294,13 @@ Task title: {}"#,
             .command(shell_cmd)
             .arg(shell_arg)
             .arg(opencode_command)
-            .stdin(&prompt)
+            .stdin(prompt)
             .working_dir(worktree_path)
             .env("NODE_NO_WARNINGS", "1");
 
         let proc = command.start().await.map_err(|e| {
             crate::executor::SpawnContext::from_command(&command, &self.executor_type)
-                .with_task(task_id, Some(task.title.clone()))
+                .with_task(task_id, None)
                 .with_context(format!("{} CLI execution for new task", self.executor_type))
                 .spawn_error(e)
         })?;
@@ -298,27 +319,59 @@ Task title: {}"#,
     ) -> Result<CommandProcess, ExecutorError> {
         let mut proc = self.spawn(pool, task_id, worktree_path).await?;
 
-        // Get stderr stream from CommandProcess for OpenCode filtering
-        let mut stream = proc
-            .stream()
-            .await
-            .expect("Failed to get streams from command process");
-        let stderr = stream
-            .stderr
-            .take()
-            .expect("Failed to get stderr from command stream");
+        Self::stream_filtered(
+            &mut proc,
+            pool,
+            attempt_id,
+            execution_process_id,
+            worktree_path,
+        )
+        .await;
+        Ok(proc)
+    }
 
-        // Start OpenCode stderr filtering task
-        let pool_clone = pool.clone();
-        let worktree_path_clone = worktree_path.to_string();
-        tokio::spawn(stream_opencode_stderr_to_db(
-            stderr,
-            pool_clone,
+    /// Execute a planning run with OpenCode filtering for stderr
+    async fn execute_plan_streaming(
+        &self,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        attempt_id: Uuid,
+        execution_process_id: Uuid,
+        worktree_path: &str,
+    ) -> Result<CommandProcess, ExecutorError> {
+        let mut proc = self.spawn_plan(pool, task_id, worktree_path).await?;
+        Self::stream_filtered(
+            &mut proc,
+            pool,
             attempt_id,
             execution_process_id,
-            worktree_path_clone,
-        ));
+            worktree_path,
+        )
+        .await;
+        Ok(proc)
+    }
 
+    /// Execute a prompt-driven run with OpenCode filtering for stderr
+    async fn execute_prompt_streaming(
+        &self,
+        pool: &sqlx::SqlitePool,
+        task_id: Uuid,
+        attempt_id: Uuid,
+        execution_process_id: Uuid,
+        prompt: &str,
+        worktree_path: &str,
+    ) -> Result<CommandProcess, ExecutorError> {
+        let mut proc = self
+            .spawn_with_prompt(pool, task_id, prompt, worktree_path)
+            .await?;
+        Self::stream_filtered(
+            &mut proc,
+            pool,
+            attempt_id,
+            execution_process_id,
+            worktree_path,
+        )
+        .await;
         Ok(proc)
     }
 
@@ -365,27 +418,14 @@ Task title: {}"#,
             .spawn_followup(pool, task_id, session_id, prompt, worktree_path)
             .await?;
 
-        // Get stderr stream from CommandProcess for OpenCode filtering
-        let mut stream = proc
-            .stream()
-            .await
-            .expect("Failed to get streams from command process");
-        let stderr = stream
-            .stderr
-            .take()
-            .expect("Failed to get stderr from command stream");
-
-        // Start OpenCode stderr filtering task
-        let pool_clone = pool.clone();
-        let worktree_path_clone = worktree_path.to_string();
-        tokio::spawn(stream_opencode_stderr_to_db(
-            stderr,
-            pool_clone,
+        Self::stream_filtered(
+            &mut proc,
+            pool,
             attempt_id,
             execution_process_id,
-            worktree_path_clone,
-        ));
-
+            worktree_path,
+        )
+        .await;
         Ok(proc)
     }
 
@@ -14,6 +14,9 @@ pub mod routes;
 pub mod services;
 pub mod utils;
 
+#[cfg(test)]
+mod test_utils;
+
 #[derive(RustEmbed)]
 #[folder = "../frontend/dist"]
 pub struct Assets;
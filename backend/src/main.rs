@@ -28,6 +28,9 @@ mod routes;
 mod services;
 mod utils;
 
+#[cfg(test)]
+mod test_utils;
+
 use app_state::AppState;
 use execution_monitor::execution_monitor;
 <<<<<<< HEAD
@@ -211,6 +214,7 @@ fn main() -> anyhow::Result<()> {
                 .merge(filesystem::filesystem_router())
                 .merge(config::config_router())
                 .merge(auth::auth_router())
+                .merge(routes::usage::usage_router())
                 .route("/sounds/:filename", get(serve_sound_file))
                 .merge(
                     Router::new()
@@ -241,21 +245,38 @@ fn main() -> anyhow::Result<()> {
             let project_routes = Router::new()
                 .merge(projects::projects_base_router())
                 .merge(projects::projects_with_id_router()
-                    .layer(from_fn_with_state(app_state.clone(), load_project_middleware)));
+                    .layer(from_fn_with_state(app_state.clone(), load_project_middleware)))
+                .merge(projects::project_cli_versions_router())
+                .merge(routes::pipelines::pipelines_project_router()
+                    .layer(from_fn_with_state(app_state.clone(), load_project_middleware)))
+                .merge(routes::pipelines::pipelines_with_id_router()
+                    .layer(from_fn_with_state(app_state.clone(), middleware::load_pipeline_middleware)))
+                .merge(routes::permissions::permissions_project_router()
+                    .layer(from_fn_with_state(app_state.clone(), load_project_middleware)))
+                .merge(routes::permissions::permission_requests_with_id_router()
+                    .layer(from_fn_with_state(app_state.clone(), middleware::load_permission_request_middleware)));
 
             // Task routes with appropriate middleware
             let task_routes = Router::new()
                 .merge(tasks::tasks_project_router()
                     .layer(from_fn_with_state(app_state.clone(), load_project_middleware)))
                 .merge(tasks::tasks_with_id_router()
-                    .layer(from_fn_with_state(app_state.clone(), load_task_middleware)));
+                    .layer(from_fn_with_state(app_state.clone(), load_task_middleware)))
+                .merge(routes::task_attachments::task_attachments_list_router()
+                    .layer(from_fn_with_state(app_state.clone(), load_task_middleware)))
+                .merge(routes::task_attachments::task_attachments_with_id_router()
+                    .layer(from_fn_with_state(app_state.clone(), middleware::load_task_attachment_middleware)));
 
             // Task attempt routes with appropriate middleware
             let task_attempt_routes = Router::new()
                 .merge(task_attempts::task_attempts_list_router(app_state.clone())
                     .layer(from_fn_with_state(app_state.clone(), load_task_middleware)))
                 .merge(task_attempts::task_attempts_with_id_router(app_state.clone())
-                    .layer(from_fn_with_state(app_state.clone(), load_task_attempt_middleware)));
+                    .layer(from_fn_with_state(app_state.clone(), load_task_attempt_middleware)))
+                .merge(routes::task_attempt_groups::task_attempt_groups_list_router()
+                    .layer(from_fn_with_state(app_state.clone(), load_task_middleware)))
+                .merge(routes::task_attempt_groups::task_attempt_groups_with_id_router()
+                    .layer(from_fn_with_state(app_state.clone(), middleware::load_task_attempt_group_middleware)));
 
             // Conditionally add GitHub routes for cloud mode
             let mut api_routes = Router::new()
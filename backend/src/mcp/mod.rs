@@ -1 +1,2 @@
+pub mod permission_server;
 pub mod task_server;
@@ -0,0 +1,343 @@
+use std::{
+    future::Future,
+    io::Write,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use rmcp::{
+    handler::server::tool::{Parameters, ToolRouter},
+    model::{
+        CallToolResult, Content, Implementation, ProtocolVersion, ServerCapabilities, ServerInfo,
+    },
+    schemars, tool, tool_handler, tool_router, Error as RmcpError, ServerHandler,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::models::{
+    execution_process::{ExecutionProcess, ExecutionProcessType},
+    permission::{
+        path_outside_worktree, PermissionDecision, PermissionDecisionSource, PermissionPolicy,
+        PermissionRequest,
+    },
+    persona::ProjectPersona,
+};
+
+pub const PERMISSION_SERVER_NAME: &str = "vibe_kanban_permissions";
+/// Name Claude uses to call the approval tool, passed to `--permission-prompt-tool`
+pub const PERMISSION_PROMPT_TOOL: &str = "mcp__vibe_kanban_permissions__approval_prompt";
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ApprovalPromptRequest {
+    #[schemars(description = "The name of the tool requesting permission")]
+    pub tool_name: String,
+    #[schemars(description = "The input the tool would be called with")]
+    pub input: Value,
+    #[schemars(description = "The ID of the tool use, if available")]
+    pub tool_use_id: Option<String>,
+}
+
+/// Answers an agent's permission prompts for one project worktree
+///
+/// Requests are checked against the project's rules; anything unmatched, including every
+/// request in a project without rules, waits for a decision made through the API until the
+/// policy's timeout applies its default decision. Paths outside the worktree are always denied.
+#[derive(Debug, Clone)]
+pub struct PermissionServer {
+    pub pool: SqlitePool,
+    project_id: Uuid,
+    worktree_path: String,
+    tool_router: ToolRouter<PermissionServer>,
+}
+
+impl PermissionServer {
+    pub fn new(pool: SqlitePool, project_id: Uuid, worktree_path: String) -> Self {
+        Self {
+            pool,
+            project_id,
+            worktree_path,
+            tool_router: Self::tool_router(),
+        }
+    }
+
+    /// The running agent process working in this server's worktree
+    async fn find_agent_process(&self) -> Option<ExecutionProcess> {
+        let running = ExecutionProcess::find_running(&self.pool).await.ok()?;
+        running.into_iter().rev().find(|process| {
+            process.working_directory == self.worktree_path
+                && matches!(
+                    process.process_type,
+                    ExecutionProcessType::CodingAgent
+                        | ExecutionProcessType::PlanningAgent
+                        | ExecutionProcessType::ReviewAgent
+                )
+        })
+    }
+
+    /// Record a tool call and wait until it is decided
+    pub async fn decide(
+        &self,
+        tool_name: &str,
+        input: &Value,
+    ) -> Result<PermissionRequest, String> {
+        let policy = PermissionPolicy::find_by_project_id(&self.pool, self.project_id)
+            .await
+            .map_err(|e| format!("Failed to load permission policy: {}", e))?;
+        let process = self.find_agent_process().await;
+        let request = PermissionRequest::create(
+            &self.pool,
+            self.project_id,
+            process.as_ref().map(|p| p.task_attempt_id),
+            process.as_ref().map(|p| p.id),
+            tool_name,
+            &input.to_string(),
+            policy.default_decision,
+            policy.timeout_seconds,
+        )
+        .await
+        .map_err(|e| format!("Failed to record permission request: {}", e))?;
+
+        // Calls reaching outside the worktree, and tools the acting persona is restricted
+        // from, are denied outright
+        let persona = match process.as_ref().and_then(|p| p.persona_id) {
+            Some(persona_id) => ProjectPersona::find_brief(&self.pool, persona_id)
+                .await
+                .map_err(|e| format!("Failed to load persona: {}", e))?,
+            None => None,
+        };
+        let denial = match path_outside_worktree(input, &self.worktree_path) {
+            Some(path) => Some(format!("{} is outside the worktree", path)),
+            None => persona
+                .filter(|persona| persona.restricts(tool_name))
+                .map(|persona| format!("{} is not allowed to use {}", persona.name, tool_name)),
+        };
+        if let Some(message) = denial {
+            if let Some(decided) = PermissionRequest::decide(
+                &self.pool,
+                request.id,
+                PermissionDecision::Deny,
+                PermissionDecisionSource::Rule,
+                None,
+                Some(&message),
+            )
+            .await
+            .map_err(|e| format!("Failed to record permission decision: {}", e))?
+            {
+                return Ok(decided);
+            }
+        }
+
+        if let Some((position, decision)) = policy.evaluate(tool_name, input, &self.worktree_path) {
+            if let Some(decided) = PermissionRequest::decide(
+                &self.pool,
+                request.id,
+                decision,
+                PermissionDecisionSource::Rule,
+                Some(position as i64),
+                None,
+            )
+            .await
+            .map_err(|e| format!("Failed to record permission decision: {}", e))?
+            {
+                return Ok(decided);
+            }
+        }
+
+        tracing::info!(
+            "Permission request {} for {} is waiting for approval",
+            request.id,
+            tool_name
+        );
+        loop {
+            let current = PermissionRequest::find_by_id(&self.pool, request.id)
+                .await
+                .map_err(|e| format!("Failed to read permission request: {}", e))?
+                .ok_or_else(|| "Permission request was deleted".to_string())?;
+            if current.decision.is_some() {
+                return Ok(current);
+            }
+            if current.is_overdue() {
+                if let Some(decided) = PermissionRequest::decide(
+                    &self.pool,
+                    current.id,
+                    current.default_decision,
+                    PermissionDecisionSource::Timeout,
+                    None,
+                    None,
+                )
+                .await
+                .map_err(|e| format!("Failed to record permission decision: {}", e))?
+                {
+                    return Ok(decided);
+                }
+                continue;
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+}
+
+#[tool_router]
+impl PermissionServer {
+    #[tool(
+        description = "Decide whether a tool call may run. Returns a JSON object with `behavior` set to `allow` or `deny`."
+    )]
+    async fn approval_prompt(
+        &self,
+        Parameters(ApprovalPromptRequest {
+            tool_name,
+            input,
+            tool_use_id,
+        }): Parameters<ApprovalPromptRequest>,
+    ) -> Result<CallToolResult, RmcpError> {
+        tracing::debug!(
+            "Permission prompt for {} (tool use {})",
+            tool_name,
+            tool_use_id.as_deref().unwrap_or("unknown")
+        );
+        let response = match self.decide(&tool_name, &input).await {
+            Ok(request) if request.decision == Some(PermissionDecision::Allow) => json!({
+                "behavior": "allow",
+                "updatedInput": input,
+            }),
+            Ok(request) => json!({
+                "behavior": "deny",
+                "message": request
+                    .message
+                    .unwrap_or_else(|| denial_message(request.decided_by)),
+            }),
+            Err(e) => {
+                tracing::error!("{}", e);
+                json!({
+                    "behavior": "deny",
+                    "message": format!("Permission check failed: {}", e),
+                })
+            }
+        };
+        Ok(CallToolResult::success(vec![Content::text(
+            response.to_string(),
+        )]))
+    }
+}
+
+fn denial_message(decided_by: Option<PermissionDecisionSource>) -> String {
+    match decided_by {
+        Some(PermissionDecisionSource::Rule) => "Denied by a project permission rule",
+        Some(PermissionDecisionSource::Timeout) => "Nobody approved this action in time",
+        _ => "Denied by the user",
+    }
+    .to_string()
+}
+
+#[tool_handler]
+impl ServerHandler for PermissionServer {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            protocol_version: ProtocolVersion::V_2025_03_26,
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            server_info: Implementation {
+                name: PERMISSION_SERVER_NAME.to_string(),
+                version: "1.0.0".to_string(),
+            },
+            instructions: Some(
+                "Permission gateway for coding agents. Call 'approval_prompt' before running a tool that needs permission.".to_string(),
+            ),
+        }
+    }
+}
+
+/// The MCP server binary shipped next to the running backend, if there is one
+pub fn server_binary() -> Option<PathBuf> {
+    let dir = std::env::current_exe().ok()?.parent()?.to_path_buf();
+    ["vibe-kanban-mcp", "mcp_task_server"]
+        .iter()
+        .map(|name| dir.join(format!("{}{}", name, std::env::consts::EXE_SUFFIX)))
+        .find(|path| path.exists())
+}
+
+/// Write an MCP config that starts the permission server for a worktree, for `--mcp-config`
+///
+/// The file is deleted when the returned path is dropped.
+pub fn write_mcp_config(
+    binary: &Path,
+    project_id: Uuid,
+    worktree_path: &str,
+) -> std::io::Result<tempfile::TempPath> {
+    let config = json!({
+        "mcpServers": {
+            PERMISSION_SERVER_NAME: {
+                "command": binary.to_string_lossy(),
+                "args": [
+                    "--permissions",
+                    "--project-id",
+                    project_id.to_string(),
+                    "--worktree",
+                    worktree_path,
+                ],
+            }
+        }
+    });
+    let worktree_name = Path::new(worktree_path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| project_id.to_string());
+    let mut file = tempfile::Builder::new()
+        .prefix(&format!("vibe-kanban-permissions-{}-", worktree_name))
+        .suffix(".json")
+        .tempfile()?;
+    file.write_all(config.to_string().as_bytes())?;
+    Ok(file.into_temp_path())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::permission::{PermissionRule, UpdatePermissionPolicy},
+        test_utils::{insert_project, test_pool},
+    };
+
+    #[tokio::test]
+    async fn unmatched_requests_wait_for_approval_until_the_timeout() {
+        let pool = test_pool().await;
+        let project_id = insert_project(&pool, "/tmp/repo").await;
+        PermissionPolicy::update(
+            &pool,
+            project_id,
+            &UpdatePermissionPolicy {
+                timeout_seconds: 1,
+                default_decision: PermissionDecision::Deny,
+                rules: vec![PermissionRule {
+                    decision: PermissionDecision::Allow,
+                    tool_name: Some("Edit".to_string()),
+                    path_glob: None,
+                    command_pattern: None,
+                }],
+            },
+        )
+        .await
+        .unwrap();
+        let server = PermissionServer::new(pool, project_id, "/tmp/worktree".to_string());
+
+        let edit = json!({ "file_path": "/tmp/worktree/src/main.rs" });
+        let request = server.decide("Edit", &edit).await.unwrap();
+        assert_eq!(request.decision, Some(PermissionDecision::Allow));
+        assert_eq!(request.decided_by, Some(PermissionDecisionSource::Rule));
+
+        // Matching rules don't reach outside the worktree
+        let escape = json!({ "file_path": "/tmp/worktree/../secrets.env" });
+        let request = server.decide("Edit", &escape).await.unwrap();
+        assert_eq!(request.decision, Some(PermissionDecision::Deny));
+        assert_eq!(request.decided_by, Some(PermissionDecisionSource::Rule));
+
+        let bash = json!({ "command": "cargo test" });
+        let request = server.decide("Bash", &bash).await.unwrap();
+        assert_eq!(request.decision, Some(PermissionDecision::Deny));
+        assert_eq!(request.decided_by, Some(PermissionDecisionSource::Timeout));
+    }
+}
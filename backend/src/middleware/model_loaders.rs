@@ -9,8 +9,9 @@ use uuid::Uuid;
 use crate::{
     app_state::AppState,
     models::{
-        execution_process::ExecutionProcess, project::Project, task::Task,
-        task_attempt::TaskAttempt, task_template::TaskTemplate,
+        execution_process::ExecutionProcess, permission::PermissionRequest, pipeline::Pipeline,
+        project::Project, task::Task, task_attachment::TaskAttachment, task_attempt::TaskAttempt,
+        task_attempt_group::TaskAttemptGroup, task_template::TaskTemplate,
     },
 };
 
@@ -129,6 +130,129 @@ pub async fn load_task_attempt_middleware(
     Ok(next.run(request).await)
 }
 
+/// Middleware that loads and injects Project, Task, and TaskAttemptGroup based on project_id, task_id, and group_id path parameters
+pub async fn load_task_attempt_group_middleware(
+    State(app_state): State<AppState>,
+    Path((project_id, task_id, group_id)): Path<(Uuid, Uuid, Uuid)>,
+    request: axum::extract::Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    // Load the task and validate it belongs to the project
+    let task = match Task::find_by_id_and_project_id(&app_state.db_pool, task_id, project_id).await
+    {
+        Ok(Some(task)) => task,
+        Ok(None) => {
+            tracing::warn!("Task {} not found in project {}", task_id, project_id);
+            return Err(StatusCode::NOT_FOUND);
+        }
+        Err(e) => {
+            tracing::error!(
+                "Failed to fetch task {} in project {}: {}",
+                task_id,
+                project_id,
+                e
+            );
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    let project = match Project::find_by_id(&app_state.db_pool, project_id).await {
+        Ok(Some(project)) => project,
+        Ok(None) => {
+            tracing::warn!("Project {} not found", project_id);
+            return Err(StatusCode::NOT_FOUND);
+        }
+        Err(e) => {
+            tracing::error!("Failed to fetch project {}: {}", project_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    // Load the group and validate it belongs to the task
+    let group = match TaskAttemptGroup::find_by_id(&app_state.db_pool, group_id).await {
+        Ok(Some(group)) if group.task_id == task_id => group,
+        Ok(_) => {
+            tracing::warn!("Attempt group {} not found in task {}", group_id, task_id);
+            return Err(StatusCode::NOT_FOUND);
+        }
+        Err(e) => {
+            tracing::error!("Failed to fetch attempt group {}: {}", group_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    // Insert all models as extensions
+    let mut request = request;
+    request.extensions_mut().insert(project);
+    request.extensions_mut().insert(task);
+    request.extensions_mut().insert(group);
+
+    // Continue with the next middleware/handler
+    Ok(next.run(request).await)
+}
+
+/// Middleware that loads and injects Project, Task, and TaskAttachment based on the
+/// project_id, task_id, and attachment_id path parameters
+pub async fn load_task_attachment_middleware(
+    State(app_state): State<AppState>,
+    Path((project_id, task_id, attachment_id)): Path<(Uuid, Uuid, Uuid)>,
+    request: axum::extract::Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    // Load the task and validate it belongs to the project
+    let task = match Task::find_by_id_and_project_id(&app_state.db_pool, task_id, project_id).await
+    {
+        Ok(Some(task)) => task,
+        Ok(None) => {
+            tracing::warn!("Task {} not found in project {}", task_id, project_id);
+            return Err(StatusCode::NOT_FOUND);
+        }
+        Err(e) => {
+            tracing::error!(
+                "Failed to fetch task {} in project {}: {}",
+                task_id,
+                project_id,
+                e
+            );
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    let project = match Project::find_by_id(&app_state.db_pool, project_id).await {
+        Ok(Some(project)) => project,
+        Ok(None) => {
+            tracing::warn!("Project {} not found", project_id);
+            return Err(StatusCode::NOT_FOUND);
+        }
+        Err(e) => {
+            tracing::error!("Failed to fetch project {}: {}", project_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    // Load the attachment and validate it belongs to the task
+    let attachment = match TaskAttachment::find_by_id(&app_state.db_pool, attachment_id).await {
+        Ok(Some(attachment)) if attachment.task_id == task_id => attachment,
+        Ok(_) => {
+            tracing::warn!("Attachment {} not found in task {}", attachment_id, task_id);
+            return Err(StatusCode::NOT_FOUND);
+        }
+        Err(e) => {
+            tracing::error!("Failed to fetch attachment {}: {}", attachment_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    // Insert all models as extensions
+    let mut request = request;
+    request.extensions_mut().insert(project);
+    request.extensions_mut().insert(task);
+    request.extensions_mut().insert(attachment);
+
+    // Continue with the next middleware/handler
+    Ok(next.run(request).await)
+}
+
 /// Simple middleware that loads and injects ExecutionProcess based on the process_id path parameter
 /// without any additional validation
 pub async fn load_execution_process_simple_middleware(
@@ -213,6 +337,99 @@ pub async fn load_execution_process_with_context_middleware(
     Ok(next.run(request).await)
 }
 
+/// Middleware that loads and injects both Project and Pipeline based on project_id and pipeline_id path parameters
+pub async fn load_pipeline_middleware(
+    State(app_state): State<AppState>,
+    Path((project_id, pipeline_id)): Path<(Uuid, Uuid)>,
+    request: axum::extract::Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let project = match Project::find_by_id(&app_state.db_pool, project_id).await {
+        Ok(Some(project)) => project,
+        Ok(None) => {
+            tracing::warn!("Project {} not found", project_id);
+            return Err(StatusCode::NOT_FOUND);
+        }
+        Err(e) => {
+            tracing::error!("Failed to fetch project {}: {}", project_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    // Load the pipeline and validate it belongs to the project
+    let pipeline = match Pipeline::find_by_id(&app_state.db_pool, pipeline_id).await {
+        Ok(Some(pipeline)) if pipeline.project_id == project_id => pipeline,
+        Ok(_) => {
+            tracing::warn!(
+                "Pipeline {} not found in project {}",
+                pipeline_id,
+                project_id
+            );
+            return Err(StatusCode::NOT_FOUND);
+        }
+        Err(e) => {
+            tracing::error!("Failed to fetch pipeline {}: {}", pipeline_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    // Insert both models as extensions
+    let mut request = request;
+    request.extensions_mut().insert(project);
+    request.extensions_mut().insert(pipeline);
+
+    // Continue with the next middleware/handler
+    Ok(next.run(request).await)
+}
+
+/// Middleware that loads and injects both Project and PermissionRequest based on project_id and request_id path parameters
+pub async fn load_permission_request_middleware(
+    State(app_state): State<AppState>,
+    Path((project_id, request_id)): Path<(Uuid, Uuid)>,
+    request: axum::extract::Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let project = match Project::find_by_id(&app_state.db_pool, project_id).await {
+        Ok(Some(project)) => project,
+        Ok(None) => {
+            tracing::warn!("Project {} not found", project_id);
+            return Err(StatusCode::NOT_FOUND);
+        }
+        Err(e) => {
+            tracing::error!("Failed to fetch project {}: {}", project_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    // Load the permission request and validate it belongs to the project
+    let permission_request =
+        match PermissionRequest::find_by_id(&app_state.db_pool, request_id).await {
+            Ok(Some(permission_request)) if permission_request.project_id == project_id => {
+                permission_request
+            }
+            Ok(_) => {
+                tracing::warn!(
+                    "Permission request {} not found in project {}",
+                    request_id,
+                    project_id
+                );
+                return Err(StatusCode::NOT_FOUND);
+            }
+            Err(e) => {
+                tracing::error!("Failed to fetch permission request {}: {}", request_id, e);
+                return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            }
+        };
+
+    // Insert both models as extensions
+    let mut request = request;
+    request.extensions_mut().insert(project);
+    request.extensions_mut().insert(permission_request);
+
+    // Continue with the next middleware/handler
+    Ok(next.run(request).await)
+}
+
 /// Middleware that loads and injects TaskTemplate based on the template_id path parameter
 pub async fn load_task_template_middleware(
     State(app_state): State<AppState>,
@@ -1,9 +1,11 @@
-use std::{path::PathBuf, str::FromStr};
+use std::{collections::HashMap, path::PathBuf, str::FromStr, time::Duration};
 
+use backon::{BackoffBuilder, ExponentialBuilder};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 
-use crate::executor::ExecutorConfig;
+use crate::{command_runner, executor::ExecutorConfig, models::pipeline};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
 #[ts(export)]
@@ -70,6 +72,140 @@ pub struct Config {
     pub analytics_enabled: Option<bool>,
     pub environment: EnvironmentInfo,
     pub workspace_dir: Option<String>,
+    pub review_executor: Option<ExecutorConfig>, // Reviews finished coding runs when set
+    pub executor_limits: HashMap<String, ExecutionLimits>, // Keyed by executor name, e.g. "claude"
+    pub retry_policies: HashMap<String, RetryPolicy>,
+    pub executor_resource_limits: HashMap<String, ResourceLimits>, // Keyed like executor_limits
+    pub stop_grace_period_seconds: u64, // Wait between SIGINT, SIGTERM and SIGKILL when stopping
+    pub pipeline_check_timeout_seconds: u64, // Pipeline check commands running longer count as failed
+}
+
+/// Runtime and idle limits for execution processes, in seconds
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ExecutionLimits {
+    pub max_runtime_seconds: Option<i64>,
+    pub idle_timeout_seconds: Option<i64>,
+}
+
+impl ExecutionLimits {
+    /// Combine two sets of limits, keeping the stricter value of each
+    pub fn strictest(self, other: ExecutionLimits) -> ExecutionLimits {
+        let min = |a: Option<i64>, b: Option<i64>| match (a, b) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        ExecutionLimits {
+            max_runtime_seconds: min(self.max_runtime_seconds, other.max_runtime_seconds),
+            idle_timeout_seconds: min(self.idle_timeout_seconds, other.idle_timeout_seconds),
+        }
+    }
+
+    /// Reason for stopping a process that has run for `runtime` and been silent for `idle`,
+    /// or `None` while it is within its limits
+    pub fn exceeded(&self, runtime: Duration, idle: Duration) -> Option<String> {
+        let exceeds = |limit: Option<i64>, elapsed: Duration| {
+            limit.is_some_and(|seconds| seconds > 0 && elapsed.as_secs() >= seconds as u64)
+        };
+
+        if exceeds(self.max_runtime_seconds, runtime) {
+            Some(format!(
+                "Exceeded the maximum runtime of {} seconds",
+                self.max_runtime_seconds.unwrap_or_default()
+            ))
+        } else if exceeds(self.idle_timeout_seconds, idle) {
+            Some(format!(
+                "No output for {} seconds",
+                self.idle_timeout_seconds.unwrap_or_default()
+            ))
+        } else {
+            None
+        }
+    }
+}
+
+/// Memory, CPU and process-count limits for an execution's process group
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ResourceLimits {
+    pub memory_limit_mb: Option<i64>,
+    pub cpu_limit_percent: Option<i64>, // 100 is one full core
+    pub max_processes: Option<i64>,
+}
+
+impl ResourceLimits {
+    /// Combine two sets of limits, keeping the stricter value of each
+    pub fn strictest(self, other: ResourceLimits) -> ResourceLimits {
+        let min = |a: Option<i64>, b: Option<i64>| match (a, b) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        ResourceLimits {
+            memory_limit_mb: min(self.memory_limit_mb, other.memory_limit_mb),
+            cpu_limit_percent: min(self.cpu_limit_percent, other.cpu_limit_percent),
+            max_processes: min(self.max_processes, other.max_processes),
+        }
+    }
+
+    /// Whether no limit is set; zero and negative values count as unset
+    pub fn is_unlimited(&self) -> bool {
+        [
+            self.memory_limit_mb,
+            self.cpu_limit_percent,
+            self.max_processes,
+        ]
+        .iter()
+        .all(|limit| !limit.is_some_and(|value| value > 0))
+    }
+
+    /// Reject limits no process could run within
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, value) in [
+            ("Memory limit", self.memory_limit_mb),
+            ("CPU limit", self.cpu_limit_percent),
+            ("Process limit", self.max_processes),
+        ] {
+            if value.is_some_and(|value| value <= 0) {
+                return Err(format!("{} must be greater than zero", name));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// When and how often a failed agent run is retried automatically
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct RetryPolicy {
+    pub transient_patterns: Vec<String>, // Regexes matched against the process stdout and stderr
+    pub max_attempts: u32,
+    pub min_delay_seconds: u32,
+    pub max_delay_seconds: u32,
+}
+
+impl RetryPolicy {
+    /// Whether `output` of a failed run matches one of the transient failure patterns
+    pub fn is_transient(&self, output: &str) -> bool {
+        self.transient_patterns
+            .iter()
+            .any(|pattern| match Regex::new(pattern) {
+                Ok(regex) => regex.is_match(output),
+                Err(e) => {
+                    tracing::warn!("Invalid transient failure pattern {:?}: {}", pattern, e);
+                    false
+                }
+            })
+    }
+
+    /// Delay before retry number `retry` (starting at 0), or `None` once the attempts are used up
+    pub fn delay_before(&self, retry: usize) -> Option<Duration> {
+        ExponentialBuilder::default()
+            .with_min_delay(Duration::from_secs(self.min_delay_seconds.into()))
+            .with_max_delay(Duration::from_secs(self.max_delay_seconds.into()))
+            .with_max_times(self.max_attempts as usize)
+            .build()
+            .nth(retry)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
@@ -227,6 +363,12 @@ impl Default for Config {
                 bitness: info.bitness().to_string(),
             },
             workspace_dir: None,
+            review_executor: None,
+            executor_limits: HashMap::new(),
+            retry_policies: HashMap::new(),
+            executor_resource_limits: HashMap::new(),
+            stop_grace_period_seconds: command_runner::DEFAULT_STOP_GRACE_PERIOD.as_secs(),
+            pipeline_check_timeout_seconds: pipeline::DEFAULT_CHECK_TIMEOUT.as_secs(),
         }
     }
 }
@@ -431,3 +573,117 @@ impl Config {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strictest_limits_take_the_smaller_value_of_each() {
+        let project = ExecutionLimits {
+            max_runtime_seconds: Some(3600),
+            idle_timeout_seconds: None,
+        };
+        let executor = ExecutionLimits {
+            max_runtime_seconds: Some(600),
+            idle_timeout_seconds: Some(120),
+        };
+
+        assert_eq!(
+            project.strictest(executor),
+            ExecutionLimits {
+                max_runtime_seconds: Some(600),
+                idle_timeout_seconds: Some(120),
+            }
+        );
+        assert_eq!(ExecutionLimits::default().strictest(project), project);
+    }
+
+    #[test]
+    fn exceeded_reports_runtime_before_idle() {
+        let limits = ExecutionLimits {
+            max_runtime_seconds: Some(600),
+            idle_timeout_seconds: Some(120),
+        };
+
+        assert_eq!(
+            limits.exceeded(Duration::from_secs(300), Duration::from_secs(60)),
+            None
+        );
+        assert_eq!(
+            limits.exceeded(Duration::from_secs(300), Duration::from_secs(120)),
+            Some("No output for 120 seconds".to_string())
+        );
+        assert_eq!(
+            limits.exceeded(Duration::from_secs(600), Duration::from_secs(600)),
+            Some("Exceeded the maximum runtime of 600 seconds".to_string())
+        );
+        assert_eq!(
+            ExecutionLimits::default().exceeded(Duration::MAX, Duration::MAX),
+            None
+        );
+    }
+
+    #[test]
+    fn resource_limits_combine_and_reject_non_positive_values() {
+        let project = ResourceLimits {
+            memory_limit_mb: Some(4096),
+            cpu_limit_percent: None,
+            max_processes: Some(512),
+        };
+        let executor = ResourceLimits {
+            memory_limit_mb: Some(2048),
+            cpu_limit_percent: Some(200),
+            max_processes: None,
+        };
+
+        assert_eq!(
+            project.strictest(executor),
+            ResourceLimits {
+                memory_limit_mb: Some(2048),
+                cpu_limit_percent: Some(200),
+                max_processes: Some(512),
+            }
+        );
+        assert!(ResourceLimits::default().is_unlimited());
+        assert!(!project.is_unlimited());
+        assert!(project.validate().is_ok());
+        assert_eq!(
+            ResourceLimits {
+                cpu_limit_percent: Some(0),
+                ..ResourceLimits::default()
+            }
+            .validate(),
+            Err("CPU limit must be greater than zero".to_string())
+        );
+    }
+
+    #[test]
+    fn retry_policy_matches_transient_output() {
+        let policy = RetryPolicy {
+            transient_patterns: vec![r"(?i)rate.?limit".to_string(), "[invalid".to_string()],
+            max_attempts: 3,
+            min_delay_seconds: 1,
+            max_delay_seconds: 60,
+        };
+
+        assert!(policy.is_transient("Error: Rate limit exceeded, try again later"));
+        assert!(!policy.is_transient("error[E0425]: cannot find value `x`"));
+    }
+
+    #[test]
+    fn retry_policy_backs_off_until_attempts_run_out() {
+        let policy = RetryPolicy {
+            transient_patterns: Vec::new(),
+            max_attempts: 4,
+            min_delay_seconds: 10,
+            max_delay_seconds: 30,
+        };
+
+        assert_eq!(policy.delay_before(0), Some(Duration::from_secs(10)));
+        assert_eq!(policy.delay_before(1), Some(Duration::from_secs(20)));
+        assert_eq!(policy.delay_before(2), Some(Duration::from_secs(30)));
+        assert_eq!(policy.delay_before(3), Some(Duration::from_secs(30)));
+        assert_eq!(policy.delay_before(4), None);
+    }
+}
@@ -31,6 +31,7 @@ pub enum ExecutionProcessStatus {
     Completed,
     Failed,
     Killed,
+    TimedOut,
 }
 
 #[derive(Debug, Clone, Type, Serialize, Deserialize, PartialEq, TS)]
@@ -41,6 +42,10 @@ pub enum ExecutionProcessType {
     SetupScript,
     CleanupScript,
     CodingAgent,
+    /// Read-only agent run producing a plan to approve
+    PlanningAgent,
+    /// Read-only agent run reviewing an attempt's diff
+    ReviewAgent,
     DevServer,
 }
 
@@ -50,6 +55,8 @@ impl From<ExecutionType> for ExecutionProcessType {
             ExecutionType::SetupScript => ExecutionProcessType::SetupScript,
             ExecutionType::CleanupScript => ExecutionProcessType::CleanupScript,
             ExecutionType::CodingAgent => ExecutionProcessType::CodingAgent,
+            ExecutionType::PlanningAgent => ExecutionProcessType::PlanningAgent,
+            ExecutionType::ReviewAgent => ExecutionProcessType::ReviewAgent,
             ExecutionType::DevServer => ExecutionProcessType::DevServer,
         }
     }
@@ -61,6 +68,8 @@ impl From<ExecutionProcessType> for ExecutionType {
             ExecutionProcessType::SetupScript => ExecutionType::SetupScript,
             ExecutionProcessType::CleanupScript => ExecutionType::CleanupScript,
             ExecutionProcessType::CodingAgent => ExecutionType::CodingAgent,
+            ExecutionProcessType::PlanningAgent => ExecutionType::PlanningAgent,
+            ExecutionProcessType::ReviewAgent => ExecutionType::ReviewAgent,
             ExecutionProcessType::DevServer => ExecutionType::DevServer,
         }
     }
@@ -81,6 +90,13 @@ pub struct ExecutionProcess {
     #[serde(serialize_with = "serialize_filtered_stderr")]
     pub stderr: Option<String>,
     pub exit_code: Option<i64>,
+    pub timeout_reason: Option<String>, // Set when a runtime or idle limit stopped the process
+    pub retry_of_process_id: Option<Uuid>, // The failed process this one automatically retries
+    pub persona_id: Option<Uuid>,       // The project persona an agent process acted as
+    pub model: Option<String>,          // Model the agent CLI was asked to use
+    pub cli_version: Option<String>,    // Pinned agent CLI version; None ran the default
+    pub peak_memory_bytes: Option<i64>, // Most memory the process group used at once
+    pub cpu_time_ms: Option<i64>,       // CPU time of the process group and its descendants
     pub started_at: DateTime<Utc>,
     pub completed_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
@@ -96,6 +112,10 @@ pub struct CreateExecutionProcess {
     pub command: String,
     pub args: Option<String>,
     pub working_directory: String,
+    pub persona_id: Option<Uuid>,
+    pub model: Option<String>,
+    pub cli_version: Option<String>,
+    pub retry_of_process_id: Option<Uuid>,
 }
 
 #[derive(Debug, Deserialize, TS)]
@@ -119,6 +139,13 @@ pub struct ExecutionProcessSummary {
     pub args: Option<String>, // JSON array of arguments
     pub working_directory: String,
     pub exit_code: Option<i64>,
+    pub timeout_reason: Option<String>, // Set when a runtime or idle limit stopped the process
+    pub retry_of_process_id: Option<Uuid>, // The failed process this one automatically retries
+    pub persona_id: Option<Uuid>,       // The project persona an agent process acted as
+    pub model: Option<String>,          // Model the agent CLI was asked to use
+    pub cli_version: Option<String>,    // Pinned agent CLI version; None ran the default
+    pub peak_memory_bytes: Option<i64>, // Most memory the process group used at once
+    pub cpu_time_ms: Option<i64>,       // CPU time of the process group and its descendants
     pub started_at: DateTime<Utc>,
     pub completed_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
@@ -142,6 +169,13 @@ impl ExecutionProcess {
                 stdout, 
                 stderr, 
                 exit_code,
+                timeout_reason,
+                retry_of_process_id as "retry_of_process_id?: Uuid",
+                persona_id as "persona_id?: Uuid",
+                model,
+                cli_version,
+                peak_memory_bytes,
+                cpu_time_ms,
                 started_at as "started_at!: DateTime<Utc>",
                 completed_at as "completed_at?: DateTime<Utc>",
                 created_at as "created_at!: DateTime<Utc>", 
@@ -173,6 +207,13 @@ impl ExecutionProcess {
                 stdout, 
                 stderr, 
                 exit_code,
+                timeout_reason,
+                retry_of_process_id as "retry_of_process_id?: Uuid",
+                persona_id as "persona_id?: Uuid",
+                model,
+                cli_version,
+                peak_memory_bytes,
+                cpu_time_ms,
                 started_at as "started_at!: DateTime<Utc>",
                 completed_at as "completed_at?: DateTime<Utc>",
                 created_at as "created_at!: DateTime<Utc>", 
@@ -203,6 +244,13 @@ impl ExecutionProcess {
                 args, 
                 working_directory, 
                 exit_code,
+                timeout_reason,
+                retry_of_process_id as "retry_of_process_id?: Uuid",
+                persona_id as "persona_id?: Uuid",
+                model,
+                cli_version,
+                peak_memory_bytes,
+                cpu_time_ms,
                 started_at as "started_at!: DateTime<Utc>",
                 completed_at as "completed_at?: DateTime<Utc>",
                 created_at as "created_at!: DateTime<Utc>", 
@@ -232,6 +280,13 @@ impl ExecutionProcess {
                 stdout, 
                 stderr, 
                 exit_code,
+                timeout_reason,
+                retry_of_process_id as "retry_of_process_id?: Uuid",
+                persona_id as "persona_id?: Uuid",
+                model,
+                cli_version,
+                peak_memory_bytes,
+                cpu_time_ms,
                 started_at as "started_at!: DateTime<Utc>",
                 completed_at as "completed_at?: DateTime<Utc>",
                 created_at as "created_at!: DateTime<Utc>", 
@@ -263,6 +318,13 @@ impl ExecutionProcess {
                 ep.stdout, 
                 ep.stderr, 
                 ep.exit_code,
+                ep.timeout_reason,
+                ep.retry_of_process_id as "retry_of_process_id?: Uuid",
+                ep.persona_id as "persona_id?: Uuid",
+                ep.model,
+                ep.cli_version,
+                ep.peak_memory_bytes,
+                ep.cpu_time_ms,
                 ep.started_at as "started_at!: DateTime<Utc>",
                 ep.completed_at as "completed_at?: DateTime<Utc>",
                 ep.created_at as "created_at!: DateTime<Utc>", 
@@ -293,9 +355,10 @@ impl ExecutionProcess {
             r#"INSERT INTO execution_processes (
                 id, task_attempt_id, process_type, executor_type, status, command, args, 
                 working_directory, stdout, stderr, exit_code, started_at, 
-                completed_at, created_at, updated_at
+                completed_at, created_at, updated_at, persona_id, model, cli_version,
+                retry_of_process_id
                ) 
-               VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15) 
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19) 
                RETURNING 
                 id as "id!: Uuid", 
                 task_attempt_id as "task_attempt_id!: Uuid", 
@@ -308,6 +371,13 @@ impl ExecutionProcess {
                 stdout, 
                 stderr, 
                 exit_code,
+                timeout_reason,
+                retry_of_process_id as "retry_of_process_id?: Uuid",
+                persona_id as "persona_id?: Uuid",
+                model,
+                cli_version,
+                peak_memory_bytes,
+                cpu_time_ms,
                 started_at as "started_at!: DateTime<Utc>",
                 completed_at as "completed_at?: DateTime<Utc>",
                 created_at as "created_at!: DateTime<Utc>", 
@@ -326,7 +396,11 @@ impl ExecutionProcess {
             now,                   // started_at
             None::<DateTime<Utc>>, // completed_at
             now,                   // created_at
-            now                    // updated_at
+            now,                   // updated_at
+            data.persona_id,
+            data.model,
+            data.cli_version,
+            data.retry_of_process_id
         )
         .fetch_one(pool)
         .await
@@ -360,6 +434,68 @@ impl ExecutionProcess {
         Ok(())
     }
 
+    /// Record the most memory and CPU time the process has used so far
+    ///
+    /// Leaves `updated_at` alone, as idle timeouts read it as the time of the last output.
+    pub async fn update_resource_usage(
+        pool: &SqlitePool,
+        id: Uuid,
+        peak_memory_bytes: i64,
+        cpu_time_ms: i64,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE execution_processes
+               SET peak_memory_bytes = MAX(COALESCE(peak_memory_bytes, 0), $1),
+                   cpu_time_ms = MAX(COALESCE(cpu_time_ms, 0), $2)
+               WHERE id = $3"#,
+            peak_memory_bytes,
+            cpu_time_ms,
+            id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Mark a process stopped for exceeding a runtime or idle limit
+    pub async fn mark_timed_out(
+        pool: &SqlitePool,
+        id: Uuid,
+        reason: &str,
+    ) -> Result<(), sqlx::Error> {
+        let completed_at = Utc::now();
+        let status = ExecutionProcessStatus::TimedOut;
+
+        sqlx::query!(
+            r#"UPDATE execution_processes
+               SET status = $1, timeout_reason = $2, completed_at = $3, updated_at = datetime('now')
+               WHERE id = $4"#,
+            status,
+            reason,
+            completed_at,
+            id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Number of automatic retries already made for the process `original_id`
+    pub async fn count_retries(pool: &SqlitePool, original_id: Uuid) -> Result<i64, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"SELECT COUNT(*) as "count!: i64"
+               FROM execution_processes
+               WHERE retry_of_process_id = $1"#,
+            original_id
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(result.count)
+    }
+
     /// Append to stdout for this execution process (for streaming updates)
     pub async fn append_stdout(
         pool: &SqlitePool,
@@ -0,0 +1,148 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use crate::executor::{NormalizedEntry, NormalizedEntryType};
+
+/// A message sent to a running execution process
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ExecutionProcessInput {
+    pub id: Uuid,
+    pub execution_process_id: Uuid,
+    pub message: String,
+    pub entry_index: Option<i64>, // Conversation length when sent; None when there was no live log
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export)]
+pub struct SendInputRequest {
+    pub message: String,
+}
+
+impl ExecutionProcessInput {
+    pub async fn create(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+        message: &str,
+        entry_index: Option<i64>,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            ExecutionProcessInput,
+            r#"INSERT INTO execution_process_inputs (id, execution_process_id, message, entry_index)
+               VALUES ($1, $2, $3, $4)
+               RETURNING id                   AS "id!: Uuid",
+                         execution_process_id AS "execution_process_id!: Uuid",
+                         message,
+                         entry_index,
+                         created_at           AS "created_at!: DateTime<Utc>""#,
+            id,
+            execution_process_id,
+            message,
+            entry_index
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn find_by_execution_process_id(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ExecutionProcessInput,
+            r#"SELECT id                   AS "id!: Uuid",
+                      execution_process_id AS "execution_process_id!: Uuid",
+                      message,
+                      entry_index,
+                      created_at           AS "created_at!: DateTime<Utc>"
+               FROM execution_process_inputs
+               WHERE execution_process_id = $1
+               ORDER BY created_at ASC"#,
+            execution_process_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// The message as it appears in the conversation
+    pub fn to_entry(&self) -> NormalizedEntry {
+        user_message_entry(&self.message, self.created_at)
+    }
+}
+
+/// Conversation entry for a message sent to a running process
+pub fn user_message_entry(message: &str, sent_at: DateTime<Utc>) -> NormalizedEntry {
+    NormalizedEntry {
+        timestamp: Some(sent_at.to_rfc3339()),
+        entry_type: NormalizedEntryType::UserMessage,
+        content: message.to_string(),
+        metadata: None,
+    }
+}
+
+/// Insert sent messages into a conversation normalized from stored output, at the positions
+/// they had in the live conversation
+pub fn merge_inputs(entries: &mut Vec<NormalizedEntry>, inputs: &[ExecutionProcessInput]) {
+    let mut positioned: Vec<&ExecutionProcessInput> = inputs.iter().collect();
+    positioned.sort_by_key(|input| input.entry_index.unwrap_or(i64::MAX));
+    for input in positioned {
+        let index = input
+            .entry_index
+            .map(|index| (index.max(0) as usize).min(entries.len()))
+            .unwrap_or(entries.len());
+        entries.insert(index, input.to_entry());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(content: &str) -> NormalizedEntry {
+        NormalizedEntry {
+            timestamp: None,
+            entry_type: NormalizedEntryType::AssistantMessage,
+            content: content.to_string(),
+            metadata: None,
+        }
+    }
+
+    fn input(message: &str, entry_index: Option<i64>) -> ExecutionProcessInput {
+        ExecutionProcessInput {
+            id: Uuid::new_v4(),
+            execution_process_id: Uuid::new_v4(),
+            message: message.to_string(),
+            entry_index,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn merge_inputs_restores_live_positions() {
+        let mut entries = vec![entry("a"), entry("b"), entry("c")];
+        merge_inputs(
+            &mut entries,
+            &[
+                input("late", None),
+                input("second", Some(3)),
+                input("first", Some(1)),
+                input("past the end", Some(10)),
+            ],
+        );
+
+        let contents: Vec<&str> = entries.iter().map(|e| e.content.as_str()).collect();
+        assert_eq!(
+            contents,
+            vec!["a", "first", "b", "second", "c", "past the end", "late"]
+        );
+        assert!(matches!(
+            entries[1].entry_type,
+            NormalizedEntryType::UserMessage
+        ));
+    }
+}
@@ -13,6 +13,7 @@ pub struct ExecutorSession {
     pub session_id: Option<String>, // External session ID from Claude/Amp
     pub prompt: Option<String>,     // The prompt sent to the executor
     pub summary: Option<String>,    // Final assistant message/summary
+    pub after_head_commit: Option<String>, // Worktree HEAD after the run's changes were committed
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -36,7 +37,6 @@ pub struct UpdateExecutorSession {
 
 impl ExecutorSession {
     /// Find executor session by ID
-    #[allow(dead_code)]
     pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
         sqlx::query_as!(
             ExecutorSession,
@@ -47,6 +47,7 @@ impl ExecutorSession {
                 session_id, 
                 prompt,
                 summary,
+                after_head_commit,
                 created_at as "created_at!: DateTime<Utc>", 
                 updated_at as "updated_at!: DateTime<Utc>"
                FROM executor_sessions 
@@ -71,6 +72,7 @@ impl ExecutorSession {
                 session_id,
                 prompt,
                 summary,
+                after_head_commit,
                 created_at as "created_at!: DateTime<Utc>",
                 updated_at as "updated_at!: DateTime<Utc>"
                FROM executor_sessions
@@ -82,7 +84,6 @@ impl ExecutorSession {
     }
 
     /// Find all executor sessions for a task attempt
-    #[allow(dead_code)]
     pub async fn find_by_task_attempt_id(
         pool: &SqlitePool,
         task_attempt_id: Uuid,
@@ -96,6 +97,7 @@ impl ExecutorSession {
                 session_id, 
                 prompt,
                 summary,
+                after_head_commit,
                 created_at as "created_at!: DateTime<Utc>", 
                 updated_at as "updated_at!: DateTime<Utc>"
                FROM executor_sessions 
@@ -134,6 +136,7 @@ impl ExecutorSession {
                 session_id,
                 prompt,
                 summary,
+                after_head_commit,
                 created_at as "created_at!: DateTime<Utc>",
                 updated_at as "updated_at!: DateTime<Utc>""#,
             session_id,
@@ -168,6 +171,25 @@ impl ExecutorSession {
         Ok(())
     }
 
+    /// Record the worktree HEAD once the run's changes were committed
+    pub async fn update_after_head_commit(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+        commit: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE executor_sessions
+               SET after_head_commit = $1, updated_at = datetime('now')
+               WHERE execution_process_id = $2"#,
+            commit,
+            execution_process_id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
     /// Update executor session prompt
     #[allow(dead_code)]
     pub async fn update_prompt(
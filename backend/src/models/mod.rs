@@ -1,11 +1,20 @@
 pub mod api_response;
 pub mod config;
 pub mod execution_process;
+pub mod execution_process_input;
 pub mod executor_session;
+pub mod permission;
 pub mod persona;
+pub mod pipeline;
 pub mod project;
+pub mod project_cli_version;
+pub mod prompt_template;
+pub mod review_finding;
 pub mod task;
+pub mod task_attachment;
 pub mod task_attempt;
+pub mod task_attempt_group;
+pub mod token_usage;
 
 pub mod task_template;
 
@@ -0,0 +1,639 @@
+use std::path::{Component, Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use globset::Glob;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{FromRow, SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Used for projects that have not configured a policy yet
+pub const DEFAULT_TIMEOUT_SECONDS: i64 = 300;
+const MAX_TIMEOUT_SECONDS: i64 = 3600;
+
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, TS)]
+#[sqlx(type_name = "permission_decision", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+#[ts(export)]
+pub enum PermissionDecision {
+    Allow,
+    Deny,
+}
+
+/// What settled a permission request
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, TS)]
+#[sqlx(type_name = "permission_decision_source", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum PermissionDecisionSource {
+    Rule,
+    User,
+    /// Nobody answered in time and the policy's default decision was applied
+    Timeout,
+    /// Recorded by earlier versions, which let everything through when a project had no rules
+    NoRules,
+}
+
+/// Matches a tool call when every criterion that is set matches
+///
+/// `tool_name` and `path_glob` are globs; `command_pattern` is a regex. A rule with no criteria
+/// matches every call.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
+#[ts(export)]
+pub struct PermissionRule {
+    pub decision: PermissionDecision,
+    pub tool_name: Option<String>,
+    pub path_glob: Option<String>,
+    pub command_pattern: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct PermissionPolicy {
+    pub project_id: Uuid,
+    pub timeout_seconds: i64, // How long a request waits for approval
+    pub default_decision: PermissionDecision, // Applied when the wait times out
+    pub rules: Vec<PermissionRule>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export)]
+pub struct UpdatePermissionPolicy {
+    pub timeout_seconds: i64,
+    pub default_decision: PermissionDecision,
+    pub rules: Vec<PermissionRule>,
+}
+
+/// A tool call an agent asked permission for, and its outcome once decided
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct PermissionRequest {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub task_attempt_id: Option<Uuid>,
+    pub execution_process_id: Option<Uuid>,
+    pub tool_name: String,
+    pub input: String, // Tool input as JSON
+    pub default_decision: PermissionDecision,
+    pub decision: Option<PermissionDecision>, // None while pending
+    pub decided_by: Option<PermissionDecisionSource>,
+    pub rule_position: Option<i64>,
+    pub message: Option<String>,
+    pub expires_at: DateTime<Utc>,
+    pub decided_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export)]
+pub struct DecidePermissionRequest {
+    pub decision: PermissionDecision,
+    pub message: Option<String>,
+}
+
+impl PermissionRule {
+    fn matches(&self, tool_name: &str, input: &Value, worktree_path: &str) -> bool {
+        if let Some(pattern) = &self.tool_name {
+            if !glob_matches(pattern, tool_name) {
+                return false;
+            }
+        }
+        if let Some(pattern) = &self.path_glob {
+            let Ok(paths) = input_paths(input, worktree_path) else {
+                return false;
+            };
+            if !paths.iter().any(|path| glob_matches(pattern, path)) {
+                return false;
+            }
+        }
+        if let Some(pattern) = &self.command_pattern {
+            let Some(command) = input.get("command").and_then(Value::as_str) else {
+                return false;
+            };
+            match Regex::new(pattern) {
+                Ok(regex) if regex.is_match(command) => {}
+                Ok(_) => return false,
+                Err(e) => {
+                    tracing::warn!("Invalid permission command pattern '{}': {}", pattern, e);
+                    return false;
+                }
+            }
+        }
+        true
+    }
+}
+
+fn glob_matches(pattern: &str, value: &str) -> bool {
+    match Glob::new(pattern) {
+        Ok(glob) => glob.compile_matcher().is_match(value),
+        Err(e) => {
+            tracing::warn!("Invalid permission glob '{}': {}", pattern, e);
+            false
+        }
+    }
+}
+
+/// File paths a tool call touches, relative to the worktree
+///
+/// Paths are resolved against the worktree without touching the filesystem, so `..` can't
+/// walk a matching prefix out of it. Returns the first path that ends up outside instead.
+fn input_paths(input: &Value, worktree_path: &str) -> Result<Vec<String>, String> {
+    let worktree = normalize(Path::new(worktree_path));
+    ["file_path", "notebook_path", "path"]
+        .iter()
+        .filter_map(|key| input.get(*key).and_then(Value::as_str))
+        .map(|path| {
+            normalize(&worktree.join(path))
+                .strip_prefix(&worktree)
+                .map(|relative| relative.to_string_lossy().to_string())
+                .map_err(|_| path.to_string())
+        })
+        .collect()
+}
+
+/// `path` with `.` and `..` components resolved lexically
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// The first path a tool call touches outside the worktree, if any
+pub fn path_outside_worktree(input: &Value, worktree_path: &str) -> Option<String> {
+    input_paths(input, worktree_path).err()
+}
+
+/// Check that a policy can be applied, returning a message for the first problem found
+pub fn validate_policy(policy: &UpdatePermissionPolicy) -> Result<(), String> {
+    if !(1..=MAX_TIMEOUT_SECONDS).contains(&policy.timeout_seconds) {
+        return Err(format!(
+            "Timeout must be between 1 and {} seconds",
+            MAX_TIMEOUT_SECONDS
+        ));
+    }
+    for (index, rule) in policy.rules.iter().enumerate() {
+        for glob in [&rule.tool_name, &rule.path_glob].into_iter().flatten() {
+            if let Err(e) = Glob::new(glob) {
+                return Err(format!("Rule {} has an invalid glob: {}", index + 1, e));
+            }
+        }
+        if let Some(pattern) = &rule.command_pattern {
+            if let Err(e) = Regex::new(pattern) {
+                return Err(format!(
+                    "Rule {} has an invalid command pattern: {}",
+                    index + 1,
+                    e
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+impl PermissionPolicy {
+    /// The first matching deny rule, otherwise the first matching allow rule
+    pub fn evaluate(
+        &self,
+        tool_name: &str,
+        input: &Value,
+        worktree_path: &str,
+    ) -> Option<(usize, PermissionDecision)> {
+        let find = |decision: PermissionDecision| {
+            self.rules.iter().position(|rule| {
+                rule.decision == decision && rule.matches(tool_name, input, worktree_path)
+            })
+        };
+        find(PermissionDecision::Deny)
+            .map(|position| (position, PermissionDecision::Deny))
+            .or_else(|| find(PermissionDecision::Allow).map(|p| (p, PermissionDecision::Allow)))
+    }
+
+    /// Whether the project has saved a policy, rather than running with the defaults
+    pub async fn is_configured(pool: &SqlitePool, project_id: Uuid) -> Result<bool, sqlx::Error> {
+        let record = sqlx::query!(
+            "SELECT project_id FROM permission_policies WHERE project_id = $1",
+            project_id
+        )
+        .fetch_optional(pool)
+        .await?;
+        Ok(record.is_some())
+    }
+
+    /// The project's policy, or an empty one with the defaults when none is stored
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Self, sqlx::Error> {
+        let record = sqlx::query!(
+            r#"SELECT timeout_seconds,
+                      default_decision AS "default_decision!: PermissionDecision"
+               FROM permission_policies
+               WHERE project_id = $1"#,
+            project_id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        let Some(record) = record else {
+            return Ok(Self {
+                project_id,
+                timeout_seconds: DEFAULT_TIMEOUT_SECONDS,
+                default_decision: PermissionDecision::Deny,
+                rules: Vec::new(),
+            });
+        };
+
+        let rules = sqlx::query_as!(
+            PermissionRule,
+            r#"SELECT decision AS "decision!: PermissionDecision",
+                      tool_name,
+                      path_glob,
+                      command_pattern
+               FROM permission_rules
+               WHERE project_id = $1
+               ORDER BY position ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(Self {
+            project_id,
+            timeout_seconds: record.timeout_seconds,
+            default_decision: record.default_decision,
+            rules,
+        })
+    }
+
+    pub async fn update(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        data: &UpdatePermissionPolicy,
+    ) -> Result<Self, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        sqlx::query!(
+            r#"INSERT INTO permission_policies (project_id, timeout_seconds, default_decision)
+               VALUES ($1, $2, $3)
+               ON CONFLICT(project_id) DO UPDATE SET
+                   timeout_seconds = excluded.timeout_seconds,
+                   default_decision = excluded.default_decision,
+                   updated_at = datetime('now', 'subsec')"#,
+            project_id,
+            data.timeout_seconds,
+            data.default_decision
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "DELETE FROM permission_rules WHERE project_id = $1",
+            project_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        for (position, rule) in data.rules.iter().enumerate() {
+            let id = Uuid::new_v4();
+            let position = position as i64;
+            sqlx::query!(
+                r#"INSERT INTO permission_rules (id, project_id, position, decision, tool_name, path_glob, command_pattern)
+                   VALUES ($1, $2, $3, $4, $5, $6, $7)"#,
+                id,
+                project_id,
+                position,
+                rule.decision,
+                rule.tool_name,
+                rule.path_glob,
+                rule.command_pattern
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        Self::find_by_project_id(pool, project_id).await
+    }
+}
+
+impl PermissionRequest {
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        task_attempt_id: Option<Uuid>,
+        execution_process_id: Option<Uuid>,
+        tool_name: &str,
+        input: &str,
+        default_decision: PermissionDecision,
+        timeout_seconds: i64,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        let expires_in = format!("+{} seconds", timeout_seconds);
+        sqlx::query_as!(
+            PermissionRequest,
+            r#"INSERT INTO permission_requests (id, project_id, task_attempt_id, execution_process_id, tool_name, input, default_decision, expires_at)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, datetime('now', 'subsec', $8))
+               RETURNING id                   AS "id!: Uuid",
+                         project_id           AS "project_id!: Uuid",
+                         task_attempt_id      AS "task_attempt_id?: Uuid",
+                         execution_process_id AS "execution_process_id?: Uuid",
+                         tool_name,
+                         input,
+                         default_decision     AS "default_decision!: PermissionDecision",
+                         decision             AS "decision?: PermissionDecision",
+                         decided_by           AS "decided_by?: PermissionDecisionSource",
+                         rule_position,
+                         message,
+                         expires_at           AS "expires_at!: DateTime<Utc>",
+                         decided_at           AS "decided_at?: DateTime<Utc>",
+                         created_at           AS "created_at!: DateTime<Utc>""#,
+            id,
+            project_id,
+            task_attempt_id,
+            execution_process_id,
+            tool_name,
+            input,
+            default_decision,
+            expires_in
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            PermissionRequest,
+            r#"SELECT id                   AS "id!: Uuid",
+                      project_id           AS "project_id!: Uuid",
+                      task_attempt_id      AS "task_attempt_id?: Uuid",
+                      execution_process_id AS "execution_process_id?: Uuid",
+                      tool_name,
+                      input,
+                      default_decision     AS "default_decision!: PermissionDecision",
+                      decision             AS "decision?: PermissionDecision",
+                      decided_by           AS "decided_by?: PermissionDecisionSource",
+                      rule_position,
+                      message,
+                      expires_at           AS "expires_at!: DateTime<Utc>",
+                      decided_at           AS "decided_at?: DateTime<Utc>",
+                      created_at           AS "created_at!: DateTime<Utc>"
+               FROM permission_requests
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Requests raised by a project's agents, newest first
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        task_attempt_id: Option<Uuid>,
+        pending_only: bool,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            PermissionRequest,
+            r#"SELECT id                   AS "id!: Uuid",
+                      project_id           AS "project_id!: Uuid",
+                      task_attempt_id      AS "task_attempt_id?: Uuid",
+                      execution_process_id AS "execution_process_id?: Uuid",
+                      tool_name,
+                      input,
+                      default_decision     AS "default_decision!: PermissionDecision",
+                      decision             AS "decision?: PermissionDecision",
+                      decided_by           AS "decided_by?: PermissionDecisionSource",
+                      rule_position,
+                      message,
+                      expires_at           AS "expires_at!: DateTime<Utc>",
+                      decided_at           AS "decided_at?: DateTime<Utc>",
+                      created_at           AS "created_at!: DateTime<Utc>"
+               FROM permission_requests
+               WHERE project_id = $1
+                 AND ($2 IS NULL OR task_attempt_id = $2)
+                 AND ($3 = 0 OR decision IS NULL)
+               ORDER BY created_at DESC"#,
+            project_id,
+            task_attempt_id,
+            pending_only
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Settle a pending request; returns None when it was already decided
+    pub async fn decide(
+        pool: &SqlitePool,
+        id: Uuid,
+        decision: PermissionDecision,
+        decided_by: PermissionDecisionSource,
+        rule_position: Option<i64>,
+        message: Option<&str>,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let request = sqlx::query_as!(
+            PermissionRequest,
+            r#"UPDATE permission_requests
+               SET decision = $2, decided_by = $3, rule_position = $4, message = $5,
+                   decided_at = datetime('now', 'subsec')
+               WHERE id = $1 AND decision IS NULL
+               RETURNING id                   AS "id!: Uuid",
+                         project_id           AS "project_id!: Uuid",
+                         task_attempt_id      AS "task_attempt_id?: Uuid",
+                         execution_process_id AS "execution_process_id?: Uuid",
+                         tool_name,
+                         input,
+                         default_decision     AS "default_decision!: PermissionDecision",
+                         decision             AS "decision?: PermissionDecision",
+                         decided_by           AS "decided_by?: PermissionDecisionSource",
+                         rule_position,
+                         message,
+                         expires_at           AS "expires_at!: DateTime<Utc>",
+                         decided_at           AS "decided_at?: DateTime<Utc>",
+                         created_at           AS "created_at!: DateTime<Utc>""#,
+            id,
+            decision,
+            decided_by,
+            rule_position,
+            message
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        if let Some(request) = &request {
+            tracing::info!(
+                "Permission request {} for {} decided: {:?} by {:?}",
+                request.id,
+                request.tool_name,
+                decision,
+                decided_by
+            );
+        }
+        Ok(request)
+    }
+
+    /// Apply the default decision to requests nobody answered in time
+    ///
+    /// The agent's gateway does this itself while it waits; this catches requests left behind
+    /// by gateways that exited early.
+    pub async fn expire_overdue(pool: &SqlitePool, project_id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"UPDATE permission_requests
+               SET decision = default_decision, decided_by = 'timeout',
+                   decided_at = datetime('now', 'subsec')
+               WHERE project_id = $1
+                 AND decision IS NULL
+                 AND expires_at <= datetime('now', 'subsec')"#,
+            project_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    pub fn is_overdue(&self) -> bool {
+        self.decision.is_none() && self.expires_at <= Utc::now()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn rule(
+        decision: PermissionDecision,
+        tool_name: Option<&str>,
+        path_glob: Option<&str>,
+        command_pattern: Option<&str>,
+    ) -> PermissionRule {
+        PermissionRule {
+            decision,
+            tool_name: tool_name.map(str::to_string),
+            path_glob: path_glob.map(str::to_string),
+            command_pattern: command_pattern.map(str::to_string),
+        }
+    }
+
+    fn policy(rules: Vec<PermissionRule>) -> PermissionPolicy {
+        PermissionPolicy {
+            project_id: Uuid::new_v4(),
+            timeout_seconds: DEFAULT_TIMEOUT_SECONDS,
+            default_decision: PermissionDecision::Deny,
+            rules,
+        }
+    }
+
+    #[test]
+    fn evaluate_prefers_deny_rules_and_matches_relative_paths() {
+        let policy = policy(vec![
+            rule(
+                PermissionDecision::Allow,
+                Some("Edit"),
+                Some("src/**"),
+                None,
+            ),
+            rule(PermissionDecision::Deny, None, Some("**/.env"), None),
+            rule(
+                PermissionDecision::Allow,
+                Some("Bash"),
+                None,
+                Some("^cargo "),
+            ),
+        ]);
+        let worktree = "/tmp/worktree";
+
+        let edit = json!({ "file_path": "/tmp/worktree/src/main.rs" });
+        assert_eq!(
+            policy.evaluate("Edit", &edit, worktree),
+            Some((0, PermissionDecision::Allow))
+        );
+
+        let env = json!({ "file_path": "/tmp/worktree/src/.env" });
+        assert_eq!(
+            policy.evaluate("Edit", &env, worktree),
+            Some((1, PermissionDecision::Deny))
+        );
+
+        let cargo = json!({ "command": "cargo test" });
+        assert_eq!(
+            policy.evaluate("Bash", &cargo, worktree),
+            Some((2, PermissionDecision::Allow))
+        );
+
+        let rm = json!({ "command": "rm -rf /" });
+        assert_eq!(policy.evaluate("Bash", &rm, worktree), None);
+    }
+
+    #[test]
+    fn evaluate_resolves_paths_against_the_worktree() {
+        let policy = policy(vec![rule(
+            PermissionDecision::Allow,
+            Some("Edit"),
+            Some("src/**"),
+            None,
+        )]);
+        let worktree = "/tmp/worktree";
+
+        let relative = json!({ "file_path": "./src/../src/main.rs" });
+        assert_eq!(
+            policy.evaluate("Edit", &relative, worktree),
+            Some((0, PermissionDecision::Allow))
+        );
+        assert_eq!(path_outside_worktree(&relative, worktree), None);
+
+        for path in [
+            "/tmp/worktree/src/../../etc/passwd",
+            "src/../../worktree-other/src/main.rs",
+            "/tmp/worktree-other/src/main.rs",
+        ] {
+            let escape = json!({ "file_path": path });
+            assert_eq!(policy.evaluate("Edit", &escape, worktree), None, "{path}");
+            assert_eq!(
+                path_outside_worktree(&escape, worktree).as_deref(),
+                Some(path)
+            );
+        }
+    }
+
+    #[test]
+    fn validate_policy_rejects_bad_patterns() {
+        let update = |rules| UpdatePermissionPolicy {
+            timeout_seconds: 60,
+            default_decision: PermissionDecision::Deny,
+            rules,
+        };
+
+        assert!(validate_policy(&update(vec![rule(
+            PermissionDecision::Deny,
+            Some("Bash"),
+            None,
+            Some("rm -rf"),
+        )]))
+        .is_ok());
+        assert!(validate_policy(&update(vec![rule(
+            PermissionDecision::Allow,
+            None,
+            None,
+            Some("("),
+        )]))
+        .is_err());
+        assert!(validate_policy(&update(vec![rule(
+            PermissionDecision::Allow,
+            None,
+            Some("src/[a"),
+            None,
+        )]))
+        .is_err());
+    }
+}
@@ -392,6 +392,56 @@ pub struct PersonaActionWithArtifacts {
     pub artifacts: Vec<ActionArtifact>,
 }
 
+/// What an agent acting as a persona is told: its instructions, capabilities and the tools it
+/// must not use
+#[derive(Debug, Clone, PartialEq)]
+pub struct PersonaBrief {
+    pub id: Uuid,
+    pub name: String,
+    pub instructions: String,
+    pub capabilities: Vec<String>,
+    pub tool_restrictions: Vec<String>,
+}
+
+impl PersonaBrief {
+    /// The brief as a prompt section
+    pub fn render(&self) -> String {
+        let mut brief = format!(
+            "You are acting as {}.\n\n{}",
+            self.name,
+            self.instructions.trim()
+        );
+        if !self.capabilities.is_empty() {
+            brief.push_str(&format!(
+                "\n\nYour capabilities: {}",
+                self.capabilities.join(", ")
+            ));
+        }
+        if !self.tool_restrictions.is_empty() {
+            brief.push_str(&format!(
+                "\n\nDo not use these tools: {}",
+                self.tool_restrictions.join(", ")
+            ));
+        }
+        brief
+    }
+
+    /// Whether the persona may not use a tool; restrictions may end in `*` to cover a prefix
+    pub fn restricts(&self, tool_name: &str) -> bool {
+        self.tool_restrictions
+            .iter()
+            .any(|restriction| match restriction.strip_suffix('*') {
+                Some(prefix) => tool_name.starts_with(prefix),
+                None => restriction == tool_name,
+            })
+    }
+}
+
+/// Parse a JSON array column, treating empty or malformed values as no entries
+fn json_list(value: &str) -> Vec<String> {
+    serde_json::from_str(value).unwrap_or_default()
+}
+
 // Implementation methods for database operations
 impl PersonaTemplate {
     pub async fn find_all(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
@@ -499,6 +549,36 @@ impl ProjectPersona {
         Ok(personas)
     }
 
+    /// The brief for an agent acting as this persona: its custom name and instructions, or else
+    /// its template's, along with the template's capabilities and tool restrictions
+    pub async fn find_brief(
+        pool: &SqlitePool,
+        id: Uuid,
+    ) -> Result<Option<PersonaBrief>, sqlx::Error> {
+        let record = sqlx::query!(
+            r#"SELECT
+                pp.id as "id!: Uuid",
+                COALESCE(pp.custom_name, pt.name) as "name!: String",
+                COALESCE(pp.custom_instructions, pt.default_instructions) as "instructions!: String",
+                pt.capabilities,
+                pt.tool_restrictions
+            FROM project_personas pp
+            JOIN persona_templates pt ON pp.template_id = pt.id
+            WHERE pp.id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(record.map(|record| PersonaBrief {
+            id: record.id,
+            name: record.name,
+            instructions: record.instructions,
+            capabilities: json_list(&record.capabilities),
+            tool_restrictions: json_list(&record.tool_restrictions),
+        }))
+    }
+
     pub async fn create(
         pool: &SqlitePool,
         data: &CreateProjectPersona,
@@ -0,0 +1,511 @@
+use std::{str::FromStr, time::Duration};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use crate::executor::ExecutorConfig;
+
+/// How long a stage's check command may run before it counts as failed, unless configured
+/// otherwise
+pub const DEFAULT_CHECK_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// When a pipeline stage runs, relative to the stage before it
+#[derive(Debug, Clone, Type, Serialize, Deserialize, PartialEq, TS)]
+#[sqlx(type_name = "stage_condition", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum StageCondition {
+    Always,
+    PreviousSucceeded,
+    /// Runs only when `check_command` exits with a non-zero status
+    CheckFails,
+}
+
+#[derive(Debug, Clone, Type, Serialize, Deserialize, PartialEq, TS)]
+#[sqlx(type_name = "stage_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+#[ts(export)]
+pub enum StageStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+    Skipped,
+}
+
+/// One step of a pipeline: an executor plus the prompt it is given
+///
+/// `prompt_template` may use `{task_title}`, `{task_description}`, `{previous_summary}` and
+/// `{diff}`; the summary and diff are appended when the template does not place them.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
+#[ts(export)]
+pub struct PipelineStage {
+    pub name: String,
+    pub executor: String,
+    pub prompt_template: String,
+    pub condition: StageCondition,
+    pub check_command: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct Pipeline {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub name: String,
+    pub stages: Vec<PipelineStage>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export)]
+pub struct CreatePipeline {
+    pub name: String,
+    pub stages: Vec<PipelineStage>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export)]
+pub struct UpdatePipeline {
+    pub name: Option<String>,
+    pub stages: Option<Vec<PipelineStage>>,
+}
+
+/// A pipeline stage as scheduled on a task attempt
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct TaskAttemptStage {
+    pub id: Uuid,
+    pub task_attempt_id: Uuid,
+    pub position: i64,
+    pub name: String,
+    pub executor: String,
+    pub prompt_template: String,
+    pub condition: StageCondition,
+    pub check_command: Option<String>,
+    pub status: StageStatus,
+    pub execution_process_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Check that a pipeline can be run, returning a message for the first problem found
+pub fn validate_stages(stages: &[PipelineStage]) -> Result<(), String> {
+    if stages.is_empty() {
+        return Err("A pipeline needs at least one stage".to_string());
+    }
+    for (index, stage) in stages.iter().enumerate() {
+        if stage.name.trim().is_empty() {
+            return Err(format!("Stage {} has no name", index + 1));
+        }
+        if ExecutorConfig::from_str(&stage.executor).is_err() {
+            return Err(format!(
+                "Stage '{}' uses unknown executor '{}'",
+                stage.name, stage.executor
+            ));
+        }
+        let has_check = stage
+            .check_command
+            .as_ref()
+            .is_some_and(|command| !command.trim().is_empty());
+        if stage.condition == StageCondition::CheckFails && !has_check {
+            return Err(format!("Stage '{}' needs a check command", stage.name));
+        }
+    }
+    Ok(())
+}
+
+/// Build the prompt a stage runs with
+///
+/// Stages after the first get the previous stage's summary and the attempt's diff so far.
+pub fn render_stage_prompt(
+    template: &str,
+    task_title: &str,
+    task_description: Option<&str>,
+    previous_summary: Option<&str>,
+    diff: &str,
+) -> String {
+    let mut prompt = String::new();
+    if !template.contains("{task_title}") {
+        prompt.push_str(&format!("Task: {}\n\n", task_title));
+        if let Some(description) = task_description.filter(|d| !d.trim().is_empty()) {
+            prompt.push_str(&format!("{}\n\n", description));
+        }
+    }
+    prompt.push_str(
+        &template
+            .replace("{task_title}", task_title)
+            .replace("{task_description}", task_description.unwrap_or(""))
+            .replace("{previous_summary}", previous_summary.unwrap_or(""))
+            .replace("{diff}", diff),
+    );
+
+    if let Some(summary) = previous_summary.filter(|_| !template.contains("{previous_summary}")) {
+        prompt.push_str(&format!("\n\n## Previous stage summary\n\n{}", summary));
+    }
+    if !diff.trim().is_empty() && !template.contains("{diff}") {
+        prompt.push_str(&format!("\n\n## Changes so far\n\n```diff\n{}\n```", diff));
+    }
+    prompt
+}
+
+impl Pipeline {
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let records = sqlx::query!(
+            r#"SELECT id         AS "id!: Uuid",
+                      project_id AS "project_id!: Uuid",
+                      name,
+                      created_at AS "created_at!: DateTime<Utc>",
+                      updated_at AS "updated_at!: DateTime<Utc>"
+               FROM pipelines
+               WHERE project_id = $1
+               ORDER BY name ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let mut pipelines = Vec::with_capacity(records.len());
+        for record in records {
+            pipelines.push(Pipeline {
+                stages: Self::find_stages(pool, record.id).await?,
+                id: record.id,
+                project_id: record.project_id,
+                name: record.name,
+                created_at: record.created_at,
+                updated_at: record.updated_at,
+            });
+        }
+        Ok(pipelines)
+    }
+
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        let record = sqlx::query!(
+            r#"SELECT id         AS "id!: Uuid",
+                      project_id AS "project_id!: Uuid",
+                      name,
+                      created_at AS "created_at!: DateTime<Utc>",
+                      updated_at AS "updated_at!: DateTime<Utc>"
+               FROM pipelines
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        match record {
+            Some(record) => Ok(Some(Pipeline {
+                stages: Self::find_stages(pool, record.id).await?,
+                id: record.id,
+                project_id: record.project_id,
+                name: record.name,
+                created_at: record.created_at,
+                updated_at: record.updated_at,
+            })),
+            None => Ok(None),
+        }
+    }
+
+    async fn find_stages(
+        pool: &SqlitePool,
+        pipeline_id: Uuid,
+    ) -> Result<Vec<PipelineStage>, sqlx::Error> {
+        sqlx::query_as!(
+            PipelineStage,
+            r#"SELECT name,
+                      executor,
+                      prompt_template,
+                      condition AS "condition!: StageCondition",
+                      check_command
+               FROM pipeline_stages
+               WHERE pipeline_id = $1
+               ORDER BY position ASC"#,
+            pipeline_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    async fn replace_stages(
+        tx: &mut sqlx::SqliteConnection,
+        pipeline_id: Uuid,
+        stages: &[PipelineStage],
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "DELETE FROM pipeline_stages WHERE pipeline_id = $1",
+            pipeline_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        for (position, stage) in stages.iter().enumerate() {
+            let id = Uuid::new_v4();
+            let position = position as i64;
+            sqlx::query!(
+                r#"INSERT INTO pipeline_stages (id, pipeline_id, position, name, executor, prompt_template, condition, check_command)
+                   VALUES ($1, $2, $3, $4, $5, $6, $7, $8)"#,
+                id,
+                pipeline_id,
+                position,
+                stage.name,
+                stage.executor,
+                stage.prompt_template,
+                stage.condition,
+                stage.check_command
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        Ok(())
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        data: &CreatePipeline,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        let mut tx = pool.begin().await?;
+        sqlx::query!(
+            "INSERT INTO pipelines (id, project_id, name) VALUES ($1, $2, $3)",
+            id,
+            project_id,
+            data.name
+        )
+        .execute(&mut *tx)
+        .await?;
+        Self::replace_stages(&mut tx, id, &data.stages).await?;
+        tx.commit().await?;
+
+        Self::find_by_id(pool, id)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)
+    }
+
+    pub async fn update(
+        pool: &SqlitePool,
+        id: Uuid,
+        data: &UpdatePipeline,
+    ) -> Result<Self, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        if let Some(name) = &data.name {
+            sqlx::query!(
+                "UPDATE pipelines SET name = $1, updated_at = datetime('now', 'subsec') WHERE id = $2",
+                name,
+                id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        if let Some(stages) = &data.stages {
+            Self::replace_stages(&mut tx, id, stages).await?;
+            sqlx::query!(
+                "UPDATE pipelines SET updated_at = datetime('now', 'subsec') WHERE id = $1",
+                id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        Self::find_by_id(pool, id)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)
+    }
+
+    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM pipelines WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+impl TaskAttemptStage {
+    /// Schedule a pipeline's stages on an attempt
+    pub async fn create_for_attempt(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+        stages: &[PipelineStage],
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        for (position, stage) in stages.iter().enumerate() {
+            let id = Uuid::new_v4();
+            let position = position as i64;
+            sqlx::query!(
+                r#"INSERT INTO task_attempt_stages (id, task_attempt_id, position, name, executor, prompt_template, condition, check_command)
+                   VALUES ($1, $2, $3, $4, $5, $6, $7, $8)"#,
+                id,
+                task_attempt_id,
+                position,
+                stage.name,
+                stage.executor,
+                stage.prompt_template,
+                stage.condition,
+                stage.check_command
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await
+    }
+
+    pub async fn find_by_task_attempt_id(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskAttemptStage,
+            r#"SELECT id                   AS "id!: Uuid",
+                      task_attempt_id      AS "task_attempt_id!: Uuid",
+                      position,
+                      name,
+                      executor,
+                      prompt_template,
+                      condition            AS "condition!: StageCondition",
+                      check_command,
+                      status               AS "status!: StageStatus",
+                      execution_process_id AS "execution_process_id: Uuid",
+                      created_at           AS "created_at!: DateTime<Utc>",
+                      updated_at           AS "updated_at!: DateTime<Utc>"
+               FROM task_attempt_stages
+               WHERE task_attempt_id = $1
+               ORDER BY position ASC"#,
+            task_attempt_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// The first stage of the attempt that has not run yet
+    pub async fn find_next_pending(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskAttemptStage,
+            r#"SELECT id                   AS "id!: Uuid",
+                      task_attempt_id      AS "task_attempt_id!: Uuid",
+                      position,
+                      name,
+                      executor,
+                      prompt_template,
+                      condition            AS "condition!: StageCondition",
+                      check_command,
+                      status               AS "status!: StageStatus",
+                      execution_process_id AS "execution_process_id: Uuid",
+                      created_at           AS "created_at!: DateTime<Utc>",
+                      updated_at           AS "updated_at!: DateTime<Utc>"
+               FROM task_attempt_stages
+               WHERE task_attempt_id = $1 AND status = 'pending'
+               ORDER BY position ASC
+               LIMIT 1"#,
+            task_attempt_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn find_by_execution_process_id(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskAttemptStage,
+            r#"SELECT id                   AS "id!: Uuid",
+                      task_attempt_id      AS "task_attempt_id!: Uuid",
+                      position,
+                      name,
+                      executor,
+                      prompt_template,
+                      condition            AS "condition!: StageCondition",
+                      check_command,
+                      status               AS "status!: StageStatus",
+                      execution_process_id AS "execution_process_id: Uuid",
+                      created_at           AS "created_at!: DateTime<Utc>",
+                      updated_at           AS "updated_at!: DateTime<Utc>"
+               FROM task_attempt_stages
+               WHERE execution_process_id = $1"#,
+            execution_process_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn update_status(
+        pool: &SqlitePool,
+        id: Uuid,
+        status: StageStatus,
+        execution_process_id: Option<Uuid>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE task_attempt_stages
+               SET status = $1,
+                   execution_process_id = COALESCE($2, execution_process_id),
+                   updated_at = datetime('now', 'subsec')
+               WHERE id = $3"#,
+            status,
+            execution_process_id,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stage(condition: StageCondition, check_command: Option<&str>) -> PipelineStage {
+        PipelineStage {
+            name: "Review".to_string(),
+            executor: "codex".to_string(),
+            prompt_template: "Review the changes".to_string(),
+            condition,
+            check_command: check_command.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn validate_stages_requires_a_check_command_for_check_fails() {
+        assert!(validate_stages(&[]).is_err());
+        assert!(validate_stages(&[stage(StageCondition::CheckFails, None)]).is_err());
+        assert!(validate_stages(&[stage(StageCondition::CheckFails, Some("cargo test"))]).is_ok());
+
+        let mut unknown = stage(StageCondition::Always, None);
+        unknown.executor = "nonexistent".to_string();
+        assert!(validate_stages(&[unknown]).is_err());
+    }
+
+    #[test]
+    fn render_stage_prompt_fills_placeholders_and_appends_context() {
+        let prompt = render_stage_prompt(
+            "Implement {task_title}: {task_description}",
+            "Add login",
+            Some("Use OAuth"),
+            None,
+            "",
+        );
+        assert_eq!(prompt, "Implement Add login: Use OAuth");
+
+        let prompt = render_stage_prompt(
+            "Review the changes",
+            "Add login",
+            None,
+            Some("Implemented the form"),
+            "+fn login() {}",
+        );
+        assert!(prompt.starts_with("Task: Add login\n\nReview the changes"));
+        assert!(prompt.contains("## Previous stage summary\n\nImplemented the form"));
+        assert!(prompt.contains("```diff\n+fn login() {}\n```"));
+    }
+}
@@ -1,10 +1,36 @@
 use chrono::{DateTime, Utc};
 use git2::{BranchType, Repository};
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, SqlitePool};
+use sqlx::{FromRow, SqlitePool, Type};
 use ts_rs::TS;
 use uuid::Uuid;
 
+use super::config::{ExecutionLimits, ResourceLimits};
+
+/// Whether a project's processes run inside a Linux namespace sandbox
+#[derive(Debug, Clone, Copy, Default, Type, Serialize, Deserialize, PartialEq, TS)]
+#[sqlx(type_name = "sandbox_mode", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum SandboxMode {
+    #[default]
+    Disabled,
+    /// Worktree writable, toolchain and agent config read-only, everything else hidden
+    Enabled,
+    /// As `Enabled`, without network access
+    Offline,
+}
+
+impl SandboxMode {
+    /// The sandbox is built on Linux namespaces, so other platforms can only run unconfined
+    pub fn check_supported(self) -> Result<(), String> {
+        if self != SandboxMode::Disabled && !cfg!(target_os = "linux") {
+            return Err("Sandboxed execution is only available on Linux".to_string());
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
 #[ts(export)]
 pub struct Project {
@@ -14,6 +40,13 @@ pub struct Project {
     pub setup_script: Option<String>,
     pub dev_script: Option<String>,
     pub cleanup_script: Option<String>,
+    pub max_runtime_seconds: Option<i64>, // Stop processes running longer than this
+    pub idle_timeout_seconds: Option<i64>, // Stop processes silent for longer than this
+    pub prompt_template: Option<String>,  // Agent prompt template; None uses the built-in one
+    pub sandbox_mode: SandboxMode,
+    pub memory_limit_mb: Option<i64>, // Bounds each execution's process group
+    pub cpu_limit_percent: Option<i64>, // 100 is one full core
+    pub max_processes: Option<i64>,
 
     #[ts(type = "Date")]
     pub created_at: DateTime<Utc>,
@@ -30,6 +63,13 @@ pub struct CreateProject {
     pub setup_script: Option<String>,
     pub dev_script: Option<String>,
     pub cleanup_script: Option<String>,
+    pub max_runtime_seconds: Option<i64>,
+    pub idle_timeout_seconds: Option<i64>,
+    pub prompt_template: Option<String>,
+    pub sandbox_mode: Option<SandboxMode>,
+    pub memory_limit_mb: Option<i64>,
+    pub cpu_limit_percent: Option<i64>,
+    pub max_processes: Option<i64>,
 }
 
 #[derive(Debug, Deserialize, TS)]
@@ -40,6 +80,13 @@ pub struct UpdateProject {
     pub setup_script: Option<String>,
     pub dev_script: Option<String>,
     pub cleanup_script: Option<String>,
+    pub max_runtime_seconds: Option<i64>,
+    pub idle_timeout_seconds: Option<i64>,
+    pub prompt_template: Option<String>,
+    pub sandbox_mode: Option<SandboxMode>,
+    pub memory_limit_mb: Option<i64>,
+    pub cpu_limit_percent: Option<i64>,
+    pub max_processes: Option<i64>,
 }
 
 #[derive(Debug, Deserialize, TS)]
@@ -51,6 +98,13 @@ pub struct CreateProjectFromGitHub {
     pub setup_script: Option<String>,
     pub dev_script: Option<String>,
     pub cleanup_script: Option<String>,
+    pub max_runtime_seconds: Option<i64>,
+    pub idle_timeout_seconds: Option<i64>,
+    pub prompt_template: Option<String>,
+    pub sandbox_mode: Option<SandboxMode>,
+    pub memory_limit_mb: Option<i64>,
+    pub cpu_limit_percent: Option<i64>,
+    pub max_processes: Option<i64>,
 }
 
 #[derive(Debug, Serialize, TS)]
@@ -62,6 +116,13 @@ pub struct ProjectWithBranch {
     pub setup_script: Option<String>,
     pub dev_script: Option<String>,
     pub cleanup_script: Option<String>,
+    pub max_runtime_seconds: Option<i64>,
+    pub idle_timeout_seconds: Option<i64>,
+    pub prompt_template: Option<String>,
+    pub sandbox_mode: SandboxMode,
+    pub memory_limit_mb: Option<i64>,
+    pub cpu_limit_percent: Option<i64>,
+    pub max_processes: Option<i64>,
     pub current_branch: Option<String>,
 
     #[ts(type = "Date")]
@@ -107,7 +168,7 @@ impl Project {
     pub async fn find_all(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
         sqlx::query_as!(
             Project,
-            r#"SELECT id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>" FROM projects ORDER BY created_at DESC"#
+            r#"SELECT id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, max_runtime_seconds, idle_timeout_seconds, prompt_template, sandbox_mode as "sandbox_mode!: SandboxMode", memory_limit_mb, cpu_limit_percent, max_processes, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>" FROM projects ORDER BY created_at DESC"#
         )
         .fetch_all(pool)
         .await
@@ -116,7 +177,7 @@ impl Project {
     pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
         sqlx::query_as!(
             Project,
-            r#"SELECT id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>" FROM projects WHERE id = $1"#,
+            r#"SELECT id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, max_runtime_seconds, idle_timeout_seconds, prompt_template, sandbox_mode as "sandbox_mode!: SandboxMode", memory_limit_mb, cpu_limit_percent, max_processes, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>" FROM projects WHERE id = $1"#,
             id
         )
         .fetch_optional(pool)
@@ -129,7 +190,7 @@ impl Project {
     ) -> Result<Option<Self>, sqlx::Error> {
         sqlx::query_as!(
             Project,
-            r#"SELECT id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>" FROM projects WHERE git_repo_path = $1"#,
+            r#"SELECT id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, max_runtime_seconds, idle_timeout_seconds, prompt_template, sandbox_mode as "sandbox_mode!: SandboxMode", memory_limit_mb, cpu_limit_percent, max_processes, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>" FROM projects WHERE git_repo_path = $1"#,
             git_repo_path
         )
         .fetch_optional(pool)
@@ -143,7 +204,7 @@ impl Project {
     ) -> Result<Option<Self>, sqlx::Error> {
         sqlx::query_as!(
             Project,
-            r#"SELECT id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>" FROM projects WHERE git_repo_path = $1 AND id != $2"#,
+            r#"SELECT id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, max_runtime_seconds, idle_timeout_seconds, prompt_template, sandbox_mode as "sandbox_mode!: SandboxMode", memory_limit_mb, cpu_limit_percent, max_processes, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>" FROM projects WHERE git_repo_path = $1 AND id != $2"#,
             git_repo_path,
             exclude_id
         )
@@ -156,20 +217,29 @@ impl Project {
         data: &CreateProject,
         project_id: Uuid,
     ) -> Result<Self, sqlx::Error> {
+        let sandbox_mode = data.sandbox_mode.unwrap_or_default();
         sqlx::query_as!(
             Project,
-            r#"INSERT INTO projects (id, name, git_repo_path, setup_script, dev_script, cleanup_script) VALUES ($1, $2, $3, $4, $5, $6) RETURNING id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            r#"INSERT INTO projects (id, name, git_repo_path, setup_script, dev_script, cleanup_script, max_runtime_seconds, idle_timeout_seconds, prompt_template, sandbox_mode, memory_limit_mb, cpu_limit_percent, max_processes) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13) RETURNING id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, max_runtime_seconds, idle_timeout_seconds, prompt_template, sandbox_mode as "sandbox_mode!: SandboxMode", memory_limit_mb, cpu_limit_percent, max_processes, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
             project_id,
             data.name,
             data.git_repo_path,
             data.setup_script,
             data.dev_script,
-            data.cleanup_script
+            data.cleanup_script,
+            data.max_runtime_seconds,
+            data.idle_timeout_seconds,
+            data.prompt_template,
+            sandbox_mode,
+            data.memory_limit_mb,
+            data.cpu_limit_percent,
+            data.max_processes
         )
         .fetch_one(pool)
         .await
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub async fn update(
         pool: &SqlitePool,
         id: Uuid,
@@ -178,16 +248,27 @@ impl Project {
         setup_script: Option<String>,
         dev_script: Option<String>,
         cleanup_script: Option<String>,
+        limits: ExecutionLimits,
+        prompt_template: Option<String>,
+        sandbox_mode: SandboxMode,
+        resource_limits: ResourceLimits,
     ) -> Result<Self, sqlx::Error> {
         sqlx::query_as!(
             Project,
-            r#"UPDATE projects SET name = $2, git_repo_path = $3, setup_script = $4, dev_script = $5, cleanup_script = $6 WHERE id = $1 RETURNING id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            r#"UPDATE projects SET name = $2, git_repo_path = $3, setup_script = $4, dev_script = $5, cleanup_script = $6, max_runtime_seconds = $7, idle_timeout_seconds = $8, prompt_template = $9, sandbox_mode = $10, memory_limit_mb = $11, cpu_limit_percent = $12, max_processes = $13 WHERE id = $1 RETURNING id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, max_runtime_seconds, idle_timeout_seconds, prompt_template, sandbox_mode as "sandbox_mode!: SandboxMode", memory_limit_mb, cpu_limit_percent, max_processes, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
             id,
             name,
             git_repo_path,
             setup_script,
             dev_script,
-            cleanup_script
+            cleanup_script,
+            limits.max_runtime_seconds,
+            limits.idle_timeout_seconds,
+            prompt_template,
+            sandbox_mode,
+            resource_limits.memory_limit_mb,
+            resource_limits.cpu_limit_percent,
+            resource_limits.max_processes
         )
         .fetch_one(pool)
         .await
@@ -215,6 +296,23 @@ impl Project {
         Ok(result.count > 0)
     }
 
+    /// Runtime and idle limits configured for this project's processes
+    pub fn execution_limits(&self) -> ExecutionLimits {
+        ExecutionLimits {
+            max_runtime_seconds: self.max_runtime_seconds,
+            idle_timeout_seconds: self.idle_timeout_seconds,
+        }
+    }
+
+    /// Memory, CPU and process-count limits configured for this project's processes
+    pub fn resource_limits(&self) -> ResourceLimits {
+        ResourceLimits {
+            memory_limit_mb: self.memory_limit_mb,
+            cpu_limit_percent: self.cpu_limit_percent,
+            max_processes: self.max_processes,
+        }
+    }
+
     pub fn get_current_branch(&self) -> Result<String, git2::Error> {
         let repo = Repository::open(&self.git_repo_path)?;
         let head = repo.head()?;
@@ -236,6 +334,13 @@ impl Project {
             setup_script: self.setup_script,
             dev_script: self.dev_script,
             cleanup_script: self.cleanup_script,
+            max_runtime_seconds: self.max_runtime_seconds,
+            idle_timeout_seconds: self.idle_timeout_seconds,
+            prompt_template: self.prompt_template,
+            sandbox_mode: self.sandbox_mode,
+            memory_limit_mb: self.memory_limit_mb,
+            cpu_limit_percent: self.cpu_limit_percent,
+            max_processes: self.max_processes,
             current_branch,
             created_at: self.created_at,
             updated_at: self.updated_at,
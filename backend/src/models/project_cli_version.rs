@@ -0,0 +1,115 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// An agent CLI version pinned for a project, so a broken latest release can be sidestepped
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ProjectCliVersion {
+    pub project_id: Uuid,
+    pub executor: String, // Executor id, e.g. "claude" or "gemini"
+    pub version: String,  // npm version or dist-tag of the executor's CLI package
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export)]
+pub struct SetProjectCliVersion {
+    pub version: String,
+}
+
+/// Whether `version` is safe to splice into an npx package spec
+pub fn is_valid_version(version: &str) -> bool {
+    !version.is_empty()
+        && version
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+' | '_'))
+}
+
+impl ProjectCliVersion {
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ProjectCliVersion,
+            r#"SELECT project_id as "project_id!: Uuid", executor, version, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+               FROM project_cli_versions
+               WHERE project_id = $1
+               ORDER BY executor ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// The version pinned for `executor` in the project, if any
+    pub async fn find_version(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        executor: &str,
+    ) -> Result<Option<String>, sqlx::Error> {
+        let row = sqlx::query!(
+            r#"SELECT version FROM project_cli_versions WHERE project_id = $1 AND executor = $2"#,
+            project_id,
+            executor
+        )
+        .fetch_optional(pool)
+        .await?;
+        Ok(row.map(|row| row.version))
+    }
+
+    pub async fn set(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        executor: &str,
+        version: &str,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            ProjectCliVersion,
+            r#"INSERT INTO project_cli_versions (project_id, executor, version)
+               VALUES ($1, $2, $3)
+               ON CONFLICT (project_id, executor) DO UPDATE
+               SET version = excluded.version, updated_at = datetime('now', 'subsec')
+               RETURNING project_id as "project_id!: Uuid", executor, version, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            project_id,
+            executor,
+            version
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        executor: &str,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM project_cli_versions WHERE project_id = $1 AND executor = $2",
+            project_id,
+            executor
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_valid_version() {
+        assert!(is_valid_version("1.0.43"));
+        assert!(is_valid_version("0.0.1752148945-gd8844f"));
+        assert!(is_valid_version("next"));
+        assert!(!is_valid_version(""));
+        assert!(!is_valid_version("1.0; rm -rf ~"));
+        assert!(!is_valid_version("latest --foo"));
+    }
+}
@@ -0,0 +1,397 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::{
+    persona::{PersonaBrief, ProjectPersona},
+    project::Project,
+    task::Task,
+    task_attachment::{render_attachments, AttachmentDelivery, TaskAttachment},
+    task_attempt::TaskAttempt,
+};
+use crate::executors::planning::find_latest_plan;
+
+/// The prompt agents have always been started with, used when a project sets no template
+pub const DEFAULT_PROMPT_TEMPLATE: &str =
+    "project_id: {project_id}\n\nTask title: {task_title}\nTask description: {task_description}";
+
+/// Repository files whose contents fill `{conventions}`, read from the worktree root
+const CONVENTION_FILES: &[&str] = &["AGENT.md", "AGENTS.md", "CLAUDE.md"];
+
+/// Placeholders a template may use
+pub const PROMPT_VARIABLES: &[&str] = &[
+    "project_id",
+    "task_title",
+    "task_description",
+    "parent_plan",
+    "persona_instructions",
+    "branch",
+    "base_branch",
+    "conventions",
+    "attachments",
+    "follow_up",
+];
+
+/// Everything a prompt template can refer to for one attempt
+#[derive(Debug, Clone, Default)]
+pub struct PromptContext {
+    pub project_id: Uuid,
+    pub task_title: String,
+    pub task_description: Option<String>,
+    pub parent_plan: Option<String>,
+    pub persona: Option<PersonaBrief>,
+    pub branch: Option<String>,
+    pub base_branch: Option<String>,
+    pub conventions: Option<String>,
+    pub attachments: Vec<TaskAttachment>,
+    pub attachment_delivery: AttachmentDelivery,
+}
+
+#[derive(Debug, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct PromptPreview {
+    pub prompt: String,
+    pub uses_default_template: bool,
+}
+
+impl PromptContext {
+    /// Gather the context for a task's run in `worktree_path`
+    ///
+    /// The branch variables come from the attempt that owns the worktree, if there is one.
+    pub async fn load(
+        pool: &SqlitePool,
+        task: &Task,
+        worktree_path: &str,
+    ) -> Result<Self, sqlx::Error> {
+        let attempt = TaskAttempt::find_by_task_id(pool, task.id)
+            .await?
+            .into_iter()
+            .find(|attempt| attempt.worktree_path == worktree_path);
+
+        let parent_plan = match task.parent_task_attempt {
+            Some(parent_attempt_id) => find_latest_plan(pool, parent_attempt_id).await?,
+            None => None,
+        };
+        let persona = match task.assigned_persona_id {
+            Some(persona_id) => ProjectPersona::find_brief(pool, persona_id).await?,
+            None => None,
+        };
+        let attachments = TaskAttachment::find_by_task_id(pool, task.id).await?;
+
+        Ok(Self {
+            project_id: task.project_id,
+            task_title: task.title.clone(),
+            task_description: task.description.clone(),
+            parent_plan,
+            persona,
+            branch: attempt.as_ref().map(|attempt| attempt.branch.clone()),
+            base_branch: attempt.map(|attempt| attempt.base_branch),
+            conventions: read_conventions(Path::new(worktree_path)),
+            attachments,
+            attachment_delivery: AttachmentDelivery::default(),
+        })
+    }
+
+    fn value(&self, variable: &str, follow_up: Option<&str>) -> Option<String> {
+        match variable {
+            "project_id" => Some(self.project_id.to_string()),
+            "task_title" => Some(self.task_title.clone()),
+            "task_description" => self.task_description.clone(),
+            "parent_plan" => self.parent_plan.clone(),
+            "persona_instructions" => self.persona.as_ref().map(PersonaBrief::render),
+            "branch" => self.branch.clone(),
+            "base_branch" => self.base_branch.clone(),
+            "conventions" => self.conventions.clone(),
+            "attachments" => render_attachments(&self.attachments, self.attachment_delivery),
+            "follow_up" => follow_up.map(str::to_string),
+            _ => None,
+        }
+    }
+}
+
+/// Concatenate the convention files present in a worktree, each under its file name
+fn read_conventions(worktree: &Path) -> Option<String> {
+    let sections: Vec<String> = CONVENTION_FILES
+        .iter()
+        .filter_map(|name| {
+            let content = std::fs::read_to_string(worktree.join(name)).ok()?;
+            let content = content.trim();
+            (!content.is_empty()).then(|| format!("## {}\n\n{}", name, content))
+        })
+        .collect();
+    (!sections.is_empty()).then(|| sections.join("\n\n"))
+}
+
+/// Whether the text between braces names a placeholder, known or not
+fn is_placeholder_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// The `{name}` placeholders in a piece of template text, in order
+fn placeholders(text: &str) -> Vec<&str> {
+    let mut found = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('{') {
+        rest = &rest[start + 1..];
+        let Some(end) = rest.find('}') else {
+            break;
+        };
+        let name = &rest[..end];
+        if is_placeholder_name(name) {
+            found.push(name);
+            rest = &rest[end + 1..];
+        }
+    }
+    found
+}
+
+/// Reject templates that refer to placeholders nothing fills
+pub fn validate_template(template: &str) -> Result<(), String> {
+    if template.trim().is_empty() {
+        return Err("Prompt template cannot be empty".to_string());
+    }
+    match placeholders(template)
+        .into_iter()
+        .find(|name| !PROMPT_VARIABLES.contains(name))
+    {
+        Some(unknown) => Err(format!(
+            "Unknown prompt template variable {{{}}}; available: {}",
+            unknown,
+            PROMPT_VARIABLES.join(", ")
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Fill a template's placeholders
+///
+/// Substitution is a single pass over the template, so braces inside a filled-in value (a task
+/// description quoting `{branch}`, say) are left as written. Lines whose placeholders all come
+/// out empty are dropped, so optional sections such as `Parent plan: {parent_plan}` vanish when
+/// there is nothing to show.
+pub fn render_prompt(template: &str, context: &PromptContext, follow_up: Option<&str>) -> String {
+    let lines: Vec<String> = template
+        .split('\n')
+        .filter_map(|line| {
+            let mut rendered = String::with_capacity(line.len());
+            let mut has_variable = false;
+            let mut any_filled = false;
+            let mut rest = line;
+            while let Some(start) = rest.find('{') {
+                rendered.push_str(&rest[..start]);
+                rest = &rest[start + 1..];
+                let name = rest.find('}').map(|end| &rest[..end]);
+                match name {
+                    Some(name) if PROMPT_VARIABLES.contains(&name) => {
+                        let value = context.value(name, follow_up).unwrap_or_default();
+                        has_variable = true;
+                        any_filled |= !value.trim().is_empty();
+                        rendered.push_str(value.trim_end());
+                        rest = &rest[name.len() + 1..];
+                    }
+                    // Unknown placeholders are kept as written, and keep their line
+                    Some(name) if is_placeholder_name(name) => {
+                        any_filled = true;
+                        rendered.push('{');
+                    }
+                    _ => rendered.push('{'),
+                }
+            }
+            rendered.push_str(rest);
+            (!has_variable || any_filled).then_some(rendered)
+        })
+        .collect();
+    lines.join("\n").trim().to_string()
+}
+
+/// Put the persona brief ahead of a prompt whose template didn't place `{persona_instructions}`
+fn with_persona(template: &str, context: &PromptContext, prompt: String) -> String {
+    match &context.persona {
+        Some(persona) if !placeholders(template).contains(&"persona_instructions") => {
+            format!("{}\n\n{}", persona.render(), prompt)
+        }
+        _ => prompt,
+    }
+}
+
+/// The prompt a project's agents start a task with
+///
+/// Attachments are listed at the end unless the template places `{attachments}` itself.
+pub fn task_prompt(project: &Project, context: &PromptContext) -> String {
+    let template = project
+        .prompt_template
+        .as_deref()
+        .unwrap_or(DEFAULT_PROMPT_TEMPLATE);
+    let prompt = render_prompt(template, context, None);
+    let prompt = match render_attachments(&context.attachments, context.attachment_delivery) {
+        Some(attachments) if !placeholders(template).contains(&"attachments") => {
+            format!("{}\n\n{}", prompt, attachments)
+        }
+        _ => prompt,
+    };
+    with_persona(template, context, prompt)
+}
+
+/// The message sent to a project's agent for a follow-up
+///
+/// Without a project template the message goes through unchanged, as before templates existed.
+/// Templates that don't place `{follow_up}` get the message appended. Either way an assigned
+/// persona's brief comes first, so the agent keeps acting as that persona.
+pub fn follow_up_prompt(project: &Project, context: &PromptContext, message: &str) -> String {
+    let Some(template) = project.prompt_template.as_deref() else {
+        return with_persona("", context, message.to_string());
+    };
+    let prompt = render_prompt(template, context, Some(message));
+    let prompt = if placeholders(template).contains(&"follow_up") {
+        prompt
+    } else {
+        format!("{}\n\n{}", prompt, message)
+    };
+    with_persona(template, context, prompt)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context() -> PromptContext {
+        PromptContext {
+            task_title: "Add login".to_string(),
+            task_description: Some("Use OAuth".to_string()),
+            branch: Some("vk-add-login".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn render_prompt_fills_placeholders_and_drops_empty_lines() {
+        let prompt = render_prompt(
+            "Task: {task_title}\n{task_description}\nPlan: {parent_plan}\nBranch: {branch}\nBase: {base_branch}",
+            &context(),
+            None,
+        );
+        assert_eq!(prompt, "Task: Add login\nUse OAuth\nBranch: vk-add-login");
+
+        let prompt = render_prompt(DEFAULT_PROMPT_TEMPLATE, &context(), None);
+        assert!(prompt.ends_with("Task title: Add login\nTask description: Use OAuth"));
+    }
+
+    #[test]
+    fn persona_brief_leads_prompts_unless_the_template_places_it() {
+        let project: Project = serde_json::from_value(serde_json::json!({
+            "id": Uuid::nil(),
+            "name": "Demo",
+            "git_repo_path": "/tmp/demo",
+            "setup_script": null,
+            "dev_script": null,
+            "cleanup_script": null,
+            "max_runtime_seconds": null,
+            "idle_timeout_seconds": null,
+            "prompt_template": null,
+            "sandbox_mode": "disabled",
+            "memory_limit_mb": null,
+            "cpu_limit_percent": null,
+            "max_processes": null,
+            "created_at": "2025-07-31T00:00:00Z",
+            "updated_at": "2025-07-31T00:00:00Z",
+        }))
+        .unwrap();
+        let context = PromptContext {
+            persona: Some(PersonaBrief {
+                id: Uuid::nil(),
+                name: "@Architect".to_string(),
+                instructions: "Design the system.".to_string(),
+                capabilities: vec!["architecture_design".to_string()],
+                tool_restrictions: vec!["Bash".to_string()],
+            }),
+            ..context()
+        };
+
+        let prompt = task_prompt(&project, &context);
+        assert!(prompt.starts_with("You are acting as @Architect.\n\nDesign the system."));
+        assert!(prompt.contains("Your capabilities: architecture_design"));
+        assert!(prompt.contains("Do not use these tools: Bash"));
+        assert!(prompt.ends_with("Task description: Use OAuth"));
+
+        let follow_up = follow_up_prompt(&project, &context, "Add tests");
+        assert!(follow_up.starts_with("You are acting as @Architect."));
+        assert!(follow_up.ends_with("Add tests"));
+
+        let placed = render_prompt("{task_title}\n{persona_instructions}", &context, None);
+        assert!(placed.starts_with("Add login\nYou are acting as @Architect."));
+    }
+
+    #[test]
+    fn attachments_are_listed_unless_the_template_places_them() {
+        let mut project: Project = serde_json::from_value(serde_json::json!({
+            "id": Uuid::nil(),
+            "name": "Demo",
+            "git_repo_path": "/tmp/demo",
+            "setup_script": null,
+            "dev_script": null,
+            "cleanup_script": null,
+            "max_runtime_seconds": null,
+            "idle_timeout_seconds": null,
+            "prompt_template": null,
+            "sandbox_mode": "disabled",
+            "memory_limit_mb": null,
+            "cpu_limit_percent": null,
+            "max_processes": null,
+            "created_at": "2025-08-01T00:00:00Z",
+            "updated_at": "2025-08-01T00:00:00Z",
+        }))
+        .unwrap();
+        let context = PromptContext {
+            attachments: vec![serde_json::from_value(serde_json::json!({
+                "id": Uuid::nil(),
+                "task_id": Uuid::nil(),
+                "kind": "repo_path",
+                "name": "src/lib.rs",
+                "path": "src/lib.rs",
+                "content_type": null,
+                "size_bytes": null,
+                "created_at": "2025-08-01T00:00:00Z",
+            }))
+            .unwrap()],
+            attachment_delivery: AttachmentDelivery::Mentions,
+            ..context()
+        };
+
+        assert!(task_prompt(&project, &context)
+            .ends_with("Use OAuth\n\nAttachments:\n- src/lib.rs (repository path): @src/lib.rs"));
+
+        project.prompt_template = Some("{attachments}\n\n{task_title}".to_string());
+        assert!(task_prompt(&project, &context).ends_with("@src/lib.rs\n\nAdd login"));
+    }
+
+    #[test]
+    fn validate_template_rejects_unknown_placeholders() {
+        assert!(validate_template("Do {task_title} per {conventions}").is_ok());
+        assert!(validate_template("Return JSON like {\"ok\": true}").is_ok());
+        assert!(validate_template("Do {task_name}").is_err());
+        assert!(validate_template("  ").is_err());
+    }
+
+    #[test]
+    fn render_prompt_leaves_placeholders_inside_values_alone() {
+        let context = PromptContext {
+            task_description: Some("Print {branch} and {follow_up} verbatim".to_string()),
+            ..context()
+        };
+        let prompt = render_prompt(
+            "{task_description}\nOn {branch}\n{follow_up}",
+            &context,
+            Some("Also {task_title}"),
+        );
+        assert_eq!(
+            prompt,
+            "Print {branch} and {follow_up} verbatim\nOn vk-add-login\nAlso {task_title}"
+        );
+    }
+}
@@ -0,0 +1,270 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::task_attempt::{DiffChunkType, WorktreeDiff};
+
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, TS)]
+#[sqlx(type_name = "review_severity", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+#[ts(export)]
+pub enum ReviewSeverity {
+    Info,
+    Warning,
+    Error,
+}
+
+/// A problem reported by a review run
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ReviewFinding {
+    pub id: Uuid,
+    pub task_attempt_id: Uuid,
+    pub execution_process_id: Uuid,
+    pub file_path: String,
+    pub start_line: Option<i64>,
+    pub end_line: Option<i64>,
+    pub severity: ReviewSeverity,
+    pub message: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A finding as reported by the reviewer, before it is stored
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct ReportedFinding {
+    pub file: String,
+    pub start_line: Option<i64>,
+    pub end_line: Option<i64>,
+    #[serde(default = "default_severity")]
+    pub severity: ReviewSeverity,
+    pub message: String,
+}
+
+fn default_severity() -> ReviewSeverity {
+    ReviewSeverity::Warning
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export)]
+pub struct StartReviewRequest {
+    pub executor: Option<String>, // Defaults to the configured review executor
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export)]
+pub struct ReviewFollowUpRequest {
+    pub finding_ids: Vec<Uuid>,
+}
+
+/// Prompt asking the reviewer to check `diff` against the task and answer with JSON findings
+pub fn review_prompt(task_title: &str, task_description: Option<&str>, diff: &str) -> String {
+    format!(
+        r#"You are reviewing changes made by another agent for this task.
+
+Task: {}
+
+{}
+
+Review the diff below for bugs, missed requirements, security problems and obvious mistakes. Do not modify any files. Line numbers refer to the current version of each file in the working directory.
+
+```diff
+{}
+```
+
+Reply with your findings as your final message, as a JSON array in a ```json code block. Each finding is an object with "file", "start_line", "end_line", "severity" ("info", "warning" or "error") and "message". Reply with an empty array if you found nothing worth fixing."#,
+        task_title,
+        task_description.unwrap_or(""),
+        diff
+    )
+}
+
+/// Render a worktree diff as a plain unified-style patch for the reviewer
+pub fn render_diff(diff: &WorktreeDiff) -> String {
+    let mut rendered = String::new();
+    for file in &diff.files {
+        rendered.push_str(&format!("--- a/{}\n+++ b/{}\n", file.path, file.path));
+        for chunk in &file.chunks {
+            let prefix = match chunk.chunk_type {
+                DiffChunkType::Equal => ' ',
+                DiffChunkType::Insert => '+',
+                DiffChunkType::Delete => '-',
+            };
+            for line in chunk.content.lines() {
+                rendered.push(prefix);
+                rendered.push_str(line);
+                rendered.push('\n');
+            }
+        }
+    }
+    rendered
+}
+
+/// Parse the findings out of a reviewer's final message
+///
+/// Accepts a fenced ```json block or a bare JSON array; anything unparseable yields no
+/// findings.
+pub fn parse_findings(message: &str) -> Vec<ReportedFinding> {
+    let json = match message.find("```json") {
+        Some(start) => {
+            let body = &message[start + "```json".len()..];
+            body.find("```").map(|end| &body[..end]).unwrap_or(body)
+        }
+        None => match (message.find('['), message.rfind(']')) {
+            (Some(start), Some(end)) if start < end => &message[start..=end],
+            _ => return Vec::new(),
+        },
+    };
+
+    serde_json::from_str(json.trim()).unwrap_or_else(|e| {
+        tracing::warn!("Failed to parse review findings: {}", e);
+        Vec::new()
+    })
+}
+
+/// Follow-up prompt asking the original agent to address `findings`
+pub fn followup_prompt(findings: &[ReviewFinding]) -> String {
+    let mut prompt =
+        "A code review of your changes found the following issues. Please address them:\n"
+            .to_string();
+    for finding in findings {
+        let location = match (finding.start_line, finding.end_line) {
+            (Some(start), Some(end)) if end > start => {
+                format!("{}:{}-{}", finding.file_path, start, end)
+            }
+            (Some(start), _) => format!("{}:{}", finding.file_path, start),
+            _ => finding.file_path.clone(),
+        };
+        let severity = match finding.severity {
+            ReviewSeverity::Info => "info",
+            ReviewSeverity::Warning => "warning",
+            ReviewSeverity::Error => "error",
+        };
+        prompt.push_str(&format!(
+            "\n- [{}] {}: {}",
+            severity, location, finding.message
+        ));
+    }
+    prompt
+}
+
+impl ReviewFinding {
+    pub async fn create_many(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+        execution_process_id: Uuid,
+        findings: &[ReportedFinding],
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        for finding in findings {
+            let id = Uuid::new_v4();
+            sqlx::query!(
+                r#"INSERT INTO review_findings (id, task_attempt_id, execution_process_id, file_path, start_line, end_line, severity, message)
+                   VALUES ($1, $2, $3, $4, $5, $6, $7, $8)"#,
+                id,
+                task_attempt_id,
+                execution_process_id,
+                finding.file,
+                finding.start_line,
+                finding.end_line,
+                finding.severity,
+                finding.message
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await
+    }
+
+    /// Findings of the attempt's most recent review run
+    pub async fn find_latest_by_task_attempt_id(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ReviewFinding,
+            r#"SELECT rf.id                   AS "id!: Uuid",
+                      rf.task_attempt_id      AS "task_attempt_id!: Uuid",
+                      rf.execution_process_id AS "execution_process_id!: Uuid",
+                      rf.file_path,
+                      rf.start_line,
+                      rf.end_line,
+                      rf.severity             AS "severity!: ReviewSeverity",
+                      rf.message,
+                      rf.created_at           AS "created_at!: DateTime<Utc>"
+               FROM review_findings rf
+               WHERE rf.task_attempt_id = $1
+                 AND rf.execution_process_id = (
+                     SELECT ep.id FROM execution_processes ep
+                     WHERE ep.task_attempt_id = $1 AND ep.process_type = 'reviewagent'
+                     ORDER BY ep.created_at DESC
+                     LIMIT 1
+                 )
+               ORDER BY rf.file_path ASC, rf.start_line ASC"#,
+            task_attempt_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_task_attempt_id(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ReviewFinding,
+            r#"SELECT id                   AS "id!: Uuid",
+                      task_attempt_id      AS "task_attempt_id!: Uuid",
+                      execution_process_id AS "execution_process_id!: Uuid",
+                      file_path,
+                      start_line,
+                      end_line,
+                      severity             AS "severity!: ReviewSeverity",
+                      message,
+                      created_at           AS "created_at!: DateTime<Utc>"
+               FROM review_findings
+               WHERE task_attempt_id = $1
+               ORDER BY created_at ASC"#,
+            task_attempt_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_findings_reads_fenced_json() {
+        let message = r#"I found one problem.
+
+```json
+[{"file": "src/lib.rs", "start_line": 3, "end_line": 5, "severity": "error", "message": "Unchecked unwrap"}]
+```"#;
+
+        assert_eq!(
+            parse_findings(message),
+            vec![ReportedFinding {
+                file: "src/lib.rs".to_string(),
+                start_line: Some(3),
+                end_line: Some(5),
+                severity: ReviewSeverity::Error,
+                message: "Unchecked unwrap".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn parse_findings_defaults_severity_and_tolerates_garbage() {
+        let findings = parse_findings(r#"[{"file": "a.rs", "message": "Typo"}]"#);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].severity, ReviewSeverity::Warning);
+        assert_eq!(findings[0].start_line, None);
+
+        assert!(parse_findings("Looks good to me!").is_empty());
+        assert!(parse_findings("```json\nnot json\n```").is_empty());
+    }
+}
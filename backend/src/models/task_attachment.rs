@@ -0,0 +1,446 @@
+use std::path::{Component, Path};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use crate::utils::attachments_dir;
+
+/// Where attachments are copied inside a worktree, relative to its root
+pub const STAGING_DIR: &str = ".vibe-kanban/attachments";
+
+#[derive(Debug)]
+pub enum TaskAttachmentError {
+    Database(sqlx::Error),
+    Io(std::io::Error),
+}
+
+impl std::fmt::Display for TaskAttachmentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TaskAttachmentError::Database(e) => write!(f, "Database error: {}", e),
+            TaskAttachmentError::Io(e) => write!(f, "File error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for TaskAttachmentError {}
+
+impl From<sqlx::Error> for TaskAttachmentError {
+    fn from(err: sqlx::Error) -> Self {
+        TaskAttachmentError::Database(err)
+    }
+}
+
+impl From<std::io::Error> for TaskAttachmentError {
+    fn from(err: std::io::Error) -> Self {
+        TaskAttachmentError::Io(err)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, TS)]
+#[sqlx(type_name = "attachment_kind", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum AttachmentKind {
+    File,
+    Image,
+    Log,
+    RepoPath,
+}
+
+/// Something handed to agents alongside a task
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct TaskAttachment {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub kind: AttachmentKind,
+    pub name: String,
+    pub path: String, // Stored file for uploads and logs, repository-relative for repo paths
+    pub content_type: Option<String>,
+    pub size_bytes: Option<i64>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Attachments created from JSON rather than an upload
+#[derive(Debug, Deserialize, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[ts(export)]
+pub enum CreateTaskAttachment {
+    Log {
+        name: Option<String>,
+        content: String,
+    },
+    RepoPath {
+        path: String,
+    },
+}
+
+/// How an executor's CLI wants attachments referenced in its prompt
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum AttachmentDelivery {
+    /// Plain worktree-relative paths the agent opens itself
+    #[default]
+    Paths,
+    /// `@path` mentions, which the CLI expands into the conversation
+    Mentions,
+}
+
+/// A file name safe to use on disk, keeping only the last component of `name`
+fn sanitize_name(name: &str) -> String {
+    let base = name.rsplit(['/', '\\']).next().unwrap_or_default();
+    let sanitized: String = base
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let sanitized = sanitized.trim_start_matches('.');
+    if sanitized.is_empty() {
+        "attachment".to_string()
+    } else {
+        sanitized.to_string()
+    }
+}
+
+/// Normalise a repository-relative path, rejecting anything that escapes the repository
+pub fn normalize_repo_path(path: &str) -> Option<String> {
+    let mut parts = Vec::new();
+    for component in Path::new(path.trim()).components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_string_lossy().to_string()),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    (!parts.is_empty()).then(|| parts.join("/"))
+}
+
+impl TaskAttachment {
+    /// The path agents see, relative to the worktree root
+    pub fn worktree_path(&self) -> String {
+        match self.kind {
+            AttachmentKind::RepoPath => self.path.clone(),
+            _ => format!("{}/{}", STAGING_DIR, self.file_name()),
+        }
+    }
+
+    fn file_name(&self) -> String {
+        Path::new(&self.path)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| sanitize_name(&self.name))
+    }
+
+    pub async fn find_by_task_id(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskAttachment,
+            r#"SELECT id           AS "id!: Uuid",
+                      task_id      AS "task_id!: Uuid",
+                      kind         AS "kind!: AttachmentKind",
+                      name,
+                      path,
+                      content_type,
+                      size_bytes,
+                      created_at   AS "created_at!: DateTime<Utc>"
+               FROM task_attachments
+               WHERE task_id = $1
+               ORDER BY created_at ASC"#,
+            task_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskAttachment,
+            r#"SELECT id           AS "id!: Uuid",
+                      task_id      AS "task_id!: Uuid",
+                      kind         AS "kind!: AttachmentKind",
+                      name,
+                      path,
+                      content_type,
+                      size_bytes,
+                      created_at   AS "created_at!: DateTime<Utc>"
+               FROM task_attachments
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn insert(
+        pool: &SqlitePool,
+        id: Uuid,
+        task_id: Uuid,
+        kind: AttachmentKind,
+        name: &str,
+        path: &str,
+        content_type: Option<&str>,
+        size_bytes: Option<i64>,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            TaskAttachment,
+            r#"INSERT INTO task_attachments (id, task_id, kind, name, path, content_type, size_bytes)
+               VALUES ($1, $2, $3, $4, $5, $6, $7)
+               RETURNING id           AS "id!: Uuid",
+                         task_id      AS "task_id!: Uuid",
+                         kind         AS "kind!: AttachmentKind",
+                         name,
+                         path,
+                         content_type,
+                         size_bytes,
+                         created_at   AS "created_at!: DateTime<Utc>""#,
+            id,
+            task_id,
+            kind,
+            name,
+            path,
+            content_type,
+            size_bytes
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Save uploaded or pasted content under the asset dir and record it
+    pub async fn store(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        kind: AttachmentKind,
+        name: &str,
+        content_type: Option<&str>,
+        content: &[u8],
+    ) -> Result<Self, TaskAttachmentError> {
+        let id = Uuid::new_v4();
+        let dir = attachments_dir().join(task_id.to_string());
+        tokio::fs::create_dir_all(&dir).await?;
+        let file_name = format!("{}-{}", &id.simple().to_string()[..8], sanitize_name(name));
+        let path = dir.join(file_name);
+        tokio::fs::write(&path, content).await?;
+
+        let attachment = Self::insert(
+            pool,
+            id,
+            task_id,
+            kind,
+            name,
+            &path.to_string_lossy(),
+            content_type,
+            Some(content.len() as i64),
+        )
+        .await;
+        if attachment.is_err() {
+            let _ = tokio::fs::remove_file(&path).await;
+        }
+        Ok(attachment?)
+    }
+
+    /// Record a path inside the project repository; `path` must already be normalised
+    pub async fn create_repo_path(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        path: &str,
+    ) -> Result<Self, sqlx::Error> {
+        Self::insert(
+            pool,
+            Uuid::new_v4(),
+            task_id,
+            AttachmentKind::RepoPath,
+            path,
+            path,
+            None,
+            None,
+        )
+        .await
+    }
+
+    /// Remove the record and any stored file
+    pub async fn delete(&self, pool: &SqlitePool) -> Result<(), sqlx::Error> {
+        sqlx::query!("DELETE FROM task_attachments WHERE id = $1", self.id)
+            .execute(pool)
+            .await?;
+        if self.kind != AttachmentKind::RepoPath {
+            if let Err(e) = tokio::fs::remove_file(&self.path).await {
+                tracing::warn!("Failed to remove attachment file {}: {}", self.path, e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove the files stored for a deleted task; its records go with it by cascade
+    pub async fn delete_stored_files(task_id: Uuid) {
+        let dir = attachments_dir().join(task_id.to_string());
+        if dir.exists() {
+            if let Err(e) = tokio::fs::remove_dir_all(&dir).await {
+                tracing::warn!("Failed to remove attachments of task {}: {}", task_id, e);
+            }
+        }
+    }
+
+    /// Copy a task's stored attachments into `worktree_path` under [`STAGING_DIR`]
+    ///
+    /// The staging folder is rebuilt on every run so deleted attachments disappear, and is
+    /// git-ignored so agents never commit it.
+    pub async fn stage(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        worktree_path: &str,
+    ) -> Result<Vec<Self>, TaskAttachmentError> {
+        let attachments = Self::find_by_task_id(pool, task_id).await?;
+        let worktree = Path::new(worktree_path);
+        let staging = worktree.join(STAGING_DIR);
+        if staging.exists() {
+            tokio::fs::remove_dir_all(&staging).await?;
+        }
+
+        let stored: Vec<&Self> = attachments
+            .iter()
+            .filter(|attachment| attachment.kind != AttachmentKind::RepoPath)
+            .collect();
+        if stored.is_empty() {
+            return Ok(attachments);
+        }
+
+        tokio::fs::create_dir_all(&staging).await?;
+        if let Some(root) = staging.parent() {
+            tokio::fs::write(root.join(".gitignore"), "*\n").await?;
+        }
+        for attachment in stored {
+            if let Err(e) =
+                tokio::fs::copy(&attachment.path, worktree.join(attachment.worktree_path())).await
+            {
+                tracing::warn!(
+                    "Failed to stage attachment {} for task {}: {}",
+                    attachment.id,
+                    task_id,
+                    e
+                );
+            }
+        }
+        Ok(attachments)
+    }
+}
+
+/// Worktree-relative paths of the files [`TaskAttachment::stage`] copied into a worktree,
+/// optionally only the images
+///
+/// Staged names are sanitised, so they can go on a command line unquoted.
+pub fn staged_files(worktree_path: &str, images_only: bool) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(Path::new(worktree_path).join(STAGING_DIR)) else {
+        return Vec::new();
+    };
+    let mut files: Vec<String> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file())
+        .filter(|path| {
+            !images_only
+                || mime_guess::from_path(path)
+                    .first()
+                    .is_some_and(|mime| mime.type_() == mime_guess::mime::IMAGE)
+        })
+        .filter_map(|path| {
+            let name = path.file_name()?.to_string_lossy().to_string();
+            Some(format!("{}/{}", STAGING_DIR, name))
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+/// The attachments section of a prompt, referencing each attachment the way the executor
+/// expects
+pub fn render_attachments(
+    attachments: &[TaskAttachment],
+    delivery: AttachmentDelivery,
+) -> Option<String> {
+    if attachments.is_empty() {
+        return None;
+    }
+    let lines: Vec<String> = attachments
+        .iter()
+        .map(|attachment| {
+            let kind = match attachment.kind {
+                AttachmentKind::File => "file",
+                AttachmentKind::Image => "image",
+                AttachmentKind::Log => "log excerpt",
+                AttachmentKind::RepoPath => "repository path",
+            };
+            let path = match delivery {
+                AttachmentDelivery::Paths => attachment.worktree_path(),
+                AttachmentDelivery::Mentions => format!("@{}", attachment.worktree_path()),
+            };
+            format!("- {} ({}): {}", attachment.name, kind, path)
+        })
+        .collect();
+    Some(format!("Attachments:\n{}", lines.join("\n")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attachment(kind: AttachmentKind, name: &str, path: &str) -> TaskAttachment {
+        TaskAttachment {
+            id: Uuid::nil(),
+            task_id: Uuid::nil(),
+            kind,
+            name: name.to_string(),
+            path: path.to_string(),
+            content_type: None,
+            size_bytes: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn render_attachments_references_staged_and_repo_paths() {
+        let attachments = vec![
+            attachment(
+                AttachmentKind::Image,
+                "bug.png",
+                "/data/attachments/t/0123abcd-bug.png",
+            ),
+            attachment(AttachmentKind::RepoPath, "src/lib.rs", "src/lib.rs"),
+        ];
+
+        assert_eq!(
+            render_attachments(&attachments, AttachmentDelivery::Paths).unwrap(),
+            "Attachments:\n- bug.png (image): .vibe-kanban/attachments/0123abcd-bug.png\n- src/lib.rs (repository path): src/lib.rs"
+        );
+        assert!(
+            render_attachments(&attachments, AttachmentDelivery::Mentions)
+                .unwrap()
+                .ends_with("(repository path): @src/lib.rs")
+        );
+        assert!(render_attachments(&[], AttachmentDelivery::Paths).is_none());
+    }
+
+    #[test]
+    fn repo_paths_and_names_cannot_escape() {
+        assert_eq!(
+            normalize_repo_path("./src//main.rs").as_deref(),
+            Some("src/main.rs")
+        );
+        assert_eq!(normalize_repo_path("../secrets"), None);
+        assert_eq!(normalize_repo_path("/etc/passwd"), None);
+        assert_eq!(normalize_repo_path(" "), None);
+
+        assert_eq!(sanitize_name("../../.bashrc"), "bashrc");
+        assert_eq!(sanitize_name("my screenshot.png"), "my_screenshot.png");
+        assert_eq!(sanitize_name(""), "attachment");
+    }
+}
@@ -8,10 +8,19 @@ use tracing::info;
 use ts_rs::TS;
 use uuid::Uuid;
 
-use super::{project::Project, task::Task};
-use crate::services::{
-    CreatePrRequest, GitHubRepoInfo, GitHubService, GitHubServiceError, GitService,
-    GitServiceError, ProcessService,
+use super::{
+    executor_session::ExecutorSession,
+    pipeline::{Pipeline, TaskAttemptStage},
+    project::Project,
+    task::Task,
+};
+use crate::{
+    executor::RunOptions,
+    services::{
+        CreatePrRequest, GitHubRepoInfo, GitHubService, GitHubServiceError, GitService,
+        GitServiceError, ProcessService,
+    },
+    utils::worktree_manager::WorktreeManager,
 };
 
 // Constants for git diff operations
@@ -100,6 +109,7 @@ pub struct TaskAttempt {
     pub pr_merged_at: Option<DateTime<Utc>>, // When PR was merged
     pub worktree_deleted: bool,    // Flag indicating if worktree has been cleaned up
     pub setup_completed_at: Option<DateTime<Utc>>, // When setup script was last completed
+    pub forked_from_session_id: Option<Uuid>, // Executor session this attempt was forked from
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -109,6 +119,10 @@ pub struct TaskAttempt {
 pub struct CreateTaskAttempt {
     pub executor: Option<String>, // Optional executor name (defaults to "echo")
     pub base_branch: Option<String>, // Optional base branch to checkout (defaults to current HEAD)
+    pub plan_first: Option<bool>, // Start with a read-only planning phase (defaults to false)
+    pub pipeline_id: Option<Uuid>, // Run the project pipeline's stages instead of a single agent
+    pub model: Option<String>,    // Model passed to the agent CLI (defaults to the CLI's own)
+    pub extra_args: Option<Vec<String>>, // Extra arguments appended to the agent CLI
 }
 
 #[derive(Debug, Deserialize, TS)]
@@ -132,6 +146,15 @@ pub struct CreatePrParams<'a> {
 #[ts(export)]
 pub struct CreateFollowUpAttempt {
     pub prompt: String,
+    pub model: Option<String>, // Switches the attempt's model for this and later runs
+    pub extra_args: Option<Vec<String>>, // Replaces the attempt's extra CLI arguments
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export)]
+pub struct ForkTaskAttempt {
+    pub executor_session_id: Uuid, // Session of the original attempt to branch off from
+    pub prompt: String,            // First instruction for the forked line of work
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
@@ -237,6 +260,7 @@ impl TaskAttempt {
                        ta.pr_merged_at      AS "pr_merged_at: DateTime<Utc>",
                        ta.worktree_deleted  AS "worktree_deleted!: bool",
                        ta.setup_completed_at AS "setup_completed_at: DateTime<Utc>",
+                       ta.forked_from_session_id AS "forked_from_session_id: Uuid",
                        ta.created_at        AS "created_at!: DateTime<Utc>",
                        ta.updated_at        AS "updated_at!: DateTime<Utc>"
                FROM    task_attempts ta
@@ -317,6 +341,7 @@ impl TaskAttempt {
                        pr_merged_at      AS "pr_merged_at: DateTime<Utc>",
                        worktree_deleted  AS "worktree_deleted!: bool",
                        setup_completed_at AS "setup_completed_at: DateTime<Utc>",
+                       forked_from_session_id AS "forked_from_session_id: Uuid",
                        created_at        AS "created_at!: DateTime<Utc>",
                        updated_at        AS "updated_at!: DateTime<Utc>"
                FROM    task_attempts
@@ -346,6 +371,7 @@ impl TaskAttempt {
                        pr_merged_at      AS "pr_merged_at: DateTime<Utc>",
                        worktree_deleted  AS "worktree_deleted!: bool",
                        setup_completed_at AS "setup_completed_at: DateTime<Utc>",
+                       forked_from_session_id AS "forked_from_session_id: Uuid",
                        created_at        AS "created_at!: DateTime<Utc>",
                        updated_at        AS "updated_at!: DateTime<Utc>"
                FROM    task_attempts
@@ -443,6 +469,22 @@ impl TaskAttempt {
             .await?
             .ok_or(TaskAttemptError::TaskNotFound)?;
 
+        // Resolve the pipeline up front so an invalid one does not leave a worktree behind
+        let pipeline = match data.pipeline_id {
+            Some(pipeline_id) => Some(
+                Pipeline::find_by_id(pool, pipeline_id)
+                    .await?
+                    .filter(|pipeline| pipeline.project_id == task.project_id)
+                    .ok_or_else(|| {
+                        TaskAttemptError::ValidationError(format!(
+                            "Pipeline {} not found in project",
+                            pipeline_id
+                        ))
+                    })?,
+            ),
+            None => None,
+        };
+
         // Create a unique and helpful branch name
         let task_title_id = crate::utils::text::git_branch_id(&task.title);
         let task_attempt_branch = format!(
@@ -478,12 +520,23 @@ impl TaskAttempt {
             data.base_branch.as_deref(),
         )?;
 
+        let extra_args = data
+            .extra_args
+            .as_ref()
+            .filter(|args| !args.is_empty())
+            .map(|args| serde_json::to_string(args).unwrap_or_default());
+        let model = data
+            .model
+            .as_deref()
+            .map(str::trim)
+            .filter(|model| !model.is_empty());
+
         // Insert the record into the database
-        Ok(sqlx::query_as!(
+        let attempt = sqlx::query_as!(
             TaskAttempt,
-            r#"INSERT INTO task_attempts (id, task_id, worktree_path, branch, base_branch, merge_commit, executor, pr_url, pr_number, pr_status, pr_merged_at, worktree_deleted, setup_completed_at)
-               VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
-               RETURNING id as "id!: Uuid", task_id as "task_id!: Uuid", worktree_path, branch, base_branch, merge_commit, executor, pr_url, pr_number, pr_status, pr_merged_at as "pr_merged_at: DateTime<Utc>", worktree_deleted as "worktree_deleted!: bool", setup_completed_at as "setup_completed_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            r#"INSERT INTO task_attempts (id, task_id, worktree_path, branch, base_branch, merge_commit, executor, pr_url, pr_number, pr_status, pr_merged_at, worktree_deleted, setup_completed_at, model, extra_args)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+               RETURNING id as "id!: Uuid", task_id as "task_id!: Uuid", worktree_path, branch, base_branch, merge_commit, executor, pr_url, pr_number, pr_status, pr_merged_at as "pr_merged_at: DateTime<Utc>", worktree_deleted as "worktree_deleted!: bool", setup_completed_at as "setup_completed_at: DateTime<Utc>", forked_from_session_id as "forked_from_session_id: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
             attempt_id,
             task_id,
             worktree_path_str,
@@ -496,10 +549,18 @@ impl TaskAttempt {
             Option::<String>::None, // pr_status is None during creation
             Option::<DateTime<Utc>>::None, // pr_merged_at is None during creation
             false, // worktree_deleted is false during creation
-            Option::<DateTime<Utc>>::None // setup_completed_at is None during creation
+            Option::<DateTime<Utc>>::None, // setup_completed_at is None during creation
+            model,
+            extra_args
         )
         .fetch_one(pool)
-        .await?)
+        .await?;
+
+        if let Some(pipeline) = pipeline {
+            TaskAttemptStage::create_for_attempt(pool, attempt.id, &pipeline.stages).await?;
+        }
+
+        Ok(attempt)
     }
 
     /// Perform the actual merge operation using GitService
@@ -594,8 +655,12 @@ impl TaskAttempt {
         attempt_id: Uuid,
         task_id: Uuid,
         project_id: Uuid,
+        plan_first: bool,
     ) -> Result<(), TaskAttemptError> {
-        ProcessService::start_execution(pool, app_state, attempt_id, task_id, project_id).await
+        ProcessService::start_execution(
+            pool, app_state, attempt_id, task_id, project_id, plan_first,
+        )
+        .await
     }
 
     /// Start a dev server for this task attempt
@@ -625,6 +690,99 @@ impl TaskAttempt {
         .await
     }
 
+    /// Create a new attempt whose worktree starts at the commit `session`'s run left behind
+    ///
+    /// The fork keeps the original attempt's executor, base branch, model and arguments, and its
+    /// first follow-up resumes `session` instead of starting a new conversation.
+    pub async fn fork(
+        pool: &SqlitePool,
+        source: &TaskAttempt,
+        session: &ExecutorSession,
+    ) -> Result<Self, TaskAttemptError> {
+        let commit = session.after_head_commit.as_deref().ok_or_else(|| {
+            TaskAttemptError::ValidationError(
+                "This execution has no recorded commit to fork from".to_string(),
+            )
+        })?;
+
+        let options = Self::find_run_options(pool, source.id).await?;
+        let attempt = Self::create(
+            pool,
+            &CreateTaskAttempt {
+                executor: source.executor.clone(),
+                base_branch: Some(source.base_branch.clone()),
+                plan_first: None,
+                pipeline_id: None,
+                model: options.model,
+                extra_args: Some(options.extra_args),
+            },
+            source.task_id,
+        )
+        .await?;
+
+        let task = Task::find_by_id(pool, source.task_id)
+            .await?
+            .ok_or(TaskAttemptError::TaskNotFound)?;
+        let project = Project::find_by_id(pool, task.project_id)
+            .await?
+            .ok_or(TaskAttemptError::ProjectNotFound)?;
+        if let Err(e) = GitService::new(&project.git_repo_path).and_then(|git_service| {
+            git_service.reset_worktree_to_commit(Path::new(&attempt.worktree_path), commit)
+        }) {
+            Self::discard(pool, &attempt, &project.git_repo_path).await;
+            return Err(e.into());
+        }
+
+        sqlx::query!(
+            "UPDATE task_attempts SET forked_from_session_id = $1, updated_at = datetime('now') WHERE id = $2",
+            session.id,
+            attempt.id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(Self {
+            forked_from_session_id: Some(session.id),
+            ..attempt
+        })
+    }
+
+    /// Remove an attempt whose setup failed: its worktree, branch and row
+    ///
+    /// Failures are logged rather than returned so the setup error reaches the caller.
+    async fn discard(pool: &SqlitePool, attempt: &TaskAttempt, git_repo_path: &str) {
+        if let Err(e) = WorktreeManager::cleanup_worktree(
+            Path::new(&attempt.worktree_path),
+            Some(git_repo_path),
+        )
+        .await
+        {
+            tracing::warn!(
+                "Failed to remove worktree {} of discarded attempt {}: {}",
+                attempt.worktree_path,
+                attempt.id,
+                e
+            );
+        }
+        if let Err(e) = Repository::open(git_repo_path).and_then(|repo| {
+            repo.find_branch(&attempt.branch, BranchType::Local)?
+                .delete()
+        }) {
+            tracing::warn!(
+                "Failed to delete branch {} of discarded attempt {}: {}",
+                attempt.branch,
+                attempt.id,
+                e
+            );
+        }
+        if let Err(e) = sqlx::query!("DELETE FROM task_attempts WHERE id = $1", attempt.id)
+            .execute(pool)
+            .await
+        {
+            tracing::error!("Failed to delete discarded attempt {}: {}", attempt.id, e);
+        }
+    }
+
     /// Ensure worktree exists, recreating from branch if needed (cold task support)
     pub async fn ensure_worktree_exists(
         pool: &SqlitePool,
@@ -1022,7 +1180,8 @@ impl TaskAttempt {
                             crate::models::execution_process::ExecutionProcessStatus::Completed => {
                                 ExecutionState::CodingAgentComplete
                             }
-                            crate::models::execution_process::ExecutionProcessStatus::Failed => {
+                            crate::models::execution_process::ExecutionProcessStatus::Failed
+                            | crate::models::execution_process::ExecutionProcessStatus::TimedOut => {
                                 ExecutionState::CodingAgentFailed
                             }
                             crate::models::execution_process::ExecutionProcessStatus::Killed => {
@@ -1033,7 +1192,8 @@ impl TaskAttempt {
                         ExecutionState::SetupComplete
                     }
                 }
-                crate::models::execution_process::ExecutionProcessStatus::Failed => {
+                crate::models::execution_process::ExecutionProcessStatus::Failed
+                | crate::models::execution_process::ExecutionProcessStatus::TimedOut => {
                     ExecutionState::SetupFailed
                 }
                 crate::models::execution_process::ExecutionProcessStatus::Killed => {
@@ -1049,7 +1209,8 @@ impl TaskAttempt {
                 crate::models::execution_process::ExecutionProcessStatus::Completed => {
                     ExecutionState::CodingAgentComplete
                 }
-                crate::models::execution_process::ExecutionProcessStatus::Failed => {
+                crate::models::execution_process::ExecutionProcessStatus::Failed
+                | crate::models::execution_process::ExecutionProcessStatus::TimedOut => {
                     ExecutionState::CodingAgentFailed
                 }
                 crate::models::execution_process::ExecutionProcessStatus::Killed => {
@@ -1103,6 +1264,50 @@ impl TaskAttempt {
         Ok(())
     }
 
+    /// Model and extra CLI arguments the attempt's agent runs use
+    pub async fn find_run_options(
+        pool: &SqlitePool,
+        attempt_id: Uuid,
+    ) -> Result<RunOptions, TaskAttemptError> {
+        let row = sqlx::query!(
+            "SELECT model, extra_args FROM task_attempts WHERE id = $1",
+            attempt_id
+        )
+        .fetch_optional(pool)
+        .await?
+        .ok_or(TaskAttemptError::TaskNotFound)?;
+
+        Ok(RunOptions {
+            model: row.model,
+            extra_args: row
+                .extra_args
+                .and_then(|args| serde_json::from_str(&args).ok())
+                .unwrap_or_default(),
+            cli_version: None,
+        })
+    }
+
+    /// Replace the model and extra CLI arguments used by the attempt's later agent runs
+    pub async fn set_run_options(
+        pool: &SqlitePool,
+        attempt_id: Uuid,
+        model: Option<&str>,
+        extra_args: &[String],
+    ) -> Result<(), TaskAttemptError> {
+        let extra_args =
+            (!extra_args.is_empty()).then(|| serde_json::to_string(extra_args).unwrap_or_default());
+        sqlx::query!(
+            "UPDATE task_attempts SET model = $1, extra_args = $2, updated_at = datetime('now') WHERE id = $3",
+            model,
+            extra_args,
+            attempt_id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
     /// Get execution history from current attempt only (simplified)
     pub async fn get_attempt_execution_history(
         pool: &SqlitePool,
@@ -1211,3 +1416,73 @@ impl TaskAttempt {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{
+        insert_attempt, insert_project, insert_running_process, insert_task, test_pool,
+    };
+
+    #[tokio::test]
+    async fn failed_fork_leaves_no_attempt_worktree_or_branch() {
+        let repo_dir = tempfile::TempDir::new().unwrap();
+        let repo = Repository::init(repo_dir.path()).unwrap();
+        let mut config = repo.config().unwrap();
+        config.set_str("user.name", "Test User").unwrap();
+        config.set_str("user.email", "test@example.com").unwrap();
+        let repo_path = repo_dir.path().to_str().unwrap();
+        let source_worktree = repo_dir.path().join("source-worktree");
+        GitService::new(repo_path)
+            .unwrap()
+            .create_worktree("source", &source_worktree, None)
+            .unwrap();
+
+        let pool = test_pool().await;
+        let project_id = insert_project(&pool, repo_path).await;
+        let task_id = insert_task(&pool, project_id).await;
+        let source_id = insert_attempt(
+            &pool,
+            task_id,
+            source_worktree.to_str().unwrap(),
+            "source",
+            "claude",
+        )
+        .await;
+        let process_id = insert_running_process(&pool, source_id).await;
+        let session_id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO executor_sessions (id, task_attempt_id, execution_process_id, after_head_commit)
+             VALUES ($1, $2, $3, 'not-a-commit')",
+        )
+        .bind(session_id)
+        .bind(source_id)
+        .bind(process_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let source = TaskAttempt::find_by_id(&pool, source_id)
+            .await
+            .unwrap()
+            .unwrap();
+        let session = ExecutorSession::find_by_id(&pool, session_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(TaskAttempt::fork(&pool, &source, &session).await.is_err());
+
+        let attempts = TaskAttempt::find_by_task_id(&pool, task_id).await.unwrap();
+        assert_eq!(attempts.len(), 1);
+        let worktrees = repo.worktrees().unwrap();
+        let worktrees: Vec<_> = worktrees.iter().flatten().collect();
+        assert_eq!(worktrees, ["source"]);
+        let mut branches: Vec<String> = repo
+            .branches(Some(BranchType::Local))
+            .unwrap()
+            .map(|branch| branch.unwrap().0.name().unwrap().unwrap().to_string())
+            .collect();
+        branches.sort();
+        assert_eq!(branches, ["main", "source"]);
+    }
+}
@@ -0,0 +1,264 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::{
+    execution_process::ExecutionProcessStatus,
+    task_attempt::{DiffChunkType, WorktreeDiff},
+    token_usage::UsageSummary,
+};
+
+/// A set of attempts racing on the same task with different executors
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct TaskAttemptGroup {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub winner_attempt_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export)]
+pub struct CreateTaskAttemptGroup {
+    pub executors: Vec<String>,      // One attempt is started per executor
+    pub base_branch: Option<String>, // Optional base branch shared by every attempt
+}
+
+/// Line and file counts for an attempt's worktree diff
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct DiffStats {
+    pub files_changed: usize,
+    pub lines_added: usize,
+    pub lines_removed: usize,
+}
+
+impl DiffStats {
+    pub fn from_diff(diff: &WorktreeDiff) -> Self {
+        let mut stats = DiffStats {
+            files_changed: diff.files.len(),
+            ..Default::default()
+        };
+        for chunk in diff.files.iter().flat_map(|file| &file.chunks) {
+            match chunk.chunk_type {
+                DiffChunkType::Insert => stats.lines_added += chunk.content.lines().count(),
+                DiffChunkType::Delete => stats.lines_removed += chunk.content.lines().count(),
+                DiffChunkType::Equal => {}
+            }
+        }
+        stats
+    }
+}
+
+/// Side-by-side summary of one attempt in a group
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct AttemptComparison {
+    pub attempt_id: Uuid,
+    pub executor: Option<String>,
+    pub branch: String,
+    pub status: Option<ExecutionProcessStatus>,
+    pub exit_code: Option<i64>,
+    pub duration_seconds: Option<f64>,
+    pub diff: Option<DiffStats>,
+    pub usage: UsageSummary,
+    pub is_winner: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct AttemptGroupComparison {
+    pub group: TaskAttemptGroup,
+    pub attempts: Vec<AttemptComparison>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[ts(export)]
+pub enum WinnerAction {
+    Merge,
+    PullRequest {
+        title: String,
+        body: Option<String>,
+        base_branch: Option<String>,
+    },
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export)]
+pub struct PickWinnerRequest {
+    pub attempt_id: Uuid,
+    pub action: WinnerAction,
+}
+
+impl TaskAttemptGroup {
+    pub async fn create(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        attempt_ids: &[Uuid],
+    ) -> Result<Self, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        let id = Uuid::new_v4();
+        let group = sqlx::query_as!(
+            TaskAttemptGroup,
+            r#"INSERT INTO task_attempt_groups (id, task_id)
+               VALUES ($1, $2)
+               RETURNING id                AS "id!: Uuid",
+                         task_id           AS "task_id!: Uuid",
+                         winner_attempt_id AS "winner_attempt_id: Uuid",
+                         created_at        AS "created_at!: DateTime<Utc>",
+                         updated_at        AS "updated_at!: DateTime<Utc>""#,
+            id,
+            task_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        for attempt_id in attempt_ids {
+            sqlx::query!(
+                "INSERT INTO task_attempt_group_members (group_id, task_attempt_id) VALUES ($1, $2)",
+                id,
+                attempt_id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(group)
+    }
+
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskAttemptGroup,
+            r#"SELECT id                AS "id!: Uuid",
+                      task_id           AS "task_id!: Uuid",
+                      winner_attempt_id AS "winner_attempt_id: Uuid",
+                      created_at        AS "created_at!: DateTime<Utc>",
+                      updated_at        AS "updated_at!: DateTime<Utc>"
+               FROM task_attempt_groups
+               WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn find_by_task_id(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TaskAttemptGroup,
+            r#"SELECT id                AS "id!: Uuid",
+                      task_id           AS "task_id!: Uuid",
+                      winner_attempt_id AS "winner_attempt_id: Uuid",
+                      created_at        AS "created_at!: DateTime<Utc>",
+                      updated_at        AS "updated_at!: DateTime<Utc>"
+               FROM task_attempt_groups
+               WHERE task_id = $1
+               ORDER BY created_at DESC"#,
+            task_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Ids of the attempts in this group, in creation order
+    pub async fn attempt_ids(pool: &SqlitePool, group_id: Uuid) -> Result<Vec<Uuid>, sqlx::Error> {
+        let records = sqlx::query!(
+            r#"SELECT m.task_attempt_id AS "task_attempt_id!: Uuid"
+               FROM task_attempt_group_members m
+               JOIN task_attempts ta ON ta.id = m.task_attempt_id
+               WHERE m.group_id = $1
+               ORDER BY ta.created_at ASC"#,
+            group_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(records.into_iter().map(|r| r.task_attempt_id).collect())
+    }
+
+    /// Record `attempt_id` as the group's winner unless one was already picked
+    ///
+    /// Returns whether this call claimed the group.
+    pub async fn claim_winner(
+        pool: &SqlitePool,
+        group_id: Uuid,
+        attempt_id: Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            "UPDATE task_attempt_groups SET winner_attempt_id = $1, updated_at = datetime('now', 'subsec') WHERE id = $2 AND winner_attempt_id IS NULL",
+            attempt_id,
+            group_id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Undo `claim_winner` for `attempt_id`, leaving the group to pick from again
+    pub async fn release_winner(
+        pool: &SqlitePool,
+        group_id: Uuid,
+        attempt_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE task_attempt_groups SET winner_attempt_id = NULL, updated_at = datetime('now', 'subsec') WHERE id = $1 AND winner_attempt_id = $2",
+            group_id,
+            attempt_id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::task_attempt::{DiffChunk, FileDiff};
+
+    fn chunk(chunk_type: DiffChunkType, content: &str) -> DiffChunk {
+        DiffChunk {
+            chunk_type,
+            content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn diff_stats_count_inserted_and_deleted_lines() {
+        let diff = WorktreeDiff {
+            files: vec![
+                FileDiff {
+                    path: "src/lib.rs".to_string(),
+                    chunks: vec![
+                        chunk(DiffChunkType::Equal, "fn main() {\n"),
+                        chunk(DiffChunkType::Delete, "    old();\n"),
+                        chunk(DiffChunkType::Insert, "    new();\n    more();\n"),
+                    ],
+                },
+                FileDiff {
+                    path: "README.md".to_string(),
+                    chunks: vec![chunk(DiffChunkType::Insert, "docs\n")],
+                },
+            ],
+        };
+
+        assert_eq!(
+            DiffStats::from_diff(&diff),
+            DiffStats {
+                files_changed: 2,
+                lines_added: 3,
+                lines_removed: 1,
+            }
+        );
+    }
+}
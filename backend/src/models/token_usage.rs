@@ -0,0 +1,337 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, QueryBuilder, Sqlite, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Token counts reported by an executor, plus cost when the executor reports one
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct TokenUsage {
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cache_read_tokens: i64,
+    pub cache_creation_tokens: i64,
+    pub cost_usd: Option<f64>,
+}
+
+impl TokenUsage {
+    /// Accumulate another usage report into this one
+    pub fn add(&mut self, other: &TokenUsage) {
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cache_read_tokens += other.cache_read_tokens;
+        self.cache_creation_tokens += other.cache_creation_tokens;
+        self.cost_usd = match (self.cost_usd, other.cost_usd) {
+            (Some(a), Some(b)) => Some(a + b),
+            (a, b) => a.or(b),
+        };
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.input_tokens == 0
+            && self.output_tokens == 0
+            && self.cache_read_tokens == 0
+            && self.cache_creation_tokens == 0
+            && self.cost_usd.is_none()
+    }
+}
+
+/// Usage recorded for a single execution process
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ExecutionProcessUsage {
+    pub id: Uuid,
+    pub execution_process_id: Uuid,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cache_read_tokens: i64,
+    pub cache_creation_tokens: i64,
+    pub cost_usd: Option<f64>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Aggregated usage over a set of execution processes
+///
+/// `group` holds the executor type or day (`YYYY-MM-DD`) for grouped queries and is
+/// `None` for plain totals.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct UsageSummary {
+    pub group: Option<String>,
+    pub process_count: i64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cache_read_tokens: i64,
+    pub cache_creation_tokens: i64,
+    pub cost_usd: Option<f64>,
+}
+
+impl ExecutionProcessUsage {
+    /// Find usage recorded for an execution process
+    pub async fn find_by_execution_process_id(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ExecutionProcessUsage,
+            r#"SELECT
+                id as "id!: Uuid",
+                execution_process_id as "execution_process_id!: Uuid",
+                input_tokens,
+                output_tokens,
+                cache_read_tokens,
+                cache_creation_tokens,
+                cost_usd,
+                created_at as "created_at!: DateTime<Utc>",
+                updated_at as "updated_at!: DateTime<Utc>"
+               FROM token_usage
+               WHERE execution_process_id = $1"#,
+            execution_process_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Record usage for an execution process, replacing any earlier record
+    pub async fn upsert(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+        usage: &TokenUsage,
+    ) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            ExecutionProcessUsage,
+            r#"INSERT INTO token_usage (
+                id, execution_process_id, input_tokens, output_tokens,
+                cache_read_tokens, cache_creation_tokens, cost_usd
+               )
+               VALUES ($1, $2, $3, $4, $5, $6, $7)
+               ON CONFLICT(execution_process_id) DO UPDATE SET
+                input_tokens = excluded.input_tokens,
+                output_tokens = excluded.output_tokens,
+                cache_read_tokens = excluded.cache_read_tokens,
+                cache_creation_tokens = excluded.cache_creation_tokens,
+                cost_usd = excluded.cost_usd,
+                updated_at = datetime('now', 'subsec')
+               RETURNING
+                id as "id!: Uuid",
+                execution_process_id as "execution_process_id!: Uuid",
+                input_tokens,
+                output_tokens,
+                cache_read_tokens,
+                cache_creation_tokens,
+                cost_usd,
+                created_at as "created_at!: DateTime<Utc>",
+                updated_at as "updated_at!: DateTime<Utc>""#,
+            id,
+            execution_process_id,
+            usage.input_tokens,
+            usage.output_tokens,
+            usage.cache_read_tokens,
+            usage.cache_creation_tokens,
+            usage.cost_usd
+        )
+        .fetch_one(pool)
+        .await
+    }
+}
+
+/// Totals selected by every usage aggregate, over `USAGE_SOURCE`
+const USAGE_TOTALS: &str = r#"
+    COUNT(u.id) AS process_count,
+    COALESCE(SUM(u.input_tokens), 0) AS input_tokens,
+    COALESCE(SUM(u.output_tokens), 0) AS output_tokens,
+    COALESCE(SUM(u.cache_read_tokens), 0) AS cache_read_tokens,
+    COALESCE(SUM(u.cache_creation_tokens), 0) AS cache_creation_tokens,
+    SUM(u.cost_usd) AS cost_usd
+"#;
+
+/// Usage records joined with the process, attempt and task they belong to
+const USAGE_SOURCE: &str = r#"
+    FROM token_usage u
+    JOIN execution_processes ep ON ep.id = u.execution_process_id
+    JOIN task_attempts ta ON ta.id = ep.task_attempt_id
+    JOIN tasks t ON t.id = ta.task_id
+"#;
+
+/// Which processes an aggregate covers
+enum UsageScope {
+    TaskAttempt(Uuid),
+    Task(Uuid),
+    Project(Option<Uuid>), // Every project when `None`
+}
+
+/// One aggregate over `scope`, grouped by the SQL expression `group` when given
+fn usage_query(group: Option<&str>, scope: UsageScope) -> QueryBuilder<'static, Sqlite> {
+    let mut query = QueryBuilder::new("SELECT ");
+    query.push(group.unwrap_or("NULL"));
+    query.push(r#" AS "group","#);
+    query.push(USAGE_TOTALS);
+    query.push(USAGE_SOURCE);
+    match scope {
+        UsageScope::TaskAttempt(id) => {
+            query.push(" WHERE ep.task_attempt_id = ").push_bind(id);
+        }
+        UsageScope::Task(id) => {
+            query.push(" WHERE ta.task_id = ").push_bind(id);
+        }
+        UsageScope::Project(Some(id)) => {
+            query.push(" WHERE t.project_id = ").push_bind(id);
+        }
+        UsageScope::Project(None) => {}
+    }
+    if let Some(group) = group {
+        query.push(" GROUP BY ").push(group).push(" ORDER BY 1 ASC");
+    }
+    query
+}
+
+impl UsageSummary {
+    /// Total usage across all execution processes of a task attempt
+    pub async fn for_task_attempt(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+    ) -> Result<Self, sqlx::Error> {
+        Self::total(pool, UsageScope::TaskAttempt(task_attempt_id)).await
+    }
+
+    /// Total usage across all attempts of a task
+    pub async fn for_task(pool: &SqlitePool, task_id: Uuid) -> Result<Self, sqlx::Error> {
+        Self::total(pool, UsageScope::Task(task_id)).await
+    }
+
+    /// Total usage across all tasks of a project
+    pub async fn for_project(pool: &SqlitePool, project_id: Uuid) -> Result<Self, sqlx::Error> {
+        Self::total(pool, UsageScope::Project(Some(project_id))).await
+    }
+
+    /// Usage grouped by executor type, optionally limited to one project
+    pub async fn by_executor(
+        pool: &SqlitePool,
+        project_id: Option<Uuid>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        usage_query(
+            Some("COALESCE(ep.executor_type, 'unknown')"),
+            UsageScope::Project(project_id),
+        )
+        .build_query_as()
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Usage grouped by the UTC day it was recorded, optionally limited to one project
+    pub async fn by_day(
+        pool: &SqlitePool,
+        project_id: Option<Uuid>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        usage_query(Some("date(u.created_at)"), UsageScope::Project(project_id))
+            .build_query_as()
+            .fetch_all(pool)
+            .await
+    }
+
+    async fn total(pool: &SqlitePool, scope: UsageScope) -> Result<Self, sqlx::Error> {
+        usage_query(None, scope)
+            .build_query_as()
+            .fetch_one(pool)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_keeps_cost_when_only_one_side_reports_it() {
+        let mut total = TokenUsage {
+            input_tokens: 10,
+            output_tokens: 5,
+            ..Default::default()
+        };
+        total.add(&TokenUsage {
+            input_tokens: 1,
+            cache_read_tokens: 100,
+            cost_usd: Some(0.25),
+            ..Default::default()
+        });
+
+        assert_eq!(total.input_tokens, 11);
+        assert_eq!(total.output_tokens, 5);
+        assert_eq!(total.cache_read_tokens, 100);
+        assert_eq!(total.cost_usd, Some(0.25));
+        assert!(!total.is_empty());
+        assert!(TokenUsage::default().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_summaries_add_up_usage_in_their_scope() {
+        use crate::test_utils::{
+            insert_attempt, insert_project, insert_running_process, insert_task, test_pool,
+        };
+
+        let pool = test_pool().await;
+        let project_id = insert_project(&pool, "/tmp/usage-project").await;
+        let task_id = insert_task(&pool, project_id).await;
+        let attempt_id = insert_attempt(&pool, task_id, "/tmp/a", "vk-a", "claude").await;
+        let other_attempt_id = insert_attempt(&pool, task_id, "/tmp/b", "vk-b", "codex").await;
+        let other_project_id = insert_project(&pool, "/tmp/other-project").await;
+        let other_task_id = insert_task(&pool, other_project_id).await;
+        let unrelated_attempt_id =
+            insert_attempt(&pool, other_task_id, "/tmp/c", "vk-c", "amp").await;
+
+        for (attempt_id, executor, input_tokens, cost_usd) in [
+            (attempt_id, "claude", 10, Some(0.5)),
+            (attempt_id, "claude", 20, None),
+            (other_attempt_id, "codex", 5, None),
+            (unrelated_attempt_id, "amp", 1000, Some(1.0)),
+        ] {
+            let process_id = insert_running_process(&pool, attempt_id).await;
+            sqlx::query("UPDATE execution_processes SET executor_type = $1 WHERE id = $2")
+                .bind(executor)
+                .bind(process_id)
+                .execute(&pool)
+                .await
+                .unwrap();
+            let usage = TokenUsage {
+                input_tokens,
+                output_tokens: 1,
+                cost_usd,
+                ..Default::default()
+            };
+            ExecutionProcessUsage::upsert(&pool, process_id, &usage)
+                .await
+                .unwrap();
+        }
+
+        let attempt = UsageSummary::for_task_attempt(&pool, attempt_id)
+            .await
+            .unwrap();
+        assert_eq!(attempt.group, None);
+        assert_eq!(attempt.process_count, 2);
+        assert_eq!(attempt.input_tokens, 30);
+        assert_eq!(attempt.cost_usd, Some(0.5));
+
+        let task = UsageSummary::for_task(&pool, task_id).await.unwrap();
+        assert_eq!(task.input_tokens, 35);
+        assert_eq!(task.output_tokens, 3);
+        let project = UsageSummary::for_project(&pool, project_id).await.unwrap();
+        assert_eq!(project.process_count, 3);
+
+        let by_executor = UsageSummary::by_executor(&pool, Some(project_id))
+            .await
+            .unwrap();
+        let groups: Vec<_> = by_executor
+            .iter()
+            .map(|summary| (summary.group.as_deref(), summary.input_tokens))
+            .collect();
+        assert_eq!(groups, vec![(Some("claude"), 30), (Some("codex"), 5)]);
+
+        let by_day = UsageSummary::by_day(&pool, None).await.unwrap();
+        assert_eq!(by_day.len(), 1);
+        assert_eq!(by_day[0].input_tokens, 1035);
+        assert_eq!(by_day[0].cost_usd, Some(1.5));
+    }
+}
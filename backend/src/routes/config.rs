@@ -9,11 +9,12 @@ use axum::{
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use tokio::fs;
+use toml_edit::{DocumentMut, InlineTable, Item, Table};
 use ts_rs::TS;
 
 use crate::{
     app_state::AppState,
-    executor::ExecutorConfig,
+    executor::{ExecutorConfig, ExecutorConstants, McpCapability, McpConfigFormat},
     models::{
         config::{Config, EditorConstants, SoundConstants},
         ApiResponse, Environment,
@@ -68,6 +69,7 @@ async fn update_config(
 #[derive(Debug, Serialize, Deserialize, TS)]
 #[ts(export)]
 pub struct ConfigConstants {
+    pub executor: ExecutorConstants,
     pub editor: EditorConstants,
     pub sound: SoundConstants,
     pub mode: Environment,
@@ -77,6 +79,7 @@ async fn get_config_constants(
     State(app_state): State<AppState>,
 ) -> ResponseJson<ApiResponse<ConfigConstants>> {
     let constants = ConfigConstants {
+        executor: ExecutorConstants::new(),
         editor: EditorConstants::new(),
         sound: SoundConstants::new(),
         mode: app_state.mode,
@@ -94,7 +97,7 @@ struct McpServerQuery {
 fn resolve_executor_config(
     query_executor: Option<String>,
     saved_config: &ExecutorConfig,
-) -> Result<ExecutorConfig, String> {
+) -> Result<(ExecutorConfig, McpCapability), String> {
     let executor_config = match query_executor {
         Some(executor_type) => executor_type
             .parse::<ExecutorConfig>()
@@ -102,14 +105,14 @@ fn resolve_executor_config(
         None => saved_config.clone(),
     };
 
-    if !executor_config.supports_mcp() {
+    let Some(mcp) = executor_config.capabilities().mcp else {
         return Err(format!(
             "{} executor does not support MCP configuration",
             executor_config.display_name()
         ));
-    }
+    };
 
-    Ok(executor_config)
+    Ok((executor_config, mcp))
 }
 
 async fn get_mcp_servers(
@@ -121,8 +124,8 @@ async fn get_mcp_servers(
         config.executor.clone()
     };
 
-    let executor_config = match resolve_executor_config(query.executor, &saved_config) {
-        Ok(config) => config,
+    let (executor_config, mcp) = match resolve_executor_config(query.executor, &saved_config) {
+        Ok(resolved) => resolved,
         Err(message) => {
             return ResponseJson(ApiResponse::error(&message));
         }
@@ -136,7 +139,7 @@ async fn get_mcp_servers(
         }
     };
 
-    match read_mcp_servers_from_config(&config_path, &executor_config).await {
+    match read_mcp_servers_from_config(&config_path, &mcp).await {
         Ok(servers) => {
             let response_data = serde_json::json!({
                 "servers": servers,
@@ -161,8 +164,8 @@ async fn update_mcp_servers(
         config.executor.clone()
     };
 
-    let executor_config = match resolve_executor_config(query.executor, &saved_config) {
-        Ok(config) => config,
+    let (executor_config, mcp) = match resolve_executor_config(query.executor, &saved_config) {
+        Ok(resolved) => resolved,
         Err(message) => {
             return ResponseJson(ApiResponse::error(&message));
         }
@@ -176,7 +179,7 @@ async fn update_mcp_servers(
         }
     };
 
-    match update_mcp_servers_in_config(&config_path, &executor_config, new_servers).await {
+    match update_mcp_servers_in_config(&config_path, &mcp, new_servers).await {
         Ok(message) => ResponseJson(ApiResponse::success(message)),
         Err(e) => ResponseJson(ApiResponse::error(&format!(
             "Failed to update MCP servers: {}",
@@ -187,32 +190,47 @@ async fn update_mcp_servers(
 
 async fn update_mcp_servers_in_config(
     file_path: &std::path::Path,
-    executor_config: &ExecutorConfig,
+    mcp: &McpCapability,
     new_servers: HashMap<String, Value>,
 ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    let mcp_path = &mcp.servers_path;
+
     // Ensure parent directory exists
     if let Some(parent) = file_path.parent() {
         fs::create_dir_all(parent).await?;
     }
 
-    // Read existing config file or create empty object if it doesn't exist
-    let file_content = fs::read_to_string(file_path)
-        .await
-        .unwrap_or_else(|_| "{}".to_string());
-    let mut config: Value = serde_json::from_str(&file_content)?;
+    let old_servers = match mcp.config_format {
+        McpConfigFormat::Json => {
+            // Read existing config file or create empty object if it doesn't exist
+            let file_content = fs::read_to_string(file_path)
+                .await
+                .unwrap_or_else(|_| "{}".to_string());
+            let mut config: Value = serde_json::from_str(&file_content)?;
 
-    // Get the attribute path for MCP servers
-    let mcp_path = executor_config.mcp_attribute_path().unwrap();
+            // Get the current server count for comparison
+            let old_servers = get_mcp_servers_from_config_path(&config, mcp_path).len();
 
-    // Get the current server count for comparison
-    let old_servers = get_mcp_servers_from_config_path(&config, &mcp_path).len();
+            // Set the MCP servers using the correct attribute path
+            set_mcp_servers_in_config_path(&mut config, mcp_path, &new_servers)?;
 
-    // Set the MCP servers using the correct attribute path
-    set_mcp_servers_in_config_path(&mut config, &mcp_path, &new_servers)?;
+            // Write the updated config back to file
+            let updated_content = serde_json::to_string_pretty(&config)?;
+            fs::write(file_path, updated_content).await?;
+            old_servers
+        }
+        McpConfigFormat::Toml => {
+            // Edit the document in place so comments and layout survive
+            let file_content = fs::read_to_string(file_path).await.unwrap_or_default();
+            let mut document: DocumentMut = file_content.parse()?;
 
-    // Write the updated config back to file
-    let updated_content = serde_json::to_string_pretty(&config)?;
-    fs::write(file_path, updated_content).await?;
+            let old_servers = get_mcp_servers_from_toml(&document, mcp_path).len();
+            set_mcp_servers_in_toml(&mut document, mcp_path, &new_servers)?;
+
+            fs::write(file_path, document.to_string()).await?;
+            old_servers
+        }
+    };
 
     let new_count = new_servers.len();
     let message = match (old_servers, new_count) {
@@ -230,67 +248,52 @@ async fn update_mcp_servers_in_config(
 
 async fn read_mcp_servers_from_config(
     file_path: &std::path::Path,
-    executor_config: &ExecutorConfig,
+    mcp: &McpCapability,
 ) -> Result<HashMap<String, Value>, Box<dyn std::error::Error + Send + Sync>> {
     // Read the config file, return empty if it doesn't exist
-    let file_content = fs::read_to_string(file_path)
-        .await
-        .unwrap_or_else(|_| "{}".to_string());
-    let config: Value = serde_json::from_str(&file_content)?;
-
-    // Get the attribute path for MCP servers
-    let mcp_path = executor_config.mcp_attribute_path().unwrap();
+    let file_content = fs::read_to_string(file_path).await.ok();
 
     // Get the servers using the correct attribute path
-    let servers = get_mcp_servers_from_config_path(&config, &mcp_path);
+    let servers = match mcp.config_format {
+        McpConfigFormat::Json => {
+            let config: Value = serde_json::from_str(file_content.as_deref().unwrap_or("{}"))?;
+            get_mcp_servers_from_config_path(&config, &mcp.servers_path)
+        }
+        McpConfigFormat::Toml => {
+            let document: DocumentMut = file_content.unwrap_or_default().parse()?;
+            get_mcp_servers_from_toml(&document, &mcp.servers_path)
+        }
+    };
 
     Ok(servers)
 }
 
 /// Helper function to get MCP servers from config using a path
-fn get_mcp_servers_from_config_path(config: &Value, path: &[&str]) -> HashMap<String, Value> {
-    // Special handling for AMP - use flat key structure
-    if path.len() == 2 && path[0] == "amp" && path[1] == "mcpServers" {
-        let flat_key = format!("{}.{}", path[0], path[1]);
-        let current = match config.get(&flat_key) {
+fn get_mcp_servers_from_config_path(config: &Value, path: &[String]) -> HashMap<String, Value> {
+    let mut current = config;
+
+    // Navigate to the target location
+    for part in path {
+        current = match current.get(part) {
             Some(val) => val,
             None => return HashMap::new(),
         };
+    }
 
-        // Extract the servers object
-        match current.as_object() {
-            Some(servers) => servers
-                .iter()
-                .map(|(k, v)| (k.clone(), v.clone()))
-                .collect(),
-            None => HashMap::new(),
-        }
-    } else {
-        let mut current = config;
-
-        // Navigate to the target location
-        for &part in path {
-            current = match current.get(part) {
-                Some(val) => val,
-                None => return HashMap::new(),
-            };
-        }
-
-        // Extract the servers object
-        match current.as_object() {
-            Some(servers) => servers
-                .iter()
-                .map(|(k, v)| (k.clone(), v.clone()))
-                .collect(),
-            None => HashMap::new(),
-        }
+    // Extract the servers object
+    match current.as_object() {
+        Some(servers) => servers
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect(),
+        None => HashMap::new(),
     }
 }
 
 /// Helper function to set MCP servers in config using a path
 fn set_mcp_servers_in_config_path(
     config: &mut Value,
-    path: &[&str],
+    path: &[String],
     servers: &HashMap<String, Value>,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     // Ensure config is an object
@@ -298,25 +301,15 @@ fn set_mcp_servers_in_config_path(
         *config = serde_json::json!({});
     }
 
-    // Special handling for AMP - use flat key structure
-    if path.len() == 2 && path[0] == "amp" && path[1] == "mcpServers" {
-        let flat_key = format!("{}.{}", path[0], path[1]);
-        config
-            .as_object_mut()
-            .unwrap()
-            .insert(flat_key, serde_json::to_value(servers)?);
-        return Ok(());
-    }
-
     let mut current = config;
 
     // Navigate/create the nested structure (all parts except the last)
-    for &part in &path[..path.len() - 1] {
+    for part in &path[..path.len() - 1] {
         if current.get(part).is_none() {
             current
                 .as_object_mut()
                 .unwrap()
-                .insert(part.to_string(), serde_json::json!({}));
+                .insert(part.clone(), serde_json::json!({}));
         }
         current = current.get_mut(part).unwrap();
         if !current.is_object() {
@@ -329,7 +322,238 @@ fn set_mcp_servers_in_config_path(
     current
         .as_object_mut()
         .unwrap()
-        .insert(final_attr.to_string(), serde_json::to_value(servers)?);
+        .insert(final_attr.clone(), serde_json::to_value(servers)?);
 
     Ok(())
 }
+
+/// Helper function to get MCP servers from a TOML document using a path
+fn get_mcp_servers_from_toml(document: &DocumentMut, path: &[String]) -> HashMap<String, Value> {
+    let mut current = document.as_item();
+
+    for part in path {
+        current = match current.get(part) {
+            Some(item) => item,
+            None => return HashMap::new(),
+        };
+    }
+
+    match current.as_table_like() {
+        Some(servers) => servers
+            .iter()
+            .map(|(name, server)| (name.to_string(), toml_item_to_json(server)))
+            .collect(),
+        None => HashMap::new(),
+    }
+}
+
+/// Helper function to set MCP servers in a TOML document using a path.
+///
+/// Servers whose settings are unchanged are left untouched, so their comments
+/// and formatting survive; changed servers are rewritten as `[path.name]`
+/// tables.
+fn set_mcp_servers_in_toml(
+    document: &mut DocumentMut,
+    path: &[String],
+    servers: &HashMap<String, Value>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut current = document.as_table_mut();
+
+    for part in path {
+        let item = current.entry(part).or_insert_with(|| {
+            let mut table = Table::new();
+            table.set_implicit(true);
+            Item::Table(table)
+        });
+        if !item.is_table() {
+            *item = Item::Table(Table::new());
+        }
+        current = item.as_table_mut().unwrap();
+    }
+
+    let removed: Vec<String> = current
+        .iter()
+        .map(|(name, _)| name.to_string())
+        .filter(|name| !servers.contains_key(name))
+        .collect();
+    for name in removed {
+        current.remove(&name);
+    }
+
+    let mut names: Vec<&String> = servers.keys().collect();
+    names.sort();
+    for name in names {
+        let server = &servers[name];
+        if current
+            .get(name)
+            .is_some_and(|existing| toml_item_to_json(existing) == *server)
+        {
+            continue;
+        }
+        let Value::Object(settings) = server else {
+            return Err(format!("MCP server \"{}\" must be an object", name).into());
+        };
+        let mut table = Table::new();
+        for (key, value) in settings {
+            table.insert(key, Item::Value(json_to_toml_value(value)?));
+        }
+        match current.get_mut(name) {
+            // Keep the comments above a server that is being rewritten
+            Some(Item::Table(existing)) => {
+                *table.decor_mut() = existing.decor().clone();
+                *existing = table;
+            }
+            _ => {
+                current.insert(name, Item::Table(table));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn toml_item_to_json(item: &Item) -> Value {
+    match item {
+        Item::None => Value::Null,
+        Item::Value(value) => toml_value_to_json(value),
+        Item::Table(table) => Value::Object(
+            table
+                .iter()
+                .map(|(key, item)| (key.to_string(), toml_item_to_json(item)))
+                .collect(),
+        ),
+        Item::ArrayOfTables(tables) => Value::Array(
+            tables
+                .iter()
+                .map(|table| toml_item_to_json(&Item::Table(table.clone())))
+                .collect(),
+        ),
+    }
+}
+
+fn toml_value_to_json(value: &toml_edit::Value) -> Value {
+    match value {
+        toml_edit::Value::String(s) => Value::String(s.value().clone()),
+        toml_edit::Value::Integer(i) => Value::from(*i.value()),
+        toml_edit::Value::Float(f) => Value::from(*f.value()),
+        toml_edit::Value::Boolean(b) => Value::Bool(*b.value()),
+        toml_edit::Value::Datetime(d) => Value::String(d.value().to_string()),
+        toml_edit::Value::Array(array) => {
+            Value::Array(array.iter().map(toml_value_to_json).collect())
+        }
+        toml_edit::Value::InlineTable(table) => Value::Object(
+            table
+                .iter()
+                .map(|(key, value)| (key.to_string(), toml_value_to_json(value)))
+                .collect(),
+        ),
+    }
+}
+
+fn json_to_toml_value(
+    value: &Value,
+) -> Result<toml_edit::Value, Box<dyn std::error::Error + Send + Sync>> {
+    Ok(match value {
+        Value::Null => return Err("TOML has no null value".into()),
+        Value::Bool(b) => (*b).into(),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => i.into(),
+            None => n.as_f64().ok_or("Number is out of range for TOML")?.into(),
+        },
+        Value::String(s) => s.as_str().into(),
+        Value::Array(values) => {
+            let mut array = toml_edit::Array::new();
+            for value in values {
+                array.push(json_to_toml_value(value)?);
+            }
+            array.into()
+        }
+        Value::Object(entries) => {
+            let mut table = InlineTable::new();
+            for (key, value) in entries {
+                table.insert(key, json_to_toml_value(value)?);
+            }
+            table.into()
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CODEX_CONFIG: &str = r#"# Codex settings
+model = "o3"
+
+# Local tools
+[mcp_servers.docs]
+command = "npx"
+args = ["-y", "docs-mcp"] # pinned by the team
+env = { "API_KEY" = "secret" }
+
+[mcp_servers.old]
+command = "old-server"
+"#;
+
+    fn servers_path() -> Vec<String> {
+        vec!["mcp_servers".to_string()]
+    }
+
+    #[test]
+    fn test_get_mcp_servers_from_toml() {
+        let document: DocumentMut = CODEX_CONFIG.parse().unwrap();
+        let servers = get_mcp_servers_from_toml(&document, &servers_path());
+
+        assert_eq!(servers.len(), 2);
+        assert_eq!(
+            servers["docs"],
+            serde_json::json!({
+                "command": "npx",
+                "args": ["-y", "docs-mcp"],
+                "env": { "API_KEY": "secret" }
+            })
+        );
+    }
+
+    #[test]
+    fn test_set_mcp_servers_in_toml_preserves_comments() {
+        let mut document: DocumentMut = CODEX_CONFIG.parse().unwrap();
+        let mut servers = get_mcp_servers_from_toml(&document, &servers_path());
+        servers.remove("old");
+        servers.insert(
+            "vibe_kanban".to_string(),
+            serde_json::json!({ "command": "npx", "args": ["-y", "vibe-kanban", "--mcp"] }),
+        );
+
+        set_mcp_servers_in_toml(&mut document, &servers_path(), &servers).unwrap();
+        let written = document.to_string();
+
+        assert!(written.starts_with("# Codex settings\nmodel = \"o3\"\n"));
+        assert!(written.contains("# Local tools\n[mcp_servers.docs]"));
+        assert!(written.contains("# pinned by the team"));
+        assert!(!written.contains("old-server"));
+        assert!(written.contains("[mcp_servers.vibe_kanban]"));
+
+        let reparsed: DocumentMut = written.parse().unwrap();
+        assert_eq!(
+            get_mcp_servers_from_toml(&reparsed, &servers_path()),
+            servers
+        );
+    }
+
+    #[test]
+    fn test_set_mcp_servers_in_empty_toml() {
+        let mut document = DocumentMut::new();
+        let servers = HashMap::from([(
+            "vibe_kanban".to_string(),
+            serde_json::json!({ "command": "npx", "env": { "DEBUG": "1" } }),
+        )]);
+
+        set_mcp_servers_in_toml(&mut document, &servers_path(), &servers).unwrap();
+
+        assert_eq!(
+            document.to_string(),
+            "[mcp_servers.vibe_kanban]\ncommand = \"npx\"\nenv = { DEBUG = \"1\" }\n"
+        );
+    }
+}
@@ -163,6 +163,13 @@ pub async fn create_project_from_github(
         setup_script: payload.setup_script,
         dev_script: payload.dev_script,
         cleanup_script: payload.cleanup_script,
+        max_runtime_seconds: payload.max_runtime_seconds,
+        idle_timeout_seconds: payload.idle_timeout_seconds,
+        prompt_template: payload.prompt_template,
+        sandbox_mode: payload.sandbox_mode,
+        memory_limit_mb: payload.memory_limit_mb,
+        cpu_limit_percent: payload.cpu_limit_percent,
+        max_processes: payload.max_processes,
     };
 
     let project_id = Uuid::new_v4();
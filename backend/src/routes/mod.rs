@@ -3,9 +3,14 @@ pub mod config;
 pub mod filesystem;
 pub mod github;
 pub mod health;
+pub mod permissions;
 pub mod personas;
+pub mod pipelines;
 pub mod projects;
 pub mod stream;
+pub mod task_attachments;
+pub mod task_attempt_groups;
 pub mod task_attempts;
 pub mod task_templates;
 pub mod tasks;
+pub mod usage;
@@ -0,0 +1,188 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::Json as ResponseJson,
+    routing::{get, post},
+    Extension, Json, Router,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    models::{
+        permission::{
+            validate_policy, DecidePermissionRequest, PermissionDecisionSource, PermissionPolicy,
+            PermissionRequest, UpdatePermissionPolicy,
+        },
+        project::Project,
+        ApiResponse,
+    },
+};
+
+#[derive(Debug, Deserialize)]
+pub struct PermissionRequestsQuery {
+    pub task_attempt_id: Option<Uuid>,
+    #[serde(default)]
+    pub pending: bool,
+}
+
+pub async fn get_permission_policy(
+    Extension(project): Extension<Project>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<PermissionPolicy>>, StatusCode> {
+    match PermissionPolicy::find_by_project_id(&app_state.db_pool, project.id).await {
+        Ok(policy) => Ok(ResponseJson(ApiResponse::success(policy))),
+        Err(e) => {
+            tracing::error!(
+                "Failed to fetch permission policy for project {}: {}",
+                project.id,
+                e
+            );
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn update_permission_policy(
+    Extension(project): Extension<Project>,
+    State(app_state): State<AppState>,
+    Json(payload): Json<UpdatePermissionPolicy>,
+) -> Result<ResponseJson<ApiResponse<PermissionPolicy>>, StatusCode> {
+    if let Err(message) = validate_policy(&payload) {
+        return Ok(ResponseJson(ApiResponse::error(&message)));
+    }
+
+    match PermissionPolicy::update(&app_state.db_pool, project.id, &payload).await {
+        Ok(policy) => {
+            app_state
+                .track_analytics_event(
+                    "permission_policy_updated",
+                    Some(serde_json::json!({
+                        "project_id": project.id.to_string(),
+                        "rule_count": policy.rules.len(),
+                        "default_decision": policy.default_decision,
+                    })),
+                )
+                .await;
+
+            Ok(ResponseJson(ApiResponse::success(policy)))
+        }
+        Err(e) => {
+            tracing::error!(
+                "Failed to update permission policy for project {}: {}",
+                project.id,
+                e
+            );
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn get_permission_requests(
+    Extension(project): Extension<Project>,
+    State(app_state): State<AppState>,
+    Query(query): Query<PermissionRequestsQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<PermissionRequest>>>, StatusCode> {
+    if let Err(e) = PermissionRequest::expire_overdue(&app_state.db_pool, project.id).await {
+        tracing::error!(
+            "Failed to expire permission requests for project {}: {}",
+            project.id,
+            e
+        );
+        return Err(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    match PermissionRequest::find_by_project_id(
+        &app_state.db_pool,
+        project.id,
+        query.task_attempt_id,
+        query.pending,
+    )
+    .await
+    {
+        Ok(requests) => Ok(ResponseJson(ApiResponse::success(requests))),
+        Err(e) => {
+            tracing::error!(
+                "Failed to fetch permission requests for project {}: {}",
+                project.id,
+                e
+            );
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn decide_permission_request(
+    Extension(permission_request): Extension<PermissionRequest>,
+    State(app_state): State<AppState>,
+    Json(payload): Json<DecidePermissionRequest>,
+) -> Result<ResponseJson<ApiResponse<PermissionRequest>>, StatusCode> {
+    if permission_request.decision.is_some() || permission_request.is_overdue() {
+        return Ok(ResponseJson(ApiResponse::error(
+            "Permission request has already been decided",
+        )));
+    }
+
+    let message = payload
+        .message
+        .as_deref()
+        .map(str::trim)
+        .filter(|message| !message.is_empty());
+
+    match PermissionRequest::decide(
+        &app_state.db_pool,
+        permission_request.id,
+        payload.decision,
+        PermissionDecisionSource::User,
+        None,
+        message,
+    )
+    .await
+    {
+        Ok(Some(decided)) => {
+            app_state
+                .track_analytics_event(
+                    "permission_request_decided",
+                    Some(serde_json::json!({
+                        "project_id": decided.project_id.to_string(),
+                        "tool_name": decided.tool_name,
+                        "decision": decided.decision,
+                    })),
+                )
+                .await;
+
+            Ok(ResponseJson(ApiResponse::success(decided)))
+        }
+        Ok(None) => Ok(ResponseJson(ApiResponse::error(
+            "Permission request has already been decided",
+        ))),
+        Err(e) => {
+            tracing::error!(
+                "Failed to decide permission request {}: {}",
+                permission_request.id,
+                e
+            );
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub fn permissions_project_router() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/projects/:project_id/permissions",
+            get(get_permission_policy).put(update_permission_policy),
+        )
+        .route(
+            "/projects/:project_id/permission-requests",
+            get(get_permission_requests),
+        )
+}
+
+pub fn permission_requests_with_id_router() -> Router<AppState> {
+    Router::new().route(
+        "/projects/:project_id/permission-requests/:request_id/decision",
+        post(decide_permission_request),
+    )
+}
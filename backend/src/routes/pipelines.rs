@@ -0,0 +1,131 @@
+use axum::{
+    extract::State, http::StatusCode, response::Json as ResponseJson, routing::get, Extension,
+    Json, Router,
+};
+
+use crate::{
+    app_state::AppState,
+    models::{
+        pipeline::{validate_stages, CreatePipeline, Pipeline, UpdatePipeline},
+        project::Project,
+        ApiResponse,
+    },
+};
+
+pub async fn get_project_pipelines(
+    Extension(project): Extension<Project>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<Vec<Pipeline>>>, StatusCode> {
+    match Pipeline::find_by_project_id(&app_state.db_pool, project.id).await {
+        Ok(pipelines) => Ok(ResponseJson(ApiResponse::success(pipelines))),
+        Err(e) => {
+            tracing::error!(
+                "Failed to fetch pipelines for project {}: {}",
+                project.id,
+                e
+            );
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn create_pipeline(
+    Extension(project): Extension<Project>,
+    State(app_state): State<AppState>,
+    Json(payload): Json<CreatePipeline>,
+) -> Result<ResponseJson<ApiResponse<Pipeline>>, StatusCode> {
+    if payload.name.trim().is_empty() {
+        return Ok(ResponseJson(ApiResponse::error(
+            "Pipeline name is required",
+        )));
+    }
+    if let Err(message) = validate_stages(&payload.stages) {
+        return Ok(ResponseJson(ApiResponse::error(&message)));
+    }
+
+    match Pipeline::create(&app_state.db_pool, project.id, &payload).await {
+        Ok(pipeline) => {
+            app_state
+                .track_analytics_event(
+                    "pipeline_created",
+                    Some(serde_json::json!({
+                        "project_id": project.id.to_string(),
+                        "pipeline_id": pipeline.id.to_string(),
+                        "stage_count": pipeline.stages.len(),
+                    })),
+                )
+                .await;
+
+            Ok(ResponseJson(ApiResponse::success(pipeline)))
+        }
+        Err(e) => {
+            tracing::error!("Failed to create pipeline: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn get_pipeline(
+    Extension(pipeline): Extension<Pipeline>,
+) -> Result<ResponseJson<ApiResponse<Pipeline>>, StatusCode> {
+    Ok(ResponseJson(ApiResponse::success(pipeline)))
+}
+
+pub async fn update_pipeline(
+    Extension(pipeline): Extension<Pipeline>,
+    State(app_state): State<AppState>,
+    Json(payload): Json<UpdatePipeline>,
+) -> Result<ResponseJson<ApiResponse<Pipeline>>, StatusCode> {
+    if payload
+        .name
+        .as_ref()
+        .is_some_and(|name| name.trim().is_empty())
+    {
+        return Ok(ResponseJson(ApiResponse::error(
+            "Pipeline name is required",
+        )));
+    }
+    if let Some(stages) = &payload.stages {
+        if let Err(message) = validate_stages(stages) {
+            return Ok(ResponseJson(ApiResponse::error(&message)));
+        }
+    }
+
+    match Pipeline::update(&app_state.db_pool, pipeline.id, &payload).await {
+        Ok(pipeline) => Ok(ResponseJson(ApiResponse::success(pipeline))),
+        Err(e) => {
+            tracing::error!("Failed to update pipeline {}: {}", pipeline.id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn delete_pipeline(
+    Extension(pipeline): Extension<Pipeline>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<()>>, StatusCode> {
+    match Pipeline::delete(&app_state.db_pool, pipeline.id).await {
+        Ok(0) => Err(StatusCode::NOT_FOUND),
+        Ok(_) => Ok(ResponseJson(ApiResponse::success(()))),
+        Err(e) => {
+            tracing::error!("Failed to delete pipeline {}: {}", pipeline.id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub fn pipelines_project_router() -> Router<AppState> {
+    Router::new().route(
+        "/projects/:project_id/pipelines",
+        get(get_project_pipelines).post(create_pipeline),
+    )
+}
+
+pub fn pipelines_with_id_router() -> Router<AppState> {
+    Router::new().route(
+        "/projects/:project_id/pipelines/:pipeline_id",
+        get(get_pipeline)
+            .put(update_pipeline)
+            .delete(delete_pipeline),
+    )
+}
@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
 use axum::{
-    extract::{Query, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     response::Json as ResponseJson,
     routing::get,
@@ -11,11 +11,16 @@ use uuid::Uuid;
 
 use crate::{
     app_state::AppState,
+    executor::ExecutorConfig,
     models::{
+        config::{ExecutionLimits, ResourceLimits},
         project::{
             CreateBranch, CreateProject, GitBranch, Project, ProjectWithBranch, SearchMatchType,
             SearchResult, UpdateProject,
         },
+        project_cli_version::{is_valid_version, ProjectCliVersion, SetProjectCliVersion},
+        prompt_template::validate_template,
+        token_usage::UsageSummary,
         ApiResponse,
     },
 };
@@ -101,6 +106,23 @@ pub async fn create_project(
 
     tracing::debug!("Creating project '{}'", payload.name);
 
+    if let Some(template) = &payload.prompt_template {
+        if let Err(message) = validate_template(template) {
+            return Ok(ResponseJson(ApiResponse::error(&message)));
+        }
+    }
+    if let Err(message) = payload.sandbox_mode.unwrap_or_default().check_supported() {
+        return Ok(ResponseJson(ApiResponse::error(&message)));
+    }
+    let resource_limits = ResourceLimits {
+        memory_limit_mb: payload.memory_limit_mb,
+        cpu_limit_percent: payload.cpu_limit_percent,
+        max_processes: payload.max_processes,
+    };
+    if let Err(message) = resource_limits.validate() {
+        return Ok(ResponseJson(ApiResponse::error(&message)));
+    }
+
     // Check if git repo path is already used by another project
     match Project::find_by_git_repo_path(&app_state.db_pool, &payload.git_repo_path).await {
         Ok(Some(_)) => {
@@ -245,10 +267,36 @@ pub async fn update_project(
         setup_script,
         dev_script,
         cleanup_script,
+        max_runtime_seconds,
+        idle_timeout_seconds,
+        prompt_template,
+        sandbox_mode,
+        memory_limit_mb,
+        cpu_limit_percent,
+        max_processes,
     } = payload;
 
     let name = name.unwrap_or(existing_project.name);
     let git_repo_path = git_repo_path.unwrap_or(existing_project.git_repo_path);
+    // A blank template falls back to the built-in one
+    let prompt_template = prompt_template.filter(|template| !template.trim().is_empty());
+    if let Some(template) = &prompt_template {
+        if let Err(message) = validate_template(template) {
+            return Ok(ResponseJson(ApiResponse::error(&message)));
+        }
+    }
+    let sandbox_mode = sandbox_mode.unwrap_or(existing_project.sandbox_mode);
+    if let Err(message) = sandbox_mode.check_supported() {
+        return Ok(ResponseJson(ApiResponse::error(&message)));
+    }
+    let resource_limits = ResourceLimits {
+        memory_limit_mb,
+        cpu_limit_percent,
+        max_processes,
+    };
+    if let Err(message) = resource_limits.validate() {
+        return Ok(ResponseJson(ApiResponse::error(&message)));
+    }
 
     match Project::update(
         &app_state.db_pool,
@@ -258,6 +306,13 @@ pub async fn update_project(
         setup_script,
         dev_script,
         cleanup_script,
+        ExecutionLimits {
+            max_runtime_seconds,
+            idle_timeout_seconds,
+        },
+        prompt_template,
+        sandbox_mode,
+        resource_limits,
     )
     .await
     {
@@ -478,6 +533,114 @@ async fn search_files_in_repo(
     Ok(results)
 }
 
+pub async fn get_project_usage(
+    Extension(project): Extension<Project>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<UsageSummary>>, StatusCode> {
+    match UsageSummary::for_project(&app_state.db_pool, project.id).await {
+        Ok(usage) => Ok(ResponseJson(ApiResponse::success(usage))),
+        Err(e) => {
+            tracing::error!(
+                "Failed to fetch token usage for project {}: {}",
+                project.id,
+                e
+            );
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn get_project_cli_versions(
+    Extension(project): Extension<Project>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<Vec<ProjectCliVersion>>>, StatusCode> {
+    match ProjectCliVersion::find_by_project_id(&app_state.db_pool, project.id).await {
+        Ok(versions) => Ok(ResponseJson(ApiResponse::success(versions))),
+        Err(e) => {
+            tracing::error!(
+                "Failed to fetch CLI versions for project {}: {}",
+                project.id,
+                e
+            );
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn ensure_project_exists(app_state: &AppState, project_id: Uuid) -> Result<(), StatusCode> {
+    match Project::exists(&app_state.db_pool, project_id).await {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to check if project {} exists: {}", project_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn set_project_cli_version(
+    Path((project_id, executor)): Path<(Uuid, String)>,
+    State(app_state): State<AppState>,
+    Json(payload): Json<SetProjectCliVersion>,
+) -> Result<ResponseJson<ApiResponse<ProjectCliVersion>>, StatusCode> {
+    ensure_project_exists(&app_state, project_id).await?;
+
+    let Ok(config) = executor.parse::<ExecutorConfig>() else {
+        return Ok(ResponseJson(ApiResponse::error(&format!(
+            "Unknown executor '{}'",
+            executor
+        ))));
+    };
+    if !config.capabilities().version_pinning {
+        return Ok(ResponseJson(ApiResponse::error(&format!(
+            "{} executor does not support pinning its CLI version",
+            config.display_name()
+        ))));
+    }
+    let version = payload.version.trim();
+    if !is_valid_version(version) {
+        return Ok(ResponseJson(ApiResponse::error(&format!(
+            "Invalid CLI version '{}'",
+            version
+        ))));
+    }
+
+    match ProjectCliVersion::set(&app_state.db_pool, project_id, &config.to_string(), version).await
+    {
+        Ok(pinned) => Ok(ResponseJson(ApiResponse::success(pinned))),
+        Err(e) => {
+            tracing::error!(
+                "Failed to pin {} CLI version for project {}: {}",
+                executor,
+                project_id,
+                e
+            );
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn delete_project_cli_version(
+    Path((project_id, executor)): Path<(Uuid, String)>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<()>>, StatusCode> {
+    ensure_project_exists(&app_state, project_id).await?;
+
+    match ProjectCliVersion::delete(&app_state.db_pool, project_id, &executor).await {
+        Ok(0) => Err(StatusCode::NOT_FOUND),
+        Ok(_) => Ok(ResponseJson(ApiResponse::success(()))),
+        Err(e) => {
+            tracing::error!(
+                "Failed to unpin {} CLI version for project {}: {}",
+                executor,
+                project_id,
+                e
+            );
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
 pub fn projects_base_router() -> Router<AppState> {
     Router::new().route("/projects", get(get_projects).post(create_project))
 }
@@ -497,4 +660,13 @@ pub fn projects_with_id_router() -> Router<AppState> {
         )
         .route("/projects/:id/search", get(search_project_files))
         .route("/projects/:id/open-editor", post(open_project_in_editor))
+        .route("/projects/:id/usage", get(get_project_usage))
+        .route("/projects/:id/cli-versions", get(get_project_cli_versions))
+}
+
+pub fn project_cli_versions_router() -> Router<AppState> {
+    Router::new().route(
+        "/projects/:project_id/cli-versions/:executor",
+        axum::routing::put(set_project_cli_version).delete(delete_project_cli_version),
+    )
 }
@@ -13,12 +13,23 @@ use uuid::Uuid;
 
 use crate::{
     app_state::AppState,
-    executors::gemini::GeminiExecutor,
-    models::execution_process::{ExecutionProcess, ExecutionProcessStatus},
+    executor::ExecutorConfig,
+    executors::planning::capture_plan,
+    models::{
+        execution_process::{ExecutionProcess, ExecutionProcessStatus, ExecutionProcessType},
+        execution_process_input::{merge_inputs, ExecutionProcessInput},
+    },
+    services::LogStreamService,
 };
 
-/// Interval for DB tail polling (ms) - now blazing fast for real-time updates
-const TAIL_INTERVAL_MS: u64 = 100;
+/// Interval for WAL polling (ms)
+const TAIL_INTERVAL_MS: u64 = 50;
+
+/// How long to keep draining a completed process's live log before giving up on it closing
+const FINISH_GRACE_PERIOD: Duration = Duration::from_secs(2);
+
+/// How long to wait for a running process's live log to be registered before giving up on it
+const LIVE_LOG_WAIT: Duration = Duration::from_secs(2);
 
 /// Structured batch data for SSE streaming
 #[derive(Serialize)]
@@ -37,197 +48,64 @@ pub struct StreamQuery {
 /// SSE handler for incremental normalized-logs JSON-Patch streaming
 ///
 /// GET /api/projects/:project_id/execution-processes/:process_id/normalized-logs/stream?since_batch_id=123
+///
+/// Batches come from the process's live log in `LogStreamService`. When there is no live log
+/// (the process finished a while ago, predates a restart or is running in another server
+/// process) the stored stdout is normalized once, sent as a single snapshot and the stream
+/// closed.
 pub async fn normalized_logs_stream(
     Path((_project_id, process_id)): Path<(Uuid, Uuid)>,
     Query(query): Query<StreamQuery>,
     State(app_state): State<AppState>,
 ) -> Sse<impl Stream<Item = Result<Event, axum::Error>>> {
-    // Check if this is a Gemini executor (only executor with streaming support)
-    let is_gemini = match ExecutionProcess::find_by_id(&app_state.db_pool, process_id).await {
-        Ok(Some(process)) => process.executor_type.as_deref() == Some("gemini"),
-        _ => {
-            tracing::warn!(
-                "Failed to find execution process {} for SSE streaming",
-                process_id
-            );
-            false
-        }
-    };
-
-    // Use blazing fast polling interval for Gemini (only streaming executor)
-    let poll_interval = if is_gemini { 50 } else { TAIL_INTERVAL_MS };
-
-    // Stream that yields patches from WAL (fast-path) or DB tail (fallback)
     let stream = async_stream::stream! {
-        // Track previous stdout length and entry count for database polling fallback
-        let mut last_len: usize = 0;
-        let mut last_entry_count: usize = query.since_batch_id.unwrap_or(1) as usize;
-        let mut interval = tokio::time::interval(Duration::from_millis(poll_interval));
-        let mut last_seen_batch_id: u64 = query.since_batch_id.unwrap_or(0); // Cursor for WAL streaming
-
-        // Monotonic batch ID for fallback polling (always start at 1)
-        let since = query.since_batch_id.unwrap_or(1);
-        let mut fallback_batch_id: u64 = since + 1;
-
-        // Fast catch-up phase for resumable streaming
-        if let Some(since_batch) = query.since_batch_id {
-            if !is_gemini {
-                // Load current process state to get all available entries
-                if let Ok(Some(proc)) = ExecutionProcess::find_by_id(&app_state.db_pool, process_id).await {
-                    if let Some(stdout) = &proc.stdout {
-                        // Create executor and normalize logs to get all entries
-                        if let Some(executor) = proc.executor_type
-                            .as_deref()
-                            .unwrap_or("unknown")
-                            .parse::<crate::executor::ExecutorConfig>()
-                            .ok()
-                            .map(|cfg| cfg.create_executor())
-                        {
-                            if let Ok(normalized) = executor.normalize_logs(stdout, &proc.working_directory) {
-                            // Send all entries after since_batch_id immediately
-                            let start_entry = since_batch as usize;
-                            let catch_up_entries = normalized.entries.get(start_entry..).unwrap_or(&[]);
-
-                            for (i, entry) in catch_up_entries.iter().enumerate() {
-                                let batch_data = BatchData {
-                                    batch_id: since_batch + 1 + i as u64,
-                                    patches: vec![serde_json::json!({
-                                        "op": "add",
-                                        "path": "/entries/-",
-                                        "value": entry
-                                    })],
-                                };
-                                yield Ok(Event::default().event("patch").data(serde_json::to_string(&batch_data).unwrap_or_default()));
-                            }
-
-                                // Update cursors to current state
-                                last_entry_count = normalized.entries.len();
-                                fallback_batch_id = since_batch + 1 + catch_up_entries.len() as u64;
-                                last_len = stdout.len();
-                            }
-                        }
-                    }
-                }
-            }
-        }
+        let mut interval = tokio::time::interval(Duration::from_millis(TAIL_INTERVAL_MS));
+        // Cursor for WAL streaming
+        let mut last_seen_batch_id: u64 = query.since_batch_id.unwrap_or(0);
+        // When the process was first seen completed with its live log still open
+        let mut completed_at: Option<tokio::time::Instant> = None;
+        let started_at = tokio::time::Instant::now();
 
         loop {
             interval.tick().await;
 
-            // Check process status first
-            let process_status = match ExecutionProcess::find_by_id(&app_state.db_pool, process_id).await {
-                Ok(Some(proc)) => proc.status,
+            let running = match ExecutionProcess::find_by_id(&app_state.db_pool, process_id).await {
+                Ok(Some(proc)) => proc.status == ExecutionProcessStatus::Running,
                 _ => {
                     tracing::warn!("Execution process {} not found during SSE streaming", process_id);
                     break;
                 }
             };
 
-            if is_gemini {
-                // Gemini streaming: Read from Gemini WAL using cursor
-                let cursor = if last_seen_batch_id == 0 { None } else { Some(last_seen_batch_id) };
-                if let Some(new_batches) = GeminiExecutor::get_wal_batches(process_id, cursor) {
-                    // Send any new batches since last cursor
-                    for batch in &new_batches {
-                        // Send full batch including batch_id for cursor tracking
-                        let batch_data = BatchData {
-                            batch_id: batch.batch_id,
-                            patches: batch.patches.clone(),
-                        };
-                        let json = serde_json::to_string(&batch_data).unwrap_or_default();
-                        yield Ok(Event::default().event("patch").data(json));
-                        // Update cursor to highest batch_id seen
+            let cursor = if last_seen_batch_id == 0 { None } else { Some(last_seen_batch_id) };
+            match LogStreamService::batches_since(process_id, cursor) {
+                Some(read) => {
+                    for batch in read.batches {
                         last_seen_batch_id = batch.batch_id.max(last_seen_batch_id);
+                        yield Ok(patch_event(batch.batch_id, batch.patches));
                     }
-                }
-            } else {
-                // Fallback: Database polling for non-streaming executors
-                // 1. Load the process
-                    let proc = match ExecutionProcess::find_by_id(&app_state.db_pool, process_id)
-                    .await
-                    .ok()
-                    .flatten()
-                {
-                    Some(p) => p,
-                    None => {
-                        tracing::warn!("Execution process {} not found during SSE polling", process_id);
-                        continue;
-                    }
-                };
-
-                // 2. Grab the stdout and check if there's new content
-                let stdout = match proc.stdout {
-                    Some(ref s) if s.len() > last_len && !s[last_len..].trim().is_empty() => s.clone(),
-                    _ => continue, // no new output
-                };
-
-                // 3. Instantiate the right executor
-                let executor = match proc.executor_type
-                    .as_deref()
-                    .unwrap_or("unknown")
-                    .parse::<crate::executor::ExecutorConfig>()
-                    .ok()
-                    .map(|cfg| cfg.create_executor())
-                {
-                    Some(exec) => exec,
-                    None => {
-                        tracing::warn!(
-                            "Unknown executor '{}' for process {}",
-                            proc.executor_type.unwrap_or_default(),
-                            process_id
-                        );
-                        continue;
+                    // Stop streaming once stdout has closed and every batch was sent
+                    if read.finished {
+                        break;
                     }
-                };
-
-                // 4. Normalize logs
-                let normalized = match executor.normalize_logs(&stdout, &proc.working_directory) {
-                    Ok(norm) => norm,
-                    Err(err) => {
-                        tracing::error!(
-                            "Failed to normalize logs for process {}: {}",
-                            process_id,
-                            err
-                        );
-                        continue;
+                    // The process can complete before its output readers flush the last
+                    // batches and the finish patch, so keep draining for a while
+                    if !running {
+                        let since = *completed_at.get_or_insert_with(tokio::time::Instant::now);
+                        if since.elapsed() >= FINISH_GRACE_PERIOD {
+                            break;
+                        }
                     }
-                };
-
-                if last_entry_count > normalized.entries.len() {
-                    continue;
                 }
-
-                // 5. Compute patches for any new entries
-                if last_entry_count >= normalized.entries.len() {
-                    continue;
+                // The live log is registered just after the process record; wait for it
+                None if running && started_at.elapsed() < LIVE_LOG_WAIT => continue,
+                None => {
+                    // No live log to follow: send what is stored, then stop
+                    if let Some(patches) = stored_conversation_patches(&app_state, process_id).await {
+                        yield Ok(patch_event(last_seen_batch_id + 1, patches));
+                    }
+                    break;
                 }
-                let new_entries = [&normalized.entries[last_entry_count]];
-                let patches: Vec<Value> = new_entries
-                    .iter()
-                    .map(|entry| serde_json::json!({
-                        "op": "add",
-                        "path": "/entries/-",
-                        "value": entry
-                    }))
-                    .collect();
-
-                // 6. Emit the batch
-                let batch_data = BatchData {
-                    batch_id: fallback_batch_id - 1,
-                    patches,
-                };
-                let json = serde_json::to_string(&batch_data).unwrap_or_default();
-                yield Ok(Event::default().event("patch").data(json));
-
-                // 7. Update our cursors
-                fallback_batch_id += 1;
-                last_entry_count += 1;
-                last_len = stdout.len();
-            }
-
-            // Stop streaming when process completed
-            if process_status != ExecutionProcessStatus::Running {
-                break;
             }
         }
     };
@@ -235,6 +113,50 @@ pub async fn normalized_logs_stream(
     Sse::new(stream).keep_alive(axum::response::sse::KeepAlive::default())
 }
 
+fn patch_event(batch_id: u64, patches: Vec<Value>) -> Event {
+    let batch_data = BatchData { batch_id, patches };
+    Event::default()
+        .event("patch")
+        .data(serde_json::to_string(&batch_data).unwrap_or_default())
+}
+
+/// Normalize the stored stdout of a process into a single `/entries` snapshot patch
+async fn stored_conversation_patches(app_state: &AppState, process_id: Uuid) -> Option<Vec<Value>> {
+    let process = ExecutionProcess::find_by_id(&app_state.db_pool, process_id)
+        .await
+        .ok()
+        .flatten()?;
+    let stdout = process.stdout.as_deref()?;
+    let executor = process
+        .executor_type
+        .as_deref()?
+        .parse::<ExecutorConfig>()
+        .ok()?
+        .create_executor();
+
+    match executor.normalize_logs(stdout, &process.working_directory) {
+        Ok(mut normalized) => {
+            if process.process_type == ExecutionProcessType::PlanningAgent {
+                capture_plan(&mut normalized.entries);
+            }
+            let inputs =
+                ExecutionProcessInput::find_by_execution_process_id(&app_state.db_pool, process_id)
+                    .await
+                    .unwrap_or_default();
+            merge_inputs(&mut normalized.entries, &inputs);
+            Some(vec![serde_json::json!({
+                "op": "replace",
+                "path": "/entries",
+                "value": normalized.entries
+            })])
+        }
+        Err(e) => {
+            tracing::error!("Failed to normalize logs for process {}: {}", process_id, e);
+            None
+        }
+    }
+}
+
 /// Router exposing `/normalized-logs/stream`
 pub fn stream_router() -> Router<AppState> {
     Router::new().route(
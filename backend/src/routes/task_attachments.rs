@@ -0,0 +1,231 @@
+use std::path::Path;
+
+use axum::{
+    body::Body,
+    extract::{DefaultBodyLimit, Multipart, State},
+    http::{header, StatusCode},
+    response::{Json as ResponseJson, Response},
+    routing::{get, post},
+    Extension, Json, Router,
+};
+
+use crate::{
+    app_state::AppState,
+    models::{
+        project::Project,
+        task::Task,
+        task_attachment::{
+            normalize_repo_path, AttachmentKind, CreateTaskAttachment, TaskAttachment,
+        },
+        ApiResponse,
+    },
+};
+
+/// Largest upload accepted, across all files in one request
+const MAX_UPLOAD_BYTES: usize = 25 * 1024 * 1024;
+
+pub async fn get_task_attachments(
+    Extension(task): Extension<Task>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<Vec<TaskAttachment>>>, StatusCode> {
+    match TaskAttachment::find_by_task_id(&app_state.db_pool, task.id).await {
+        Ok(attachments) => Ok(ResponseJson(ApiResponse::success(attachments))),
+        Err(e) => {
+            tracing::error!("Failed to fetch attachments for task {}: {}", task.id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Attach a log excerpt or a path inside the project repository
+pub async fn create_task_attachment(
+    Extension(project): Extension<Project>,
+    Extension(task): Extension<Task>,
+    State(app_state): State<AppState>,
+    Json(payload): Json<CreateTaskAttachment>,
+) -> Result<ResponseJson<ApiResponse<TaskAttachment>>, StatusCode> {
+    let created = match payload {
+        CreateTaskAttachment::Log { name, content } => {
+            if content.trim().is_empty() {
+                return Ok(ResponseJson(ApiResponse::error(
+                    "Log content cannot be empty",
+                )));
+            }
+            let name = name
+                .map(|name| name.trim().to_string())
+                .filter(|name| !name.is_empty())
+                .unwrap_or_else(|| "log.txt".to_string());
+            TaskAttachment::store(
+                &app_state.db_pool,
+                task.id,
+                AttachmentKind::Log,
+                &name,
+                Some("text/plain"),
+                content.as_bytes(),
+            )
+            .await
+            .map_err(|e| e.to_string())
+        }
+        CreateTaskAttachment::RepoPath { path } => {
+            let Some(path) = normalize_repo_path(&path) else {
+                return Ok(ResponseJson(ApiResponse::error(
+                    "Path must be relative to the repository root",
+                )));
+            };
+            if !Path::new(&project.git_repo_path).join(&path).exists() {
+                return Ok(ResponseJson(ApiResponse::error(&format!(
+                    "{} does not exist in the repository",
+                    path
+                ))));
+            }
+            TaskAttachment::create_repo_path(&app_state.db_pool, task.id, &path)
+                .await
+                .map_err(|e| e.to_string())
+        }
+    };
+
+    match created {
+        Ok(attachment) => {
+            app_state
+                .track_analytics_event(
+                    "task_attachment_created",
+                    Some(serde_json::json!({
+                        "task_id": task.id.to_string(),
+                        "kind": attachment.kind,
+                    })),
+                )
+                .await;
+            Ok(ResponseJson(ApiResponse::success(attachment)))
+        }
+        Err(e) => {
+            tracing::error!("Failed to create attachment for task {}: {}", task.id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Attach uploaded files; images are recognised by their content type
+pub async fn upload_task_attachments(
+    Extension(task): Extension<Task>,
+    State(app_state): State<AppState>,
+    mut multipart: Multipart,
+) -> Result<ResponseJson<ApiResponse<Vec<TaskAttachment>>>, StatusCode> {
+    let mut attachments = Vec::new();
+    loop {
+        let field = match multipart.next_field().await {
+            Ok(Some(field)) => field,
+            Ok(None) => break,
+            Err(e) => return Ok(ResponseJson(ApiResponse::error(&e.body_text()))),
+        };
+        let Some(name) = field.file_name().map(str::to_string) else {
+            continue;
+        };
+        let content_type = field.content_type().map(str::to_string).unwrap_or_else(|| {
+            mime_guess::from_path(&name)
+                .first_or_octet_stream()
+                .to_string()
+        });
+        let content = match field.bytes().await {
+            Ok(content) => content,
+            Err(e) => return Ok(ResponseJson(ApiResponse::error(&e.body_text()))),
+        };
+        let kind = if content_type.starts_with("image/") {
+            AttachmentKind::Image
+        } else {
+            AttachmentKind::File
+        };
+
+        match TaskAttachment::store(
+            &app_state.db_pool,
+            task.id,
+            kind,
+            &name,
+            Some(&content_type),
+            &content,
+        )
+        .await
+        {
+            Ok(attachment) => attachments.push(attachment),
+            Err(e) => {
+                tracing::error!("Failed to store attachment for task {}: {}", task.id, e);
+                return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            }
+        }
+    }
+
+    if attachments.is_empty() {
+        return Ok(ResponseJson(ApiResponse::error("No files were uploaded")));
+    }
+    app_state
+        .track_analytics_event(
+            "task_attachment_created",
+            Some(serde_json::json!({
+                "task_id": task.id.to_string(),
+                "kind": "upload",
+                "count": attachments.len(),
+            })),
+        )
+        .await;
+    Ok(ResponseJson(ApiResponse::success(attachments)))
+}
+
+/// The attachment's content, for previews and downloads
+pub async fn get_task_attachment_content(
+    Extension(project): Extension<Project>,
+    Extension(attachment): Extension<TaskAttachment>,
+) -> Result<Response, StatusCode> {
+    let path = match attachment.kind {
+        AttachmentKind::RepoPath => Path::new(&project.git_repo_path).join(&attachment.path),
+        _ => Path::new(&attachment.path).to_path_buf(),
+    };
+    let content = match tokio::fs::read(&path).await {
+        Ok(content) => content,
+        Err(e) => {
+            tracing::warn!("Failed to read attachment {}: {}", attachment.id, e);
+            return Err(StatusCode::NOT_FOUND);
+        }
+    };
+    let content_type = attachment.content_type.clone().unwrap_or_else(|| {
+        mime_guess::from_path(&path)
+            .first_or_octet_stream()
+            .to_string()
+    });
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, content_type)
+        .body(Body::from(content))
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+pub async fn delete_task_attachment(
+    Extension(attachment): Extension<TaskAttachment>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<()>>, StatusCode> {
+    match attachment.delete(&app_state.db_pool).await {
+        Ok(()) => Ok(ResponseJson(ApiResponse::success(()))),
+        Err(e) => {
+            tracing::error!("Failed to delete attachment {}: {}", attachment.id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub fn task_attachments_list_router() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/projects/:project_id/tasks/:task_id/attachments",
+            get(get_task_attachments).post(create_task_attachment),
+        )
+        .route(
+            "/projects/:project_id/tasks/:task_id/attachments/upload",
+            post(upload_task_attachments).layer(DefaultBodyLimit::max(MAX_UPLOAD_BYTES)),
+        )
+}
+
+pub fn task_attachments_with_id_router() -> Router<AppState> {
+    Router::new().route(
+        "/projects/:project_id/tasks/:task_id/attachments/:attachment_id",
+        get(get_task_attachment_content).delete(delete_task_attachment),
+    )
+}
@@ -0,0 +1,170 @@
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::Json as ResponseJson,
+    routing::{get, post},
+    Extension, Json, Router,
+};
+
+use crate::{
+    app_state::AppState,
+    models::{
+        config::Config,
+        project::Project,
+        task::Task,
+        task_attempt::TaskAttemptError,
+        task_attempt_group::{
+            AttemptGroupComparison, CreateTaskAttemptGroup, PickWinnerRequest, TaskAttemptGroup,
+            WinnerAction,
+        },
+        ApiResponse,
+    },
+    services::AttemptGroupService,
+};
+
+pub async fn get_task_attempt_groups(
+    Extension(task): Extension<Task>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<Vec<TaskAttemptGroup>>>, StatusCode> {
+    match TaskAttemptGroup::find_by_task_id(&app_state.db_pool, task.id).await {
+        Ok(groups) => Ok(ResponseJson(ApiResponse::success(groups))),
+        Err(e) => {
+            tracing::error!("Failed to fetch attempt groups for task {}: {}", task.id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn create_task_attempt_group(
+    Extension(task): Extension<Task>,
+    State(app_state): State<AppState>,
+    Json(payload): Json<CreateTaskAttemptGroup>,
+) -> Result<ResponseJson<ApiResponse<TaskAttemptGroup>>, StatusCode> {
+    match AttemptGroupService::start_race(&app_state, &task, &payload).await {
+        Ok(group) => {
+            app_state
+                .track_analytics_event(
+                    "task_attempt_group_started",
+                    Some(serde_json::json!({
+                        "task_id": task.id.to_string(),
+                        "group_id": group.id.to_string(),
+                        "executors": payload.executors,
+                    })),
+                )
+                .await;
+
+            Ok(ResponseJson(ApiResponse::success(group)))
+        }
+        Err(TaskAttemptError::ValidationError(message)) => {
+            Ok(ResponseJson(ApiResponse::error(&message)))
+        }
+        Err(e) => {
+            tracing::error!("Failed to start attempt group for task {}: {}", task.id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn get_task_attempt_group_comparison(
+    Extension(project): Extension<Project>,
+    Extension(group): Extension<TaskAttemptGroup>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<AttemptGroupComparison>>, StatusCode> {
+    match AttemptGroupService::compare(&app_state, &group, project.id).await {
+        Ok(comparison) => Ok(ResponseJson(ApiResponse::success(comparison))),
+        Err(e) => {
+            tracing::error!("Failed to compare attempt group {}: {}", group.id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn pick_task_attempt_group_winner(
+    Extension(project): Extension<Project>,
+    Extension(group): Extension<TaskAttemptGroup>,
+    State(app_state): State<AppState>,
+    Json(payload): Json<PickWinnerRequest>,
+) -> Result<ResponseJson<ApiResponse<TaskAttemptGroup>>, StatusCode> {
+    // Only pull requests need the user's GitHub credentials
+    let github_token = match &payload.action {
+        WinnerAction::Merge => None,
+        WinnerAction::PullRequest { .. } => {
+            let config = match Config::load(&crate::utils::config_path()) {
+                Ok(config) => config,
+                Err(e) => {
+                    tracing::error!("Failed to load config: {}", e);
+                    return Err(StatusCode::INTERNAL_SERVER_ERROR);
+                }
+            };
+            match config.github.token {
+                Some(token) => Some(config.github.pat.unwrap_or(token)),
+                None => {
+                    return Ok(ResponseJson(ApiResponse::error(
+                        "GitHub authentication not configured. Please sign in with GitHub.",
+                    )));
+                }
+            }
+        }
+    };
+
+    match AttemptGroupService::pick_winner(
+        &app_state,
+        &group,
+        project.id,
+        payload.attempt_id,
+        &payload.action,
+        github_token.as_deref(),
+    )
+    .await
+    {
+        Ok(group) => {
+            app_state
+                .track_analytics_event(
+                    "task_attempt_group_winner_picked",
+                    Some(serde_json::json!({
+                        "task_id": group.task_id.to_string(),
+                        "group_id": group.id.to_string(),
+                        "attempt_id": payload.attempt_id.to_string(),
+                        "action": match payload.action {
+                            WinnerAction::Merge => "merge",
+                            WinnerAction::PullRequest { .. } => "pull_request",
+                        },
+                    })),
+                )
+                .await;
+
+            Ok(ResponseJson(ApiResponse::success(group)))
+        }
+        Err(e) => {
+            tracing::error!(
+                "Failed to pick winner {} for attempt group {}: {}",
+                payload.attempt_id,
+                group.id,
+                e
+            );
+            Ok(ResponseJson(ApiResponse::error(&format!(
+                "Failed to pick winner: {}",
+                e
+            ))))
+        }
+    }
+}
+
+pub fn task_attempt_groups_list_router() -> Router<AppState> {
+    Router::new().route(
+        "/projects/:project_id/tasks/:task_id/attempt-groups",
+        get(get_task_attempt_groups).post(create_task_attempt_group),
+    )
+}
+
+pub fn task_attempt_groups_with_id_router() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/projects/:project_id/tasks/:task_id/attempt-groups/:group_id/comparison",
+            get(get_task_attempt_group_comparison),
+        )
+        .route(
+            "/projects/:project_id/tasks/:task_id/attempt-groups/:group_id/winner",
+            post(pick_task_attempt_group_winner),
+        )
+}
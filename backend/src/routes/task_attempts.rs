@@ -13,23 +13,36 @@ use uuid::Uuid;
 
 use crate::{
     app_state::AppState,
+    command_runner::{CommandError, StopOutcome},
     executor::{
-        ActionType, ExecutorConfig, NormalizedConversation, NormalizedEntry, NormalizedEntryType,
+        ExecutorConfig, NormalizedConversation, NormalizedEntry, NormalizedEntryType, PlanMode,
     },
+    executors::planning::{capture_plan, find_latest_plan},
     middleware::{load_execution_process_with_context_middleware, load_task_attempt_middleware},
     models::{
         config::Config,
         execution_process::{
             ExecutionProcess, ExecutionProcessStatus, ExecutionProcessSummary, ExecutionProcessType,
         },
+        execution_process_input::{
+            merge_inputs, user_message_entry, ExecutionProcessInput, SendInputRequest,
+        },
+        executor_session::ExecutorSession,
+        pipeline::TaskAttemptStage,
         project::Project,
+        prompt_template::{follow_up_prompt, task_prompt, PromptContext, PromptPreview},
+        review_finding::{
+            followup_prompt, ReviewFinding, ReviewFollowUpRequest, StartReviewRequest,
+        },
         task::{Task, TaskStatus},
         task_attempt::{
-            BranchStatus, CreateFollowUpAttempt, CreatePrParams, CreateTaskAttempt, TaskAttempt,
-            TaskAttemptState, WorktreeDiff,
+            BranchStatus, CreateFollowUpAttempt, CreatePrParams, CreateTaskAttempt,
+            ForkTaskAttempt, TaskAttempt, TaskAttemptError, TaskAttemptState, WorktreeDiff,
         },
+        token_usage::{ExecutionProcessUsage, UsageSummary},
         ApiResponse,
     },
+    services::LogStreamService,
 };
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -59,6 +72,7 @@ pub struct ProcessLogsResponse {
     pub command: String,
     pub executor_type: Option<String>,
     pub status: ExecutionProcessStatus,
+    pub retry_of_process_id: Option<Uuid>,
     pub normalized_conversation: NormalizedConversation,
 }
 
@@ -74,6 +88,9 @@ async fn normalize_process_logs(
         .await
         .ok()
         .flatten();
+    let inputs = ExecutionProcessInput::find_by_execution_process_id(db_pool, process.id)
+        .await
+        .unwrap_or_default();
 
     let has_stdout = process
         .stdout
@@ -87,8 +104,10 @@ async fn normalize_process_logs(
         .unwrap_or(false);
 
     if !has_stdout && !has_stderr {
+        let mut entries = vec![];
+        merge_inputs(&mut entries, &inputs);
         return NormalizedConversation {
-            entries: vec![],
+            entries,
             session_id: None,
             executor_type: process
                 .executor_type
@@ -132,6 +151,9 @@ async fn normalize_process_logs(
             };
             if let Ok(normalized) = executor.normalize_logs(stdout, &working_dir_path) {
                 stdout_entries = normalized.entries;
+                if process.process_type == ExecutionProcessType::PlanningAgent {
+                    capture_plan(&mut stdout_entries);
+                }
             }
         }
     }
@@ -166,6 +188,7 @@ async fn normalize_process_logs(
         (None, Some(_)) => std::cmp::Ordering::Greater,
         (None, None) => std::cmp::Ordering::Equal,
     });
+    merge_inputs(&mut all_entries, &inputs);
     let executor_type = if process.process_type == ExecutionProcessType::SetupScript {
         "setup-script".to_string()
     } else {
@@ -210,6 +233,7 @@ pub async fn get_task_attempt_all_logs(
             command: process.command.clone(),
             executor_type: process.executor_type.clone(),
             status: process.status.clone(),
+            retry_of_process_id: process.retry_of_process_id,
             normalized_conversation,
         });
     }
@@ -230,6 +254,28 @@ pub async fn get_task_attempts(
     }
 }
 
+/// Reject a model or extra arguments the executor has no way to pass to its CLI
+fn validate_run_options(
+    executor: &ExecutorConfig,
+    model: Option<&str>,
+    extra_args: Option<&[String]>,
+) -> Result<(), String> {
+    let capabilities = executor.capabilities();
+    if model.is_some_and(|model| !model.trim().is_empty()) && !capabilities.model_selection {
+        return Err(format!(
+            "{} executor does not support model selection",
+            executor.display_name()
+        ));
+    }
+    if extra_args.is_some_and(|args| !args.is_empty()) && !capabilities.extra_args {
+        return Err(format!(
+            "{} executor does not accept extra CLI arguments",
+            executor.display_name()
+        ));
+    }
+    Ok(())
+}
+
 pub async fn create_task_attempt(
     Extension(_project): Extension<Project>,
     Extension(task): Extension<Task>,
@@ -237,6 +283,27 @@ pub async fn create_task_attempt(
     Json(payload): Json<CreateTaskAttempt>,
 ) -> Result<ResponseJson<ApiResponse<TaskAttempt>>, StatusCode> {
     let executor_string = payload.executor.as_ref().map(|exec| exec.to_string());
+    let plan_first = payload.plan_first.unwrap_or(false);
+
+    if let Some(executor) = payload
+        .executor
+        .as_ref()
+        .and_then(|executor| executor.parse::<ExecutorConfig>().ok())
+    {
+        if plan_first && executor.capabilities().plan_mode == PlanMode::Unsupported {
+            return Ok(ResponseJson(ApiResponse::error(&format!(
+                "{} executor cannot run a planning phase",
+                executor.display_name()
+            ))));
+        }
+        if let Err(message) = validate_run_options(
+            &executor,
+            payload.model.as_deref(),
+            payload.extra_args.as_deref(),
+        ) {
+            return Ok(ResponseJson(ApiResponse::error(&message)));
+        }
+    }
 
     match TaskAttempt::create(&app_state.db_pool, &payload, task.id).await {
         Ok(attempt) => {
@@ -247,6 +314,7 @@ pub async fn create_task_attempt(
                         "task_id": task.id.to_string(),
                         "executor_type": executor_string.as_deref().unwrap_or("default"),
                         "attempt_id": attempt.id.to_string(),
+                        "plan_first": plan_first,
                     })),
                 )
                 .await;
@@ -263,6 +331,7 @@ pub async fn create_task_attempt(
                     attempt_id,
                     task_id,
                     project_id,
+                    plan_first,
                 )
                 .await
                 {
@@ -276,6 +345,9 @@ pub async fn create_task_attempt(
 
             Ok(ResponseJson(ApiResponse::success(attempt)))
         }
+        Err(TaskAttemptError::ValidationError(message)) => {
+            Ok(ResponseJson(ApiResponse::error(&message)))
+        }
         Err(e) => {
             tracing::error!("Failed to create task attempt: {}", e);
             Err(StatusCode::INTERNAL_SERVER_ERROR)
@@ -283,6 +355,171 @@ pub async fn create_task_attempt(
     }
 }
 
+pub async fn get_task_attempt_stages(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<Vec<TaskAttemptStage>>>, StatusCode> {
+    match TaskAttemptStage::find_by_task_attempt_id(&app_state.db_pool, task_attempt.id).await {
+        Ok(stages) => Ok(ResponseJson(ApiResponse::success(stages))),
+        Err(e) => {
+            tracing::error!(
+                "Failed to fetch pipeline stages for task attempt {}: {}",
+                task_attempt.id,
+                e
+            );
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn get_task_attempt_review_findings(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<Vec<ReviewFinding>>>, StatusCode> {
+    match ReviewFinding::find_latest_by_task_attempt_id(&app_state.db_pool, task_attempt.id).await {
+        Ok(findings) => Ok(ResponseJson(ApiResponse::success(findings))),
+        Err(e) => {
+            tracing::error!(
+                "Failed to fetch review findings for task attempt {}: {}",
+                task_attempt.id,
+                e
+            );
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn start_task_attempt_review(
+    Extension(project): Extension<Project>,
+    Extension(task): Extension<Task>,
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(app_state): State<AppState>,
+    Json(payload): Json<StartReviewRequest>,
+) -> Result<ResponseJson<ApiResponse<()>>, StatusCode> {
+    if app_state.has_running_execution(task_attempt.id).await {
+        return Ok(ResponseJson(ApiResponse::error(
+            "Wait for the running process to finish before starting a review",
+        )));
+    }
+
+    // Fall back to the configured reviewer, then to the attempt's own executor
+    let executor = match payload.executor {
+        Some(executor) => Some(executor),
+        None => match app_state.get_review_executor().await {
+            Some(config) => Some(config.to_string()),
+            None => task_attempt.executor.clone(),
+        },
+    };
+    let config = match executor.as_deref().map(str::parse::<ExecutorConfig>) {
+        Some(Ok(config)) => config,
+        Some(Err(message)) => return Ok(ResponseJson(ApiResponse::error(&message))),
+        None => {
+            return Ok(ResponseJson(ApiResponse::error(
+                "No review executor configured",
+            )))
+        }
+    };
+
+    match crate::services::ProcessService::start_review_agent(
+        &app_state.db_pool,
+        &app_state,
+        task_attempt.id,
+        task.id,
+        project.id,
+        config,
+    )
+    .await
+    {
+        Ok(true) => {
+            app_state
+                .track_analytics_event(
+                    "task_attempt_review_started",
+                    Some(serde_json::json!({
+                        "task_id": task.id.to_string(),
+                        "project_id": project.id.to_string(),
+                        "attempt_id": task_attempt.id.to_string(),
+                    })),
+                )
+                .await;
+
+            Ok(ResponseJson(ApiResponse::success(())))
+        }
+        Ok(false) => Ok(ResponseJson(ApiResponse::error(
+            "This attempt has no changes to review",
+        ))),
+        Err(e) => {
+            tracing::error!(
+                "Failed to start review for task attempt {}: {}",
+                task_attempt.id,
+                e
+            );
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Send the selected review findings back to the attempt's coding session as a follow-up
+pub async fn follow_up_on_review_findings(
+    Extension(project): Extension<Project>,
+    Extension(task): Extension<Task>,
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(app_state): State<AppState>,
+    Json(payload): Json<ReviewFollowUpRequest>,
+) -> Result<ResponseJson<ApiResponse<FollowUpResponse>>, StatusCode> {
+    let findings =
+        match ReviewFinding::find_by_task_attempt_id(&app_state.db_pool, task_attempt.id).await {
+            Ok(findings) => findings,
+            Err(e) => {
+                tracing::error!(
+                    "Failed to fetch review findings for task attempt {}: {}",
+                    task_attempt.id,
+                    e
+                );
+                return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            }
+        };
+    let selected: Vec<ReviewFinding> = findings
+        .into_iter()
+        .filter(|finding| payload.finding_ids.contains(&finding.id))
+        .collect();
+    if selected.is_empty() {
+        return Ok(ResponseJson(ApiResponse::error(
+            "Select at least one finding of this attempt",
+        )));
+    }
+
+    match TaskAttempt::start_followup_execution(
+        &app_state.db_pool,
+        &app_state,
+        task_attempt.id,
+        task.id,
+        project.id,
+        &followup_prompt(&selected),
+    )
+    .await
+    {
+        Ok(actual_attempt_id) => {
+            let created_new_attempt = actual_attempt_id != task_attempt.id;
+            Ok(ResponseJson(ApiResponse::success(FollowUpResponse {
+                message: format!("Follow-up started for {} review findings", selected.len()),
+                actual_attempt_id,
+                created_new_attempt,
+            })))
+        }
+        Err(TaskAttemptError::ValidationError(message)) => {
+            Ok(ResponseJson(ApiResponse::error(&message)))
+        }
+        Err(e) => {
+            tracing::error!(
+                "Failed to start review follow-up for task attempt {}: {}",
+                task_attempt.id,
+                e
+            );
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
 pub async fn get_task_attempt_diff(
     Extension(project): Extension<Project>,
     Extension(task): Extension<Task>,
@@ -635,7 +872,7 @@ pub async fn stop_all_execution_processes(
     // Stop all running processes
     for process in processes {
         match app_state.stop_running_execution_by_id(process.id).await {
-            Ok(true) => {
+            Ok(Some(_)) => {
                 stopped_count += 1;
 
                 // Update the execution process status in the database
@@ -653,7 +890,7 @@ pub async fn stop_all_execution_processes(
                     // Process stopped successfully
                 }
             }
-            Ok(false) => {
+            Ok(None) => {
                 // Process was not running, which is fine
             }
             Err(e) => {
@@ -678,6 +915,7 @@ pub async fn stop_all_execution_processes(
     Ok(ResponseJson(ApiResponse::success(())))
 }
 
+/// Stop a running execution process, reporting the signal that ended it
 #[axum::debug_handler]
 pub async fn stop_execution_process(
     Extension(_project): Extension<Project>,
@@ -685,13 +923,13 @@ pub async fn stop_execution_process(
     Extension(_task_attempt): Extension<TaskAttempt>,
     Extension(execution_process): Extension<ExecutionProcess>,
     State(app_state): State<AppState>,
-) -> Result<ResponseJson<ApiResponse<()>>, StatusCode> {
+) -> Result<ResponseJson<ApiResponse<Option<StopOutcome>>>, StatusCode> {
     // Stop the specific execution process
-    let stopped = match app_state
+    let outcome = match app_state
         .stop_running_execution_by_id(execution_process.id)
         .await
     {
-        Ok(stopped) => stopped,
+        Ok(outcome) => outcome,
         Err(e) => {
             tracing::error!(
                 "Failed to stop execution process {}: {}",
@@ -702,9 +940,9 @@ pub async fn stop_execution_process(
         }
     };
 
-    if !stopped {
-        return Ok(ResponseJson(ApiResponse::success(())));
-    }
+    let Some(outcome) = outcome else {
+        return Ok(ResponseJson(ApiResponse::success(None)));
+    };
 
     // Update the execution process status in the database
     if let Err(e) = ExecutionProcess::update_completion(
@@ -719,9 +957,113 @@ pub async fn stop_execution_process(
         return Err(StatusCode::INTERNAL_SERVER_ERROR);
     }
 
-    // Process stopped successfully
+    tracing::info!(
+        "Stopped execution process {} with {:?}",
+        execution_process.id,
+        outcome.signal
+    );
 
-    Ok(ResponseJson(ApiResponse::success(())))
+    Ok(ResponseJson(ApiResponse::success(Some(outcome))))
+}
+
+/// Send a message to the stdin of a running agent
+pub async fn send_execution_process_input(
+    Extension(_project): Extension<Project>,
+    Extension(task): Extension<Task>,
+    Extension(task_attempt): Extension<TaskAttempt>,
+    Extension(execution_process): Extension<ExecutionProcess>,
+    State(app_state): State<AppState>,
+    Json(payload): Json<SendInputRequest>,
+) -> Result<ResponseJson<ApiResponse<ExecutionProcessInput>>, StatusCode> {
+    let message = payload.message.trim();
+    if message.is_empty() {
+        return Ok(ResponseJson(ApiResponse::error("Message cannot be empty")));
+    }
+    if execution_process.status != ExecutionProcessStatus::Running {
+        return Ok(ResponseJson(ApiResponse::error(
+            "Execution process is not running",
+        )));
+    }
+
+    let config = execution_process
+        .executor_type
+        .as_deref()
+        .and_then(|executor| executor.parse::<ExecutorConfig>().ok());
+    let Some(input) = config
+        .as_ref()
+        .filter(|config| config.capabilities().live_input)
+        .and_then(|config| config.create_executor().format_input(message))
+    else {
+        let executor = config.map_or_else(|| "This executor".to_string(), |c| c.to_string());
+        return Ok(ResponseJson(ApiResponse::error(&format!(
+            "{} does not accept input while running",
+            executor
+        ))));
+    };
+
+    match app_state
+        .write_to_running_execution(execution_process.id, &input)
+        .await
+    {
+        Ok(true) => {}
+        Ok(false) => {
+            return Ok(ResponseJson(ApiResponse::error(
+                "Execution process is not running",
+            )))
+        }
+        Err(CommandError::StdinUnavailable) => {
+            return Ok(ResponseJson(ApiResponse::error(
+                "This execution process does not accept input",
+            )))
+        }
+        Err(e) => {
+            tracing::error!(
+                "Failed to send input to execution process {}: {}",
+                execution_process.id,
+                e
+            );
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    }
+
+    // Show the message in the live conversation, remembering where it went
+    let entry_index = LogStreamService::push_entry(
+        execution_process.id,
+        &user_message_entry(message, chrono::Utc::now()),
+    )
+    .map(|index| index as i64);
+
+    match ExecutionProcessInput::create(
+        &app_state.db_pool,
+        execution_process.id,
+        message,
+        entry_index,
+    )
+    .await
+    {
+        Ok(input) => {
+            app_state
+                .track_analytics_event(
+                    "execution_process_input_sent",
+                    Some(serde_json::json!({
+                        "task_id": task.id.to_string(),
+                        "attempt_id": task_attempt.id.to_string(),
+                        "executor_type": execution_process.executor_type,
+                    })),
+                )
+                .await;
+
+            Ok(ResponseJson(ApiResponse::success(input)))
+        }
+        Err(e) => {
+            tracing::error!(
+                "Failed to store input for execution process {}: {}",
+                execution_process.id,
+                e
+            );
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
 }
 
 #[derive(serde::Deserialize)]
@@ -766,6 +1108,44 @@ pub async fn create_followup_attempt(
     State(app_state): State<AppState>,
     Json(payload): Json<CreateFollowUpAttempt>,
 ) -> Result<ResponseJson<ApiResponse<FollowUpResponse>>, StatusCode> {
+    // A model or arguments given with the follow-up become the attempt's new defaults
+    if payload.model.is_some() || payload.extra_args.is_some() {
+        if let Some(executor) = task_attempt
+            .executor
+            .as_ref()
+            .and_then(|executor| executor.parse::<ExecutorConfig>().ok())
+        {
+            if let Err(message) = validate_run_options(
+                &executor,
+                payload.model.as_deref(),
+                payload.extra_args.as_deref(),
+            ) {
+                return Ok(ResponseJson(ApiResponse::error(&message)));
+            }
+        }
+
+        let current = TaskAttempt::find_run_options(&app_state.db_pool, task_attempt.id)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        let model = match &payload.model {
+            Some(model) if model.trim().is_empty() => None,
+            Some(model) => Some(model.trim()),
+            None => current.model.as_deref(),
+        };
+        let extra_args = payload.extra_args.as_ref().unwrap_or(&current.extra_args);
+        if let Err(e) =
+            TaskAttempt::set_run_options(&app_state.db_pool, task_attempt.id, model, extra_args)
+                .await
+        {
+            tracing::error!(
+                "Failed to update run options for task attempt {}: {}",
+                task_attempt.id,
+                e
+            );
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    }
+
     // Start follow-up execution synchronously to catch errors
     match TaskAttempt::start_followup_execution(
         &app_state.db_pool,
@@ -794,6 +1174,9 @@ pub async fn create_followup_attempt(
                 created_new_attempt,
             })))
         }
+        Err(TaskAttemptError::ValidationError(message)) => {
+            Ok(ResponseJson(ApiResponse::error(&message)))
+        }
         Err(e) => {
             tracing::error!(
                 "Failed to start follow-up execution for task attempt {}: {}",
@@ -900,80 +1283,36 @@ pub async fn get_task_attempt_execution_state(
     }
 }
 
-/// Find plan content with context by searching through multiple processes in the same attempt
+/// Find the latest plan produced in an attempt, by a planning phase or the legacy Claude plan
+/// executor
 async fn find_plan_content_with_context(
     pool: &SqlitePool,
     attempt_id: Uuid,
 ) -> Result<String, StatusCode> {
-    // Get all execution processes for this attempt
-    let execution_processes =
-        match ExecutionProcess::find_by_task_attempt_id(pool, attempt_id).await {
-            Ok(processes) => processes,
-            Err(e) => {
-                tracing::error!(
-                    "Failed to fetch execution processes for attempt {}: {}",
-                    attempt_id,
-                    e
-                );
-                return Err(StatusCode::INTERNAL_SERVER_ERROR);
-            }
-        };
-
-    // Look for claudeplan processes (most recent first)
-    for claudeplan_process in execution_processes
-        .iter()
-        .rev()
-        .filter(|p| p.executor_type.as_deref() == Some("claude-plan"))
-    {
-        if let Some(stdout) = &claudeplan_process.stdout {
-            if !stdout.trim().is_empty() {
-                // Create executor and normalize logs
-                let executor_config = ExecutorConfig::ClaudePlan;
-                let executor = executor_config.create_executor();
-
-                // Use working directory for normalization
-                let working_dir_path =
-                    match std::fs::canonicalize(&claudeplan_process.working_directory) {
-                        Ok(canonical_path) => canonical_path.to_string_lossy().to_string(),
-                        Err(_) => claudeplan_process.working_directory.clone(),
-                    };
-
-                // Normalize logs and extract plan content
-                match executor.normalize_logs(stdout, &working_dir_path) {
-                    Ok(normalized_conversation) => {
-                        // Search for plan content in the normalized conversation
-                        if let Some(plan_content) = normalized_conversation
-                            .entries
-                            .iter()
-                            .rev()
-                            .find_map(|entry| {
-                                if let NormalizedEntryType::ToolUse {
-                                    action_type: ActionType::PlanPresentation { plan },
-                                    ..
-                                } = &entry.entry_type
-                                {
-                                    Some(plan.clone())
-                                } else {
-                                    None
-                                }
-                            })
-                        {
-                            return Ok(plan_content);
-                        }
-                    }
-                    Err(_) => {
-                        continue;
-                    }
-                }
-            }
+    match find_latest_plan(pool, attempt_id).await {
+        Ok(Some(plan_content)) => Ok(plan_content),
+        Ok(None) => {
+            tracing::error!(
+                "No plan content found in any process in attempt {}",
+                attempt_id
+            );
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) => {
+            tracing::error!(
+                "Failed to fetch execution processes for attempt {}: {}",
+                attempt_id,
+                e
+            );
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
         }
     }
+}
 
-    tracing::error!(
-        "No claudeplan content found in any process in attempt {}",
-        attempt_id
-    );
-    Err(StatusCode::NOT_FOUND)
+#[derive(Debug, Deserialize, TS)]
+#[ts(export)]
+pub struct ApprovePlanRequest {
+    pub executor: Option<String>, // Executor implementing the plan (defaults to the planning one)
 }
 
 pub async fn approve_plan(
@@ -981,6 +1320,7 @@ pub async fn approve_plan(
     Extension(task): Extension<Task>,
     Extension(task_attempt): Extension<TaskAttempt>,
     State(app_state): State<AppState>,
+    Json(payload): Json<ApprovePlanRequest>,
 ) -> Result<ResponseJson<ApiResponse<FollowUpResponse>>, StatusCode> {
     let current_task = &task;
 
@@ -1017,6 +1357,79 @@ pub async fn approve_plan(
         );
     }
 
+    // Implement the plan with the chosen executor, on top of the same base branch; the
+    // planning executor's model and arguments carry over when it implements too
+    let run_options = match payload.executor {
+        None => TaskAttempt::find_run_options(&app_state.db_pool, task_attempt.id)
+            .await
+            .unwrap_or_default(),
+        Some(_) => Default::default(),
+    };
+    let executor = payload.executor.or_else(|| {
+        match task_attempt
+            .executor
+            .as_deref()
+            .map(str::parse::<ExecutorConfig>)
+        {
+            // Executors that only plan implement through their regular counterpart
+            Some(Ok(config)) if config.capabilities().plan_mode == PlanMode::Always => {
+                Some(config.implementer().to_string())
+            }
+            _ => task_attempt.executor.clone(),
+        }
+    });
+    let attempt_payload = CreateTaskAttempt {
+        executor: executor.clone(),
+        base_branch: Some(task_attempt.base_branch.clone()),
+        plan_first: None,
+        pipeline_id: None,
+        model: run_options.model,
+        extra_args: Some(run_options.extra_args),
+    };
+    let attempt = match TaskAttempt::create(&app_state.db_pool, &attempt_payload, new_task.id).await
+    {
+        Ok(attempt) => attempt,
+        Err(e) => {
+            tracing::error!("Failed to create attempt for approved plan: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    app_state
+        .track_analytics_event(
+            "plan_approved",
+            Some(serde_json::json!({
+                "task_id": task.id.to_string(),
+                "new_task_id": new_task.id.to_string(),
+                "planning_executor": task_attempt.executor,
+                "executor_type": executor.as_deref().unwrap_or("default"),
+            })),
+        )
+        .await;
+
+    // Start execution asynchronously (don't block the response)
+    let app_state_clone = app_state.clone();
+    let attempt_id = attempt.id;
+    let project_id = project.id;
+    tokio::spawn(async move {
+        if let Err(e) = TaskAttempt::start_execution(
+            &app_state_clone.db_pool,
+            &app_state_clone,
+            attempt_id,
+            new_task_id,
+            project_id,
+            false,
+        )
+        .await
+        {
+            tracing::error!(
+                "Failed to start execution for task attempt {}: {}",
+                attempt_id,
+                e
+            );
+        }
+    });
+
     Ok(ResponseJson(ApiResponse::success(FollowUpResponse {
         message: format!("Plan approved and new task created: {}", new_task.title),
         actual_attempt_id: new_task_id, // Return the new task ID
@@ -1050,6 +1463,221 @@ pub async fn get_task_attempt_children(
     }
 }
 
+pub async fn get_task_attempt_sessions(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<Vec<ExecutorSession>>>, StatusCode> {
+    match ExecutorSession::find_by_task_attempt_id(&app_state.db_pool, task_attempt.id).await {
+        Ok(sessions) => Ok(ResponseJson(ApiResponse::success(sessions))),
+        Err(e) => {
+            tracing::error!(
+                "Failed to fetch executor sessions for task attempt {}: {}",
+                task_attempt.id,
+                e
+            );
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Branch a new attempt off one of this attempt's earlier executions and resume its session
+pub async fn fork_task_attempt(
+    Extension(project): Extension<Project>,
+    Extension(task): Extension<Task>,
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(app_state): State<AppState>,
+    Json(payload): Json<ForkTaskAttempt>,
+) -> Result<ResponseJson<ApiResponse<TaskAttempt>>, StatusCode> {
+    let pool = &app_state.db_pool;
+    let session = match ExecutorSession::find_by_id(pool, payload.executor_session_id).await {
+        Ok(Some(session)) if session.task_attempt_id == task_attempt.id => session,
+        Ok(_) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!(
+                "Failed to fetch executor session {}: {}",
+                payload.executor_session_id,
+                e
+            );
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    if payload.prompt.trim().is_empty() {
+        return Ok(ResponseJson(ApiResponse::error(
+            "A prompt is required to fork an execution",
+        )));
+    }
+    if session.session_id.is_none() {
+        return Ok(ResponseJson(ApiResponse::error(
+            "This execution has no agent session to resume",
+        )));
+    }
+
+    let executor = match ExecutionProcess::find_by_id(pool, session.execution_process_id).await {
+        Ok(process) => process
+            .and_then(|process| process.executor_type)
+            .and_then(|executor| executor.parse::<ExecutorConfig>().ok()),
+        Err(e) => {
+            tracing::error!(
+                "Failed to fetch execution process {}: {}",
+                session.execution_process_id,
+                e
+            );
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+    match executor {
+        Some(executor) if executor.capabilities().session_forking => {}
+        Some(executor) => {
+            return Ok(ResponseJson(ApiResponse::error(&format!(
+                "{} executor cannot resume an earlier session",
+                executor.display_name()
+            ))))
+        }
+        None => {
+            return Ok(ResponseJson(ApiResponse::error(
+                "This execution was not run by an agent",
+            )))
+        }
+    }
+
+    let fork = match TaskAttempt::fork(pool, &task_attempt, &session).await {
+        Ok(fork) => fork,
+        Err(TaskAttemptError::ValidationError(message)) => {
+            return Ok(ResponseJson(ApiResponse::error(&message)))
+        }
+        Err(e) => {
+            tracing::error!(
+                "Failed to fork task attempt {} from session {}: {}",
+                task_attempt.id,
+                session.id,
+                e
+            );
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    app_state
+        .track_analytics_event(
+            "task_attempt_forked",
+            Some(serde_json::json!({
+                "task_id": task.id.to_string(),
+                "attempt_id": fork.id.to_string(),
+                "forked_from_attempt_id": task_attempt.id.to_string(),
+            })),
+        )
+        .await;
+
+    // The fork's first follow-up resumes a copy of the session it was forked from
+    match TaskAttempt::start_followup_execution(
+        pool,
+        &app_state,
+        fork.id,
+        task.id,
+        project.id,
+        &payload.prompt,
+    )
+    .await
+    {
+        Ok(_) => Ok(ResponseJson(ApiResponse::success(fork))),
+        Err(TaskAttemptError::ValidationError(message)) => {
+            Ok(ResponseJson(ApiResponse::error(&message)))
+        }
+        Err(e) => {
+            tracing::error!(
+                "Failed to start execution on forked attempt {}: {}",
+                fork.id,
+                e
+            );
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn get_task_attempt_usage(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<UsageSummary>>, StatusCode> {
+    match UsageSummary::for_task_attempt(&app_state.db_pool, task_attempt.id).await {
+        Ok(usage) => Ok(ResponseJson(ApiResponse::success(usage))),
+        Err(e) => {
+            tracing::error!(
+                "Failed to fetch token usage for task attempt {}: {}",
+                task_attempt.id,
+                e
+            );
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn get_execution_process_usage(
+    Extension(execution_process): Extension<ExecutionProcess>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<Option<ExecutionProcessUsage>>>, StatusCode> {
+    match ExecutionProcessUsage::find_by_execution_process_id(
+        &app_state.db_pool,
+        execution_process.id,
+    )
+    .await
+    {
+        Ok(usage) => Ok(ResponseJson(ApiResponse::success(usage))),
+        Err(e) => {
+            tracing::error!(
+                "Failed to fetch token usage for execution process {}: {}",
+                execution_process.id,
+                e
+            );
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PromptPreviewQuery {
+    pub follow_up: Option<String>,
+}
+
+/// The exact prompt the attempt's agent would receive, for a new run or a follow-up message
+pub async fn get_task_attempt_prompt_preview(
+    Extension(project): Extension<Project>,
+    Extension(task): Extension<Task>,
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(app_state): State<AppState>,
+    Query(query): Query<PromptPreviewQuery>,
+) -> Result<ResponseJson<ApiResponse<PromptPreview>>, StatusCode> {
+    let mut context =
+        match PromptContext::load(&app_state.db_pool, &task, &task_attempt.worktree_path).await {
+            Ok(context) => context,
+            Err(e) => {
+                tracing::error!(
+                    "Failed to load prompt context for task attempt {}: {}",
+                    task_attempt.id,
+                    e
+                );
+                return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            }
+        };
+
+    // Attachments are referenced the way the attempt's executor expects
+    if let Some(config) = task_attempt
+        .executor
+        .as_deref()
+        .and_then(|executor| executor.parse::<ExecutorConfig>().ok())
+    {
+        context.attachment_delivery = config.create_executor().attachment_delivery();
+    }
+
+    let prompt = match query.follow_up.as_deref() {
+        Some(message) => follow_up_prompt(&project, &context, message),
+        None => task_prompt(&project, &context),
+    };
+    Ok(ResponseJson(ApiResponse::success(PromptPreview {
+        prompt,
+        uses_default_template: project.prompt_template.is_none(),
+    })))
+}
+
 pub fn task_attempts_list_router(_state: AppState) -> Router<AppState> {
     Router::new().route(
         "/projects/:project_id/tasks/:task_id/attempts",
@@ -1097,12 +1725,36 @@ pub fn task_attempts_with_id_router(_state: AppState) -> Router<AppState> {
             "/projects/:project_id/tasks/:task_id/attempts/:attempt_id/stop",
             post(stop_all_execution_processes),
         )
+        .route(
+            "/projects/:project_id/tasks/:task_id/attempts/:attempt_id/stages",
+            get(get_task_attempt_stages),
+        )
+        .route(
+            "/projects/:project_id/tasks/:task_id/attempts/:attempt_id/review",
+            post(start_task_attempt_review),
+        )
+        .route(
+            "/projects/:project_id/tasks/:task_id/attempts/:attempt_id/review-findings",
+            get(get_task_attempt_review_findings),
+        )
+        .route(
+            "/projects/:project_id/tasks/:task_id/attempts/:attempt_id/review-findings/follow-up",
+            post(follow_up_on_review_findings),
+        )
         .merge(
             Router::new()
                 .route(
                     "/projects/:project_id/tasks/:task_id/attempts/:attempt_id/execution-processes/:process_id/stop",
                     post(stop_execution_process),
                 )
+                .route(
+                    "/projects/:project_id/tasks/:task_id/attempts/:attempt_id/execution-processes/:process_id/input",
+                    post(send_execution_process_input),
+                )
+                .route(
+                    "/projects/:project_id/tasks/:task_id/attempts/:attempt_id/execution-processes/:process_id/usage",
+                    get(get_execution_process_usage),
+                )
                 .route_layer(from_fn_with_state(_state.clone(), load_execution_process_with_context_middleware))
         )
         .route(
@@ -1129,6 +1781,22 @@ pub fn task_attempts_with_id_router(_state: AppState) -> Router<AppState> {
             "/projects/:project_id/tasks/:task_id/attempts/:attempt_id/children",
             get(get_task_attempt_children),
         )
+        .route(
+            "/projects/:project_id/tasks/:task_id/attempts/:attempt_id/usage",
+            get(get_task_attempt_usage),
+        )
+        .route(
+            "/projects/:project_id/tasks/:task_id/attempts/:attempt_id/sessions",
+            get(get_task_attempt_sessions),
+        )
+        .route(
+            "/projects/:project_id/tasks/:task_id/attempts/:attempt_id/fork",
+            post(fork_task_attempt),
+        )
+        .route(
+            "/projects/:project_id/tasks/:task_id/attempts/:attempt_id/prompt-preview",
+            get(get_task_attempt_prompt_preview),
+        )
         .merge(
             Router::new()
                 .route(
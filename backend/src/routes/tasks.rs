@@ -10,7 +10,9 @@ use crate::{
     models::{
         project::Project,
         task::{CreateTask, CreateTaskAndStart, Task, TaskWithAttemptStatus, UpdateTask},
+        task_attachment::TaskAttachment,
         task_attempt::{CreateTaskAttempt, TaskAttempt},
+        token_usage::UsageSummary,
         ApiResponse,
     },
 };
@@ -113,6 +115,10 @@ pub async fn create_task_and_start(
     let attempt_payload = CreateTaskAttempt {
         executor: executor_string.clone(),
         base_branch: None, // Not supported in task creation endpoint, only in task attempts
+        plan_first: None,
+        pipeline_id: None,
+        model: None,
+        extra_args: None,
     };
 
     match TaskAttempt::create(&app_state.db_pool, &attempt_payload, task_id).await {
@@ -149,6 +155,7 @@ pub async fn create_task_and_start(
                     attempt_id,
                     task_id,
                     project.id,
+                    false,
                 )
                 .await
                 {
@@ -261,6 +268,7 @@ pub async fn delete_task(
             if rows_affected == 0 {
                 Err(StatusCode::NOT_FOUND)
             } else {
+                TaskAttachment::delete_stored_files(task.id).await;
                 Ok(ResponseJson(ApiResponse::success(())))
             }
         }
@@ -271,6 +279,19 @@ pub async fn delete_task(
     }
 }
 
+pub async fn get_task_usage(
+    Extension(task): Extension<Task>,
+    State(app_state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<UsageSummary>>, StatusCode> {
+    match UsageSummary::for_task(&app_state.db_pool, task.id).await {
+        Ok(usage) => Ok(ResponseJson(ApiResponse::success(usage))),
+        Err(e) => {
+            tracing::error!("Failed to fetch token usage for task {}: {}", task.id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
 pub fn tasks_project_router() -> Router<AppState> {
     use axum::routing::post;
 
@@ -286,8 +307,10 @@ pub fn tasks_project_router() -> Router<AppState> {
 }
 
 pub fn tasks_with_id_router() -> Router<AppState> {
-    Router::new().route(
-        "/projects/:project_id/tasks/:task_id",
-        get(get_task).put(update_task).delete(delete_task),
-    )
+    Router::new()
+        .route(
+            "/projects/:project_id/tasks/:task_id",
+            get(get_task).put(update_task).delete(delete_task),
+        )
+        .route("/projects/:project_id/tasks/:task_id/usage", get(get_task_usage))
 }
@@ -0,0 +1,52 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::Json as ResponseJson,
+    routing::get,
+    Router,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    models::{token_usage::UsageSummary, ApiResponse},
+};
+
+#[derive(Debug, Deserialize)]
+pub struct UsageQuery {
+    /// Limit the breakdown to a single project
+    pub project_id: Option<Uuid>,
+}
+
+pub async fn get_usage_by_executor(
+    State(app_state): State<AppState>,
+    Query(query): Query<UsageQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<UsageSummary>>>, StatusCode> {
+    match UsageSummary::by_executor(&app_state.db_pool, query.project_id).await {
+        Ok(usage) => Ok(ResponseJson(ApiResponse::success(usage))),
+        Err(e) => {
+            tracing::error!("Failed to fetch token usage by executor: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn get_usage_by_day(
+    State(app_state): State<AppState>,
+    Query(query): Query<UsageQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<UsageSummary>>>, StatusCode> {
+    match UsageSummary::by_day(&app_state.db_pool, query.project_id).await {
+        Ok(usage) => Ok(ResponseJson(ApiResponse::success(usage))),
+        Err(e) => {
+            tracing::error!("Failed to fetch token usage by day: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub fn usage_router() -> Router<AppState> {
+    Router::new()
+        .route("/usage/executors", get(get_usage_by_executor))
+        .route("/usage/daily", get(get_usage_by_day))
+}
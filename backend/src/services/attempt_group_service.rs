@@ -0,0 +1,398 @@
+use std::str::FromStr;
+
+use chrono::Utc;
+use sqlx::SqlitePool;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    execution_monitor::cleanup_attempt_worktrees,
+    executor::ExecutorConfig,
+    models::{
+        execution_process::{ExecutionProcess, ExecutionProcessStatus, ExecutionProcessType},
+        task::{Task, TaskStatus},
+        task_attempt::{CreatePrParams, CreateTaskAttempt, TaskAttempt, TaskAttemptError},
+        task_attempt_group::{
+            AttemptComparison, AttemptGroupComparison, CreateTaskAttemptGroup, DiffStats,
+            TaskAttemptGroup, WinnerAction,
+        },
+        token_usage::UsageSummary,
+    },
+};
+
+/// Service running several executors against the same task and resolving the race
+pub struct AttemptGroupService;
+
+impl AttemptGroupService {
+    /// Create one attempt per executor, record them as a group and start them all
+    pub async fn start_race(
+        app_state: &AppState,
+        task: &Task,
+        data: &CreateTaskAttemptGroup,
+    ) -> Result<TaskAttemptGroup, TaskAttemptError> {
+        if data.executors.len() < 2 {
+            return Err(TaskAttemptError::ValidationError(
+                "A race needs at least two executors".to_string(),
+            ));
+        }
+        for executor in &data.executors {
+            ExecutorConfig::from_str(executor).map_err(TaskAttemptError::ValidationError)?;
+        }
+
+        let pool = &app_state.db_pool;
+        let mut attempts = Vec::with_capacity(data.executors.len());
+        for executor in &data.executors {
+            let attempt = TaskAttempt::create(
+                pool,
+                &CreateTaskAttempt {
+                    executor: Some(executor.clone()),
+                    base_branch: data.base_branch.clone(),
+                    plan_first: None,
+                    pipeline_id: None,
+                    model: None,
+                    extra_args: None,
+                },
+                task.id,
+            )
+            .await?;
+            attempts.push(attempt);
+        }
+
+        let attempt_ids: Vec<Uuid> = attempts.iter().map(|a| a.id).collect();
+        let group = TaskAttemptGroup::create(pool, task.id, &attempt_ids).await?;
+        info!(
+            "Started attempt group {} for task {} with {} executors",
+            group.id,
+            task.id,
+            attempts.len()
+        );
+
+        for attempt in attempts {
+            let app_state = app_state.clone();
+            let task_id = task.id;
+            let project_id = task.project_id;
+            tokio::spawn(async move {
+                if let Err(e) = TaskAttempt::start_execution(
+                    &app_state.db_pool,
+                    &app_state,
+                    attempt.id,
+                    task_id,
+                    project_id,
+                    false,
+                )
+                .await
+                {
+                    tracing::error!(
+                        "Failed to start execution for task attempt {}: {}",
+                        attempt.id,
+                        e
+                    );
+                }
+            });
+        }
+
+        Ok(group)
+    }
+
+    /// Summarise every attempt in the group: outcome, timing, diff size and token usage
+    pub async fn compare(
+        app_state: &AppState,
+        group: &TaskAttemptGroup,
+        project_id: Uuid,
+    ) -> Result<AttemptGroupComparison, TaskAttemptError> {
+        let pool = &app_state.db_pool;
+        let mut attempts = Vec::new();
+
+        for attempt_id in TaskAttemptGroup::attempt_ids(pool, group.id).await? {
+            let Some(attempt) = TaskAttempt::find_by_id(pool, attempt_id).await? else {
+                continue;
+            };
+
+            let processes = ExecutionProcess::find_by_task_attempt_id(pool, attempt_id).await?;
+            let agent = processes.iter().rev().find(|p| {
+                matches!(
+                    p.process_type,
+                    ExecutionProcessType::CodingAgent | ExecutionProcessType::PlanningAgent
+                )
+            });
+            let duration_seconds = agent.map(|p| {
+                let end = p.completed_at.unwrap_or_else(Utc::now);
+                (end - p.started_at).num_milliseconds() as f64 / 1000.0
+            });
+
+            // Worktrees of finished races may already be cleaned up
+            let diff =
+                match TaskAttempt::get_diff(pool, attempt_id, group.task_id, project_id).await {
+                    Ok(diff) => Some(DiffStats::from_diff(&diff)),
+                    Err(e) => {
+                        warn!("Failed to get diff for task attempt {}: {}", attempt_id, e);
+                        None
+                    }
+                };
+
+            attempts.push(AttemptComparison {
+                attempt_id,
+                executor: attempt.executor,
+                branch: attempt.branch,
+                status: agent.map(|p| p.status.clone()),
+                exit_code: agent.and_then(|p| p.exit_code),
+                duration_seconds,
+                diff,
+                usage: UsageSummary::for_task_attempt(pool, attempt_id).await?,
+                is_winner: group.winner_attempt_id == Some(attempt_id),
+            });
+        }
+
+        Ok(AttemptGroupComparison {
+            group: group.clone(),
+            attempts,
+        })
+    }
+
+    /// Merge or open a PR for the winning attempt, stop the others and remove their worktrees
+    pub async fn pick_winner(
+        app_state: &AppState,
+        group: &TaskAttemptGroup,
+        project_id: Uuid,
+        winner_id: Uuid,
+        action: &WinnerAction,
+        github_token: Option<&str>,
+    ) -> Result<TaskAttemptGroup, TaskAttemptError> {
+        let pool = &app_state.db_pool;
+
+        let attempt_ids = TaskAttemptGroup::attempt_ids(pool, group.id).await?;
+        if !attempt_ids.contains(&winner_id) {
+            return Err(TaskAttemptError::ValidationError(format!(
+                "Attempt {} is not part of group {}",
+                winner_id, group.id
+            )));
+        }
+        let losers: Vec<Uuid> = attempt_ids
+            .into_iter()
+            .filter(|id| *id != winner_id)
+            .collect();
+
+        // Claiming the group first keeps concurrent picks from landing two winners
+        if !TaskAttemptGroup::claim_winner(pool, group.id, winner_id).await? {
+            return Err(TaskAttemptError::ValidationError(
+                "A winner has already been picked for this group".to_string(),
+            ));
+        }
+
+        // The losers keep running until the winner's changes have landed, so a failed merge or
+        // PR releases the claim and leaves the whole race to pick from again
+        if let Err(e) =
+            Self::land_winner(pool, group, project_id, winner_id, action, github_token).await
+        {
+            if let Err(release_error) =
+                TaskAttemptGroup::release_winner(pool, group.id, winner_id).await
+            {
+                warn!(
+                    "Failed to release winner of group {}: {}",
+                    group.id, release_error
+                );
+            }
+            return Err(e);
+        }
+
+        for loser in &losers {
+            Self::stop_running_processes(app_state, *loser).await;
+        }
+
+        if let Err(e) = cleanup_attempt_worktrees(pool, group.task_id, &losers).await {
+            warn!(
+                "Failed to clean up losing worktrees for group {}: {}",
+                group.id, e
+            );
+        }
+
+        TaskAttemptGroup::find_by_id(pool, group.id)
+            .await?
+            .ok_or(TaskAttemptError::TaskNotFound)
+    }
+
+    /// Merge the winning attempt's changes or open a PR for them
+    async fn land_winner(
+        pool: &SqlitePool,
+        group: &TaskAttemptGroup,
+        project_id: Uuid,
+        winner_id: Uuid,
+        action: &WinnerAction,
+        github_token: Option<&str>,
+    ) -> Result<(), TaskAttemptError> {
+        match action {
+            WinnerAction::Merge => {
+                TaskAttempt::merge_changes(pool, winner_id, group.task_id, project_id).await?;
+                Task::update_status(pool, group.task_id, project_id, TaskStatus::Done).await?;
+            }
+            WinnerAction::PullRequest {
+                title,
+                body,
+                base_branch,
+            } => {
+                let github_token = github_token.ok_or_else(|| {
+                    TaskAttemptError::ValidationError(
+                        "GitHub authentication not configured".to_string(),
+                    )
+                })?;
+                TaskAttempt::create_github_pr(
+                    pool,
+                    CreatePrParams {
+                        attempt_id: winner_id,
+                        task_id: group.task_id,
+                        project_id,
+                        github_token,
+                        title,
+                        body: body.as_deref(),
+                        base_branch: base_branch.as_deref(),
+                    },
+                )
+                .await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn stop_running_processes(app_state: &AppState, attempt_id: Uuid) {
+        let processes =
+            match ExecutionProcess::find_by_task_attempt_id(&app_state.db_pool, attempt_id).await {
+                Ok(processes) => processes,
+                Err(e) => {
+                    warn!(
+                        "Failed to fetch execution processes for attempt {}: {}",
+                        attempt_id, e
+                    );
+                    return;
+                }
+            };
+
+        for process in processes
+            .iter()
+            .filter(|p| p.status == ExecutionProcessStatus::Running)
+        {
+            match app_state.stop_running_execution_by_id(process.id).await {
+                Ok(Some(_)) => {
+                    if let Err(e) = ExecutionProcess::update_completion(
+                        &app_state.db_pool,
+                        process.id,
+                        ExecutionProcessStatus::Killed,
+                        None,
+                    )
+                    .await
+                    {
+                        warn!("Failed to update execution process status: {}", e);
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => warn!("Failed to stop execution process {}: {}", process.id, e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        app_state::{ExecutionType, RunningExecution},
+        command_runner::CommandRunner,
+        test_utils::{
+            insert_attempt, insert_project, insert_running_process, insert_task, test_app_state,
+        },
+    };
+
+    #[tokio::test]
+    async fn test_failed_merge_leaves_losers_running() {
+        let app_state = test_app_state().await;
+        let pool = &app_state.db_pool;
+
+        // The repository does not exist, so merging the winner fails
+        let project_id = insert_project(pool, "/nonexistent/vibe-kanban-repo").await;
+        let task_id = insert_task(pool, project_id).await;
+        let winner = insert_attempt(pool, task_id, "/nonexistent/a", "vk-a", "echo").await;
+        let loser = insert_attempt(pool, task_id, "/nonexistent/b", "vk-b", "echo").await;
+        let group = TaskAttemptGroup::create(pool, task_id, &[winner, loser])
+            .await
+            .unwrap();
+
+        let process_id = insert_running_process(pool, loser).await;
+        let child = CommandRunner::new()
+            .command("sleep")
+            .arg("30")
+            .start()
+            .await
+            .unwrap();
+        app_state
+            .add_running_execution(
+                process_id,
+                RunningExecution {
+                    task_attempt_id: loser,
+                    _execution_type: ExecutionType::CodingAgent,
+                    child,
+                },
+            )
+            .await;
+
+        let result = AttemptGroupService::pick_winner(
+            &app_state,
+            &group,
+            project_id,
+            winner,
+            &WinnerAction::Merge,
+            None,
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert!(app_state.has_running_execution(loser).await);
+        let group = TaskAttemptGroup::find_by_id(pool, group.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(group.winner_attempt_id, None);
+
+        app_state
+            .stop_running_execution_by_id(process_id)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_winner_already_claimed_is_not_landed_again() {
+        let app_state = test_app_state().await;
+        let pool = &app_state.db_pool;
+
+        let project_id = insert_project(pool, "/nonexistent/vibe-kanban-repo").await;
+        let task_id = insert_task(pool, project_id).await;
+        let first = insert_attempt(pool, task_id, "/nonexistent/a", "vk-a", "echo").await;
+        let second = insert_attempt(pool, task_id, "/nonexistent/b", "vk-b", "echo").await;
+        // Loaded before another request picked the first attempt
+        let stale = TaskAttemptGroup::create(pool, task_id, &[first, second])
+            .await
+            .unwrap();
+        assert!(TaskAttemptGroup::claim_winner(pool, stale.id, first)
+            .await
+            .unwrap());
+
+        let result = AttemptGroupService::pick_winner(
+            &app_state,
+            &stale,
+            project_id,
+            second,
+            &WinnerAction::Merge,
+            None,
+        )
+        .await;
+
+        assert!(matches!(
+            result,
+            Err(TaskAttemptError::ValidationError(message)) if message.contains("already been picked")
+        ));
+        let group = TaskAttemptGroup::find_by_id(pool, stale.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(group.winner_attempt_id, Some(first));
+    }
+}
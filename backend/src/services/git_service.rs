@@ -457,6 +457,26 @@ impl GitService {
         Ok(final_commit.id().to_string())
     }
 
+    /// Hard-reset a worktree branch to `commit`, e.g. one recorded on another attempt's branch
+    pub fn reset_worktree_to_commit(
+        &self,
+        worktree_path: &Path,
+        commit: &str,
+    ) -> Result<(), GitServiceError> {
+        // Worktrees share the main repository's objects, so any attempt's commits are reachable
+        let worktree_repo = Repository::open(worktree_path)?;
+        let oid = git2::Oid::from_str(commit)?;
+        let target = worktree_repo.find_commit(oid)?;
+        worktree_repo.reset(target.as_object(), git2::ResetType::Hard, None)?;
+
+        info!(
+            "Reset worktree {} to commit {}",
+            worktree_path.display(),
+            commit
+        );
+        Ok(())
+    }
+
     /// Get enhanced diff for task attempts (from merge commit or worktree)
     pub fn get_enhanced_diff(
         &self,
@@ -1370,4 +1390,55 @@ mod tests {
         let branch_name = git_service.get_default_branch_name().unwrap();
         assert_eq!(branch_name, "main");
     }
+
+    #[test]
+    fn test_reset_worktree_to_commit_from_other_branch() {
+        let (temp_dir, _repo) = create_test_repo();
+        let git_service = GitService::new(temp_dir.path()).unwrap();
+        let worktrees = TempDir::new().unwrap();
+        let original = worktrees.path().join("original");
+        let fork = worktrees.path().join("fork");
+
+        git_service
+            .create_worktree("original", &original, None)
+            .unwrap();
+        std::fs::write(original.join("notes.txt"), "first direction").unwrap();
+        let original_repo = Repository::open(&original).unwrap();
+        let mut index = original_repo.index().unwrap();
+        index
+            .add_all(["*"].iter(), git2::IndexAddOption::DEFAULT, None)
+            .unwrap();
+        index.write().unwrap();
+        let tree = original_repo
+            .find_tree(index.write_tree().unwrap())
+            .unwrap();
+        let signature = original_repo.signature().unwrap();
+        let parent = original_repo.head().unwrap().peel_to_commit().unwrap();
+        let commit = original_repo
+            .commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                "Agent run",
+                &tree,
+                &[&parent],
+            )
+            .unwrap();
+
+        git_service
+            .create_worktree("fork", &fork, Some("main"))
+            .unwrap();
+        assert!(!fork.join("notes.txt").exists());
+
+        git_service
+            .reset_worktree_to_commit(&fork, &commit.to_string())
+            .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(fork.join("notes.txt")).unwrap(),
+            "first direction"
+        );
+        assert!(git_service
+            .reset_worktree_to_commit(&fork, "not-a-commit")
+            .is_err());
+    }
 }
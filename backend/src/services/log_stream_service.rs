@@ -0,0 +1,511 @@
+//! Per-process write-ahead log of normalized conversation patches
+//!
+//! Executor stdout is fed through the process's [`IncrementalNormalizer`] as it arrives and the
+//! resulting JSON-patch batches are kept here, so SSE clients can follow (and resume) any
+//! running process without re-parsing its log.
+
+use std::{collections::HashMap, sync::Mutex, time::Duration};
+
+use chrono::{DateTime, Utc};
+use json_patch::{Patch, PatchOperation};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::executor::{IncrementalNormalizer, NormalizedEntry};
+
+/// Number of batches that triggers folding older batches into a snapshot
+const WAL_COMPACTION_THRESHOLD: usize = 40;
+/// Batches kept verbatim after compaction for smooth incremental updates
+const WAL_RECENT_BATCHES: usize = 3;
+/// How long a finished log stays readable so connected clients can drain it
+const FINISHED_RETENTION: Duration = Duration::from_secs(60);
+
+lazy_static::lazy_static! {
+    /// Write-Ahead Log: Maps execution_process_id → log state
+    static ref LOG_WAL_MAP: Mutex<HashMap<Uuid, ProcessLog>> = Mutex::new(HashMap::new());
+}
+
+/// A batch of JSON patches for streaming
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatchBatch {
+    /// Monotonic batch identifier for cursor-based streaming
+    pub batch_id: u64,
+    /// Array of JSON Patch operations (RFC 6902 format)
+    pub patches: Vec<Value>,
+    /// ISO 8601 timestamp when this batch was created
+    pub timestamp: String,
+}
+
+/// Batches read from the WAL after a cursor
+#[derive(Debug, Clone)]
+pub struct WalRead {
+    pub batches: Vec<PatchBatch>,
+    /// Stdout has closed; no further batches will be added
+    pub finished: bool,
+}
+
+struct ProcessLog {
+    normalizer: Option<Box<dyn IncrementalNormalizer>>,
+    partial_line: String,
+    batches: Vec<PatchBatch>,
+    next_batch_id: u64,
+    /// Conversation state with every compacted batch applied
+    compacted: Value,
+    finished: bool,
+    /// When the process last produced output, for idle timeouts
+    last_output_at: DateTime<Utc>,
+    /// Entries added alongside the normalizer's (e.g. messages sent to the process), by
+    /// position in the conversation, ascending
+    injected: Vec<(usize, Value)>,
+    /// Number of entries the normalizer has produced
+    normalized_entries: usize,
+}
+
+impl ProcessLog {
+    fn new(normalizer: Option<Box<dyn IncrementalNormalizer>>) -> Self {
+        Self {
+            normalizer,
+            partial_line: String::new(),
+            batches: Vec::new(),
+            next_batch_id: 1,
+            compacted: serde_json::json!({ "entries": [] }),
+            finished: false,
+            last_output_at: Utc::now(),
+            injected: Vec::new(),
+            normalized_entries: 0,
+        }
+    }
+
+    fn push_batch(&mut self, patches: Vec<Value>) {
+        if patches.is_empty() {
+            return;
+        }
+
+        self.batches.push(PatchBatch {
+            batch_id: self.next_batch_id,
+            patches,
+            timestamp: Utc::now().to_rfc3339(),
+        });
+        self.next_batch_id += 1;
+
+        if self.batches.len() >= WAL_COMPACTION_THRESHOLD {
+            self.compact();
+        }
+    }
+
+    fn push_line(&mut self, line: &str) {
+        let line = line.trim_end_matches('\r');
+        if line.trim().is_empty() {
+            return;
+        }
+        if let Some(normalizer) = self.normalizer.as_mut() {
+            let patches = normalizer.push_line(line);
+            self.push_normalized(patches);
+        }
+    }
+
+    /// Push patches produced by the normalizer, which numbers entries without knowing about
+    /// injected ones
+    fn push_normalized(&mut self, patches: Vec<Value>) {
+        let patches = patches
+            .into_iter()
+            .map(|operation| self.remap_operation(operation))
+            .collect();
+        self.push_batch(patches);
+    }
+
+    /// Append an entry that did not come from the normalizer, returning its position
+    fn push_injected(&mut self, entry: Value) -> usize {
+        let position = self.normalized_entries + self.injected.len();
+        self.injected.push((position, entry.clone()));
+        self.push_batch(vec![serde_json::json!({
+            "op": "add",
+            "path": "/entries/-",
+            "value": entry
+        })]);
+        position
+    }
+
+    /// Rewrite a normalizer operation's entry index into a conversation position, keeping
+    /// track of how adds and removes move the injected entries
+    fn remap_operation(&mut self, mut operation: Value) -> Value {
+        let op = operation
+            .get("op")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string();
+        let Some(path) = operation.get("path").and_then(Value::as_str) else {
+            return operation;
+        };
+
+        // A full replace from the normalizer must not drop the injected entries
+        if path == "/entries" {
+            if let Some(normalized) = operation.get("value").and_then(Value::as_array) {
+                self.normalized_entries = normalized.len();
+                let mut entries = normalized.clone();
+                for (position, entry) in &self.injected {
+                    entries.insert((*position).min(entries.len()), entry.clone());
+                }
+                operation["value"] = Value::Array(entries);
+            }
+            return operation;
+        }
+
+        let Some(rest) = path.strip_prefix("/entries/") else {
+            return operation;
+        };
+        let (index, suffix) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+        if index == "-" {
+            if op == "add" && suffix.is_empty() {
+                self.normalized_entries += 1;
+            }
+            return operation;
+        }
+        let Ok(index) = index.parse::<usize>() else {
+            return operation;
+        };
+
+        let mut position = index;
+        for (injected, _) in &self.injected {
+            if *injected <= position {
+                position += 1;
+            } else {
+                break;
+            }
+        }
+
+        if suffix.is_empty() {
+            match op.as_str() {
+                "add" => {
+                    self.normalized_entries += 1;
+                    for (injected, _) in self.injected.iter_mut() {
+                        if *injected >= position {
+                            *injected += 1;
+                        }
+                    }
+                }
+                // Replacing one past the end appends
+                "replace" if index >= self.normalized_entries => {
+                    self.normalized_entries = index + 1;
+                }
+                "remove" => {
+                    self.normalized_entries = self.normalized_entries.saturating_sub(1);
+                    for (injected, _) in self.injected.iter_mut() {
+                        if *injected > position {
+                            *injected -= 1;
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        operation["path"] = Value::from(format!("/entries/{}{}", position, suffix));
+        operation
+    }
+
+    /// Losslessly fold all but the most recent batches into a single snapshot batch
+    ///
+    /// The snapshot takes the id of the newest batch it replaces, so a client whose cursor
+    /// falls inside the compacted range receives the snapshot (an idempotent replace of
+    /// `/entries`) rather than silently missing updates.
+    fn compact(&mut self) {
+        let compact_count = self.batches.len().saturating_sub(WAL_RECENT_BATCHES);
+        if compact_count <= 1 {
+            return;
+        }
+
+        let compacted: Vec<PatchBatch> = self.batches.drain(..compact_count).collect();
+        for batch in &compacted {
+            for operation in &batch.patches {
+                apply_operation(&mut self.compacted, operation);
+            }
+        }
+
+        let last = compacted.last().expect("compacted at least two batches");
+        let snapshot = PatchBatch {
+            batch_id: last.batch_id,
+            patches: vec![serde_json::json!({
+                "op": "replace",
+                "path": "/entries",
+                "value": self.compacted["entries"].clone()
+            })],
+            timestamp: last.timestamp.clone(),
+        };
+        self.batches.insert(0, snapshot);
+
+        tracing::debug!(
+            "Compacted {} log batches into a snapshot at batch {}",
+            compact_count,
+            last.batch_id
+        );
+    }
+}
+
+/// Apply a single patch operation to the snapshot document
+///
+/// A `replace` one past the end of `/entries` appends, matching how the frontend applies the
+/// same patches.
+fn apply_operation(document: &mut Value, operation: &Value) {
+    let Ok(parsed) = serde_json::from_value::<PatchOperation>(operation.clone()) else {
+        tracing::warn!("Skipping malformed patch operation: {}", operation);
+        return;
+    };
+    if json_patch::patch(document, &Patch(vec![parsed])).is_ok() {
+        return;
+    }
+
+    if operation.get("op").and_then(Value::as_str) == Some("replace") {
+        let mut as_add = operation.clone();
+        as_add["op"] = Value::from("add");
+        if let Ok(parsed) = serde_json::from_value::<PatchOperation>(as_add) {
+            if json_patch::patch(document, &Patch(vec![parsed])).is_ok() {
+                return;
+            }
+        }
+    }
+    tracing::warn!("Failed to apply patch during compaction: {}", operation);
+}
+
+/// Live normalized-log streams for running execution processes
+pub struct LogStreamService;
+
+impl LogStreamService {
+    /// Start a log for an execution process, normalizing its stdout with `normalizer`
+    pub fn start(execution_process_id: Uuid, normalizer: Box<dyn IncrementalNormalizer>) {
+        if let Ok(mut map) = LOG_WAL_MAP.lock() {
+            map.insert(execution_process_id, ProcessLog::new(Some(normalizer)));
+        }
+    }
+
+    /// Feed raw stdout; complete lines are normalized immediately, a trailing partial line
+    /// waits for the rest of its content
+    pub fn push_stdout(execution_process_id: Uuid, chunk: &str) {
+        let Ok(mut map) = LOG_WAL_MAP.lock() else {
+            return;
+        };
+        let Some(log) = map.get_mut(&execution_process_id) else {
+            return;
+        };
+
+        log.last_output_at = Utc::now();
+        log.partial_line.push_str(chunk);
+        while let Some(newline) = log.partial_line.find('\n') {
+            let line: String = log.partial_line.drain(..=newline).collect();
+            log.push_line(line.trim_end_matches('\n'));
+        }
+    }
+
+    /// Push pre-built patches, for executors that stream patches themselves
+    pub fn push_patches(execution_process_id: Uuid, patches: Vec<Value>) {
+        if let Ok(mut map) = LOG_WAL_MAP.lock() {
+            let log = map
+                .entry(execution_process_id)
+                .or_insert_with(|| ProcessLog::new(None));
+            log.last_output_at = Utc::now();
+            log.push_normalized(patches);
+        }
+    }
+
+    /// Get batches after `after_batch_id` (all batches when `None`)
+    ///
+    /// Returns `None` when the process has no live log, e.g. it finished a while ago or was
+    /// started before the server restarted.
+    pub fn batches_since(
+        execution_process_id: Uuid,
+        after_batch_id: Option<u64>,
+    ) -> Option<WalRead> {
+        let map = LOG_WAL_MAP.lock().ok()?;
+        let log = map.get(&execution_process_id)?;
+        let cursor = after_batch_id.unwrap_or(0);
+
+        Some(WalRead {
+            batches: log
+                .batches
+                .iter()
+                .filter(|batch| batch.batch_id > cursor)
+                .cloned()
+                .collect(),
+            finished: log.finished,
+        })
+    }
+
+    /// Append an entry that does not come from the process's output, such as a message sent
+    /// to it, returning its position in the conversation
+    ///
+    /// Returns `None` when the process has no live log.
+    pub fn push_entry(execution_process_id: Uuid, entry: &NormalizedEntry) -> Option<usize> {
+        let mut map = LOG_WAL_MAP.lock().ok()?;
+        let log = map.get_mut(&execution_process_id)?;
+        let entry = serde_json::to_value(entry).ok()?;
+        Some(log.push_injected(entry))
+    }
+
+    /// When a live process last produced output
+    pub fn last_output_at(execution_process_id: Uuid) -> Option<DateTime<Utc>> {
+        let map = LOG_WAL_MAP.lock().ok()?;
+        map.get(&execution_process_id).map(|log| log.last_output_at)
+    }
+
+    /// Mark stdout as closed: flush the partial line and the normalizer, then drop the log
+    /// once clients have had time to drain it
+    pub fn finish(execution_process_id: Uuid) {
+        {
+            let Ok(mut map) = LOG_WAL_MAP.lock() else {
+                return;
+            };
+            let Some(log) = map.get_mut(&execution_process_id) else {
+                return;
+            };
+            if log.finished {
+                return;
+            }
+
+            let remaining = std::mem::take(&mut log.partial_line);
+            log.push_line(&remaining);
+            if let Some(mut normalizer) = log.normalizer.take() {
+                let patches = normalizer.finish();
+                log.push_normalized(patches);
+            }
+            log.finished = true;
+        }
+
+        tokio::spawn(async move {
+            tokio::time::sleep(FINISHED_RETENTION).await;
+            Self::purge(execution_process_id);
+        });
+    }
+
+    /// Remove the log for an execution process
+    pub fn purge(execution_process_id: Uuid) {
+        if let Ok(mut map) = LOG_WAL_MAP.lock() {
+            map.remove(&execution_process_id);
+            tracing::debug!(
+                "Cleaned up log stream for execution process {}",
+                execution_process_id
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Emits one entry per line, replacing the previous entry when a line starts with '+'
+    struct AppendingNormalizer {
+        entries: usize,
+    }
+
+    impl IncrementalNormalizer for AppendingNormalizer {
+        fn push_line(&mut self, line: &str) -> Vec<Value> {
+            if let Some(rest) = line.strip_prefix('+') {
+                vec![serde_json::json!({
+                    "op": "replace",
+                    "path": format!("/entries/{}", self.entries - 1),
+                    "value": rest
+                })]
+            } else {
+                self.entries += 1;
+                vec![serde_json::json!({ "op": "add", "path": "/entries/-", "value": line })]
+            }
+        }
+
+        fn finish(&mut self) -> Vec<Value> {
+            vec![serde_json::json!({ "op": "add", "path": "/entries/-", "value": "done" })]
+        }
+    }
+
+    fn replay(batches: &[PatchBatch]) -> Value {
+        let mut document = serde_json::json!({ "entries": [] });
+        for batch in batches {
+            for operation in &batch.patches {
+                apply_operation(&mut document, operation);
+            }
+        }
+        document
+    }
+
+    #[tokio::test]
+    async fn test_partial_lines_wait_for_newline() {
+        let id = Uuid::new_v4();
+        LogStreamService::start(id, Box::new(AppendingNormalizer { entries: 0 }));
+
+        LogStreamService::push_stdout(id, "hel");
+        assert!(LogStreamService::batches_since(id, None)
+            .unwrap()
+            .batches
+            .is_empty());
+
+        LogStreamService::push_stdout(id, "lo\nwor");
+        LogStreamService::finish(id);
+
+        let read = LogStreamService::batches_since(id, None).unwrap();
+        assert!(read.finished);
+        assert_eq!(
+            replay(&read.batches)["entries"],
+            serde_json::json!(["hello", "wor", "done"])
+        );
+
+        let after_first = LogStreamService::batches_since(id, Some(1)).unwrap();
+        assert_eq!(after_first.batches.len(), 2);
+        LogStreamService::purge(id);
+    }
+
+    #[test]
+    fn test_compaction_preserves_conversation_for_every_cursor() {
+        let id = Uuid::new_v4();
+        LogStreamService::start(id, Box::new(AppendingNormalizer { entries: 0 }));
+
+        let mut expected = Vec::new();
+        for i in 0..WAL_COMPACTION_THRESHOLD * 2 {
+            if i % 5 == 4 {
+                LogStreamService::push_stdout(id, &format!("+edited {}\n", i));
+                *expected.last_mut().unwrap() = format!("edited {}", i);
+            } else {
+                LogStreamService::push_stdout(id, &format!("line {}\n", i));
+                expected.push(format!("line {}", i));
+            }
+        }
+
+        let all = LogStreamService::batches_since(id, None).unwrap().batches;
+        assert!(all.len() < WAL_COMPACTION_THRESHOLD);
+        assert_eq!(replay(&all)["entries"], serde_json::json!(expected));
+
+        // A client that saw a prefix of the stream converges on the same state
+        let snapshot_id = all[0].batch_id;
+        let resumed = LogStreamService::batches_since(id, Some(snapshot_id - 1))
+            .unwrap()
+            .batches;
+        assert_eq!(resumed[0].batch_id, snapshot_id);
+        assert_eq!(replay(&resumed)["entries"], serde_json::json!(expected));
+        LogStreamService::purge(id);
+    }
+
+    #[test]
+    fn test_injected_entries_keep_normalizer_indices_valid() {
+        let id = Uuid::new_v4();
+        LogStreamService::start(id, Box::new(AppendingNormalizer { entries: 0 }));
+
+        LogStreamService::push_stdout(id, "question?\n");
+        let message = NormalizedEntry {
+            timestamp: None,
+            entry_type: crate::executor::NormalizedEntryType::UserMessage,
+            content: "answer".to_string(),
+            metadata: None,
+        };
+        assert_eq!(LogStreamService::push_entry(id, &message), Some(1));
+
+        // The normalizer still edits its own last entry, not the injected one
+        LogStreamService::push_stdout(id, "+question, edited\nthanks\n");
+
+        let entries =
+            replay(&LogStreamService::batches_since(id, None).unwrap().batches)["entries"].clone();
+        assert_eq!(entries[0], "question, edited");
+        assert_eq!(entries[1]["content"], "answer");
+        assert_eq!(entries[2], "thanks");
+        assert_eq!(LogStreamService::push_entry(id, &message), Some(3));
+        LogStreamService::purge(id);
+    }
+}
@@ -1,13 +1,17 @@
 pub mod analytics;
+pub mod attempt_group_service;
 pub mod git_service;
 pub mod github_service;
+pub mod log_stream_service;
 pub mod notification_service;
 pub mod pr_monitor;
 pub mod process_service;
 
 pub use analytics::{generate_user_id, AnalyticsConfig, AnalyticsService};
+pub use attempt_group_service::AttemptGroupService;
 pub use git_service::{GitService, GitServiceError};
 pub use github_service::{CreatePrRequest, GitHubRepoInfo, GitHubService, GitHubServiceError};
+pub use log_stream_service::LogStreamService;
 pub use notification_service::{NotificationConfig, NotificationService};
 pub use pr_monitor::PrMonitorService;
 pub use process_service::ProcessService;
@@ -1,4 +1,4 @@
-use std::str::FromStr;
+use std::{str::FromStr, time::Duration};
 
 use sqlx::SqlitePool;
 use tracing::{debug, info};
@@ -6,17 +6,28 @@ use uuid::Uuid;
 
 use crate::{
     command_runner,
-    executor::Executor,
+    executor::{Executor, RunOptions},
     models::{
+        config::ResourceLimits,
         execution_process::{CreateExecutionProcess, ExecutionProcess, ExecutionProcessType},
         executor_session::{CreateExecutorSession, ExecutorSession},
+        pipeline::{render_stage_prompt, StageCondition, StageStatus, TaskAttemptStage},
         project::Project,
-        task::Task,
+        project_cli_version::ProjectCliVersion,
+        prompt_template::{follow_up_prompt, PromptContext},
+        review_finding::{render_diff, review_prompt},
+        task::{Task, TaskStatus},
+        task_attachment::TaskAttachment,
         task_attempt::{TaskAttempt, TaskAttemptError},
     },
+    services::LogStreamService,
     utils::shell::get_shell_command,
 };
 
+/// Prompt used when an automatic retry resumes the failed run's session
+const RETRY_PROMPT: &str =
+    "The previous run was interrupted by a transient error. Continue with the task from where you left off.";
+
 /// Service responsible for managing process execution lifecycle
 pub struct ProcessService;
 
@@ -69,7 +80,7 @@ impl ProcessService {
         attempt_id: Uuid,
         task_id: Uuid,
         project_id: Uuid,
-        operation: &str, // "dev_server", "coding_agent", or "followup"
+        operation: &str, // "dev_server", "coding_agent", "planning_agent", or "followup"
         operation_params: Option<serde_json::Value>,
     ) -> Result<(), TaskAttemptError> {
         // Check if setup is completed for this worktree
@@ -104,6 +115,10 @@ impl ProcessService {
                 "coding_agent" => {
                     Self::start_coding_agent(pool, app_state, attempt_id, task_id, project_id).await
                 }
+                "planning_agent" => {
+                    Self::start_planning_agent(pool, app_state, attempt_id, task_id, project_id)
+                        .await
+                }
                 "followup" => {
                     let prompt = operation_params
                         .as_ref()
@@ -201,15 +216,17 @@ impl ProcessService {
     }
 
     /// Start the execution flow for a task attempt (setup script + executor)
+    ///
+    /// With `plan_first` the executor first runs a read-only planning phase, whose plan is
+    /// approved before implementation starts.
     pub async fn start_execution(
         pool: &SqlitePool,
         app_state: &crate::app_state::AppState,
         attempt_id: Uuid,
         task_id: Uuid,
         project_id: Uuid,
+        plan_first: bool,
     ) -> Result<(), TaskAttemptError> {
-        use crate::models::task::{Task, TaskStatus};
-
         // Load required entities
         let (task_attempt, project) =
             Self::load_execution_context(pool, attempt_id, project_id).await?;
@@ -217,8 +234,30 @@ impl ProcessService {
         // Update task status to indicate execution has started
         Task::update_status(pool, task_id, project_id, TaskStatus::InProgress).await?;
 
+        // Executors that always plan, like the legacy Claude plan executor, start with a planning phase
+        let plan_first = plan_first
+            || Self::resolve_executor_config(&task_attempt.executor)
+                .capabilities()
+                .plan_mode
+                == crate::executor::PlanMode::Always;
+
         // Determine execution sequence based on project configuration
-        if Self::should_run_setup_script(&project) {
+        if plan_first {
+            if Self::should_run_setup_script(&project) {
+                Self::execute_setup_with_delegation(
+                    pool,
+                    app_state,
+                    attempt_id,
+                    task_id,
+                    project_id,
+                    "planning_agent",
+                    None,
+                )
+                .await
+            } else {
+                Self::start_planning_agent(pool, app_state, attempt_id, task_id, project_id).await
+            }
+        } else if Self::should_run_setup_script(&project) {
             Self::start_setup_script(
                 pool,
                 app_state,
@@ -239,12 +278,32 @@ impl ProcessService {
         app_state: &crate::app_state::AppState,
         attempt_id: Uuid,
         task_id: Uuid,
-        _project_id: Uuid,
+        project_id: Uuid,
     ) -> Result<(), TaskAttemptError> {
         let task_attempt = TaskAttempt::find_by_id(pool, attempt_id)
             .await?
             .ok_or(TaskAttemptError::TaskNotFound)?;
 
+        // Attempts started from a pipeline run its stages instead of a single agent
+        if !TaskAttemptStage::find_by_task_attempt_id(pool, attempt_id)
+            .await?
+            .is_empty()
+        {
+            let started = Self::start_next_pipeline_stage(
+                pool,
+                app_state,
+                attempt_id,
+                task_id,
+                project_id,
+                true,
+            )
+            .await?;
+            if !started {
+                Task::update_status(pool, task_id, project_id, TaskStatus::InReview).await?;
+            }
+            return Ok(());
+        }
+
         let executor_config = Self::resolve_executor_config(&task_attempt.executor);
 
         Self::start_process_execution(
@@ -263,6 +322,324 @@ impl ProcessService {
         .await
     }
 
+    /// Start the read-only planning phase after setup is complete or if no setup is needed
+    pub async fn start_planning_agent(
+        pool: &SqlitePool,
+        app_state: &crate::app_state::AppState,
+        attempt_id: Uuid,
+        task_id: Uuid,
+        _project_id: Uuid,
+    ) -> Result<(), TaskAttemptError> {
+        let task_attempt = TaskAttempt::find_by_id(pool, attempt_id)
+            .await?
+            .ok_or(TaskAttemptError::TaskNotFound)?;
+
+        let executor_config = Self::resolve_executor_config(&task_attempt.executor);
+
+        Self::start_process_execution(
+            pool,
+            app_state,
+            attempt_id,
+            task_id,
+            crate::executor::ExecutorType::PlanningAgent {
+                config: executor_config,
+                follow_up: None,
+            },
+            "Starting planning executor".to_string(),
+            ExecutionProcessType::PlanningAgent,
+            &task_attempt.worktree_path,
+        )
+        .await
+    }
+
+    /// Start the attempt's next pending pipeline stage
+    ///
+    /// Stages whose condition does not hold are marked skipped. A stage gated on a check
+    /// command has its check run in the background, and the execution monitor picks the
+    /// pipeline up again once it finishes. Returns `false` once no stage is left to run.
+    pub async fn start_next_pipeline_stage(
+        pool: &SqlitePool,
+        app_state: &crate::app_state::AppState,
+        attempt_id: Uuid,
+        task_id: Uuid,
+        project_id: Uuid,
+        previous_succeeded: bool,
+    ) -> Result<bool, TaskAttemptError> {
+        let task_attempt = TaskAttempt::find_by_id(pool, attempt_id)
+            .await?
+            .ok_or(TaskAttemptError::TaskNotFound)?;
+
+        while let Some(stage) = TaskAttemptStage::find_next_pending(pool, attempt_id).await? {
+            let should_run = match stage.condition {
+                StageCondition::Always => true,
+                StageCondition::PreviousSucceeded => previous_succeeded,
+                StageCondition::CheckFails => {
+                    Self::spawn_pipeline_check(
+                        app_state,
+                        task_id,
+                        stage,
+                        task_attempt.worktree_path.clone(),
+                        previous_succeeded,
+                    );
+                    return Ok(true);
+                }
+            };
+            if !should_run {
+                info!(
+                    "Skipping pipeline stage '{}' for task attempt {}",
+                    stage.name, attempt_id
+                );
+                TaskAttemptStage::update_status(pool, stage.id, StageStatus::Skipped, None).await?;
+                continue;
+            }
+
+            Self::start_pipeline_stage(pool, app_state, &task_attempt, project_id, &stage).await?;
+            return Ok(true);
+        }
+
+        Ok(false)
+    }
+
+    /// Continue the attempt's pipeline once the check gating `stage` has finished
+    ///
+    /// The stage runs when its check failed and is skipped otherwise. Returns `false` once no
+    /// stage is left to run.
+    pub async fn continue_pipeline_after_check(
+        pool: &SqlitePool,
+        app_state: &crate::app_state::AppState,
+        task_id: Uuid,
+        project_id: Uuid,
+        stage: &TaskAttemptStage,
+        check_passed: bool,
+        previous_succeeded: bool,
+    ) -> Result<bool, TaskAttemptError> {
+        let attempt_id = stage.task_attempt_id;
+        if check_passed {
+            info!(
+                "Skipping pipeline stage '{}' for task attempt {}",
+                stage.name, attempt_id
+            );
+            TaskAttemptStage::update_status(pool, stage.id, StageStatus::Skipped, None).await?;
+            return Self::start_next_pipeline_stage(
+                pool,
+                app_state,
+                attempt_id,
+                task_id,
+                project_id,
+                previous_succeeded,
+            )
+            .await;
+        }
+
+        let task_attempt = TaskAttempt::find_by_id(pool, attempt_id)
+            .await?
+            .ok_or(TaskAttemptError::TaskNotFound)?;
+        Self::start_pipeline_stage(pool, app_state, &task_attempt, project_id, stage).await?;
+        Ok(true)
+    }
+
+    /// Spawn the executor for a pipeline stage and mark the stage running
+    async fn start_pipeline_stage(
+        pool: &SqlitePool,
+        app_state: &crate::app_state::AppState,
+        task_attempt: &TaskAttempt,
+        project_id: Uuid,
+        stage: &TaskAttemptStage,
+    ) -> Result<(), TaskAttemptError> {
+        let attempt_id = task_attempt.id;
+        let task_id = task_attempt.task_id;
+        let task = Task::find_by_id(pool, task_id)
+            .await?
+            .ok_or(TaskAttemptError::TaskNotFound)?;
+
+        // Hand the previous stage's summary and the changes so far to this stage
+        let previous_summary = match TaskAttemptStage::find_by_task_attempt_id(pool, attempt_id)
+            .await?
+            .into_iter()
+            .rev()
+            .filter(|s| s.position < stage.position)
+            .find_map(|s| s.execution_process_id)
+        {
+            Some(process_id) => ExecutorSession::find_by_execution_process_id(pool, process_id)
+                .await?
+                .and_then(|session| session.summary),
+            None => None,
+        };
+        let resume_context =
+            TaskAttempt::get_attempt_resume_context(pool, attempt_id, task_id, project_id).await?;
+        let prompt = render_stage_prompt(
+            &stage.prompt_template,
+            &task.title,
+            task.description.as_deref(),
+            previous_summary.as_deref(),
+            &resume_context.cumulative_diffs,
+        );
+
+        let config = crate::executor::ExecutorConfig::from_str(&stage.executor)
+            .map_err(TaskAttemptError::ValidationError)?;
+        let process_id = Self::spawn_process_execution(
+            pool,
+            app_state,
+            attempt_id,
+            task_id,
+            crate::executor::ExecutorType::PipelineStage { config, prompt },
+            format!("pipeline stage '{}'", stage.name),
+            ExecutionProcessType::CodingAgent,
+            &task_attempt.worktree_path,
+            None,
+        )
+        .await?;
+        TaskAttemptStage::update_status(pool, stage.id, StageStatus::Running, Some(process_id))
+            .await?;
+        Ok(())
+    }
+
+    /// Run the check gating `stage` off the monitor loop and hand its result to the monitor
+    fn spawn_pipeline_check(
+        app_state: &crate::app_state::AppState,
+        task_id: Uuid,
+        stage: TaskAttemptStage,
+        worktree_path: String,
+        previous_succeeded: bool,
+    ) {
+        let app_state = app_state.clone();
+        tokio::spawn(async move {
+            let command = stage.check_command.clone().unwrap_or_default();
+            let timeout = app_state.get_pipeline_check_timeout().await;
+            // Checks run the project's code, so they get the same sandbox as its agents
+            let passed =
+                match Self::resolve_sandbox(&app_state.db_pool, task_id, &worktree_path).await {
+                    Ok(sandbox) => {
+                        command_runner::with_sandbox(
+                            sandbox,
+                            Self::check_command_succeeds(&command, &worktree_path, timeout),
+                        )
+                        .await
+                    }
+                    Err(e) => {
+                        tracing::warn!(
+                            "Failed to resolve the sandbox for check '{}': {}",
+                            command,
+                            e
+                        );
+                        false
+                    }
+                };
+            crate::execution_monitor::handle_pipeline_check_result(
+                &app_state,
+                task_id,
+                stage,
+                passed,
+                previous_succeeded,
+            )
+            .await;
+        });
+    }
+
+    /// Start a read-only review of the attempt's diff with `config`
+    ///
+    /// Returns `false` without starting anything when the attempt has no changes to review.
+    pub async fn start_review_agent(
+        pool: &SqlitePool,
+        app_state: &crate::app_state::AppState,
+        attempt_id: Uuid,
+        task_id: Uuid,
+        project_id: Uuid,
+        config: crate::executor::ExecutorConfig,
+    ) -> Result<bool, TaskAttemptError> {
+        if !config.capabilities().plan_mode.is_read_only() {
+            return Err(TaskAttemptError::ValidationError(format!(
+                "{} cannot run read-only, so it cannot review changes",
+                config
+            )));
+        }
+
+        let task = Task::find_by_id(pool, task_id)
+            .await?
+            .ok_or(TaskAttemptError::TaskNotFound)?;
+        let worktree_path =
+            TaskAttempt::ensure_worktree_exists(pool, attempt_id, project_id, "review").await?;
+        let diff = TaskAttempt::get_diff(pool, attempt_id, task_id, project_id).await?;
+        if diff.files.is_empty() {
+            info!("No changes to review for task attempt {}", attempt_id);
+            return Ok(false);
+        }
+
+        let prompt = review_prompt(
+            &task.title,
+            task.description.as_deref(),
+            &render_diff(&diff),
+        );
+
+        Self::start_process_execution(
+            pool,
+            app_state,
+            attempt_id,
+            task_id,
+            crate::executor::ExecutorType::ReviewAgent { config, prompt },
+            "Starting code review".to_string(),
+            ExecutionProcessType::ReviewAgent,
+            &worktree_path,
+        )
+        .await?;
+        Ok(true)
+    }
+
+    /// Run a pipeline check command in the worktree and report whether it exited successfully
+    ///
+    /// A check still running after `timeout` is killed and counts as failed.
+    async fn check_command_succeeds(command: &str, worktree_path: &str, timeout: Duration) -> bool {
+        let (shell_cmd, shell_arg) = get_shell_command();
+        let mut runner = command_runner::CommandRunner::new();
+        runner
+            .command(shell_cmd)
+            .arg(shell_arg)
+            .arg(command)
+            .working_dir(worktree_path);
+
+        let mut process = match runner.start().await {
+            Ok(process) => process,
+            Err(e) => {
+                tracing::warn!("Failed to start check command '{}': {}", command, e);
+                return false;
+            }
+        };
+
+        let outcome = tokio::time::timeout(timeout, async {
+            // Drain output so a chatty check cannot block on a full pipe
+            if let Ok(stream) = process.stream().await {
+                let drain =
+                    |reader: Option<Box<dyn tokio::io::AsyncRead + Unpin + Send>>| async move {
+                        if let Some(mut reader) = reader {
+                            let _ = tokio::io::copy(&mut reader, &mut tokio::io::sink()).await;
+                        }
+                    };
+                tokio::join!(drain(stream.stdout), drain(stream.stderr));
+            }
+            process.wait().await
+        })
+        .await;
+
+        match outcome {
+            Ok(Ok(status)) => status.success(),
+            Ok(Err(e)) => {
+                tracing::warn!("Failed to wait for check command '{}': {}", command, e);
+                false
+            }
+            Err(_) => {
+                tracing::warn!(
+                    "Check command '{}' timed out after {} seconds",
+                    command,
+                    timeout.as_secs()
+                );
+                if let Err(e) = process.kill().await {
+                    tracing::warn!("Failed to kill check command '{}': {}", command, e);
+                }
+                false
+            }
+        }
+    }
+
     /// Start a dev server for this task attempt (with automatic setup)
     pub async fn start_dev_server(
         pool: &SqlitePool,
@@ -355,8 +732,6 @@ impl ProcessService {
         project_id: Uuid,
         prompt: &str,
     ) -> Result<Uuid, TaskAttemptError> {
-        use crate::models::task::{Task, TaskStatus};
-
         // Get the current task attempt to check if worktree is deleted
         let current_attempt = TaskAttempt::find_by_id(pool, attempt_id)
             .await?
@@ -364,6 +739,14 @@ impl ProcessService {
 
         let actual_attempt_id = attempt_id;
 
+        let executor_config = Self::resolve_executor_config(&current_attempt.executor);
+        if !executor_config.capabilities().follow_up {
+            return Err(TaskAttemptError::ValidationError(format!(
+                "{} executor does not support follow-ups",
+                executor_config.display_name()
+            )));
+        }
+
         if current_attempt.worktree_deleted {
             info!(
                 "Resurrecting deleted attempt {} (branch: {}) for followup execution - maintaining session continuity",
@@ -418,14 +801,39 @@ impl ProcessService {
         let worktree_path =
             TaskAttempt::ensure_worktree_exists(pool, attempt_id, project_id, "followup").await?;
 
-        // Find the most recent coding agent execution process to get the executor type
+        // Find the most recent agent execution process to get the executor type
         // Look up processes from the ORIGINAL attempt to find the session
         let execution_processes =
             ExecutionProcess::find_by_task_attempt_id(pool, attempt_id).await?;
-        let most_recent_coding_agent = execution_processes
+        let most_recent_coding_agent = match execution_processes
             .iter()
             .rev() // Reverse to get most recent first (since they're ordered by created_at ASC)
-            .find(|p| matches!(p.process_type, ExecutionProcessType::CodingAgent))
+            .find(|p| {
+                matches!(
+                    p.process_type,
+                    ExecutionProcessType::CodingAgent | ExecutionProcessType::PlanningAgent
+                )
+            }) {
+            Some(process) => Some(process.clone()),
+            // A fresh fork continues a copy of the session it was forked from
+            None => {
+                if let Some((source, session)) = Self::find_fork_source(pool, attempt_id).await? {
+                    return Self::start_forked_execution(
+                        pool,
+                        app_state,
+                        attempt_id,
+                        task_id,
+                        project_id,
+                        (&source, &session),
+                        prompt,
+                        &worktree_path,
+                    )
+                    .await;
+                }
+                None
+            }
+        };
+        let most_recent_coding_agent = most_recent_coding_agent
             .ok_or_else(|| {
                 tracing::error!(
                     "No previous coding agent execution found for task attempt {}. Found {} processes: {:?}",
@@ -474,6 +882,28 @@ impl ProcessService {
             }
         };
 
+        // A follow-up on a plan refines the plan instead of implementing it
+        let process_type = most_recent_coding_agent.process_type.clone();
+        let agent = |config: crate::executor::ExecutorConfig,
+                     follow_up: Option<crate::executor::FollowUpInfo>| {
+            match process_type {
+                ExecutionProcessType::PlanningAgent => {
+                    crate::executor::ExecutorType::PlanningAgent { config, follow_up }
+                }
+                _ => crate::executor::ExecutorType::CodingAgent { config, follow_up },
+            }
+        };
+
+        // Follow-ups are rendered through the project's prompt template too
+        let task = Task::find_by_id(pool, task_id)
+            .await?
+            .ok_or(TaskAttemptError::TaskNotFound)?;
+        let project = Project::find_by_id(pool, project_id)
+            .await?
+            .ok_or(TaskAttemptError::ProjectNotFound)?;
+        let context = PromptContext::load(pool, &task, &worktree_path).await?;
+        let prompt = follow_up_prompt(&project, &context, prompt);
+
         // Try to use follow-up with session ID, but fall back to new session if it fails
         let followup_executor = if let Some(session_id) = &executor_session.session_id {
             // First try with session ID for continuation
@@ -481,23 +911,20 @@ impl ProcessService {
                 "SESSION_FOLLOWUP: Attempting follow-up execution with session ID: {} (attempt: {}, worktree: {})",
                 session_id, attempt_id, worktree_path
             );
-            crate::executor::ExecutorType::CodingAgent {
-                config: executor_config.clone(),
-                follow_up: Some(crate::executor::FollowUpInfo {
+            agent(
+                executor_config.clone(),
+                Some(crate::executor::FollowUpInfo {
                     session_id: session_id.clone(),
-                    prompt: prompt.to_string(),
+                    prompt,
                 }),
-            }
+            )
         } else {
             // No session ID available, start new session
             tracing::warn!(
                 "SESSION_FOLLOWUP: No session ID available for follow-up execution on attempt {}, starting new session (worktree: {})",
                 attempt_id, worktree_path
             );
-            crate::executor::ExecutorType::CodingAgent {
-                config: executor_config.clone(),
-                follow_up: None,
-            }
+            agent(executor_config.clone(), None)
         };
 
         // Try to start the follow-up execution
@@ -508,7 +935,7 @@ impl ProcessService {
             task_id,
             followup_executor,
             "Starting follow-up executor".to_string(),
-            ExecutionProcessType::CodingAgent,
+            process_type.clone(),
             &worktree_path,
         )
         .await;
@@ -524,10 +951,7 @@ impl ProcessService {
             );
 
             // Create a new session instead of trying to resume
-            let new_session_executor = crate::executor::ExecutorType::CodingAgent {
-                config: executor_config,
-                follow_up: None,
-            };
+            let new_session_executor = agent(executor_config, None);
 
             Self::start_process_execution(
                 pool,
@@ -536,7 +960,7 @@ impl ProcessService {
                 task_id,
                 new_session_executor,
                 "Starting new executor session (follow-up session failed)".to_string(),
-                ExecutionProcessType::CodingAgent,
+                process_type,
                 &worktree_path,
             )
             .await?;
@@ -548,6 +972,219 @@ impl ProcessService {
         Ok(attempt_id)
     }
 
+    /// Start a fork's first run, which resumes a copy of the session it was forked from
+    #[allow(clippy::too_many_arguments)]
+    async fn start_forked_execution(
+        pool: &SqlitePool,
+        app_state: &crate::app_state::AppState,
+        attempt_id: Uuid,
+        task_id: Uuid,
+        project_id: Uuid,
+        (source, session): (&ExecutionProcess, &ExecutorSession),
+        prompt: &str,
+        worktree_path: &str,
+    ) -> Result<Uuid, TaskAttemptError> {
+        let config = crate::executor::ExecutorConfig::from_str(
+            source.executor_type.as_deref().unwrap_or_default(),
+        )
+        .map_err(TaskAttemptError::ValidationError)?;
+        let task = Task::find_by_id(pool, task_id)
+            .await?
+            .ok_or(TaskAttemptError::TaskNotFound)?;
+        let project = Project::find_by_id(pool, project_id)
+            .await?
+            .ok_or(TaskAttemptError::ProjectNotFound)?;
+        let context = PromptContext::load(pool, &task, worktree_path).await?;
+        let fork = Self::fork_info(
+            source,
+            session,
+            follow_up_prompt(&project, &context, prompt),
+        )?;
+
+        Self::start_process_execution(
+            pool,
+            app_state,
+            attempt_id,
+            task_id,
+            crate::executor::ExecutorType::ForkedAgent {
+                config,
+                fork,
+                planning: source.process_type == ExecutionProcessType::PlanningAgent,
+            },
+            "Starting forked executor session".to_string(),
+            source.process_type.clone(),
+            worktree_path,
+        )
+        .await?;
+        Ok(attempt_id)
+    }
+
+    /// What a fork's first run resumes: the agent session of `source`, where it ran
+    fn fork_info(
+        source: &ExecutionProcess,
+        session: &ExecutorSession,
+        prompt: String,
+    ) -> Result<crate::executor::ForkInfo, TaskAttemptError> {
+        let session_id = session.session_id.clone().ok_or_else(|| {
+            TaskAttemptError::ValidationError(
+                "The forked execution has no agent session to resume".to_string(),
+            )
+        })?;
+        Ok(crate::executor::ForkInfo {
+            session_id,
+            source_worktree_path: source.working_directory.clone(),
+            prompt,
+        })
+    }
+
+    /// Re-run a failed agent process as a new execution process linked to `original_id`
+    ///
+    /// The failed run's session is resumed when there is one, falling back to running its
+    /// original prompt again in a new session. A follow-up without a session of its own sends
+    /// its message to the session it followed up on again.
+    pub async fn retry_execution(
+        pool: &SqlitePool,
+        app_state: &crate::app_state::AppState,
+        failed: &ExecutionProcess,
+        original_id: Uuid,
+    ) -> Result<Uuid, TaskAttemptError> {
+        let task_attempt = TaskAttempt::find_by_id(pool, failed.task_attempt_id)
+            .await?
+            .ok_or(TaskAttemptError::TaskNotFound)?;
+        let config = crate::executor::ExecutorConfig::from_str(
+            failed.executor_type.as_deref().unwrap_or_default(),
+        )
+        .map_err(TaskAttemptError::ValidationError)?;
+        let session = ExecutorSession::find_by_execution_process_id(pool, failed.id).await?;
+        let prompt = session.as_ref().and_then(|session| session.prompt.clone());
+
+        let process_type = failed.process_type.clone();
+        let agent = |config: crate::executor::ExecutorConfig,
+                     follow_up: Option<crate::executor::FollowUpInfo>| {
+            match process_type {
+                ExecutionProcessType::PlanningAgent => {
+                    crate::executor::ExecutorType::PlanningAgent { config, follow_up }
+                }
+                _ => crate::executor::ExecutorType::CodingAgent { config, follow_up },
+            }
+        };
+
+        // Pipeline stages and reviews were started with a prompt of their own
+        let new_session_executor = match (failed.command.as_str(), prompt.clone()) {
+            ("pipeline_stage_executor", Some(prompt)) => {
+                Some(crate::executor::ExecutorType::PipelineStage {
+                    config: config.clone(),
+                    prompt,
+                })
+            }
+            ("review_executor", Some(prompt)) => Some(crate::executor::ExecutorType::ReviewAgent {
+                config: config.clone(),
+                prompt,
+            }),
+            ("forked_executor", Some(prompt)) => {
+                match Self::find_fork_source(pool, task_attempt.id).await? {
+                    Some((source, session)) => Some(crate::executor::ExecutorType::ForkedAgent {
+                        config: config.clone(),
+                        fork: Self::fork_info(&source, &session, prompt)?,
+                        planning: failed.process_type == ExecutionProcessType::PlanningAgent,
+                    }),
+                    None => Some(agent(config.clone(), None)),
+                }
+            }
+            // Running the task's own prompt again would drop the follow-up's message
+            ("followup_executor" | "followup_planning_executor", _) => None,
+            _ => Some(agent(config.clone(), None)),
+        };
+
+        let resume = match session.and_then(|session| session.session_id) {
+            Some(session_id) => Some(crate::executor::FollowUpInfo {
+                session_id,
+                prompt: RETRY_PROMPT.to_string(),
+            }),
+            // A follow-up that failed before its agent reported a session sends its message
+            // to the session it followed up on again
+            None if new_session_executor.is_none() => {
+                match (prompt, Self::find_previous_session_id(pool, failed).await?) {
+                    (Some(prompt), Some(session_id)) => {
+                        Some(crate::executor::FollowUpInfo { session_id, prompt })
+                    }
+                    _ => None,
+                }
+            }
+            None => None,
+        };
+
+        let process_id = match (resume, new_session_executor) {
+            (Some(follow_up), new_session_executor) => {
+                match Self::spawn_process_execution(
+                    pool,
+                    app_state,
+                    task_attempt.id,
+                    task_attempt.task_id,
+                    agent(config, Some(follow_up)),
+                    "retry of failed execution".to_string(),
+                    failed.process_type.clone(),
+                    &task_attempt.worktree_path,
+                    Some(original_id),
+                )
+                .await
+                {
+                    Ok(process_id) => process_id,
+                    Err(e) => {
+                        let Some(new_session_executor) = new_session_executor else {
+                            return Err(e);
+                        };
+                        tracing::warn!(
+                            "Resuming session failed for retry of execution {}, starting a new session: {}",
+                            failed.id,
+                            e
+                        );
+                        Self::spawn_process_execution(
+                            pool,
+                            app_state,
+                            task_attempt.id,
+                            task_attempt.task_id,
+                            new_session_executor,
+                            "retry of failed execution in a new session".to_string(),
+                            failed.process_type.clone(),
+                            &task_attempt.worktree_path,
+                            Some(original_id),
+                        )
+                        .await?
+                    }
+                }
+            }
+            (None, Some(new_session_executor)) => {
+                Self::spawn_process_execution(
+                    pool,
+                    app_state,
+                    task_attempt.id,
+                    task_attempt.task_id,
+                    new_session_executor,
+                    "retry of failed execution in a new session".to_string(),
+                    failed.process_type.clone(),
+                    &task_attempt.worktree_path,
+                    Some(original_id),
+                )
+                .await?
+            }
+            (None, None) => {
+                return Err(TaskAttemptError::ValidationError(
+                    "The failed follow-up has no session to send its message to again".to_string(),
+                ))
+            }
+        };
+
+        // A retried pipeline stage now waits on the new run
+        if let Some(stage) = TaskAttemptStage::find_by_execution_process_id(pool, failed.id).await?
+        {
+            TaskAttemptStage::update_status(pool, stage.id, StageStatus::Running, Some(process_id))
+                .await?;
+        }
+
+        Ok(process_id)
+    }
+
     /// Unified function to start any type of process execution
     #[allow(clippy::too_many_arguments)]
     pub async fn start_process_execution(
@@ -560,27 +1197,108 @@ impl ProcessService {
         process_type: ExecutionProcessType,
         worktree_path: &str,
     ) -> Result<(), TaskAttemptError> {
+        Self::spawn_process_execution(
+            pool,
+            app_state,
+            attempt_id,
+            task_id,
+            executor_type,
+            activity_note,
+            process_type,
+            worktree_path,
+            None,
+        )
+        .await
+        .map(|_| ())
+    }
+
+    /// Start a process execution and return the id of its execution process
+    ///
+    /// An automatic retry passes the process it retries, linked as soon as the record exists.
+    #[allow(clippy::too_many_arguments)]
+    async fn spawn_process_execution(
+        pool: &SqlitePool,
+        app_state: &crate::app_state::AppState,
+        attempt_id: Uuid,
+        task_id: Uuid,
+        executor_type: crate::executor::ExecutorType,
+        activity_note: String,
+        process_type: ExecutionProcessType,
+        worktree_path: &str,
+        retry_of_process_id: Option<Uuid>,
+    ) -> Result<Uuid, TaskAttemptError> {
         let process_id = Uuid::new_v4();
 
+        // Agents act as the task's assigned persona, if it has one
+        let task = if matches!(
+            process_type,
+            ExecutionProcessType::CodingAgent
+                | ExecutionProcessType::PlanningAgent
+                | ExecutionProcessType::ReviewAgent
+        ) {
+            Task::find_by_id(pool, task_id).await?
+        } else {
+            None
+        };
+        let persona_id = task.as_ref().and_then(|task| task.assigned_persona_id);
+
+        // Agents run with the attempt's model and arguments and the project's pinned CLI
+        let run_options = match (&task, executor_type.agent_config()) {
+            (Some(task), Some(config)) => {
+                Self::resolve_run_options(pool, attempt_id, task.project_id, config).await?
+            }
+            _ => RunOptions::default(),
+        };
+
+        // Agents that use tools without asking can't honour a project's permission policy
+        if let (Some(task), Some(config)) = (&task, executor_type.agent_config()) {
+            if !config.capabilities().permission_prompts
+                && crate::models::permission::PermissionPolicy::is_configured(pool, task.project_id)
+                    .await?
+            {
+                return Err(TaskAttemptError::ValidationError(format!(
+                    "{} uses tools without asking for permission, so it cannot run in a project with a permission policy",
+                    config
+                )));
+            }
+        }
+
         // Create execution process record
-        let _execution_process = Self::create_execution_process_record(
+        let execution_process = Self::create_execution_process_record(
             pool,
             attempt_id,
             process_id,
             &executor_type,
             process_type.clone(),
             worktree_path,
+            persona_id,
+            &run_options,
+            retry_of_process_id,
         )
         .await?;
 
-        // Create executor session for coding agents
-        if matches!(process_type, ExecutionProcessType::CodingAgent) {
+        // Create executor session for agent runs
+        if matches!(
+            process_type,
+            ExecutionProcessType::CodingAgent
+                | ExecutionProcessType::PlanningAgent
+                | ExecutionProcessType::ReviewAgent
+        ) {
             // Extract follow-up prompt if this is a follow-up execution
             let followup_prompt = match &executor_type {
                 crate::executor::ExecutorType::CodingAgent {
                     follow_up: Some(ref info),
                     ..
+                }
+                | crate::executor::ExecutorType::PlanningAgent {
+                    follow_up: Some(ref info),
+                    ..
                 } => Some(info.prompt.clone()),
+                crate::executor::ExecutorType::PipelineStage { prompt, .. }
+                | crate::executor::ExecutorType::ReviewAgent { prompt, .. } => Some(prompt.clone()),
+                crate::executor::ExecutorType::ForkedAgent { fork, .. } => {
+                    Some(fork.prompt.clone())
+                }
                 _ => None,
             };
             Self::create_executor_session_record(
@@ -593,13 +1311,51 @@ impl ProcessService {
             .await?;
         }
 
+        // Agents find the task's attachments in their worktree
+        if matches!(
+            process_type,
+            ExecutionProcessType::CodingAgent
+                | ExecutionProcessType::PlanningAgent
+                | ExecutionProcessType::ReviewAgent
+        ) {
+            if let Err(e) = TaskAttachment::stage(pool, task_id, worktree_path).await {
+                tracing::warn!(
+                    "Failed to stage attachments for task attempt {}: {}",
+                    attempt_id,
+                    e
+                );
+            }
+        }
+
         // Process started successfully
 
         tracing::info!("Starting {} for task attempt {}", activity_note, attempt_id);
 
+        // Projects can confine their processes to a sandbox around the worktree
+        let sandbox = Self::resolve_sandbox(pool, task_id, worktree_path).await?;
+        if sandbox.is_some() {
+            tracing::info!(
+                "Sandboxing {} for task attempt {}",
+                activity_note,
+                attempt_id
+            );
+        }
+
+        // Bound the process group by the project's and the executor's resource limits
+        let resource_limits = Self::resolve_resource_limits(
+            app_state,
+            pool,
+            task_id,
+            execution_process.executor_type.as_deref(),
+        )
+        .await?;
+
         // Execute the process
         let child = Self::execute_process(
             &executor_type,
+            &run_options,
+            sandbox,
+            resource_limits,
             pool,
             task_id,
             attempt_id,
@@ -617,7 +1373,7 @@ impl ProcessService {
             process_id,
             attempt_id
         );
-        Ok(())
+        Ok(process_id)
     }
 
     /// Load the execution context (task attempt and project) with validation
@@ -718,7 +1474,105 @@ impl ProcessService {
         }
     }
 
+    /// The latest agent session reported in `process`'s attempt before it ran
+    async fn find_previous_session_id(
+        pool: &SqlitePool,
+        process: &ExecutionProcess,
+    ) -> Result<Option<String>, TaskAttemptError> {
+        let sessions =
+            ExecutorSession::find_by_task_attempt_id(pool, process.task_attempt_id).await?;
+        Ok(sessions
+            .into_iter()
+            .take_while(|session| session.execution_process_id != process.id)
+            .filter_map(|session| session.session_id)
+            .last())
+    }
+
+    /// Agent process and session a forked attempt was forked from, if the attempt is a fork
+    async fn find_fork_source(
+        pool: &SqlitePool,
+        attempt_id: Uuid,
+    ) -> Result<Option<(ExecutionProcess, ExecutorSession)>, TaskAttemptError> {
+        let Some(session_id) = TaskAttempt::find_by_id(pool, attempt_id)
+            .await?
+            .and_then(|attempt| attempt.forked_from_session_id)
+        else {
+            return Ok(None);
+        };
+        let Some(session) = ExecutorSession::find_by_id(pool, session_id).await? else {
+            return Ok(None);
+        };
+        Ok(
+            ExecutionProcess::find_by_id(pool, session.execution_process_id)
+                .await?
+                .map(|process| (process, session)),
+        )
+    }
+
+    /// Model, extra arguments and pinned CLI version for an agent run with `config`
+    ///
+    /// The attempt's model and arguments only apply to runs of the attempt's own executor, so
+    /// pipeline stages and reviews on other executors run with their defaults.
+    async fn resolve_run_options(
+        pool: &SqlitePool,
+        attempt_id: Uuid,
+        project_id: Uuid,
+        config: &crate::executor::ExecutorConfig,
+    ) -> Result<RunOptions, TaskAttemptError> {
+        let executor = config.to_string();
+        let attempt = TaskAttempt::find_by_id(pool, attempt_id)
+            .await?
+            .ok_or(TaskAttemptError::TaskNotFound)?;
+        let mut options = if attempt.executor.as_deref() == Some(executor.as_str()) {
+            TaskAttempt::find_run_options(pool, attempt_id).await?
+        } else {
+            RunOptions::default()
+        };
+        options.cli_version = ProjectCliVersion::find_version(pool, project_id, &executor).await?;
+        Ok(options.supported_by(&config.capabilities()))
+    }
+
+    /// The sandbox the task's project confines processes in `worktree_path` to, if any
+    async fn resolve_sandbox(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        worktree_path: &str,
+    ) -> Result<Option<command_runner::SandboxPolicy>, TaskAttemptError> {
+        let task = Task::find_by_id(pool, task_id)
+            .await?
+            .ok_or(TaskAttemptError::TaskNotFound)?;
+        let project = Project::find_by_id(pool, task.project_id)
+            .await?
+            .ok_or(TaskAttemptError::ProjectNotFound)?;
+        Ok(command_runner::SandboxPolicy::for_project(
+            project.sandbox_mode,
+            std::path::Path::new(worktree_path),
+        ))
+    }
+
+    /// The strictest of the task's project's and `executor`'s resource limits
+    async fn resolve_resource_limits(
+        app_state: &crate::app_state::AppState,
+        pool: &SqlitePool,
+        task_id: Uuid,
+        executor: Option<&str>,
+    ) -> Result<ResourceLimits, TaskAttemptError> {
+        let task = Task::find_by_id(pool, task_id)
+            .await?
+            .ok_or(TaskAttemptError::TaskNotFound)?;
+        let project = Project::find_by_id(pool, task.project_id)
+            .await?
+            .ok_or(TaskAttemptError::ProjectNotFound)?;
+
+        let mut limits = project.resource_limits();
+        if let Some(executor) = executor {
+            limits = limits.strictest(app_state.get_executor_resource_limits(executor).await);
+        }
+        Ok(limits)
+    }
+
     /// Create execution process database record
+    #[allow(clippy::too_many_arguments)]
     async fn create_execution_process_record(
         pool: &SqlitePool,
         attempt_id: Uuid,
@@ -726,6 +1580,9 @@ impl ProcessService {
         executor_type: &crate::executor::ExecutorType,
         process_type: ExecutionProcessType,
         worktree_path: &str,
+        persona_id: Option<Uuid>,
+        run_options: &RunOptions,
+        retry_of_process_id: Option<Uuid>,
     ) -> Result<ExecutionProcess, TaskAttemptError> {
         let (shell_cmd, shell_arg) = get_shell_command();
         let (command, args, executor_type_string) = match executor_type {
@@ -752,6 +1609,29 @@ impl ProcessService {
                 };
                 (command, None, Some(format!("{}", config)))
             }
+            crate::executor::ExecutorType::PlanningAgent { config, follow_up } => {
+                let command = if follow_up.is_some() {
+                    "followup_planning_executor".to_string()
+                } else {
+                    "planning_executor".to_string()
+                };
+                (command, None, Some(format!("{}", config)))
+            }
+            crate::executor::ExecutorType::PipelineStage { config, .. } => (
+                "pipeline_stage_executor".to_string(),
+                None,
+                Some(format!("{}", config)),
+            ),
+            crate::executor::ExecutorType::ReviewAgent { config, .. } => (
+                "review_executor".to_string(),
+                None,
+                Some(format!("{}", config)),
+            ),
+            crate::executor::ExecutorType::ForkedAgent { config, .. } => (
+                "forked_executor".to_string(),
+                None,
+                Some(format!("{}", config)),
+            ),
         };
 
         let create_process = CreateExecutionProcess {
@@ -761,6 +1641,10 @@ impl ProcessService {
             command,
             args,
             working_directory: worktree_path.to_string(),
+            persona_id,
+            model: run_options.model.clone(),
+            cli_version: run_options.cli_version.clone(),
+            retry_of_process_id,
         };
 
         ExecutionProcess::create(pool, &create_process, process_id)
@@ -800,45 +1684,81 @@ impl ProcessService {
     }
 
     /// Execute the process based on type
+    #[allow(clippy::too_many_arguments)]
     async fn execute_process(
         executor_type: &crate::executor::ExecutorType,
+        run_options: &RunOptions,
+        sandbox: Option<command_runner::SandboxPolicy>,
+        resource_limits: ResourceLimits,
         pool: &SqlitePool,
         task_id: Uuid,
         attempt_id: Uuid,
         process_id: Uuid,
         worktree_path: &str,
     ) -> Result<command_runner::CommandProcess, TaskAttemptError> {
-        use crate::executors::{CleanupScriptExecutor, DevServerExecutor, SetupScriptExecutor};
+        use crate::executors::{
+            CleanupScriptExecutor, DevServerExecutor, PlanningExecutor, SetupScriptExecutor,
+        };
 
-        let result = match executor_type {
-            crate::executor::ExecutorType::SetupScript(script) => {
-                let executor = SetupScriptExecutor {
-                    script: script.clone(),
-                };
-                executor
-                    .execute_streaming(pool, task_id, attempt_id, process_id, worktree_path)
-                    .await
-            }
-            crate::executor::ExecutorType::CleanupScript(script) => {
-                let executor = CleanupScriptExecutor {
-                    script: script.clone(),
-                };
-                executor
-                    .execute_streaming(pool, task_id, attempt_id, process_id, worktree_path)
-                    .await
-            }
-            crate::executor::ExecutorType::DevServer(script) => {
-                let executor = DevServerExecutor {
+        let create_executor = || -> Box<dyn Executor> {
+            match executor_type {
+                crate::executor::ExecutorType::SetupScript(script) => {
+                    Box::new(SetupScriptExecutor {
+                        script: script.clone(),
+                    })
+                }
+                crate::executor::ExecutorType::CleanupScript(script) => {
+                    Box::new(CleanupScriptExecutor {
+                        script: script.clone(),
+                    })
+                }
+                crate::executor::ExecutorType::DevServer(script) => Box::new(DevServerExecutor {
                     script: script.clone(),
-                };
-                executor
-                    .execute_streaming(pool, task_id, attempt_id, process_id, worktree_path)
-                    .await
+                }),
+                crate::executor::ExecutorType::CodingAgent { config, .. } => {
+                    config.create_executor_with(run_options)
+                }
+                crate::executor::ExecutorType::PlanningAgent { config, .. } => Box::new(
+                    PlanningExecutor::with_run_options(config.clone(), run_options),
+                ),
+                crate::executor::ExecutorType::PipelineStage { config, .. } => {
+                    config.create_executor_with(run_options)
+                }
+                // Reviewers must not change the code they review
+                crate::executor::ExecutorType::ReviewAgent { config, .. } => {
+                    config.create_read_only_executor_with(run_options)
+                }
+                crate::executor::ExecutorType::ForkedAgent {
+                    config,
+                    planning: true,
+                    ..
+                } => Box::new(PlanningExecutor::with_run_options(
+                    config.clone(),
+                    run_options,
+                )),
+                crate::executor::ExecutorType::ForkedAgent { config, .. } => {
+                    config.create_executor_with(run_options)
+                }
             }
-            crate::executor::ExecutorType::CodingAgent { config, follow_up } => {
-                let executor = config.create_executor();
+        };
 
-                if let Some(ref follow_up_info) = follow_up {
+        // Register the live log before any output can arrive
+        LogStreamService::start(
+            process_id,
+            crate::executor::incremental_normalizer_for(create_executor(), worktree_path),
+        );
+
+        let executor = create_executor();
+        let run = command_runner::with_sandbox(sandbox, async {
+            match executor_type {
+                crate::executor::ExecutorType::CodingAgent {
+                    follow_up: Some(follow_up_info),
+                    ..
+                }
+                | crate::executor::ExecutorType::PlanningAgent {
+                    follow_up: Some(follow_up_info),
+                    ..
+                } => {
                     executor
                         .execute_followup_streaming(
                             pool,
@@ -850,13 +1770,44 @@ impl ProcessService {
                             worktree_path,
                         )
                         .await
-                } else {
+                }
+                crate::executor::ExecutorType::PipelineStage { prompt, .. }
+                | crate::executor::ExecutorType::ReviewAgent { prompt, .. } => {
+                    executor
+                        .execute_prompt_streaming(
+                            pool,
+                            task_id,
+                            attempt_id,
+                            process_id,
+                            prompt,
+                            worktree_path,
+                        )
+                        .await
+                }
+                crate::executor::ExecutorType::ForkedAgent { fork, .. } => {
+                    executor
+                        .execute_fork_streaming(
+                            pool,
+                            task_id,
+                            attempt_id,
+                            process_id,
+                            fork,
+                            worktree_path,
+                        )
+                        .await
+                }
+                _ => {
                     executor
                         .execute_streaming(pool, task_id, attempt_id, process_id, worktree_path)
                         .await
                 }
             }
-        };
+        });
+        let result = command_runner::with_resource_limits(resource_limits, run).await;
+
+        if result.is_err() {
+            LogStreamService::purge(process_id);
+        }
 
         result.map_err(|e| TaskAttemptError::Git(git2::Error::from_str(&e.to_string())))
     }
@@ -873,6 +1824,8 @@ impl ProcessService {
             ExecutionProcessType::SetupScript => crate::app_state::ExecutionType::SetupScript,
             ExecutionProcessType::CleanupScript => crate::app_state::ExecutionType::CleanupScript,
             ExecutionProcessType::CodingAgent => crate::app_state::ExecutionType::CodingAgent,
+            ExecutionProcessType::PlanningAgent => crate::app_state::ExecutionType::PlanningAgent,
+            ExecutionProcessType::ReviewAgent => crate::app_state::ExecutionType::ReviewAgent,
             ExecutionProcessType::DevServer => crate::app_state::ExecutionType::DevServer,
         };
 
@@ -914,6 +1867,10 @@ impl ProcessService {
             command: shell_cmd.to_string(),
             args: Some(args_with_delegation.to_string()),
             working_directory: worktree_path.to_string(),
+            persona_id: None,
+            model: None,
+            cli_version: None,
+            retry_of_process_id: None,
         };
 
         ExecutionProcess::create(pool, &create_process, process_id)
@@ -936,9 +1893,250 @@ impl ProcessService {
             script: setup_script.to_string(),
         };
 
-        executor
+        LogStreamService::start(
+            process_id,
+            crate::executor::incremental_normalizer_for(
+                Box::new(SetupScriptExecutor {
+                    script: setup_script.to_string(),
+                }),
+                worktree_path,
+            ),
+        );
+
+        let result = executor
             .execute_streaming(pool, task_id, attempt_id, process_id, worktree_path)
+            .await;
+
+        if result.is_err() {
+            LogStreamService::purge(process_id);
+        }
+
+        result.map_err(|e| TaskAttemptError::Git(git2::Error::from_str(&e.to_string())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::*;
+    use crate::test_utils::{
+        insert_attempt, insert_project, insert_running_process, insert_task, test_app_state,
+    };
+
+    #[tokio::test]
+    async fn test_check_command_is_killed_after_timeout() {
+        let dir = tempfile::tempdir().unwrap();
+        let worktree = dir.path().to_str().unwrap();
+
+        assert!(
+            ProcessService::check_command_succeeds("true", worktree, Duration::from_secs(10)).await
+        );
+
+        let started = Instant::now();
+        let passed = ProcessService::check_command_succeeds(
+            "sleep 30",
+            worktree,
+            Duration::from_millis(200),
+        )
+        .await;
+        assert!(!passed);
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    #[tokio::test]
+    async fn test_fork_resumes_the_session_it_was_forked_from() {
+        let app_state = test_app_state().await;
+        let pool = &app_state.db_pool;
+        let dir = tempfile::tempdir().unwrap();
+        let worktree = dir.path().to_str().unwrap();
+        let scenario = dir.path().join("scenario.json");
+        // Forks, like follow-ups, replay the follow-up run
+        std::fs::write(
+            &scenario,
+            r#"{"session_id": "s-1", "steps": [], "follow_up": {"session_id": "s-2", "steps": []}}"#,
+        )
+        .unwrap();
+        let executor = format!("mock:{}", scenario.display());
+
+        let project_id = insert_project(pool, worktree).await;
+        let task_id = insert_task(pool, project_id).await;
+        let source_id = insert_attempt(pool, task_id, worktree, "source", &executor).await;
+        let source_process = insert_running_process(pool, source_id).await;
+        sqlx::query(
+            "UPDATE execution_processes SET executor_type = $1, status = 'completed' WHERE id = $2",
+        )
+        .bind(&executor)
+        .bind(source_process)
+        .execute(pool)
+        .await
+        .unwrap();
+        let session_id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO executor_sessions (id, task_attempt_id, execution_process_id, session_id, summary)
+             VALUES ($1, $2, $3, 's-1', 'Added OAuth login.')",
+        )
+        .bind(session_id)
+        .bind(source_id)
+        .bind(source_process)
+        .execute(pool)
+        .await
+        .unwrap();
+        let fork_id = insert_attempt(pool, task_id, worktree, "fork", &executor).await;
+        sqlx::query("UPDATE task_attempts SET forked_from_session_id = $1 WHERE id = $2")
+            .bind(session_id)
+            .bind(fork_id)
+            .execute(pool)
+            .await
+            .unwrap();
+
+        ProcessService::start_followup_execution_direct(
+            pool,
+            &app_state,
+            fork_id,
+            task_id,
+            project_id,
+            "Now add logout",
+        )
+        .await
+        .unwrap();
+
+        let processes = ExecutionProcess::find_by_task_attempt_id(pool, fork_id)
+            .await
+            .unwrap();
+        assert_eq!(processes.len(), 1);
+        assert_eq!(processes[0].command, "forked_executor");
+        let session = ExecutorSession::find_by_execution_process_id(pool, processes[0].id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(session.session_id.as_deref(), Some("s-2"));
+        let prompt = session.prompt.unwrap();
+        assert!(prompt.ends_with("Now add logout"));
+        assert!(!prompt.contains("Added OAuth login."));
+    }
+
+    #[tokio::test]
+    async fn test_retry_is_linked_to_the_original_when_created() {
+        let app_state = test_app_state().await;
+        let pool = &app_state.db_pool;
+        let dir = tempfile::tempdir().unwrap();
+        let worktree = dir.path().to_str().unwrap();
+        let scenario = dir.path().join("scenario.json");
+        std::fs::write(&scenario, r#"{"steps": []}"#).unwrap();
+        let executor = format!("mock:{}", scenario.display());
+
+        let project_id = insert_project(pool, worktree).await;
+        let task_id = insert_task(pool, project_id).await;
+        let attempt_id = insert_attempt(pool, task_id, worktree, "main", &executor).await;
+        let failed_id = insert_running_process(pool, attempt_id).await;
+        sqlx::query(
+            "UPDATE execution_processes SET executor_type = $1, status = 'failed' WHERE id = $2",
+        )
+        .bind(&executor)
+        .bind(failed_id)
+        .execute(pool)
+        .await
+        .unwrap();
+        let failed = ExecutionProcess::find_by_id(pool, failed_id)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let retry_id = ProcessService::retry_execution(pool, &app_state, &failed, failed_id)
+            .await
+            .unwrap();
+
+        let retry = ExecutionProcess::find_by_id(pool, retry_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(retry.retry_of_process_id, Some(failed_id));
+        assert_eq!(
+            ExecutionProcess::count_retries(pool, failed_id)
+                .await
+                .unwrap(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_retried_follow_up_resends_its_message_to_the_earlier_session() {
+        let app_state = test_app_state().await;
+        let pool = &app_state.db_pool;
+        let dir = tempfile::tempdir().unwrap();
+        let worktree = dir.path().to_str().unwrap();
+        let scenario = dir.path().join("scenario.json");
+        std::fs::write(
+            &scenario,
+            r#"{"session_id": "s-1", "steps": [], "follow_up": {"session_id": "s-2", "steps": []}}"#,
+        )
+        .unwrap();
+        let executor = format!("mock:{}", scenario.display());
+
+        let project_id = insert_project(pool, worktree).await;
+        let task_id = insert_task(pool, project_id).await;
+        let attempt_id = insert_attempt(pool, task_id, worktree, "main", &executor).await;
+        let first_id = insert_running_process(pool, attempt_id).await;
+        let failed_id = insert_running_process(pool, attempt_id).await;
+        sqlx::query(
+            "UPDATE execution_processes SET executor_type = $1, status = 'completed',
+                created_at = '2026-01-01 00:00:00'
+             WHERE id = $2",
+        )
+        .bind(&executor)
+        .bind(first_id)
+        .execute(pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "UPDATE execution_processes SET executor_type = $1, status = 'failed',
+                command = 'followup_executor', created_at = '2026-01-01 00:01:00'
+             WHERE id = $2",
+        )
+        .bind(&executor)
+        .bind(failed_id)
+        .execute(pool)
+        .await
+        .unwrap();
+        for (process_id, session_id, prompt, created_at) in [
+            (first_id, Some("s-1"), "Add login", "2026-01-01 00:00:00"),
+            (failed_id, None, "Now add logout", "2026-01-01 00:01:00"),
+        ] {
+            sqlx::query(
+                "INSERT INTO executor_sessions
+                    (id, task_attempt_id, execution_process_id, session_id, prompt,
+                     created_at, updated_at)
+                 VALUES ($1, $2, $3, $4, $5, $6, $6)",
+            )
+            .bind(Uuid::new_v4())
+            .bind(attempt_id)
+            .bind(process_id)
+            .bind(session_id)
+            .bind(prompt)
+            .bind(created_at)
+            .execute(pool)
+            .await
+            .unwrap();
+        }
+        let failed = ExecutionProcess::find_by_id(pool, failed_id)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let retry_id = ProcessService::retry_execution(pool, &app_state, &failed, failed_id)
+            .await
+            .unwrap();
+
+        let retry = ExecutionProcess::find_by_id(pool, retry_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(retry.command, "followup_executor");
+        let session = ExecutorSession::find_by_execution_process_id(pool, retry_id)
             .await
-            .map_err(|e| TaskAttemptError::Git(git2::Error::from_str(&e.to_string())))
+            .unwrap()
+            .unwrap();
+        assert_eq!(session.prompt.as_deref(), Some("Now add logout"));
     }
 }
@@ -0,0 +1,104 @@
+//! Fixtures for tests that need a database or application state
+
+use std::sync::Arc;
+
+use sqlx::{sqlite::SqlitePoolOptions, SqlitePool};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    models::{Config, Environment},
+};
+
+/// A migrated in-memory database
+pub async fn test_pool() -> SqlitePool {
+    // Every connection to :memory: gets its own database, so keep to one
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("open in-memory database");
+    sqlx::migrate!("./migrations")
+        .run(&pool)
+        .await
+        .expect("run migrations");
+    pool
+}
+
+/// Application state over a fresh database, with analytics and notifications off
+pub async fn test_app_state() -> AppState {
+    let config = Config {
+        analytics_enabled: Some(false),
+        sound_alerts: false,
+        push_notifications: false,
+        ..Config::default()
+    };
+    AppState::new(
+        test_pool().await,
+        Arc::new(RwLock::new(config)),
+        Environment::Local,
+    )
+    .await
+}
+
+pub async fn insert_project(pool: &SqlitePool, git_repo_path: &str) -> Uuid {
+    let id = Uuid::new_v4();
+    sqlx::query("INSERT INTO projects (id, name, git_repo_path) VALUES ($1, 'test', $2)")
+        .bind(id)
+        .bind(git_repo_path)
+        .execute(pool)
+        .await
+        .expect("insert project");
+    id
+}
+
+pub async fn insert_task(pool: &SqlitePool, project_id: Uuid) -> Uuid {
+    let id = Uuid::new_v4();
+    sqlx::query("INSERT INTO tasks (id, project_id, title) VALUES ($1, $2, 'test task')")
+        .bind(id)
+        .bind(project_id)
+        .execute(pool)
+        .await
+        .expect("insert task");
+    id
+}
+
+pub async fn insert_attempt(
+    pool: &SqlitePool,
+    task_id: Uuid,
+    worktree_path: &str,
+    branch: &str,
+    executor: &str,
+) -> Uuid {
+    let id = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO task_attempts (id, task_id, worktree_path, branch, executor)
+         VALUES ($1, $2, $3, $4, $5)",
+    )
+    .bind(id)
+    .bind(task_id)
+    .bind(worktree_path)
+    .bind(branch)
+    .bind(executor)
+    .execute(pool)
+    .await
+    .expect("insert task attempt");
+    id
+}
+
+/// A running coding agent process record for `attempt_id`
+pub async fn insert_running_process(pool: &SqlitePool, attempt_id: Uuid) -> Uuid {
+    let id = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO execution_processes
+            (id, task_attempt_id, command, working_directory, process_type, status)
+         VALUES ($1, $2, 'sleep', '/tmp', 'codingagent', 'running')",
+    )
+    .bind(id)
+    .bind(attempt_id)
+    .execute(pool)
+    .await
+    .expect("insert execution process");
+    id
+}
@@ -53,6 +53,16 @@ pub fn config_path() -> std::path::PathBuf {
     asset_dir().join("config.json")
 }
 
+/// User-declared executors, see `executors::custom`
+pub fn custom_executors_path() -> std::path::PathBuf {
+    asset_dir().join("custom_executors.json")
+}
+
+/// Uploaded task attachments, one directory per task, see `models::task_attachment`
+pub fn attachments_dir() -> std::path::PathBuf {
+    asset_dir().join("attachments")
+}
+
 pub fn cache_dir() -> std::path::PathBuf {
     let proj = if cfg!(debug_assertions) {
         ProjectDirs::from("ai", "bloop-dev", env!("CARGO_PKG_NAME"))
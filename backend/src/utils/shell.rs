@@ -17,3 +17,12 @@ pub fn get_shell_command() -> (&'static str, &'static str) {
         }
     }
 }
+
+/// Quote a value for the platform shell used to run executor commands
+pub fn shell_quote(value: &str, shell_cmd: &str) -> String {
+    if shell_cmd == "cmd" {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        format!("'{}'", value.replace('\'', "'\\''"))
+    }
+}
@@ -6,7 +6,7 @@ import {
   useEffect,
   useState,
 } from 'react';
-import type { Config } from 'shared/types';
+import type { Config, ExecutorCapabilities } from 'shared/types';
 import { configApi, githubAuthApi } from '../lib/api';
 
 interface ConfigContextType {
@@ -16,6 +16,8 @@ interface ConfigContextType {
   saveConfig: () => Promise<boolean>;
   loading: boolean;
   githubTokenInvalid: boolean;
+  // What an executor can do, looked up by its id (e.g. "claude-plan")
+  capabilities: (executor?: string | null) => ExecutorCapabilities | null;
 }
 
 const ConfigContext = createContext<ConfigContextType | undefined>(undefined);
@@ -28,6 +30,9 @@ export function ConfigProvider({ children }: ConfigProviderProps) {
   const [config, setConfig] = useState<Config | null>(null);
   const [loading, setLoading] = useState(true);
   const [githubTokenInvalid, setGithubTokenInvalid] = useState(false);
+  const [executorCapabilities, setExecutorCapabilities] = useState<
+    ExecutorCapabilities[]
+  >([]);
 
   useEffect(() => {
     const loadConfig = async () => {
@@ -41,7 +46,17 @@ export function ConfigProvider({ children }: ConfigProviderProps) {
       }
     };
 
+    const loadConstants = async () => {
+      try {
+        const constants = await configApi.getConstants();
+        setExecutorCapabilities(constants.executor.executor_capabilities);
+      } catch (err) {
+        console.error('Error loading config constants:', err);
+      }
+    };
+
     loadConfig();
+    loadConstants();
   }, []);
 
   // Check GitHub token validity after config loads
@@ -58,6 +73,12 @@ export function ConfigProvider({ children }: ConfigProviderProps) {
     checkToken();
   }, [loading]);
 
+  const capabilities = useCallback(
+    (executor?: string | null) =>
+      executorCapabilities.find((c) => c.executor === executor) ?? null,
+    [executorCapabilities]
+  );
+
   const updateConfig = useCallback((updates: Partial<Config>) => {
     setConfig((prev) => (prev ? { ...prev, ...updates } : null));
   }, []);
@@ -103,6 +124,7 @@ export function ConfigProvider({ children }: ConfigProviderProps) {
         loading,
         updateAndSaveConfig,
         githubTokenInvalid,
+        capabilities,
       }}
     >
       {children}
@@ -33,7 +33,7 @@ import {
   TaskSelectedAttemptContext,
 } from './taskDetailsContext.ts';
 import { TaskPlanContext } from './TaskPlanContext.ts';
-import { is_planning_executor_type } from '@/lib/utils.ts';
+import { useConfig } from '@/components/config-provider.tsx';
 import type { AttemptData } from '@/lib/types.ts';
 
 const TaskDetailsProvider: FC<{
@@ -49,6 +49,7 @@ const TaskDetailsProvider: FC<{
   setShowEditorDialog,
   projectHasDevScript,
 }) => {
+  const { capabilities } = useConfig();
   const [loading, setLoading] = useState(false);
   const [isStopping, setIsStopping] = useState(false);
   const [selectedAttempt, setSelectedAttempt] = useState<TaskAttempt | null>(
@@ -285,6 +286,7 @@ const TaskDetailsProvider: FC<{
     return attemptData.processes.some(
       (process: ExecutionProcessSummary) =>
         (process.process_type === 'codingagent' ||
+          process.process_type === 'planningagent' ||
           process.process_type === 'setupscript' ||
           process.process_type === 'cleanupscript') &&
         process.status === 'running'
@@ -443,8 +445,8 @@ const TaskDetailsProvider: FC<{
     const isPlanningMode =
       attemptData.processes?.some(
         (process) =>
-          process.executor_type &&
-          is_planning_executor_type(process.executor_type)
+          process.process_type === 'planningagent' ||
+          capabilities(process.executor_type)?.plan_mode === 'always'
       ) ?? false;
 
     const planCount =
@@ -486,7 +488,7 @@ const TaskDetailsProvider: FC<{
       latestProcessHasNoPlan,
       canCreateTask,
     };
-  }, [attemptData.processes, attemptData.allLogs]);
+  }, [attemptData.processes, attemptData.allLogs, capabilities]);
 
   return (
     <TaskDetailsContext.Provider value={value}>
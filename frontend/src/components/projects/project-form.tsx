@@ -1,6 +1,13 @@
 import { useEffect, useState } from 'react';
 import { Button } from '@/components/ui/button';
 import { Label } from '@/components/ui/label';
+import {
+  Select,
+  SelectContent,
+  SelectItem,
+  SelectTrigger,
+  SelectValue,
+} from '@/components/ui/select';
 import { Loader2 } from 'lucide-react';
 import {
   Dialog,
@@ -21,9 +28,39 @@ import {
   Project,
   UpdateProject,
   Environment,
+  SandboxMode,
 } from 'shared/types';
 import { projectsApi, configApi, githubApi, RepositoryInfo } from '@/lib/api';
 
+const PROMPT_TEMPLATE_PLACEHOLDER = `Task: {task_title}
+{task_description}
+
+{persona_instructions}
+
+{conventions}`;
+
+const SANDBOX_OPTIONS: {
+  value: SandboxMode;
+  label: string;
+  description: string;
+}[] = [
+  {
+    value: 'disabled',
+    label: 'Off',
+    description: 'Processes run with your full user permissions',
+  },
+  {
+    value: 'enabled',
+    label: 'Sandboxed',
+    description: 'Only the worktree is writable; the rest of $HOME is hidden',
+  },
+  {
+    value: 'offline',
+    label: 'Sandboxed, offline',
+    description: 'As sandboxed, with no network access',
+  },
+];
+
 interface ProjectFormProps {
   open: boolean;
   onClose: () => void;
@@ -44,6 +81,12 @@ export function ProjectForm({
   const [cleanupScript, setCleanupScript] = useState(
     project?.cleanup_script ?? ''
   );
+  const [promptTemplate, setPromptTemplate] = useState(
+    project?.prompt_template ?? ''
+  );
+  const [sandboxMode, setSandboxMode] = useState<SandboxMode>(
+    project?.sandbox_mode ?? 'disabled'
+  );
   const [loading, setLoading] = useState(false);
   const [error, setError] = useState('');
   const [showFolderPicker, setShowFolderPicker] = useState(false);
@@ -87,12 +130,16 @@ export function ProjectForm({
       setSetupScript(project.setup_script ?? '');
       setDevScript(project.dev_script ?? '');
       setCleanupScript(project.cleanup_script ?? '');
+      setPromptTemplate(project.prompt_template ?? '');
+      setSandboxMode(project.sandbox_mode);
     } else {
       setName('');
       setGitRepoPath('');
       setSetupScript('');
       setDevScript('');
       setCleanupScript('');
+      setPromptTemplate('');
+      setSandboxMode('disabled');
       setSelectedRepository(null);
     }
   }, [project]);
@@ -134,6 +181,13 @@ export function ProjectForm({
           setup_script: setupScript.trim() || null,
           dev_script: devScript.trim() || null,
           cleanup_script: cleanupScript.trim() || null,
+          max_runtime_seconds: project.max_runtime_seconds,
+          idle_timeout_seconds: project.idle_timeout_seconds,
+          prompt_template: promptTemplate.trim() || null,
+          sandbox_mode: sandboxMode,
+          memory_limit_mb: project.memory_limit_mb,
+          cpu_limit_percent: project.cpu_limit_percent,
+          max_processes: project.max_processes,
         };
 
         await projectsApi.update(project.id, updateData);
@@ -153,6 +207,13 @@ export function ProjectForm({
             setup_script: setupScript.trim() || null,
             dev_script: devScript.trim() || null,
             cleanup_script: cleanupScript.trim() || null,
+            max_runtime_seconds: null,
+            idle_timeout_seconds: null,
+            prompt_template: null,
+            sandbox_mode: null,
+            memory_limit_mb: null,
+            cpu_limit_percent: null,
+            max_processes: null,
           };
 
           await githubApi.createProjectFromRepository(githubData);
@@ -173,6 +234,13 @@ export function ProjectForm({
             setup_script: setupScript.trim() || null,
             dev_script: devScript.trim() || null,
             cleanup_script: cleanupScript.trim() || null,
+            max_runtime_seconds: null,
+            idle_timeout_seconds: null,
+            prompt_template: null,
+            sandbox_mode: null,
+            memory_limit_mb: null,
+            cpu_limit_percent: null,
+            max_processes: null,
           };
 
           await projectsApi.create(createData);
@@ -259,6 +327,56 @@ export function ProjectForm({
                   setCleanupScript={setCleanupScript}
                   error={error}
                 />
+                <div className="space-y-2">
+                  <Label htmlFor="prompt-template">
+                    Agent Prompt Template (optional)
+                  </Label>
+                  <textarea
+                    id="prompt-template"
+                    placeholder={PROMPT_TEMPLATE_PLACEHOLDER}
+                    value={promptTemplate}
+                    onChange={(e) => setPromptTemplate(e.target.value)}
+                    className="w-full p-2 border rounded-md font-mono text-sm"
+                    rows={6}
+                  />
+                  <p className="text-sm text-muted-foreground">
+                    Variables: {'{task_title}'}, {'{task_description}'},{' '}
+                    {'{parent_plan}'}, {'{persona_instructions}'},{' '}
+                    {'{branch}'}, {'{base_branch}'}, {'{conventions}'},{' '}
+                    {'{follow_up}'}, {'{project_id}'}. Lines whose variables
+                    are all empty are left out. Leave blank for the default
+                    prompt.
+                  </p>
+                </div>
+                <div className="space-y-2">
+                  <Label htmlFor="sandbox-mode">Sandbox (Linux only)</Label>
+                  <Select
+                    value={sandboxMode}
+                    onValueChange={(value) =>
+                      setSandboxMode(value as SandboxMode)
+                    }
+                  >
+                    <SelectTrigger id="sandbox-mode">
+                      <SelectValue />
+                    </SelectTrigger>
+                    <SelectContent>
+                      {SANDBOX_OPTIONS.map((option) => (
+                        <SelectItem key={option.value} value={option.value}>
+                          <div className="flex flex-col">
+                            <span className="font-medium">{option.label}</span>
+                            <span className="text-xs text-muted-foreground">
+                              {option.description}
+                            </span>
+                          </div>
+                        </SelectItem>
+                      ))}
+                    </SelectContent>
+                  </Select>
+                  <p className="text-sm text-muted-foreground">
+                    Sandboxed processes can read the toolchain and agent CLI
+                    config, but not the rest of your home directory.
+                  </p>
+                </div>
                 <DialogFooter>
                   <Button
                     type="button"
@@ -17,7 +17,7 @@ import {
   XCircle,
 } from 'lucide-react';
 import type { TaskWithAttemptStatus, ProjectPersonaWithTemplate } from 'shared/types';
-import { is_planning_executor_type } from '@/lib/utils';
+import { useConfig } from '@/components/config-provider.tsx';
 
 type Task = TaskWithAttemptStatus;
 
@@ -44,6 +44,7 @@ export function TaskCard({
   isFocused,
   tabIndex = -1,
 }: TaskCardProps) {
+  const { capabilities } = useConfig();
   // Find the assigned persona
   const assignedPersona = personas.find(p => p.id === task.assigned_persona_id);
   const localRef = useRef<HTMLDivElement>(null);
@@ -86,12 +87,12 @@ export function TaskCard({
           <div className="flex-1 pr-2">
             <div className="mb-1">
               <h4 className="font-medium text-sm break-words">
-                {task.latest_attempt_executor &&
-                  is_planning_executor_type(task.latest_attempt_executor) && (
-                    <Badge className="bg-blue-600 text-white hover:bg-blue-700 text-xs font-medium px-1.5 py-0.5 h-4 text-[10px] mr-1">
-                      PLAN
-                    </Badge>
-                  )}
+                {capabilities(task.latest_attempt_executor)?.plan_mode ===
+                  'always' && (
+                  <Badge className="bg-blue-600 text-white hover:bg-blue-700 text-xs font-medium px-1.5 py-0.5 h-4 text-[10px] mr-1">
+                    PLAN
+                  </Badge>
+                )}
                 {task.title}
               </h4>
             </div>
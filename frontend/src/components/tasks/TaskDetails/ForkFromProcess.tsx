@@ -0,0 +1,100 @@
+import { useContext, useEffect, useState } from 'react';
+import { GitFork } from 'lucide-react';
+import {
+  TaskAttemptDataContext,
+  TaskDetailsContext,
+  TaskSelectedAttemptContext,
+} from '@/components/context/taskDetailsContext.ts';
+import { Button } from '@/components/ui/button';
+import { Textarea } from '@/components/ui/textarea';
+import { attemptsApi } from '@/lib/api.ts';
+import type { ExecutorSession } from 'shared/types';
+
+type Props = {
+  processId: string;
+};
+
+// Starts a new attempt from the commit and agent session an earlier run left
+// behind, leaving the current attempt untouched
+function ForkFromProcess({ processId }: Props) {
+  const { projectId } = useContext(TaskDetailsContext);
+  const { selectedAttempt, setSelectedAttempt } = useContext(
+    TaskSelectedAttemptContext
+  );
+  const { fetchAttemptData } = useContext(TaskAttemptDataContext);
+  const [session, setSession] = useState<ExecutorSession | null>(null);
+  const [prompt, setPrompt] = useState('');
+  const [error, setError] = useState<string | null>(null);
+  const [busy, setBusy] = useState(false);
+
+  useEffect(() => {
+    if (!selectedAttempt) return;
+    attemptsApi
+      .getSessions(projectId, selectedAttempt.task_id, selectedAttempt.id)
+      .then((sessions) =>
+        setSession(
+          sessions.find(
+            (s) =>
+              s.execution_process_id === processId &&
+              s.session_id !== null &&
+              s.after_head_commit !== null
+          ) ?? null
+        )
+      )
+      .catch(() => setSession(null));
+  }, [projectId, selectedAttempt, processId]);
+
+  if (!selectedAttempt || !session) return null;
+
+  const fork = async () => {
+    setBusy(true);
+    setError(null);
+    try {
+      const attempt = await attemptsApi.fork(
+        projectId,
+        selectedAttempt.task_id,
+        selectedAttempt.id,
+        { executor_session_id: session.id, prompt: prompt.trim() }
+      );
+      setPrompt('');
+      setSelectedAttempt(attempt);
+      fetchAttemptData(attempt.id, attempt.task_id);
+    } catch (err) {
+      setError(err instanceof Error ? err.message : 'Failed to fork');
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  return (
+    <div>
+      <h3 className="font-medium text-sm mb-2">Fork From Here</h3>
+      <p className="text-xs text-muted-foreground mb-2">
+        Starts a new attempt at commit{' '}
+        <span className="font-mono">
+          {session.after_head_commit?.slice(0, 8)}
+        </span>{' '}
+        and resumes this run's session with a new instruction.
+      </p>
+      <Textarea
+        value={prompt}
+        onChange={(e) => setPrompt(e.target.value)}
+        placeholder="What should the agent try instead?"
+        rows={3}
+        className="text-sm"
+      />
+      {error && <p className="text-xs text-destructive mt-1">{error}</p>}
+      <Button
+        size="sm"
+        className="mt-2"
+        onClick={fork}
+        disabled={busy || !prompt.trim()}
+      >
+        <GitFork className="h-3 w-3 mr-1.5" />
+        Fork
+      </Button>
+    </div>
+  );
+}
+
+export default ForkFromProcess;
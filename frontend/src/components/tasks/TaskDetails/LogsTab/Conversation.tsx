@@ -14,6 +14,7 @@ import { Button } from '@/components/ui/button';
 import { AlertTriangle } from 'lucide-react';
 import Prompt from './Prompt';
 import ConversationEntry from './ConversationEntry';
+import PendingPermissions from './PendingPermissions';
 import { ConversationEntryDisplayType } from '@/lib/types';
 
 function Conversation() {
@@ -58,8 +59,10 @@ function Conversation() {
     () =>
       attemptData.allLogs.find(
         (log) =>
-          log.process_type.toLowerCase() === 'codingagent' &&
-          log.command === 'executor'
+          (log.process_type.toLowerCase() === 'codingagent' &&
+            log.command === 'executor') ||
+          (log.process_type.toLowerCase() === 'planningagent' &&
+            log.command === 'planning_executor')
       ),
     [attemptData.allLogs]
   );
@@ -67,8 +70,13 @@ function Conversation() {
     () =>
       attemptData.allLogs.filter(
         (log) =>
-          log.process_type.toLowerCase() === 'codingagent' &&
-          log.command === 'followup_executor'
+          (log.process_type.toLowerCase() === 'codingagent' &&
+            log.command === 'followup_executor') ||
+          (log.process_type.toLowerCase() === 'planningagent' &&
+            log.command === 'followup_planning_executor') ||
+          log.process_type.toLowerCase() === 'reviewagent' ||
+          // Automatic retries continue the conversation of the run they replace
+          log.retry_of_process_id !== null
       ),
     [attemptData.allLogs]
   );
@@ -192,7 +200,8 @@ function Conversation() {
   const showStatusBanner =
     mostRecentProcess &&
     (mostRecentProcess.status === 'failed' ||
-      mostRecentProcess.status === 'killed');
+      mostRecentProcess.status === 'killed' ||
+      mostRecentProcess.status === 'timedout');
 
   return (
     <div
@@ -231,6 +240,9 @@ function Conversation() {
         />
       )}
 
+      {/* Tool calls waiting for the user to approve or reject them */}
+      <PendingPermissions />
+
       {/* Status banner for failed/stopped states - shown at bottom */}
       {showStatusBanner && mostRecentProcess && (
         <div className="mt-4 p-4 rounded-lg border">
@@ -243,12 +255,16 @@ function Conversation() {
           >
             {mostRecentProcess.status === 'failed'
               ? 'Coding Agent Failed'
-              : 'Coding Agent Stopped'}
+              : mostRecentProcess.status === 'timedout'
+                ? 'Coding Agent Timed Out'
+                : 'Coding Agent Stopped'}
           </p>
           <p className="text-muted-foreground">
             {mostRecentProcess.status === 'failed'
               ? 'The coding agent encountered an error.'
-              : 'The coding agent was stopped.'}
+              : mostRecentProcess.status === 'timedout'
+                ? 'The coding agent exceeded its runtime or idle limit.'
+                : 'The coding agent was stopped.'}
           </p>
         </div>
       )}
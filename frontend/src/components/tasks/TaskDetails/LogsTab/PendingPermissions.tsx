@@ -0,0 +1,119 @@
+import { useCallback, useContext, useEffect, useState } from 'react';
+import { ShieldAlert } from 'lucide-react';
+import {
+  TaskAttemptDataContext,
+  TaskDetailsContext,
+  TaskSelectedAttemptContext,
+} from '@/components/context/taskDetailsContext.ts';
+import { Button } from '@/components/ui/button';
+import { permissionsApi } from '@/lib/api.ts';
+import type { PermissionDecision, PermissionRequest } from 'shared/types';
+
+// Show the most useful part of a tool's input: its command or path
+function describeInput(input: string): string {
+  try {
+    const parsed = JSON.parse(input);
+    return (
+      parsed.command ??
+      parsed.file_path ??
+      parsed.notebook_path ??
+      parsed.path ??
+      JSON.stringify(parsed, null, 2)
+    );
+  } catch {
+    return input;
+  }
+}
+
+function PendingPermissions() {
+  const { projectId } = useContext(TaskDetailsContext);
+  const { selectedAttempt } = useContext(TaskSelectedAttemptContext);
+  const { isAttemptRunning } = useContext(TaskAttemptDataContext);
+  const [requests, setRequests] = useState<PermissionRequest[]>([]);
+  const [decidingId, setDecidingId] = useState<string | null>(null);
+
+  const fetchRequests = useCallback(async () => {
+    if (!selectedAttempt) return;
+    try {
+      const result = await permissionsApi.getRequests(projectId, {
+        taskAttemptId: selectedAttempt.id,
+        pending: true,
+      });
+      setRequests(result);
+    } catch (err) {
+      console.error('Failed to fetch permission requests:', err);
+    }
+  }, [projectId, selectedAttempt]);
+
+  useEffect(() => {
+    if (!isAttemptRunning) {
+      setRequests([]);
+      return;
+    }
+
+    fetchRequests();
+    const interval = setInterval(fetchRequests, 2000);
+    return () => clearInterval(interval);
+  }, [isAttemptRunning, fetchRequests]);
+
+  const decide = async (requestId: string, decision: PermissionDecision) => {
+    setDecidingId(requestId);
+    try {
+      await permissionsApi.decide(projectId, requestId, {
+        decision,
+        message: null,
+      });
+    } catch (err) {
+      console.error('Failed to decide permission request:', err);
+    } finally {
+      setDecidingId(null);
+      fetchRequests();
+    }
+  };
+
+  if (requests.length === 0) return null;
+
+  return (
+    <div className="mt-4 space-y-2">
+      {requests.map((request) => (
+        <div
+          key={request.id}
+          className="p-4 rounded-lg border border-orange-200 dark:border-orange-800 bg-orange-50 dark:bg-orange-950/20"
+        >
+          <div className="flex items-center gap-2 mb-2">
+            <ShieldAlert className="h-5 w-5 text-orange-600 dark:text-orange-400" />
+            <p className="font-semibold text-orange-800 dark:text-orange-300">
+              {request.tool_name} needs approval
+            </p>
+          </div>
+          <pre className="text-sm whitespace-pre-wrap break-all mb-3">
+            {describeInput(request.input)}
+          </pre>
+          <div className="flex items-center gap-2">
+            <Button
+              size="sm"
+              disabled={decidingId === request.id}
+              onClick={() => decide(request.id, 'allow')}
+            >
+              Approve
+            </Button>
+            <Button
+              size="sm"
+              variant="outline"
+              disabled={decidingId === request.id}
+              onClick={() => decide(request.id, 'deny')}
+            >
+              Reject
+            </Button>
+            <span className="text-xs text-muted-foreground">
+              Defaults to {request.default_decision} at{' '}
+              {new Date(request.expires_at).toLocaleTimeString()}
+            </span>
+          </div>
+        </div>
+      ))}
+    </div>
+  );
+}
+
+export default PendingPermissions;
@@ -10,6 +10,8 @@ import {
 } from 'lucide-react';
 import { TaskAttemptDataContext } from '@/components/context/taskDetailsContext.ts';
 import { executionProcessesApi } from '@/lib/api.ts';
+import { useConfig } from '@/components/config-provider.tsx';
+import ForkFromProcess from '@/components/tasks/TaskDetails/ForkFromProcess.tsx';
 import type {
   ExecutionProcessStatus,
   ExecutionProcessSummary,
@@ -17,6 +19,7 @@ import type {
 
 function ProcessesTab() {
   const { attemptData, setAttemptData } = useContext(TaskAttemptDataContext);
+  const { capabilities } = useConfig();
   const [selectedProcessId, setSelectedProcessId] = useState<string | null>(
     null
   );
@@ -32,6 +35,8 @@ function ProcessesTab() {
         return <AlertCircle className="h-4 w-4 text-red-500" />;
       case 'killed':
         return <Square className="h-4 w-4 text-gray-500" />;
+      case 'timedout':
+        return <Clock className="h-4 w-4 text-orange-500" />;
       default:
         return <Clock className="h-4 w-4 text-gray-400" />;
     }
@@ -47,6 +52,8 @@ function ProcessesTab() {
         return 'bg-red-50 border-red-200 text-red-800';
       case 'killed':
         return 'bg-gray-50 border-gray-200 text-gray-800';
+      case 'timedout':
+        return 'bg-orange-50 border-orange-200 text-orange-800';
       default:
         return 'bg-gray-50 border-gray-200 text-gray-800';
     }
@@ -203,10 +210,36 @@ function ProcessesTab() {
                           {selectedProcess.executor_type}
                         </p>
                       )}
+                      {selectedProcess.model && (
+                        <p>
+                          <span className="font-medium">Model:</span>{' '}
+                          {selectedProcess.model}
+                        </p>
+                      )}
+                      {selectedProcess.cli_version && (
+                        <p>
+                          <span className="font-medium">CLI Version:</span>{' '}
+                          {selectedProcess.cli_version}
+                        </p>
+                      )}
                       <p>
                         <span className="font-medium">Exit Code:</span>{' '}
                         {selectedProcess.exit_code?.toString() ?? 'N/A'}
                       </p>
+                      {selectedProcess.timeout_reason && (
+                        <p>
+                          <span className="font-medium">Timed Out:</span>{' '}
+                          {selectedProcess.timeout_reason}
+                        </p>
+                      )}
+                      {selectedProcess.retry_of_process_id && (
+                        <p>
+                          <span className="font-medium">Retry Of:</span>{' '}
+                          <span className="font-mono">
+                            {selectedProcess.retry_of_process_id}
+                          </span>
+                        </p>
+                      )}
                     </div>
                   </div>
                   <div>
@@ -222,6 +255,25 @@ function ProcessesTab() {
                           {formatDate(selectedProcess.completed_at)}
                         </p>
                       )}
+                      {selectedProcess.cpu_time_ms !== null && (
+                        <p>
+                          <span className="font-medium">CPU Time:</span>{' '}
+                          {(Number(selectedProcess.cpu_time_ms) / 1000).toFixed(
+                            1
+                          )}
+                          s
+                        </p>
+                      )}
+                      {selectedProcess.peak_memory_bytes !== null && (
+                        <p>
+                          <span className="font-medium">Peak Memory:</span>{' '}
+                          {(
+                            Number(selectedProcess.peak_memory_bytes) /
+                            (1024 * 1024)
+                          ).toFixed(0)}{' '}
+                          MB
+                        </p>
+                      )}
                     </div>
                   </div>
                 </div>
@@ -247,6 +299,12 @@ function ProcessesTab() {
                   </div>
                 </div>
 
+                {selectedProcess.status !== 'running' &&
+                  capabilities(selectedProcess.executor_type)
+                    ?.session_forking && (
+                    <ForkFromProcess processId={selectedProcess.id} />
+                  )}
+
                 {selectedProcess.stdout && (
                   <div>
                     <h3 className="font-medium text-sm mb-2">Stdout</h3>
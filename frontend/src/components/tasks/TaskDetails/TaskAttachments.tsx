@@ -0,0 +1,185 @@
+import { useCallback, useContext, useEffect, useRef, useState } from 'react';
+import { FileText, FolderGit2, Image, Paperclip, X } from 'lucide-react';
+import { TaskDetailsContext } from '@/components/context/taskDetailsContext.ts';
+import { Button } from '@/components/ui/button';
+import { Input } from '@/components/ui/input';
+import { Textarea } from '@/components/ui/textarea';
+import { attachmentsApi } from '@/lib/api.ts';
+import type { AttachmentKind, TaskAttachment } from 'shared/types';
+
+const kindIcons: Record<AttachmentKind, typeof FileText> = {
+  file: FileText,
+  image: Image,
+  log: FileText,
+  repo_path: FolderGit2,
+};
+
+type Draft =
+  | { type: 'log'; content: string }
+  | { type: 'repo_path'; path: string };
+
+function TaskAttachments() {
+  const { task, projectId } = useContext(TaskDetailsContext);
+  const [attachments, setAttachments] = useState<TaskAttachment[]>([]);
+  const [draft, setDraft] = useState<Draft | null>(null);
+  const [error, setError] = useState<string | null>(null);
+  const [busy, setBusy] = useState(false);
+  const fileInput = useRef<HTMLInputElement>(null);
+
+  const fetchAttachments = useCallback(async () => {
+    try {
+      setAttachments(await attachmentsApi.getAll(projectId, task.id));
+    } catch (err) {
+      console.error('Failed to fetch attachments:', err);
+    }
+  }, [projectId, task.id]);
+
+  useEffect(() => {
+    fetchAttachments();
+  }, [fetchAttachments]);
+
+  const run = async (action: () => Promise<unknown>) => {
+    setBusy(true);
+    setError(null);
+    try {
+      await action();
+      setDraft(null);
+    } catch (err) {
+      setError(err instanceof Error ? err.message : 'Failed to attach');
+    } finally {
+      setBusy(false);
+      fetchAttachments();
+    }
+  };
+
+  const upload = (files: FileList | null) => {
+    if (!files || files.length === 0) return;
+    run(() => attachmentsApi.upload(projectId, task.id, Array.from(files)));
+    if (fileInput.current) fileInput.current.value = '';
+  };
+
+  const saveDraft = () => {
+    if (!draft) return;
+    run(() =>
+      attachmentsApi.create(
+        projectId,
+        task.id,
+        draft.type === 'log'
+          ? { type: 'log', name: null, content: draft.content }
+          : { type: 'repo_path', path: draft.path }
+      )
+    );
+  };
+
+  return (
+    <div className="mt-2 space-y-2">
+      {attachments.length > 0 && (
+        <div className="flex flex-wrap gap-1">
+          {attachments.map((attachment) => {
+            const Icon = kindIcons[attachment.kind];
+            return (
+              <span
+                key={attachment.id}
+                className="inline-flex items-center gap-1 rounded border px-2 py-0.5 text-xs"
+              >
+                <Icon className="h-3 w-3" />
+                <a
+                  href={attachmentsApi.contentUrl(
+                    projectId,
+                    task.id,
+                    attachment.id
+                  )}
+                  target="_blank"
+                  rel="noreferrer"
+                  className="hover:underline"
+                >
+                  {attachment.name}
+                </a>
+                <button
+                  onClick={() =>
+                    run(() =>
+                      attachmentsApi.delete(projectId, task.id, attachment.id)
+                    )
+                  }
+                  disabled={busy}
+                  className="text-muted-foreground hover:text-foreground"
+                >
+                  <X className="h-3 w-3" />
+                </button>
+              </span>
+            );
+          })}
+        </div>
+      )}
+
+      {draft?.type === 'log' && (
+        <Textarea
+          value={draft.content}
+          onChange={(e) => setDraft({ type: 'log', content: e.target.value })}
+          placeholder="Paste a log excerpt"
+          className="text-xs font-mono"
+          rows={4}
+        />
+      )}
+      {draft?.type === 'repo_path' && (
+        <Input
+          value={draft.path}
+          onChange={(e) =>
+            setDraft({ type: 'repo_path', path: e.target.value })
+          }
+          placeholder="Path inside the repository, e.g. src/main.rs"
+          className="h-8 text-xs"
+        />
+      )}
+
+      <div className="flex items-center gap-1">
+        <input
+          ref={fileInput}
+          type="file"
+          multiple
+          className="hidden"
+          onChange={(e) => upload(e.target.files)}
+        />
+        {draft ? (
+          <>
+            <Button size="sm" onClick={saveDraft} disabled={busy}>
+              Attach
+            </Button>
+            <Button size="sm" variant="ghost" onClick={() => setDraft(null)}>
+              Cancel
+            </Button>
+          </>
+        ) : (
+          <>
+            <Button
+              size="sm"
+              variant="ghost"
+              disabled={busy}
+              onClick={() => fileInput.current?.click()}
+            >
+              <Paperclip className="h-3 w-3 mr-1" />
+              Attach files
+            </Button>
+            <Button
+              size="sm"
+              variant="ghost"
+              onClick={() => setDraft({ type: 'log', content: '' })}
+            >
+              Add log
+            </Button>
+            <Button
+              size="sm"
+              variant="ghost"
+              onClick={() => setDraft({ type: 'repo_path', path: '' })}
+            >
+              Add repo path
+            </Button>
+          </>
+        )}
+      </div>
+      {error && <p className="text-xs text-destructive">{error}</p>}
+    </div>
+  );
+}
+
+export default TaskAttachments;
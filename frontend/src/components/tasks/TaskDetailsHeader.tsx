@@ -10,6 +10,7 @@ import {
 } from '@/components/ui/tooltip';
 import type { TaskStatus, TaskWithAttemptStatus } from 'shared/types';
 import { TaskDetailsContext } from '@/components/context/taskDetailsContext.ts';
+import TaskAttachments from '@/components/tasks/TaskDetails/TaskAttachments.tsx';
 
 interface TaskDetailsHeaderProps {
   onClose: () => void;
@@ -161,6 +162,8 @@ function TaskDetailsHeader({
             )}
           </div>
         </div>
+
+        <TaskAttachments />
       </div>
     </div>
   );
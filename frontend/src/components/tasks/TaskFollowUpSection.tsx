@@ -10,9 +10,11 @@ import {
   TaskSelectedAttemptContext,
 } from '@/components/context/taskDetailsContext.ts';
 import { Loader } from '@/components/ui/loader';
+import { useConfig } from '@/components/config-provider.tsx';
 
 export function TaskFollowUpSection() {
   const { task, projectId } = useContext(TaskDetailsContext);
+  const { capabilities } = useConfig();
   const { selectedAttempt } = useContext(TaskSelectedAttemptContext);
   const { attemptData, fetchAttemptData, isAttemptRunning } = useContext(
     TaskAttemptDataContext
@@ -22,9 +24,32 @@ export function TaskFollowUpSection() {
   const [isSendingFollowUp, setIsSendingFollowUp] = useState(false);
   const [followUpError, setFollowUpError] = useState<string | null>(null);
 
+  // While an agent that accepts input runs, messages go to it directly
+  // instead of starting a follow-up
+  const runningAgentProcess = useMemo(
+    () =>
+      attemptData.processes.find(
+        (process) =>
+          (process.process_type === 'codingagent' ||
+            process.process_type === 'planningagent') &&
+          process.status === 'running' &&
+          capabilities(process.executor_type)?.live_input
+      ),
+    [attemptData.processes, capabilities]
+  );
+
+  // Unknown executors are left to the server to reject
+  const supportsFollowUp =
+    capabilities(selectedAttempt?.executor)?.follow_up ?? true;
+
   const canSendFollowUp = useMemo(() => {
+    if (selectedAttempt && runningAgentProcess && !isSendingFollowUp) {
+      return true;
+    }
+
     if (
       !selectedAttempt ||
+      !supportsFollowUp ||
       attemptData.processes.length === 0 ||
       isAttemptRunning ||
       isSendingFollowUp
@@ -34,14 +59,19 @@ export function TaskFollowUpSection() {
 
     const completedOrKilledCodingAgentProcesses = attemptData.processes.filter(
       (process) =>
-        process.process_type === 'codingagent' &&
-        (process.status === 'completed' || process.status === 'killed')
+        (process.process_type === 'codingagent' ||
+          process.process_type === 'planningagent') &&
+        (process.status === 'completed' ||
+          process.status === 'killed' ||
+          process.status === 'timedout')
     );
 
     return completedOrKilledCodingAgentProcesses.length > 0;
   }, [
     selectedAttempt,
     attemptData.processes,
+    runningAgentProcess,
+    supportsFollowUp,
     isAttemptRunning,
     isSendingFollowUp,
   ]);
@@ -49,6 +79,27 @@ export function TaskFollowUpSection() {
   const onSendFollowUp = async () => {
     if (!task || !selectedAttempt || !followUpMessage.trim()) return;
 
+    if (runningAgentProcess) {
+      try {
+        setIsSendingFollowUp(true);
+        setFollowUpError(null);
+        await attemptsApi.sendProcessInput(
+          projectId!,
+          selectedAttempt.task_id,
+          selectedAttempt.id,
+          runningAgentProcess.id,
+          { message: followUpMessage.trim() }
+        );
+        setFollowUpMessage('');
+      } catch (error: unknown) {
+        // @ts-expect-error it is type ApiError
+        setFollowUpError(`Failed to send message: ${error.message}`);
+      } finally {
+        setIsSendingFollowUp(false);
+      }
+      return;
+    }
+
     try {
       setIsSendingFollowUp(true);
       setFollowUpError(null);
@@ -58,6 +109,8 @@ export function TaskFollowUpSection() {
         selectedAttempt.id,
         {
           prompt: followUpMessage.trim(),
+          model: null,
+          extra_args: null,
         }
       );
       setFollowUpMessage('');
@@ -82,7 +135,13 @@ export function TaskFollowUpSection() {
           )}
           <div className="flex gap-2 items-start">
             <FileSearchTextarea
-              placeholder="Continue working on this task... Type @ to search files."
+              placeholder={
+                runningAgentProcess
+                  ? 'Send a message to the running agent...'
+                  : supportsFollowUp
+                    ? 'Continue working on this task... Type @ to search files.'
+                    : 'This executor does not support follow-ups'
+              }
               value={followUpMessage}
               onChange={(value) => {
                 setFollowUpMessage(value);
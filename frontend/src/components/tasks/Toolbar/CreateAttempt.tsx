@@ -16,6 +16,8 @@ import {
 import { useTaskPlan } from '@/components/context/TaskPlanContext.ts';
 import { useConfig } from '@/components/config-provider.tsx';
 import BranchSelector from '@/components/tasks/BranchSelector.tsx';
+import { Checkbox } from '@/components/ui/checkbox.tsx';
+import { Input } from '@/components/ui/input.tsx';
 import { useKeyboardShortcuts } from '@/lib/keyboard-shortcuts.ts';
 import {
   Dialog,
@@ -60,7 +62,7 @@ function CreateAttempt({
   const { task, projectId } = useContext(TaskDetailsContext);
   const { isAttemptRunning } = useContext(TaskAttemptDataContext);
   const { isPlanningMode, canCreateTask } = useTaskPlan();
-  const { config } = useConfig();
+  const { config, capabilities } = useConfig();
 
   const [showCreateAttemptConfirmation, setShowCreateAttemptConfirmation] =
     useState(false);
@@ -70,6 +72,14 @@ function CreateAttempt({
   const [pendingBaseBranch, setPendingBaseBranch] = useState<
     string | undefined
   >(undefined);
+  const [planFirst, setPlanFirst] = useState(false);
+  const [model, setModel] = useState('');
+  const [extraArgs, setExtraArgs] = useState('');
+  const canPlan =
+    capabilities(createAttemptExecutor)?.plan_mode !== 'unsupported';
+  const canSelectModel =
+    capabilities(createAttemptExecutor)?.model_selection ?? false;
+  const canPassArgs = capabilities(createAttemptExecutor)?.extra_args ?? false;
 
   // Create attempt logic
   const actuallyCreateAttempt = useCallback(
@@ -78,13 +88,31 @@ function CreateAttempt({
         await attemptsApi.create(projectId!, task.id, {
           executor: executor || selectedExecutor,
           base_branch: baseBranch || selectedBranch,
+          plan_first: planFirst && canPlan,
+          pipeline_id: null,
+          model: canSelectModel && model.trim() ? model.trim() : null,
+          extra_args: canPassArgs
+            ? extraArgs.split(/\s+/).filter((arg) => arg.length > 0)
+            : null,
         });
         fetchTaskAttempts();
       } catch (error) {
         // Optionally handle error
       }
     },
-    [projectId, task.id, selectedExecutor, selectedBranch, fetchTaskAttempts]
+    [
+      projectId,
+      task.id,
+      selectedExecutor,
+      selectedBranch,
+      planFirst,
+      canPlan,
+      model,
+      extraArgs,
+      canSelectModel,
+      canPassArgs,
+      fetchTaskAttempts,
+    ]
   );
 
   // Handler for Enter key or Start button
@@ -262,6 +290,45 @@ function CreateAttempt({
             </Button>
           </div>
         </div>
+
+        <div className="flex items-center space-x-2">
+          <Checkbox
+            id="plan-first"
+            checked={planFirst && canPlan}
+            disabled={!canPlan}
+            onCheckedChange={(checked: boolean) =>
+              setPlanFirst(checked === true)
+            }
+          />
+          <label
+            htmlFor="plan-first"
+            className="text-xs font-medium text-muted-foreground"
+          >
+            Plan first: the agent proposes a plan to approve before changing
+            any files
+          </label>
+        </div>
+
+        {(canSelectModel || canPassArgs) && (
+          <div className="flex items-center gap-2">
+            {canSelectModel && (
+              <Input
+                value={model}
+                onChange={(e) => setModel(e.target.value)}
+                placeholder="Model (CLI default)"
+                className="h-8 text-xs"
+              />
+            )}
+            {canPassArgs && (
+              <Input
+                value={extraArgs}
+                onChange={(e) => setExtraArgs(e.target.value)}
+                placeholder="Extra CLI arguments"
+                className="h-8 text-xs"
+              />
+            )}
+          </div>
+        )}
       </div>
 
       {/* Confirmation Dialog */}
@@ -10,7 +10,6 @@ import {
   Settings,
   StopCircle,
 } from 'lucide-react';
-import { is_planning_executor_type } from '@/lib/utils';
 import {
   Tooltip,
   TooltipContent,
@@ -116,7 +115,7 @@ function CurrentAttempt({
 }: Props) {
   const { task, projectId, handleOpenInEditor, projectHasDevScript } =
     useContext(TaskDetailsContext);
-  const { config } = useConfig();
+  const { config, capabilities } = useConfig();
   const { setSelectedAttempt } = useContext(TaskSelectedAttemptContext);
   const navigate = useNavigate();
   const { isStopping, setIsStopping } = useContext(TaskAttemptStoppingContext);
@@ -164,11 +163,13 @@ function CurrentAttempt({
 
   // Check if plan approval is needed
   const isPlanTask = useMemo(() => {
-    return !!(
-      selectedAttempt.executor &&
-      is_planning_executor_type(selectedAttempt.executor)
+    return (
+      capabilities(selectedAttempt.executor)?.plan_mode === 'always' ||
+      attemptData.processes.some(
+        (process) => process.process_type === 'planningagent'
+      )
     );
-  }, [selectedAttempt.executor]);
+  }, [selectedAttempt.executor, attemptData.processes, capabilities]);
 
   const fetchDevServerDetails = useCallback(async () => {
     if (!runningDevServer || !task || !selectedAttempt) return;
@@ -409,7 +410,8 @@ function CurrentAttempt({
         `/api/projects/${projectId}/tasks/${task.id}/attempts/${selectedAttempt.id}/approve-plan`,
         {
           method: 'POST',
-          // No body needed - endpoint only handles approval now
+          // Implement with the planning executor
+          body: JSON.stringify({ executor: null }),
         }
       );
 
@@ -1,29 +1,53 @@
 // Import all necessary types from shared types
 import {
+  AttemptGroupComparison,
   BranchStatus,
   Config,
   ConfigConstants,
   CreateFollowUpAttempt,
+  CreatePipeline,
   CreateProject,
   CreateProjectFromGitHub,
   CreateTask,
   CreateTaskAndStart,
+  CreateTaskAttachment,
   CreateTaskAttempt,
+  CreateTaskAttemptGroup,
   CreateTaskTemplate,
+  DecidePermissionRequest,
   DeviceStartResponse,
   DirectoryEntry,
   type EditorType,
   ExecutionProcess,
+  ExecutionProcessInput,
   ExecutionProcessSummary,
+  ExecutorSession,
+  ForkTaskAttempt,
   GitBranch,
+  PermissionPolicy,
+  PermissionRequest,
+  Pipeline,
+  PickWinnerRequest,
   ProcessLogsResponse,
   Project,
+  ProjectCliVersion,
   ProjectWithBranch,
+  PromptPreview,
+  ReviewFinding,
+  ReviewFollowUpRequest,
+  SendInputRequest,
+  StartReviewRequest,
+  StopOutcome,
   Task,
+  TaskAttachment,
   TaskAttempt,
+  TaskAttemptGroup,
+  TaskAttemptStage,
   TaskAttemptState,
   TaskTemplate,
   TaskWithAttemptStatus,
+  UpdatePermissionPolicy,
+  UpdatePipeline,
   UpdateProject,
   UpdateTask,
   UpdateTaskTemplate,
@@ -192,6 +216,34 @@ export const projectsApi = {
     );
     return handleApiResponse<FileSearchResult[]>(response);
   },
+
+  getCliVersions: async (id: string): Promise<ProjectCliVersion[]> => {
+    const response = await makeRequest(`/api/projects/${id}/cli-versions`);
+    return handleApiResponse<ProjectCliVersion[]>(response);
+  },
+
+  setCliVersion: async (
+    id: string,
+    executor: string,
+    version: string
+  ): Promise<ProjectCliVersion> => {
+    const response = await makeRequest(
+      `/api/projects/${id}/cli-versions/${executor}`,
+      {
+        method: 'PUT',
+        body: JSON.stringify({ version }),
+      }
+    );
+    return handleApiResponse<ProjectCliVersion>(response);
+  },
+
+  deleteCliVersion: async (id: string, executor: string): Promise<void> => {
+    const response = await makeRequest(
+      `/api/projects/${id}/cli-versions/${executor}`,
+      { method: 'DELETE' }
+    );
+    return handleApiResponse<void>(response);
+  },
 };
 
 // Task Management APIs
@@ -265,6 +317,22 @@ export const tasksApi = {
     );
     return handleApiResponse<Task[]>(response);
   },
+
+  getPromptPreview: async (
+    projectId: string,
+    taskId: string,
+    attemptId: string,
+    followUp?: string
+  ): Promise<PromptPreview> => {
+    const query =
+      followUp !== undefined
+        ? `?follow_up=${encodeURIComponent(followUp)}`
+        : '';
+    const response = await makeRequest(
+      `/api/projects/${projectId}/tasks/${taskId}/attempts/${attemptId}/prompt-preview${query}`
+    );
+    return handleApiResponse<PromptPreview>(response);
+  },
 };
 
 // Task Attempts APIs
@@ -291,6 +359,33 @@ export const attemptsApi = {
     return handleApiResponse<TaskAttempt>(response);
   },
 
+  getSessions: async (
+    projectId: string,
+    taskId: string,
+    attemptId: string
+  ): Promise<ExecutorSession[]> => {
+    const response = await makeRequest(
+      `/api/projects/${projectId}/tasks/${taskId}/attempts/${attemptId}/sessions`
+    );
+    return handleApiResponse<ExecutorSession[]>(response);
+  },
+
+  fork: async (
+    projectId: string,
+    taskId: string,
+    attemptId: string,
+    data: ForkTaskAttempt
+  ): Promise<TaskAttempt> => {
+    const response = await makeRequest(
+      `/api/projects/${projectId}/tasks/${taskId}/attempts/${attemptId}/fork`,
+      {
+        method: 'POST',
+        body: JSON.stringify(data),
+      }
+    );
+    return handleApiResponse<TaskAttempt>(response);
+  },
+
   getState: async (
     projectId: string,
     taskId: string,
@@ -472,14 +567,31 @@ export const attemptsApi = {
     taskId: string,
     attemptId: string,
     processId: string
-  ): Promise<void> => {
+  ): Promise<StopOutcome | null> => {
     const response = await makeRequest(
       `/api/projects/${projectId}/tasks/${taskId}/attempts/${attemptId}/execution-processes/${processId}/stop`,
       {
         method: 'POST',
       }
     );
-    return handleApiResponse<void>(response);
+    return handleApiResponse<StopOutcome | null>(response);
+  },
+
+  sendProcessInput: async (
+    projectId: string,
+    taskId: string,
+    attemptId: string,
+    processId: string,
+    data: SendInputRequest
+  ): Promise<ExecutionProcessInput> => {
+    const response = await makeRequest(
+      `/api/projects/${projectId}/tasks/${taskId}/attempts/${attemptId}/execution-processes/${processId}/input`,
+      {
+        method: 'POST',
+        body: JSON.stringify(data),
+      }
+    );
+    return handleApiResponse<ExecutionProcessInput>(response);
   },
 
   getDetails: async (attemptId: string): Promise<TaskAttempt> => {
@@ -497,6 +609,278 @@ export const attemptsApi = {
     );
     return handleApiResponse(response);
   },
+
+  startReview: async (
+    projectId: string,
+    taskId: string,
+    attemptId: string,
+    data: StartReviewRequest
+  ): Promise<void> => {
+    const response = await makeRequest(
+      `/api/projects/${projectId}/tasks/${taskId}/attempts/${attemptId}/review`,
+      {
+        method: 'POST',
+        body: JSON.stringify(data),
+      }
+    );
+    return handleApiResponse<void>(response);
+  },
+
+  getReviewFindings: async (
+    projectId: string,
+    taskId: string,
+    attemptId: string
+  ): Promise<ReviewFinding[]> => {
+    const response = await makeRequest(
+      `/api/projects/${projectId}/tasks/${taskId}/attempts/${attemptId}/review-findings`
+    );
+    return handleApiResponse<ReviewFinding[]>(response);
+  },
+
+  followUpOnReviewFindings: async (
+    projectId: string,
+    taskId: string,
+    attemptId: string,
+    data: ReviewFollowUpRequest
+  ): Promise<FollowUpResponse> => {
+    const response = await makeRequest(
+      `/api/projects/${projectId}/tasks/${taskId}/attempts/${attemptId}/review-findings/follow-up`,
+      {
+        method: 'POST',
+        body: JSON.stringify(data),
+      }
+    );
+    return handleApiResponse<FollowUpResponse>(response);
+  },
+};
+
+// Pipeline APIs (ordered agent stages per project)
+export const pipelinesApi = {
+  getAll: async (projectId: string): Promise<Pipeline[]> => {
+    const response = await makeRequest(`/api/projects/${projectId}/pipelines`);
+    return handleApiResponse<Pipeline[]>(response);
+  },
+
+  create: async (
+    projectId: string,
+    data: CreatePipeline
+  ): Promise<Pipeline> => {
+    const response = await makeRequest(
+      `/api/projects/${projectId}/pipelines`,
+      {
+        method: 'POST',
+        body: JSON.stringify(data),
+      }
+    );
+    return handleApiResponse<Pipeline>(response);
+  },
+
+  update: async (
+    projectId: string,
+    pipelineId: string,
+    data: UpdatePipeline
+  ): Promise<Pipeline> => {
+    const response = await makeRequest(
+      `/api/projects/${projectId}/pipelines/${pipelineId}`,
+      {
+        method: 'PUT',
+        body: JSON.stringify(data),
+      }
+    );
+    return handleApiResponse<Pipeline>(response);
+  },
+
+  delete: async (projectId: string, pipelineId: string): Promise<void> => {
+    const response = await makeRequest(
+      `/api/projects/${projectId}/pipelines/${pipelineId}`,
+      {
+        method: 'DELETE',
+      }
+    );
+    return handleApiResponse<void>(response);
+  },
+
+  getAttemptStages: async (
+    projectId: string,
+    taskId: string,
+    attemptId: string
+  ): Promise<TaskAttemptStage[]> => {
+    const response = await makeRequest(
+      `/api/projects/${projectId}/tasks/${taskId}/attempts/${attemptId}/stages`
+    );
+    return handleApiResponse<TaskAttemptStage[]>(response);
+  },
+};
+
+// Tool permission APIs (rules and approvals for agent tool calls)
+export const permissionsApi = {
+  getPolicy: async (projectId: string): Promise<PermissionPolicy> => {
+    const response = await makeRequest(
+      `/api/projects/${projectId}/permissions`
+    );
+    return handleApiResponse<PermissionPolicy>(response);
+  },
+
+  updatePolicy: async (
+    projectId: string,
+    data: UpdatePermissionPolicy
+  ): Promise<PermissionPolicy> => {
+    const response = await makeRequest(
+      `/api/projects/${projectId}/permissions`,
+      {
+        method: 'PUT',
+        body: JSON.stringify(data),
+      }
+    );
+    return handleApiResponse<PermissionPolicy>(response);
+  },
+
+  getRequests: async (
+    projectId: string,
+    options: { taskAttemptId?: string; pending?: boolean } = {}
+  ): Promise<PermissionRequest[]> => {
+    const params = new URLSearchParams();
+    if (options.taskAttemptId) {
+      params.set('task_attempt_id', options.taskAttemptId);
+    }
+    if (options.pending) {
+      params.set('pending', 'true');
+    }
+    const response = await makeRequest(
+      `/api/projects/${projectId}/permission-requests?${params.toString()}`
+    );
+    return handleApiResponse<PermissionRequest[]>(response);
+  },
+
+  decide: async (
+    projectId: string,
+    requestId: string,
+    data: DecidePermissionRequest
+  ): Promise<PermissionRequest> => {
+    const response = await makeRequest(
+      `/api/projects/${projectId}/permission-requests/${requestId}/decision`,
+      {
+        method: 'POST',
+        body: JSON.stringify(data),
+      }
+    );
+    return handleApiResponse<PermissionRequest>(response);
+  },
+};
+
+// Attempt Group APIs (several executors racing on one task)
+export const attemptGroupsApi = {
+  getAll: async (
+    projectId: string,
+    taskId: string
+  ): Promise<TaskAttemptGroup[]> => {
+    const response = await makeRequest(
+      `/api/projects/${projectId}/tasks/${taskId}/attempt-groups`
+    );
+    return handleApiResponse<TaskAttemptGroup[]>(response);
+  },
+
+  create: async (
+    projectId: string,
+    taskId: string,
+    data: CreateTaskAttemptGroup
+  ): Promise<TaskAttemptGroup> => {
+    const response = await makeRequest(
+      `/api/projects/${projectId}/tasks/${taskId}/attempt-groups`,
+      {
+        method: 'POST',
+        body: JSON.stringify(data),
+      }
+    );
+    return handleApiResponse<TaskAttemptGroup>(response);
+  },
+
+  getComparison: async (
+    projectId: string,
+    taskId: string,
+    groupId: string
+  ): Promise<AttemptGroupComparison> => {
+    const response = await makeRequest(
+      `/api/projects/${projectId}/tasks/${taskId}/attempt-groups/${groupId}/comparison`
+    );
+    return handleApiResponse<AttemptGroupComparison>(response);
+  },
+
+  pickWinner: async (
+    projectId: string,
+    taskId: string,
+    groupId: string,
+    data: PickWinnerRequest
+  ): Promise<TaskAttemptGroup> => {
+    const response = await makeRequest(
+      `/api/projects/${projectId}/tasks/${taskId}/attempt-groups/${groupId}/winner`,
+      {
+        method: 'POST',
+        body: JSON.stringify(data),
+      }
+    );
+    return handleApiResponse<TaskAttemptGroup>(response);
+  },
+};
+
+// Task Attachment APIs
+export const attachmentsApi = {
+  getAll: async (
+    projectId: string,
+    taskId: string
+  ): Promise<TaskAttachment[]> => {
+    const response = await makeRequest(
+      `/api/projects/${projectId}/tasks/${taskId}/attachments`
+    );
+    return handleApiResponse<TaskAttachment[]>(response);
+  },
+
+  create: async (
+    projectId: string,
+    taskId: string,
+    data: CreateTaskAttachment
+  ): Promise<TaskAttachment> => {
+    const response = await makeRequest(
+      `/api/projects/${projectId}/tasks/${taskId}/attachments`,
+      {
+        method: 'POST',
+        body: JSON.stringify(data),
+      }
+    );
+    return handleApiResponse<TaskAttachment>(response);
+  },
+
+  upload: async (
+    projectId: string,
+    taskId: string,
+    files: File[]
+  ): Promise<TaskAttachment[]> => {
+    const body = new FormData();
+    files.forEach((file) => body.append('file', file, file.name));
+    // Not makeRequest: the browser must set the multipart boundary header
+    const response = await fetch(
+      `/api/projects/${projectId}/tasks/${taskId}/attachments/upload`,
+      { method: 'POST', body }
+    );
+    return handleApiResponse<TaskAttachment[]>(response);
+  },
+
+  contentUrl: (projectId: string, taskId: string, attachmentId: string) =>
+    `/api/projects/${projectId}/tasks/${taskId}/attachments/${attachmentId}`,
+
+  delete: async (
+    projectId: string,
+    taskId: string,
+    attachmentId: string
+  ): Promise<void> => {
+    const response = await makeRequest(
+      `/api/projects/${projectId}/tasks/${taskId}/attachments/${attachmentId}`,
+      {
+        method: 'DELETE',
+      }
+    );
+    return handleApiResponse<void>(response);
+  },
 };
 
 // Execution Process APIs
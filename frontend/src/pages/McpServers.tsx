@@ -1,4 +1,4 @@
-import { useState, useEffect } from 'react';
+import { useState, useEffect, useMemo } from 'react';
 import {
   Card,
   CardContent,
@@ -18,16 +18,31 @@ import { Label } from '@/components/ui/label';
 import { Alert, AlertDescription } from '@/components/ui/alert';
 import { Textarea } from '@/components/ui/textarea';
 import { Loader2 } from 'lucide-react';
-import {
-  EXECUTOR_TYPES,
-  EXECUTOR_LABELS,
-  MCP_SUPPORTED_EXECUTORS,
-} from 'shared/types';
+import { EXECUTOR_TYPES, EXECUTOR_LABELS } from 'shared/types';
 import { useConfig } from '@/components/config-provider';
 import { mcpServersApi } from '../lib/api';
 
+const DEFAULT_SERVERS_PATH = ['mcpServers'];
+
+// Nest the servers object under the keys the executor's config file uses
+function wrapServers(executorType: string, path: string[], servers: object) {
+  const config = path.reduceRight<Record<string, unknown>>(
+    (inner, key) => ({ [key]: inner }),
+    servers as Record<string, unknown>
+  );
+  // opencode validates its config against a published schema
+  return executorType === 'sst-opencode'
+    ? { ...config, $schema: 'https://opencode.ai/config.json' }
+    : config;
+}
+
+function unwrapServers(config: any, path: string[]) {
+  const servers = path.reduce((current, key) => current?.[key], config);
+  return servers && typeof servers === 'object' ? servers : null;
+}
+
 export function McpServers() {
-  const { config } = useConfig();
+  const { config, capabilities } = useConfig();
   const [mcpServers, setMcpServers] = useState('{}');
   const [mcpError, setMcpError] = useState<string | null>(null);
   const [mcpLoading, setMcpLoading] = useState(true);
@@ -36,17 +51,28 @@ export function McpServers() {
   const [mcpConfigPath, setMcpConfigPath] = useState<string>('');
   const [success, setSuccess] = useState(false);
 
-  // Initialize selected MCP executor when config loads
+  // Executors whose MCP servers can be edited here. TOML config files are
+  // converted to and from JSON by the server.
+  const mcpExecutors = useMemo(
+    () => EXECUTOR_TYPES.filter((type) => !!capabilities(type)?.mcp),
+    [capabilities]
+  );
+  const serversPath =
+    capabilities(selectedMcpExecutor)?.mcp?.servers_path ??
+    DEFAULT_SERVERS_PATH;
+  const serversKey = serversPath.join('.');
+
+  // Initialize selected MCP executor when config and capabilities load
   useEffect(() => {
-    if (config?.executor?.type && !selectedMcpExecutor) {
+    if (config?.executor?.type && !selectedMcpExecutor && mcpExecutors[0]) {
       // If current executor supports MCP, use it; otherwise use first available MCP executor
-      if (MCP_SUPPORTED_EXECUTORS.includes(config.executor.type)) {
+      if (mcpExecutors.includes(config.executor.type)) {
         setSelectedMcpExecutor(config.executor.type);
       } else {
-        setSelectedMcpExecutor(MCP_SUPPORTED_EXECUTORS[0] || 'claude');
+        setSelectedMcpExecutor(mcpExecutors[0]);
       }
     }
-  }, [config?.executor?.type, selectedMcpExecutor]);
+  }, [config?.executor?.type, selectedMcpExecutor, mcpExecutors]);
 
   // Load existing MCP configuration when selected executor changes
   useEffect(() => {
@@ -55,14 +81,12 @@ export function McpServers() {
       setMcpLoading(true);
       setMcpError(null);
 
-      // Set default empty config based on executor type
-      const defaultConfig =
-        executorType === 'amp'
-          ? '{\n  "amp.mcpServers": {\n  }\n}'
-          : executorType === 'sst-opencode'
-            ? '{\n  "mcp": {\n  }, "$schema": "https://opencode.ai/config.json"\n}'
-            : '{\n  "mcpServers": {\n  }\n}';
-      setMcpServers(defaultConfig);
+      // Set default empty config based on where the executor keeps servers
+      const path =
+        capabilities(executorType)?.mcp?.servers_path ?? DEFAULT_SERVERS_PATH;
+      setMcpServers(
+        JSON.stringify(wrapServers(executorType, path, {}), null, 2)
+      );
       setMcpConfigPath('');
 
       try {
@@ -74,19 +98,7 @@ export function McpServers() {
         const configPath = data.config_path || '';
 
         // Create the full configuration structure based on executor type
-        let fullConfig;
-        if (executorType === 'amp') {
-          // For AMP, use the amp.mcpServers structure
-          fullConfig = { 'amp.mcpServers': servers };
-        } else if (executorType === 'sst-opencode') {
-          fullConfig = {
-            mcp: servers,
-            $schema: 'https://opencode.ai/config.json',
-          };
-        } else {
-          // For other executors, use the standard mcpServers structure
-          fullConfig = { mcpServers: servers };
-        }
+        const fullConfig = wrapServers(executorType, path, servers);
 
         const configJson = JSON.stringify(fullConfig, null, 2);
         setMcpServers(configJson);
@@ -106,7 +118,7 @@ export function McpServers() {
     if (selectedMcpExecutor) {
       loadMcpServersForExecutor(selectedMcpExecutor);
     }
-  }, [selectedMcpExecutor]);
+  }, [selectedMcpExecutor, capabilities]);
 
   const handleMcpServersChange = (value: string) => {
     setMcpServers(value);
@@ -117,23 +129,8 @@ export function McpServers() {
       try {
         const config = JSON.parse(value);
         // Validate that the config has the expected structure based on executor type
-        if (selectedMcpExecutor === 'amp') {
-          if (
-            !config['amp.mcpServers'] ||
-            typeof config['amp.mcpServers'] !== 'object'
-          ) {
-            setMcpError(
-              'AMP configuration must contain an "amp.mcpServers" object'
-            );
-          }
-        } else if (selectedMcpExecutor === 'sst-opencode') {
-          if (!config.mcp || typeof config.mcp !== 'object') {
-            setMcpError('Configuration must contain an "mcp" object');
-          }
-        } else {
-          if (!config.mcpServers || typeof config.mcpServers !== 'object') {
-            setMcpError('Configuration must contain an "mcpServers" object');
-          }
+        if (!unwrapServers(config, serversPath)) {
+          setMcpError(`Configuration must contain a "${serversKey}" object`);
         }
       } catch (err) {
         setMcpError('Invalid JSON format');
@@ -162,32 +159,13 @@ export function McpServers() {
             };
 
       // Add vibe_kanban to the existing configuration
-      let updatedConfig;
-      if (selectedMcpExecutor === 'amp') {
-        updatedConfig = {
-          ...existingConfig,
-          'amp.mcpServers': {
-            ...(existingConfig['amp.mcpServers'] || {}),
-            vibe_kanban: vibeKanbanConfig,
-          },
-        };
-      } else if (selectedMcpExecutor === 'sst-opencode') {
-        updatedConfig = {
-          ...existingConfig,
-          mcp: {
-            ...(existingConfig.mcp || {}),
-            vibe_kanban: vibeKanbanConfig,
-          },
-        };
-      } else {
-        updatedConfig = {
-          ...existingConfig,
-          mcpServers: {
-            ...(existingConfig.mcpServers || {}),
-            vibe_kanban: vibeKanbanConfig,
-          },
-        };
-      }
+      const updatedConfig = {
+        ...existingConfig,
+        ...wrapServers(selectedMcpExecutor, serversPath, {
+          ...(unwrapServers(existingConfig, serversPath) || {}),
+          vibe_kanban: vibeKanbanConfig,
+        }),
+      };
 
       // Update the textarea with the new configuration
       const configJson = JSON.stringify(updatedConfig, null, 2);
@@ -211,36 +189,12 @@ export function McpServers() {
         try {
           const fullConfig = JSON.parse(mcpServers);
 
-          // Validate that the config has the expected structure based on executor type
-          let mcpServersConfig;
-          if (selectedMcpExecutor === 'amp') {
-            if (
-              !fullConfig['amp.mcpServers'] ||
-              typeof fullConfig['amp.mcpServers'] !== 'object'
-            ) {
-              throw new Error(
-                'AMP configuration must contain an "amp.mcpServers" object'
-              );
-            }
-            // Extract just the inner servers object for the API - backend will handle nesting
-            mcpServersConfig = fullConfig['amp.mcpServers'];
-          } else if (selectedMcpExecutor === 'sst-opencode') {
-            if (!fullConfig.mcp || typeof fullConfig.mcp !== 'object') {
-              throw new Error('Configuration must contain an "mcp" object');
-            }
-            // Extract just the mcp part for the API
-            mcpServersConfig = fullConfig.mcp;
-          } else {
-            if (
-              !fullConfig.mcpServers ||
-              typeof fullConfig.mcpServers !== 'object'
-            ) {
-              throw new Error(
-                'Configuration must contain an "mcpServers" object'
-              );
-            }
-            // Extract just the mcpServers part for the API
-            mcpServersConfig = fullConfig.mcpServers;
+          // Extract just the servers object; the backend handles nesting
+          const mcpServersConfig = unwrapServers(fullConfig, serversPath);
+          if (!mcpServersConfig) {
+            throw new Error(
+              `Configuration must contain a "${serversKey}" object`
+            );
           }
 
           await mcpServersApi.save(selectedMcpExecutor, mcpServersConfig);
@@ -323,9 +277,7 @@ export function McpServers() {
                   <SelectValue placeholder="Select executor" />
                 </SelectTrigger>
                 <SelectContent>
-                  {EXECUTOR_TYPES.filter((type) =>
-                    MCP_SUPPORTED_EXECUTORS.includes(type)
-                  ).map((type) => (
+                  {mcpExecutors.map((type) => (
                     <SelectItem key={type} value={type}>
                       {EXECUTOR_LABELS[type]}
                     </SelectItem>
@@ -348,7 +300,7 @@ export function McpServers() {
                       <p>{mcpError}</p>
                       <p className="mt-1">
                         To use MCP servers, please select a different executor
-                        (Claude, Amp, or Gemini) above.
+                        above.
                       </p>
                     </div>
                   </div>
@@ -4,7 +4,7 @@
 
 export type ApiResponse<T> = { success: boolean, data: T | null, message: string | null, };
 
-export type Config = { theme: ThemeMode, executor: ExecutorConfig, disclaimer_acknowledged: boolean, onboarding_acknowledged: boolean, github_login_acknowledged: boolean, telemetry_acknowledged: boolean, sound_alerts: boolean, sound_file: SoundFile, push_notifications: boolean, editor: EditorConfig, github: GitHubConfig, analytics_enabled: boolean | null, environment: EnvironmentInfo, workspace_dir: string | null, };
+export type Config = { theme: ThemeMode, executor: ExecutorConfig, disclaimer_acknowledged: boolean, onboarding_acknowledged: boolean, github_login_acknowledged: boolean, telemetry_acknowledged: boolean, sound_alerts: boolean, sound_file: SoundFile, push_notifications: boolean, editor: EditorConfig, github: GitHubConfig, analytics_enabled: boolean | null, environment: EnvironmentInfo, workspace_dir: string | null, review_executor: ExecutorConfig | null, executor_limits: { [key: string]: ExecutionLimits }, retry_policies: { [key: string]: RetryPolicy }, executor_resource_limits: { [key: string]: ResourceLimits }, stop_grace_period_seconds: bigint, pipeline_check_timeout_seconds: bigint, };
 
 export type EnvironmentInfo = { os_type: string, os_version: string, architecture: string, bitness: string, };
 
@@ -16,6 +16,12 @@ export type EditorConfig = { editor_type: EditorType, custom_command: string | n
 
 export type GitHubConfig = { pat: string | null, token: string | null, username: string | null, primary_email: string | null, default_pr_base: string | null, };
 
+export type ExecutionLimits = { max_runtime_seconds: bigint | null, idle_timeout_seconds: bigint | null, };
+
+export type ResourceLimits = { memory_limit_mb: bigint | null, cpu_limit_percent: bigint | null, max_processes: bigint | null, };
+
+export type RetryPolicy = { transient_patterns: Array<string>, max_attempts: number, min_delay_seconds: number, max_delay_seconds: number, };
+
 export type EditorType = "vscode" | "cursor" | "windsurf" | "intellij" | "zed" | "custom";
 
 export type EditorConstants = { editor_types: Array<EditorType>, editor_labels: Array<string>, };
@@ -24,21 +30,31 @@ export type SoundFile = "abstract-sound1" | "abstract-sound2" | "abstract-sound3
 
 export type SoundConstants = { sound_files: Array<SoundFile>, sound_labels: Array<string>, };
 
-export type ConfigConstants = { editor: EditorConstants, sound: SoundConstants, mode: Environment, };
+export type ConfigConstants = { executor: ExecutorConstants, editor: EditorConstants, sound: SoundConstants, mode: Environment, };
+
+export type ExecutorConfig = { "type": "echo" } | { "type": "claude" } | { "type": "claude-plan" } | { "type": "amp" } | { "type": "gemini" } | { "type": "setup-script", script: string, } | { "type": "claude-code-router" } | { "type": "charm-opencode" } | { "type": "sst-opencode" } | { "type": "aider" } | { "type": "codex" } | { "type": "custom", name: string, } | { "type": "acp", command: string, } | { "type": "mock", scenario: string, };
+
+export type ExecutorConstants = { executor_types: Array<ExecutorConfig>, executor_labels: Array<string>, executor_capabilities: Array<ExecutorCapabilities>, };
+
+export type McpConfigFormat = "json" | "toml";
+
+export type McpCapability = { config_format: McpConfigFormat, servers_path: Array<string>, };
 
-export type ExecutorConfig = { "type": "echo" } | { "type": "claude" } | { "type": "claude-plan" } | { "type": "amp" } | { "type": "gemini" } | { "type": "setup-script", script: string, } | { "type": "claude-code-router" } | { "type": "charm-opencode" } | { "type": "sst-opencode" } | { "type": "aider" } | { "type": "codex" };
+export type PlanMode = "unsupported" | "prompted" | "native" | "always";
 
-export type ExecutorConstants = { executor_types: Array<ExecutorConfig>, executor_labels: Array<string>, };
+export type ExecutorCapabilities = { executor: string, follow_up: boolean, live_input: boolean, mcp: McpCapability | null, plan_mode: PlanMode, streaming: boolean, image_input: boolean, model_selection: boolean, extra_args: boolean, version_pinning: boolean, session_forking: boolean, permission_prompts: boolean, };
 
-export type CreateProject = { name: string, git_repo_path: string, use_existing_repo: boolean, setup_script: string | null, dev_script: string | null, cleanup_script: string | null, };
+export type SandboxMode = "disabled" | "enabled" | "offline";
 
-export type CreateProjectFromGitHub = { repository_id: bigint, name: string, clone_url: string, setup_script: string | null, dev_script: string | null, cleanup_script: string | null, };
+export type CreateProject = { name: string, git_repo_path: string, use_existing_repo: boolean, setup_script: string | null, dev_script: string | null, cleanup_script: string | null, max_runtime_seconds: bigint | null, idle_timeout_seconds: bigint | null, prompt_template: string | null, sandbox_mode: SandboxMode | null, memory_limit_mb: bigint | null, cpu_limit_percent: bigint | null, max_processes: bigint | null, };
 
-export type Project = { id: string, name: string, git_repo_path: string, setup_script: string | null, dev_script: string | null, cleanup_script: string | null, created_at: Date, updated_at: Date, };
+export type CreateProjectFromGitHub = { repository_id: bigint, name: string, clone_url: string, setup_script: string | null, dev_script: string | null, cleanup_script: string | null, max_runtime_seconds: bigint | null, idle_timeout_seconds: bigint | null, prompt_template: string | null, sandbox_mode: SandboxMode | null, memory_limit_mb: bigint | null, cpu_limit_percent: bigint | null, max_processes: bigint | null, };
 
-export type ProjectWithBranch = { id: string, name: string, git_repo_path: string, setup_script: string | null, dev_script: string | null, cleanup_script: string | null, current_branch: string | null, created_at: Date, updated_at: Date, };
+export type Project = { id: string, name: string, git_repo_path: string, setup_script: string | null, dev_script: string | null, cleanup_script: string | null, max_runtime_seconds: bigint | null, idle_timeout_seconds: bigint | null, prompt_template: string | null, sandbox_mode: SandboxMode, memory_limit_mb: bigint | null, cpu_limit_percent: bigint | null, max_processes: bigint | null, created_at: Date, updated_at: Date, };
 
-export type UpdateProject = { name: string | null, git_repo_path: string | null, setup_script: string | null, dev_script: string | null, cleanup_script: string | null, };
+export type ProjectWithBranch = { id: string, name: string, git_repo_path: string, setup_script: string | null, dev_script: string | null, cleanup_script: string | null, max_runtime_seconds: bigint | null, idle_timeout_seconds: bigint | null, prompt_template: string | null, sandbox_mode: SandboxMode, memory_limit_mb: bigint | null, cpu_limit_percent: bigint | null, max_processes: bigint | null, current_branch: string | null, created_at: Date, updated_at: Date, };
+
+export type UpdateProject = { name: string | null, git_repo_path: string | null, setup_script: string | null, dev_script: string | null, cleanup_script: string | null, max_runtime_seconds: bigint | null, idle_timeout_seconds: bigint | null, prompt_template: string | null, sandbox_mode: SandboxMode | null, memory_limit_mb: bigint | null, cpu_limit_percent: bigint | null, max_processes: bigint | null, };
 
 export type SearchResult = { path: string, is_file: boolean, match_type: SearchMatchType, };
 
@@ -68,13 +84,15 @@ export type UpdateTaskTemplate = { title: string | null, description: string | n
 
 export type TaskAttemptStatus = "setuprunning" | "setupcomplete" | "setupfailed" | "executorrunning" | "executorcomplete" | "executorfailed";
 
-export type TaskAttempt = { id: string, task_id: string, worktree_path: string, branch: string, base_branch: string, merge_commit: string | null, executor: string | null, pr_url: string | null, pr_number: bigint | null, pr_status: string | null, pr_merged_at: string | null, worktree_deleted: boolean, setup_completed_at: string | null, created_at: string, updated_at: string, };
+export type TaskAttempt = { id: string, task_id: string, worktree_path: string, branch: string, base_branch: string, merge_commit: string | null, executor: string | null, pr_url: string | null, pr_number: bigint | null, pr_status: string | null, pr_merged_at: string | null, worktree_deleted: boolean, setup_completed_at: string | null, forked_from_session_id: string | null, created_at: string, updated_at: string, };
 
-export type CreateTaskAttempt = { executor: string | null, base_branch: string | null, };
+export type CreateTaskAttempt = { executor: string | null, base_branch: string | null, plan_first: boolean | null, pipeline_id: string | null, model: string | null, extra_args: Array<string> | null, };
 
 export type UpdateTaskAttempt = Record<string, never>;
 
-export type CreateFollowUpAttempt = { prompt: string, };
+export type CreateFollowUpAttempt = { prompt: string, model: string | null, extra_args: Array<string> | null, };
+
+export type ForkTaskAttempt = { executor_session_id: string, prompt: string, };
 
 export type DirectoryEntry = { name: string, path: string, is_directory: boolean, is_git_repo: boolean, };
 
@@ -84,7 +102,9 @@ export type DeviceStartResponse = { device_code: string, user_code: string, veri
 
 export type RepositoryInfo = { id: bigint, name: string, full_name: string, owner: string, description: string | null, clone_url: string, ssh_url: string, default_branch: string, private: boolean, };
 
-export type ProcessLogsResponse = { id: string, process_type: ExecutionProcessType, command: string, executor_type: string | null, status: ExecutionProcessStatus, normalized_conversation: NormalizedConversation, };
+export type ProcessLogsResponse = { id: string, process_type: ExecutionProcessType, command: string, executor_type: string | null, status: ExecutionProcessStatus, retry_of_process_id: string | null, normalized_conversation: NormalizedConversation, };
+
+export type ApprovePlanRequest = { executor: string | null, };
 
 export type DiffChunkType = "Equal" | "Insert" | "Delete";
 
@@ -100,24 +120,100 @@ export type ExecutionState = "NotStarted" | "SetupRunning" | "SetupComplete" | "
 
 export type TaskAttemptState = { execution_state: ExecutionState, has_changes: boolean, has_setup_script: boolean, setup_process_id: string | null, coding_agent_process_id: string | null, };
 
-export type ExecutionProcess = { id: string, task_attempt_id: string, process_type: ExecutionProcessType, executor_type: string | null, status: ExecutionProcessStatus, command: string, args: string | null, working_directory: string, stdout: string | null, stderr: string | null, exit_code: bigint | null, started_at: string, completed_at: string | null, created_at: string, updated_at: string, };
+export type ExecutionProcess = { id: string, task_attempt_id: string, process_type: ExecutionProcessType, executor_type: string | null, status: ExecutionProcessStatus, command: string, args: string | null, working_directory: string, stdout: string | null, stderr: string | null, exit_code: bigint | null, timeout_reason: string | null, retry_of_process_id: string | null, persona_id: string | null, model: string | null, cli_version: string | null, peak_memory_bytes: bigint | null, cpu_time_ms: bigint | null, started_at: string, completed_at: string | null, created_at: string, updated_at: string, };
 
-export type ExecutionProcessSummary = { id: string, task_attempt_id: string, process_type: ExecutionProcessType, executor_type: string | null, status: ExecutionProcessStatus, command: string, args: string | null, working_directory: string, exit_code: bigint | null, started_at: string, completed_at: string | null, created_at: string, updated_at: string, };
+export type ExecutionProcessSummary = { id: string, task_attempt_id: string, process_type: ExecutionProcessType, executor_type: string | null, status: ExecutionProcessStatus, command: string, args: string | null, working_directory: string, exit_code: bigint | null, timeout_reason: string | null, retry_of_process_id: string | null, persona_id: string | null, model: string | null, cli_version: string | null, peak_memory_bytes: bigint | null, cpu_time_ms: bigint | null, started_at: string, completed_at: string | null, created_at: string, updated_at: string, };
 
-export type ExecutionProcessStatus = "running" | "completed" | "failed" | "killed";
+export type ExecutionProcessStatus = "running" | "completed" | "failed" | "killed" | "timedout";
 
-export type ExecutionProcessType = "setupscript" | "cleanupscript" | "codingagent" | "devserver";
+export type ExecutionProcessType = "setupscript" | "cleanupscript" | "codingagent" | "planningagent" | "reviewagent" | "devserver";
 
-export type CreateExecutionProcess = { task_attempt_id: string, process_type: ExecutionProcessType, executor_type: string | null, command: string, args: string | null, working_directory: string, };
+export type CreateExecutionProcess = { task_attempt_id: string, process_type: ExecutionProcessType, executor_type: string | null, command: string, args: string | null, working_directory: string, persona_id: string | null, model: string | null, cli_version: string | null, retry_of_process_id: string | null, };
 
 export type UpdateExecutionProcess = { status: ExecutionProcessStatus | null, exit_code: bigint | null, completed_at: string | null, };
 
-export type ExecutorSession = { id: string, task_attempt_id: string, execution_process_id: string, session_id: string | null, prompt: string | null, summary: string | null, created_at: string, updated_at: string, };
+export type StopSignal = "interrupt" | "terminate" | "kill";
+
+export type StopOutcome = { signal: StopSignal | null, };
+
+export type ExecutionProcessInput = { id: string, execution_process_id: string, message: string, entry_index: bigint | null, created_at: string, };
+
+export type SendInputRequest = { message: string, };
+
+export type ExecutorSession = { id: string, task_attempt_id: string, execution_process_id: string, session_id: string | null, prompt: string | null, summary: string | null, after_head_commit: string | null, created_at: string, updated_at: string, };
 
 export type CreateExecutorSession = { task_attempt_id: string, execution_process_id: string, prompt: string | null, };
 
 export type UpdateExecutorSession = { session_id: string | null, prompt: string | null, summary: string | null, };
 
+export type TokenUsage = { input_tokens: bigint, output_tokens: bigint, cache_read_tokens: bigint, cache_creation_tokens: bigint, cost_usd: number | null, };
+
+export type ExecutionProcessUsage = { id: string, execution_process_id: string, input_tokens: bigint, output_tokens: bigint, cache_read_tokens: bigint, cache_creation_tokens: bigint, cost_usd: number | null, created_at: string, updated_at: string, };
+
+export type UsageSummary = { group: string | null, process_count: bigint, input_tokens: bigint, output_tokens: bigint, cache_read_tokens: bigint, cache_creation_tokens: bigint, cost_usd: number | null, };
+
+export type StageCondition = "always" | "previous_succeeded" | "check_fails";
+
+export type StageStatus = "pending" | "running" | "completed" | "failed" | "skipped";
+
+export type PipelineStage = { name: string, executor: string, prompt_template: string, condition: StageCondition, check_command: string | null, };
+
+export type Pipeline = { id: string, project_id: string, name: string, stages: Array<PipelineStage>, created_at: string, updated_at: string, };
+
+export type CreatePipeline = { name: string, stages: Array<PipelineStage>, };
+
+export type UpdatePipeline = { name: string | null, stages: Array<PipelineStage> | null, };
+
+export type TaskAttemptStage = { id: string, task_attempt_id: string, position: bigint, name: string, executor: string, prompt_template: string, condition: StageCondition, check_command: string | null, status: StageStatus, execution_process_id: string | null, created_at: string, updated_at: string, };
+
+export type ReviewSeverity = "info" | "warning" | "error";
+
+export type ReviewFinding = { id: string, task_attempt_id: string, execution_process_id: string, file_path: string, start_line: bigint | null, end_line: bigint | null, severity: ReviewSeverity, message: string, created_at: string, };
+
+export type StartReviewRequest = { executor: string | null, };
+
+export type ReviewFollowUpRequest = { finding_ids: Array<string>, };
+
+export type PermissionDecision = "allow" | "deny";
+
+export type PermissionDecisionSource = "rule" | "user" | "timeout" | "no_rules";
+
+export type PermissionRule = { decision: PermissionDecision, tool_name: string | null, path_glob: string | null, command_pattern: string | null, };
+
+export type PermissionPolicy = { project_id: string, timeout_seconds: bigint, default_decision: PermissionDecision, rules: Array<PermissionRule>, };
+
+export type UpdatePermissionPolicy = { timeout_seconds: bigint, default_decision: PermissionDecision, rules: Array<PermissionRule>, };
+
+export type PermissionRequest = { id: string, project_id: string, task_attempt_id: string | null, execution_process_id: string | null, tool_name: string, input: string, default_decision: PermissionDecision, decision: PermissionDecision | null, decided_by: PermissionDecisionSource | null, rule_position: bigint | null, message: string | null, expires_at: string, decided_at: string | null, created_at: string, };
+
+export type DecidePermissionRequest = { decision: PermissionDecision, message: string | null, };
+
+export type PromptPreview = { prompt: string, uses_default_template: boolean, };
+
+export type AttachmentKind = "file" | "image" | "log" | "repo_path";
+
+export type TaskAttachment = { id: string, task_id: string, kind: AttachmentKind, name: string, path: string, content_type: string | null, size_bytes: bigint | null, created_at: string, };
+
+export type CreateTaskAttachment = { "type": "log", name: string | null, content: string, } | { "type": "repo_path", path: string, };
+
+export type ProjectCliVersion = { project_id: string, executor: string, version: string, created_at: string, updated_at: string, };
+
+export type SetProjectCliVersion = { version: string, };
+
+export type TaskAttemptGroup = { id: string, task_id: string, winner_attempt_id: string | null, created_at: string, updated_at: string, };
+
+export type CreateTaskAttemptGroup = { executors: Array<string>, base_branch: string | null, };
+
+export type DiffStats = { files_changed: number, lines_added: number, lines_removed: number, };
+
+export type AttemptComparison = { attempt_id: string, executor: string | null, branch: string, status: ExecutionProcessStatus | null, exit_code: bigint | null, duration_seconds: number | null, diff: DiffStats | null, usage: UsageSummary, is_winner: boolean, };
+
+export type AttemptGroupComparison = { group: TaskAttemptGroup, attempts: Array<AttemptComparison>, };
+
+export type WinnerAction = { "type": "merge" } | { "type": "pull_request", title: string, body: string | null, base_branch: string | null, };
+
+export type PickWinnerRequest = { attempt_id: string, action: WinnerAction, };
+
 export type NormalizedConversation = { entries: Array<NormalizedEntry>, session_id: string | null, executor_type: string, prompt: string | null, summary: string | null, };
 
 export type NormalizedEntry = { timestamp: string | null, entry_type: NormalizedEntryType, content: string, };
@@ -217,15 +313,6 @@ export const EDITOR_LABELS: Record<string, string> = {
     "custom": "Custom"
 };
 
-export const MCP_SUPPORTED_EXECUTORS: string[] = [
-    "claude",
-    "amp",
-    "gemini",
-    "sst-opencode",
-    "charm-opencode",
-    "claude-code-router"
-];
-
 export const SOUND_FILES: SoundFile[] = [
     "abstract-sound1",
     "abstract-sound2",